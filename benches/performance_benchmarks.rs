@@ -1,6 +1,8 @@
 // Phase 5: Performance Benchmarks
 // Comprehensive performance testing for lexer, parser, and runtime
 
+#![allow(clippy::result_large_err)]
+
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use dist_agent_lang::stdlib::ai;
 use dist_agent_lang::stdlib::chain;
@@ -245,6 +247,56 @@ fn bench_runtime_control_flow(c: &mut Criterion) {
     }
 }
 
+fn bench_runtime_tight_loop(c: &mut Criterion) {
+    // A user function call per iteration: call overhead is shared by both engines.
+    let calls = r#"
+    fn step(acc, i) {
+        return acc + i % 7;
+    }
+    let total = 0;
+    let i = 0;
+    while (i < 2000) {
+        total = step(total, i);
+        i = i + 1;
+    }
+    "#;
+    // Locals and arithmetic only, inside a function body.
+    let locals = r#"
+    fn run() {
+        let total = 0;
+        let i = 0;
+        while (i < 2000) {
+            total = total + i % 7;
+            i = i + 1;
+        }
+        return total;
+    }
+    run()
+    "#;
+    compare_engines(c, "runtime_tight_loop", calls);
+    compare_engines(c, "runtime_local_loop", locals);
+}
+
+/// Run `code` under the tree-walker and the bytecode VM.
+fn compare_engines(c: &mut Criterion, group_name: &str, code: &str) {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let mut group = c.benchmark_group(group_name);
+    for (label, bytecode_vm) in [("tree_walker", false), ("bytecode_vm", true)] {
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                let mut runtime = Runtime::new();
+                runtime.set_bytecode_vm(bytecode_vm);
+                black_box(runtime.execute_program(black_box(program.clone()), None))
+            })
+        });
+    }
+    group.finish();
+}
+
 // ============================================
 // STANDARD LIBRARY PERFORMANCE BENCHMARKS
 // ============================================
@@ -433,6 +485,7 @@ criterion_group!(
     bench_runtime_variable_operations,
     bench_runtime_function_calls,
     bench_runtime_control_flow,
+    bench_runtime_tight_loop,
     bench_chain_operations,
     bench_crypto_operations,
     bench_ai_operations,
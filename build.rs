@@ -0,0 +1,447 @@
+//! Generates the stdlib signature registry (`stdlib::signatures`) from the namespace dispatch in
+//! `src/runtime/engine.rs`: every `"ns" => self.call_<x>_function(..)` arm of
+//! `call_namespace_function`, and every `"name" => { .. }` arm of the `match name` in that
+//! function. Arity comes from the argument-count checks of an arm, parameter names from the
+//! `let` bindings of `args[i]`, and types from the `Value::*` variants an arm matches and returns.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const ENGINE: &str = "src/runtime/engine.rs";
+
+struct Function {
+    namespace: String,
+    name: String,
+    doc: String,
+    params: Vec<(String, &'static str, bool)>,
+    ret: &'static str,
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", ENGINE);
+    println!("cargo:rerun-if-changed=build.rs");
+    let source = fs::read_to_string(ENGINE).unwrap_or_default();
+    let chars: Vec<char> = source.chars().collect();
+
+    let mut functions = Vec::new();
+    for (namespaces, handler) in dispatch_table(&source) {
+        let Some(arms) = handler_arms(&chars, &source, &handler) else {
+            continue;
+        };
+        for namespace in &namespaces {
+            for (names, body) in &arms {
+                let (params, ret) = analyze_arm(body);
+                for name in names {
+                    functions.push(Function {
+                        namespace: namespace.clone(),
+                        name: name.clone(),
+                        doc: leading_comment(body),
+                        params: params.clone(),
+                        ret,
+                    });
+                }
+            }
+        }
+    }
+    functions.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+    functions.dedup_by(|a, b| a.namespace == b.namespace && a.name == b.name);
+
+    let mut out = String::from("&[\n");
+    for f in &functions {
+        out.push_str(&format!(
+            "    StdlibFunction {{ namespace: {:?}, name: {:?}, doc: {:?}, params: &[",
+            f.namespace, f.name, f.doc
+        ));
+        for (name, ty, optional) in &f.params {
+            out.push_str(&format!(
+                "StdlibParam {{ name: {:?}, ty: {:?}, optional: {} }}, ",
+                name, ty, optional
+            ));
+        }
+        out.push_str(&format!("], ret: {:?} }},\n", f.ret));
+    }
+    out.push_str("]\n");
+    let dest = Path::new(&env::var("OUT_DIR").unwrap()).join("stdlib_signatures.rs");
+    fs::write(dest, out).unwrap();
+}
+
+/// `(namespaces, handler)` for each `"a" | "b" => self.call_x_function(...)` line.
+fn dispatch_table(source: &str) -> Vec<(Vec<String>, String)> {
+    let Some(start) = source.find("fn call_namespace_function(") else {
+        return Vec::new();
+    };
+    let body = &source[start..];
+    let body = &body[..body.find("\n    fn ").unwrap_or(body.len())];
+    body.lines()
+        .filter_map(|line| {
+            let (pattern, call) = line.trim().split_once("=> self.")?;
+            let handler = call.split('(').next()?.trim();
+            if !handler.starts_with("call_") || !handler.ends_with("_function") {
+                return None;
+            }
+            let namespaces = string_alternatives(pattern.trim())?;
+            Some((namespaces, handler.to_string()))
+        })
+        .collect()
+}
+
+/// `"a" | "b"` as `["a", "b"]`; `None` for anything else.
+fn string_alternatives(pattern: &str) -> Option<Vec<String>> {
+    pattern
+        .split('|')
+        .map(|p| {
+            let p = p.trim();
+            let inner = p.strip_prefix('"')?.strip_suffix('"')?;
+            (!inner.is_empty() && !inner.contains('"')).then(|| inner.to_string())
+        })
+        .collect()
+}
+
+/// The string-literal arms of the first `match name {` in `fn <handler>(`, each with the text of
+/// its body.
+fn handler_arms(chars: &[char], source: &str, handler: &str) -> Option<Vec<(Vec<String>, String)>> {
+    let header = source.find(&format!("fn {}(", handler))?;
+    let header = source[..header].chars().count();
+    let open = find_match_name(chars, header)?;
+
+    let mut arms: Vec<(Vec<String>, usize)> = Vec::new();
+    let mut ends = Vec::new();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    ends.push(i);
+                    break;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '\'' => {
+                i = skip_char_literal(chars, i);
+                continue;
+            }
+            '"' | 'r' if starts_string(chars, i) => {
+                let arm_start = i;
+                let mut names = Vec::new();
+                let mut j = i;
+                // `"a" | "b" =>` at arm level starts an arm.
+                loop {
+                    let (literal, after) = read_string(chars, j);
+                    names.push(literal);
+                    j = skip_whitespace(chars, after);
+                    if chars.get(j) == Some(&'|') && depth == 1 {
+                        j = skip_whitespace(chars, j + 1);
+                        if starts_string(chars, j) {
+                            continue;
+                        }
+                    }
+                    break;
+                }
+                if depth == 1 && chars.get(j) == Some(&'=') && chars.get(j + 1) == Some(&'>') {
+                    ends.push(arm_start);
+                    arms.push((names, j + 2));
+                    i = j + 2;
+                } else {
+                    i = read_string(chars, i).1;
+                }
+                continue;
+            }
+            '=' if depth == 1 && chars.get(i + 1) == Some(&'>') => {
+                // Any other arm (`_ =>`, `other if ..`) ends the previous one.
+                let line_start = chars[..i].iter().rposition(|c| *c == '\n').unwrap_or(0);
+                ends.push(line_start);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    ends.sort_unstable();
+    Some(
+        arms.into_iter()
+            .map(|(names, start)| {
+                let end = ends
+                    .iter()
+                    .copied()
+                    .find(|e| *e > start)
+                    .unwrap_or(chars.len());
+                (names, chars[start..end].iter().collect())
+            })
+            .collect(),
+    )
+}
+
+/// The `{` of the first `match name {` after `from`.
+fn find_match_name(chars: &[char], from: usize) -> Option<usize> {
+    let needle: Vec<char> = "match name {".chars().collect();
+    (from..chars.len().saturating_sub(needle.len()))
+        .find(|&i| chars[i..i + needle.len()] == needle[..])
+        .map(|i| i + needle.len() - 1)
+}
+
+fn starts_string(chars: &[char], i: usize) -> bool {
+    match chars.get(i) {
+        Some('"') => true,
+        Some('r') => {
+            let ident_before = i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+            let mut j = i + 1;
+            while chars.get(j) == Some(&'#') {
+                j += 1;
+            }
+            !ident_before && chars.get(j) == Some(&'"')
+        }
+        _ => false,
+    }
+}
+
+/// The contents of the (raw) string literal at `i` and the index after it.
+fn read_string(chars: &[char], i: usize) -> (String, usize) {
+    let mut j = i;
+    let mut hashes = 0;
+    let raw = chars[j] == 'r';
+    if raw {
+        j += 1;
+        while chars[j] == '#' {
+            hashes += 1;
+            j += 1;
+        }
+    }
+    j += 1;
+    let mut text = String::new();
+    while j < chars.len() {
+        let c = chars[j];
+        if !raw && c == '\\' {
+            text.push(c);
+            if let Some(next) = chars.get(j + 1) {
+                text.push(*next);
+            }
+            j += 2;
+            continue;
+        }
+        if c == '"' && (0..hashes).all(|h| chars.get(j + 1 + h) == Some(&'#')) {
+            return (text, j + 1 + hashes);
+        }
+        text.push(c);
+        j += 1;
+    }
+    (text, j)
+}
+
+/// Past a char literal at `i`, or just past the quote of a lifetime.
+fn skip_char_literal(chars: &[char], i: usize) -> usize {
+    if chars.get(i + 1) == Some(&'\\') {
+        let close = chars[i + 2..].iter().position(|c| *c == '\'');
+        return close.map(|p| i + 3 + p).unwrap_or(i + 1);
+    }
+    if chars.get(i + 2) == Some(&'\'') {
+        return i + 3;
+    }
+    i + 1
+}
+
+fn skip_whitespace(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    i
+}
+
+/// The first `//` comment of an arm body, before any code.
+fn leading_comment(body: &str) -> String {
+    let body = body.trim_start().trim_start_matches('{');
+    body.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .and_then(|l| l.strip_prefix("//"))
+        .map(|l| l.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn integers_after(body: &str, pattern: &str) -> Vec<usize> {
+    body.match_indices(pattern)
+        .filter_map(|(at, _)| {
+            let rest = &body[at + pattern.len()..];
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Parameters `(name, type, optional)` and return type of one arm.
+#[allow(clippy::type_complexity)]
+fn analyze_arm(body: &str) -> (Vec<(String, &'static str, bool)>, &'static str) {
+    let exact = integers_after(body, "args.len() != ");
+    let below = integers_after(body, "args.len() < ");
+    let above = integers_after(body, "args.len() > ");
+    let expected = integers_after(body, "expected: ");
+    let mut indices = integers_after(body, "args[");
+    let optional_indices = integers_after(body, "args.get(");
+    indices.extend(optional_indices.iter().copied());
+    let used = indices.iter().map(|i| i + 1).max().unwrap_or(0);
+
+    let min = if let Some(min) = exact.iter().min() {
+        *min
+    } else if let Some(min) = below.iter().max() {
+        *min
+    } else if body.contains("!args.is_empty()") {
+        0
+    } else if let Some(min) = expected.first() {
+        (*min).min(used.max(*min))
+    } else {
+        used.min(
+            (0..used)
+                .find(|i| optional_indices.contains(i))
+                .unwrap_or(used),
+        )
+    };
+    let max = exact
+        .iter()
+        .max()
+        .copied()
+        .or_else(|| above.iter().min().copied())
+        .unwrap_or(0)
+        .max(used)
+        .max(min);
+    let max = if body.contains("!args.is_empty()") && exact.is_empty() && used == 0 {
+        0
+    } else {
+        max
+    };
+
+    let mut params: Vec<(String, &'static str, bool)> = Vec::new();
+    for i in 0..max {
+        let (mut name, ty) = param_at(body, i);
+        // Two arguments read into one binding (`args[3]` or `args[4]`): keep the first.
+        if params.iter().any(|(n, _, _)| *n == name) {
+            name = format!("arg{}", i + 1);
+        }
+        params.push((name, ty, i >= min));
+    }
+    (params, return_type(body))
+}
+
+/// Name and type of argument `i`, from the statement that reads it.
+fn param_at(body: &str, i: usize) -> (String, &'static str) {
+    let fallback = || format!("arg{}", i + 1);
+    let needles = [format!("args[{}]", i), format!("args.get({})", i)];
+    let Some(at) = needles.iter().filter_map(|n| body.find(n.as_str())).min() else {
+        return (fallback(), "any");
+    };
+    // The `let` this read belongs to: no statement boundary in between.
+    let before = &body[..at];
+    let name = before.rfind("let ").and_then(|let_at| {
+        let between = &before[let_at..];
+        let mut depth = 0i32;
+        for c in between.chars() {
+            match c {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth -= 1,
+                ';' if depth <= 0 => return None,
+                _ => {}
+            }
+        }
+        let binding = between["let ".len()..].trim_start();
+        let binding = binding.strip_prefix("mut ").unwrap_or(binding);
+        let ident: String = binding
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        let is_ident = ident
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_');
+        (is_ident && ident != "_").then_some(ident)
+    });
+    let name = name.map(|n| n.trim_start_matches('_').to_string());
+    let name = match name {
+        Some(n) if !n.is_empty() => n,
+        _ => fallback(),
+    };
+
+    // The type the read expects: an explicit `TypeError { expected: "..." }`, the first
+    // `Value::*` variant it matches, or a conversion helper.
+    let window: String = body[at..].chars().take(400).collect();
+    let statement_end = window.find(";\n").unwrap_or(window.len());
+    let window = &window[..statement_end];
+    let direct = &body[..at];
+    let ty = if direct.ends_with("value_to_string(&") || direct.ends_with("value_to_string(") {
+        "string"
+    } else if direct.ends_with("value_map_to_string_map(&") {
+        "map"
+    } else if let Some(ty) = window
+        .split("expected: \"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .and_then(type_name)
+    {
+        ty
+    } else {
+        window
+            .find("Value::")
+            .and_then(|p| {
+                let variant: String = window[p + "Value::".len()..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric())
+                    .collect();
+                variant_type(&variant)
+            })
+            .unwrap_or("any")
+    };
+    (name, ty)
+}
+
+fn type_name(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "int" | "integer" => "int",
+        "float" | "number" => "float",
+        "string" => "string",
+        "bool" | "boolean" => "bool",
+        "map" | "object" => "map",
+        "list" | "array" => "list",
+        "task" => "task",
+        "chan" => "chan",
+        _ => return None,
+    })
+}
+
+fn variant_type(variant: &str) -> Option<&'static str> {
+    Some(match variant {
+        "Int" => "int",
+        "Float" => "float",
+        "String" => "string",
+        "Bool" => "bool",
+        "Map" | "Struct" => "map",
+        "List" | "Array" => "list",
+        "Null" => "null",
+        _ => return None,
+    })
+}
+
+/// The one `Value::*` variant every `Ok(Value::..)` of an arm returns, else `any`.
+fn return_type(body: &str) -> &'static str {
+    let mut kinds: Vec<&'static str> = body
+        .match_indices("Ok(Value::")
+        .map(|(at, pattern)| {
+            let variant: String = body[at + pattern.len()..]
+                .chars()
+                .take_while(|c| c.is_alphanumeric())
+                .collect();
+            variant_type(&variant).unwrap_or("any")
+        })
+        .collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+    let other_ok = body.matches("Ok(").count() > body.matches("Ok(Value::").count();
+    match kinds.as_slice() {
+        [only] if !other_ok => only,
+        _ => "any",
+    }
+}
@@ -74,7 +74,12 @@ pub enum ColorChoice {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run a DAL file
-    Run { file: String },
+    Run {
+        file: String,
+        /// Build the file's @compile_target("wasm") services and run them in the embedded wasm runtime
+        #[arg(long)]
+        wasm: bool,
+    },
 
     /// Run tests (discovers *.test.dal files)
     Test {
@@ -262,6 +267,9 @@ pub enum Commands {
         rest: Vec<String>,
     },
 
+    /// Start Debug Adapter Protocol server on stdio (for editors)
+    Dap,
+
     /// Generate documentation
     Doc {
         #[arg(required = true)]
@@ -278,8 +286,9 @@ pub enum Commands {
     /// Debug a DAL file
     Debug {
         file: String,
-        #[arg(long)]
-        breakpoint: Option<u32>,
+        /// Breakpoint line, optionally conditional ("12" or "12 if x > 3"); repeatable
+        #[arg(long, short = 'b')]
+        breakpoint: Vec<String>,
     },
 
     /// Agent lifecycle & coordination
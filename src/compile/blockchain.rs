@@ -16,8 +16,12 @@ fn dal_type_to_solidity(dal_type: &str) -> String {
     if t.is_empty() {
         return "uint256".to_string();
     }
-    if t == "int" {
-        return "int256".to_string();
+    match t {
+        "int" | "i256" | "int256" => return "int256".to_string(),
+        "u256" | "uint256" => return "uint256".to_string(),
+        // Fixed-point decimals are stored as their scaled integer (18 fractional digits).
+        "decimal" => return "int256".to_string(),
+        _ => {}
     }
     if t == "string" || t == "bool" {
         return t.to_string();
@@ -101,7 +105,10 @@ fn infer_expr_solidity_type(
     expr: &Expression,
 ) -> Option<String> {
     match expr {
-        Expression::Literal(Literal::Int(_)) => Some("int256".to_string()),
+        Expression::Literal(Literal::Int(_) | Literal::I256(_) | Literal::Decimal(_)) => {
+            Some("int256".to_string())
+        }
+        Expression::Literal(Literal::U256(_)) => Some("uint256".to_string()),
         Expression::Literal(Literal::Bool(_)) => Some("bool".to_string()),
         Expression::Literal(Literal::String(_)) => Some("string memory".to_string()),
         Expression::Identifier(name) => locals
@@ -118,10 +125,19 @@ fn infer_expr_solidity_type(
                 | Operator::Star
                 | Operator::Slash
                 | Operator::Percent => {
-                    if left_ty == "int256" && right_ty == "int256" {
-                        Some("int256".to_string())
-                    } else {
-                        None
+                    let is_int_literal =
+                        |e: &Expression| matches!(e, Expression::Literal(Literal::Int(_)));
+                    match (left_ty.as_str(), right_ty.as_str()) {
+                        ("int256", "int256") => Some("int256".to_string()),
+                        ("uint256", "uint256") => Some("uint256".to_string()),
+                        // Plain literals adopt the unsigned operand's type (`balance + 1`).
+                        ("uint256", "int256") if is_int_literal(right) => {
+                            Some("uint256".to_string())
+                        }
+                        ("int256", "uint256") if is_int_literal(left) => {
+                            Some("uint256".to_string())
+                        }
+                        _ => None,
                     }
                 }
                 Operator::Equal
@@ -187,6 +203,10 @@ fn infer_expr_solidity_type(
 fn expression_to_solidity(expr: &Expression) -> Result<String, String> {
     match expr {
         Expression::Literal(Literal::Int(v)) => Ok(v.to_string()),
+        Expression::Literal(Literal::U256(v)) => Ok(v.to_string()),
+        Expression::Literal(Literal::I256(v)) => Ok(v.to_string()),
+        // Scaled integer; products of two decimals are not rescaled on chain.
+        Expression::Literal(Literal::Decimal(d)) => Ok(d.raw().to_string()),
         Expression::Literal(Literal::Float(_)) => {
             Err("float literals are unsupported in decentralized-v1 blockchain codegen".to_string())
         }
@@ -604,14 +624,28 @@ fn collect_namespaces_from_statement(stmt: &Statement) -> HashSet<String> {
             .iter()
             .flat_map(|m| collect_namespaces_from_block(&m.body))
             .collect(),
+        Impl(i) => i
+            .methods
+            .iter()
+            .flat_map(|m| collect_namespaces_from_block(&m.body))
+            .collect(),
+        Trait(t) => t
+            .methods
+            .iter()
+            .filter_map(|m| m.default_body.as_ref())
+            .flat_map(collect_namespaces_from_block)
+            .collect(),
         Agent(a) => collect_namespaces_from_block(&a.body),
         Spawn(s) => collect_namespaces_from_block(&s.body),
-        Import(_) | Break(_) | Continue(_) | Message(_) => HashSet::new(),
+        Import(_) | Break(_) | Continue(_) | Message(_) | Struct(_) | Enum(_) => HashSet::new(),
         Loop(loop_stmt) => collect_namespaces_from_block(&loop_stmt.body),
         Match(match_stmt) => {
             let mut set = collect_namespaces_from_expression(&match_stmt.expression);
             for case in &match_stmt.cases {
                 set.extend(collect_namespaces_from_block(&case.body));
+                if let Some(guard) = &case.guard {
+                    set.extend(collect_namespaces_from_expression(guard));
+                }
                 if let crate::parser::ast::MatchPattern::Range(start, end) = &case.pattern {
                     set.extend(collect_namespaces_from_expression(start.as_ref()));
                     set.extend(collect_namespaces_from_expression(end.as_ref()));
@@ -647,14 +681,18 @@ fn collect_namespaces_from_expression(expr: &Expression) -> HashSet<String> {
         Assignment(_, e) => {
             out.extend(collect_namespaces_from_expression(e));
         }
-        FieldAccess(obj, _) => {
+        FieldAccess(obj, _) | OptionalFieldAccess(obj, _) => {
             out.extend(collect_namespaces_from_expression(obj));
         }
+        NullCoalesce(l, r) | Pipeline(l, r) => {
+            out.extend(collect_namespaces_from_expression(l));
+            out.extend(collect_namespaces_from_expression(r));
+        }
         FieldAssignment(obj, _, v) => {
             out.extend(collect_namespaces_from_expression(obj));
             out.extend(collect_namespaces_from_expression(v));
         }
-        Await(e) | Spawn(e) | Throw(e) => {
+        Await(e) | Spawn(e) | Throw(e) | Propagate(e) => {
             out.extend(collect_namespaces_from_expression(e));
         }
         ObjectLiteral(map) => {
@@ -662,7 +700,7 @@ fn collect_namespaces_from_expression(expr: &Expression) -> HashSet<String> {
                 out.extend(collect_namespaces_from_expression(e));
             }
         }
-        ArrayLiteral(items) => {
+        ArrayLiteral(items) | Interpolation(items) => {
             for e in items {
                 out.extend(collect_namespaces_from_expression(e));
             }
@@ -682,12 +720,26 @@ fn collect_namespaces_from_expression(expr: &Expression) -> HashSet<String> {
             receiver,
             arguments,
             ..
+        }
+        | OptionalMethodCall {
+            receiver,
+            arguments,
+            ..
+        }
+        | Call {
+            callee: receiver,
+            arguments,
         } => {
             out.extend(collect_namespaces_from_expression(receiver));
             for arg in arguments {
                 out.extend(collect_namespaces_from_expression(arg));
             }
         }
+        StructLiteral { fields, .. } => {
+            for e in fields.values() {
+                out.extend(collect_namespaces_from_expression(e));
+            }
+        }
         Literal(_) | Identifier(_) => {}
     }
     out
@@ -960,6 +1012,7 @@ service Ops @compile_target("blockchain") {
                                     )),
                                 }),
                             ],
+                            statement_spans: Vec::new(),
                         },
                         alternative: None,
                     }),
@@ -970,6 +1023,7 @@ service Ops @compile_target("blockchain") {
                         )),
                     }),
                 ],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -999,6 +1053,7 @@ service Ops @compile_target("blockchain") {
                 field_type: "int".to_string(),
                 initial_value: None,
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![method.clone()],
             events: vec![EventDeclaration {
@@ -1095,6 +1150,7 @@ service Counter @compile_target("blockchain") {
                             Box::new(Expression::Literal(crate::lexer::tokens::Literal::Int(1))),
                         ),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::Let(LetStatement {
                         name: "is_large".to_string(),
@@ -1104,6 +1160,7 @@ service Counter @compile_target("blockchain") {
                             Box::new(Expression::Literal(crate::lexer::tokens::Literal::Int(10))),
                         ),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::If(IfStatement {
                         condition: Expression::Identifier("is_large".to_string()),
@@ -1121,6 +1178,7 @@ service Counter @compile_target("blockchain") {
                                     )),
                                 }),
                             ],
+                            statement_spans: Vec::new(),
                         },
                         alternative: None,
                     }),
@@ -1128,6 +1186,7 @@ service Counter @compile_target("blockchain") {
                         value: Some(Expression::Identifier("next".to_string())),
                     }),
                 ],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -1148,6 +1207,7 @@ service Counter @compile_target("blockchain") {
                 field_type: "int".to_string(),
                 initial_value: None,
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![method],
             events: vec![],
@@ -1189,6 +1249,7 @@ service Counter @compile_target("blockchain") {
                 field_type: "int".to_string(),
                 initial_value: Some(Expression::Literal(crate::lexer::tokens::Literal::Int(7))),
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![],
             events: vec![],
@@ -1204,6 +1265,46 @@ service Counter @compile_target("blockchain") {
         );
     }
 
+    #[test]
+    fn decentralized_v1_maps_wide_numeric_types() {
+        let source = r#"
+@secure
+@trust("decentralized")
+@chain("ethereum")
+service Vault @compile_target("blockchain") {
+    supply: u256 = 1000000000000000000000u256;
+    rate: decimal = 1.5d;
+
+    fn mint(amount: u256) -> u256 {
+        let next = amount + 1;
+        return next;
+    }
+}
+"#;
+        let program = crate::parse_source(source).expect("parse DAL source");
+        let services = crate::compile::select_services_for_target(
+            &program,
+            &crate::lexer::tokens::CompilationTarget::Blockchain,
+        );
+        let service = services[0];
+
+        let solidity =
+            service_to_solidity(service, service.methods.as_slice()).expect("solidity generation");
+        for expected in [
+            "uint256 supply = 1000000000000000000000;",
+            "int256 rate = 1500000000000000000;",
+            "function mint(uint256 amount)",
+            "uint256 next = (amount + 1);",
+        ] {
+            assert!(
+                solidity.contains(expected),
+                "expected `{}`, got:\n{}",
+                expected,
+                solidity
+            );
+        }
+    }
+
     #[test]
     fn decentralized_v1_lowers_unary_and_boolean_expression_family() {
         use crate::parser::ast::{
@@ -1228,6 +1329,7 @@ service Counter @compile_target("blockchain") {
                             Box::new(Expression::Identifier("amount".to_string())),
                         ),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::Let(LetStatement {
                         name: "is_valid".to_string(),
@@ -1249,11 +1351,13 @@ service Counter @compile_target("blockchain") {
                             )),
                         ),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::Return(ReturnStatement {
                         value: Some(Expression::Identifier("is_valid".to_string())),
                     }),
                 ],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -1274,6 +1378,7 @@ service Counter @compile_target("blockchain") {
                 field_type: "int".to_string(),
                 initial_value: None,
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![method],
             events: vec![],
@@ -1343,6 +1448,7 @@ service Counter @compile_target("blockchain") {
                                 )),
                             }),
                         ],
+                        statement_spans: Vec::new(),
                     },
                     alternative: Some(BlockStatement {
                         statements: vec![Statement::Return(ReturnStatement {
@@ -1351,8 +1457,10 @@ service Counter @compile_target("blockchain") {
                                 "total".to_string(),
                             )),
                         })],
+                        statement_spans: Vec::new(),
                     }),
                 })],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -1373,6 +1481,7 @@ service Counter @compile_target("blockchain") {
                 field_type: "int".to_string(),
                 initial_value: Some(Expression::Literal(crate::lexer::tokens::Literal::Int(0))),
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![method],
             events: vec![],
@@ -1465,11 +1574,13 @@ service Counter @compile_target("blockchain") {
                         name: "bal".to_string(),
                         value: map_read(),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::Return(ReturnStatement {
                         value: Some(Expression::Identifier("bal".to_string())),
                     }),
                 ],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -1490,6 +1601,7 @@ service Counter @compile_target("blockchain") {
                 field_type: "map<address,int>".to_string(),
                 initial_value: None,
                 visibility: FieldVisibility::Private,
+                attributes: vec![],
             }],
             methods: vec![method],
             events: vec![],
@@ -1575,6 +1687,7 @@ service Counter @compile_target("blockchain") {
                         Box::new(Expression::Literal(crate::lexer::tokens::Literal::Int(2))),
                     )),
                 })],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
@@ -1597,6 +1710,7 @@ service Counter @compile_target("blockchain") {
                             arguments: vec![Expression::Identifier("n".to_string())],
                         }),
                         line: None,
+                        pattern: None,
                     }),
                     Statement::Return(ReturnStatement {
                         value: Some(Expression::FunctionCall(AstFunctionCall {
@@ -1605,6 +1719,7 @@ service Counter @compile_target("blockchain") {
                         })),
                     }),
                 ],
+                statement_spans: Vec::new(),
             },
             attributes: vec![],
             is_async: false,
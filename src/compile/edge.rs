@@ -7,125 +7,8 @@ use crate::parser::ast::{Program, ServiceStatement};
 use std::env;
 use std::process::Command;
 
-fn dal_type_to_rust(dal_type: &str) -> String {
-    let t = dal_type.trim();
-    if t.is_empty() || t == "int" {
-        return "i64".to_string();
-    }
-    if t == "string" {
-        return "String".to_string();
-    }
-    if t == "bool" {
-        return "bool".to_string();
-    }
-    if t.starts_with("list<") || t.starts_with("vector<") {
-        let inner = t
-            .strip_prefix("list<")
-            .or_else(|| t.strip_prefix("vector<"))
-            .and_then(|s| s.strip_suffix('>'))
-            .unwrap_or("i64");
-        return format!("Vec<{}>", dal_type_to_rust(inner));
-    }
-    if t.starts_with("map<") {
-        let inner = t
-            .strip_prefix("map<")
-            .and_then(|s| s.strip_suffix('>'))
-            .unwrap_or("String, i64");
-        let mut parts = inner.splitn(2, ',');
-        let k = parts.next().unwrap_or("String").trim();
-        let v = parts.next().unwrap_or("i64").trim();
-        return format!(
-            "std::collections::HashMap<{}, {}>",
-            dal_type_to_rust(k),
-            dal_type_to_rust(v)
-        );
-    }
-    if t == "any" || t == "float" {
-        return "f64".to_string();
-    }
-    t.to_string()
-}
-
-fn default_return_expr(ret: &str, method_name: &str) -> String {
-    let r = ret.trim();
-    if r == "()" {
-        return "()".to_string();
-    }
-    if r == "bool" {
-        return "false".to_string();
-    }
-    if r == "i64" || r == "i32" || r == "u64" || r == "u32" || r == "usize" {
-        return "0".to_string();
-    }
-    if r == "f64" || r == "f32" {
-        return "0.0".to_string();
-    }
-    if r == "String" {
-        return "String::new()".to_string();
-    }
-    if r.starts_with("Vec<") {
-        return "Default::default()".to_string();
-    }
-    if r.contains("HashMap") {
-        return "Default::default()".to_string();
-    }
-    if r.starts_with("Option<") {
-        return "None".to_string();
-    }
-    if r.starts_with("Result<") {
-        return format!(
-            "panic!(\"DAL edge codegen: Result-returning method '{}' is not fully lowered. Use `dal run` / the interpreter, or extend the edge backend (see docs/USAGE_GUIDE.md @compile_target).\")",
-            method_name
-        );
-    }
-    "Default::default()".to_string()
-}
-
-fn services_to_rust(services: &[&ServiceStatement]) -> String {
-    let mut out = String::new();
-    out.push_str("// Generated by DAL edge/IoT backend. For embedded, set CARGO_BUILD_TARGET (e.g. thumbv7em-none-eabihf).\n");
-    out.push_str("#![allow(dead_code)]\n\n");
-
-    for service in services {
-        let mod_name = service.name.to_lowercase().replace('-', "_");
-        out.push_str(&format!("pub mod {} {{\n", mod_name));
-        if service.fields.is_empty() {
-            out.push_str("    pub struct State;\n");
-        } else {
-            out.push_str("    pub struct State {\n");
-            for field in &service.fields {
-                let rust_type = dal_type_to_rust(&field.field_type);
-                out.push_str(&format!("        pub {}: {},\n", field.name, rust_type));
-            }
-            out.push_str("    }\n");
-        }
-        for method in &service.methods {
-            let params_str: Vec<String> = method
-                .parameters
-                .iter()
-                .map(|p| {
-                    let ty = p.param_type.as_deref().unwrap_or("i64");
-                    format!("{}: {}", p.name, dal_type_to_rust(ty))
-                })
-                .collect();
-            let ret = method
-                .return_type
-                .as_deref()
-                .map(dal_type_to_rust)
-                .unwrap_or_else(|| "()".to_string());
-            let default_ret = default_return_expr(&ret, &method.name);
-            out.push_str(&format!(
-                "    /// Edge stub: replace with device logic or keep default.\n    pub fn {}({}) -> {} {{\n        {}\n    }}\n",
-                method.name,
-                params_str.join(", "),
-                ret,
-                default_ret
-            ));
-        }
-        out.push_str("}\n\n");
-    }
-    out
-}
+/// Leading comment of the generated lib.rs.
+const HEADER: &str = "// Generated by DAL edge/IoT backend. For embedded, set CARGO_BUILD_TARGET (e.g. thumbv7em-none-eabihf).\n";
 
 fn check_rust_available() -> bool {
     if let Some(available) = super::get_compiler_available_override() {
@@ -144,7 +27,7 @@ pub struct EdgeBackend;
 impl CompileBackend for EdgeBackend {
     fn compile(
         &self,
-        _program: &Program,
+        program: &Program,
         services: &[&ServiceStatement],
         opts: &CompileOptions,
     ) -> Result<CompileArtifacts, CompileError> {
@@ -165,7 +48,7 @@ impl CompileBackend for EdgeBackend {
         let src_dir = build_dir.join("src");
         std::fs::create_dir_all(&src_dir).map_err(CompileError::Io)?;
 
-        let rust_code = services_to_rust(services);
+        let rust_code = super::rust_lower::services_to_rust(program, services, HEADER);
         let lib_path = src_dir.join("lib.rs");
         std::fs::write(&lib_path, rust_code).map_err(CompileError::Io)?;
 
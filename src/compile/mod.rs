@@ -5,6 +5,10 @@
 mod blockchain;
 mod edge;
 mod native;
+#[cfg(test)]
+#[allow(dead_code)]
+mod native_prelude;
+pub mod rust_lower;
 mod wasm;
 
 use crate::lexer::tokens::CompilationTarget;
@@ -455,7 +459,12 @@ fn collect_unsupported_from_statement(
 ) {
     use crate::parser::ast::Statement::*;
     match stmt {
-        Let(s) => collect_unsupported_from_expression(&s.value, unsupported),
+        Let(s) => {
+            if s.pattern.is_some() {
+                unsupported.insert("destructuring-let".to_string());
+            }
+            collect_unsupported_from_expression(&s.value, unsupported);
+        }
         Return(s) => {
             if let Some(v) = &s.value {
                 collect_unsupported_from_expression(v, unsupported);
@@ -507,7 +516,8 @@ fn collect_unsupported_from_statement(
         Message(_) => {
             unsupported.insert("message-statement".to_string());
         }
-        Import(_) | Function(_) | Service(_) | Break(_) | Continue(_) => {}
+        Import(_) | Function(_) | Service(_) | Break(_) | Continue(_) | Struct(_) | Enum(_)
+        | Trait(_) | Impl(_) => {}
     }
 }
 
@@ -522,7 +532,13 @@ fn collect_unsupported_from_expression(
             collect_unsupported_from_expression(l, unsupported);
             collect_unsupported_from_expression(r, unsupported);
         }
-        UnaryOp(_, e) | Assignment(_, e) | FieldAccess(e, _) | Await(e) | Spawn(e) | Throw(e) => {
+        UnaryOp(_, e)
+        | Assignment(_, e)
+        | FieldAccess(e, _)
+        | Await(e)
+        | Spawn(e)
+        | Throw(e)
+        | Propagate(e) => {
             if matches!(expr, Await(_)) {
                 unsupported.insert("await".to_string());
             }
@@ -532,6 +548,9 @@ fn collect_unsupported_from_expression(
             if matches!(expr, Throw(_)) {
                 unsupported.insert("throw".to_string());
             }
+            if matches!(expr, Propagate(_)) {
+                unsupported.insert("?-operator".to_string());
+            }
             collect_unsupported_from_expression(e, unsupported);
         }
         FunctionCall(call) => {
@@ -562,6 +581,13 @@ fn collect_unsupported_from_expression(
         ArrowFunction { .. } => {
             unsupported.insert("arrow-function".to_string());
         }
+        Call { callee, arguments } => {
+            unsupported.insert("closure-call".to_string());
+            collect_unsupported_from_expression(callee, unsupported);
+            for arg in arguments {
+                collect_unsupported_from_expression(arg, unsupported);
+            }
+        }
         Range(start, end) => {
             unsupported.insert("range-expression".to_string());
             collect_unsupported_from_expression(start, unsupported);
@@ -578,6 +604,45 @@ fn collect_unsupported_from_expression(
                 collect_unsupported_from_expression(arg, unsupported);
             }
         }
+        OptionalFieldAccess(receiver, _) => {
+            unsupported.insert("optional-chaining".to_string());
+            collect_unsupported_from_expression(receiver, unsupported);
+        }
+        OptionalMethodCall {
+            receiver,
+            arguments,
+            ..
+        } => {
+            unsupported.insert("optional-chaining".to_string());
+            collect_unsupported_from_expression(receiver, unsupported);
+            for arg in arguments {
+                collect_unsupported_from_expression(arg, unsupported);
+            }
+        }
+        NullCoalesce(l, r) | Pipeline(l, r) => {
+            unsupported.insert(
+                if matches!(expr, NullCoalesce(..)) {
+                    "null-coalescing"
+                } else {
+                    "pipeline"
+                }
+                .to_string(),
+            );
+            collect_unsupported_from_expression(l, unsupported);
+            collect_unsupported_from_expression(r, unsupported);
+        }
+        Interpolation(parts) => {
+            unsupported.insert("string-interpolation".to_string());
+            for part in parts {
+                collect_unsupported_from_expression(part, unsupported);
+            }
+        }
+        StructLiteral { fields, .. } => {
+            unsupported.insert("struct-literal".to_string());
+            for value in fields.values() {
+                collect_unsupported_from_expression(value, unsupported);
+            }
+        }
     }
 }
 
@@ -638,6 +703,9 @@ fn collect_namespaces_from_statement(stmt: &Statement) -> std::collections::Hash
             let mut set = collect_namespaces_from_expression(&m.expression);
             for case in &m.cases {
                 set.extend(collect_namespaces_from_block(&case.body));
+                if let Some(guard) = &case.guard {
+                    set.extend(collect_namespaces_from_expression(guard));
+                }
             }
             if let Some(default_case) = &m.default_case {
                 set.extend(collect_namespaces_from_block(default_case));
@@ -645,9 +713,8 @@ fn collect_namespaces_from_statement(stmt: &Statement) -> std::collections::Hash
             set
         }
         Loop(l) => collect_namespaces_from_block(&l.body),
-        Service(_) | Import(_) | Agent(_) | Spawn(_) | Break(_) | Continue(_) | Message(_) => {
-            std::collections::HashSet::new()
-        }
+        Service(_) | Import(_) | Agent(_) | Spawn(_) | Break(_) | Continue(_) | Message(_)
+        | Struct(_) | Enum(_) | Trait(_) | Impl(_) => std::collections::HashSet::new(),
     }
 }
 
@@ -667,10 +734,15 @@ fn collect_namespaces_from_expression(expr: &Expression) -> std::collections::Ha
             set.extend(collect_namespaces_from_expression(l));
             set.extend(collect_namespaces_from_expression(r));
         }
-        UnaryOp(_, e) | Assignment(_, e) | FieldAccess(e, _) | Await(e) | Spawn(e) | Throw(e) => {
-            set.extend(collect_namespaces_from_expression(e))
-        }
-        FieldAssignment(l, _, r) => {
+        UnaryOp(_, e)
+        | Assignment(_, e)
+        | FieldAccess(e, _)
+        | OptionalFieldAccess(e, _)
+        | Await(e)
+        | Spawn(e)
+        | Throw(e)
+        | Propagate(e) => set.extend(collect_namespaces_from_expression(e)),
+        FieldAssignment(l, _, r) | NullCoalesce(l, r) | Pipeline(l, r) => {
             set.extend(collect_namespaces_from_expression(l));
             set.extend(collect_namespaces_from_expression(r));
         }
@@ -679,7 +751,7 @@ fn collect_namespaces_from_expression(expr: &Expression) -> std::collections::Ha
                 set.extend(collect_namespaces_from_expression(v));
             }
         }
-        ArrayLiteral(list) => {
+        ArrayLiteral(list) | Interpolation(list) => {
             for e in list {
                 set.extend(collect_namespaces_from_expression(e));
             }
@@ -692,6 +764,15 @@ fn collect_namespaces_from_expression(expr: &Expression) -> std::collections::Ha
             receiver,
             arguments,
             ..
+        }
+        | OptionalMethodCall {
+            receiver,
+            arguments,
+            ..
+        }
+        | Call {
+            callee: receiver,
+            arguments,
         } => {
             set.extend(collect_namespaces_from_expression(receiver));
             for arg in arguments {
@@ -702,6 +783,11 @@ fn collect_namespaces_from_expression(expr: &Expression) -> std::collections::Ha
             set.extend(collect_namespaces_from_expression(start));
             set.extend(collect_namespaces_from_expression(end));
         }
+        StructLiteral { fields, .. } => {
+            for v in fields.values() {
+                set.extend(collect_namespaces_from_expression(v));
+            }
+        }
         Identifier(_) | Literal(_) | ArrowFunction { .. } => {}
     }
     set
@@ -0,0 +1,838 @@
+//! Runtime support embedded as `mod dal` in every crate generated by the native and edge
+//! backends. Mirrors the interpreter's value semantics (SafeMath arithmetic, string
+//! concatenation, truthiness, indexing and list/map methods) so a lowered service method returns
+//! what `dal run` returns. Dependency-free; maps are ordered so output is deterministic.
+//! Values serialize to JSON (`to_json` / `from_json`) for the wasm backend's host ABI.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+/// A runtime error raised by lowered DAL code (the interpreter's `RuntimeError`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DalError(pub String);
+
+pub type DalResult<T> = Result<T, DalError>;
+
+impl fmt::Display for DalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DalError {}
+
+pub fn err<T>(message: impl Into<String>) -> DalResult<T> {
+    Err(DalError(message.into()))
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(s) => write!(f, "\"{}\"", s),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Map(map) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{}\": {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+
+    pub fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Int(i) => *i != 0,
+            Value::Float(x) => *x != 0.0,
+            Value::Bool(b) => *b,
+            Value::Str(s) => !s.is_empty(),
+            Value::List(items) => !items.is_empty(),
+            Value::Map(map) => !map.is_empty(),
+        }
+    }
+
+    /// Map key form of a scalar (strings unquoted); None for lists and maps.
+    fn key_string(&self) -> Option<String> {
+        match self {
+            Value::Str(s) => Some(s.clone()),
+            Value::Int(i) => Some(i.to_string()),
+            Value::Float(x) => Some(x.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Null => Some("null".to_string()),
+            Value::List(_) | Value::Map(_) => None,
+        }
+    }
+
+    fn to_int(&self) -> DalResult<i64> {
+        match self {
+            Value::Int(i) => Ok(*i),
+            Value::Float(x) => Ok(*x as i64),
+            Value::Str(s) => s
+                .parse::<i64>()
+                .or_else(|_| err("Cannot convert string to int")),
+            Value::Bool(b) => Ok(i64::from(*b)),
+            _ => err("Cannot convert value to int"),
+        }
+    }
+}
+
+fn float_result(x: f64, error: &str) -> DalResult<Value> {
+    if x.is_infinite() || x.is_nan() {
+        err(error)
+    } else {
+        Ok(Value::Float(x))
+    }
+}
+
+/// Both operands as floats when at least one is a float and neither is anything but a number.
+fn float_pair(a: &Value, b: &Value) -> Option<(f64, f64)> {
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => Some((*x, *y)),
+        (Value::Int(x), Value::Float(y)) => Some((*x as f64, *y)),
+        (Value::Float(x), Value::Int(y)) => Some((*x, *y as f64)),
+        _ => None,
+    }
+}
+
+pub fn add(a: &Value, b: &Value) -> DalResult<Value> {
+    match (a, b) {
+        (Value::Str(x), Value::Str(y)) => Ok(Value::Str(format!("{}{}", x, y))),
+        (Value::Str(x), other) => Ok(Value::Str(format!("{}{}", x, other))),
+        (other, Value::Str(y)) => Ok(Value::Str(format!("{}{}", other, y))),
+        (Value::List(x), Value::List(y)) => Ok(Value::List(x.iter().chain(y).cloned().collect())),
+        (Value::Int(x), Value::Int(y)) => x
+            .checked_add(*y)
+            .map(Value::Int)
+            .map_or_else(|| err("Integer overflow"), Ok),
+        _ => match float_pair(a, b) {
+            Some((x, y)) => float_result(x + y, "Integer overflow"),
+            None => err("Type mismatch in addition"),
+        },
+    }
+}
+
+pub fn sub(a: &Value, b: &Value) -> DalResult<Value> {
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => x
+            .checked_sub(*y)
+            .map(Value::Int)
+            .map_or_else(|| err("Integer underflow"), Ok),
+        _ => match float_pair(a, b) {
+            Some((x, y)) => float_result(x - y, "Integer underflow"),
+            None => err("Type mismatch in subtraction"),
+        },
+    }
+}
+
+pub fn mul(a: &Value, b: &Value) -> DalResult<Value> {
+    match (a, b) {
+        (Value::Int(x), Value::Int(y)) => x
+            .checked_mul(*y)
+            .map(Value::Int)
+            .map_or_else(|| err("Integer overflow"), Ok),
+        _ => match float_pair(a, b) {
+            Some((x, y)) => float_result(x * y, "Integer overflow"),
+            None => err("Type mismatch in multiplication"),
+        },
+    }
+}
+
+pub fn div(a: &Value, b: &Value) -> DalResult<Value> {
+    match (a, b) {
+        (Value::Int(_), Value::Int(0)) => err("Division by zero"),
+        (Value::Int(x), Value::Int(y)) => x
+            .checked_div(*y)
+            .map(Value::Int)
+            .map_or_else(|| err("Integer overflow"), Ok),
+        _ => match float_pair(a, b) {
+            Some((_, 0.0)) => err("Division by zero"),
+            Some((x, y)) => float_result(x / y, "Division by zero"),
+            None => err("Type mismatch in division"),
+        },
+    }
+}
+
+pub fn rem(a: &Value, b: &Value) -> DalResult<Value> {
+    match (a, b) {
+        (Value::Int(_), Value::Int(0)) => err("Division by zero"),
+        (Value::Int(x), Value::Int(y)) => x
+            .checked_rem(*y)
+            .map(Value::Int)
+            .map_or_else(|| err("Integer overflow"), Ok),
+        (Value::Float(_), Value::Float(y)) if *y == 0.0 => err("Division by zero"),
+        (Value::Float(x), Value::Float(y)) => float_result(x % y, "Division by zero"),
+        _ => err("Type mismatch in modulo"),
+    }
+}
+
+/// `==` compares structurally; an int never equals a float.
+pub fn eq(a: &Value, b: &Value) -> Value {
+    Value::Bool(a == b)
+}
+
+pub fn ne(a: &Value, b: &Value) -> Value {
+    Value::Bool(a != b)
+}
+
+/// `<`, `<=`, `>`, `>=` on numbers, strings and bools.
+pub fn compare(op: &str, a: &Value, b: &Value) -> DalResult<Value> {
+    use std::cmp::Ordering;
+    let ordering = match (a, b) {
+        (Value::Int(x), Value::Int(y)) => x.cmp(y),
+        (Value::Str(x), Value::Str(y)) => x.cmp(y),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => match float_pair(a, b) {
+            Some((x, y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            None => return err("Cannot compare these value types"),
+        },
+    };
+    Ok(Value::Bool(match op {
+        "<" => ordering == Ordering::Less,
+        "<=" => ordering != Ordering::Greater,
+        ">" => ordering == Ordering::Greater,
+        _ => ordering != Ordering::Less,
+    }))
+}
+
+pub fn neg(v: &Value) -> DalResult<Value> {
+    match v {
+        Value::Int(i) => i
+            .checked_neg()
+            .map(Value::Int)
+            .map_or_else(|| err("Integer overflow"), Ok),
+        _ => err("Type mismatch in negation"),
+    }
+}
+
+pub fn not(v: &Value) -> Value {
+    Value::Bool(!v.truthy())
+}
+
+/// `container[key]`: a missing key or out-of-range index is null.
+pub fn index(container: &Value, key: &Value) -> DalResult<Value> {
+    match container {
+        Value::Map(map) => {
+            let key = map_key(key)?;
+            Ok(map.get(&key).cloned().unwrap_or_default())
+        }
+        Value::List(items) => match key {
+            Value::Int(i) => Ok(usize::try_from(*i)
+                .ok()
+                .and_then(|i| items.get(i))
+                .cloned()
+                .unwrap_or_default()),
+            other => err(format!(
+                "Array index must be int, got: {}",
+                other.type_name()
+            )),
+        },
+        other => err(format!("Cannot index value of type: {}", other.type_name())),
+    }
+}
+
+fn map_key(key: &Value) -> DalResult<String> {
+    match key {
+        Value::Str(s) => Ok(s.clone()),
+        Value::Int(i) => Ok(i.to_string()),
+        other => err(format!(
+            "Map key must be string or int, got: {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// `variable[key] = value`: lists grow with nulls when assigning past the end.
+pub fn set_index(target: &mut Value, key: &Value, value: Value) -> DalResult<()> {
+    match target {
+        Value::Map(map) => {
+            map.insert(map_key(key)?, value);
+            Ok(())
+        }
+        Value::List(items) => {
+            let index = match key {
+                Value::Int(i) if *i >= 0 => *i as usize,
+                other => {
+                    return err(format!(
+                        "Array index must be non-negative int, got: {}",
+                        other.type_name()
+                    ))
+                }
+            };
+            if index >= items.len() {
+                items.resize(index + 1, Value::Null);
+            }
+            items[index] = value;
+            Ok(())
+        }
+        other => err(format!(
+            "Cannot assign to index of type: {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// `self.field[key] = value`: only map fields take keyed assignment.
+pub fn set_field_index(
+    field: &str,
+    target: &mut Value,
+    key: &Value,
+    value: Value,
+) -> DalResult<()> {
+    match target {
+        Value::Map(map) => {
+            map.insert(map_key(key)?, value);
+            Ok(())
+        }
+        _ => err(format!(
+            "Field '{}' not found or not a map on service instance",
+            field
+        )),
+    }
+}
+
+/// `value.field` on a map.
+pub fn field(object: &Value, name: &str) -> DalResult<Value> {
+    match object {
+        Value::Map(map) => map
+            .get(name)
+            .cloned()
+            .map_or_else(|| err(format!("Field '{}' not found in map", name)), Ok),
+        other => err(format!(
+            "Cannot access field '{}' on value of type '{}'",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+/// `object?.field`: null for a null object or a map without the key.
+pub fn optional_field(object: &Value, name: &str) -> DalResult<Value> {
+    match object {
+        Value::Null => Ok(Value::Null),
+        Value::Map(map) => Ok(map.get(name).cloned().unwrap_or_default()),
+        other => field(other, name),
+    }
+}
+
+/// `variable.field = value` on a map.
+pub fn set_field(target: &mut Value, name: &str, value: Value) -> DalResult<()> {
+    match target {
+        Value::Map(map) => {
+            map.insert(name.to_string(), value);
+            Ok(())
+        }
+        other => err(format!(
+            "Cannot assign field '{}' on value of type '{}'",
+            name,
+            other.type_name()
+        )),
+    }
+}
+
+/// `start..end` as a list of ints, end exclusive.
+pub fn range(start: &Value, end: &Value) -> DalResult<Value> {
+    const MAX_RANGE_LEN: i64 = 100_000;
+    let (Value::Int(start), Value::Int(end)) = (start, end) else {
+        return err("Range bounds must be int");
+    };
+    if end.saturating_sub(*start) > MAX_RANGE_LEN {
+        return err(format!(
+            "Range too large: {}..{} exceeds limit of {} elements",
+            start, end, MAX_RANGE_LEN
+        ));
+    }
+    Ok(Value::List((*start..*end).map(Value::Int).collect()))
+}
+
+/// Items visited by `for x in value`: list elements or map keys.
+pub fn iter_items(value: Value) -> DalResult<Vec<Value>> {
+    match value {
+        Value::List(items) => Ok(items),
+        Value::Map(map) => Ok(map.into_keys().map(Value::Str).collect()),
+        other => err(format!(
+            "for-in requires list, array, or map; got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// `map(...)` literal helper used by lowered object literals.
+pub fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+/// `len(value)`: string bytes or list length.
+pub fn len(value: &Value) -> DalResult<Value> {
+    match value {
+        Value::Str(s) => Ok(Value::Int(s.len() as i64)),
+        Value::List(items) => Ok(Value::Int(items.len() as i64)),
+        other => err(format!(
+            "Type error: expected string, list, or array, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// List and map methods (`xs.push(v)`, `m.get(k)`, ...). The receiver is updated in place.
+pub fn call_method(receiver: &mut Value, name: &str, args: Vec<Value>) -> DalResult<Value> {
+    let arg = |i: usize| args.get(i).cloned().unwrap_or_default();
+    let key = |i: usize| args.get(i).and_then(Value::key_string).unwrap_or_default();
+    let type_name = receiver.type_name();
+    match (receiver, name) {
+        (Value::List(items), "length" | "size") => Ok(Value::Int(items.len() as i64)),
+        (Value::List(items), "get") => {
+            let i = arg(0).to_int()?;
+            Ok(items.get(i as usize).cloned().unwrap_or_default())
+        }
+        (Value::List(items), "set") => {
+            let i = arg(0).to_int()? as usize;
+            if i < items.len() {
+                items[i] = arg(1);
+                Ok(Value::Bool(true))
+            } else {
+                Ok(Value::Bool(false))
+            }
+        }
+        (Value::List(items), "push") => {
+            items.push(arg(0));
+            Ok(Value::Bool(true))
+        }
+        (Value::Map(map), "size") => Ok(Value::Int(map.len() as i64)),
+        (Value::Map(map), "get") => Ok(map.get(&key(0)).cloned().unwrap_or_default()),
+        (Value::Map(map), "set") => {
+            map.insert(key(0), arg(1));
+            Ok(Value::Bool(true))
+        }
+        (Value::Map(map), "contains_key") => Ok(Value::Bool(map.contains_key(&key(0)))),
+        (Value::Map(map), "keys") => Ok(Value::List(map.keys().cloned().map(Value::Str).collect())),
+        (Value::Map(map), "values") => Ok(Value::List(map.values().cloned().collect())),
+        (Value::Map(map), "remove") => Ok(map.remove(&key(0)).unwrap_or_default()),
+        _ => err(format!(
+            "Method '{}' not found on type '{}'",
+            name, type_name
+        )),
+    }
+}
+
+/// JSON text of `value`. Floats always carry a `.` or an exponent so they read back as floats;
+/// the wasm backend passes values across the host boundary in this form.
+pub fn to_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_json(value, &mut out);
+    out
+}
+
+fn write_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Int(i) => out.push_str(&i.to_string()),
+        Value::Float(x) => out.push_str(&format!("{:?}", x)),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Str(s) => write_json_string(s, out),
+        Value::List(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json(item, out);
+            }
+            out.push(']');
+        }
+        Value::Map(map) => {
+            out.push('{');
+            for (i, (key, item)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_string(key, out);
+                out.push(':');
+                write_json(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Parse JSON text produced by [`to_json`] (or any JSON document) back into a `Value`.
+pub fn from_json(text: &str) -> DalResult<Value> {
+    let mut parser = JsonParser {
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos != parser.bytes.len() {
+        return err(format!(
+            "Invalid JSON: trailing data at byte {}",
+            parser.pos
+        ));
+    }
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\n' | b'\r' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> DalResult<()> {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            err(format!(
+                "Invalid JSON: expected '{}' at byte {}",
+                byte as char, self.pos
+            ))
+        }
+    }
+
+    fn value(&mut self) -> DalResult<Value> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::Str),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Value::List(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b']')?;
+                Ok(Value::List(items))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut map = BTreeMap::new();
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Map(map));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    map.insert(key, self.value()?);
+                    self.skip_whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        _ => break,
+                    }
+                }
+                self.expect(b'}')?;
+                Ok(Value::Map(map))
+            }
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => err(format!(
+                "Invalid JSON: unexpected input at byte {}",
+                self.pos
+            )),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> DalResult<Value> {
+        if self.bytes[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            err(format!(
+                "Invalid JSON: unexpected input at byte {}",
+                self.pos
+            ))
+        }
+    }
+
+    fn number(&mut self) -> DalResult<Value> {
+        let start = self.pos;
+        while matches!(
+            self.bytes.get(self.pos),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap_or_default();
+        let parsed = if text.contains(['.', 'e', 'E']) {
+            text.parse().ok().map(Value::Float)
+        } else {
+            text.parse().ok().map(Value::Int)
+        };
+        parsed.map_or_else(|| err(format!("Invalid JSON number '{}'", text)), Ok)
+    }
+
+    fn string(&mut self) -> DalResult<String> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            // `"` and `\` are ASCII, so the run between them is whole UTF-8 characters.
+            let start = self.pos;
+            while !matches!(self.bytes.get(self.pos), Some(b'"' | b'\\') | None) {
+                self.pos += 1;
+            }
+            let run = std::str::from_utf8(&self.bytes[start..self.pos])
+                .map_err(|_| DalError("Invalid JSON: string is not UTF-8".to_string()))?;
+            out.push_str(run);
+            let Some(&stop) = self.bytes.get(self.pos) else {
+                return err("Invalid JSON: unterminated string");
+            };
+            self.pos += 1;
+            if stop == b'"' {
+                return Ok(out);
+            }
+            let escape = self.bytes.get(self.pos).copied().unwrap_or_default();
+            self.pos += 1;
+            match escape {
+                b'"' => out.push('"'),
+                b'\\' => out.push('\\'),
+                b'/' => out.push('/'),
+                b'b' => out.push('\u{8}'),
+                b'f' => out.push('\u{c}'),
+                b'n' => out.push('\n'),
+                b'r' => out.push('\r'),
+                b't' => out.push('\t'),
+                b'u' => {
+                    let mut code = self.hex4()?;
+                    if (0xD800..0xDC00).contains(&code)
+                        && self.bytes[self.pos..].starts_with(b"\\u")
+                    {
+                        self.pos += 2;
+                        let low = self.hex4()?;
+                        code =
+                            0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                    }
+                    out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                }
+                _ => return err("Invalid JSON: bad escape"),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> DalResult<u32> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| DalError("Invalid JSON: bad \\u escape".to_string()))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+}
+
+/// Conversion out of `Value` for typed service fields, parameters and return values.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> DalResult<Self>;
+}
+
+fn expected<T>(what: &str, got: &Value) -> DalResult<T> {
+    err(format!(
+        "Type error: expected {}, got {}",
+        what,
+        got.type_name()
+    ))
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> DalResult<Self> {
+        Ok(value)
+    }
+}
+
+impl FromValue for () {
+    fn from_value(_value: Value) -> DalResult<Self> {
+        Ok(())
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::Int(i) => Ok(i),
+            other => expected("int", &other),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::Float(x) => Ok(x),
+            Value::Int(i) => Ok(i as f64),
+            other => expected("float", &other),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            other => expected("bool", &other),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::Str(s) => Ok(s),
+            other => expected("string", &other),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::List(items) => items.into_iter().map(T::from_value).collect(),
+            Value::Null => Ok(Vec::new()),
+            other => expected("list", &other),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: Value) -> DalResult<Self> {
+        match value {
+            Value::Map(map) => map
+                .into_iter()
+                .map(|(k, v)| Ok((k, T::from_value(v)?)))
+                .collect(),
+            Value::Null => Ok(HashMap::new()),
+            other => expected("map", &other),
+        }
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Value::Int(i)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Float(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<HashMap<String, T>> for Value {
+    fn from(map: HashMap<String, T>) -> Self {
+        Value::Map(map.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
@@ -0,0 +1,1216 @@
+//! DAL → Rust lowering shared by the native and edge backends.
+//!
+//! Each service becomes `pub mod <name> { pub struct State { ... } }` with typed fields, a
+//! `State::new()` that runs the field initializers, a typed wrapper per method and a dynamic
+//! `State::call(name, args)`. Method bodies are lowered statement by statement onto the `dal`
+//! prelude (`native_prelude.rs`), which reproduces the interpreter's value semantics, so a
+//! compiled method returns what `dal run` returns. Top-level `fn`s called from a service are
+//! lowered into `pub mod functions`. Constructs with no lowering yet (stdlib namespace calls,
+//! closures, `match`, `try`, ...) compile to a method that returns an error naming the construct.
+//!
+//! The wasm flavor (`services_to_wasm`) adds the host ABI (`wasm_abi.rs`): `log::` and `crypto::`
+//! calls and `print` go through the `host_call` import, `@persistent` fields load from and store
+//! to host storage around every call, and each method is exported as `<service>_<method>`.
+
+use crate::lexer::tokens::{Literal, Operator};
+use crate::parser::ast::{
+    BlockStatement, Expression, FunctionStatement, Program, ServiceField, ServiceStatement,
+    Statement,
+};
+use std::collections::{BTreeSet, HashMap};
+
+/// Source of the `dal` runtime module embedded in every generated crate.
+const PRELUDE: &str = include_str!("native_prelude.rs");
+
+/// Host ABI appended to the prelude in wasm builds.
+const WASM_ABI: &str = include_str!("wasm_abi.rs");
+
+/// Revision of the wasm host ABI, reported by the `dal_manifest` export.
+pub const WASM_ABI_VERSION: i64 = 1;
+
+/// Namespaces whose calls a wasm module forwards to the host through `host_call`.
+pub const WASM_HOST_NAMESPACES: &[&str] = &["log", "crypto"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    Native,
+    Wasm,
+}
+
+/// Rust source for a construct, or a description of the construct that cannot be lowered.
+type Lowered = Result<String, String>;
+
+/// Emit `lib.rs` for `services`. `header` is the leading comment naming the backend.
+pub fn services_to_rust(program: &Program, services: &[&ServiceStatement], header: &str) -> String {
+    lower_services(program, services, header, Flavor::Native)
+}
+
+/// Emit `lib.rs` of a wasm32 cdylib: the native lowering plus the host ABI, per-method exports
+/// and a `dal_manifest` export listing the services and their methods.
+pub fn services_to_wasm(program: &Program, services: &[&ServiceStatement], header: &str) -> String {
+    lower_services(program, services, header, Flavor::Wasm)
+}
+
+fn lower_services(
+    program: &Program,
+    services: &[&ServiceStatement],
+    header: &str,
+    flavor: Flavor,
+) -> String {
+    let functions: HashMap<&str, &FunctionStatement> = program
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Function(f) => Some((f.name.as_str(), f)),
+            _ => None,
+        })
+        .collect();
+    let mut used = BTreeSet::new();
+    let mut notes = Vec::new();
+
+    let mut modules = String::new();
+    for service in services {
+        modules.push_str(&service_module(
+            service, &functions, flavor, &mut used, &mut notes,
+        ));
+    }
+
+    // Lower every top-level function reachable from a service; lowering one may reach more.
+    let mut lowered: BTreeSet<String> = BTreeSet::new();
+    let mut free_fns = Vec::new();
+    while let Some(name) = used.iter().find(|n| !lowered.contains(*n)).cloned() {
+        lowered.insert(name.clone());
+        let func = functions[name.as_str()];
+        let mut lowerer = Lowerer::new(None, &functions, flavor);
+        let body = lowerer.function_body(func, &name);
+        used.extend(lowerer.used_functions);
+        notes.extend(lowerer.notes);
+        free_fns.push(format!(
+            "    pub fn {}({}) -> DalResult<Value> {{\n{}    }}\n",
+            rust_ident(&name),
+            dynamic_params(func),
+            body
+        ));
+    }
+
+    let mut out = String::new();
+    out.push_str(header);
+    out.push_str("#![allow(unused, clippy::all)]\n\n");
+    for note in &notes {
+        out.push_str(&format!("// Not lowered: {}\n", note));
+    }
+    if !notes.is_empty() {
+        out.push('\n');
+    }
+    out.push_str("pub mod dal {\n");
+    out.push_str(PRELUDE);
+    if flavor == Flavor::Wasm {
+        out.push('\n');
+        out.push_str(WASM_ABI);
+    }
+    out.push_str("}\n\n");
+    if !free_fns.is_empty() {
+        out.push_str("pub mod functions {\n");
+        out.push_str("    use super::dal::{self, DalError, DalResult, FromValue, Value};\n\n");
+        out.push_str(&free_fns.join("\n"));
+        out.push_str("}\n\n");
+    }
+    out.push_str(&modules);
+    if flavor == Flavor::Wasm {
+        out.push_str(&wasm_manifest(services));
+    }
+    out
+}
+
+fn service_module(
+    service: &ServiceStatement,
+    functions: &HashMap<&str, &FunctionStatement>,
+    flavor: Flavor,
+    used: &mut BTreeSet<String>,
+    notes: &mut Vec<String>,
+) -> String {
+    let mod_name = service.name.to_lowercase().replace('-', "_");
+    let mut out = format!("pub mod {} {{\n", mod_name);
+    out.push_str("    use super::dal::{self, DalError, DalResult, FromValue, Value};\n");
+    out.push_str("    use std::collections::HashMap;\n\n");
+
+    out.push_str("    pub struct State {\n");
+    for field in &service.fields {
+        out.push_str(&format!(
+            "        pub {}: {},\n",
+            rust_ident(&field.name),
+            rust_type(&field.field_type)
+        ));
+    }
+    out.push_str("    }\n\n    impl State {\n");
+
+    // Field initializers: no `self` and no locals in scope.
+    let mut inits = Vec::new();
+    let mut init_error = None;
+    for field in &service.fields {
+        let mut lowerer = Lowerer::new(None, functions, flavor);
+        let value = match &field.initial_value {
+            Some(expr) => lowerer.expr(expr),
+            None => Ok(default_field_value(&field.field_type)),
+        };
+        used.extend(lowerer.used_functions);
+        match value {
+            Ok(v) => inits.push(format!(
+                "                {}: FromValue::from_value({})?,\n",
+                rust_ident(&field.name),
+                v
+            )),
+            Err(what) => {
+                init_error.get_or_insert_with(|| {
+                    format!(
+                        "{} in the initializer of {}.{}",
+                        what, service.name, field.name
+                    )
+                });
+            }
+        }
+    }
+    out.push_str("        /// A fresh instance with every field initialized as `dal run` would.\n");
+    out.push_str("        pub fn new() -> DalResult<Self> {\n");
+    match init_error {
+        Some(note) => {
+            out.push_str(&format!("            {}\n", unsupported_error(&note)));
+            notes.push(note);
+        }
+        None => out.push_str(&format!(
+            "            Ok(Self {{\n{}            }})\n",
+            inits.join("")
+        )),
+    }
+    out.push_str("        }\n\n");
+
+    out.push_str("        /// Call a method by its DAL name with dynamic arguments.\n");
+    out.push_str(
+        "        pub fn call(&mut self, method: &str, args: Vec<Value>) -> DalResult<Value> {\n",
+    );
+    out.push_str("            let arg_count = args.len();\n");
+    out.push_str("            let mut args = args.into_iter();\n");
+    out.push_str("            match method {\n");
+    for method in &service.methods {
+        let params = method.parameters.len();
+        let args: Vec<String> = (0..params)
+            .map(|_| "args.next().unwrap_or_default()".to_string())
+            .collect();
+        out.push_str(&format!(
+            "                \"{name}\" if arg_count == {params} => self.__dal_{name}({args}),\n                \"{name}\" => dal::err(format!(\"Argument count mismatch: expected {params}, got {{}}\", arg_count)),\n",
+            name = method.name,
+            params = params,
+            args = args.join(", ")
+        ));
+    }
+    out.push_str(&format!(
+        "                _ => dal::err(format!(\"Method '{{}}' not found on service '{}'\", method)),\n",
+        service.name
+    ));
+    out.push_str("            }\n        }\n");
+
+    for method in &service.methods {
+        // `new` and `call` are taken by the generated API; those methods stay reachable via `call`.
+        if method.name != "new" && method.name != "call" {
+            out.push_str(&typed_wrapper(method));
+        }
+        let mut lowerer = Lowerer::new(Some(service), functions, flavor);
+        let body = lowerer.function_body(method, &format!("{}::{}", service.name, method.name));
+        used.extend(lowerer.used_functions);
+        notes.extend(lowerer.notes);
+        let params = dynamic_params(method);
+        let params = if params.is_empty() {
+            "&mut self".to_string()
+        } else {
+            format!("&mut self, {}", params)
+        };
+        out.push_str(&format!(
+            "\n        fn __dal_{}({}) -> DalResult<Value> {{\n{}        }}\n",
+            method.name,
+            params,
+            indent(&body, 4)
+        ));
+    }
+    out.push_str("    }\n");
+    if flavor == Flavor::Wasm {
+        out.push_str(&wasm_exports(service, &mod_name));
+    }
+    out.push_str("}\n\n");
+    out
+}
+
+/// Names of the fields stored under `Service.field` (the interpreter's `@persistent` rule: the
+/// whole service or individual fields).
+fn persistent_fields(service: &ServiceStatement) -> Vec<&ServiceField> {
+    let whole = service.attributes.iter().any(|a| a.name == "@persistent");
+    service
+        .fields
+        .iter()
+        .filter(|f| whole || f.attributes.iter().any(|a| a.name == "@persistent"))
+        .collect()
+}
+
+/// Persistence hooks, the module's single instance and one export per method.
+fn wasm_exports(service: &ServiceStatement, mod_name: &str) -> String {
+    let mut out = String::from("\n    impl State {\n");
+    out.push_str("        fn load_persistent(&mut self) -> DalResult<()> {\n");
+    let persistent = persistent_fields(service);
+    for field in &persistent {
+        out.push_str(&format!(
+            "            if let Some(v) = dal::storage_get({})? {{\n                self.{} = FromValue::from_value(v)?;\n            }}\n",
+            rust_string(&format!("{}.{}", service.name, field.name)),
+            rust_ident(&field.name)
+        ));
+    }
+    out.push_str("            Ok(())\n        }\n\n");
+    out.push_str("        fn store_persistent(&self) {\n");
+    for field in &persistent {
+        out.push_str(&format!(
+            "            dal::storage_set({}, &Value::from(self.{}.clone()));\n",
+            rust_string(&format!("{}.{}", service.name, field.name)),
+            rust_ident(&field.name)
+        ));
+    }
+    out.push_str("        }\n    }\n\n");
+    out.push_str(
+        "    thread_local! {\n        static INSTANCE: std::cell::RefCell<Option<State>> = const { std::cell::RefCell::new(None) };\n    }\n\n",
+    );
+    out.push_str("    /// Run `method` on the module's instance. As under `dal run`, persistent fields load\n");
+    out.push_str("    /// when the instance is created and are stored after every call.\n");
+    out.push_str("    fn dispatch(method: &str, args: Vec<Value>) -> DalResult<Value> {\n");
+    out.push_str("        INSTANCE.with(|cell| {\n");
+    out.push_str("            let mut slot = cell.borrow_mut();\n");
+    out.push_str("            if slot.is_none() {\n                let mut state = State::new()?;\n                state.load_persistent()?;\n                *slot = Some(state);\n            }\n");
+    out.push_str("            let state = slot.as_mut().expect(\"instance\");\n");
+    out.push_str("            let out = state.call(method, args);\n");
+    out.push_str("            state.store_persistent();\n");
+    out.push_str("            out\n        })\n    }\n");
+    for method in &service.methods {
+        out.push_str(&format!(
+            "\n    #[no_mangle]\n    pub extern \"C\" fn {}_{}(args_ptr: i32, args_len: i32) -> i64 {{\n        dal::export_call(args_ptr, args_len, |args| dispatch({}, args))\n    }}\n",
+            mod_name,
+            method.name,
+            rust_string(&method.name)
+        ));
+    }
+    out
+}
+
+/// `dal_manifest()`: `{"abi": 1, "services": {"Name": {"prefix": "name_", "methods": [...]}}}`.
+fn wasm_manifest(services: &[&ServiceStatement]) -> String {
+    let entries: Vec<String> = services
+        .iter()
+        .map(|service| {
+            let methods: Vec<String> = service
+                .methods
+                .iter()
+                .map(|m| format!("{:?}", m.name))
+                .collect();
+            format!(
+                "{:?}:{{\"prefix\":\"{}_\",\"methods\":[{}]}}",
+                service.name,
+                service.name.to_lowercase().replace('-', "_"),
+                methods.join(",")
+            )
+        })
+        .collect();
+    let manifest = format!(
+        "{{\"abi\":{},\"services\":{{{}}}}}",
+        WASM_ABI_VERSION,
+        entries.join(",")
+    );
+    format!(
+        "#[no_mangle]\npub extern \"C\" fn dal_manifest() -> i64 {{\n    dal::pack(String::from({}))\n}}\n",
+        rust_string(&manifest)
+    )
+}
+
+/// `pub fn name(&mut self, typed params) -> DalResult<Ret>` over the dynamic method.
+fn typed_wrapper(method: &FunctionStatement) -> String {
+    let mut params = vec!["&mut self".to_string()];
+    let mut args = Vec::new();
+    for p in &method.parameters {
+        let ty = p
+            .param_type
+            .as_deref()
+            .map(rust_type)
+            .unwrap_or_else(|| "Value".to_string());
+        params.push(format!("{}: {}", rust_ident(&p.name), ty));
+        args.push(format!("Value::from({})", rust_ident(&p.name)));
+    }
+    let ret = method
+        .return_type
+        .as_deref()
+        .map(rust_type)
+        .unwrap_or_else(|| "Value".to_string());
+    format!(
+        "\n        pub fn {}({}) -> DalResult<{}> {{\n            let out = self.__dal_{}({})?;\n            FromValue::from_value(out)\n        }}\n",
+        rust_ident(&method.name),
+        params.join(", "),
+        ret,
+        method.name,
+        args.join(", ")
+    )
+}
+
+fn dynamic_params(func: &FunctionStatement) -> String {
+    func.parameters
+        .iter()
+        .map(|p| format!("mut {}: Value", local(&p.name)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn indent(text: &str, by: usize) -> String {
+    let pad = " ".repeat(by);
+    text.lines().map(|l| format!("{}{}\n", pad, l)).collect()
+}
+
+/// Rust type of a typed service field, parameter or return value. Anything without a direct
+/// Rust counterpart (`any`, `address`, untyped, ...) stays a dynamic `Value`.
+pub(crate) fn rust_type(dal_type: &str) -> String {
+    let t = dal_type.trim();
+    match t {
+        "int" | "i64" => return "i64".to_string(),
+        "float" | "f64" => return "f64".to_string(),
+        "string" => return "String".to_string(),
+        "bool" => return "bool".to_string(),
+        "void" | "()" => return "()".to_string(),
+        _ => {}
+    }
+    if let Some(inner) = t
+        .strip_prefix("list<")
+        .or_else(|| t.strip_prefix("vector<"))
+        .and_then(|s| s.strip_suffix('>'))
+    {
+        return format!("Vec<{}>", rust_type(inner));
+    }
+    if let Some(inner) = t.strip_prefix("map<").and_then(|s| s.strip_suffix('>')) {
+        let value = inner.split_once(',').map(|(_, v)| v).unwrap_or("any");
+        return format!("HashMap<String, {}>", rust_type(value));
+    }
+    "Value".to_string()
+}
+
+/// Value of a field declared without an initializer (the interpreter's `get_default_value`).
+fn default_field_value(dal_type: &str) -> String {
+    match dal_type.trim() {
+        "int" => "Value::Int(0)".to_string(),
+        "string" => "Value::Str(String::new())".to_string(),
+        "bool" => "Value::Bool(false)".to_string(),
+        "float" => "Value::Float(0.0)".to_string(),
+        _ => "Value::Null".to_string(),
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+    "move", "mut", "priv", "pub", "ref", "return", "static", "struct", "trait", "true", "try",
+    "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// A DAL name usable as a Rust item or field name.
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" | "_" => format!("{}_", name),
+        n if RUST_KEYWORDS.contains(&n) => format!("r#{}", n),
+        n => n.to_string(),
+    }
+}
+
+/// Rust name of a DAL local; the prefix keeps locals clear of keywords and generated names.
+fn local(name: &str) -> String {
+    format!("l_{}", name)
+}
+
+fn rust_string(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+fn unsupported_error(note: &str) -> String {
+    format!(
+        "dal::err({})",
+        rust_string(&format!(
+            "not lowered by the Rust backend: {}; run this method with `dal run`",
+            note
+        ))
+    )
+}
+
+struct Lowerer<'a> {
+    service: Option<&'a ServiceStatement>,
+    functions: &'a HashMap<&'a str, &'a FunctionStatement>,
+    flavor: Flavor,
+    locals: BTreeSet<String>,
+    /// Result variables of the enclosing loops, innermost last.
+    loops: Vec<String>,
+    next_temp: usize,
+    used_functions: BTreeSet<String>,
+    notes: Vec<String>,
+}
+
+impl<'a> Lowerer<'a> {
+    fn new(
+        service: Option<&'a ServiceStatement>,
+        functions: &'a HashMap<&'a str, &'a FunctionStatement>,
+        flavor: Flavor,
+    ) -> Self {
+        Self {
+            service,
+            functions,
+            flavor,
+            locals: BTreeSet::new(),
+            loops: Vec::new(),
+            next_temp: 0,
+            used_functions: BTreeSet::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    fn temp(&mut self, prefix: &str) -> String {
+        self.next_temp += 1;
+        format!("__{}{}", prefix, self.next_temp)
+    }
+
+    /// Body of a lowered function: hoisted locals, then the block's value. DAL scopes are flat per
+    /// function (a `let` in a nested block assigns the function-wide variable), so every local
+    /// is declared once up front.
+    fn function_body(&mut self, func: &FunctionStatement, qualified_name: &str) -> String {
+        let params: BTreeSet<String> = func.parameters.iter().map(|p| p.name.clone()).collect();
+        let mut names = BTreeSet::new();
+        collect_block_locals(&func.body, &mut names);
+        self.locals = names.union(&params).cloned().collect();
+        match self.block(&func.body) {
+            Ok(body) => {
+                let mut out = String::new();
+                for name in names.difference(&params) {
+                    out.push_str(&format!("        let mut {} = Value::Null;\n", local(name)));
+                }
+                out.push_str(&format!("        Ok({})\n", body));
+                out
+            }
+            Err(what) => {
+                let note = format!("{} in {}", what, qualified_name);
+                let out = format!("        {}\n", unsupported_error(&note));
+                self.notes.push(note);
+                out
+            }
+        }
+    }
+
+    /// A block as a Rust expression whose value is that of its last statement (null if empty).
+    fn block(&mut self, block: &BlockStatement) -> Lowered {
+        let Some((last, rest)) = block.statements.split_last() else {
+            return Ok("Value::Null".to_string());
+        };
+        let mut out = String::from("{ ");
+        for stmt in rest {
+            out.push_str(&self.discarded(stmt)?);
+            out.push(' ');
+        }
+        out.push_str(&self.statement(last)?);
+        out.push_str(" }");
+        Ok(out)
+    }
+
+    /// A statement whose value is not used.
+    fn discarded(&mut self, stmt: &Statement) -> Lowered {
+        match stmt {
+            Statement::Let(let_stmt) if let_stmt.pattern.is_none() => Ok(format!(
+                "{} = {};",
+                local(&let_stmt.name),
+                self.expr(&let_stmt.value)?
+            )),
+            Statement::Expression(Expression::Literal(_)) => Ok(String::new()),
+            _ => Ok(format!("let _ = {};", self.statement(stmt)?)),
+        }
+    }
+
+    /// A statement as a Rust expression of type `Value` (the statement's value).
+    fn statement(&mut self, stmt: &Statement) -> Lowered {
+        match stmt {
+            Statement::Expression(expr) => self.expr(expr),
+            Statement::Let(let_stmt) => {
+                if let_stmt.pattern.is_some() {
+                    return Err("a destructuring `let`".to_string());
+                }
+                let name = local(&let_stmt.name);
+                Ok(format!(
+                    "{{ {} = {}; {}.clone() }}",
+                    name,
+                    self.expr(&let_stmt.value)?,
+                    name
+                ))
+            }
+            Statement::Return(ret) => {
+                let value = match &ret.value {
+                    Some(expr) => self.expr(expr)?,
+                    None => "Value::Null".to_string(),
+                };
+                Ok(format!("return Ok({})", value))
+            }
+            Statement::Block(block) => self.block(block),
+            Statement::If(if_stmt) => {
+                let condition = self.expr(&if_stmt.condition)?;
+                let consequence = self.block(&if_stmt.consequence)?;
+                let alternative = match &if_stmt.alternative {
+                    Some(block) => self.block(block)?,
+                    None => "Value::Null".to_string(),
+                };
+                Ok(format!(
+                    "if ({}).truthy() {{ {} }} else {{ {} }}",
+                    condition, consequence, alternative
+                ))
+            }
+            Statement::While(while_stmt) => {
+                let condition = self.expr(&while_stmt.condition)?;
+                let result = self.temp("loop");
+                let body = self.loop_body(&while_stmt.body, &result)?;
+                Ok(format!(
+                    "{{ let mut {r} = Value::Null; while ({c}).truthy() {{ {b} }} {r} }}",
+                    r = result,
+                    c = condition,
+                    b = body
+                ))
+            }
+            Statement::ForIn(for_stmt) => {
+                let iterable = self.expr(&for_stmt.iterable)?;
+                let result = self.temp("loop");
+                let item = self.temp("item");
+                let body = self.loop_body(&for_stmt.body, &result)?;
+                Ok(format!(
+                    "{{ let mut {r} = Value::Null; for {i} in dal::iter_items({it})? {{ {v} = {i}; {b} }} {r} }}",
+                    r = result,
+                    i = item,
+                    it = iterable,
+                    v = local(&for_stmt.variable),
+                    b = body
+                ))
+            }
+            Statement::Loop(loop_stmt) => {
+                let result = self.temp("loop");
+                let body = self.loop_body(&loop_stmt.body, &result)?;
+                Ok(format!(
+                    "{{ let mut {r} = Value::Null; loop {{ {b} }} {r} }}",
+                    r = result,
+                    b = body
+                ))
+            }
+            Statement::Break(break_stmt) => {
+                let Some(result) = self.loops.last().cloned() else {
+                    return Err("`break` outside a loop".to_string());
+                };
+                let value = match &break_stmt.value {
+                    Some(expr) => self.expr(expr)?,
+                    None => "Value::Null".to_string(),
+                };
+                Ok(format!("{{ {} = {}; break }}", result, value))
+            }
+            Statement::Continue(_) => {
+                if self.loops.is_empty() {
+                    return Err("`continue` outside a loop".to_string());
+                }
+                Ok("continue".to_string())
+            }
+            other => Err(format!("a `{}` statement", statement_kind(other))),
+        }
+    }
+
+    /// Loop body: each statement's value becomes the loop's running result.
+    fn loop_body(&mut self, body: &BlockStatement, result: &str) -> Lowered {
+        self.loops.push(result.to_string());
+        let mut out = String::new();
+        for stmt in &body.statements {
+            let lowered = self.statement(stmt);
+            let lowered = match lowered {
+                Ok(l) => l,
+                Err(e) => {
+                    self.loops.pop();
+                    return Err(e);
+                }
+            };
+            match stmt {
+                Statement::Break(_) | Statement::Continue(_) | Statement::Return(_) => {
+                    out.push_str(&format!("{}; ", lowered))
+                }
+                _ => out.push_str(&format!("{} = {}; ", result, lowered)),
+            }
+        }
+        self.loops.pop();
+        Ok(out)
+    }
+
+    fn expr(&mut self, expr: &Expression) -> Lowered {
+        match expr {
+            Expression::Literal(literal) => literal_value(literal),
+            Expression::Identifier(name) => {
+                if self.locals.contains(name) {
+                    Ok(format!("{}.clone()", local(name)))
+                } else if name == "self" {
+                    Err("`self` as a value".to_string())
+                } else {
+                    Err(format!("the non-local name `{}`", name))
+                }
+            }
+            Expression::BinaryOp(left, op, right) => self.binary(left, op, right),
+            Expression::UnaryOp(op, operand) => {
+                let operand = self.expr(operand)?;
+                match op {
+                    Operator::Minus => Ok(format!("dal::neg(&({}))?", operand)),
+                    Operator::Not | Operator::Bang => Ok(format!("dal::not(&({}))", operand)),
+                    other => Err(format!("the unary operator {:?}", other)),
+                }
+            }
+            Expression::Assignment(name, value) => {
+                let value = self.expr(value)?;
+                Ok(format!(
+                    "{{ {n} = {v}; {n}.clone() }}",
+                    n = local(name),
+                    v = value
+                ))
+            }
+            Expression::FieldAccess(object, name) => match self.self_field(object, name) {
+                Some(field) => Ok(field_read(field)),
+                None if is_self(object) => Err(format!("the unknown field `self.{}`", name)),
+                None => Ok(format!(
+                    "dal::field(&({}), {})?",
+                    self.expr(object)?,
+                    rust_string(name)
+                )),
+            },
+            Expression::FieldAssignment(object, name, value) => {
+                let value = self.expr(value)?;
+                let temp = self.temp("v");
+                if let Some(field) = self.self_field(object, name) {
+                    return Ok(format!(
+                        "{{ let {t} = {v}; self.{f} = FromValue::from_value({t}.clone())?; {t} }}",
+                        t = temp,
+                        v = value,
+                        f = rust_ident(&field.name)
+                    ));
+                }
+                match object.as_ref() {
+                    Expression::Identifier(var) if self.locals.contains(var) => Ok(format!(
+                        "{{ let {t} = {v}; dal::set_field(&mut {l}, {n}, {t}.clone())?; {t} }}",
+                        t = temp,
+                        v = value,
+                        l = local(var),
+                        n = rust_string(name)
+                    )),
+                    _ => Err(format!("assignment to the field `{}`", name)),
+                }
+            }
+            Expression::FunctionCall(call) => self.call(&call.name, &call.arguments),
+            Expression::Await(inner) => self.expr(inner),
+            Expression::Throw(value) => Ok(format!(
+                "return Err(DalError(format!(\"{{}}\", {})))",
+                self.expr(value)?
+            )),
+            Expression::ObjectLiteral(entries) => {
+                let mut keys: Vec<&String> = entries.keys().collect();
+                keys.sort();
+                let mut parts = Vec::new();
+                for key in keys {
+                    parts.push(format!(
+                        "({}, {})",
+                        rust_string(key),
+                        self.expr(&entries[key])?
+                    ));
+                }
+                Ok(format!("dal::map(vec![{}])", parts.join(", ")))
+            }
+            // `"" + part + ...`, so each part displays as in a concatenation.
+            Expression::Interpolation(parts) => {
+                let mut out = "Value::Str(String::new())".to_string();
+                for part in parts {
+                    out = format!("dal::add(&({}), &({}))?", out, self.expr(part)?);
+                }
+                Ok(out)
+            }
+            Expression::ArrayLiteral(items) => {
+                let items = items
+                    .iter()
+                    .map(|e| self.expr(e))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!("Value::List(vec![{}])", items.join(", ")))
+            }
+            Expression::IndexAccess(container, key) => Ok(format!(
+                "dal::index(&({}), &({}))?",
+                self.expr(container)?,
+                self.expr(key)?
+            )),
+            Expression::Range(start, end) => Ok(format!(
+                "dal::range(&({}), &({}))?",
+                self.expr(start)?,
+                self.expr(end)?
+            )),
+            Expression::MethodCall {
+                receiver,
+                method_name,
+                arguments,
+            } => self.method_call(receiver, method_name, arguments),
+            Expression::OptionalFieldAccess(object, name) => Ok(format!(
+                "dal::optional_field(&({}), {})?",
+                self.expr(object)?,
+                rust_string(name)
+            )),
+            Expression::NullCoalesce(left, right) => {
+                let l = self.expr(left)?;
+                let r = self.expr(right)?;
+                let temp = self.temp("l");
+                Ok(format!(
+                    "{{ let {t} = {l}; if {t} == Value::Null {{ {r} }} else {{ {t} }} }}",
+                    t = temp,
+                    l = l,
+                    r = r
+                ))
+            }
+            Expression::Pipeline(value, stage) => match stage.as_ref() {
+                Expression::FunctionCall(call) => {
+                    let mut arguments = vec![value.as_ref().clone()];
+                    arguments.extend(call.arguments.iter().cloned());
+                    self.call(&call.name, &arguments)
+                }
+                Expression::Identifier(name) => self.call(name, std::slice::from_ref(value)),
+                _ => Err("a pipeline into a function value".to_string()),
+            },
+            Expression::Spawn(_) => Err("`spawn`".to_string()),
+            Expression::Propagate(_) => Err("the `?` operator".to_string()),
+            Expression::OptionalMethodCall { .. } => Err("an optional method call".to_string()),
+            Expression::ArrowFunction { .. } => Err("a closure".to_string()),
+            Expression::Call { .. } => Err("a call of a function value".to_string()),
+            Expression::StructLiteral { name, .. } => Err(format!("the struct literal `{}`", name)),
+        }
+    }
+
+    fn binary(&mut self, left: &Expression, op: &Operator, right: &Expression) -> Lowered {
+        let l = self.expr(left)?;
+        let r = self.expr(right)?;
+        let temp = self.temp("l");
+        let out = match op {
+            // `&&` / `||` return the left operand when it decides the result, else a bool.
+            Operator::And => format!(
+                "{{ let {t} = {l}; if !{t}.truthy() {{ {t} }} else {{ Value::Bool(({r}).truthy()) }} }}",
+                t = temp,
+                l = l,
+                r = r
+            ),
+            Operator::Or => format!(
+                "{{ let {t} = {l}; if {t}.truthy() {{ {t} }} else {{ Value::Bool(({r}).truthy()) }} }}",
+                t = temp,
+                l = l,
+                r = r
+            ),
+            Operator::Plus => format!("dal::add(&({}), &({}))?", l, r),
+            Operator::Minus => format!("dal::sub(&({}), &({}))?", l, r),
+            Operator::Star => format!("dal::mul(&({}), &({}))?", l, r),
+            Operator::Slash => format!("dal::div(&({}), &({}))?", l, r),
+            Operator::Percent => format!("dal::rem(&({}), &({}))?", l, r),
+            Operator::Equal => format!("dal::eq(&({}), &({}))", l, r),
+            Operator::NotEqual => format!("dal::ne(&({}), &({}))", l, r),
+            Operator::Less => format!("dal::compare(\"<\", &({}), &({}))?", l, r),
+            Operator::LessEqual => format!("dal::compare(\"<=\", &({}), &({}))?", l, r),
+            Operator::Greater => format!("dal::compare(\">\", &({}), &({}))?", l, r),
+            Operator::GreaterEqual => format!("dal::compare(\">=\", &({}), &({}))?", l, r),
+            other => return Err(format!("the operator {:?}", other)),
+        };
+        Ok(out)
+    }
+
+    /// Evaluate `arguments` into temporaries, left to right, before a call borrows anything.
+    fn bind_args(&mut self, arguments: &[Expression]) -> Result<(String, Vec<String>), String> {
+        let mut bindings = String::new();
+        let mut names = Vec::new();
+        for arg in arguments {
+            let value = self.expr(arg)?;
+            let name = self.temp("a");
+            bindings.push_str(&format!("let {} = {}; ", name, value));
+            names.push(name);
+        }
+        Ok((bindings, names))
+    }
+
+    fn call(&mut self, name: &str, arguments: &[Expression]) -> Lowered {
+        if name == "__index_assign__" {
+            return self.index_assign(arguments);
+        }
+        if let Some(func) = self.functions.get(name) {
+            if func.parameters.len() != arguments.len() {
+                return Err(format!(
+                    "a call of `{}` with {} arguments (expects {})",
+                    name,
+                    arguments.len(),
+                    func.parameters.len()
+                ));
+            }
+            self.used_functions.insert(name.to_string());
+            let (bindings, args) = self.bind_args(arguments)?;
+            return Ok(format!(
+                "{{ {}super::functions::{}({})? }}",
+                bindings,
+                rust_ident(name),
+                args.join(", ")
+            ));
+        }
+        if self.flavor == Flavor::Wasm {
+            let host_call = name == "print"
+                || name
+                    .split_once("::")
+                    .is_some_and(|(ns, _)| WASM_HOST_NAMESPACES.contains(&ns));
+            if host_call {
+                let (bindings, args) = self.bind_args(arguments)?;
+                return Ok(format!(
+                    "{{ {}dal::host_call({}, vec![{}])? }}",
+                    bindings,
+                    rust_string(name),
+                    args.join(", ")
+                ));
+            }
+        }
+        match (name, arguments) {
+            ("print", [arg]) => Ok(format!(
+                "{{ println!(\"{{}}\", {}); Value::Null }}",
+                self.expr(arg)?
+            )),
+            ("len", [arg]) => Ok(format!("dal::len(&({}))?", self.expr(arg)?)),
+            _ if name.contains("::") => Err(format!("the namespace call `{}`", name)),
+            _ => Err(format!("the call `{}`", name)),
+        }
+    }
+
+    /// `var[key] = value` (4 arguments) or `self.field[key] = value` (5 arguments).
+    fn index_assign(&mut self, arguments: &[Expression]) -> Lowered {
+        let (container, key, value) = match arguments {
+            [c, k, v, ..] => (c, k, v),
+            _ => return Err("an index assignment".to_string()),
+        };
+        let key = self.expr(key)?;
+        let value = self.expr(value)?;
+        let (k, v) = (self.temp("k"), self.temp("v"));
+        match container {
+            Expression::Identifier(var) if arguments.len() == 4 && self.locals.contains(var) => {
+                Ok(format!(
+                    "{{ let {k} = {key}; let {v} = {value}; dal::set_index(&mut {l}, &{k}, {v}.clone())?; {v} }}",
+                    k = k,
+                    v = v,
+                    key = key,
+                    value = value,
+                    l = local(var)
+                ))
+            }
+            Expression::FieldAccess(object, name) if arguments.len() == 5 => {
+                let Some(field) = self.self_field(object, name) else {
+                    return Err(format!("an index assignment into the field `{}`", name));
+                };
+                let r = self.temp("r");
+                let res = self.temp("res");
+                Ok(format!(
+                    "{{ let {k} = {key}; let {v} = {value}; let mut {r} = Value::from(std::mem::take(&mut self.{f})); let {res} = dal::set_field_index({n}, &mut {r}, &{k}, {v}.clone()); self.{f} = FromValue::from_value({r})?; {res}?; {v} }}",
+                    k = k,
+                    v = v,
+                    key = key,
+                    value = value,
+                    r = r,
+                    res = res,
+                    f = rust_ident(&field.name),
+                    n = rust_string(&field.name)
+                ))
+            }
+            _ => Err("an index assignment to a temporary".to_string()),
+        }
+    }
+
+    fn method_call(
+        &mut self,
+        receiver: &Expression,
+        method_name: &str,
+        arguments: &[Expression],
+    ) -> Lowered {
+        if is_self(receiver) {
+            let Some(method) = self
+                .service
+                .and_then(|s| s.methods.iter().find(|m| m.name == method_name))
+            else {
+                return Err(format!("the call `self.{}`", method_name));
+            };
+            if method.parameters.len() != arguments.len() {
+                return Err(format!(
+                    "a call of `self.{}` with {} arguments (expects {})",
+                    method_name,
+                    arguments.len(),
+                    method.parameters.len()
+                ));
+            }
+            let (bindings, args) = self.bind_args(arguments)?;
+            return Ok(format!(
+                "{{ {}self.__dal_{}({})? }}",
+                bindings,
+                method_name,
+                args.join(", ")
+            ));
+        }
+        let (bindings, args) = self.bind_args(arguments)?;
+        let args = format!("vec![{}]", args.join(", "));
+        let method = rust_string(method_name);
+        // List and map methods update their receiver, which is written back to where it lives.
+        if let Expression::FieldAccess(object, name) = receiver {
+            if let Some(field) = self.self_field(object, name) {
+                let r = self.temp("r");
+                let out = self.temp("out");
+                return Ok(format!(
+                    "{{ {b}let mut {r} = Value::from(std::mem::take(&mut self.{f})); let {o} = dal::call_method(&mut {r}, {m}, {a}); self.{f} = FromValue::from_value({r})?; {o}? }}",
+                    b = bindings,
+                    r = r,
+                    f = rust_ident(&field.name),
+                    o = out,
+                    m = method,
+                    a = args
+                ));
+            }
+        }
+        if let Expression::Identifier(var) = receiver {
+            if self.locals.contains(var) {
+                return Ok(format!(
+                    "{{ {}dal::call_method(&mut {}, {}, {})? }}",
+                    bindings,
+                    local(var),
+                    method,
+                    args
+                ));
+            }
+        }
+        let receiver = self.expr(receiver)?;
+        let r = self.temp("r");
+        Ok(format!(
+            "{{ {b}let mut {r} = {recv}; dal::call_method(&mut {r}, {m}, {a})? }}",
+            b = bindings,
+            r = r,
+            recv = receiver,
+            m = method,
+            a = args
+        ))
+    }
+
+    /// The service field named by `self.<name>`, if `object` is `self`.
+    fn self_field(&self, object: &Expression, name: &str) -> Option<&'a ServiceField> {
+        if !is_self(object) {
+            return None;
+        }
+        self.service?.fields.iter().find(|f| f.name == name)
+    }
+}
+
+fn is_self(expr: &Expression) -> bool {
+    matches!(expr, Expression::Identifier(name) if name == "self")
+}
+
+fn field_read(field: &ServiceField) -> String {
+    format!("Value::from(self.{}.clone())", rust_ident(&field.name))
+}
+
+fn literal_value(literal: &Literal) -> Lowered {
+    Ok(match literal {
+        Literal::Int(i) => format!("Value::Int({}i64)", i),
+        Literal::Float(x) => format!("Value::Float({:?}f64)", x),
+        Literal::String(s) => format!("Value::Str(String::from({}))", rust_string(s)),
+        Literal::Bool(b) => format!("Value::Bool({})", b),
+        Literal::Null => "Value::Null".to_string(),
+        Literal::U256(_) => return Err("a `u256` literal".to_string()),
+        Literal::I256(_) => return Err("an `i256` literal".to_string()),
+        Literal::Decimal(_) => return Err("a `decimal` literal".to_string()),
+        Literal::Bytes(_) => return Err("a `bytes` literal".to_string()),
+    })
+}
+
+fn statement_kind(stmt: &Statement) -> &'static str {
+    match stmt {
+        Statement::Function(_) => "fn",
+        Statement::Service(_) => "service",
+        Statement::Spawn(_) => "spawn",
+        Statement::Agent(_) => "agent",
+        Statement::Message(_) => "msg",
+        Statement::Event(_) => "event",
+        Statement::Try(_) => "try",
+        Statement::Match(_) => "match",
+        Statement::Struct(_) => "struct",
+        Statement::Enum(_) => "enum",
+        Statement::Trait(_) => "trait",
+        Statement::Impl(_) => "impl",
+        Statement::Import(_) => "import",
+        _ => "statement",
+    }
+}
+
+/// Names bound anywhere in `block` by `let`, assignment or `for`.
+fn collect_block_locals(block: &BlockStatement, names: &mut BTreeSet<String>) {
+    for stmt in &block.statements {
+        collect_statement_locals(stmt, names);
+    }
+}
+
+fn collect_statement_locals(stmt: &Statement, names: &mut BTreeSet<String>) {
+    match stmt {
+        Statement::Expression(expr) => collect_expr_locals(expr, names),
+        Statement::Let(let_stmt) => {
+            if let_stmt.pattern.is_none() {
+                names.insert(let_stmt.name.clone());
+            }
+            collect_expr_locals(&let_stmt.value, names);
+        }
+        Statement::Return(ret) => {
+            if let Some(value) = &ret.value {
+                collect_expr_locals(value, names);
+            }
+        }
+        Statement::Block(block) => collect_block_locals(block, names),
+        Statement::If(if_stmt) => {
+            collect_expr_locals(&if_stmt.condition, names);
+            collect_block_locals(&if_stmt.consequence, names);
+            if let Some(alt) = &if_stmt.alternative {
+                collect_block_locals(alt, names);
+            }
+        }
+        Statement::While(while_stmt) => {
+            collect_expr_locals(&while_stmt.condition, names);
+            collect_block_locals(&while_stmt.body, names);
+        }
+        Statement::ForIn(for_stmt) => {
+            names.insert(for_stmt.variable.clone());
+            collect_expr_locals(&for_stmt.iterable, names);
+            collect_block_locals(&for_stmt.body, names);
+        }
+        Statement::Loop(loop_stmt) => collect_block_locals(&loop_stmt.body, names),
+        Statement::Break(break_stmt) => {
+            if let Some(value) = &break_stmt.value {
+                collect_expr_locals(value, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_expr_locals(expr: &Expression, names: &mut BTreeSet<String>) {
+    match expr {
+        Expression::Assignment(name, value) => {
+            names.insert(name.clone());
+            collect_expr_locals(value, names);
+        }
+        Expression::BinaryOp(l, _, r)
+        | Expression::IndexAccess(l, r)
+        | Expression::Range(l, r)
+        | Expression::NullCoalesce(l, r)
+        | Expression::Pipeline(l, r)
+        | Expression::FieldAssignment(l, _, r) => {
+            collect_expr_locals(l, names);
+            collect_expr_locals(r, names);
+        }
+        Expression::UnaryOp(_, e)
+        | Expression::FieldAccess(e, _)
+        | Expression::OptionalFieldAccess(e, _)
+        | Expression::Await(e)
+        | Expression::Throw(e)
+        | Expression::Propagate(e) => collect_expr_locals(e, names),
+        Expression::FunctionCall(call) => {
+            for arg in &call.arguments {
+                collect_expr_locals(arg, names);
+            }
+        }
+        Expression::MethodCall {
+            receiver,
+            arguments,
+            ..
+        } => {
+            collect_expr_locals(receiver, names);
+            for arg in arguments {
+                collect_expr_locals(arg, names);
+            }
+        }
+        Expression::ArrayLiteral(items) | Expression::Interpolation(items) => {
+            for item in items {
+                collect_expr_locals(item, names);
+            }
+        }
+        Expression::ObjectLiteral(entries) => {
+            for value in entries.values() {
+                collect_expr_locals(value, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::native_prelude::{self as dal, Value};
+    use std::collections::HashMap as StdHashMap;
+
+    #[test]
+    fn test_rust_type_mapping() {
+        assert_eq!(rust_type("int"), "i64");
+        assert_eq!(rust_type("float"), "f64");
+        assert_eq!(rust_type("list<string>"), "Vec<String>");
+        assert_eq!(rust_type("map<string, any>"), "HashMap<String, Value>");
+        assert_eq!(rust_type("address"), "Value");
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("self"), "self_");
+    }
+
+    #[test]
+    fn test_prelude_matches_interpreter_arithmetic() {
+        let s = |v: &str| Value::Str(v.to_string());
+        assert_eq!(dal::add(&s("a"), &Value::Float(1.5)).unwrap(), s("a1.5"));
+        assert_eq!(
+            dal::add(&s("x"), &Value::List(vec![Value::Int(1), s("b")])).unwrap(),
+            s("x[1, \"b\"]")
+        );
+        assert_eq!(
+            dal::div(&Value::Int(7), &Value::Int(2)).unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            dal::rem(&Value::Int(-7), &Value::Int(3)).unwrap(),
+            Value::Int(-1)
+        );
+        assert!(dal::add(&Value::Int(i64::MAX), &Value::Int(1)).is_err());
+        assert!(dal::div(&Value::Int(1), &Value::Int(0)).is_err());
+        assert!(dal::rem(&Value::Float(1.0), &Value::Int(1)).is_err());
+        assert!(dal::neg(&Value::Float(2.5)).is_err());
+        assert_eq!(
+            dal::eq(&Value::Int(1), &Value::Float(1.0)),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_prelude_collections() {
+        let mut list = Value::List(vec![]);
+        dal::set_index(&mut list, &Value::Int(2), Value::Int(9)).unwrap();
+        assert_eq!(list.to_string(), "[null, null, 9]");
+        assert_eq!(dal::index(&list, &Value::Int(-1)).unwrap(), Value::Null);
+        let mut map = dal::map(vec![("b", Value::Int(2)), ("a", Value::Int(1))]);
+        dal::call_method(&mut map, "set", vec![Value::Int(3), Value::Bool(true)]).unwrap();
+        assert_eq!(map.to_string(), "{\"3\": true, \"a\": 1, \"b\": 2}");
+        let typed: StdHashMap<String, Value> = dal::FromValue::from_value(map).unwrap();
+        assert_eq!(typed.len(), 3);
+    }
+
+    #[test]
+    fn test_prelude_json_round_trip() {
+        let value = dal::map(vec![
+            ("f", Value::Float(2.0)),
+            ("i", Value::Int(-3)),
+            ("s", Value::Str("q\"\n\u{1}é".to_string())),
+            ("l", Value::List(vec![Value::Null, Value::Bool(true)])),
+        ]);
+        let json = dal::to_json(&value);
+        assert!(json.contains("\"f\":2.0"), "{}", json);
+        assert_eq!(dal::from_json(&json).unwrap(), value);
+        assert_eq!(
+            dal::from_json(" [1e2, \"\\ud83d\\ude00\"] ").unwrap(),
+            Value::List(vec![Value::Float(100.0), Value::Str("😀".to_string())])
+        );
+        assert!(dal::from_json("[1,").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+// Host ABI appended to the `dal` prelude by the wasm backend (wasm32 only).
+//
+// Values cross the boundary as JSON (`to_json` / `from_json`); a result is an envelope
+// `{"ok": value}` or `{"err": "message"}`. Memory handed over in either direction is a boxed
+// byte slice: the host fills buffers from `dal_alloc` and the module frees them after reading;
+// results come back packed as `(ptr << 32) | len` and the host releases them with `dal_free`.
+// Imports live in module "dal"; results of `host_call` and `storage_get` are fetched with a
+// second call, `host_result(ptr)`, into a buffer of the announced length.
+
+mod ffi {
+    #[link(wasm_import_module = "dal")]
+    extern "C" {
+        pub fn host_call(name_ptr: i32, name_len: i32, args_ptr: i32, args_len: i32) -> i32;
+        pub fn host_result(ptr: i32);
+        pub fn storage_get(key_ptr: i32, key_len: i32) -> i32;
+        pub fn storage_set(key_ptr: i32, key_len: i32, value_ptr: i32, value_len: i32);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn dal_alloc(len: i32) -> i32 {
+    Box::into_raw(vec![0u8; len as usize].into_boxed_slice()) as *mut u8 as usize as i32
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn dal_free(ptr: i32, len: i32) {
+    drop(take_buffer(ptr, len));
+}
+
+unsafe fn take_buffer(ptr: i32, len: i32) -> Box<[u8]> {
+    Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+        ptr as usize as *mut u8,
+        len as usize,
+    ))
+}
+
+/// Hand `text` to the host as a packed `(ptr << 32) | len`.
+pub fn pack(text: String) -> i64 {
+    let len = text.len() as i64;
+    let ptr = Box::into_raw(text.into_bytes().into_boxed_slice()) as *mut u8 as usize as i64;
+    (ptr << 32) | len
+}
+
+fn envelope(result: DalResult<Value>) -> String {
+    match result {
+        Ok(value) => format!("{{\"ok\":{}}}", to_json(&value)),
+        Err(e) => {
+            let mut out = String::from("{\"err\":");
+            write_json_string(&e.0, &mut out);
+            out.push('}');
+            out
+        }
+    }
+}
+
+fn open_envelope(text: &str) -> DalResult<Value> {
+    match from_json(text)? {
+        Value::Map(mut map) => match (map.remove("ok"), map.remove("err")) {
+            (Some(value), _) => Ok(value),
+            (None, Some(Value::Str(message))) => Err(DalError(message)),
+            _ => err("Malformed host result"),
+        },
+        _ => err("Malformed host result"),
+    }
+}
+
+/// Body of an exported method: decode the JSON argument array the host wrote at `args_ptr`,
+/// run `f` and return the packed result envelope.
+pub fn export_call(
+    args_ptr: i32,
+    args_len: i32,
+    f: impl FnOnce(Vec<Value>) -> DalResult<Value>,
+) -> i64 {
+    let bytes = unsafe { take_buffer(args_ptr, args_len) };
+    let result = std::str::from_utf8(&bytes)
+        .map_err(|_| DalError("Arguments are not valid UTF-8".to_string()))
+        .and_then(from_json)
+        .and_then(|args| match args {
+            Value::List(items) => f(items),
+            other => err(format!(
+                "Expected an argument list, got {}",
+                other.type_name()
+            )),
+        });
+    pack(envelope(result))
+}
+
+fn take_host_result(len: i32) -> DalResult<String> {
+    let mut buffer = vec![0u8; len.max(0) as usize];
+    unsafe { ffi::host_result(buffer.as_mut_ptr() as usize as i32) };
+    String::from_utf8(buffer).map_err(|_| DalError("Host result is not valid UTF-8".to_string()))
+}
+
+/// Call a host function (`log::info`, `crypto::hash`, `print`, ...) with DAL arguments.
+pub fn host_call(name: &str, args: Vec<Value>) -> DalResult<Value> {
+    let args = to_json(&Value::List(args));
+    let len = unsafe {
+        ffi::host_call(
+            name.as_ptr() as usize as i32,
+            name.len() as i32,
+            args.as_ptr() as usize as i32,
+            args.len() as i32,
+        )
+    };
+    open_envelope(&take_host_result(len)?)
+}
+
+/// Stored value of a persistent field (`Service.field`), if any.
+pub fn storage_get(key: &str) -> DalResult<Option<Value>> {
+    let len = unsafe { ffi::storage_get(key.as_ptr() as usize as i32, key.len() as i32) };
+    if len < 0 {
+        return Ok(None);
+    }
+    from_json(&take_host_result(len)?).map(Some)
+}
+
+pub fn storage_set(key: &str, value: &Value) {
+    let json = to_json(value);
+    unsafe {
+        ffi::storage_set(
+            key.as_ptr() as usize as i32,
+            key.len() as i32,
+            json.as_ptr() as usize as i32,
+            json.len() as i32,
+        )
+    };
+}
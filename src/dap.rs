@@ -0,0 +1,638 @@
+//! Debug Adapter Protocol server for DAL (`dal dap`).
+//!
+//! Speaks DAP (Content-Length framed JSON, the same base protocol as LSP) so editors can
+//! launch a `.dal` file, set line and conditional breakpoints, step, and inspect the call
+//! stack and variables. The program runs on its own thread under a [`DebugHook`] built on
+//! [`Debugger`]; requests that need a paused runtime are queued until the next pause.
+//! The IDE server exposes the same session over WebSocket at `/api/dap/stream`.
+
+use crate::lexer::Lexer;
+use crate::parser::ast::Program;
+use crate::parser::Parser;
+use crate::runtime::debugger::{
+    evaluate, frame_variables, stack_frames, PauseReason, StepMode, SESSION_TERMINATED,
+};
+use crate::runtime::{DebugHook, Debugger, Runtime, RuntimeError};
+use serde_json::{json, Value as Json};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// DAL programs are single-threaded; every event and stack trace refers to this thread.
+const THREAD_ID: i64 = 1;
+
+/// Read one `Content-Length` framed message body. Returns `None` at end of input.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(len) = line.strip_prefix("Content-Length:") {
+            content_length = len.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0u8; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write one `Content-Length` framed message body.
+pub fn write_message<W: Write>(writer: &mut W, body: &str) -> io::Result<()> {
+    write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+    writer.write_all(body.as_bytes())?;
+    writer.flush()
+}
+
+/// Serializes outgoing responses and events and numbers them.
+pub struct Outbox {
+    writer: Mutex<Box<dyn Write + Send>>,
+    seq: AtomicI64,
+}
+
+impl Outbox {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            seq: AtomicI64::new(1),
+        }
+    }
+
+    fn send(&self, mut message: Json) {
+        if let Ok(mut writer) = self.writer.lock() {
+            // Numbered under the lock so sequence numbers go out in order.
+            message["seq"] = json!(self.seq.fetch_add(1, Ordering::SeqCst));
+            // A closed client is noticed by the read loop; nothing useful to do here.
+            let _ = write_message(&mut *writer, &message.to_string());
+        }
+    }
+
+    pub fn respond(&self, request: &Json, body: Json) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    pub fn fail(&self, request: &Json, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    pub fn event(&self, event: &str, body: Json) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    /// Send program or adapter text to the client's debug console.
+    pub fn output(&self, category: &str, text: &str) {
+        self.event("output", json!({ "category": category, "output": text }));
+    }
+}
+
+/// Messages from the request loop to the program thread.
+enum Control {
+    /// A request that needs the paused runtime (stack, variables, evaluate, stepping).
+    Request(Json),
+    SetBreakpoints(Vec<(usize, Option<String>)>),
+    Pause,
+    Terminate,
+}
+
+/// The launched program, kept until `configurationDone` starts it.
+struct Launch {
+    path: String,
+    program: Program,
+    stop_on_entry: bool,
+    no_debug: bool,
+}
+
+struct RunningProgram {
+    control: mpsc::Sender<Control>,
+    handle: thread::JoinHandle<()>,
+}
+
+/// One debug session: answers requests from `input` and writes to the outbox.
+pub struct DapSession {
+    outbox: Arc<Outbox>,
+    launch: Option<Launch>,
+    /// The launched program, as a [`source_key`]. Breakpoints in other files never apply.
+    program: Option<PathBuf>,
+    /// Breakpoints per source file, as set by the last `setBreakpoints` for that file.
+    breakpoints: HashMap<PathBuf, Vec<(usize, Option<String>)>>,
+    running: Option<RunningProgram>,
+}
+
+impl DapSession {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            outbox: Arc::new(Outbox::new(writer)),
+            launch: None,
+            program: None,
+            breakpoints: HashMap::new(),
+            running: None,
+        }
+    }
+
+    pub fn outbox(&self) -> Arc<Outbox> {
+        self.outbox.clone()
+    }
+
+    /// Handle requests until the client disconnects or `input` ends.
+    pub fn run<R: BufRead>(mut self, mut input: R) -> io::Result<()> {
+        while let Some(body) = read_message(&mut input)? {
+            let request: Json = match serde_json::from_str(&body) {
+                Ok(request) => request,
+                Err(e) => {
+                    self.outbox
+                        .output("stderr", &format!("Invalid DAP message: {}\n", e));
+                    continue;
+                }
+            };
+            if request["type"] != "request" {
+                continue;
+            }
+            if !self.handle(request) {
+                break;
+            }
+        }
+        self.stop();
+        Ok(())
+    }
+
+    /// Handle one request. Returns `false` when the session is over.
+    fn handle(&mut self, request: Json) -> bool {
+        let args = &request["arguments"];
+        match request["command"].as_str().unwrap_or("") {
+            "initialize" => {
+                self.outbox.respond(
+                    &request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsConditionalBreakpoints": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsTerminateRequest": true,
+                    }),
+                );
+                self.outbox.event("initialized", json!({}));
+            }
+            "launch" => match load_program(args) {
+                Ok(launch) => {
+                    self.program = Some(source_key(&launch.path));
+                    self.launch = Some(launch);
+                    self.outbox.respond(&request, json!({}));
+                }
+                Err(message) => self.outbox.fail(&request, &message),
+            },
+            "setBreakpoints" => {
+                let key = source_key(args["source"]["path"].as_str().unwrap_or(""));
+                let source = std::fs::read_to_string(&key).unwrap_or_default();
+                let other_file = self.program.as_ref().is_some_and(|program| *program != key);
+                let requested = args["breakpoints"].as_array().cloned().unwrap_or_default();
+                let mut accepted = Vec::new();
+                let mut results = Vec::new();
+                for bp in &requested {
+                    let Some(line) = bp["line"].as_u64().map(|line| line as usize) else {
+                        continue;
+                    };
+                    let problem = if other_file {
+                        Some("Breakpoints can only be set in the launched program")
+                    } else if !can_pause_at(&source, line) {
+                        Some("No statement on this line")
+                    } else {
+                        None
+                    };
+                    results.push(match problem {
+                        Some(message) => {
+                            json!({ "verified": false, "line": line, "message": message })
+                        }
+                        None => json!({ "verified": true, "line": line }),
+                    });
+                    if problem.is_none() {
+                        let condition = bp["condition"]
+                            .as_str()
+                            .filter(|c| !c.trim().is_empty())
+                            .map(str::to_string);
+                        accepted.push((line, condition));
+                    }
+                }
+                if !other_file {
+                    if let Some(running) = &self.running {
+                        let _ = running
+                            .control
+                            .send(Control::SetBreakpoints(accepted.clone()));
+                    }
+                    self.breakpoints.insert(key, accepted);
+                }
+                self.outbox
+                    .respond(&request, json!({ "breakpoints": results }));
+            }
+            "configurationDone" => {
+                self.outbox.respond(&request, json!({}));
+                self.start();
+            }
+            "threads" => self.outbox.respond(
+                &request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "pause" => {
+                if let Some(running) = &self.running {
+                    let _ = running.control.send(Control::Pause);
+                }
+                self.outbox.respond(&request, json!({}));
+            }
+            "disconnect" | "terminate" => {
+                self.stop();
+                self.outbox.respond(&request, json!({}));
+                return request["command"] == "terminate";
+            }
+            "stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next" | "stepIn"
+            | "stepOut" => {
+                let Some(running) = &self.running else {
+                    self.outbox.fail(&request, "No program is running");
+                    return true;
+                };
+                if let Err(mpsc::SendError(Control::Request(request))) =
+                    running.control.send(Control::Request(request))
+                {
+                    self.outbox.fail(&request, "The program has finished");
+                }
+            }
+            other => self
+                .outbox
+                .fail(&request, &format!("Unsupported request '{}'", other)),
+        }
+        true
+    }
+
+    /// Start the launched program on its own thread.
+    fn start(&mut self) {
+        let Some(launch) = self.launch.take() else {
+            return;
+        };
+        let (control, receiver) = mpsc::channel();
+        let outbox = self.outbox.clone();
+        let mut debugger = Debugger::new().with_stop_on_entry(launch.stop_on_entry);
+        let program = source_key(&launch.path);
+        for (line, condition) in self.breakpoints.get(&program).into_iter().flatten() {
+            debugger.set_breakpoint(*line, condition.clone());
+        }
+        let handle = thread::spawn(move || run_program(launch, debugger, receiver, outbox));
+        self.running = Some(RunningProgram { control, handle });
+    }
+
+    /// End the program (if any) and wait for its thread.
+    fn stop(&mut self) {
+        if let Some(running) = self.running.take() {
+            let _ = running.control.send(Control::Terminate);
+            let _ = running.handle.join();
+        }
+    }
+}
+
+fn load_program(args: &Json) -> Result<Launch, String> {
+    let path = args["program"]
+        .as_str()
+        .ok_or("launch requires a 'program' path")?
+        .to_string();
+    let source =
+        std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    let tokens = Lexer::new(&source)
+        .tokenize_with_positions_immutable()
+        .map_err(|e| format!("Lexer error: {}", e))?;
+    let program = Parser::new_with_positions(tokens)
+        .parse()
+        .map_err(|e| format!("Parse error: {}", e))?;
+    Ok(Launch {
+        path,
+        program,
+        stop_on_entry: args["stopOnEntry"].as_bool().unwrap_or(false),
+        no_debug: args["noDebug"].as_bool().unwrap_or(false),
+    })
+}
+
+fn run_program(
+    launch: Launch,
+    debugger: Debugger,
+    receiver: mpsc::Receiver<Control>,
+    outbox: Arc<Outbox>,
+) {
+    let mut runtime = Runtime::new();
+    if launch.no_debug {
+        runtime.set_debug_hook(Box::new(NoDebugHook {
+            control: receiver,
+            outbox: outbox.clone(),
+        }));
+    } else {
+        runtime.set_debug_hook(Box::new(DapHook {
+            debugger,
+            control: receiver,
+            outbox: outbox.clone(),
+            path: launch.path.clone(),
+            queued: VecDeque::new(),
+            pause_requested: false,
+        }));
+    }
+    let exit_code = match runtime.execute_program(launch.program, None) {
+        Ok(result) => {
+            if let Some(value) = result {
+                outbox.output("console", &format!("Result: {}\n", value));
+            }
+            0
+        }
+        Err(e) if matches!(e.inner.root(), RuntimeError::General(m) if m == SESSION_TERMINATED) => {
+            0
+        }
+        Err(e) => {
+            outbox.output("stderr", &e.format_display(None, Some(&launch.path)));
+            1
+        }
+    };
+    if let Some(mut hook) = runtime.take_debug_hook() {
+        hook.on_finish();
+    }
+    outbox.event("exited", json!({ "exitCode": exit_code }));
+    outbox.event("terminated", json!({}));
+}
+
+/// Runs inside the program thread: pauses per [`Debugger`] and serves requests while paused.
+struct DapHook {
+    debugger: Debugger,
+    control: mpsc::Receiver<Control>,
+    outbox: Arc<Outbox>,
+    path: String,
+    /// Requests received while running, answered at the next pause.
+    queued: VecDeque<Json>,
+    pause_requested: bool,
+}
+
+impl DapHook {
+    /// Apply a control message. Returns an error when the session is terminating.
+    fn apply(&mut self, control: Control) -> Result<(), RuntimeError> {
+        match control {
+            Control::Request(request) => self.queued.push_back(request),
+            Control::SetBreakpoints(breakpoints) => {
+                self.debugger.clear_breakpoints();
+                for (line, condition) in breakpoints {
+                    self.debugger.set_breakpoint(line, condition);
+                }
+            }
+            Control::Pause => self.pause_requested = true,
+            Control::Terminate => return Err(RuntimeError::General(SESSION_TERMINATED.into())),
+        }
+        Ok(())
+    }
+
+    /// Answer one request at a pause. Returns `true` when execution should resume.
+    fn serve(&mut self, request: &Json, runtime: &mut Runtime) -> bool {
+        let args = &request["arguments"];
+        // Frame and variable references are frame index + 1 (DAP treats 0 as "none").
+        let frame = args["frameId"].as_u64().unwrap_or(1).saturating_sub(1) as usize;
+        match request["command"].as_str().unwrap_or("") {
+            "stackTrace" => {
+                let frames: Vec<Json> = stack_frames(runtime)
+                    .into_iter()
+                    .map(|f| {
+                        json!({
+                            "id": f.index + 1,
+                            "name": f.name,
+                            "line": f.line.unwrap_or(0),
+                            "column": 1,
+                            "source": { "name": file_name(&self.path), "path": self.path },
+                        })
+                    })
+                    .collect();
+                let total = frames.len();
+                self.outbox.respond(
+                    request,
+                    json!({ "stackFrames": frames, "totalFrames": total }),
+                );
+            }
+            "scopes" => self.outbox.respond(
+                request,
+                json!({ "scopes": [{
+                    "name": "Locals",
+                    "variablesReference": frame + 1,
+                    "expensive": false,
+                }] }),
+            ),
+            "variables" => {
+                let reference = args["variablesReference"].as_u64().unwrap_or(1);
+                let variables: Vec<Json> =
+                    frame_variables(runtime, reference.saturating_sub(1) as usize)
+                        .into_iter()
+                        .map(|(name, value)| {
+                            json!({
+                                "name": name,
+                                "value": value.to_string(),
+                                "type": value.type_name(),
+                                "variablesReference": 0,
+                            })
+                        })
+                        .collect();
+                self.outbox
+                    .respond(request, json!({ "variables": variables }));
+            }
+            "evaluate" => {
+                let expression = args["expression"].as_str().unwrap_or("");
+                match evaluate(runtime, expression, frame) {
+                    Ok(value) => self.outbox.respond(
+                        request,
+                        json!({ "result": value.to_string(), "variablesReference": 0 }),
+                    ),
+                    Err(e) => self.outbox.fail(request, &e),
+                }
+            }
+            command @ ("continue" | "next" | "stepIn" | "stepOut") => {
+                match command {
+                    "continue" => self.debugger.resume(StepMode::Continue),
+                    "next" => self.debugger.step_over(runtime),
+                    "stepIn" => self.debugger.step_into(),
+                    _ => self.debugger.step_out(runtime),
+                }
+                let body = match command {
+                    "continue" => json!({ "allThreadsContinued": true }),
+                    _ => json!({}),
+                };
+                self.outbox.respond(request, body);
+                return true;
+            }
+            other => self
+                .outbox
+                .fail(request, &format!("Unsupported request '{}'", other)),
+        }
+        false
+    }
+}
+
+impl DebugHook for DapHook {
+    fn on_statement(&mut self, runtime: &mut Runtime) -> Result<(), RuntimeError> {
+        while let Ok(control) = self.control.try_recv() {
+            self.apply(control)?;
+        }
+        let reason = if std::mem::take(&mut self.pause_requested) {
+            Some("pause")
+        } else {
+            self.debugger
+                .should_pause(runtime)
+                .map(|reason| match reason {
+                    PauseReason::Entry => "entry",
+                    PauseReason::Breakpoint(_) => "breakpoint",
+                    PauseReason::Step => "step",
+                })
+        };
+        let Some(reason) = reason else {
+            return Ok(());
+        };
+        self.outbox.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+        loop {
+            while let Some(request) = self.queued.pop_front() {
+                if self.serve(&request, runtime) {
+                    return Ok(());
+                }
+            }
+            match self.control.recv() {
+                Ok(control) => self.apply(control)?,
+                // The client went away: stop the program.
+                Err(_) => return Err(RuntimeError::General(SESSION_TERMINATED.into())),
+            }
+        }
+    }
+
+    fn on_finish(&mut self) {
+        // Requests that were waiting for a pause will never get one.
+        while let Ok(control) = self.control.try_recv() {
+            let _ = self.apply(control);
+        }
+        for request in self.queued.drain(..) {
+            self.outbox.fail(&request, "The program has finished");
+        }
+    }
+}
+
+/// Installed for `noDebug` launches: never pauses, but still ends the program on `terminate`.
+struct NoDebugHook {
+    control: mpsc::Receiver<Control>,
+    outbox: Arc<Outbox>,
+}
+
+impl DebugHook for NoDebugHook {
+    fn on_statement(&mut self, _runtime: &mut Runtime) -> Result<(), RuntimeError> {
+        loop {
+            match self.control.try_recv() {
+                Ok(Control::Request(request)) => self
+                    .outbox
+                    .fail(&request, "The program was launched without debugging"),
+                Ok(Control::SetBreakpoints(_) | Control::Pause) => {}
+                Ok(Control::Terminate) | Err(mpsc::TryRecvError::Disconnected) => {
+                    return Err(RuntimeError::General(SESSION_TERMINATED.into()))
+                }
+                Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Key for breakpoints and the launched program, so different spellings of a path agree.
+fn source_key(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Whether a breakpoint on 1-based `line` of `source` can be hit: the line exists and holds
+/// more than whitespace, a `//` comment or brackets.
+fn can_pause_at(source: &str, line: usize) -> bool {
+    let Some(text) = line.checked_sub(1).and_then(|i| source.lines().nth(i)) else {
+        return false;
+    };
+    let code = text.split("//").next().unwrap_or("");
+    code.chars()
+        .any(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | '(' | ')' | '[' | ']' | ';' | ','))
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Run a DAP session on stdin/stdout (`dal dap`).
+///
+/// On Unix, stdout is reserved for the protocol: file descriptor 1 is redirected to a pipe
+/// and everything the program prints is forwarded to the client as `output` events.
+pub fn run_stdio() -> io::Result<()> {
+    let stdin = io::stdin();
+    #[cfg(unix)]
+    {
+        let (protocol, captured) = redirect_stdout()?;
+        let session = DapSession::new(Box::new(protocol));
+        let outbox = session.outbox();
+        thread::spawn(move || {
+            let mut captured = io::BufReader::new(captured);
+            let mut line = String::new();
+            while captured
+                .read_line(&mut line)
+                .map(|n| n > 0)
+                .unwrap_or(false)
+            {
+                outbox.output("stdout", &line);
+                line.clear();
+            }
+        });
+        session.run(stdin.lock())
+    }
+    #[cfg(not(unix))]
+    {
+        DapSession::new(Box::new(io::stdout())).run(stdin.lock())
+    }
+}
+
+/// Duplicate the real stdout for protocol use and point fd 1 at a pipe.
+/// Returns (protocol writer, read end of the pipe).
+#[cfg(unix)]
+fn redirect_stdout() -> io::Result<(std::fs::File, std::fs::File)> {
+    use std::os::unix::io::FromRawFd;
+
+    io::stdout().flush()?;
+    let mut fds = [0; 2];
+    // SAFETY: plain POSIX calls on descriptors owned by this process; each return value is
+    // checked before the descriptor is wrapped in a `File`, which then owns it.
+    unsafe {
+        let protocol = libc::dup(1);
+        if protocol < 0 || libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if libc::dup2(fds[1], 1) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        libc::close(fds[1]);
+        Ok((
+            std::fs::File::from_raw_fd(protocol),
+            std::fs::File::from_raw_fd(fds[0]),
+        ))
+    }
+}
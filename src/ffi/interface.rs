@@ -191,18 +191,18 @@ impl FFIInterface {
         match value {
             Value::Int(_) => 8,
             Value::Float(_) => 8,
+            Value::U256(_) | Value::I256(_) | Value::Decimal(_) => 32,
             Value::Bool(_) => 1,
             Value::Null => 0,
             Value::String(s) => s.len(),
+            Value::Bytes(b) => b.len(),
             Value::List(arr) => arr.iter().map(|v| self.estimate_value_size(v)).sum(),
             Value::Array(arr) => arr.iter().map(|v| self.estimate_value_size(v)).sum(),
             Value::Map(map) => map
                 .iter()
                 .map(|(k, v)| k.len() + self.estimate_value_size(v))
                 .sum(),
-            Value::Result(ok_val, err_val) => {
-                self.estimate_value_size(ok_val) + self.estimate_value_size(err_val)
-            }
+            Value::Result(Ok(v) | Err(v)) => self.estimate_value_size(v),
             Value::Option(opt) => opt
                 .as_ref()
                 .map(|v| self.estimate_value_size(v))
@@ -213,6 +213,15 @@ impl FFIInterface {
                 .map(|(k, v)| k.len() + self.estimate_value_size(v))
                 .sum(),
             Value::Closure(id) => id.len() + 8,
+            Value::Task(_) | Value::Channel(_) => 8,
+            Value::Enum(enum_name, variant, payload) => {
+                enum_name.len()
+                    + variant.len()
+                    + payload
+                        .iter()
+                        .map(|v| self.estimate_value_size(v))
+                        .sum::<usize>()
+            }
         }
     }
 }
@@ -346,6 +355,11 @@ pub fn value_to_json(value: &Value) -> serde_json::Value {
         Value::String(s) => serde_json::Value::String(s.clone()),
         Value::Bool(b) => serde_json::Value::Bool(*b),
         Value::Null => serde_json::Value::Null,
+        // JSON numbers lose precision past 2^53, so wide values travel as decimal text.
+        Value::U256(_) | Value::I256(_) | Value::Decimal(_) => {
+            serde_json::Value::String(value.to_string())
+        }
+        Value::Bytes(b) => serde_json::Value::String(format!("0x{}", hex::encode(b))),
         Value::List(arr) => serde_json::Value::Array(arr.iter().map(value_to_json).collect()),
         Value::Map(map) => {
             let mut json_map = serde_json::Map::new();
@@ -354,11 +368,15 @@ pub fn value_to_json(value: &Value) -> serde_json::Value {
             }
             serde_json::Value::Object(json_map)
         }
-        Value::Result(ok_val, err_val) => {
-            // Serialize Result as object
+        Value::Result(result) => {
+            // Serialize Result as object with the unused slot null
+            let (ok_val, err_val) = match result {
+                Ok(ok_val) => (value_to_json(ok_val), serde_json::Value::Null),
+                Err(err_val) => (serde_json::Value::Null, value_to_json(err_val)),
+            };
             let mut json_map = serde_json::Map::new();
-            json_map.insert("ok".to_string(), value_to_json(ok_val));
-            json_map.insert("err".to_string(), value_to_json(err_val));
+            json_map.insert("ok".to_string(), ok_val);
+            json_map.insert("err".to_string(), err_val);
             serde_json::Value::Object(json_map)
         }
         Value::Option(opt) => match opt {
@@ -380,6 +398,26 @@ pub fn value_to_json(value: &Value) -> serde_json::Value {
         }
         Value::Array(arr) => serde_json::Value::Array(arr.iter().map(value_to_json).collect()),
         Value::Closure(id) => serde_json::Value::String(format!("<closure {}>", id)),
+        Value::Task(id) => serde_json::Value::String(format!("<task {}>", id)),
+        Value::Channel(id) => serde_json::Value::String(format!("<chan {}>", id)),
+        Value::Enum(enum_name, variant, payload) => {
+            let mut json_map = serde_json::Map::new();
+            json_map.insert(
+                "_type".to_string(),
+                serde_json::Value::String(enum_name.clone()),
+            );
+            json_map.insert(
+                "variant".to_string(),
+                serde_json::Value::String(variant.clone()),
+            );
+            if !payload.is_empty() {
+                json_map.insert(
+                    "values".to_string(),
+                    serde_json::Value::Array(payload.iter().map(value_to_json).collect()),
+                );
+            }
+            serde_json::Value::Object(json_map)
+        }
     }
 }
 
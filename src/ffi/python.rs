@@ -168,11 +168,13 @@ fn dal_to_python_value(value: Value) -> PyObject {
             }
             py_list.into_any().unbind()
         }
-        Value::Result(ok, _err) => dal_to_python_value(*ok),
+        Value::Result(Ok(ok)) => dal_to_python_value(*ok),
+        Value::Result(Err(_)) => py.None(),
         Value::Option(opt) => match opt {
             Some(v) => dal_to_python_value(*v),
             None => py.None(),
         },
+        v @ Value::Enum(..) => v.to_string().into_py(py),
     })
 }
 
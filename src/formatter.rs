@@ -0,0 +1,630 @@
+//! Lossless source formatter behind `dal fmt` and LSP `textDocument/formatting`.
+//!
+//! Works on the token stream plus its trivia (the whitespace and comments between tokens) rather
+//! than on the AST, so comments and every construct survive; only whitespace changes. Lines are
+//! re-indented from bracket nesting, spacing inside a line is normalized, runs of blank lines
+//! collapse to one, and a line over the width limit has its first comma-separated bracket list
+//! split one element per line. Line breaks already in the source are kept, which makes the
+//! output a fixed point: formatting it again changes nothing.
+//!
+//! Settings come from the `[fmt]` section of the nearest `dal.toml`:
+//!
+//! ```toml
+//! [fmt]
+//! width = 100  # maximum line length before bracket lists are split
+//! indent = 4   # spaces per nesting level
+//! ```
+
+use crate::lexer::tokens::{Keyword, Operator, Punctuation, Token, TokenWithPosition};
+use crate::lexer::{Lexer, LexerError};
+use crate::parser::error::ParserError;
+use crate::parser::Parser;
+use std::path::Path;
+use thiserror::Error;
+
+/// `[fmt]` settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatConfig {
+    pub width: usize,
+    pub indent: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            width: 100,
+            indent: 4,
+        }
+    }
+}
+
+impl FormatConfig {
+    /// Read `[fmt]` from dal.toml text; missing keys keep their defaults.
+    pub fn from_manifest_str(content: &str) -> Result<Self, String> {
+        let table: toml::Table = content
+            .parse()
+            .map_err(|e| format!("invalid dal.toml: {}", e))?;
+        let mut config = Self::default();
+        let Some(fmt) = table.get("fmt") else {
+            return Ok(config);
+        };
+        let fmt = fmt
+            .as_table()
+            .ok_or_else(|| "dal.toml: [fmt] must be a table".to_string())?;
+        let setting = |key: &str, range: std::ops::RangeInclusive<i64>| match fmt.get(key) {
+            None => Ok(None),
+            Some(toml::Value::Integer(n)) if range.contains(n) => Ok(Some(*n as usize)),
+            Some(other) => Err(format!(
+                "dal.toml: fmt.{} must be an integer in {}..={}, got {}",
+                key,
+                range.start(),
+                range.end(),
+                other
+            )),
+        };
+        if let Some(width) = setting("width", 20..=1000)? {
+            config.width = width;
+        }
+        if let Some(indent) = setting("indent", 1..=16)? {
+            config.indent = indent;
+        }
+        Ok(config)
+    }
+
+    /// Settings for the file at `path` from the nearest dal.toml in its directory or above;
+    /// `None` when there is no dal.toml.
+    pub fn discover(path: &Path) -> Result<Option<Self>, String> {
+        let start = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(Path::new("."))
+        };
+        let start = if start.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            start
+        };
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        for dir in start.ancestors() {
+            let manifest = dir.join("dal.toml");
+            if manifest.is_file() {
+                let content = std::fs::read_to_string(&manifest)
+                    .map_err(|e| format!("{}: {}", manifest.display(), e))?;
+                return Self::from_manifest_str(&content).map(Some);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Why a source could not be formatted. Only sources free of syntax errors are formatted.
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("{0}")]
+    Lexer(#[from] LexerError),
+    #[error("{0}")]
+    Parser(#[from] ParserError),
+    /// Safety net: the output did not lex to the same tokens as the input.
+    #[error("formatter changed the token stream at token {0}; the file was left unchanged")]
+    TokensChanged(usize),
+}
+
+/// Format DAL source. The result always ends with a single newline.
+pub fn format_source(source: &str, config: &FormatConfig) -> Result<String, FormatError> {
+    let chars: Vec<char> = source.chars().collect();
+    let spanned = Lexer::new(source).tokenize_with_spans()?;
+    let tokens: Vec<TokenWithPosition> = spanned.iter().map(|(t, _)| t.clone()).collect();
+    // Semantic checks the parser runs (attribute rules and the like) do not concern layout.
+    let (_, errors) = Parser::new_with_positions(tokens).parse_with_recovery();
+    if let Some(error) = errors
+        .into_iter()
+        .find(|e| !matches!(e, ParserError::SemanticError { .. }))
+    {
+        return Err(error.into());
+    }
+
+    let lines = build_lines(&chars, &spanned);
+    let output = render(&lines, config);
+
+    // Only whitespace may change: the token texts must match one for one.
+    let formatted_chars: Vec<char> = output.chars().collect();
+    let after = Lexer::new(&output).tokenize_with_spans()?;
+    let before_texts = spanned.iter().map(|(_, r)| &chars[r.clone()]);
+    let after_texts = after.iter().map(|(_, r)| &formatted_chars[r.clone()]);
+    if let Some(i) = before_texts
+        .zip(after_texts)
+        .position(|(before, after)| before != after)
+    {
+        return Err(FormatError::TokensChanged(i));
+    }
+    if spanned.len() != after.len() {
+        return Err(FormatError::TokensChanged(spanned.len().min(after.len())));
+    }
+    Ok(output)
+}
+
+/// Line-based diff of `original` against `formatted` for `dal fmt --check`: `-` / `+` lines
+/// under `@@ line N @@` headers, empty when the texts are equal.
+pub fn diff(original: &str, formatted: &str) -> String {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = formatted.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // Longest common subsequence over the changed middle; very large middles are shown whole.
+    let ops: Vec<(char, &str)> = if old_mid.len().saturating_mul(new_mid.len()) > 4_000_000 {
+        old_mid
+            .iter()
+            .map(|l| ('-', *l))
+            .chain(new_mid.iter().map(|l| ('+', *l)))
+            .collect()
+    } else {
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        let mut ops = Vec::new();
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                ops.push((' ', old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                ops.push(('-', old_mid[i]));
+                i += 1;
+            } else {
+                ops.push(('+', new_mid[j]));
+                j += 1;
+            }
+        }
+        ops
+    };
+
+    let mut out = String::new();
+    let mut old_line = prefix + 1;
+    let mut in_hunk = false;
+    for (op, text) in ops {
+        if op == ' ' {
+            in_hunk = false;
+            old_line += 1;
+            continue;
+        }
+        if !in_hunk {
+            out.push_str(&format!("@@ line {} @@\n", old_line));
+            in_hunk = true;
+        }
+        out.push_str(&format!("{}{}\n", op, text));
+        if op == '-' {
+            old_line += 1;
+        }
+    }
+    if out.is_empty() && original != formatted {
+        out.push_str("@@ whitespace at end of file @@\n");
+    }
+    out
+}
+
+/// A token or comment on an output line.
+struct Piece {
+    text: String,
+    /// `None` for comments.
+    token: Option<Token>,
+    space_before: bool,
+}
+
+struct Line {
+    /// Nesting level.
+    indent: usize,
+    pieces: Vec<Piece>,
+    /// The source had at least one blank line before this one.
+    blank_before: bool,
+}
+
+enum Trivia {
+    Newline,
+    Comment(String),
+}
+
+/// Split the text between two tokens into newlines and comments.
+fn scan_trivia(chars: &[char]) -> Vec<Trivia> {
+    let mut trivia = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\n' => trivia.push(Trivia::Newline),
+            '\r' => {
+                if chars.get(i + 1) != Some(&'\n') {
+                    trivia.push(Trivia::Newline);
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                let end = (i..chars.len())
+                    .find(|&k| chars[k] == '\n' || chars[k] == '\r')
+                    .unwrap_or(chars.len());
+                trivia.push(Trivia::Comment(
+                    chars[i..end]
+                        .iter()
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string(),
+                ));
+                i = end;
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                let end = (i + 2..chars.len().saturating_sub(1))
+                    .find(|&k| chars[k] == '*' && chars[k + 1] == '/')
+                    .map(|k| k + 2)
+                    .unwrap_or(chars.len());
+                trivia.push(Trivia::Comment(chars[i..end].iter().collect()));
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    trivia
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Punctuation(
+            Punctuation::LeftParen | Punctuation::LeftBracket | Punctuation::LeftBrace
+        )
+    )
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Punctuation(
+            Punctuation::RightParen | Punctuation::RightBracket | Punctuation::RightBrace
+        )
+    )
+}
+
+/// Lines that continue the previous expression get one extra level: those starting with
+/// `.method()` or `|> stage`, and those after a line ending in a binary operator or `=>`.
+fn is_continuation(token: &Token, previous: Option<&Token>) -> bool {
+    let leading = matches!(
+        token,
+        Token::Operator(Operator::Dot | Operator::OptionalChain | Operator::Pipeline)
+            | Token::Punctuation(Punctuation::Dot)
+    );
+    let trailing = matches!(
+        previous,
+        Some(Token::Operator(_) | Token::Punctuation(Punctuation::FatArrow))
+    );
+    leading || trailing
+}
+
+/// `-`, `&` and `*` are prefix operators after anything that cannot end an operand.
+fn is_prefix_position(previous: Option<&Token>) -> bool {
+    match previous {
+        None => true,
+        Some(Token::Operator(_)) => true,
+        Some(Token::Punctuation(p)) => !matches!(
+            p,
+            Punctuation::RightParen | Punctuation::RightBracket | Punctuation::Question
+        ),
+        Some(Token::Keyword(k)) => !matches!(k, Keyword::Self_ | Keyword::SelfType | Keyword::None),
+        Some(_) => false,
+    }
+}
+
+fn is_unary_prefix(token: &Token, previous: Option<&Token>) -> bool {
+    match token {
+        Token::Operator(Operator::Bang | Operator::Not | Operator::Tilde)
+        | Token::Punctuation(Punctuation::Exclamation) => true,
+        Token::Operator(Operator::Minus | Operator::Ampersand | Operator::Star) => {
+            is_prefix_position(previous)
+        }
+        _ => false,
+    }
+}
+
+/// Whether one space separates `prev` and `next` on the same line. `before_prev` is the token
+/// ahead of `prev`; `source_space` is what the source had, used where the tokens alone do not
+/// decide (`<` / `>`, which may be generic brackets, the inside of `{ }`, a call-like keyword
+/// such as `Some` before `(`).
+fn space_between(
+    before_prev: Option<&Token>,
+    prev: &Token,
+    next: &Token,
+    source_space: bool,
+) -> bool {
+    use Punctuation as P;
+    let dot_like = |t: &Token| {
+        matches!(
+            t,
+            Token::Operator(Operator::Dot | Operator::OptionalChain)
+                | Token::Punctuation(P::Dot | P::DotDot | P::DoubleColon)
+        )
+    };
+    if matches!(prev, Token::Punctuation(P::LeftParen | P::LeftBracket))
+        || matches!(next, Token::Punctuation(P::RightParen | P::RightBracket))
+    {
+        return false;
+    }
+    if matches!(
+        next,
+        Token::Punctuation(P::Comma | P::Semicolon | P::Question | P::Colon)
+            | Token::Operator(Operator::Colon)
+    ) {
+        return false;
+    }
+    if dot_like(prev) || dot_like(next) || matches!(prev, Token::Punctuation(P::At)) {
+        return false;
+    }
+    if is_unary_prefix(prev, before_prev) {
+        return false;
+    }
+    if matches!(
+        prev,
+        Token::Punctuation(P::Comma | P::Semicolon | P::Colon) | Token::Operator(Operator::Colon)
+    ) {
+        return true;
+    }
+    let operand_end = |t: &Token| {
+        matches!(
+            t,
+            Token::Identifier(_)
+                | Token::Literal(_)
+                | Token::Template(_)
+                | Token::Punctuation(P::RightParen | P::RightBracket | P::Question)
+        )
+    };
+    match next {
+        Token::Punctuation(P::LeftParen) => {
+            if operand_end(prev) {
+                return false;
+            }
+            if matches!(
+                prev,
+                Token::Keyword(
+                    Keyword::If
+                        | Keyword::While
+                        | Keyword::For
+                        | Keyword::Match
+                        | Keyword::Return
+                        | Keyword::In
+                        | Keyword::Throw
+                        | Keyword::Catch
+                )
+            ) {
+                return true;
+            }
+            if matches!(prev, Token::Keyword(_)) {
+                return source_space;
+            }
+        }
+        Token::Punctuation(P::LeftBrace) if !matches!(prev, Token::Punctuation(P::LeftBrace)) => {
+            return true;
+        }
+        Token::Punctuation(P::LeftBracket) if operand_end(prev) => return false,
+        _ => {}
+    }
+    if matches!(prev, Token::Punctuation(P::LeftBrace))
+        || matches!(next, Token::Punctuation(P::RightBrace))
+    {
+        return source_space;
+    }
+    if matches!(
+        prev,
+        Token::Operator(Operator::Less | Operator::Greater)
+            | Token::Punctuation(P::Hash | P::Dollar)
+    ) || matches!(
+        next,
+        Token::Operator(Operator::Less | Operator::Greater)
+            | Token::Punctuation(P::Hash | P::Dollar)
+    ) {
+        return source_space;
+    }
+    true
+}
+
+/// Group tokens and comments into lines, keeping the source's line breaks.
+fn build_lines(
+    chars: &[char],
+    spanned: &[(TokenWithPosition, std::ops::Range<usize>)],
+) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut current: Option<Line> = None;
+    let mut newlines = 0usize;
+    // Per open bracket: the nesting level of the line it was opened on.
+    let mut stack: Vec<usize> = Vec::new();
+    let mut previous: Option<&Token> = None;
+    let mut before_previous: Option<&Token> = None;
+    let mut prev_end = 0;
+
+    let content_indent = |stack: &[usize]| stack.last().map(|i| i + 1).unwrap_or(0);
+
+    for (twp, range) in spanned {
+        let gap = &chars[prev_end..range.start];
+        for trivia in scan_trivia(gap) {
+            match trivia {
+                Trivia::Newline => {
+                    if let Some(line) = current.take() {
+                        lines.push(line);
+                    }
+                    newlines += 1;
+                }
+                Trivia::Comment(text) => match current.as_mut() {
+                    Some(line) => line.pieces.push(Piece {
+                        text,
+                        token: None,
+                        space_before: true,
+                    }),
+                    None => {
+                        current = Some(Line {
+                            indent: content_indent(&stack),
+                            pieces: vec![Piece {
+                                text,
+                                token: None,
+                                space_before: false,
+                            }],
+                            blank_before: newlines >= 2 && !lines.is_empty(),
+                        });
+                        newlines = 0;
+                    }
+                },
+            }
+        }
+        prev_end = range.end;
+        let token = &twp.token;
+        if matches!(token, Token::EOF) {
+            break;
+        }
+
+        let line = current.get_or_insert_with(|| {
+            let indent = if is_closer(token) {
+                stack.last().copied().unwrap_or(0)
+            } else if is_continuation(token, previous) {
+                content_indent(&stack) + 1
+            } else {
+                content_indent(&stack)
+            };
+            let line = Line {
+                indent,
+                pieces: Vec::new(),
+                blank_before: newlines >= 2 && !lines.is_empty(),
+            };
+            newlines = 0;
+            line
+        });
+        let space_before = match line.pieces.last() {
+            None => false,
+            Some(Piece { token: None, .. }) => true,
+            Some(Piece {
+                token: Some(prev), ..
+            }) => {
+                let source_space = range.start > 0 && chars[range.start - 1].is_whitespace();
+                space_between(before_previous, prev, token, source_space)
+            }
+        };
+        let line_indent = line.indent;
+        line.pieces.push(Piece {
+            text: chars[range.clone()].iter().collect(),
+            token: Some(token.clone()),
+            space_before,
+        });
+        if is_opener(token) {
+            stack.push(line_indent);
+        } else if is_closer(token) {
+            stack.pop();
+        }
+        before_previous = previous;
+        previous = Some(token);
+    }
+    if let Some(line) = current.take() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn render(lines: &[Line], config: &FormatConfig) -> String {
+    let mut out = String::new();
+    let mut previous_opens = false;
+    for line in lines {
+        let starts_with_closer = matches!(
+            line.pieces.first(),
+            Some(Piece { token: Some(t), .. }) if is_closer(t)
+        );
+        // No blank line right after an opening brace or right before a closing one.
+        if line.blank_before && !previous_opens && !starts_with_closer {
+            out.push('\n');
+        }
+        for (indent, pieces) in split_long_line(line.indent, &line.pieces, config) {
+            out.push_str(&render_line(indent, pieces, config));
+            out.push('\n');
+        }
+        previous_opens = matches!(
+            line.pieces.last(),
+            Some(Piece { token: Some(t), .. }) if is_opener(t)
+        );
+    }
+    out
+}
+
+fn render_line(indent: usize, pieces: &[Piece], config: &FormatConfig) -> String {
+    let mut text = " ".repeat(indent * config.indent);
+    for (i, piece) in pieces.iter().enumerate() {
+        if i > 0 && piece.space_before {
+            text.push(' ');
+        }
+        text.push_str(&piece.text);
+    }
+    text.trim_end().to_string()
+}
+
+/// Split a line over the width limit at the first bracket pair on it that holds a
+/// comma-separated list: the opener ends the first line, each element goes on its own line one
+/// level deeper, and the closer starts the last line. Elements are split again if still long.
+fn split_long_line<'a>(
+    indent: usize,
+    pieces: &'a [Piece],
+    config: &FormatConfig,
+) -> Vec<(usize, &'a [Piece])> {
+    let too_long = render_line(indent, pieces, config).chars().count() > config.width;
+    if !too_long || pieces.iter().any(|p| p.text.contains('\n')) {
+        return vec![(indent, pieces)];
+    }
+    let token = |i: usize| pieces[i].token.as_ref();
+    for open in 0..pieces.len() {
+        if !token(open).is_some_and(is_opener) {
+            continue;
+        }
+        let mut depth = 0usize;
+        let mut commas = Vec::new();
+        let mut close = None;
+        for (k, piece) in pieces.iter().enumerate().skip(open) {
+            match piece.token.as_ref() {
+                Some(t) if is_opener(t) => depth += 1,
+                Some(t) if is_closer(t) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(k);
+                        break;
+                    }
+                }
+                Some(Token::Punctuation(Punctuation::Comma)) if depth == 1 => commas.push(k),
+                _ => {}
+            }
+        }
+        let Some(close) = close else {
+            // Unclosed on this line: nothing after it can be a complete pair.
+            break;
+        };
+        if commas.is_empty() {
+            continue;
+        }
+        let mut result = vec![(indent, &pieces[..=open])];
+        let mut start = open + 1;
+        for end in commas.iter().map(|c| c + 1).chain(std::iter::once(close)) {
+            if start < end {
+                result.extend(split_long_line(indent + 1, &pieces[start..end], config));
+            }
+            start = end;
+        }
+        result.extend(split_long_line(indent, &pieces[close..], config));
+        return result;
+    }
+    vec![(indent, pieces)]
+}
@@ -94,6 +94,9 @@ fn resource_budget_from_env() -> ResourceBudget {
             .or_else(|| strict_mode.then_some(1)),
         max_total_tokens: env_u64("DAL_AGENT_MAX_TOTAL_TOKENS"),
         max_cost_microusd: env_u64("DAL_AGENT_MAX_COST_MICROUSD"),
+        max_eval_steps: env_u64("DAL_AGENT_MAX_EVAL_STEPS"),
+        max_allocated_items: env_u64("DAL_AGENT_MAX_ALLOCATED_ITEMS"),
+        max_call_depth: env_u64("DAL_AGENT_MAX_CALL_DEPTH"),
     }
 }
 
@@ -0,0 +1,225 @@
+//! Quick fixes for the language server: insert the `;` or closing bracket a syntax error asks
+//! for, and prefix an unused variable with `_` (which silences the warning).
+
+use super::diagnostics::Diagnostic;
+use crate::lexer::tokens::{Keyword, Token, TokenWithPosition};
+use crate::lexer::Lexer;
+use crate::parser::ast::Program;
+use crate::parser::error::ParserError;
+use crate::parser::{collect_warnings, Parser};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Replace the text from `line` / `column` up to `end_line` / `end_column` (1-based, end
+/// exclusive, columns in chars) with `new_text`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceEdit {
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+    pub new_text: String,
+}
+
+/// A fix for one diagnostic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickFix {
+    pub title: String,
+    pub diagnostic: Diagnostic,
+    pub edits: Vec<SourceEdit>,
+}
+
+struct Analysis {
+    program: Program,
+    chars: Vec<char>,
+    tokens: Vec<(TokenWithPosition, Range<usize>)>,
+    errors: Vec<ParserError>,
+}
+
+fn analyze(source: &str) -> Option<Analysis> {
+    let tokens = Lexer::new(source).tokenize_with_spans().ok()?;
+    let positioned = tokens.iter().map(|(t, _)| t.clone()).collect();
+    let (program, errors) = Parser::new_with_positions(positioned).parse_with_recovery();
+    Some(Analysis {
+        program,
+        chars: source.chars().collect(),
+        tokens,
+        errors,
+    })
+}
+
+impl Analysis {
+    fn line_column(&self, offset: usize) -> (u32, u32) {
+        let before = &self.chars[..offset];
+        let line = before.iter().filter(|c| **c == '\n').count() + 1;
+        let column = before.iter().rev().take_while(|c| **c != '\n').count() + 1;
+        (line as u32, column as u32)
+    }
+
+    fn insert_at(&self, offset: usize, text: &str) -> SourceEdit {
+        let (line, column) = self.line_column(offset);
+        SourceEdit {
+            line,
+            column,
+            end_line: line,
+            end_column: column,
+            new_text: text.to_string(),
+        }
+    }
+}
+
+/// Warnings from `parser::warnings` (unused variables, deprecated calls) for a source without
+/// syntax errors. Unused `let` bindings are ranged over the name, others over their line.
+pub fn warning_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let Some(analysis) = analyze(source) else {
+        return Vec::new();
+    };
+    if !analysis.errors.is_empty() {
+        return Vec::new();
+    }
+    warnings(source, &analysis)
+        .into_iter()
+        .map(|(diagnostic, _)| diagnostic)
+        .collect()
+}
+
+/// Quick fixes for the syntax errors and unused-variable warnings in `source`.
+pub fn quick_fixes(source: &str) -> Vec<QuickFix> {
+    let Some(analysis) = analyze(source) else {
+        return Vec::new();
+    };
+    if !analysis.errors.is_empty() {
+        return analysis
+            .errors
+            .iter()
+            .filter_map(|e| missing_token_fix(&analysis, e))
+            .collect();
+    }
+    warnings(source, &analysis)
+        .into_iter()
+        .filter_map(|(diagnostic, name_at)| {
+            let offset = name_at?;
+            let name = diagnostic
+                .message
+                .strip_prefix("unused variable '")?
+                .strip_suffix('\'')?
+                .to_string();
+            Some(QuickFix {
+                title: format!("Prefix `{}` with `_` to mark it unused", name),
+                edits: vec![analysis.insert_at(offset, "_")],
+                diagnostic,
+            })
+        })
+        .collect()
+}
+
+/// Every warning as a diagnostic, with the char offset of the bound name for unused `let`s.
+fn warnings(source: &str, analysis: &Analysis) -> Vec<(Diagnostic, Option<usize>)> {
+    collect_warnings(&analysis.program)
+        .into_iter()
+        .filter(|w| w.line > 0)
+        .map(|w| {
+            let name = w
+                .message
+                .strip_prefix("unused variable '")
+                .and_then(|m| m.strip_suffix('\''));
+            let name_at = name.and_then(|name| {
+                // The name after a `let` on the warning's line.
+                analysis
+                    .tokens
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, (t, range))| {
+                        let bound = matches!(&t.token, Token::Identifier(n) if n == name);
+                        let after_let = analysis.tokens[..i].iter().rev().take(8).any(|(p, r)| {
+                            p.token == Token::Keyword(Keyword::Let)
+                                && analysis.line_column(r.start).0 == w.line as u32
+                        });
+                        (bound && after_let && analysis.line_column(range.start).0 == w.line as u32)
+                            .then_some(range.start)
+                    })
+            });
+            let (column, end_column) = match (name, name_at) {
+                (Some(name), Some(offset)) => {
+                    let column = analysis.line_column(offset).1;
+                    (column, column + name.chars().count() as u32)
+                }
+                _ => {
+                    let text = source.lines().nth(w.line - 1).unwrap_or("");
+                    (1, text.chars().count() as u32 + 1)
+                }
+            };
+            let diagnostic = Diagnostic {
+                line: w.line as u32,
+                column,
+                end_line: w.line as u32,
+                end_column,
+                message: w.message,
+                severity: "warning".to_string(),
+            };
+            (diagnostic, name_at)
+        })
+        .collect()
+}
+
+/// The text a syntax error's expectation names, if it is a token worth inserting.
+fn missing_text(expected: &str) -> Option<&'static str> {
+    expected.split(", ").find_map(|e| match e.trim() {
+        ";" | "Punctuation(Semicolon)" => Some(";"),
+        ")" | "Punctuation(RightParen)" => Some(")"),
+        "]" | "Punctuation(RightBracket)" => Some("]"),
+        "}" | "Punctuation(RightBrace)" => Some("}"),
+        _ => None,
+    })
+}
+
+fn missing_token_fix(analysis: &Analysis, error: &ParserError) -> Option<QuickFix> {
+    let (text, offset) = match error {
+        ParserError::UnexpectedToken {
+            token,
+            expected,
+            line,
+            column,
+            ..
+        } => {
+            let text = missing_text(expected)?;
+            // The offending token, then insert right after the one before it.
+            let at = analysis.tokens.iter().position(|(t, _)| {
+                t.line == *line && t.column == *column && format!("{:?}", t.token) == *token
+            })?;
+            let offset = at
+                .checked_sub(1)
+                .map(|p| analysis.tokens[p].1.end)
+                .unwrap_or(0);
+            (text, offset)
+        }
+        ParserError::UnexpectedEOF { expected, .. } => {
+            (missing_text(expected)?, analysis.chars.len())
+        }
+        _ => return None,
+    };
+    let insert = if text == "}" && offset == analysis.chars.len() {
+        if analysis.chars.last() == Some(&'\n') {
+            "}\n".to_string()
+        } else {
+            "\n}".to_string()
+        }
+    } else {
+        text.to_string()
+    };
+    // Positioned like the error diagnostics the server publishes.
+    let line = error.line_number().unwrap_or(1);
+    let column = error.column_number().unwrap_or(1);
+    Some(QuickFix {
+        title: format!("Insert missing `{}`", text),
+        diagnostic: Diagnostic {
+            line: line as u32,
+            column: column as u32,
+            end_line: line as u32,
+            end_column: column as u32 + 1,
+            message: error.to_string(),
+            severity: "error".to_string(),
+        },
+        edits: vec![analysis.insert_at(offset, &insert)],
+    })
+}
@@ -37,7 +37,8 @@ pub fn diagnostics_from_source(source: &str) -> Vec<Diagnostic> {
         }
         Ok(tokens_with_pos) => {
             let mut parser = Parser::new_with_positions(tokens_with_pos);
-            if let Err(e) = parser.parse() {
+            let (_, errors) = parser.parse_with_recovery();
+            for e in errors {
                 let line = e.line_number().unwrap_or(1);
                 let col = e.column_number().unwrap_or(1);
                 diags.push(Diagnostic {
@@ -125,10 +126,8 @@ fn collect_symbols_from_source(source: &str) -> Vec<(String, String)> {
         Err(_) => return symbols,
     };
     let mut parser = Parser::new_with_positions(tokens_with_pos);
-    let program = match parser.parse() {
-        Ok(p) => p,
-        Err(_) => return symbols,
-    };
+    // A partial program still has the symbols around syntax errors.
+    let (program, _) = parser.parse_with_recovery();
     for stmt in &program.statements {
         match stmt {
             Statement::Service(s) => {
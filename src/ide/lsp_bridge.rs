@@ -1,5 +1,6 @@
 //! WebSocket bridge for `dal lsp` stdio. Allows the browser to use full LSP (monaco-languageclient)
 //! by connecting to GET /api/lsp/stream, which spawns `dal lsp` and forwards LSP messages.
+//! GET /api/dap/stream bridges `dal dap` the same way (DAP uses the same message framing).
 
 use axum::extract::ws::{Message, WebSocket};
 use futures_util::{SinkExt, StreamExt};
@@ -34,14 +35,8 @@ fn read_lsp_message<R: Read>(reader: &mut BufReader<R>) -> Option<String> {
     }
     let n = content_length?;
     let mut buf = vec![0u8; n];
-    let mut read = 0;
-    while read < n {
-        let got = reader.get_mut().read(&mut buf[read..]).ok()?;
-        if got == 0 {
-            return None;
-        }
-        read += got;
-    }
+    // Read through the BufReader: part of the body may already be buffered.
+    reader.read_exact(&mut buf).ok()?;
     String::from_utf8(buf).ok()
 }
 
@@ -54,10 +49,13 @@ fn write_lsp_message<W: Write>(writer: &mut W, body: &str) -> std::io::Result<()
     Ok(())
 }
 
-/// Spawn `dal lsp` with workspace as cwd. Returns (child, stdout_reader) for bridging.
-fn spawn_dal_lsp(workspace_root: &Path) -> Option<(Child, BufReader<std::process::ChildStdout>)> {
+/// Spawn `dal <subcommand>` with workspace as cwd. Returns (child, stdout_reader) for bridging.
+fn spawn_dal(
+    subcommand: &str,
+    workspace_root: &Path,
+) -> Option<(Child, BufReader<std::process::ChildStdout>)> {
     let mut cmd = Command::new("dal");
-    cmd.arg("lsp")
+    cmd.arg(subcommand)
         .current_dir(workspace_root)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -71,7 +69,16 @@ fn spawn_dal_lsp(workspace_root: &Path) -> Option<(Child, BufReader<std::process
 /// Run the LSP bridge: spawn `dal lsp`, forward between WebSocket and process stdio.
 /// WebSocket sends/receives raw JSON-RPC body (no Content-Length header); we add/strip header for stdio.
 pub async fn run_lsp_bridge(socket: WebSocket, workspace_root: std::path::PathBuf) {
-    if let Some((mut child, mut stdout_reader)) = spawn_dal_lsp(&workspace_root) {
+    run_stdio_bridge(socket, workspace_root, "lsp").await
+}
+
+/// Run the debug adapter bridge: spawn `dal dap` and forward DAP messages like the LSP bridge.
+pub async fn run_dap_bridge(socket: WebSocket, workspace_root: std::path::PathBuf) {
+    run_stdio_bridge(socket, workspace_root, "dap").await
+}
+
+async fn run_stdio_bridge(socket: WebSocket, workspace_root: std::path::PathBuf, subcommand: &str) {
+    if let Some((mut child, mut stdout_reader)) = spawn_dal(subcommand, &workspace_root) {
         let mut stdin = match child.stdin.take() {
             Some(s) => s,
             None => return,
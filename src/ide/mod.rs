@@ -2,10 +2,13 @@
 //! See docs/development/IDE design/.
 
 pub mod agent_runner;
+pub mod code_actions;
 pub mod diagnostics;
 pub mod lsp_bridge;
 pub mod lsp_client;
 pub mod orchestration;
 pub mod run_backend;
+pub mod semantic;
 pub mod server;
 pub mod symbols;
+pub mod workspace;
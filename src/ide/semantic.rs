@@ -0,0 +1,557 @@
+//! Semantic highlighting and inlay hints for the language server.
+//!
+//! Names are classified by what the workspace index resolves them to (services, functions,
+//! parameters, ...), stdlib namespaces and functions by the signature registry
+//! (`stdlib::signatures`), and agents by their `agent` / `spawn` declarations. Inlay hints show
+//! stdlib parameter names at call sites, types inferred for untyped function parameters, and
+//! the runtime effect of attributes such as `@txn` and `@secure`. Lines and columns are 1-based,
+//! columns in chars.
+
+use super::workspace::{flat_tokens, ResolvedName, SymbolKind, WorkspaceIndex};
+use crate::lexer::tokens::{Keyword, Literal, Operator, Punctuation, Token};
+use crate::stdlib::signatures::{self, StdlibFunction};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemanticKind {
+    Namespace,
+    Service,
+    Agent,
+    Struct,
+    Enum,
+    Function,
+    Method,
+    Parameter,
+    Variable,
+    Property,
+    Attribute,
+    Keyword,
+    String,
+    Number,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SemanticToken {
+    pub line: u32,
+    pub column: u32,
+    pub length: u32,
+    pub kind: SemanticKind,
+    /// The defining occurrence of the name.
+    pub declaration: bool,
+    /// A stdlib namespace or function.
+    pub default_library: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InlayHintKind {
+    Type,
+    Parameter,
+    /// What an attribute does at runtime.
+    Effect,
+}
+
+/// A label shown before (`Parameter`) or after (`Type`, `Effect`) `line` / `column`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlayHint {
+    pub line: u32,
+    pub column: u32,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+/// Tokens of one source with the resolutions of its names.
+struct Document {
+    chars: Vec<char>,
+    line_starts: Vec<usize>,
+    tokens: Vec<(Token, Range<usize>)>,
+    /// Token index by start offset.
+    token_at: HashMap<usize, usize>,
+    /// Resolved names by (line, column).
+    names: HashMap<(u32, u32), ResolvedName>,
+    /// `import stdlib::chain as ch` aliases.
+    stdlib_aliases: HashMap<String, String>,
+}
+
+impl Document {
+    fn new(index: &WorkspaceIndex, path: &Path, source: &str) -> Self {
+        let chars: Vec<char> = source.chars().collect();
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            chars
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c == '\n')
+                .map(|(i, _)| i + 1),
+        );
+        let mut tokens = Vec::new();
+        flat_tokens(&chars, 0, &mut tokens);
+        let names = index
+            .resolved_names(path)
+            .into_iter()
+            .map(|n| ((n.location.line, n.location.column), n))
+            .collect();
+        let token_at = tokens
+            .iter()
+            .enumerate()
+            .map(|(k, (_, r))| (r.start, k))
+            .collect();
+        let mut document = Self {
+            chars,
+            line_starts,
+            tokens,
+            token_at,
+            names,
+            stdlib_aliases: HashMap::new(),
+        };
+        for i in 0..document.tokens.len() {
+            let word = |k: usize| document.word(k);
+            if document.tokens[i].0 == Token::Keyword(Keyword::Import)
+                && word(i + 1).as_deref() == Some("stdlib")
+                && document.is(i + 2, Punctuation::DoubleColon)
+                && matches!(
+                    document.tokens.get(i + 4),
+                    Some((Token::Keyword(Keyword::As), _))
+                )
+            {
+                if let (Some(namespace), Some(alias)) = (word(i + 3), word(i + 5)) {
+                    document.stdlib_aliases.insert(alias, namespace);
+                }
+            }
+        }
+        document
+    }
+
+    fn line_column(&self, offset: usize) -> (u32, u32) {
+        let line = self.line_starts.partition_point(|&s| s <= offset).max(1);
+        (
+            line as u32,
+            (offset - self.line_starts[line - 1] + 1) as u32,
+        )
+    }
+
+    fn text(&self, k: usize) -> String {
+        self.chars[self.tokens[k].1.clone()].iter().collect()
+    }
+
+    /// The text of an identifier or keyword token.
+    fn word(&self, k: usize) -> Option<String> {
+        match self.tokens.get(k)? {
+            (Token::Identifier(name), _) => Some(name.clone()),
+            (Token::Keyword(_), _) => Some(self.text(k)),
+            _ => None,
+        }
+    }
+
+    fn is(&self, k: usize, punctuation: Punctuation) -> bool {
+        matches!(self.tokens.get(k), Some((Token::Punctuation(p), _)) if *p == punctuation)
+    }
+
+    fn resolved(&self, k: usize) -> Option<&ResolvedName> {
+        self.names.get(&self.line_column(self.tokens[k].1.start))
+    }
+
+    /// The stdlib namespace a word names, through `import stdlib::x as y` aliases.
+    fn stdlib_namespace(&self, word: &str) -> Option<String> {
+        let namespace = self
+            .stdlib_aliases
+            .get(word)
+            .cloned()
+            .unwrap_or_else(|| word.to_string());
+        signatures::is_namespace(&namespace).then_some(namespace)
+    }
+
+    /// The stdlib function called by `ns::name(` at `k`, and the index of its `(`.
+    fn stdlib_call(&self, k: usize) -> Option<(&'static StdlibFunction, usize)> {
+        let namespace = self.stdlib_namespace(&self.word(k)?)?;
+        if !self.is(k + 1, Punctuation::DoubleColon) || !self.is(k + 3, Punctuation::LeftParen) {
+            return None;
+        }
+        let function = signatures::lookup(&namespace, &self.word(k + 2)?)?;
+        Some((function, k + 3))
+    }
+
+    /// Token ranges of the comma-separated arguments in the brackets opened at `open`.
+    fn arguments(&self, open: usize) -> Vec<Range<usize>> {
+        let mut arguments = Vec::new();
+        let mut depth = 0;
+        let mut start = open + 1;
+        for k in open + 1..self.tokens.len() {
+            match &self.tokens[k].0 {
+                Token::Punctuation(
+                    Punctuation::LeftParen | Punctuation::LeftBracket | Punctuation::LeftBrace,
+                ) => depth += 1,
+                Token::Punctuation(
+                    Punctuation::RightParen | Punctuation::RightBracket | Punctuation::RightBrace,
+                ) if depth == 0 => {
+                    if k > start {
+                        arguments.push(start..k);
+                    }
+                    break;
+                }
+                Token::Punctuation(
+                    Punctuation::RightParen | Punctuation::RightBracket | Punctuation::RightBrace,
+                ) => depth -= 1,
+                Token::Punctuation(Punctuation::Comma) if depth == 0 => {
+                    arguments.push(start..k);
+                    start = k + 1;
+                }
+                _ => {}
+            }
+        }
+        arguments
+    }
+
+    /// The `(` enclosing token `k`, and how many top-level commas come between them.
+    fn enclosing_parens(&self, k: usize) -> Option<(usize, usize)> {
+        let mut position = 0;
+        let mut depth = 0;
+        for j in (0..k).rev() {
+            match &self.tokens[j].0 {
+                Token::Punctuation(
+                    Punctuation::RightParen | Punctuation::RightBracket | Punctuation::RightBrace,
+                ) => depth += 1,
+                Token::Punctuation(Punctuation::LeftParen) if depth == 0 => {
+                    return Some((j, position))
+                }
+                Token::Punctuation(Punctuation::LeftBracket | Punctuation::LeftBrace)
+                    if depth == 0 =>
+                {
+                    return None
+                }
+                Token::Punctuation(
+                    Punctuation::LeftParen | Punctuation::LeftBracket | Punctuation::LeftBrace,
+                ) => depth -= 1,
+                Token::Punctuation(Punctuation::Comma) if depth == 0 => position += 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Index of the token at `offset`.
+    fn token_at(&self, offset: usize) -> Option<usize> {
+        self.token_at.get(&offset).copied()
+    }
+
+    fn offset(&self, line: u32, column: u32) -> usize {
+        self.line_starts[line as usize - 1] + column as usize - 1
+    }
+}
+
+/// Semantic tokens of `source`, which must be indexed in `index` as `path`, in file order.
+pub fn semantic_tokens(index: &WorkspaceIndex, path: &Path, source: &str) -> Vec<SemanticToken> {
+    let document = Document::new(index, path, source);
+    let tokens = &document.tokens;
+    // Agents by name, from `agent Name: type` and `spawn name: type`.
+    let agents: Vec<String> = (0..tokens.len())
+        .filter(|&k| {
+            matches!(tokens[k].0, Token::Keyword(Keyword::Agent | Keyword::Spawn))
+                && document.is(k + 2, Punctuation::Colon)
+        })
+        .filter_map(|k| document.word(k + 1))
+        .collect();
+
+    let mut out = Vec::new();
+    let mut k = 0;
+    while k < tokens.len() {
+        let (token, range) = &tokens[k];
+        let mut emit = |range: Range<usize>, kind, declaration, default_library| {
+            if document.chars[range.clone()].contains(&'\n') {
+                return;
+            }
+            let (line, column) = document.line_column(range.start);
+            out.push(SemanticToken {
+                line,
+                column,
+                length: (range.end - range.start) as u32,
+                kind,
+                declaration,
+                default_library,
+            });
+        };
+        let previous = k.checked_sub(1).map(|p| &tokens[p].0);
+        let next = tokens.get(k + 1).map(|(t, _)| t);
+
+        // `@name`, with any arguments left to the other rules.
+        if *token == Token::Punctuation(Punctuation::At) {
+            if let Some((_, name)) = tokens.get(k + 1).filter(|(_, r)| r.start == range.end) {
+                if document.word(k + 1).is_some() {
+                    emit(range.start..name.end, SemanticKind::Attribute, false, false);
+                    k += 2;
+                    continue;
+                }
+            }
+        }
+        let Some(word) = document.word(k) else {
+            match token {
+                Token::Literal(Literal::String(_)) => {
+                    emit(range.clone(), SemanticKind::String, false, false)
+                }
+                Token::Literal(
+                    Literal::Int(_)
+                    | Literal::Float(_)
+                    | Literal::U256(_)
+                    | Literal::I256(_)
+                    | Literal::Decimal(_),
+                ) => emit(range.clone(), SemanticKind::Number, false, false),
+                _ => {}
+            }
+            k += 1;
+            continue;
+        };
+
+        let before_path = document.is(k + 1, Punctuation::DoubleColon);
+        let after_path = previous == Some(&Token::Punctuation(Punctuation::DoubleColon));
+        let resolved = document.resolved(k);
+        if before_path && document.stdlib_namespace(&word).is_some() && resolved.is_none() {
+            emit(range.clone(), SemanticKind::Namespace, false, true);
+        } else if after_path
+            && k >= 2
+            && document.word(k - 2).is_some_and(|ns| {
+                document
+                    .stdlib_namespace(&ns)
+                    .is_some_and(|ns| signatures::lookup(&ns, &word).is_some())
+            })
+        {
+            emit(range.clone(), SemanticKind::Function, false, true);
+        } else if let Some(name) = resolved {
+            let kind = match name.kind {
+                SymbolKind::Function => SemanticKind::Function,
+                SymbolKind::Method => SemanticKind::Method,
+                SymbolKind::Service => SemanticKind::Service,
+                SymbolKind::Struct => SemanticKind::Struct,
+                SymbolKind::Enum => SemanticKind::Enum,
+                SymbolKind::Field => SemanticKind::Property,
+                SymbolKind::Variable if agents.contains(&word) => SemanticKind::Agent,
+                SymbolKind::Variable => SemanticKind::Variable,
+                SymbolKind::Parameter => SemanticKind::Parameter,
+                SymbolKind::Module => SemanticKind::Namespace,
+            };
+            let stdlib_alias =
+                kind == SemanticKind::Namespace && document.stdlib_namespace(&word).is_some();
+            emit(range.clone(), kind, name.is_declaration(), stdlib_alias);
+        } else if let Token::Keyword(_) = token {
+            emit(range.clone(), SemanticKind::Keyword, false, false);
+        } else if agents.contains(&word) {
+            let declaration = matches!(
+                previous,
+                Some(Token::Keyword(Keyword::Agent | Keyword::Spawn))
+            );
+            emit(range.clone(), SemanticKind::Agent, declaration, false);
+        } else if before_path {
+            emit(range.clone(), SemanticKind::Namespace, false, false);
+        } else if matches!(
+            previous,
+            Some(Token::Punctuation(Punctuation::Dot) | Token::Operator(Operator::OptionalChain))
+        ) {
+            let kind = if next == Some(&Token::Punctuation(Punctuation::LeftParen)) {
+                SemanticKind::Method
+            } else {
+                SemanticKind::Property
+            };
+            emit(range.clone(), kind, false, false);
+        } else if next == Some(&Token::Punctuation(Punctuation::LeftParen)) {
+            emit(range.clone(), SemanticKind::Function, false, false);
+        }
+        k += 1;
+    }
+    out
+}
+
+/// Inlay hints for `source`, which must be indexed in `index` as `path`, in file order.
+pub fn inlay_hints(index: &WorkspaceIndex, path: &Path, source: &str) -> Vec<InlayHint> {
+    let document = Document::new(index, path, source);
+    let mut hints = Vec::new();
+    parameter_name_hints(&document, &mut hints);
+    parameter_type_hints(&document, &mut hints);
+    attribute_hints(&document, &mut hints);
+    hints.sort_by_key(|h| (h.line, h.column));
+    hints
+}
+
+/// `name:` before each argument of a stdlib call, unless the argument already says it.
+fn parameter_name_hints(document: &Document, hints: &mut Vec<InlayHint>) {
+    for k in 0..document.tokens.len() {
+        let Some((function, open)) = document.stdlib_call(k) else {
+            continue;
+        };
+        for (argument, param) in document.arguments(open).iter().zip(function.params) {
+            let generated = param.name.starts_with("arg")
+                && param.name[3..].chars().all(|c| c.is_ascii_digit());
+            let same_name =
+                argument.len() == 1 && document.word(argument.start).as_deref() == Some(param.name);
+            if generated || same_name {
+                continue;
+            }
+            let (line, column) = document.line_column(document.tokens[argument.start].1.start);
+            hints.push(InlayHint {
+                line,
+                column,
+                label: format!("{}:", param.name),
+                kind: InlayHintKind::Parameter,
+            });
+        }
+    }
+}
+
+/// The DAL type of an argument that is a single literal.
+fn literal_type(document: &Document, argument: &Range<usize>) -> Option<&'static str> {
+    if argument.len() != 1 {
+        return None;
+    }
+    Some(match &document.tokens[argument.start].0 {
+        Token::Literal(Literal::Int(_)) => "int",
+        Token::Literal(Literal::Float(_)) => "float",
+        Token::Literal(Literal::String(_)) => "string",
+        Token::Literal(Literal::Bool(_)) => "bool",
+        _ => return None,
+    })
+}
+
+/// `: type` after an untyped `fn` parameter whose uses agree on one type: passed whole to a
+/// typed stdlib parameter, or given a literal at the function's call sites in this file.
+fn parameter_type_hints(document: &Document, hints: &mut Vec<InlayHint>) {
+    let tokens = &document.tokens;
+    let names: Vec<&ResolvedName> = document.names.values().collect();
+    for declaration in names
+        .iter()
+        .filter(|n| n.kind == SymbolKind::Parameter && n.is_declaration())
+    {
+        let offset = document.offset(declaration.location.line, declaration.location.column);
+        let Some(k) = document.token_at(offset) else {
+            continue;
+        };
+        if document.is(k + 1, Punctuation::Colon)
+            || tokens.get(k + 1).map(|(t, _)| t) == Some(&Token::Operator(Operator::Colon))
+        {
+            continue;
+        }
+        let Some((open, position)) = document.enclosing_parens(k) else {
+            continue;
+        };
+        if open < 2 || tokens[open - 2].0 != Token::Keyword(Keyword::Fn) {
+            continue;
+        }
+        let function = document.resolved(open - 1);
+
+        let mut evidence: Vec<&'static str> = Vec::new();
+        for name in &names {
+            let use_at = document.offset(name.location.line, name.location.column);
+            let Some(u) = document.token_at(use_at) else {
+                continue;
+            };
+            if name.definition == declaration.location && !name.is_declaration() {
+                // Passed whole as a stdlib argument.
+                let whole = matches!(
+                    tokens.get(u + 1),
+                    Some((
+                        Token::Punctuation(Punctuation::Comma | Punctuation::RightParen),
+                        _
+                    ))
+                ) && matches!(
+                    tokens.get(u.wrapping_sub(1)),
+                    Some((
+                        Token::Punctuation(Punctuation::Comma | Punctuation::LeftParen),
+                        _
+                    ))
+                );
+                let call = document
+                    .enclosing_parens(u)
+                    .filter(|_| whole)
+                    .and_then(|(open, at)| {
+                        let (function, _) = document.stdlib_call(open.checked_sub(3)?)?;
+                        Some(function.params.get(at)?.ty)
+                    });
+                if let Some(ty) = call.filter(|ty| *ty != "any" && *ty != "null") {
+                    evidence.push(ty);
+                }
+            } else if function
+                .is_some_and(|f| name.definition == f.definition && !name.is_declaration())
+                && document.is(u + 1, Punctuation::LeftParen)
+            {
+                if let Some(ty) = document
+                    .arguments(u + 1)
+                    .get(position)
+                    .and_then(|a| literal_type(document, a))
+                {
+                    evidence.push(ty);
+                }
+            }
+        }
+        evidence.sort_unstable();
+        evidence.dedup();
+        if let [ty] = evidence.as_slice() {
+            let end = tokens[k].1.end;
+            let (line, column) = document.line_column(end);
+            hints.push(InlayHint {
+                line,
+                column,
+                label: format!(": {}", ty),
+                kind: InlayHintKind::Type,
+            });
+        }
+    }
+}
+
+/// What an attribute does when the program runs, after the attribute.
+fn attribute_hints(document: &Document, hints: &mut Vec<InlayHint>) {
+    let tokens = &document.tokens;
+    for k in 0..tokens.len() {
+        if tokens[k].0 != Token::Punctuation(Punctuation::At) {
+            continue;
+        }
+        let Some(name) = document.word(k + 1) else {
+            continue;
+        };
+        let (arguments, end) = if document.is(k + 2, Punctuation::LeftParen) {
+            let arguments = document.arguments(k + 2);
+            let close = arguments.last().map(|a| a.end).unwrap_or(k + 3);
+            (arguments, tokens.get(close).map(|(_, r)| r.end))
+        } else {
+            (Vec::new(), Some(tokens[k + 1].1.end))
+        };
+        let argument = |i: usize| -> Option<String> {
+            let a = arguments.get(i)?;
+            match &tokens[a.start].0 {
+                Token::Literal(Literal::String(s)) => Some(s.clone()),
+                _ => Some(document.text(a.start)),
+            }
+        };
+        let label = match name.as_str() {
+            "txn" => format!(
+                "transaction ({}, {} ms timeout), rolled back on error",
+                argument(0).unwrap_or_else(|| "read_committed".to_string()),
+                argument(1).unwrap_or_else(|| "30000".to_string())
+            ),
+            "secure" => "requires authentication, guarded against reentrancy".to_string(),
+            "public" => "no authentication required".to_string(),
+            "cached" => match argument(0) {
+                Some(ttl) => format!("memoized for {} s", ttl),
+                None => "memoized".to_string(),
+            },
+            "persistent" => "kept across restarts".to_string(),
+            "versioned" => "records a migration history".to_string(),
+            "deprecated" => "calls are warned about".to_string(),
+            "limit" => match argument(0) {
+                Some(n) => format!("at most {} operations", n),
+                None => continue,
+            },
+            _ => continue,
+        };
+        let Some(end) = end else {
+            continue;
+        };
+        let (line, column) = document.line_column(end);
+        hints.push(InlayHint {
+            line,
+            column,
+            label,
+            kind: InlayHintKind::Effect,
+        });
+    }
+}
@@ -1933,6 +1933,16 @@ async fn get_lsp_ws(
     ws.on_upgrade(move |socket| lsp_bridge::run_lsp_bridge(socket, workspace_root))
 }
 
+/// WebSocket upgrade for debugging (dal dap over stdio bridged to client).
+/// Connect to GET /api/dap/stream; requires `dal` on PATH. Messages are raw DAP JSON bodies.
+async fn get_dap_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+) -> axum::response::Response {
+    let workspace_root = state.workspace_root.clone();
+    ws.on_upgrade(move |socket| lsp_bridge::run_dap_bridge(socket, workspace_root))
+}
+
 /// Resolve workspace root from optional user input. Ensures the result is under
 /// state.workspace_root to prevent path traversal (absolute paths are canonicalized and checked).
 fn resolve_workspace_root(state: &AppState, workspace: Option<&str>) -> PathBuf {
@@ -2910,6 +2920,7 @@ pub fn build_router(workspace_root: PathBuf) -> Router {
         )
         .route("/api/lsp/references", post(post_lsp_references))
         .route("/api/lsp/stream", get(get_lsp_ws))
+        .route("/api/dap/stream", get(get_dap_ws))
         .route("/api/agent/run_command", post(post_agent_run_command))
         .route(
             "/api/agent/run_command_stream",
@@ -33,10 +33,8 @@ pub fn document_symbols_from_source(source: &str) -> Vec<DocumentSymbol> {
         Err(_) => return symbols,
     };
     let mut parser = Parser::new_with_positions(tokens_with_pos);
-    let program = match parser.parse() {
-        Ok(p) => p,
-        Err(_) => return symbols,
-    };
+    // A partial program still has the symbols around syntax errors.
+    let (program, _) = parser.parse_with_recovery();
     for (i, stmt) in program.statements.iter().enumerate() {
         let span = program
             .statement_spans
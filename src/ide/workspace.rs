@@ -0,0 +1,1008 @@
+//! Workspace symbol index for the language server: definitions, references and renames across
+//! files, following `import "./file.dal" as alias` edges through `ModuleResolver`.
+//!
+//! Each file is indexed from its tokens (with exact character spans), so broken files still
+//! contribute what they define. A bare name resolves, in order, to the definition it is written
+//! at, the innermost local binding in scope (`let`, parameter, `for`, `catch`, closure or
+//! match-arm binding), a member of the service it is written in, or a top-level definition of
+//! the file. `alias::name` resolves into the imported file, `Service::name` and `self.name` to a
+//! member of that service, and `value.name` to the only member of that name in the workspace.
+//! Lines and columns are 1-based, columns in chars.
+
+use crate::lexer::tokens::{Keyword, Literal, Operator, Punctuation, Token};
+use crate::lexer::Lexer;
+use crate::manifest::{load_resolved_deps, ResolvedDeps};
+use crate::module_resolver::{package_entry_path, ModuleResolver, ResolvedImport};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Files indexed by `index_directory`, at most.
+const MAX_INDEXED_FILES: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Service,
+    Struct,
+    Enum,
+    Field,
+    Variable,
+    Parameter,
+    Module,
+}
+
+/// A name in a file: 1-based line and column of its first char, and its length in chars.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub length: u32,
+}
+
+/// A top-level or member definition, for `workspace/symbol`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Service or struct holding a method or field.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub location: SymbolLocation,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum RenameError {
+    #[error("no renameable symbol at this position")]
+    NoSymbol,
+    #[error("'{0}' is not a valid identifier")]
+    InvalidName(String),
+    #[error("'{0}' is already defined in this scope")]
+    Conflict(String),
+}
+
+/// A name written in a file and the definition it resolves to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedName {
+    pub location: SymbolLocation,
+    pub kind: SymbolKind,
+    pub definition: SymbolLocation,
+}
+
+impl ResolvedName {
+    /// Whether this is the defining occurrence.
+    pub fn is_declaration(&self) -> bool {
+        self.location == self.definition
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Definition {
+    name: String,
+    kind: SymbolKind,
+    container: Option<String>,
+    span: Range<usize>,
+    /// Char range a local binding is visible in; `None` for top-level definitions and members.
+    scope: Option<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Qualifier {
+    /// A bare name, with the service whose body it is written in.
+    None(Option<String>),
+    /// After `.` or `?.`; the service when written `self.name` inside one.
+    Member(Option<String>),
+    /// After `prefix::`.
+    Path(String),
+}
+
+#[derive(Debug, Clone)]
+struct NameRef {
+    name: String,
+    span: Range<usize>,
+    qualifier: Qualifier,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FileIndex {
+    /// Char offset of the start of each line.
+    line_starts: Vec<usize>,
+    definitions: Vec<Definition>,
+    /// Definition index by the char offset of its name.
+    definition_at: HashMap<usize, usize>,
+    names: Vec<NameRef>,
+    /// Import alias -> resolved file, if it is a DAL file.
+    imports: Vec<(String, Option<PathBuf>)>,
+    /// Import paths as written, resolved when the file is indexed.
+    import_paths: Vec<String>,
+}
+
+/// Identifies a definition: file and index in its `definitions`.
+type SymbolId = (PathBuf, usize);
+
+/// Definitions and name references of every indexed file.
+#[derive(Debug, Default)]
+pub struct WorkspaceIndex {
+    files: BTreeMap<PathBuf, FileIndex>,
+    root: Option<PathBuf>,
+    dependencies: Option<ResolvedDeps>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `root` for relative imports without a file directory and for package imports from
+    /// its dal.toml / dal.lock.
+    pub fn with_root(mut self, root: PathBuf) -> Self {
+        let manifest = root.join("dal.toml");
+        if manifest.is_file() {
+            self.dependencies = load_resolved_deps(&manifest).ok();
+        }
+        self.root = Some(root);
+        self
+    }
+
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// (Re)index `path` from `source`, e.g. an open editor buffer.
+    pub fn update_file(&mut self, path: &Path, source: &str) {
+        let mut index = index_source(source);
+        let resolver = self.resolver();
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty());
+        index.imports = index
+            .imports
+            .iter()
+            .zip(&index.import_paths)
+            .map(|((alias, _), import_path)| {
+                let target = match resolver.resolve(import_path, dir) {
+                    Ok(ResolvedImport::RelativeFile(file)) => Some(file),
+                    Ok(ResolvedImport::Package { path, .. }) => package_entry_path(&path),
+                    _ => None,
+                };
+                (alias.clone(), target)
+            })
+            .collect();
+        self.files.insert(path.to_path_buf(), index);
+    }
+
+    /// (Re)index `path` from disk; false when it cannot be read.
+    pub fn load_file(&mut self, path: &Path) -> bool {
+        match std::fs::read_to_string(path) {
+            Ok(source) => {
+                self.update_file(path, &source);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn remove_file(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Index the files `path` imports, transitively, reading from disk those not yet indexed.
+    pub fn load_imports(&mut self, path: &Path) {
+        let mut queue = vec![path.to_path_buf()];
+        let mut seen = HashSet::new();
+        while let Some(file) = queue.pop() {
+            if !seen.insert(file.clone()) {
+                continue;
+            }
+            if !self.files.contains_key(&file) && !self.load_file(&file) {
+                continue;
+            }
+            let targets = self.files[&file]
+                .imports
+                .iter()
+                .filter_map(|(_, t)| t.clone());
+            queue.extend(targets.collect::<Vec<_>>());
+        }
+    }
+
+    /// Index every `.dal` file under `dir` that is not indexed yet (open buffers win), skipping
+    /// hidden directories, `target` and `node_modules`.
+    pub fn index_directory(&mut self, dir: &Path) {
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if self.files.len() >= MAX_INDEXED_FILES {
+                    return;
+                }
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if path.is_dir() {
+                    if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                        pending.push(path);
+                    }
+                } else if name.ends_with(".dal") {
+                    let path = path.canonicalize().unwrap_or(path);
+                    if !self.files.contains_key(&path) {
+                        self.load_file(&path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Where the name at `line` / `column` of `path` is defined.
+    pub fn definition(&self, path: &Path, line: u32, column: u32) -> Option<SymbolLocation> {
+        let (file, id) = self.symbol_at(path, line, column)?;
+        Some(self.location(&file, &self.files[&file].definitions[id].span))
+    }
+
+    /// Every place the symbol at `line` / `column` is written, in file order.
+    pub fn references(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+        include_declaration: bool,
+    ) -> Vec<SymbolLocation> {
+        let Some(target) = self.symbol_at(path, line, column) else {
+            return Vec::new();
+        };
+        let definition_span = self.files[&target.0].definitions[target.1].span.clone();
+        let mut locations = Vec::new();
+        for (file, index) in &self.files {
+            for name in &index.names {
+                if self.resolve(file, name).as_ref() != Some(&target) {
+                    continue;
+                }
+                if !include_declaration && *file == target.0 && name.span == definition_span {
+                    continue;
+                }
+                locations.push(self.location(file, &name.span));
+            }
+        }
+        locations
+    }
+
+    /// The places to rewrite when renaming the symbol at `line` / `column` to `new_name`.
+    pub fn rename(
+        &self,
+        path: &Path,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> Result<Vec<SymbolLocation>, RenameError> {
+        let (file, id) = self
+            .symbol_at(path, line, column)
+            .ok_or(RenameError::NoSymbol)?;
+        let tokens = Lexer::new(new_name).tokenize().unwrap_or_default();
+        if !matches!(tokens.as_slice(), [Token::Identifier(n), Token::EOF] if n == new_name) {
+            return Err(RenameError::InvalidName(new_name.to_string()));
+        }
+        let index = &self.files[&file];
+        let target = &index.definitions[id];
+        let clash = index.definitions.iter().any(|d| {
+            d.name == new_name
+                && match (&target.scope, &d.scope) {
+                    (Some(a), Some(b)) => a.start < b.end && b.start < a.end,
+                    (None, None) => d.container == target.container,
+                    // A local would shadow, or be shadowed by, the file's top-level name.
+                    _ => d.container.is_none() && target.container.is_none(),
+                }
+        });
+        if clash {
+            return Err(RenameError::Conflict(new_name.to_string()));
+        }
+        Ok(self.references(path, line, column, true))
+    }
+
+    /// Every name in `path` that resolves to a definition, in file order.
+    pub fn resolved_names(&self, path: &Path) -> Vec<ResolvedName> {
+        let Some(index) = self.files.get(path) else {
+            return Vec::new();
+        };
+        index
+            .names
+            .iter()
+            .filter_map(|name| {
+                let (file, id) = self.resolve(path, name)?;
+                let definition = &self.files[&file].definitions[id];
+                Some(ResolvedName {
+                    location: self.location(path, &name.span),
+                    kind: definition.kind,
+                    definition: self.location(&file, &definition.span),
+                })
+            })
+            .collect()
+    }
+
+    /// Top-level definitions, services' and structs' members and imports' aliases excluded
+    /// locals, whose name contains `query` (case-insensitive), by file and position.
+    pub fn symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        let query = query.to_lowercase();
+        let mut symbols = Vec::new();
+        for (file, index) in &self.files {
+            for d in &index.definitions {
+                if d.scope.is_some()
+                    || d.kind == SymbolKind::Module
+                    || !d.name.to_lowercase().contains(&query)
+                {
+                    continue;
+                }
+                symbols.push(WorkspaceSymbol {
+                    name: d.name.clone(),
+                    kind: d.kind,
+                    container: d.container.clone(),
+                    location: self.location(file, &d.span),
+                });
+            }
+        }
+        symbols
+    }
+
+    fn resolver(&self) -> ModuleResolver {
+        let mut resolver = ModuleResolver::new();
+        if let Some(root) = &self.root {
+            resolver = resolver.with_root_dir(root.clone());
+        }
+        if let Some(deps) = &self.dependencies {
+            resolver = resolver.with_dependencies(deps.clone());
+        }
+        resolver
+    }
+
+    fn location(&self, file: &Path, span: &Range<usize>) -> SymbolLocation {
+        let (line, column) = self.files[file].line_column(span.start);
+        SymbolLocation {
+            path: file.to_path_buf(),
+            line,
+            column,
+            length: (span.end - span.start) as u32,
+        }
+    }
+
+    fn symbol_at(&self, path: &Path, line: u32, column: u32) -> Option<SymbolId> {
+        let index = self.files.get(path)?;
+        let offset = index.offset(line, column)?;
+        // The name under the cursor, or the one just before it.
+        let name = index
+            .names
+            .iter()
+            .find(|n| n.span.start <= offset && offset < n.span.end)
+            .or_else(|| index.names.iter().find(|n| n.span.end == offset))?;
+        self.resolve(path, name)
+    }
+
+    fn resolve(&self, file: &Path, name: &NameRef) -> Option<SymbolId> {
+        let index = self.files.get(file)?;
+        if let Some(&id) = index.definition_at.get(&name.span.start) {
+            if index.definitions[id].name == name.name {
+                return Some((file.to_path_buf(), id));
+            }
+        }
+        let top_level = |index: &FileIndex, container: Option<&str>| {
+            index.definitions.iter().position(|d| {
+                d.name == name.name && d.scope.is_none() && d.container.as_deref() == container
+            })
+        };
+        match &name.qualifier {
+            Qualifier::None(service) => {
+                let offset = name.span.start;
+                let local = index
+                    .definitions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| {
+                        d.name == name.name
+                            && d.span.start <= offset
+                            && d.scope.as_ref().is_some_and(|s| s.contains(&offset))
+                    })
+                    .max_by_key(|(_, d)| d.span.start)
+                    .map(|(i, _)| i);
+                local
+                    .or_else(|| service.as_deref().and_then(|s| top_level(index, Some(s))))
+                    .or_else(|| top_level(index, None))
+                    .map(|id| (file.to_path_buf(), id))
+            }
+            Qualifier::Path(prefix) => {
+                if let Some((_, Some(target))) = index.imports.iter().find(|(a, _)| a == prefix) {
+                    let imported = self.files.get(target)?;
+                    return top_level(imported, None).map(|id| (target.clone(), id));
+                }
+                top_level(index, Some(prefix)).map(|id| (file.to_path_buf(), id))
+            }
+            Qualifier::Member(Some(service)) => {
+                top_level(index, Some(service)).map(|id| (file.to_path_buf(), id))
+            }
+            Qualifier::Member(None) => {
+                let members: Vec<SymbolId> = self
+                    .files
+                    .iter()
+                    .flat_map(|(path, index)| {
+                        index
+                            .definitions
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, d)| d.name == name.name && d.container.is_some())
+                            .map(move |(i, _)| (path.clone(), i))
+                    })
+                    .collect();
+                let local: Vec<&SymbolId> = members.iter().filter(|(p, _)| p == file).collect();
+                match (members.len(), local.len()) {
+                    (1, _) => members.into_iter().next(),
+                    (_, 1) => Some(local[0].clone()),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl FileIndex {
+    fn line_column(&self, offset: usize) -> (u32, u32) {
+        let line = self.line_starts.partition_point(|&s| s <= offset).max(1);
+        (
+            line as u32,
+            (offset - self.line_starts[line - 1] + 1) as u32,
+        )
+    }
+
+    fn offset(&self, line: u32, column: u32) -> Option<usize> {
+        let start = *self.line_starts.get((line as usize).checked_sub(1)?)?;
+        Some(start + (column as usize).saturating_sub(1))
+    }
+}
+
+/// Tokens with char spans, with the code inside `${...}` of templates spliced in place of the
+/// template token.
+pub(super) fn flat_tokens(chars: &[char], base: usize, out: &mut Vec<(Token, Range<usize>)>) {
+    let source: String = chars.iter().collect();
+    let Ok(tokens) = Lexer::new(&source).tokenize_with_spans() else {
+        return;
+    };
+    for (t, range) in tokens {
+        match t.token {
+            Token::EOF => {}
+            Token::Template(_) => {
+                for code in template_code_ranges(chars, range.clone()) {
+                    flat_tokens(&chars[code.clone()], base + code.start, out);
+                }
+            }
+            token => out.push((token, base + range.start..base + range.end)),
+        }
+    }
+}
+
+/// Char ranges of the `${...}` code in the string literal at `range`.
+fn template_code_ranges(chars: &[char], range: Range<usize>) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let quotes = if chars[range.start..].starts_with(&['"', '"', '"']) {
+        3
+    } else {
+        1
+    };
+    let mut i = range.start + quotes;
+    while i < range.end {
+        match chars[i] {
+            '\\' => i += 2,
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                let end = skip_code(chars, i + 2, range.end);
+                ranges.push(i + 2..end);
+                i = end + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    ranges
+}
+
+/// Index of the `}` closing code that starts at `i`, skipping nested braces and strings.
+fn skip_code(chars: &[char], mut i: usize, limit: usize) -> usize {
+    let mut depth = 0usize;
+    while i < limit {
+        match chars[i] {
+            '{' => depth += 1,
+            '}' if depth == 0 => return i,
+            '}' => depth -= 1,
+            '"' => {
+                i = skip_string(chars, i, limit);
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    limit
+}
+
+/// Index just past the string literal starting at `i`.
+fn skip_string(chars: &[char], mut i: usize, limit: usize) -> usize {
+    let triple = chars[i..limit.min(chars.len())].starts_with(&['"', '"', '"']);
+    i += if triple { 3 } else { 1 };
+    while i < limit {
+        if chars[i] == '\\' {
+            i += 2;
+        } else if chars[i] == '$' && chars.get(i + 1) == Some(&'{') {
+            i = skip_code(chars, i + 2, limit) + 1;
+        } else if triple && chars[i..limit].starts_with(&['"', '"', '"']) {
+            return i + 3;
+        } else if !triple && chars[i] == '"' {
+            return i + 1;
+        } else {
+            i += 1;
+        }
+    }
+    limit
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Frame {
+    Service(String),
+    Struct(String),
+    Block,
+    Group,
+}
+
+fn is_opener(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Punctuation(
+            Punctuation::LeftParen | Punctuation::LeftBracket | Punctuation::LeftBrace
+        )
+    )
+}
+
+fn is_closer(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Punctuation(
+            Punctuation::RightParen | Punctuation::RightBracket | Punctuation::RightBrace
+        )
+    )
+}
+
+fn is_colon(token: Option<&Token>) -> bool {
+    matches!(
+        token,
+        Some(Token::Punctuation(Punctuation::Colon) | Token::Operator(Operator::Colon))
+    )
+}
+
+fn index_source(source: &str) -> FileIndex {
+    let chars: Vec<char> = source.chars().collect();
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        chars
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c == '\n')
+            .map(|(i, _)| i + 1),
+    );
+    let mut tokens = Vec::new();
+    flat_tokens(&chars, 0, &mut tokens);
+
+    // Matching bracket of every bracket token.
+    let mut matching: Vec<Option<usize>> = vec![None; tokens.len()];
+    let mut stack = Vec::new();
+    for (i, (t, _)) in tokens.iter().enumerate() {
+        if is_opener(t) {
+            stack.push(i);
+        } else if is_closer(t) {
+            if let Some(open) = stack.pop() {
+                matching[open] = Some(i);
+                matching[i] = Some(open);
+            }
+        }
+    }
+    let end_of = |open: usize| {
+        matching[open]
+            .map(|c| tokens[c].1.end)
+            .unwrap_or(chars.len())
+    };
+
+    let mut index = FileIndex {
+        line_starts,
+        ..FileIndex::default()
+    };
+    let define = |index: &mut FileIndex,
+                  name: &str,
+                  kind: SymbolKind,
+                  container: Option<String>,
+                  span: Range<usize>,
+                  scope: Option<Range<usize>>| {
+        index
+            .definition_at
+            .insert(span.start, index.definitions.len());
+        index.definitions.push(Definition {
+            name: name.to_string(),
+            kind,
+            container,
+            span,
+            scope,
+        });
+    };
+    // Identifiers bound by a bracket group: the first name of each comma-separated element,
+    // so `(a, b: int)` binds `a` and `b` and `Some(x)` binds `x`.
+    let group_bindings = |open: usize| -> Vec<usize> {
+        let Some(close) = matching[open] else {
+            return Vec::new();
+        };
+        let mut depth = 0;
+        let mut names = Vec::new();
+        for k in open + 1..close {
+            match &tokens[k].0 {
+                t if is_opener(t) => depth += 1,
+                t if is_closer(t) => depth -= 1,
+                Token::Identifier(_) if depth == 0 => {
+                    if matches!(
+                        tokens[k - 1].0,
+                        Token::Punctuation(Punctuation::LeftParen | Punctuation::Comma)
+                            | Token::Punctuation(Punctuation::LeftBracket)
+                    ) {
+                        names.push(k);
+                    }
+                }
+                _ => {}
+            }
+        }
+        names
+    };
+    // The `{` opening the block a header at `from` introduces, before any `;`.
+    let next_block = |from: usize| {
+        tokens[from..]
+            .iter()
+            .take_while(|(t, _)| *t != Token::Punctuation(Punctuation::Semicolon))
+            .position(|(t, _)| *t == Token::Punctuation(Punctuation::LeftBrace))
+            .map(|p| from + p)
+    };
+
+    let mut frames: Vec<(Frame, usize)> = Vec::new();
+    let mut pending: Option<Frame> = None;
+    for i in 0..tokens.len() {
+        let (token, span) = &tokens[i];
+        let next = tokens.get(i + 1).map(|(t, _)| t);
+        let previous = i.checked_sub(1).map(|p| &tokens[p].0);
+        let scope_end = frames.last().map(|(_, end)| *end).unwrap_or(chars.len());
+        let top_level = frames.is_empty();
+
+        match token {
+            t if is_opener(t) => {
+                let frame = if *t == Token::Punctuation(Punctuation::LeftBrace) {
+                    pending.take().unwrap_or(Frame::Block)
+                } else {
+                    Frame::Group
+                };
+                frames.push((frame, end_of(i)));
+            }
+            t if is_closer(t) => {
+                frames.pop();
+            }
+            Token::Keyword(Keyword::Service | Keyword::Struct | Keyword::Enum) => {
+                if let Some(Token::Identifier(name)) = next {
+                    let (kind, frame) = match token {
+                        Token::Keyword(Keyword::Service) => {
+                            (SymbolKind::Service, Frame::Service(name.clone()))
+                        }
+                        Token::Keyword(Keyword::Struct) => {
+                            (SymbolKind::Struct, Frame::Struct(name.clone()))
+                        }
+                        _ => (SymbolKind::Enum, Frame::Block),
+                    };
+                    define(&mut index, name, kind, None, tokens[i + 1].1.clone(), None);
+                    pending = Some(frame);
+                }
+            }
+            Token::Keyword(Keyword::Import) => {
+                let end = tokens[i..]
+                    .iter()
+                    .position(|(t, _)| *t == Token::Punctuation(Punctuation::Semicolon))
+                    .map(|p| i + p)
+                    .unwrap_or(tokens.len());
+                let path = match next {
+                    Some(Token::Literal(Literal::String(path))) => Some(path.clone()),
+                    Some(Token::Identifier(package)) if i + 2 == end => Some(package.clone()),
+                    _ => None,
+                };
+                let alias = (i + 1..end).find_map(|k| match (&tokens[k].0, tokens.get(k + 1)) {
+                    (Token::Keyword(Keyword::As), Some((Token::Identifier(alias), range))) => {
+                        Some((alias.clone(), range.clone()))
+                    }
+                    _ => None,
+                });
+                if let Some((alias, range)) = &alias {
+                    define(
+                        &mut index,
+                        alias,
+                        SymbolKind::Module,
+                        None,
+                        range.clone(),
+                        None,
+                    );
+                }
+                if let Some(path) = path {
+                    let alias = alias
+                        .map(|(a, _)| a)
+                        .unwrap_or_else(|| "module".to_string());
+                    index.imports.push((alias, None));
+                    index.import_paths.push(path);
+                }
+            }
+            Token::Keyword(Keyword::Fn) => {
+                let Some(Token::Identifier(name)) = next else {
+                    continue;
+                };
+                let name_span = tokens[i + 1].1.clone();
+                match frames.last() {
+                    Some((Frame::Service(service), _)) => define(
+                        &mut index,
+                        name,
+                        SymbolKind::Method,
+                        Some(service.clone()),
+                        name_span,
+                        None,
+                    ),
+                    None => define(
+                        &mut index,
+                        name,
+                        SymbolKind::Function,
+                        None,
+                        name_span,
+                        None,
+                    ),
+                    Some(_) => define(
+                        &mut index,
+                        name,
+                        SymbolKind::Function,
+                        None,
+                        name_span.clone(),
+                        Some(name_span.start..scope_end),
+                    ),
+                }
+                let params = i + 2;
+                if tokens.get(params).map(|(t, _)| t)
+                    != Some(&Token::Punctuation(Punctuation::LeftParen))
+                {
+                    continue;
+                }
+                let body_end = matching[params]
+                    .and_then(next_block)
+                    .map(end_of)
+                    .unwrap_or_else(|| end_of(params));
+                for k in group_bindings(params) {
+                    if let Token::Identifier(param) = &tokens[k].0 {
+                        let scope = tokens[params].1.start..body_end;
+                        define(
+                            &mut index,
+                            param,
+                            SymbolKind::Parameter,
+                            None,
+                            tokens[k].1.clone(),
+                            Some(scope),
+                        );
+                    }
+                }
+            }
+            Token::Keyword(Keyword::Let) => {
+                let mut k = i + 1;
+                if tokens.get(k).map(|(t, _)| t) == Some(&Token::Keyword(Keyword::Mut)) {
+                    k += 1;
+                }
+                let bound: Vec<usize> = match tokens.get(k).map(|(t, _)| t) {
+                    Some(Token::Identifier(_)) => vec![k],
+                    Some(t) if is_opener(t) => {
+                        let close = matching[k].unwrap_or(tokens.len());
+                        (k + 1..close)
+                            .filter(|&j| {
+                                matches!(tokens[j].0, Token::Identifier(_))
+                                    && !is_colon(tokens.get(j + 1).map(|(t, _)| t))
+                            })
+                            .collect()
+                    }
+                    _ => Vec::new(),
+                };
+                for j in bound {
+                    if let Token::Identifier(name) = &tokens[j].0 {
+                        let scope = (!top_level).then_some(span.start..scope_end);
+                        define(
+                            &mut index,
+                            name,
+                            SymbolKind::Variable,
+                            None,
+                            tokens[j].1.clone(),
+                            scope,
+                        );
+                    }
+                }
+            }
+            Token::Keyword(Keyword::For) => {
+                let bound: Vec<usize> = match next {
+                    Some(Token::Identifier(_)) => vec![i + 1],
+                    Some(Token::Punctuation(Punctuation::LeftParen)) => group_bindings(i + 1),
+                    _ => Vec::new(),
+                };
+                let end = next_block(i + 1).map(end_of).unwrap_or(scope_end);
+                for j in bound {
+                    if let Token::Identifier(name) = &tokens[j].0 {
+                        define(
+                            &mut index,
+                            name,
+                            SymbolKind::Variable,
+                            None,
+                            tokens[j].1.clone(),
+                            Some(span.start..end),
+                        );
+                    }
+                }
+            }
+            Token::Keyword(Keyword::Catch) => {
+                // `catch (Type e)`, `catch (e: Type)` or `catch (e)`; a capitalized lone name is
+                // a type, as in the parser.
+                if next != Some(&Token::Punctuation(Punctuation::LeftParen)) {
+                    continue;
+                }
+                let variable = match (tokens.get(i + 2), tokens.get(i + 3)) {
+                    (Some((Token::Identifier(_), _)), Some((Token::Identifier(_), _))) => {
+                        Some(i + 3)
+                    }
+                    (Some((Token::Identifier(first), _)), third)
+                        if is_colon(third.map(|(t, _)| t))
+                            || !first.starts_with(|c: char| c.is_ascii_uppercase()) =>
+                    {
+                        Some(i + 2)
+                    }
+                    _ => None,
+                };
+                let end = next_block(i + 1).map(end_of).unwrap_or(scope_end);
+                if let Some(j) = variable {
+                    if let Token::Identifier(name) = &tokens[j].0 {
+                        define(
+                            &mut index,
+                            name,
+                            SymbolKind::Variable,
+                            None,
+                            tokens[j].1.clone(),
+                            Some(span.start..end),
+                        );
+                    }
+                }
+            }
+            Token::Punctuation(Punctuation::FatArrow) => {
+                // Closure parameters and match-arm bindings: `(a, b) => ...`, `Some(x) => ...`,
+                // `x => ...`; visible to the end of the body, the next `;` or `,` outside brackets.
+                let bound = match previous {
+                    Some(Token::Punctuation(Punctuation::RightParen)) => matching[i - 1]
+                        .map(|open| (tokens[open].1.start, group_bindings(open)))
+                        .unwrap_or((span.start, Vec::new())),
+                    Some(Token::Identifier(_))
+                        if !matches!(
+                            tokens.get(i.wrapping_sub(2)).map(|(t, _)| t),
+                            Some(Token::Punctuation(Punctuation::DoubleColon))
+                        ) =>
+                    {
+                        (tokens[i - 1].1.start, vec![i - 1])
+                    }
+                    _ => (span.start, Vec::new()),
+                };
+                let mut depth = 0usize;
+                let body_end = tokens[i + 1..]
+                    .iter()
+                    .find(|(t, _)| {
+                        if is_opener(t) {
+                            depth += 1;
+                        } else if is_closer(t) {
+                            if depth == 0 {
+                                return true;
+                            }
+                            depth -= 1;
+                        } else if depth == 0
+                            && matches!(
+                                t,
+                                Token::Punctuation(Punctuation::Semicolon | Punctuation::Comma)
+                            )
+                        {
+                            return true;
+                        }
+                        false
+                    })
+                    .map(|(_, r)| r.start)
+                    .unwrap_or(scope_end)
+                    .min(scope_end);
+                for j in bound.1 {
+                    if let Token::Identifier(name) = &tokens[j].0 {
+                        define(
+                            &mut index,
+                            name,
+                            SymbolKind::Parameter,
+                            None,
+                            tokens[j].1.clone(),
+                            Some(bound.0..body_end),
+                        );
+                    }
+                }
+            }
+            Token::Identifier(name) => {
+                if matches!(previous, Some(Token::Punctuation(Punctuation::At))) {
+                    continue;
+                }
+                let followed_by_colon = is_colon(next);
+                let at_member_start = matches!(
+                    previous,
+                    Some(Token::Punctuation(
+                        Punctuation::LeftBrace
+                            | Punctuation::RightBrace
+                            | Punctuation::Semicolon
+                            | Punctuation::Comma
+                            | Punctuation::RightParen
+                    ))
+                );
+                match frames.last() {
+                    Some((Frame::Service(owner) | Frame::Struct(owner), _))
+                        if followed_by_colon && at_member_start =>
+                    {
+                        define(
+                            &mut index,
+                            name,
+                            SymbolKind::Field,
+                            Some(owner.clone()),
+                            span.clone(),
+                            None,
+                        );
+                    }
+                    // A key in a map or struct literal.
+                    Some((Frame::Block, _))
+                        if followed_by_colon
+                            && matches!(
+                                previous,
+                                Some(Token::Punctuation(
+                                    Punctuation::LeftBrace | Punctuation::Comma
+                                ))
+                            ) =>
+                    {
+                        continue;
+                    }
+                    _ => {}
+                }
+                let service = frames.iter().rev().find_map(|(f, _)| match f {
+                    Frame::Service(s) => Some(s.clone()),
+                    _ => None,
+                });
+                let qualifier = match previous {
+                    Some(
+                        Token::Operator(Operator::Dot | Operator::OptionalChain)
+                        | Token::Punctuation(Punctuation::Dot),
+                    ) => {
+                        let on_self = i >= 2 && tokens[i - 2].0 == Token::Keyword(Keyword::Self_);
+                        Qualifier::Member(service.filter(|_| on_self))
+                    }
+                    Some(Token::Punctuation(Punctuation::DoubleColon)) => {
+                        match i.checked_sub(2).map(|p| &tokens[p].0) {
+                            Some(Token::Identifier(prefix)) => Qualifier::Path(prefix.clone()),
+                            _ => continue,
+                        }
+                    }
+                    _ => Qualifier::None(service),
+                };
+                index.names.push(NameRef {
+                    name: name.clone(),
+                    span: span.clone(),
+                    qualifier,
+                });
+            }
+            _ => {}
+        }
+    }
+    index
+}
@@ -1,4 +1,31 @@
 use crate::lexer::tokens::*;
+use crate::runtime::numeric::{Decimal, I256, U256};
+
+/// `text` (an `...i256` literal) negated, if that fits in an i256.
+fn negated_i256(text: &str) -> Option<I256> {
+    let digits = text.strip_suffix("i256")?;
+    I256::from_dec_str(&format!("-{}", digits))
+}
+
+/// Whether the last token is a `-` that negates what follows rather than subtracting.
+fn ends_with_unary_minus(
+    tokens: &[(
+        crate::lexer::tokens::TokenWithPosition,
+        std::ops::Range<usize>,
+    )],
+) -> bool {
+    let mut previous = tokens.iter().rev().map(|(t, _)| &t.token);
+    previous.next() == Some(&Token::Operator(Operator::Minus))
+        && !matches!(
+            previous.next(),
+            Some(
+                Token::Literal(_)
+                    | Token::Identifier(_)
+                    | Token::Template(_)
+                    | Token::Punctuation(Punctuation::RightParen | Punctuation::RightBracket)
+            )
+        )
+}
 
 #[derive(Debug)]
 pub struct Lexer {
@@ -31,6 +58,21 @@ impl Lexer {
     pub fn tokenize_with_positions_immutable(
         &self,
     ) -> Result<Vec<crate::lexer::tokens::TokenWithPosition>, LexerError> {
+        self.tokenize_with_spans()
+            .map(|tokens| tokens.into_iter().map(|(token, _)| token).collect())
+    }
+
+    /// Tokens with positions and the range of input chars each one covers. The chars between
+    /// two ranges are whitespace and comments; `EOF` gets an empty range at the end.
+    pub fn tokenize_with_spans(
+        &self,
+    ) -> Result<
+        Vec<(
+            crate::lexer::tokens::TokenWithPosition,
+            std::ops::Range<usize>,
+        )>,
+        LexerError,
+    > {
         let mut tokens = Vec::new();
         let mut position = self.position;
         let mut line = self.line;
@@ -75,8 +117,26 @@ impl Lexer {
             }
 
             // Get the next token
-            let (new_pos, new_line, new_col, token) =
-                self.next_token_immutable(position, line, column)?;
+            let next = self.next_token_immutable(position, line, column);
+            if let Err(LexerError::InvalidNumber(text, ..)) = &next {
+                // `-<2^255>i256` is i256::MIN, whose magnitude alone does not fit in an i256.
+                if let Some(min) = negated_i256(text).filter(|_| ends_with_unary_minus(&tokens)) {
+                    let (minus, range) = tokens.pop().expect("checked by ends_with_unary_minus");
+                    let width = text.chars().count();
+                    tokens.push((
+                        crate::lexer::tokens::TokenWithPosition::new(
+                            Token::Literal(Literal::I256(min)),
+                            minus.line,
+                            minus.column,
+                        ),
+                        range.start..position + width,
+                    ));
+                    position += width;
+                    column += width;
+                    continue;
+                }
+            }
+            let (new_pos, new_line, new_col, token) = next?;
 
             // Safety check: ensure position advances (prevents infinite loops from mutations)
             if new_pos <= position {
@@ -84,18 +144,18 @@ impl Lexer {
             }
 
             // Store the token with its position (before advancing)
-            tokens.push(crate::lexer::tokens::TokenWithPosition::new(
-                token, line, column,
+            tokens.push((
+                crate::lexer::tokens::TokenWithPosition::new(token, line, column),
+                position..new_pos,
             ));
             position = new_pos;
             line = new_line;
             column = new_col.max(1); // Ensure column is always >= 1
         }
 
-        tokens.push(crate::lexer::tokens::TokenWithPosition::new(
-            Token::EOF,
-            line,
-            column,
+        tokens.push((
+            crate::lexer::tokens::TokenWithPosition::new(Token::EOF, line, column),
+            self.input.len()..self.input.len(),
         ));
         Ok(tokens)
     }
@@ -125,6 +185,18 @@ impl Lexer {
         let mut new_column = column.max(1); // Ensure column is always >= 1
 
         match ch {
+            // Byte-string literal: b"..."
+            'b' if self.input.get(position + 1) == Some(&'"') => {
+                let (pos, final_line, final_col, bytes) =
+                    self.read_bytes_literal(position + 1, new_line, new_column + 1)?;
+                Ok((
+                    pos,
+                    final_line,
+                    final_col,
+                    Token::Literal(Literal::Bytes(bytes)),
+                ))
+            }
+
             // Identifiers and keywords (include $ for SQL placeholders in strings, identifiers)
             'a'..='z' | 'A'..='Z' | '_' | '$' => {
                 let (pos, identifier) = self.read_identifier_immutable(position);
@@ -178,24 +250,30 @@ impl Lexer {
                 }
             }
 
+            // Hex byte literal: 0xdeadbeef
+            '0' if matches!(self.input.get(position + 1), Some('x') | Some('X')) => {
+                let (pos, bytes) = self.read_hex_bytes_immutable(position, new_line, new_column)?;
+                Ok((
+                    pos,
+                    new_line,
+                    new_column,
+                    Token::Literal(Literal::Bytes(bytes)),
+                ))
+            }
+
             // Numbers
             '0'..='9' => {
-                let (pos, literal) = self.read_number_immutable(position)?;
+                let (pos, literal) = self.read_number_immutable(position, new_line, new_column)?;
                 new_position = pos;
                 Ok((new_position, new_line, new_column, Token::Literal(literal)))
             }
 
             // Strings
             '"' => {
-                let (pos, final_line, final_col, string) =
+                let (pos, final_line, final_col, token) =
                     self.read_string_immutable_with_positions(position, new_line, new_column)?;
                 new_position = pos;
-                Ok((
-                    new_position,
-                    final_line,
-                    final_col,
-                    Token::Literal(Literal::String(string)),
-                ))
+                Ok((new_position, final_line, final_col, token))
             }
 
             // Operators and punctuation
@@ -394,6 +472,15 @@ impl Lexer {
                         new_column,
                         Token::Operator(Operator::Or),
                     ))
+                } else if position + 1 < self.input.len() && self.input[position + 1] == '>' {
+                    new_position += 2;
+                    new_column += 2;
+                    Ok((
+                        new_position,
+                        new_line,
+                        new_column,
+                        Token::Operator(Operator::Pipeline),
+                    ))
                 } else {
                     Err(LexerError::UnexpectedCharacter(ch, line, column))
                 }
@@ -444,14 +531,25 @@ impl Lexer {
                 }
             }
             '?' => {
-                new_position += 1;
-                new_column += 1;
-                Ok((
-                    new_position,
-                    new_line,
-                    new_column,
-                    Token::Punctuation(Punctuation::Question),
-                ))
+                // `?.` and `??` before the postfix `?`
+                let operator = match self.input.get(position + 1) {
+                    Some('.') => Some(Operator::OptionalChain),
+                    Some('?') => Some(Operator::NullCoalesce),
+                    _ => None,
+                };
+                let token = match operator {
+                    Some(operator) => {
+                        new_position += 2;
+                        new_column += 2;
+                        Token::Operator(operator)
+                    }
+                    None => {
+                        new_position += 1;
+                        new_column += 1;
+                        Token::Punctuation(Punctuation::Question)
+                    }
+                };
+                Ok((new_position, new_line, new_column, token))
             }
             ',' => {
                 new_position += 1;
@@ -736,6 +834,8 @@ impl Lexer {
             ("msg", Keyword::Msg),
             ("event", Keyword::Event),
             ("service", Keyword::Service),
+            ("trait", Keyword::Trait),
+            ("impl", Keyword::Impl),
             ("with", Keyword::With),
             ("async", Keyword::Async),
             ("try", Keyword::Try),
@@ -802,7 +902,12 @@ impl Lexer {
         (position, identifier)
     }
 
-    fn read_number_immutable(&self, mut position: usize) -> Result<(usize, Literal), LexerError> {
+    fn read_number_immutable(
+        &self,
+        mut position: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<(usize, Literal), LexerError> {
         let start = position;
         let mut has_decimal = false;
 
@@ -837,17 +942,110 @@ impl Lexer {
 
         let number_str: String = self.input[start..position].iter().collect();
 
+        // Wide literal suffixes: `100u256`, `5i256`, `1.5d`.
+        let suffix_end = (position..self.input.len())
+            .find(|&i| !self.input[i].is_ascii_alphanumeric() && self.input[i] != '_')
+            .unwrap_or(self.input.len());
+        let suffix: String = self.input[position..suffix_end].iter().collect();
+        let wide = match suffix.as_str() {
+            // Integer suffixes on a fractional number (`1.5u256`) are rejected.
+            "u256" | "i256" if has_decimal => Some(None),
+            "u256" => Some(U256::from_dec_str(&number_str).ok().map(Literal::U256)),
+            "i256" => Some(I256::from_dec_str(&number_str).map(Literal::I256)),
+            "d" => Some(Decimal::from_str_exact(&number_str).map(Literal::Decimal)),
+            _ => None,
+        };
+        if let Some(literal) = wide {
+            return literal
+                .map(|literal| (suffix_end, literal))
+                .ok_or_else(|| LexerError::InvalidNumber(number_str + &suffix, line, column));
+        }
+
         if has_decimal {
             match number_str.parse::<f64>() {
                 Ok(num) => Ok((position, Literal::Float(num))),
-                Err(_) => Err(LexerError::InvalidNumber(number_str, 0, 0)),
+                Err(_) => Err(LexerError::InvalidNumber(number_str, line, column)),
             }
         } else {
             match number_str.parse::<i64>() {
                 Ok(num) => Ok((position, Literal::Int(num))),
-                Err(_) => Err(LexerError::InvalidNumber(number_str, 0, 0)),
+                Err(_) => Err(LexerError::InvalidNumber(number_str, line, column)),
+            }
+        }
+    }
+
+    /// `0x` followed by an even number of hex digits.
+    fn read_hex_bytes_immutable(
+        &self,
+        position: usize,
+        line: usize,
+        column: usize,
+    ) -> Result<(usize, Vec<u8>), LexerError> {
+        let end = (position + 2..self.input.len())
+            .find(|&i| !self.input[i].is_ascii_alphanumeric() && self.input[i] != '_')
+            .unwrap_or(self.input.len());
+        let text: String = self.input[position..end].iter().collect();
+        hex::decode(&text[2..])
+            .map(|bytes| (end, bytes))
+            .map_err(|_| LexerError::InvalidNumber(text, line, column))
+    }
+
+    /// Body of `b"..."` starting at the opening quote. Characters are UTF-8 encoded; escapes
+    /// are those of strings plus `\0` and `\xNN`.
+    fn read_bytes_literal(
+        &self,
+        mut position: usize,
+        mut line: usize,
+        mut column: usize,
+    ) -> Result<(usize, usize, usize, Vec<u8>), LexerError> {
+        position += 1; // Skip opening quote
+        column += 1;
+        let mut bytes = Vec::new();
+        while position < self.input.len() {
+            let ch = self.input[position];
+            position += 1;
+            column += 1;
+            match ch {
+                '"' => return Ok((position, line, column, bytes)),
+                '\\' => {
+                    let escape = *self
+                        .input
+                        .get(position)
+                        .ok_or(LexerError::UnterminatedString(line, column))?;
+                    position += 1;
+                    column += 1;
+                    match escape {
+                        '0' => bytes.push(0),
+                        'x' => {
+                            let digits: String = self.input.iter().skip(position).take(2).collect();
+                            let byte = u8::from_str_radix(&digits, 16)
+                                .ok()
+                                .filter(|_| digits.len() == 2)
+                                .ok_or(LexerError::UnexpectedCharacter('x', line, column))?;
+                            bytes.push(byte);
+                            position += 2;
+                            column += 2;
+                        }
+                        other => {
+                            let mut buf = [0u8; 4];
+                            bytes.extend_from_slice(
+                                decode_escape(other).encode_utf8(&mut buf).as_bytes(),
+                            );
+                        }
+                    }
+                }
+                '\n' => {
+                    line += 1;
+                    column = 1;
+                    bytes.push(b'\n');
+                }
+                other => {
+                    let mut buf = [0u8; 4];
+                    bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+                }
             }
         }
+        Err(LexerError::UnterminatedString(line, column))
     }
 
     fn read_string_immutable(&self, mut position: usize) -> Result<(usize, String), LexerError> {
@@ -873,56 +1071,211 @@ impl Lexer {
         Err(LexerError::UnterminatedString(0, 0))
     }
 
+    /// Read a `"..."` or `"""..."""` literal starting at its opening quote. Triple-quoted literals
+    /// may span lines and are dedented (see `dedent`). Content without `${...}` is a string
+    /// literal; otherwise the token is a `Token::Template` whose code is lexed in place, so its
+    /// tokens carry source positions. `\$` keeps a literal `$`.
     fn read_string_immutable_with_positions(
         &self,
         mut position: usize,
         mut line: usize,
         mut column: usize,
-    ) -> Result<(usize, usize, usize, String), LexerError> {
-        position += 1; // Skip opening quote
-        column += 1;
-        let mut string = String::new();
-        while position < self.input.len() {
-            let ch = self.input[position];
-            if ch == '"' {
-                position += 1; // Skip closing quote
-                column += 1;
-                return Ok((position, line, column, string));
-            } else if ch == '\\' {
-                position += 1;
-                column += 1;
-                if position < self.input.len() {
-                    let next_ch = self.input[position];
-                    if next_ch == '\n' {
-                        line += 1;
-                        column = 1;
-                    } else {
-                        column += 1;
+    ) -> Result<(usize, usize, usize, Token), LexerError> {
+        const TRIPLE_QUOTE: &[char] = &['"', '"', '"'];
+        let triple = self.input.get(position..position + 3) == Some(TRIPLE_QUOTE);
+        let quote_len = if triple { 3 } else { 1 };
+        position += quote_len; // Skip opening quote
+        column += quote_len;
+        let mut pieces = Vec::new();
+        loop {
+            let Some(&ch) = self.input.get(position) else {
+                return Err(LexerError::UnterminatedString(line, column));
+            };
+            if ch == '"'
+                && (!triple || self.input.get(position..position + 3) == Some(TRIPLE_QUOTE))
+            {
+                position += quote_len; // Skip closing quote
+                column += quote_len;
+                break;
+            }
+            match ch {
+                '\\' => {
+                    position += 1;
+                    column += 1;
+                    if let Some(&next_ch) = self.input.get(position) {
+                        if next_ch == '\n' {
+                            line += 1;
+                            column = 1;
+                        } else {
+                            column += 1;
+                        }
+                        pieces.push(StringPiece::Escape(next_ch));
+                        position += 1;
                     }
-                    string.push(decode_escape(next_ch));
+                }
+                '$' if self.input.get(position + 1) == Some(&'{') => {
+                    let start = position + 2;
+                    let (end, end_line, end_column) =
+                        self.find_interpolation_end(start, line, column + 2)?;
+                    pieces.push(StringPiece::Code {
+                        start,
+                        end,
+                        line,
+                        column: column + 2,
+                    });
+                    position = end + 1; // Skip closing brace
+                    line = end_line;
+                    column = end_column + 1;
+                }
+                '\n' => {
                     position += 1;
+                    line += 1;
+                    column = 1;
+                    pieces.push(StringPiece::Char('\n'));
                 }
-            } else if ch == '\n' {
-                position += 1;
-                line += 1;
-                column = 1;
-                string.push(ch);
-            } else if ch == '\r' {
-                position += 1;
-                if position < self.input.len() && self.input[position] == '\n' {
+                '\r' => {
+                    position += 1;
+                    if self.input.get(position) == Some(&'\n') {
+                        position += 1;
+                    }
+                    line += 1;
+                    column = 1;
+                    pieces.push(StringPiece::Char(if triple { '\n' } else { '\r' }));
+                }
+                _ => {
+                    position += 1;
+                    column += 1;
+                    pieces.push(StringPiece::Char(ch));
+                }
+            }
+        }
+        if triple {
+            pieces = dedent(pieces);
+        }
+        Ok((position, line, column, self.string_token(pieces)?))
+    }
+
+    /// Find the `}` closing a `${` whose code starts at `position`, skipping nested braces and
+    /// string literals. Returns its index, line and column.
+    fn find_interpolation_end(
+        &self,
+        mut position: usize,
+        mut line: usize,
+        mut column: usize,
+    ) -> Result<(usize, usize, usize), LexerError> {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        while let Some(&ch) = self.input.get(position) {
+            match ch {
+                '\\' if in_string => {
                     position += 1;
+                    column += 1;
                 }
+                '"' => in_string = !in_string,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    if depth == 0 {
+                        return Ok((position, line, column));
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            if self.input.get(position) == Some(&'\n') {
                 line += 1;
                 column = 1;
-                string.push('\r');
             } else {
-                position += 1;
                 column += 1;
-                string.push(ch);
             }
+            position += 1;
         }
         Err(LexerError::UnterminatedString(line, column))
     }
+
+    /// Decode escapes and lex each `${...}`, returning a string literal when there is no code.
+    fn string_token(&self, pieces: Vec<StringPiece>) -> Result<Token, LexerError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        for piece in pieces {
+            match piece {
+                StringPiece::Char(ch) => text.push(ch),
+                StringPiece::Escape(ch) => text.push(decode_escape(ch)),
+                StringPiece::Code {
+                    start,
+                    end,
+                    line,
+                    column,
+                } => {
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    let code = Lexer {
+                        input: self.input[start..end].to_vec(),
+                        position: 0,
+                        line,
+                        column,
+                    };
+                    parts.push(TemplatePart::Code(
+                        code.tokenize_with_positions_immutable()?,
+                    ));
+                }
+            }
+        }
+        if parts.is_empty() {
+            return Ok(Token::Literal(Literal::String(text)));
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        Ok(Token::Template(parts))
+    }
+}
+
+/// One unit of string literal content, before escapes are decoded and `${...}` is lexed.
+#[derive(Debug, Clone)]
+enum StringPiece {
+    Char(char),
+    /// The character after a backslash.
+    Escape(char),
+    /// `${...}`: the input range between the braces and the position where it starts.
+    Code {
+        start: usize,
+        end: usize,
+        line: usize,
+        column: usize,
+    },
+}
+
+/// Dedent triple-quoted content: drop a blank first and last line, empty the other blank lines
+/// and remove the indentation shared by the rest. Escapes and `${...}` count as content.
+fn dedent(pieces: Vec<StringPiece>) -> Vec<StringPiece> {
+    let is_indent = |piece: &StringPiece| matches!(piece, StringPiece::Char(' ' | '\t'));
+    let is_blank = |line: &[StringPiece]| line.iter().all(is_indent);
+    let mut lines: Vec<&[StringPiece]> = pieces
+        .split(|piece| matches!(piece, StringPiece::Char('\n')))
+        .collect();
+    if lines.len() > 1 && is_blank(lines[0]) {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && lines.last().is_some_and(|line| is_blank(line)) {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|line| !is_blank(line))
+        .map(|line| line.iter().take_while(|piece| is_indent(piece)).count())
+        .min()
+        .unwrap_or(0);
+    let mut out = Vec::new();
+    for (i, line) in lines.into_iter().enumerate() {
+        if i > 0 {
+            out.push(StringPiece::Char('\n'));
+        }
+        if !is_blank(line) {
+            out.extend(line[indent..].iter().cloned());
+        }
+    }
+    out
 }
 
 /// Decode a single escape sequence character (the char after the backslash).
@@ -1,3 +1,4 @@
+use crate::runtime::numeric::{Decimal, I256, U256};
 use std::collections::HashMap;
 use std::fmt;
 
@@ -403,8 +404,11 @@ pub enum Operator {
     Assign,
     Not,
     Colon,
-    NotEqual, // != operator
-    Dot,      // . operator
+    NotEqual,      // != operator
+    Dot,           // . operator
+    OptionalChain, // ?. operator
+    NullCoalesce,  // ?? operator
+    Pipeline,      // |> operator
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -437,6 +441,14 @@ pub enum Literal {
     String(String),
     Bool(bool),
     Null,
+    /// `100u256`
+    U256(U256),
+    /// `5i256` (a leading `-` is a unary minus)
+    I256(I256),
+    /// `1.5d`
+    Decimal(Decimal),
+    /// `b"..."` or `0x...`
+    Bytes(Vec<u8>),
 }
 
 // Token enum variants for compatibility with parser
@@ -457,12 +469,23 @@ pub enum Token {
     // Identifier variant
     Identifier(String),
 
+    // Interpolated string: "Hello ${name}"
+    Template(Vec<TemplatePart>),
+
     // Special token
     EOF,
 }
 
+/// A piece of an interpolated string literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+    Text(String),
+    /// The tokens of a `${...}` expression, ending in `EOF`, with their source positions.
+    Code(Vec<TokenWithPosition>),
+}
+
 // Token with position information for accurate error reporting
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TokenWithPosition {
     pub token: Token,
     pub line: usize,
@@ -517,6 +540,7 @@ impl fmt::Display for Token {
             Token::Punctuation(p) => write!(f, "Punctuation({:?})", p),
             Token::Literal(l) => write!(f, "Literal({:?})", l),
             Token::Identifier(i) => write!(f, "Identifier({})", i),
+            Token::Template(parts) => write!(f, "Template({} parts)", parts.len()),
             Token::EOF => write!(f, "EOF"),
         }
     }
@@ -41,8 +41,10 @@ pub mod cli;
 pub mod cli_design;
 pub mod compile;
 pub mod dal_summary;
+pub mod dap;
 pub mod ffi;
 pub mod fleet;
+pub mod formatter;
 pub mod http_server;
 pub mod http_server_converters;
 pub mod http_server_handlers;
@@ -156,9 +158,19 @@ pub fn execute_source(source: &str) -> Result<Value, Box<dyn std::error::Error>>
 pub fn execute_dal_with_scope(
     vars: &std::collections::HashMap<String, Value>,
     source: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    execute_dal_with_scope_and_budget(vars, source, None)
+}
+
+/// Like `execute_dal_with_scope`, metered against `budget` when given.
+pub fn execute_dal_with_scope_and_budget(
+    vars: &std::collections::HashMap<String, Value>,
+    source: &str,
+    budget: Option<runtime::metering::Budget>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let program = parse_source(source)?;
     let mut runtime = Runtime::new();
+    runtime.set_budget(budget);
     for (k, v) in vars {
         runtime.set_variable(k.clone(), v.clone());
     }
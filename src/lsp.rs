@@ -3,23 +3,44 @@
 
 #![cfg(feature = "lsp")]
 
+use dist_agent_lang::formatter::{format_source, FormatConfig};
+use dist_agent_lang::ide::code_actions;
+use dist_agent_lang::ide::diagnostics::Diagnostic as IdeDiagnostic;
+use dist_agent_lang::ide::semantic::{
+    self, InlayHint as IdeInlayHint, InlayHintKind as IdeInlayHintKind, SemanticKind,
+    SemanticToken as IdeSemanticToken,
+};
+use dist_agent_lang::ide::workspace::{
+    SymbolKind as DalSymbolKind, SymbolLocation, WorkspaceIndex,
+};
+use dist_agent_lang::lexer::tokens::{Keyword, Punctuation, Token};
 use dist_agent_lang::lexer::Lexer;
 use dist_agent_lang::parser::ast::Statement;
 use dist_agent_lang::parser::Parser;
+use dist_agent_lang::stdlib::signatures::{self, StdlibFunction};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tower_lsp::{
     async_trait,
     lsp_types::{
-        CompletionItem, CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
-        DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
-        DidOpenTextDocumentParams, GotoDefinitionParams, GotoDefinitionResponse, Hover,
+        CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+        CodeActionProviderCapability, CodeActionResponse, CompletionItem, CompletionOptions,
+        CompletionParams, CompletionResponse, Diagnostic, DiagnosticSeverity, DiagnosticTag,
+        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+        DocumentFormattingParams, GotoDefinitionParams, GotoDefinitionResponse, Hover,
         HoverContents, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
-        InitializedParams, Location, OneOf, ParameterInformation, Position, Range,
+        InitializedParams, InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams,
+        InsertTextFormat, Location, OneOf, ParameterInformation, Position, Range, ReferenceParams,
+        RenameParams, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+        SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+        SemanticTokensParams, SemanticTokensResult, SemanticTokensServerCapabilities,
         ServerCapabilities, SignatureHelp, SignatureHelpOptions, SignatureHelpParams,
-        SignatureInformation, TextDocumentSyncCapability, TextDocumentSyncKind,
-        TextDocumentSyncOptions, Url,
+        SignatureInformation, SymbolInformation, SymbolKind, TextDocumentSyncCapability,
+        TextDocumentSyncKind, TextDocumentSyncOptions, TextEdit, Url, WorkspaceEdit,
+        WorkspaceSymbolParams,
     },
     Client, LanguageServer, LspService, Server,
 };
@@ -28,6 +49,10 @@ use tower_lsp::{
 pub struct Backend {
     client: Client,
     documents: Arc<Mutex<HashMap<Url, DocumentState>>>,
+    /// Definitions and references across the workspace's files; open buffers override disk.
+    workspace: Arc<Mutex<WorkspaceIndex>>,
+    /// Whether every `.dal` file under the workspace root has been indexed.
+    workspace_indexed: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,6 +65,119 @@ impl Backend {
         Self {
             client,
             documents: Arc::new(Mutex::new(HashMap::new())),
+            workspace: Arc::new(Mutex::new(WorkspaceIndex::new())),
+            workspace_indexed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Workspace index key for a document: its canonical file path, or the URI itself for
+    /// documents that are not files.
+    fn uri_to_path(uri: &Url) -> PathBuf {
+        match uri.to_file_path() {
+            Ok(path) => path.canonicalize().unwrap_or(path),
+            Err(_) => PathBuf::from(uri.as_str()),
+        }
+    }
+
+    fn path_to_uri(path: &Path) -> Option<Url> {
+        if path.is_absolute() {
+            Url::from_file_path(path).ok()
+        } else {
+            Url::parse(path.to_str()?).ok()
+        }
+    }
+
+    fn to_lsp_location(location: &SymbolLocation) -> Option<Location> {
+        let line = location.line as usize;
+        let column = location.column as usize;
+        Some(Location {
+            uri: Self::path_to_uri(&location.path)?,
+            range: Range {
+                start: Self::to_lsp_position(line, column),
+                end: Self::to_lsp_position(line, column + location.length as usize),
+            },
+        })
+    }
+
+    fn ide_to_lsp_diagnostic(d: &IdeDiagnostic) -> Diagnostic {
+        let warning = d.severity == "warning";
+        Diagnostic {
+            range: Range {
+                start: Self::to_lsp_position(d.line as usize, d.column as usize),
+                end: Self::to_lsp_position(d.end_line as usize, d.end_column as usize),
+            },
+            severity: Some(if warning {
+                DiagnosticSeverity::WARNING
+            } else {
+                DiagnosticSeverity::ERROR
+            }),
+            source: Some("dal".to_string()),
+            message: d.message.clone(),
+            tags: (warning && d.message.starts_with("unused variable"))
+                .then(|| vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        }
+    }
+
+    /// Make sure the index has the workspace's files and everything `path` imports.
+    async fn ensure_workspace(&self, path: Option<&Path>) {
+        let mut workspace = self.workspace.lock().await;
+        if !self.workspace_indexed.swap(true, Ordering::SeqCst) {
+            if let Some(root) = workspace.root().map(Path::to_path_buf) {
+                workspace.index_directory(&root);
+            }
+        }
+        if let Some(path) = path {
+            workspace.load_imports(path);
+        }
+    }
+
+    /// Quick fixes from `ide::code_actions` for diagnostics on the lines of `range`.
+    fn code_actions_for(source: &str, uri: &Url, range: Range) -> Vec<CodeActionOrCommand> {
+        code_actions::quick_fixes(source)
+            .into_iter()
+            .filter(|fix| {
+                let line = fix.diagnostic.line.saturating_sub(1);
+                range.start.line <= line && line <= range.end.line
+            })
+            .map(|fix| {
+                let edits = fix
+                    .edits
+                    .iter()
+                    .map(|e| TextEdit {
+                        range: Range {
+                            start: Self::to_lsp_position(e.line as usize, e.column as usize),
+                            end: Self::to_lsp_position(e.end_line as usize, e.end_column as usize),
+                        },
+                        new_text: e.new_text.clone(),
+                    })
+                    .collect();
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title.clone(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![Self::ide_to_lsp_diagnostic(&fix.diagnostic)]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), edits)])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// `workspace/symbol` kind for an index symbol kind.
+    fn symbol_kind(kind: DalSymbolKind) -> SymbolKind {
+        match kind {
+            DalSymbolKind::Function => SymbolKind::FUNCTION,
+            DalSymbolKind::Method => SymbolKind::METHOD,
+            DalSymbolKind::Service => SymbolKind::CLASS,
+            DalSymbolKind::Struct => SymbolKind::STRUCT,
+            DalSymbolKind::Enum => SymbolKind::ENUM,
+            DalSymbolKind::Field => SymbolKind::FIELD,
+            DalSymbolKind::Variable | DalSymbolKind::Parameter => SymbolKind::VARIABLE,
+            DalSymbolKind::Module => SymbolKind::MODULE,
         }
     }
 
@@ -77,9 +215,10 @@ impl Backend {
                 return diags;
             }
             Ok(tokens_with_pos) => {
-                // Parser
+                // Parser: every error, recovering at statement boundaries
                 let mut parser = Parser::new_with_positions(tokens_with_pos);
-                if let Err(e) = parser.parse() {
+                let (_, errors) = parser.parse_with_recovery();
+                for e in errors {
                     let line = e.line_number().unwrap_or(1);
                     let col = e.column_number().unwrap_or(1);
                     diags.push(Diagnostic {
@@ -100,6 +239,15 @@ impl Backend {
             }
         }
 
+        // Warnings (unused variables, deprecated calls) once the file parses
+        if diags.is_empty() {
+            diags.extend(
+                code_actions::warning_diagnostics(source)
+                    .iter()
+                    .map(Self::ide_to_lsp_diagnostic),
+            );
+        }
+
         diags
     }
 
@@ -144,6 +292,129 @@ impl Backend {
         Some(chars[start..end].iter().collect())
     }
 
+    /// True when 0-based line and character fall in the literal text of a string, as opposed
+    /// to code or the code inside a `${...}` interpolation.
+    fn in_string_text(source: &str, line_0: u32, char_0: u32) -> bool {
+        enum Mode {
+            Code(usize),
+            Str { triple: bool },
+        }
+        let target = source
+            .split('\n')
+            .take(line_0 as usize)
+            .map(|l| l.chars().count() + 1)
+            .sum::<usize>()
+            + char_0 as usize;
+        let chars: Vec<char> = source.chars().collect();
+        let at = |i: usize, s: &str| {
+            s.chars()
+                .enumerate()
+                .all(|(k, c)| chars.get(i + k) == Some(&c))
+        };
+        let mut stack = vec![Mode::Code(0)];
+        let mut i = 0;
+        while i < target.min(chars.len()) {
+            let nested = stack.len() > 1;
+            match stack.last_mut() {
+                Some(Mode::Code(depth)) => {
+                    if at(i, "//") {
+                        while i < chars.len() && chars[i] != '\n' {
+                            i += 1;
+                        }
+                        continue;
+                    } else if at(i, "\"\"\"") {
+                        stack.push(Mode::Str { triple: true });
+                        i += 3;
+                        continue;
+                    } else if chars[i] == '"' {
+                        stack.push(Mode::Str { triple: false });
+                    } else if chars[i] == '{' {
+                        *depth += 1;
+                    } else if chars[i] == '}' {
+                        if *depth == 0 && nested {
+                            stack.pop();
+                        } else {
+                            *depth = depth.saturating_sub(1);
+                        }
+                    }
+                    i += 1;
+                }
+                Some(Mode::Str { triple }) => {
+                    let close = if *triple { "\"\"\"" } else { "\"" };
+                    if chars[i] == '\\' {
+                        i += 2;
+                    } else if at(i, "${") {
+                        stack.push(Mode::Code(0));
+                        i += 2;
+                    } else if at(i, close) {
+                        stack.pop();
+                        i += close.len();
+                    } else {
+                        i += 1;
+                    }
+                }
+                None => break,
+            }
+        }
+        matches!(stack.last(), Some(Mode::Str { .. }))
+    }
+
+    /// Variables visible in source: `let` and `for` bindings and function parameters, with a
+    /// short detail. Lexes the given text only, so callers can pass the source before an
+    /// incomplete line.
+    fn collect_variables_from_source(source: &str) -> Vec<(String, String)> {
+        let tokens = match Lexer::new(source).tokenize() {
+            Ok(t) => t,
+            Err(_) => return Vec::new(),
+        };
+        let mut vars: Vec<(String, String)> = Vec::new();
+        let mut push = |name: &String, detail: String| {
+            if !vars.iter().any(|(n, _)| n == name) {
+                vars.push((name.clone(), detail));
+            }
+        };
+        for (i, token) in tokens.iter().enumerate() {
+            match (token, tokens.get(i + 1)) {
+                (Token::Keyword(Keyword::Let), Some(Token::Identifier(name))) => {
+                    push(name, format!("let {}", name))
+                }
+                (Token::Keyword(Keyword::For), Some(Token::Identifier(name))) => {
+                    push(name, format!("for {} in ...", name))
+                }
+                (Token::Keyword(Keyword::Fn), Some(Token::Identifier(fn_name))) => {
+                    let params = tokens[i + 2..]
+                        .iter()
+                        .take_while(|t| **t != Token::Punctuation(Punctuation::RightParen));
+                    let mut previous: Option<&Token> = None;
+                    for t in params {
+                        if let Token::Identifier(name) = t {
+                            if matches!(
+                                previous,
+                                Some(Token::Punctuation(
+                                    Punctuation::LeftParen | Punctuation::Comma
+                                ))
+                            ) {
+                                push(name, format!("parameter of fn {}", fn_name));
+                            }
+                        }
+                        previous = Some(t);
+                    }
+                }
+                _ => {}
+            }
+        }
+        vars
+    }
+
+    /// Source text before 0-based `line_0`, which lexes even while that line is being typed.
+    fn source_before_line(source: &str, line_0: u32) -> String {
+        source
+            .split('\n')
+            .take(line_0 as usize)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Short doc for a keyword (Phase 2: minimal set).
     fn keyword_doc(word: &str) -> Option<&'static str> {
         Some(match word {
@@ -163,20 +434,222 @@ impl Backend {
         })
     }
 
-    /// Short doc for a stdlib module name.
-    fn stdlib_doc(module: &str) -> Option<&'static str> {
-        Some(match module {
+    /// Doc for a stdlib namespace: a summary and its functions from the signature registry.
+    fn stdlib_doc(module: &str) -> Option<String> {
+        let functions: Vec<&str> = signatures::functions(module).map(|f| f.name).collect();
+        if functions.is_empty() {
+            return None;
+        }
+        let summary = match module {
             "chain" => "Blockchain operations: deploy, call, balance, gas, etc.",
             "ai" | "assist" => "AI/LLM: generate_text, spawn_agent, send_message, etc.",
             "log" => "Logging: info, error, warning, audit.",
             "auth" => "Authentication and authorization.",
             "config" => "Configuration and environment.",
-            "db" => "Database operations.",
+            "database" => "Database operations.",
             "crypto" => "Cryptography: hash, sign, verify, keygen.",
             "oracle" => "Oracle and external data.",
             "agent" => "Agent coordination and communication.",
-            _ => return None,
-        })
+            _ => "Standard library namespace.",
+        };
+        Some(format!(
+            "{}\n\n{} functions: {}",
+            summary,
+            functions.len(),
+            functions.join(", ")
+        ))
+    }
+
+    /// The stdlib namespace `name` refers to, through `import stdlib::ns as name` in `source`.
+    fn stdlib_namespace(source: &str, name: &str) -> Option<String> {
+        let aliased = source.lines().find_map(|line| {
+            let rest = line.trim().strip_prefix("import stdlib::")?;
+            let (namespace, alias) = rest.trim_end_matches(';').split_once(" as ")?;
+            (alias.trim() == name).then(|| namespace.trim().to_string())
+        });
+        let namespace = aliased.unwrap_or_else(|| name.to_string());
+        signatures::is_namespace(&namespace).then_some(namespace)
+    }
+
+    /// The identifier ending at char `end` of `chars`, and where it starts.
+    fn identifier_before(chars: &[char], end: usize) -> (String, usize) {
+        let start = chars[..end]
+            .iter()
+            .rposition(|c| !(c.is_alphanumeric() || *c == '_'))
+            .map(|p| p + 1)
+            .unwrap_or(0);
+        (chars[start..end].iter().collect(), start)
+    }
+
+    /// The stdlib function named by `ns::name` ending at char `end` of `chars`.
+    fn stdlib_function_before(
+        source: &str,
+        chars: &[char],
+        end: usize,
+    ) -> Option<&'static StdlibFunction> {
+        let (name, start) = Self::identifier_before(chars, end);
+        if start < 2 || chars[start - 2..start] != [':', ':'] {
+            return None;
+        }
+        let (namespace, _) = Self::identifier_before(chars, start - 2);
+        signatures::lookup(&Self::stdlib_namespace(source, &namespace)?, &name)
+    }
+
+    /// The stdlib function whose name is under the cursor in `ns::name`.
+    fn stdlib_function_at_position(
+        source: &str,
+        line_0: u32,
+        char_0: u32,
+    ) -> Option<&'static StdlibFunction> {
+        let chars: Vec<char> = source.lines().nth(line_0 as usize)?.chars().collect();
+        let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+        let at = (char_0 as usize).min(chars.len());
+        let end = at + chars[at..].iter().take_while(|c| is_word(c)).count();
+        Self::stdlib_function_before(source, &chars, end)
+    }
+
+    /// The stdlib call whose argument list holds the cursor, and the index of the argument.
+    fn stdlib_call_at_position(
+        source: &str,
+        line_0: u32,
+        char_0: u32,
+    ) -> Option<(&'static StdlibFunction, u32)> {
+        let chars: Vec<char> = source.lines().nth(line_0 as usize)?.chars().collect();
+        let at = (char_0 as usize).min(chars.len());
+        let mut depth = 0;
+        let mut active = 0;
+        for i in (0..at).rev() {
+            match chars[i] {
+                ')' | ']' | '}' => depth += 1,
+                '(' if depth == 0 => {
+                    return Some((Self::stdlib_function_before(source, &chars, i)?, active));
+                }
+                '(' | '[' | '{' => depth -= 1,
+                ',' if depth == 0 => active += 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Completions for `ns::` with the namespace's functions, each inserting a snippet with its
+    /// required parameters.
+    fn stdlib_completions(source: &str, line_0: u32, char_0: u32) -> Option<Vec<CompletionItem>> {
+        let chars: Vec<char> = source.lines().nth(line_0 as usize)?.chars().collect();
+        let at = (char_0 as usize).min(chars.len());
+        let (partial, start) = Self::identifier_before(&chars, at);
+        if start < 2 || chars[start - 2..start] != [':', ':'] {
+            return None;
+        }
+        let (namespace, _) = Self::identifier_before(&chars, start - 2);
+        let namespace = Self::stdlib_namespace(source, &namespace)?;
+        Some(
+            signatures::functions(&namespace)
+                .filter(|f| f.name.starts_with(&partial))
+                .map(|f| CompletionItem {
+                    label: f.name.to_string(),
+                    kind: Some(tower_lsp::lsp_types::CompletionItemKind::FUNCTION),
+                    detail: Some(f.signature()),
+                    documentation: (!f.doc.is_empty())
+                        .then(|| tower_lsp::lsp_types::Documentation::String(f.doc.to_string())),
+                    insert_text: Some(f.snippet()),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    /// Token types and modifiers, in the order `encode_semantic_tokens` indexes them.
+    fn semantic_tokens_legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![
+                SemanticTokenType::NAMESPACE,
+                SemanticTokenType::CLASS,
+                SemanticTokenType::STRUCT,
+                SemanticTokenType::ENUM,
+                SemanticTokenType::FUNCTION,
+                SemanticTokenType::METHOD,
+                SemanticTokenType::PARAMETER,
+                SemanticTokenType::VARIABLE,
+                SemanticTokenType::PROPERTY,
+                SemanticTokenType::DECORATOR,
+                SemanticTokenType::KEYWORD,
+                SemanticTokenType::STRING,
+                SemanticTokenType::NUMBER,
+            ],
+            token_modifiers: vec![
+                SemanticTokenModifier::DECLARATION,
+                SemanticTokenModifier::DEFAULT_LIBRARY,
+                SemanticTokenModifier::new("service"),
+                SemanticTokenModifier::new("agent"),
+            ],
+        }
+    }
+
+    /// Relative (LSP) encoding of semantic tokens; services and agents are classes with a
+    /// `service` / `agent` modifier.
+    fn encode_semantic_tokens(tokens: &[IdeSemanticToken]) -> Vec<SemanticToken> {
+        let mut encoded = Vec::with_capacity(tokens.len());
+        let (mut line, mut column) = (0, 0);
+        for t in tokens {
+            let (token_type, mut modifiers) = match t.kind {
+                SemanticKind::Namespace => (0, 0),
+                SemanticKind::Service => (1, 1 << 2),
+                SemanticKind::Agent => (1, 1 << 3),
+                SemanticKind::Struct => (2, 0),
+                SemanticKind::Enum => (3, 0),
+                SemanticKind::Function => (4, 0),
+                SemanticKind::Method => (5, 0),
+                SemanticKind::Parameter => (6, 0),
+                SemanticKind::Variable => (7, 0),
+                SemanticKind::Property => (8, 0),
+                SemanticKind::Attribute => (9, 0),
+                SemanticKind::Keyword => (10, 0),
+                SemanticKind::String => (11, 0),
+                SemanticKind::Number => (12, 0),
+            };
+            if t.declaration {
+                modifiers |= 1;
+            }
+            if t.default_library {
+                modifiers |= 1 << 1;
+            }
+            let (t_line, t_column) = (t.line - 1, t.column - 1);
+            let delta_line = t_line - line;
+            let delta_start = if delta_line == 0 {
+                t_column - column
+            } else {
+                t_column
+            };
+            encoded.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length: t.length,
+                token_type,
+                token_modifiers_bitset: modifiers,
+            });
+            (line, column) = (t_line, t_column);
+        }
+        encoded
+    }
+
+    fn to_lsp_inlay_hint(hint: &IdeInlayHint) -> InlayHint {
+        let (kind, padding_left, padding_right) = match hint.kind {
+            IdeInlayHintKind::Type => (Some(InlayHintKind::TYPE), false, false),
+            IdeInlayHintKind::Parameter => (Some(InlayHintKind::PARAMETER), false, true),
+            IdeInlayHintKind::Effect => (None, true, false),
+        };
+        InlayHint {
+            position: Self::to_lsp_position(hint.line as usize, hint.column as usize),
+            label: InlayHintLabel::String(hint.label.clone()),
+            kind,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(padding_left),
+            padding_right: Some(padding_right),
+            data: None,
+        }
     }
 
     /// Collect symbol names and details from parsed AST for completion/hover.
@@ -188,10 +661,8 @@ impl Backend {
             Err(_) => return symbols,
         };
         let mut parser = Parser::new_with_positions(tokens_with_pos);
-        let program = match parser.parse() {
-            Ok(p) => p,
-            Err(_) => return symbols,
-        };
+        // A partial program still has the symbols around syntax errors.
+        let (program, _) = parser.parse_with_recovery();
         for stmt in &program.statements {
             match stmt {
                 Statement::Service(s) => {
@@ -220,6 +691,37 @@ impl Backend {
         symbols
     }
 
+    /// Edits formatting the whole document, or `None` when it is already formatted or has
+    /// syntax errors. Uses `[fmt]` from the nearest dal.toml when there is one, else the
+    /// client's tab size.
+    fn formatting_edits(source: &str, uri: &Url, tab_size: u32) -> Option<Vec<TextEdit>> {
+        let manifest_config = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| FormatConfig::discover(&path).ok().flatten());
+        let config = manifest_config.unwrap_or_else(|| FormatConfig {
+            indent: (tab_size as usize).clamp(1, 16),
+            ..FormatConfig::default()
+        });
+        let formatted = format_source(source, &config).ok()?;
+        if formatted == source {
+            return None;
+        }
+        let last_line = source.split('\n').count() - 1;
+        let last_len = source
+            .rsplit('\n')
+            .next()
+            .map(|l| l.encode_utf16().count())
+            .unwrap_or(0);
+        Some(vec![TextEdit {
+            range: Range {
+                start: Position::new(0, 0),
+                end: Position::new(last_line as u32, last_len as u32),
+            },
+            new_text: formatted,
+        }])
+    }
+
     /// Resolve hover content for a word (keyword, stdlib, or symbol from AST).
     fn hover_for_word(&self, source: &str, word: &str) -> Option<String> {
         if let Some(doc) = Self::keyword_doc(word) {
@@ -384,8 +886,19 @@ impl Backend {
 impl LanguageServer for Backend {
     async fn initialize(
         &self,
-        _params: InitializeParams,
+        params: InitializeParams,
     ) -> tower_lsp::jsonrpc::Result<InitializeResult> {
+        #[allow(deprecated)]
+        let root = params
+            .workspace_folders
+            .and_then(|folders| folders.into_iter().next())
+            .map(|folder| folder.uri)
+            .or(params.root_uri)
+            .and_then(|uri| uri.to_file_path().ok());
+        if let Some(root) = root {
+            let root = root.canonicalize().unwrap_or(root);
+            *self.workspace.lock().await = WorkspaceIndex::new().with_root(root);
+        }
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -402,6 +915,22 @@ impl LanguageServer for Backend {
                     ..Default::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: Self::semantic_tokens_legend(),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            range: None,
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 signature_help_provider: Some(SignatureHelpOptions {
                     trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
                     ..Default::default()
@@ -437,6 +966,10 @@ impl LanguageServer for Backend {
             let mut docs = self.documents.lock().await;
             docs.insert(uri.clone(), DocumentState { text: text.clone() });
         }
+        self.workspace
+            .lock()
+            .await
+            .update_file(&Self::uri_to_path(&uri), &text);
         self.publish_diagnostics_for_uri(uri, version).await;
     }
 
@@ -454,6 +987,10 @@ impl LanguageServer for Backend {
             let mut docs = self.documents.lock().await;
             docs.insert(uri.clone(), DocumentState { text: text.clone() });
         }
+        self.workspace
+            .lock()
+            .await
+            .update_file(&Self::uri_to_path(&uri), &text);
         self.publish_diagnostics_for_uri(uri, version).await;
     }
 
@@ -463,6 +1000,14 @@ impl LanguageServer for Backend {
             let mut docs = self.documents.lock().await;
             docs.remove(&uri);
         }
+        // Back to the file on disk, if there is one.
+        {
+            let path = Self::uri_to_path(&uri);
+            let mut workspace = self.workspace.lock().await;
+            if !workspace.load_file(&path) {
+                workspace.remove_file(&path);
+            }
+        }
         self.client.publish_diagnostics(uri, vec![], None).await;
     }
 
@@ -477,13 +1022,35 @@ impl LanguageServer for Backend {
             Some(s) => s,
             None => return Ok(None),
         };
+        if Self::in_string_text(&source, pos.line, pos.character) {
+            return Ok(None);
+        }
         let word = match Self::word_at_position(&source, pos.line, pos.character) {
             Some(w) if !w.is_empty() => w,
             _ => return Ok(None),
         };
+        if let Some(function) = Self::stdlib_function_at_position(&source, pos.line, pos.character)
+        {
+            let mut content = format!("```dal\n{}\n```", function.signature());
+            if !function.doc.is_empty() {
+                content.push_str(&format!("\n\n{}", function.doc));
+            }
+            return Ok(Some(Hover {
+                contents: HoverContents::Scalar(tower_lsp::lsp_types::MarkedString::String(
+                    content,
+                )),
+                range: None,
+            }));
+        }
         let content = match self.hover_for_word(&source, &word) {
             Some(c) => c,
-            None => return Ok(None),
+            None => match Self::collect_variables_from_source(&source)
+                .into_iter()
+                .find(|(name, _)| *name == word)
+            {
+                Some((name, detail)) => format!("**{}**\n\n`{}`", name, detail),
+                None => return Ok(None),
+            },
         };
         Ok(Some(Hover {
             contents: HoverContents::Scalar(tower_lsp::lsp_types::MarkedString::String(content)),
@@ -502,6 +1069,13 @@ impl LanguageServer for Backend {
             docs.get(&uri).map(|d| d.text.clone())
         };
         let source = source.unwrap_or_default();
+        // Plain string text gets no completions; `${...}` inside a string is code.
+        if Self::in_string_text(&source, pos.line, pos.character) {
+            return Ok(None);
+        }
+        if let Some(items) = Self::stdlib_completions(&source, pos.line, pos.character) {
+            return Ok(Some(CompletionResponse::Array(items)));
+        }
         let prefix = Self::word_at_position(&source, pos.line, pos.character).unwrap_or_default();
 
         let mut items = Vec::new();
@@ -523,16 +1097,15 @@ impl LanguageServer for Backend {
             }
         }
 
-        // Stdlib modules
-        let modules = [
-            "chain", "ai", "assist", "log", "auth", "config", "db", "crypto", "oracle", "agent",
-        ];
-        for m in modules {
+        // Stdlib namespaces
+        for m in signatures::namespaces() {
             if prefix.is_empty() || m.starts_with(&prefix) {
                 items.push(CompletionItem {
                     label: m.to_string(),
                     kind: Some(tower_lsp::lsp_types::CompletionItemKind::MODULE),
-                    detail: Self::stdlib_doc(m).map(String::from),
+                    detail: Some(format!("{} functions", signatures::functions(m).count())),
+                    documentation: Self::stdlib_doc(m)
+                        .map(tower_lsp::lsp_types::Documentation::String),
                     ..Default::default()
                 });
             }
@@ -550,6 +1123,19 @@ impl LanguageServer for Backend {
             }
         }
 
+        // Variables declared above the cursor line
+        let before = Self::source_before_line(&source, pos.line);
+        for (name, detail) in Self::collect_variables_from_source(&before) {
+            if prefix.is_empty() || name.starts_with(&prefix) {
+                items.push(CompletionItem {
+                    label: name,
+                    kind: Some(tower_lsp::lsp_types::CompletionItemKind::VARIABLE),
+                    detail: Some(detail),
+                    ..Default::default()
+                });
+            }
+        }
+
         Ok(Some(CompletionResponse::Array(items)))
     }
 
@@ -559,6 +1145,17 @@ impl LanguageServer for Backend {
     ) -> tower_lsp::jsonrpc::Result<Option<GotoDefinitionResponse>> {
         let uri = params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
+        // Across files first: locals, members and `alias::name` imports.
+        let path = Self::uri_to_path(&uri);
+        self.ensure_workspace(Some(&path)).await;
+        let found = self
+            .workspace
+            .lock()
+            .await
+            .definition(&path, pos.line + 1, pos.character + 1);
+        if let Some(location) = found.as_ref().and_then(Self::to_lsp_location) {
+            return Ok(Some(GotoDefinitionResponse::Scalar(location)));
+        }
         let source = {
             let docs = self.documents.lock().await;
             docs.get(&uri).map(|d| d.text.clone())
@@ -584,6 +1181,155 @@ impl LanguageServer for Backend {
         })))
     }
 
+    async fn formatting(
+        &self,
+        params: DocumentFormattingParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<TextEdit>>> {
+        let uri = params.text_document.uri;
+        let source = {
+            let docs = self.documents.lock().await;
+            docs.get(&uri).map(|d| d.text.clone())
+        };
+        let source = match source {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        Ok(Self::formatting_edits(
+            &source,
+            &uri,
+            params.options.tab_size,
+        ))
+    }
+
+    async fn references(
+        &self,
+        params: ReferenceParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<Location>>> {
+        let position = params.text_document_position;
+        let path = Self::uri_to_path(&position.text_document.uri);
+        self.ensure_workspace(Some(&path)).await;
+        let locations = self.workspace.lock().await.references(
+            &path,
+            position.position.line + 1,
+            position.position.character + 1,
+            params.context.include_declaration,
+        );
+        Ok(Some(
+            locations.iter().filter_map(Self::to_lsp_location).collect(),
+        ))
+    }
+
+    async fn rename(
+        &self,
+        params: RenameParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<WorkspaceEdit>> {
+        let position = params.text_document_position;
+        let path = Self::uri_to_path(&position.text_document.uri);
+        self.ensure_workspace(Some(&path)).await;
+        let locations = self
+            .workspace
+            .lock()
+            .await
+            .rename(
+                &path,
+                position.position.line + 1,
+                position.position.character + 1,
+                &params.new_name,
+            )
+            .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for location in locations.iter().filter_map(Self::to_lsp_location) {
+            changes.entry(location.uri).or_default().push(TextEdit {
+                range: location.range,
+                new_text: params.new_name.clone(),
+            });
+        }
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<SymbolInformation>>> {
+        self.ensure_workspace(None).await;
+        let symbols = self.workspace.lock().await.symbols(&params.query);
+        #[allow(deprecated)]
+        let symbols = symbols
+            .into_iter()
+            .filter_map(|symbol| {
+                Some(SymbolInformation {
+                    location: Self::to_lsp_location(&symbol.location)?,
+                    name: symbol.name,
+                    kind: Self::symbol_kind(symbol.kind),
+                    tags: None,
+                    deprecated: None,
+                    container_name: symbol.container,
+                })
+            })
+            .collect();
+        Ok(Some(symbols))
+    }
+
+    async fn code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let source = {
+            let docs = self.documents.lock().await;
+            docs.get(&uri).map(|d| d.text.clone())
+        };
+        let Some(source) = source else {
+            return Ok(None);
+        };
+        Ok(Some(Self::code_actions_for(&source, &uri, params.range)))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<SemanticTokensResult>> {
+        let uri = params.text_document.uri;
+        let source = {
+            let docs = self.documents.lock().await;
+            docs.get(&uri).map(|d| d.text.clone())
+        };
+        let Some(source) = source else {
+            return Ok(None);
+        };
+        let path = Self::uri_to_path(&uri);
+        let tokens = semantic::semantic_tokens(&*self.workspace.lock().await, &path, &source);
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: Self::encode_semantic_tokens(&tokens),
+        })))
+    }
+
+    async fn inlay_hint(
+        &self,
+        params: InlayHintParams,
+    ) -> tower_lsp::jsonrpc::Result<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri;
+        let source = {
+            let docs = self.documents.lock().await;
+            docs.get(&uri).map(|d| d.text.clone())
+        };
+        let Some(source) = source else {
+            return Ok(None);
+        };
+        let path = Self::uri_to_path(&uri);
+        let range = params.range;
+        let hints = semantic::inlay_hints(&*self.workspace.lock().await, &path, &source)
+            .iter()
+            .map(Self::to_lsp_inlay_hint)
+            .filter(|h| range.start <= h.position && h.position <= range.end)
+            .collect();
+        Ok(Some(hints))
+    }
+
     async fn signature_help(
         &self,
         params: SignatureHelpParams,
@@ -595,6 +1341,35 @@ impl LanguageServer for Backend {
             docs.get(&uri).map(|d| d.text.clone())
         };
         let source = source.unwrap_or_default();
+        if let Some((function, active)) =
+            Self::stdlib_call_at_position(&source, pos.line, pos.character)
+        {
+            let parameters = function
+                .params
+                .iter()
+                .map(|p| ParameterInformation {
+                    label: tower_lsp::lsp_types::ParameterLabel::Simple(format!(
+                        "{}{}: {}",
+                        p.name,
+                        if p.optional { "?" } else { "" },
+                        p.ty
+                    )),
+                    documentation: None,
+                })
+                .collect();
+            return Ok(Some(SignatureHelp {
+                signatures: vec![SignatureInformation {
+                    label: function.signature(),
+                    documentation: (!function.doc.is_empty()).then(|| {
+                        tower_lsp::lsp_types::Documentation::String(function.doc.to_string())
+                    }),
+                    parameters: Some(parameters),
+                    active_parameter: None,
+                }],
+                active_signature: Some(0),
+                active_parameter: Some(active),
+            }));
+        }
         let (fn_name, active_param) =
             match Self::function_call_at_position(&source, pos.line, pos.character) {
                 Some(x) => x,
@@ -900,4 +1675,176 @@ mod tests {
         assert!(diags[0].range.start.line <= diags[0].range.end.line);
         assert!(diags[0].range.start.character <= diags[0].range.end.character);
     }
+
+    #[test]
+    fn test_in_string_text_distinguishes_interpolation() {
+        let source = "let s = \"Hi ${user.name}!\";";
+        assert!(!Backend::in_string_text(source, 0, 4));
+        assert!(Backend::in_string_text(source, 0, 10));
+        assert!(!Backend::in_string_text(source, 0, 16));
+        assert!(Backend::in_string_text(source, 0, 24));
+        assert!(!Backend::in_string_text(source, 0, 26));
+        // Braces and strings nested inside `${}` stay balanced.
+        let source = "let s = \"${f({\"k\": \"}\"})} and ${x}\";";
+        assert!(Backend::in_string_text(source, 0, 20));
+        assert!(Backend::in_string_text(source, 0, 28));
+        assert!(!Backend::in_string_text(source, 0, 33));
+    }
+
+    #[test]
+    fn test_in_string_text_triple_quoted() {
+        let source = "let p = \"\"\"\n  Dear \"${name}\",\n  \"\"\";\nlet q = 1;";
+        assert!(Backend::in_string_text(source, 1, 4));
+        assert!(!Backend::in_string_text(source, 1, 11));
+        assert!(Backend::in_string_text(source, 1, 15));
+        assert!(!Backend::in_string_text(source, 3, 5));
+    }
+
+    #[test]
+    fn test_collect_variables_from_source() {
+        let source =
+            "fn prompt(task, context: string) {\n  let out = \"\";\n  for item in task { }\n}";
+        let vars: Vec<String> = Backend::collect_variables_from_source(source)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(vars, vec!["task", "context", "out", "item"]);
+        // The line being typed is left out, so an unterminated string still lexes.
+        let source = "let user = 1;\nlet s = \"${us";
+        let vars = Backend::collect_variables_from_source(&Backend::source_before_line(source, 1));
+        assert_eq!(vars, vec![("user".to_string(), "let user".to_string())]);
+    }
+
+    #[test]
+    fn test_formatting_edits_replace_whole_document() {
+        let uri = Url::parse("untitled:scratch.dal").unwrap();
+        let source = "fn f(a,b) {\n  // sum\n  return a+b;\n}";
+        let edits = Backend::formatting_edits(source, &uri, 2).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.end, Position::new(3, 1));
+        assert_eq!(
+            edits[0].new_text,
+            "fn f(a, b) {\n  // sum\n  return a + b;\n}\n"
+        );
+        assert!(Backend::formatting_edits(&edits[0].new_text, &uri, 2).is_none());
+        // Sources with syntax errors are left alone.
+        assert!(Backend::formatting_edits("fn f( {", &uri, 4).is_none());
+    }
+
+    #[test]
+    fn test_code_actions_for_unused_variable() {
+        let uri = Url::parse("untitled:scratch.dal").unwrap();
+        let source = "fn f() {\n    let spare = 2;\n    return 1;\n}\n";
+        let diagnostics = Backend::diagnostics_from_source(source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        let range = Range::new(Position::new(1, 0), Position::new(1, 0));
+        let actions = Backend::code_actions_for(source, &uri, range);
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.start, Position::new(1, 8));
+        assert_eq!(edits[0].new_text, "_");
+        // Nothing to fix outside the requested lines.
+        let other = Range::new(Position::new(2, 0), Position::new(3, 0));
+        assert!(Backend::code_actions_for(source, &uri, other).is_empty());
+    }
+
+    #[test]
+    fn test_stdlib_completions_insert_parameter_snippets() {
+        let source = "import stdlib::chain as ch;\nlet g = ch::estimate_g";
+        let items = Backend::stdlib_completions(source, 1, 22).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "estimate_gas");
+        assert_eq!(
+            items[0].insert_text.as_deref(),
+            Some("estimate_gas(${1:chain_id}, ${2:operation})")
+        );
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert_eq!(
+            items[0].detail.as_deref(),
+            Some("chain::estimate_gas(chain_id: int, operation: string) -> int")
+        );
+        // Every `log::` function; nothing for names that are not stdlib namespaces.
+        let all = Backend::stdlib_completions("log::", 0, 5).unwrap();
+        assert_eq!(all.len(), signatures::functions("log").count());
+        assert!(Backend::stdlib_completions("Vault::", 0, 7).is_none());
+        assert!(Backend::stdlib_completions("let x = lo", 0, 10).is_none());
+    }
+
+    #[test]
+    fn test_stdlib_call_and_function_at_position() {
+        let source = "let b = chain::get_balance(1, foo(2, 3), ";
+        let (function, active) = Backend::stdlib_call_at_position(source, 0, 41).unwrap();
+        assert_eq!(function.name, "get_balance");
+        assert_eq!(active, 2);
+        let (_, active) = Backend::stdlib_call_at_position(source, 0, 28).unwrap();
+        assert_eq!(active, 0);
+        assert!(Backend::stdlib_call_at_position(source, 0, 36).is_none());
+        let hovered = Backend::stdlib_function_at_position(source, 0, 18).unwrap();
+        assert_eq!(hovered.namespace, "chain");
+        assert!(Backend::stdlib_function_at_position(source, 0, 10).is_none());
+    }
+
+    #[test]
+    fn test_stdlib_doc_lists_registry_functions() {
+        let doc = Backend::stdlib_doc("time").unwrap();
+        assert!(doc.contains("unix_ms_now"));
+        assert!(doc.contains(&format!(
+            "{} functions",
+            signatures::functions("time").count()
+        )));
+        assert!(Backend::stdlib_doc("database").is_some());
+    }
+
+    #[test]
+    fn test_encode_semantic_tokens_is_relative() {
+        let token = |line, column, length, kind| IdeSemanticToken {
+            line,
+            column,
+            length,
+            kind,
+            declaration: false,
+            default_library: false,
+        };
+        let mut service = token(2, 9, 5, SemanticKind::Service);
+        service.declaration = true;
+        let tokens = [
+            token(1, 1, 6, SemanticKind::Keyword),
+            token(1, 8, 5, SemanticKind::Attribute),
+            service,
+            token(2, 16, 3, SemanticKind::Agent),
+        ];
+        let encoded: Vec<[u32; 5]> = Backend::encode_semantic_tokens(&tokens)
+            .iter()
+            .map(|t| {
+                [
+                    t.delta_line,
+                    t.delta_start,
+                    t.length,
+                    t.token_type,
+                    t.token_modifiers_bitset,
+                ]
+            })
+            .collect();
+        assert_eq!(
+            encoded,
+            vec![
+                [0, 0, 6, 10, 0],
+                [0, 7, 5, 9, 0],
+                [1, 8, 5, 1, 0b101],
+                [0, 7, 3, 1, 0b1000]
+            ]
+        );
+        let legend = Backend::semantic_tokens_legend();
+        assert_eq!(legend.token_types[1], SemanticTokenType::CLASS);
+        assert_eq!(
+            legend.token_modifiers[2],
+            SemanticTokenModifier::new("service")
+        );
+    }
 }
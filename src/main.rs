@@ -16,6 +16,7 @@ use dist_agent_lang::parser;
 use dist_agent_lang::performance;
 use dist_agent_lang::reporting::{
     format_lexer_error, format_parse_warnings, format_parser_error, format_runtime_error,
+    format_type_errors,
 };
 use dist_agent_lang::runtime;
 use dist_agent_lang::stdlib;
@@ -119,7 +120,7 @@ fn main() {
     };
 
     match cmd {
-        Commands::Run { file } => run_dal_file(&file),
+        Commands::Run { file, wasm } => run_dal_file(file, *wasm),
         Commands::Test { file } => {
             if let Some(f) = file {
                 run_dal_tests(&f);
@@ -227,6 +228,12 @@ fn main() {
             #[cfg(not(feature = "lsp"))]
             handle_lsp_command(rest);
         }
+        Commands::Dap => {
+            if let Err(e) = dist_agent_lang::dap::run_stdio() {
+                eprintln!("❌ DAP server error: {}", e);
+                std::process::exit(1);
+            }
+        }
         Commands::Doc {
             target,
             output,
@@ -248,9 +255,9 @@ fn main() {
         }
         Commands::Debug { file, breakpoint } => {
             let mut a = vec![file.clone()];
-            if let Some(b) = breakpoint {
+            for b in breakpoint {
                 a.push("--breakpoint".to_string());
-                a.push(b.to_string());
+                a.push(b.clone());
             }
             handle_debug_command(&a);
         }
@@ -379,95 +386,6 @@ fn resolve_mcp_bridge_transport(raw: Option<&str>) -> Result<McpBridgeTransport,
     }
 }
 
-#[cfg(test)]
-mod mcp_bridge_transport_tests {
-    use super::{resolve_mcp_bridge_transport, McpBridgeTransport};
-    use std::sync::{Mutex, OnceLock};
-
-    fn env_lock() -> &'static Mutex<()> {
-        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
-        LOCK.get_or_init(|| Mutex::new(()))
-    }
-
-    struct EnvGuard {
-        original: Option<String>,
-    }
-
-    impl EnvGuard {
-        fn set(value: Option<&str>) -> Self {
-            let original = std::env::var("DAL_MCP_TRANSPORT").ok();
-            if let Some(v) = value {
-                std::env::set_var("DAL_MCP_TRANSPORT", v);
-            } else {
-                std::env::remove_var("DAL_MCP_TRANSPORT");
-            }
-            Self { original }
-        }
-    }
-
-    impl Drop for EnvGuard {
-        fn drop(&mut self) {
-            if let Some(v) = &self.original {
-                std::env::set_var("DAL_MCP_TRANSPORT", v);
-            } else {
-                std::env::remove_var("DAL_MCP_TRANSPORT");
-            }
-        }
-    }
-
-    #[test]
-    fn defaults_to_stdio_when_unset() {
-        let _lock = env_lock().lock().expect("lock");
-        let _guard = EnvGuard::set(None);
-        let transport = resolve_mcp_bridge_transport(None).expect("transport");
-        assert_eq!(transport, McpBridgeTransport::Stdio);
-    }
-
-    #[test]
-    fn honors_env_when_cli_unset() {
-        let _lock = env_lock().lock().expect("lock");
-        let _guard = EnvGuard::set(Some("http_stream"));
-        let transport = resolve_mcp_bridge_transport(None).expect("transport");
-        assert_eq!(transport, McpBridgeTransport::HttpStream);
-    }
-
-    #[test]
-    fn cli_transport_overrides_env() {
-        let _lock = env_lock().lock().expect("lock");
-        let _guard = EnvGuard::set(Some("stdio"));
-        let transport = resolve_mcp_bridge_transport(Some("http-stream")).expect("transport");
-        assert_eq!(transport, McpBridgeTransport::HttpStream);
-    }
-
-    #[test]
-    fn accepts_http_stream_aliases() {
-        let _lock = env_lock().lock().expect("lock");
-        let _guard = EnvGuard::set(None);
-        assert_eq!(
-            resolve_mcp_bridge_transport(Some("http-stream")).expect("transport"),
-            McpBridgeTransport::HttpStream
-        );
-        assert_eq!(
-            resolve_mcp_bridge_transport(Some("http_stream")).expect("transport"),
-            McpBridgeTransport::HttpStream
-        );
-        assert_eq!(
-            resolve_mcp_bridge_transport(Some("httpstream")).expect("transport"),
-            McpBridgeTransport::HttpStream
-        );
-    }
-
-    #[test]
-    fn rejects_unsupported_transport_values() {
-        let _lock = env_lock().lock().expect("lock");
-        let _guard = EnvGuard::set(None);
-        let err = resolve_mcp_bridge_transport(Some("ws")).expect_err("should fail");
-        assert!(err.contains("unsupported transport"));
-        assert!(err.contains("stdio"));
-        assert!(err.contains("http-stream"));
-    }
-}
-
 fn handle_mcp_bridge_command(url: Option<&str>, transport: Option<&str>) {
     let transport = match resolve_mcp_bridge_transport(transport) {
         Ok(t) => t,
@@ -656,7 +574,8 @@ fn parse_dal_file(filename: &str) {
     }
 }
 
-fn run_dal_file(filename: &str) {
+/// Run a DAL file; with `wasm`, its `@compile_target("wasm")` services run from the built module.
+fn run_dal_file(filename: &str, wasm: bool) {
     println!("🪩  Running dist_agent_lang file: {}", filename);
 
     // Read the file
@@ -707,37 +626,13 @@ fn run_dal_file(filename: &str) {
         );
     }
 
-    // M2/M4: Resolve imports when running from a file; pass to runtime for loading
-    let has_imports = ast
-        .statements
-        .iter()
-        .any(|s| matches!(s, Statement::Import(_)));
     // Execute (M4: pass resolved imports when present so runtime can load modules)
+    let resolved = resolve_entry_imports(filename, &ast);
     let mut runtime = Runtime::new();
-    let exec_result = if has_imports {
-        let entry_path = std::path::Path::new(filename);
-        let entry_dir = entry_path
-            .parent()
-            .unwrap_or_else(|| std::path::Path::new("."));
-        let manifest_path = entry_dir.join("dal.toml");
-        let mut resolver =
-            dist_agent_lang::ModuleResolver::new().with_root_dir(entry_dir.to_path_buf());
-        if manifest_path.exists() {
-            if let Ok(deps) = dist_agent_lang::manifest::load_resolved_deps(&manifest_path) {
-                resolver = resolver.with_dependencies(deps);
-            }
-        }
-        let resolved = match resolver.resolve_program_imports(&ast, Some(entry_path)) {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("❌ Import resolution failed: {}", e);
-                std::process::exit(1);
-            }
-        };
-        runtime.execute_program(ast, Some(&resolved))
-    } else {
-        runtime.execute_program(ast, None)
-    };
+    if wasm {
+        attach_wasm_services(filename, &source_code, &ast, &mut runtime);
+    }
+    let exec_result = runtime.execute_program(ast, resolved.as_deref());
     match exec_result {
         Ok(result) => {
             println!("✅ Execution successful!");
@@ -764,6 +659,111 @@ fn run_dal_file(filename: &str) {
     }
 }
 
+/// `dal run --wasm`: build the file's wasm-target services under `.dal/wasm/<stem>` and route
+/// their method calls to the module loaded in the embedded wasm runtime.
+#[cfg(feature = "wasm-runtime")]
+fn attach_wasm_services(
+    filename: &str,
+    source_code: &str,
+    ast: &parser::ast::Program,
+    runtime: &mut Runtime,
+) {
+    use dist_agent_lang::compile::{run_compile, select_services_for_target};
+    use dist_agent_lang::lexer::tokens::CompilationTarget;
+
+    if select_services_for_target(ast, &CompilationTarget::WebAssembly).is_empty() {
+        eprintln!(
+            "❌ --wasm: {} has no @compile_target(\"wasm\") services",
+            filename
+        );
+        std::process::exit(1);
+    }
+    let stem = std::path::Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "main".to_string());
+    let out_dir = std::path::Path::new(".dal").join("wasm").join(stem);
+    let artifacts = match run_compile(
+        std::path::PathBuf::from(filename),
+        CompilationTarget::WebAssembly,
+        out_dir,
+        source_code,
+    ) {
+        Ok(artifacts) => artifacts,
+        Err(e) => {
+            eprintln!("❌ wasm build failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let Some(module) = artifacts
+        .artifact_paths
+        .iter()
+        .find(|p| p.extension().is_some_and(|e| e == "wasm"))
+    else {
+        eprintln!("❌ wasm build produced no .wasm artifact");
+        std::process::exit(1);
+    };
+    match runtime::WasmServices::from_file(module, Runtime::new()) {
+        Ok(services) => {
+            println!(
+                "✅ Loaded wasm module {} ({})",
+                module.display(),
+                services.service_names().join(", ")
+            );
+            runtime.attach_wasm_services(services);
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "wasm-runtime"))]
+fn attach_wasm_services(
+    _filename: &str,
+    _source_code: &str,
+    _ast: &parser::ast::Program,
+    _runtime: &mut Runtime,
+) {
+    eprintln!("❌ --wasm requires dal built with the `wasm-runtime` feature");
+    std::process::exit(1);
+}
+
+/// M2/M4: Resolve imports when running from a file, so the runtime can load modules.
+/// Returns `None` when the program has no imports; exits on resolution failure.
+fn resolve_entry_imports(
+    filename: &str,
+    ast: &parser::ast::Program,
+) -> Option<Vec<dist_agent_lang::module_resolver::ResolvedImportEntry>> {
+    let has_imports = ast
+        .statements
+        .iter()
+        .any(|s| matches!(s, Statement::Import(_)));
+    if !has_imports {
+        return None;
+    }
+    let entry_path = std::path::Path::new(filename);
+    let entry_dir = entry_path
+        .parent()
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let manifest_path = entry_dir.join("dal.toml");
+    let mut resolver =
+        dist_agent_lang::ModuleResolver::new().with_root_dir(entry_dir.to_path_buf());
+    if manifest_path.exists() {
+        if let Ok(deps) = dist_agent_lang::manifest::load_resolved_deps(&manifest_path) {
+            resolver = resolver.with_dependencies(deps);
+        }
+    }
+    match resolver.resolve_program_imports(ast, Some(entry_path)) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            eprintln!("❌ Import resolution failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 // Test discovery and execution
 fn run_dal_tests(path: &str) {
     use std::fs;
@@ -2103,20 +2103,8 @@ fn check_dal_file(filename: &str) {
         }
     };
 
-    // Parse
-    let ast = match Parser::new_with_positions(tokens_with_pos).parse() {
-        Ok(ast) => {
-            println!("✅ Parsed {} statements", ast.statements.len());
-            ast
-        }
-        Err(e) => {
-            eprintln!(
-                "❌ Type check failed:\n{}",
-                format_parser_error(&e, Some(filename), Some(&source_code))
-            );
-            std::process::exit(1);
-        }
-    };
+    // Parse, reporting every syntax error rather than only the first
+    let ast = parse_reporting_all_errors(tokens_with_pos, filename, &source_code, "Type check");
 
     // Warnings (unused variables, etc.)
     let warnings = parser::collect_warnings(&ast);
@@ -2127,6 +2115,17 @@ fn check_dal_file(filename: &str) {
         );
     }
 
+    // Static types: argument, return and field-assignment mismatches against annotations
+    let type_errors = runtime::check_program(&ast);
+    if !type_errors.is_empty() {
+        eprintln!(
+            "\n{}",
+            format_type_errors(&type_errors, Some(filename), Some(&source_code))
+        );
+        eprintln!("❌ Type check failed: {} error(s)", type_errors.len());
+        std::process::exit(1);
+    }
+
     println!("✅ Type check passed!");
     println!("   {} statements validated", ast.statements.len());
     if warnings.is_empty() {
@@ -2136,8 +2135,33 @@ fn check_dal_file(filename: &str) {
     }
 }
 
-/// Format DAL code
+/// Parse with recovery for `dal check` / `dal lint`: print every syntax error and exit, or
+/// return the program.
+fn parse_reporting_all_errors(
+    tokens_with_pos: Vec<lexer::tokens::TokenWithPosition>,
+    filename: &str,
+    source_code: &str,
+    command: &str,
+) -> parser::ast::Program {
+    let (ast, errors) = Parser::new_with_positions(tokens_with_pos).parse_with_recovery();
+    if errors.is_empty() {
+        println!("✅ Parsed {} statements", ast.statements.len());
+        return ast;
+    }
+    for e in &errors {
+        eprintln!(
+            "{}",
+            format_parser_error(e, Some(filename), Some(source_code))
+        );
+    }
+    eprintln!("❌ {} failed: {} syntax error(s)", command, errors.len());
+    std::process::exit(1);
+}
+
+/// Format DAL code, keeping comments (see `dist_agent_lang::formatter`)
 fn format_dal_file(filename: &str, check_only: bool) {
+    use dist_agent_lang::formatter::{self, FormatConfig, FormatError};
+
     if check_only {
         println!("🪩  Checking format of: {}", filename);
     } else {
@@ -2153,46 +2177,44 @@ fn format_dal_file(filename: &str, check_only: bool) {
         }
     };
 
-    // Tokenize and parse
-    let tokens = match Lexer::new(&source_code).tokenize_immutable() {
-        Ok(tokens) => {
-            println!("✅ Lexer scanning... {} tokens", tokens.len());
-            tokens
-        }
+    // `[fmt]` in the nearest dal.toml
+    let config = match FormatConfig::discover(std::path::Path::new(filename)) {
+        Ok(config) => config.unwrap_or_default(),
         Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let formatted = match formatter::format_source(&source_code, &config) {
+        Ok(formatted) => formatted,
+        Err(FormatError::Lexer(e)) => {
             eprintln!(
                 "❌ Lexer error:\n{}",
                 format_lexer_error(&e, Some(filename), Some(&source_code))
             );
             std::process::exit(1);
         }
-    };
-
-    let ast = match Parser::new(tokens).parse() {
-        Ok(ast) => {
-            println!("✅ Parsed {} statements", ast.statements.len());
-            ast
-        }
-        Err(e) => {
+        Err(FormatError::Parser(e)) => {
             eprintln!(
                 "❌ Parsing failed:\n{}",
                 format_parser_error(&e, Some(filename), Some(&source_code))
             );
             std::process::exit(1);
         }
-    };
-
-    // Format AST back to code
-    let formatted = format_ast(&ast);
-
-    if check_only {
-        if formatted.trim() == source_code.trim() {
-            println!("✅ File is properly formatted");
-        } else {
-            println!("❌ File needs formatting");
-            println!("   Run '{} fmt {}' to format", binary_name(), filename);
+        Err(e) => {
+            eprintln!("❌ {}", e);
             std::process::exit(1);
         }
+    };
+
+    if formatted == source_code {
+        println!("✅ File is properly formatted");
+    } else if check_only {
+        print!("{}", formatter::diff(&source_code, &formatted));
+        println!("❌ File needs formatting");
+        println!("   Run '{} fmt {}' to format", binary_name(), filename);
+        std::process::exit(1);
     } else {
         // Write formatted code back to file
         match std::fs::write(filename, formatted) {
@@ -2207,204 +2229,6 @@ fn format_dal_file(filename: &str, check_only: bool) {
     }
 }
 
-/// Format AST back to source code
-fn format_ast(ast: &parser::ast::Program) -> String {
-    let mut output = String::new();
-
-    for (i, statement) in ast.statements.iter().enumerate() {
-        if i > 0 {
-            output.push('\n');
-        }
-        output.push_str(&format_statement(statement));
-    }
-
-    output
-}
-
-fn format_statement(stmt: &Statement) -> String {
-    use parser::ast::Statement;
-
-    match stmt {
-        Statement::Let(let_stmt) => {
-            format!(
-                "let {} = {};\n",
-                let_stmt.name,
-                format_expression(&let_stmt.value)
-            )
-        }
-        Statement::Return(ret_stmt) => {
-            if let Some(e) = &ret_stmt.value {
-                format!("return {};\n", format_expression(e))
-            } else {
-                "return;\n".to_string()
-            }
-        }
-        Statement::Expression(expr) => {
-            format!("{};\n", format_expression(expr))
-        }
-        Statement::If(if_stmt) => {
-            let mut result = format!("if {} {{\n", format_expression(&if_stmt.condition));
-            for stmt in &if_stmt.consequence.statements {
-                result.push_str("    ");
-                result.push_str(&format_statement(stmt));
-            }
-            if let Some(alt) = &if_stmt.alternative {
-                result.push_str("} else {\n");
-                for stmt in &alt.statements {
-                    result.push_str("    ");
-                    result.push_str(&format_statement(stmt));
-                }
-            }
-            result.push_str("}\n");
-            result
-        }
-        Statement::Function(fn_stmt) => {
-            let params: Vec<String> = fn_stmt
-                .parameters
-                .iter()
-                .map(|p| {
-                    if let Some(ref t) = p.param_type {
-                        format!("{}: {}", p.name, t)
-                    } else {
-                        p.name.clone()
-                    }
-                })
-                .collect();
-            let ret_type = fn_stmt
-                .return_type
-                .as_ref()
-                .map(|t| format!(" -> {}", t))
-                .unwrap_or_default();
-            let mut result = format!(
-                "fn {}({}){} {{\n",
-                fn_stmt.name,
-                params.join(", "),
-                ret_type
-            );
-            for stmt in &fn_stmt.body.statements {
-                result.push_str("    ");
-                result.push_str(&format_statement(stmt));
-            }
-            result.push_str("}\n");
-            result
-        }
-        Statement::Import(import_stmt) => {
-            let alias = import_stmt
-                .alias
-                .as_ref()
-                .map(|a| format!(" as {}", a))
-                .unwrap_or_default();
-            format!("import {}{};\n", import_stmt.path, alias)
-        }
-        _ => "// Unformatted statement\n".to_string(),
-    }
-}
-
-fn format_expression(expr: &parser::ast::Expression) -> String {
-    use lexer::tokens::Literal;
-    use parser::ast::Expression;
-
-    match expr {
-        Expression::Literal(lit) => match lit {
-            Literal::Int(n) => n.to_string(),
-            Literal::Float(f) => f.to_string(),
-            Literal::String(s) => format!("\"{}\"", s),
-            Literal::Bool(b) => b.to_string(),
-            Literal::Null => "null".to_string(),
-        },
-        Expression::Identifier(name) => name.clone(),
-        Expression::BinaryOp(left, op, right) => {
-            format!(
-                "{} {} {}",
-                format_expression(left),
-                format_operator(op),
-                format_expression(right)
-            )
-        }
-        Expression::FunctionCall(call) => {
-            let formatted_args: Vec<String> =
-                call.arguments.iter().map(format_expression).collect();
-            format!("{}({})", call.name, formatted_args.join(", "))
-        }
-        Expression::MethodCall {
-            receiver,
-            method_name,
-            arguments,
-        } => {
-            let formatted_args: Vec<String> = arguments.iter().map(format_expression).collect();
-            format!(
-                "{}.{}({})",
-                format_expression(receiver),
-                method_name,
-                formatted_args.join(", ")
-            )
-        }
-        Expression::ArrayLiteral(elements) => {
-            let formatted_elements: Vec<String> = elements.iter().map(format_expression).collect();
-            format!("[{}]", formatted_elements.join(", "))
-        }
-        Expression::ObjectLiteral(obj) => {
-            let formatted_pairs: Vec<String> = obj
-                .iter()
-                .map(|(k, v)| format!("\"{}\": {}", k, format_expression(v)))
-                .collect();
-            format!("{{{}}}", formatted_pairs.join(", "))
-        }
-        Expression::Assignment(name, value) => {
-            format!("{} = {}", name, format_expression(value))
-        }
-        Expression::FieldAccess(obj, field) => {
-            format!("{}.{}", format_expression(obj), field)
-        }
-        Expression::IndexAccess(arr, index) => {
-            format!("{}[{}]", format_expression(arr), format_expression(index))
-        }
-        _ => "/* complex expression */".to_string(),
-    }
-}
-
-fn format_operator(op: &lexer::tokens::Operator) -> &str {
-    use lexer::tokens::Operator;
-    match op {
-        Operator::Plus => "+",
-        Operator::Minus => "-",
-        Operator::Star => "*",
-        Operator::Slash => "/",
-        Operator::Percent => "%",
-        Operator::EqualEqual => "==",
-        Operator::Equal => "=",
-        Operator::BangEqual => "!=",
-        Operator::Bang => "!",
-        Operator::Less => "<",
-        Operator::LessEqual => "<=",
-        Operator::Greater => ">",
-        Operator::GreaterEqual => ">=",
-        Operator::And => "&&",
-        Operator::Or => "||",
-        Operator::Ampersand => "&",
-        Operator::Pipe => "|",
-        Operator::Caret => "^",
-        Operator::Tilde => "~",
-        Operator::LeftShift => "<<",
-        Operator::RightShift => ">>",
-        Operator::PlusEqual => "+=",
-        Operator::MinusEqual => "-=",
-        Operator::StarEqual => "*=",
-        Operator::SlashEqual => "/=",
-        Operator::PercentEqual => "%=",
-        Operator::AndEqual => "&&=",
-        Operator::OrEqual => "||=",
-        Operator::CaretEqual => "^=",
-        Operator::LeftShiftEqual => "<<=",
-        Operator::RightShiftEqual => ">>=",
-        Operator::Assign => "=",
-        Operator::Not => "!",
-        Operator::Colon => ":",
-        Operator::NotEqual => "!=",
-        Operator::Dot => ".",
-    }
-}
-
 /// Lint DAL code
 fn lint_dal_file(filename: &str) {
     println!("🪩  Linting dist_agent_lang file: {}", filename);
@@ -2419,7 +2243,7 @@ fn lint_dal_file(filename: &str) {
     };
 
     // Tokenize and parse
-    let tokens = match Lexer::new(&source_code).tokenize_immutable() {
+    let tokens_with_pos = match Lexer::new(&source_code).tokenize_with_positions_immutable() {
         Ok(tokens) => {
             println!("✅ Lexer scanning... {} tokens", tokens.len());
             tokens
@@ -2433,19 +2257,7 @@ fn lint_dal_file(filename: &str) {
         }
     };
 
-    let ast = match Parser::new(tokens).parse() {
-        Ok(ast) => {
-            println!("✅ Parsed {} statements", ast.statements.len());
-            ast
-        }
-        Err(e) => {
-            eprintln!(
-                "❌ Parsing failed:\n{}",
-                format_parser_error(&e, Some(filename), Some(&source_code))
-            );
-            std::process::exit(1);
-        }
-    };
+    let ast = parse_reporting_all_errors(tokens_with_pos, filename, &source_code, "Lint");
 
     // Run lint checks
     let mut issues = Vec::new();
@@ -3179,7 +2991,7 @@ fn watch_dal_file(filename: &str) {
 
     // Initial run
     println!("🪩 Initial run:");
-    run_dal_file(filename);
+    run_dal_file(filename, false);
 
     // Get initial modification time
     let mut last_modified = match std::fs::metadata(filename) {
@@ -3200,7 +3012,7 @@ fn watch_dal_file(filename: &str) {
                 if let Ok(modified) = metadata.modified() {
                     if Some(modified) != last_modified {
                         println!("\n🔄 File changed, re-running...\n");
-                        run_dal_file(filename);
+                        run_dal_file(filename, false);
                         last_modified = Some(modified);
                     }
                 }
@@ -6310,6 +6122,7 @@ fn handle_completions_command(args: &[String]) {
         "doc",
         "completions",
         "debug",
+        "dap",
         "agent",
         "iot",
         "log",
@@ -6368,12 +6181,28 @@ fn handle_completions_command(args: &[String]) {
 }
 
 fn handle_debug_command(args: &[String]) {
+    use dist_agent_lang::runtime::debugger::{parse_breakpoint_spec, SESSION_TERMINATED};
+    use dist_agent_lang::runtime::{ConsoleDebugger, Debugger, RuntimeError};
+
     let file = &args[0];
-    let breakpoint = args
-        .iter()
-        .position(|a| a == "--breakpoint")
-        .and_then(|i| args.get(i + 1))
-        .and_then(|s| s.parse::<u32>().ok());
+    let mut debugger = Debugger::new();
+    let mut specs = args.iter().skip(1);
+    while let Some(arg) = specs.next() {
+        if arg != "--breakpoint" {
+            continue;
+        }
+        let spec = specs.next().map(String::as_str).unwrap_or("");
+        match parse_breakpoint_spec(spec) {
+            Ok((line, condition)) => debugger.set_breakpoint(line, condition),
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // Without breakpoints there is nothing to run to, so pause before the first statement.
+    let has_breakpoints = debugger.breakpoints().next().is_some();
+    let debugger = debugger.with_stop_on_entry(!has_breakpoints);
 
     let source = match std::fs::read_to_string(file) {
         Ok(s) => s,
@@ -6383,27 +6212,8 @@ fn handle_debug_command(args: &[String]) {
         }
     };
 
-    println!("🪩  Debug mode");
-    println!();
-    println!("   File: {}", file);
-    if let Some(line) = breakpoint {
-        println!("   Breakpoint at line: {}", line);
-    }
-    println!();
-    println!("   Interactive debugger features (planned):");
-    println!("   • Step over / into / out");
-    println!("   • Variable inspection");
-    println!("   • Call stack view");
-    println!("   • Conditional breakpoints");
-    println!();
-    println!("   Current: Running file without breakpoints.");
-    println!();
-
     let tokens = match Lexer::new(&source).tokenize_with_positions_immutable() {
-        Ok(t) => {
-            println!("✅ Lexer scanning... {} tokens", t.len());
-            t
-        }
+        Ok(t) => t,
         Err(e) => {
             eprintln!(
                 "❌ Lexer error:\n{}",
@@ -6412,11 +6222,8 @@ fn handle_debug_command(args: &[String]) {
             std::process::exit(1);
         }
     };
-    match Parser::new_with_positions(tokens).parse() {
-        Ok(ast) => {
-            println!("✅ Parsed {} statements", ast.statements.len());
-            println!("✅ Parse OK. Execute with: {} run {}", binary_name(), file);
-        }
+    let ast = match Parser::new_with_positions(tokens).parse() {
+        Ok(ast) => ast,
         Err(e) => {
             eprintln!(
                 "❌ Parse error:\n{}",
@@ -6424,6 +6231,41 @@ fn handle_debug_command(args: &[String]) {
             );
             std::process::exit(1);
         }
+    };
+
+    println!("🪩  Debugging {} (type 'help' for commands)", file);
+    for bp in debugger.breakpoints() {
+        match &bp.condition {
+            Some(c) => println!("   Breakpoint at line {} if {}", bp.line, c),
+            None => println!("   Breakpoint at line {}", bp.line),
+        }
+    }
+
+    let resolved = resolve_entry_imports(file, &ast);
+    let mut runtime = Runtime::new();
+    runtime.set_debug_hook(Box::new(ConsoleDebugger::new(
+        debugger,
+        &source,
+        std::io::BufReader::new(std::io::stdin()),
+        std::io::stdout(),
+    )));
+    match runtime.execute_program(ast, resolved.as_deref()) {
+        Ok(result) => {
+            println!("✅ Program finished");
+            if let Some(value) = result {
+                println!("   Result: {}", value);
+            }
+        }
+        Err(e) if matches!(e.inner.root(), RuntimeError::General(m) if m == SESSION_TERMINATED) => {
+            println!("Debug session ended.");
+        }
+        Err(e) => {
+            eprintln!(
+                "❌ Execution failed:\n{}",
+                format_runtime_error(&e, Some(file), Some(&source))
+            );
+            std::process::exit(1);
+        }
     }
 }
 
@@ -8622,3 +8464,92 @@ fn optimize_gas_basic(code: &str) {
     println!();
     println!("💡 For AI-powered detailed gas analysis, set OPENAI_API_KEY or ANTHROPIC_API_KEY");
 }
+
+#[cfg(test)]
+mod mcp_bridge_transport_tests {
+    use super::{resolve_mcp_bridge_transport, McpBridgeTransport};
+    use std::sync::{Mutex, OnceLock};
+
+    fn env_lock() -> &'static Mutex<()> {
+        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        LOCK.get_or_init(|| Mutex::new(()))
+    }
+
+    struct EnvGuard {
+        original: Option<String>,
+    }
+
+    impl EnvGuard {
+        fn set(value: Option<&str>) -> Self {
+            let original = std::env::var("DAL_MCP_TRANSPORT").ok();
+            if let Some(v) = value {
+                std::env::set_var("DAL_MCP_TRANSPORT", v);
+            } else {
+                std::env::remove_var("DAL_MCP_TRANSPORT");
+            }
+            Self { original }
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            if let Some(v) = &self.original {
+                std::env::set_var("DAL_MCP_TRANSPORT", v);
+            } else {
+                std::env::remove_var("DAL_MCP_TRANSPORT");
+            }
+        }
+    }
+
+    #[test]
+    fn defaults_to_stdio_when_unset() {
+        let _lock = env_lock().lock().expect("lock");
+        let _guard = EnvGuard::set(None);
+        let transport = resolve_mcp_bridge_transport(None).expect("transport");
+        assert_eq!(transport, McpBridgeTransport::Stdio);
+    }
+
+    #[test]
+    fn honors_env_when_cli_unset() {
+        let _lock = env_lock().lock().expect("lock");
+        let _guard = EnvGuard::set(Some("http_stream"));
+        let transport = resolve_mcp_bridge_transport(None).expect("transport");
+        assert_eq!(transport, McpBridgeTransport::HttpStream);
+    }
+
+    #[test]
+    fn cli_transport_overrides_env() {
+        let _lock = env_lock().lock().expect("lock");
+        let _guard = EnvGuard::set(Some("stdio"));
+        let transport = resolve_mcp_bridge_transport(Some("http-stream")).expect("transport");
+        assert_eq!(transport, McpBridgeTransport::HttpStream);
+    }
+
+    #[test]
+    fn accepts_http_stream_aliases() {
+        let _lock = env_lock().lock().expect("lock");
+        let _guard = EnvGuard::set(None);
+        assert_eq!(
+            resolve_mcp_bridge_transport(Some("http-stream")).expect("transport"),
+            McpBridgeTransport::HttpStream
+        );
+        assert_eq!(
+            resolve_mcp_bridge_transport(Some("http_stream")).expect("transport"),
+            McpBridgeTransport::HttpStream
+        );
+        assert_eq!(
+            resolve_mcp_bridge_transport(Some("httpstream")).expect("transport"),
+            McpBridgeTransport::HttpStream
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_transport_values() {
+        let _lock = env_lock().lock().expect("lock");
+        let _guard = EnvGuard::set(None);
+        let err = resolve_mcp_bridge_transport(Some("ws")).expect_err("should fail");
+        assert!(err.contains("unsupported transport"));
+        assert!(err.contains("stdio"));
+        assert!(err.contains("http-stream"));
+    }
+}
@@ -334,7 +334,7 @@ impl ModuleResolver {
 }
 
 /// M3: Return path to package entry file (main.dal or lib.dal) if present.
-fn package_entry_path(package_root: &Path) -> Option<PathBuf> {
+pub(crate) fn package_entry_path(package_root: &Path) -> Option<PathBuf> {
     let main = package_root.join("main.dal");
     let lib = package_root.join("lib.dal");
     if main.exists() {
@@ -60,6 +60,6 @@ impl MoldConfig {
             (s.as_str(), 1)
         };
         let n: usize = num_part.trim().parse().unwrap_or(2);
-        (n * unit).min(usize::MAX) / 4096 // rough page count for max_memory
+        n.saturating_mul(unit) / 4096 // rough page count for max_memory
     }
 }
@@ -36,6 +36,10 @@ pub enum Statement {
     Continue(ContinueStatement),
     Loop(LoopStatement),
     Match(MatchStatement),
+    Struct(StructStatement),
+    Enum(EnumStatement),
+    Trait(TraitStatement),
+    Impl(ImplStatement),
     /// Top-level only: `import <path>;` or `import <path> as <alias>;`
     Import(ImportStatement),
 }
@@ -49,6 +53,71 @@ pub struct ImportStatement {
     pub alias: Option<String>,
 }
 
+/// `struct Name { field: type, ... }` — a named record type; values are `Value::Struct`.
+#[derive(Debug, Clone)]
+pub struct StructStatement {
+    pub name: String,
+    pub fields: Vec<StructField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub name: String,
+    pub field_type: String,
+}
+
+/// `enum Name { A, B(int), ... }` — a tagged union; values are `Value::Enum`.
+#[derive(Debug, Clone)]
+pub struct EnumStatement {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+}
+
+/// One enum variant with its positional payload types (empty for unit variants).
+#[derive(Debug, Clone)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// `trait Name { fn sig(...) -> T; ... }` (or `interface Name { ... }`) — a method contract
+/// that services opt into with `impl Name for Service`.
+#[derive(Debug, Clone)]
+pub struct TraitStatement {
+    pub name: String,
+    pub methods: Vec<TraitMethod>,
+}
+
+/// A required trait method; `default_body` is set when the trait supplies an implementation.
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: String,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<String>,
+    pub default_body: Option<BlockStatement>,
+}
+
+impl TraitMethod {
+    /// The method as a service method, using the default body (empty when there is none).
+    pub fn to_function(&self) -> FunctionStatement {
+        FunctionStatement::new(
+            self.name.clone(),
+            self.parameters.clone(),
+            self.return_type.clone(),
+            self.default_body.clone().unwrap_or_default(),
+        )
+    }
+}
+
+/// `impl Trait for Service { fn ... }`, or `impl Service { fn ... }` with no trait.
+/// Methods are added to the service; trait methods not listed fall back to trait defaults.
+#[derive(Debug, Clone)]
+pub struct ImplStatement {
+    pub trait_name: Option<String>,
+    pub service_name: String,
+    pub methods: Vec<FunctionStatement>,
+}
+
 /// `while ( condition ) { body }`
 #[derive(Debug, Clone)]
 pub struct WhileStatement {
@@ -88,10 +157,12 @@ pub struct MatchStatement {
     pub default_case: Option<BlockStatement>,
 }
 
-/// A single case in a match statement: `pattern => { body }`
+/// A single case in a match statement: `pattern => { body }` or `pattern if guard => { body }`
 #[derive(Debug, Clone)]
 pub struct MatchCase {
     pub pattern: MatchPattern,
+    /// Evaluated with the pattern's bindings in scope; the case is skipped when it is falsy.
+    pub guard: Option<Expression>,
     pub body: BlockStatement,
 }
 
@@ -102,6 +173,68 @@ pub enum MatchPattern {
     Identifier(String), // Match and bind to variable
     Wildcard,           // _ matches anything
     Range(Box<Expression>, Box<Expression>), // start..end (inclusive range)
+    /// `Enum::Variant` or `Enum::Variant(p1, p2)`; payload patterns match positionally
+    EnumVariant {
+        enum_name: String,
+        variant: String,
+        fields: Vec<MatchPattern>,
+    },
+    /// `[a, b, ..rest]`; without a rest the list length must match exactly. An unnamed `..`
+    /// is stored as `"_"`.
+    List {
+        elements: Vec<MatchPattern>,
+        rest: Option<String>,
+    },
+    /// `{ status: "ok", data }` or `Point { x, y }`; matches maps and structs that have every
+    /// listed key. A shorthand key binds the field to a variable of the same name.
+    Map {
+        struct_name: Option<String>,
+        fields: Vec<(String, MatchPattern)>,
+    },
+    Ok(Box<MatchPattern>),   // Ok(p)
+    Err(Box<MatchPattern>),  // Err(p)
+    Some(Box<MatchPattern>), // Some(p)
+    None,                    // None
+}
+
+impl MatchPattern {
+    /// Names bound by this pattern, in source order.
+    pub fn bound_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_bound_names(&mut names);
+        names
+    }
+
+    fn collect_bound_names(&self, names: &mut Vec<String>) {
+        match self {
+            MatchPattern::Identifier(name) => names.push(name.clone()),
+            MatchPattern::EnumVariant { fields, .. } => {
+                for field in fields {
+                    field.collect_bound_names(names);
+                }
+            }
+            MatchPattern::List { elements, rest } => {
+                for element in elements {
+                    element.collect_bound_names(names);
+                }
+                if let Some(rest) = rest.as_ref().filter(|r| r.as_str() != "_") {
+                    names.push(rest.clone());
+                }
+            }
+            MatchPattern::Map { fields, .. } => {
+                for (_, field) in fields {
+                    field.collect_bound_names(names);
+                }
+            }
+            MatchPattern::Ok(inner) | MatchPattern::Err(inner) | MatchPattern::Some(inner) => {
+                inner.collect_bound_names(names)
+            }
+            MatchPattern::Literal(_)
+            | MatchPattern::Wildcard
+            | MatchPattern::Range(..)
+            | MatchPattern::None => {}
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -110,6 +243,9 @@ pub struct LetStatement {
     pub value: Expression,
     /// 1-based line number for warnings (e.g. unused variable); None if unknown.
     pub line: Option<usize>,
+    /// `let [a, b] = xs;` / `let { a, b } = obj;`. When set, `name` is empty and the names
+    /// come from the pattern; a value that does not match is a runtime error.
+    pub pattern: Option<MatchPattern>,
 }
 
 #[derive(Debug, Clone)]
@@ -120,6 +256,8 @@ pub struct ReturnStatement {
 #[derive(Debug, Clone)]
 pub struct BlockStatement {
     pub statements: Vec<Statement>,
+    /// Parallel to `statements` when filled by the parser; may be shorter for hand-built blocks.
+    pub statement_spans: Vec<Option<Span>>,
 }
 
 #[derive(Debug, Clone)]
@@ -232,6 +370,8 @@ pub struct ServiceField {
     pub field_type: String,
     pub initial_value: Option<Expression>,
     pub visibility: FieldVisibility,
+    /// Field annotations other than visibility (e.g. `@persistent`).
+    pub attributes: Vec<Attribute>,
 }
 
 #[derive(Debug, Clone)]
@@ -296,15 +436,25 @@ pub enum Expression {
     Await(Box<Expression>),
     Spawn(Box<Expression>), // spawn <expr> e.g. spawn worker_process(i)
     Throw(Box<Expression>),
+    /// `expr?`: the `Ok` / `Some` payload, or return the `Err` / `None` (or `null`) from the
+    /// enclosing function.
+    Propagate(Box<Expression>),
     ObjectLiteral(HashMap<String, Expression>), // NEW: object literal syntax
     ArrayLiteral(Vec<Expression>),              // NEW: array literal syntax [expr1, expr2, ...]
     /// Index access: `expr[index]` — array or map key access
     IndexAccess(Box<Expression>, Box<Expression>),
-    /// Arrow function: (param => { body }) — single param, block body
+    /// Function value: `x => expr`, `(a, b) => { body }` or `fn(a, b) { body }`. An expression
+    /// body is stored as a block holding `return expr;`.
     ArrowFunction {
-        param: String,
+        params: Vec<String>,
         body: BlockStatement,
     },
+    /// Call of a computed function value: `make_adder(1)(2)`, `handlers[i](event)`, `(x => x)(1)`.
+    /// Calls by name stay `FunctionCall`.
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
     /// Range expression: start..end for for-loops
     Range(Box<Expression>, Box<Expression>),
     /// Method call: receiver.method_name(args) — dispatch by receiver type (service or value)
@@ -313,6 +463,28 @@ pub enum Expression {
         method_name: String,
         arguments: Vec<Expression>,
     },
+    /// `receiver?.field`: `null` when the receiver is `null` / `None` or is a map without the key.
+    OptionalFieldAccess(Box<Expression>, String),
+    /// `receiver?.method(args)`: `null`, without evaluating the arguments, when the receiver is
+    /// `null` / `None`.
+    OptionalMethodCall {
+        receiver: Box<Expression>,
+        method_name: String,
+        arguments: Vec<Expression>,
+    },
+    /// `left ?? right`: `left` unless it is `null` / `None`; `right` is only evaluated then.
+    NullCoalesce(Box<Expression>, Box<Expression>),
+    /// `value |> f`: `f(value)`. A call on the right, `value |> f(a)`, becomes `f(value, a)`; any
+    /// other right side must evaluate to a function value.
+    Pipeline(Box<Expression>, Box<Expression>),
+    /// Interpolated string `"a ${b} c"`: text parts are string literals; every part's display
+    /// text is concatenated.
+    Interpolation(Vec<Expression>),
+    /// Struct constructor: `Name { field: expr, ... }`
+    StructLiteral {
+        name: String,
+        fields: HashMap<String, Expression>,
+    },
 }
 
 impl Program {
@@ -343,11 +515,22 @@ impl BlockStatement {
     pub fn new() -> Self {
         Self {
             statements: Vec::new(),
+            statement_spans: Vec::new(),
         }
     }
 
     pub fn add_statement(&mut self, statement: Statement) {
+        self.add_statement_with_span(statement, None);
+    }
+
+    pub fn add_statement_with_span(&mut self, statement: Statement, span: Option<Span>) {
         self.statements.push(statement);
+        self.statement_spans.push(span);
+    }
+
+    /// Span of the statement at `index`, if the parser recorded one.
+    pub fn span_at(&self, index: usize) -> Option<Span> {
+        self.statement_spans.get(index).copied().flatten()
     }
 }
 
@@ -1,11 +1,14 @@
-use crate::lexer::tokens::{Keyword, Literal, Operator, Punctuation, Token};
+use crate::lexer::tokens::{
+    Keyword, Literal, Operator, Punctuation, TemplatePart, Token, TokenWithPosition,
+};
 use crate::parser::ast::{
     AgentStatement, Attribute, AttributeTarget, BlockStatement, BreakStatement, CatchBlock,
-    CompilationTargetInfo, ContinueStatement, EventDeclaration, EventStatement, Expression,
-    FieldVisibility, ForInStatement, FunctionCall, FunctionStatement, IfStatement, ImportStatement,
-    LetStatement, LoopStatement, MatchCase, MatchPattern, MatchStatement, MessageStatement,
-    Parameter, Program, ReturnStatement, ServiceField, ServiceStatement, Span, SpawnStatement,
-    Statement, TryStatement, WhileStatement,
+    CompilationTargetInfo, ContinueStatement, EnumStatement, EnumVariant, EventDeclaration,
+    EventStatement, Expression, FieldVisibility, ForInStatement, FunctionCall, FunctionStatement,
+    IfStatement, ImplStatement, ImportStatement, LetStatement, LoopStatement, MatchCase,
+    MatchPattern, MatchStatement, MessageStatement, Parameter, Program, ReturnStatement,
+    ServiceField, ServiceStatement, Span, SpawnStatement, Statement, StructField, StructStatement,
+    TraitMethod, TraitStatement, TryStatement, WhileStatement,
 };
 use crate::parser::error::{ErrorContext, ErrorRecovery, ParserError};
 use std::collections::{HashMap, HashSet};
@@ -17,6 +20,14 @@ pub struct Parser {
     pub(crate) recovery_skip_from: Option<usize>,
     /// Set by skip_to_synchronization_point; caller reads this to continue parsing after recovery.
     pub(crate) recovery_continue_at: Option<usize>,
+    /// Token the error being recovered from points at; set alongside `recovery_skip_from`.
+    pub(crate) recovery_error_at: Option<usize>,
+    /// Names declared with `struct Name`, so `Name { ... }` parses as a struct literal.
+    struct_names: HashSet<String>,
+    /// Set by `parse_with_recovery`: blocks and service bodies record a failed statement in
+    /// `recovered_errors` and continue with the next one instead of returning the error.
+    recovering: bool,
+    recovered_errors: Vec<ParserError>,
 }
 
 impl Parser {
@@ -29,10 +40,14 @@ impl Parser {
     /// For user-facing parse paths (CLI, IDE) prefer [`Self::new_with_positions`] so errors have accurate line/column.
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
+            struct_names: Self::declared_struct_names(&tokens),
             tokens,
             token_positions: Vec::new(),
             recovery_skip_from: None,
             recovery_continue_at: None,
+            recovery_error_at: None,
+            recovering: false,
+            recovered_errors: Vec::new(),
         }
     }
 
@@ -46,13 +61,28 @@ impl Parser {
             tokens.push(twp.token);
         }
         Self {
+            struct_names: Self::declared_struct_names(&tokens),
             tokens,
             token_positions: positions,
             recovery_skip_from: None,
             recovery_continue_at: None,
+            recovery_error_at: None,
+            recovering: false,
+            recovered_errors: Vec::new(),
         }
     }
 
+    /// Struct names are collected up front so literals may appear before the declaration.
+    fn declared_struct_names(tokens: &[Token]) -> HashSet<String> {
+        tokens
+            .windows(2)
+            .filter_map(|pair| match pair {
+                [Token::Keyword(Keyword::Struct), Token::Identifier(name)] => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Set the position to skip from when recover_from_error is called (used for multi-error recovery).
     pub fn set_recovery_skip_from(&mut self, position: usize) {
         self.recovery_skip_from = Some(position);
@@ -65,10 +95,14 @@ impl Parser {
 
     /// Advances from `start` to the next synchronization point (`;`, `}`, statement-start keyword, etc.)
     /// and sets `recovery_continue_at`. Used by ErrorRecovery; keeps token stream private.
+    /// Blocks opened on the way are skipped whole, and nothing before `recovery_error_at` (the
+    /// offending token, when known) is a sync point.
     pub(crate) fn skip_to_sync_point_from(&mut self, start: usize) -> bool {
         // Start from start + 1 to ensure we always advance past the error position
         // This prevents infinite loops if the error position itself is a sync point
         let mut pos = start.saturating_add(1);
+        let error_at = self.recovery_error_at.take().unwrap_or(0);
+        let mut depth = 0usize;
 
         // If start + 1 is already beyond bounds, set continue_at to end and exit
         if pos >= self.tokens.len() {
@@ -78,6 +112,38 @@ impl Parser {
 
         while pos < self.tokens.len() {
             let token = &self.tokens[pos];
+            let past_error = pos > error_at;
+            match token {
+                Token::Punctuation(Punctuation::LeftBrace) => {
+                    depth += 1;
+                    pos += 1;
+                    continue;
+                }
+                Token::Punctuation(Punctuation::RightBrace) if depth > 0 => {
+                    depth -= 1;
+                    pos += 1;
+                    // A block of the failed statement closed: resume after it unless the
+                    // statement goes on (`} else`, `} catch`).
+                    if depth == 0
+                        && pos > error_at
+                        && !matches!(
+                            self.tokens.get(pos),
+                            Some(Token::Keyword(
+                                Keyword::Else | Keyword::Catch | Keyword::Finally
+                            ))
+                        )
+                    {
+                        self.recovery_continue_at = Some(pos);
+                        return true;
+                    }
+                    continue;
+                }
+                _ if depth > 0 || pos < error_at => {
+                    pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
             let is_sync = match token {
                 Token::Punctuation(Punctuation::Semicolon)
                 | Token::Punctuation(Punctuation::RightBrace) => true,
@@ -92,14 +158,18 @@ impl Parser {
                         | Keyword::For
                         | Keyword::Return
                         | Keyword::Service
+                        | Keyword::Struct
+                        | Keyword::Enum
+                        | Keyword::Trait
+                        | Keyword::Impl
                         | Keyword::Agent
                         | Keyword::Spawn
                         | Keyword::Event
                         | Keyword::Msg
                         | Keyword::Async
                 ),
-                Token::Punctuation(Punctuation::LeftBrace) => true,
-                Token::Punctuation(Punctuation::At) => true,
+                // An attribute starts the next statement, but not when it is the offending token.
+                Token::Punctuation(Punctuation::At) => past_error,
                 _ => false,
             };
             if is_sync {
@@ -113,6 +183,14 @@ impl Parser {
         true
     }
 
+    /// Index of the token an `UnexpectedToken` error points at, searching from `start`.
+    fn error_token_index(&self, start: usize, error: &ParserError) -> Option<usize> {
+        let ParserError::UnexpectedToken { line, column, .. } = error else {
+            return None;
+        };
+        (start..self.token_positions.len()).find(|&i| self.token_positions[i] == (*line, *column))
+    }
+
     fn get_token_position(&self, position: usize) -> (usize, usize) {
         if position < self.token_positions.len() {
             self.token_positions[position]
@@ -169,10 +247,12 @@ impl Parser {
 
     /// Parse and collect multiple errors by recovering at statement boundaries.
     /// Returns (program with successfully parsed statements, list of parse errors).
+    /// A statement that fails inside a block or service body is dropped and parsing goes on with
+    /// the next one, so the enclosing function or service is still returned.
     pub fn parse_with_recovery(&mut self) -> (Program, Vec<ParserError>) {
         let mut program = Program::new();
-        let mut errors = Vec::new();
         let mut position = 0;
+        self.recovering = true;
 
         while position < self.tokens.len() {
             if matches!(self.tokens.get(position), Some(Token::EOF)) {
@@ -185,30 +265,37 @@ impl Parser {
                     program.add_statement_with_span(statement, Some(Span { line, column }));
                     position = new_position;
                 }
-                Err(e) => {
-                    errors.push(e.clone());
-                    self.set_recovery_skip_from(position);
-                    if self.recover_from_error(&e).is_ok() {
-                        if let Some(next_pos) = self.get_recovery_continue_at() {
-                            // skip_to_sync_point_from now always starts from start+1, so next_pos should always be > position
-                            // But add defensive check just in case
-                            if next_pos > position {
-                                position = next_pos;
-                            } else {
-                                // This shouldn't happen with the fix above, but if it does, advance to prevent infinite loop
-                                position += 1;
-                            }
-                        } else {
-                            break;
-                        }
-                    } else {
-                        break;
-                    }
-                }
+                Err(e) => match self.recover_statement(position, e) {
+                    // Recovery always advances past the failed statement's first token.
+                    Ok(next_pos) => position = next_pos.max(position + 1),
+                    Err(_) => break,
+                },
             }
         }
 
-        (program, errors)
+        self.recovering = false;
+        (program, std::mem::take(&mut self.recovered_errors))
+    }
+
+    /// When recovering, records `error` for the statement starting at `position` and returns the
+    /// position of the next statement (past a trailing `;`). Otherwise returns the error.
+    fn recover_statement(
+        &mut self,
+        position: usize,
+        error: ParserError,
+    ) -> Result<usize, ParserError> {
+        if !self.recovering {
+            return Err(error);
+        }
+        self.set_recovery_skip_from(position);
+        self.recovery_error_at = self.error_token_index(position, &error);
+        self.recover_from_error(&error)?;
+        self.recovered_errors.push(error);
+        let mut next_pos = self.get_recovery_continue_at().unwrap_or(self.tokens.len());
+        if let Some(Token::Punctuation(Punctuation::Semicolon)) = self.tokens.get(next_pos) {
+            next_pos += 1;
+        }
+        Ok(next_pos)
     }
 
     fn parse_statement(
@@ -407,6 +494,20 @@ impl Parser {
             self.parse_loop_statement(position, depth)
         } else if let Some(Token::Keyword(Keyword::Match)) = self.tokens.get(position) {
             self.parse_match_statement(position, depth)
+        } else if let Some(Token::Keyword(Keyword::Struct)) = self.tokens.get(position) {
+            self.parse_struct_statement(position)
+        } else if let Some(Token::Keyword(Keyword::Enum)) = self.tokens.get(position) {
+            self.parse_enum_statement(position)
+        } else if let Some(Token::Keyword(Keyword::Trait)) = self.tokens.get(position) {
+            self.parse_trait_statement(position, depth)
+        } else if matches!(
+            self.tokens.get(position),
+            Some(Token::Keyword(Keyword::Interface))
+        ) && matches!(self.tokens.get(position + 1), Some(Token::Identifier(_)))
+        {
+            self.parse_trait_statement(position, depth)
+        } else if let Some(Token::Keyword(Keyword::Impl)) = self.tokens.get(position) {
+            self.parse_impl_statement(position, depth)
         } else if let Some(Token::Keyword(Keyword::Service)) = self.tokens.get(position) {
             self.parse_service_statement(position, depth)
         } else if let Some(Token::Keyword(Keyword::Return)) = self.tokens.get(position) {
@@ -453,9 +554,21 @@ impl Parser {
                 false
             };
 
-        // Use expect_identifier_or_keyword to allow keywords as variable names (e.g., "agent", "ai", "chain")
-        let (new_position, name) = self.expect_identifier_or_keyword(current_position)?;
-        current_position = new_position;
+        // Destructuring: `let [a, ..rest] = xs;` / `let { a, b } = obj;`
+        let (name, pattern) = if matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::LeftBracket))
+                | Some(Token::Punctuation(Punctuation::LeftBrace))
+        ) {
+            let (new_position, pattern) = self.parse_match_pattern(current_position)?;
+            current_position = new_position;
+            (String::new(), Some(pattern))
+        } else {
+            // Use expect_identifier_or_keyword to allow keywords as variable names (e.g., "agent", "ai", "chain")
+            let (new_position, name) = self.expect_identifier_or_keyword(current_position)?;
+            current_position = new_position;
+            (name, None)
+        };
 
         let (new_position, _) =
             self.expect_token(current_position, &Token::Operator(Operator::Assign))?;
@@ -476,6 +589,7 @@ impl Parser {
                 name,
                 value,
                 line: Some(line),
+                pattern,
             }),
         ))
     }
@@ -599,9 +713,14 @@ impl Parser {
                 return Ok((new_position, block));
             }
 
-            let (new_position, statement) = self.parse_statement(current_position, depth + 1)?;
-            block.add_statement(statement);
-            current_position = new_position;
+            match self.parse_statement(current_position, depth + 1) {
+                Ok((new_position, statement)) => {
+                    let (line, column) = self.get_token_position(current_position);
+                    block.add_statement_with_span(statement, Some(Span { line, column }));
+                    current_position = new_position;
+                }
+                Err(e) => current_position = self.recover_statement(current_position, e)?,
+            }
         }
 
         Err(ParserError::unexpected_eof("}"))
@@ -639,7 +758,11 @@ impl Parser {
             });
         }
 
-        let (position, expr) = self.parse_or(position, depth)?;
+        if let Some(params) = self.arrow_parameters(position) {
+            return self.parse_arrow_function(position, params, depth);
+        }
+
+        let (position, expr) = self.parse_pipeline(position, depth)?;
 
         if let Some(Token::Operator(Operator::Assign)) = self.tokens.get(position) {
             let (position, _) = self.expect_token(position, &Token::Operator(Operator::Assign))?;
@@ -714,6 +837,144 @@ impl Parser {
         Ok((position, expr))
     }
 
+    /// When `position` starts an arrow function (`x =>`, `() =>` or `(a, b) =>`), return its
+    /// parameter names without consuming anything.
+    fn arrow_parameters(&self, position: usize) -> Option<Vec<String>> {
+        match self.tokens.get(position)? {
+            Token::Identifier(name) => matches!(
+                self.tokens.get(position + 1),
+                Some(Token::Punctuation(Punctuation::FatArrow))
+            )
+            .then(|| vec![name.clone()]),
+            Token::Punctuation(Punctuation::LeftParen) => {
+                let mut params = Vec::new();
+                let mut current_position = position + 1;
+                if !matches!(
+                    self.tokens.get(current_position),
+                    Some(Token::Punctuation(Punctuation::RightParen))
+                ) {
+                    loop {
+                        let Some(Token::Identifier(name)) = self.tokens.get(current_position)
+                        else {
+                            return None;
+                        };
+                        params.push(name.clone());
+                        current_position += 1;
+                        match self.tokens.get(current_position) {
+                            Some(Token::Punctuation(Punctuation::Comma)) => current_position += 1,
+                            Some(Token::Punctuation(Punctuation::RightParen)) => break,
+                            _ => return None,
+                        }
+                    }
+                }
+                matches!(
+                    self.tokens.get(current_position + 1),
+                    Some(Token::Punctuation(Punctuation::FatArrow))
+                )
+                .then_some(params)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse an arrow function whose parameters `arrow_parameters` found at `position`. The body
+    /// is a block, or an expression that becomes `{ return expr; }`.
+    fn parse_arrow_function(
+        &mut self,
+        position: usize,
+        params: Vec<String>,
+        depth: usize,
+    ) -> Result<(usize, Expression), ParserError> {
+        let mut current_position = position;
+        while !matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::FatArrow))
+        ) {
+            current_position += 1;
+        }
+        current_position += 1; // consume '=>'
+
+        if let Some(Token::Punctuation(Punctuation::LeftBrace)) = self.tokens.get(current_position)
+        {
+            let (new_position, body) = self.parse_block_statement(current_position, depth + 1)?;
+            return Ok((new_position, Expression::ArrowFunction { params, body }));
+        }
+
+        let (line, column) = self.get_token_position(current_position);
+        let (new_position, value) = self.parse_assignment(current_position, depth + 1)?;
+        let mut body = BlockStatement::new();
+        body.add_statement_with_span(
+            Statement::Return(ReturnStatement { value: Some(value) }),
+            Some(Span { line, column }),
+        );
+        Ok((new_position, Expression::ArrowFunction { params, body }))
+    }
+
+    /// Parse an anonymous `fn(params) -> T { body }` expression (the return type is ignored).
+    fn parse_anonymous_function(
+        &mut self,
+        position: usize,
+        depth: usize,
+    ) -> Result<(usize, Expression), ParserError> {
+        let (mut current_position, _) =
+            self.expect_token(position + 1, &Token::Punctuation(Punctuation::LeftParen))?;
+        let (new_position, parameters) = self.parse_parameters(current_position)?;
+        let (new_position, _) =
+            self.expect_token(new_position, &Token::Punctuation(Punctuation::RightParen))?;
+        current_position = new_position;
+        if let Some(Token::Punctuation(Punctuation::Arrow)) = self.tokens.get(current_position) {
+            let (new_position, _) = self.parse_type_expression(current_position + 1)?;
+            current_position = new_position;
+        }
+        let (new_position, body) = self.parse_block_statement(current_position, depth + 1)?;
+        Ok((
+            new_position,
+            Expression::ArrowFunction {
+                params: parameters.into_iter().map(|p| p.name).collect(),
+                body,
+            },
+        ))
+    }
+
+    /// `value |> f |> g(a)`, left-associative and binding looser than every other binary operator.
+    /// A stage may be an arrow function; its expression body extends to the end of the pipeline.
+    fn parse_pipeline(
+        &mut self,
+        position: usize,
+        depth: usize,
+    ) -> Result<(usize, Expression), ParserError> {
+        let (mut current_position, mut expr) = self.parse_null_coalesce(position, depth)?;
+
+        while let Some(Token::Operator(Operator::Pipeline)) = self.tokens.get(current_position) {
+            current_position += 1;
+            let (new_pos, stage) = match self.arrow_parameters(current_position) {
+                Some(params) => self.parse_arrow_function(current_position, params, depth + 1)?,
+                None => self.parse_null_coalesce(current_position, depth)?,
+            };
+            expr = Expression::Pipeline(Box::new(expr), Box::new(stage));
+            current_position = new_pos;
+        }
+
+        Ok((current_position, expr))
+    }
+
+    fn parse_null_coalesce(
+        &mut self,
+        position: usize,
+        depth: usize,
+    ) -> Result<(usize, Expression), ParserError> {
+        let (mut current_position, mut expr) = self.parse_or(position, depth)?;
+
+        while let Some(Token::Operator(Operator::NullCoalesce)) = self.tokens.get(current_position)
+        {
+            let (new_pos, right) = self.parse_or(current_position + 1, depth)?;
+            expr = Expression::NullCoalesce(Box::new(expr), Box::new(right));
+            current_position = new_pos;
+        }
+
+        Ok((current_position, expr))
+    }
+
     fn parse_or(
         &mut self,
         position: usize,
@@ -988,6 +1249,58 @@ impl Parser {
                 continue;
             }
 
+            // Error propagation: expr?
+            if let Some(Token::Punctuation(Punctuation::Question)) =
+                self.tokens.get(current_position)
+            {
+                current_position += 1;
+                expr = Expression::Propagate(Box::new(expr));
+                continue;
+            }
+
+            // Optional chaining: expr?.field and expr?.method(args)
+            if let Some(Token::Operator(Operator::OptionalChain)) =
+                self.tokens.get(current_position)
+            {
+                let (new_pos, field_name) =
+                    self.expect_identifier_or_keyword(current_position + 1)?;
+                current_position = new_pos;
+                if let Some(Token::Punctuation(Punctuation::LeftParen)) =
+                    self.tokens.get(current_position)
+                {
+                    let (new_pos, arguments) =
+                        self.parse_function_arguments(current_position, depth)?;
+                    current_position = new_pos;
+                    expr = Expression::OptionalMethodCall {
+                        receiver: Box::new(expr),
+                        method_name: field_name,
+                        arguments,
+                    };
+                } else {
+                    expr = Expression::OptionalFieldAccess(Box::new(expr), field_name);
+                }
+                continue;
+            }
+
+            // Call of a computed function value: expr(args)
+            if let Some(Token::Punctuation(Punctuation::LeftParen)) =
+                self.tokens.get(current_position)
+            {
+                let (new_pos, arguments) =
+                    self.parse_function_arguments(current_position, depth)?;
+                current_position = new_pos;
+                expr = match expr {
+                    Expression::Identifier(name) => {
+                        Expression::FunctionCall(FunctionCall { name, arguments })
+                    }
+                    callee => Expression::Call {
+                        callee: Box::new(callee),
+                        arguments,
+                    },
+                };
+                continue;
+            }
+
             // Chained field access: expr.field (for cases like self.balances[key])
             if let Some(Token::Punctuation(Punctuation::Dot)) = self.tokens.get(current_position) {
                 let (new_pos, _) =
@@ -1021,6 +1334,24 @@ impl Parser {
         Ok((current_position, expr))
     }
 
+    /// Parse the tokens of one `${...}` as a single expression.
+    fn parse_interpolated_code(
+        &self,
+        tokens: Vec<TokenWithPosition>,
+        depth: usize,
+    ) -> Result<Expression, ParserError> {
+        let mut parser = Parser::new_with_positions(tokens);
+        parser.struct_names = self.struct_names.clone();
+        if matches!(parser.tokens.first(), Some(Token::EOF)) {
+            return Err(parser.error_unexpected_token(0, &["expression"]));
+        }
+        let (position, expr) = parser.parse_expression_with_depth(0, depth + 1)?;
+        match parser.tokens.get(position) {
+            Some(Token::EOF) => Ok(expr),
+            _ => Err(parser.error_unexpected_token(position, &["}"])),
+        }
+    }
+
     fn parse_primary(
         &mut self,
         position: usize,
@@ -1051,12 +1382,33 @@ impl Parser {
                         Expression::Literal(Literal::String(value.clone())),
                     ));
                 }
+                Token::Template(template) => {
+                    let template = template.clone();
+                    let mut parts = Vec::with_capacity(template.len());
+                    for part in template {
+                        parts.push(match part {
+                            TemplatePart::Text(text) => Expression::Literal(Literal::String(text)),
+                            TemplatePart::Code(tokens) => {
+                                self.parse_interpolated_code(tokens, depth)?
+                            }
+                        });
+                    }
+                    return Ok((position + 1, Expression::Interpolation(parts)));
+                }
                 Token::Literal(Literal::Bool(value)) => {
                     return Ok((position + 1, Expression::Literal(Literal::Bool(*value))));
                 }
                 Token::Literal(Literal::Null) => {
                     return Ok((position + 1, Expression::Literal(Literal::Null)));
                 }
+                Token::Literal(
+                    literal @ (Literal::U256(_)
+                    | Literal::I256(_)
+                    | Literal::Decimal(_)
+                    | Literal::Bytes(_)),
+                ) => {
+                    return Ok((position + 1, Expression::Literal(literal.clone())));
+                }
                 Token::Identifier(name) => {
                     let namespace_name = name.clone();
 
@@ -1124,6 +1476,24 @@ impl Parser {
                         }
                     }
 
+                    // Struct literal: Name { field: value, ... }
+                    if self.struct_names.contains(&namespace_name)
+                        && matches!(
+                            self.tokens.get(position + 1),
+                            Some(Token::Punctuation(Punctuation::LeftBrace))
+                        )
+                    {
+                        let (new_position, fields) =
+                            self.parse_object_literal(position + 1, depth)?;
+                        return Ok((
+                            new_position,
+                            Expression::StructLiteral {
+                                name: namespace_name,
+                                fields,
+                            },
+                        ));
+                    }
+
                     // Check if this is a namespace call (identifier::identifier)
                     if let Some(Token::Punctuation(Punctuation::DoubleColon)) =
                         self.tokens.get(position + 1)
@@ -1299,6 +1669,14 @@ impl Parser {
                     let (position, expr) = self.parse_expression_with_depth(position, depth + 1)?;
                     return Ok((position, Expression::Throw(Box::new(expr))));
                 }
+                Token::Keyword(Keyword::Fn)
+                    if matches!(
+                        self.tokens.get(position + 1),
+                        Some(Token::Punctuation(Punctuation::LeftParen))
+                    ) =>
+                {
+                    return self.parse_anonymous_function(position, depth);
+                }
                 Token::Keyword(_) => {
                     // Allow keywords to be used as identifiers in expressions (e.g., "chain" as a variable name)
                     let (new_position, name) = self.expect_identifier_or_keyword(position)?;
@@ -1357,6 +1735,32 @@ impl Parser {
         &mut self,
         position: usize,
     ) -> Result<(usize, Statement), ParserError> {
+        let (mut current_position, (name, parameters, return_type)) =
+            self.parse_function_signature(position)?;
+
+        let (new_position, body) = self.parse_block_statement(current_position, 0)?;
+        current_position = new_position;
+
+        Ok((
+            current_position,
+            Statement::Function(FunctionStatement {
+                name,
+                parameters,
+                return_type,
+                body,
+                attributes: Vec::new(),
+                is_async: false,
+                exported: false,
+            }),
+        ))
+    }
+
+    /// Parse `fn name(params) -> T` up to (not including) the body.
+    #[allow(clippy::type_complexity)]
+    fn parse_function_signature(
+        &self,
+        position: usize,
+    ) -> Result<(usize, (String, Vec<Parameter>, Option<String>)), ParserError> {
         let mut current_position = position + 1; // consume 'fn'
 
         let (new_position, name) = self.expect_identifier(current_position)?;
@@ -1391,21 +1795,7 @@ impl Parser {
             None
         };
 
-        let (new_position, body) = self.parse_block_statement(current_position, 0)?;
-        current_position = new_position;
-
-        Ok((
-            current_position,
-            Statement::Function(FunctionStatement {
-                name,
-                parameters,
-                return_type,
-                body,
-                attributes: Vec::new(),
-                is_async: false,
-                exported: false,
-            }),
-        ))
+        Ok((current_position, (name, parameters, return_type)))
     }
 
     fn parse_async_function_statement(
@@ -1904,9 +2294,10 @@ impl Parser {
         let (new_position, recipient) = self.expect_identifier(current_position)?;
         current_position = new_position;
 
-        let (new_position, _) =
-            self.expect_token(current_position, &Token::Keyword(Keyword::With))?;
-        current_position = new_position;
+        // `msg recipient { ... }`; the older `msg recipient with { ... }` is still accepted.
+        if let Some(Token::Keyword(Keyword::With)) = self.tokens.get(current_position) {
+            current_position += 1;
+        }
 
         let (new_position, data) = self.parse_message_data(current_position, depth)?;
         current_position = new_position;
@@ -2034,10 +2425,12 @@ impl Parser {
                 self.tokens.get(current_position),
                 self.tokens.get(current_position + 1),
             ) {
+                let (line, column) = self.get_token_position(current_position);
                 let (new_pos, if_stmt) = self.parse_if_statement(current_position, depth)?;
                 current_position = new_pos;
                 BlockStatement {
                     statements: vec![if_stmt],
+                    statement_spans: vec![Some(Span { line, column })],
                 }
             } else {
                 let (new_pos, block) = self.parse_block_statement(current_position, depth + 1)?;
@@ -2122,34 +2515,6 @@ impl Parser {
             current_position = new_position;
             arguments.push(argument);
 
-            // Arrow function: (param => { body }) — if last arg is single identifier and next is =>
-            let param_name = arguments.last().and_then(|a| {
-                if let Expression::Identifier(p) = a {
-                    Some(p.clone())
-                } else {
-                    None
-                }
-            });
-            if let Some(param) = param_name {
-                if let Some(Token::Punctuation(Punctuation::FatArrow)) =
-                    self.tokens.get(current_position)
-                {
-                    let (pos2, _) = self.expect_token(
-                        current_position,
-                        &Token::Punctuation(Punctuation::FatArrow),
-                    )?;
-                    let (pos3, body) = self.parse_block_statement(pos2, depth)?;
-                    arguments.pop();
-                    arguments.push(Expression::ArrowFunction { param, body });
-                    current_position = pos3;
-                    let (new_pos, _) = self.expect_token(
-                        current_position,
-                        &Token::Punctuation(Punctuation::RightParen),
-                    )?;
-                    return Ok((new_pos, arguments));
-                }
-            }
-
             // Check for comma or end of arguments
             if let Some(Token::Punctuation(Punctuation::Comma)) = self.tokens.get(current_position)
             {
@@ -2320,7 +2685,8 @@ impl Parser {
         let mut error_type = None;
         let mut error_variable = None;
 
-        // Check if catch has parameters: catch (ErrorType error_var)
+        // Check if catch has parameters: catch (ErrorType error_var), catch (error_var: ErrorType)
+        // or catch (name), where a capitalized name is a type and anything else a variable.
         if let Some(Token::Punctuation(Punctuation::LeftParen)) = self.tokens.get(current_position)
         {
             let (new_position, _) = self.expect_token(
@@ -2329,17 +2695,26 @@ impl Parser {
             )?;
             current_position = new_position;
 
-            // Parse error type if present
-            if let Some(Token::Identifier(type_name)) = self.tokens.get(current_position) {
-                let (new_position, _) = self.expect_identifier(current_position)?;
-                current_position = new_position;
-                error_type = Some(type_name.clone());
-
-                // Parse error variable name
-                if let Some(Token::Identifier(var_name)) = self.tokens.get(current_position) {
-                    let (new_position, _) = self.expect_identifier(current_position)?;
-                    current_position = new_position;
-                    error_variable = Some(var_name.clone());
+            if let Some(Token::Identifier(first)) = self.tokens.get(current_position) {
+                let first = first.clone();
+                current_position += 1;
+                match self.tokens.get(current_position) {
+                    Some(Token::Identifier(var_name)) => {
+                        error_type = Some(first);
+                        error_variable = Some(var_name.clone());
+                        current_position += 1;
+                    }
+                    Some(Token::Punctuation(Punctuation::Colon)) => {
+                        let (new_position, type_name) =
+                            self.expect_identifier(current_position + 1)?;
+                        current_position = new_position;
+                        error_variable = Some(first);
+                        error_type = Some(type_name);
+                    }
+                    _ if first.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                        error_type = Some(first);
+                    }
+                    _ => error_variable = Some(first),
                 }
             }
 
@@ -2575,6 +2950,17 @@ impl Parser {
             let (new_position, pattern) = pattern;
             current_position = new_position;
 
+            // Optional guard: `pattern if cond => ...`. Parsed below assignment level so a bare
+            // `flag =>` is not mistaken for an arrow function.
+            let guard = if let Some(Token::Keyword(Keyword::If)) = self.tokens.get(current_position)
+            {
+                let (new_position, guard) = self.parse_or(current_position + 1, depth)?;
+                current_position = new_position;
+                Some(guard)
+            } else {
+                None
+            };
+
             // Expect fat arrow
             let (new_position, _) =
                 self.expect_token(current_position, &Token::Punctuation(Punctuation::FatArrow))?;
@@ -2625,7 +3011,11 @@ impl Parser {
                 block
             };
 
-            cases.push(MatchCase { pattern, body });
+            cases.push(MatchCase {
+                pattern,
+                guard,
+                body,
+            });
 
             // Check for comma (optional)
             if let Some(Token::Punctuation(Punctuation::Comma)) = self.tokens.get(current_position)
@@ -2644,18 +3034,52 @@ impl Parser {
         ))
     }
 
-    /// Parse a match pattern: literal, identifier, wildcard, or range
+    /// Parse a match pattern: literal, identifier, wildcard, range, enum variant, list, map or
+    /// struct, or `Ok`/`Err`/`Some`/`None`
     fn parse_match_pattern(
         &mut self,
         position: usize,
     ) -> Result<(usize, MatchPattern), ParserError> {
         let current_position = position;
 
-        // Check for wildcard
-        if let Some(Token::Identifier(ref id)) = self.tokens.get(current_position) {
-            if id == "_" {
-                return Ok((current_position + 1, MatchPattern::Wildcard));
+        match self.tokens.get(current_position) {
+            Some(Token::Punctuation(Punctuation::LeftBracket)) => {
+                return self.parse_list_pattern(current_position);
+            }
+            Some(Token::Punctuation(Punctuation::LeftBrace)) => {
+                return self.parse_map_pattern(current_position, None);
+            }
+            Some(Token::Identifier(id)) => {
+                let id = id.clone();
+                let next = self.tokens.get(current_position + 1);
+                match id.as_str() {
+                    // Check for wildcard
+                    "_" => return Ok((current_position + 1, MatchPattern::Wildcard)),
+                    "None" => return Ok((current_position + 1, MatchPattern::None)),
+                    "Ok" | "Err" | "Some"
+                        if matches!(next, Some(Token::Punctuation(Punctuation::LeftParen))) =>
+                    {
+                        let (new_position, inner) =
+                            self.parse_match_pattern(current_position + 2)?;
+                        let (new_position, _) = self.expect_token(
+                            new_position,
+                            &Token::Punctuation(Punctuation::RightParen),
+                        )?;
+                        let inner = Box::new(inner);
+                        let pattern = match id.as_str() {
+                            "Ok" => MatchPattern::Ok(inner),
+                            "Err" => MatchPattern::Err(inner),
+                            _ => MatchPattern::Some(inner),
+                        };
+                        return Ok((new_position, pattern));
+                    }
+                    _ if matches!(next, Some(Token::Punctuation(Punctuation::LeftBrace))) => {
+                        return self.parse_map_pattern(current_position + 1, Some(id));
+                    }
+                    _ => {}
+                }
             }
+            _ => {}
         }
 
         // Check for range pattern (start..end)
@@ -2696,16 +3120,426 @@ impl Parser {
             return Ok((current_position + 1, MatchPattern::Literal(lit.clone())));
         }
 
-        // Otherwise parse as identifier (binds the value)
-        let (new_position, identifier) = self.expect_identifier_or_keyword(current_position)?;
-        Ok((new_position, MatchPattern::Identifier(identifier)))
-    }
-
-    // NEW: Service statement parsing with pre-parsed attributes (M5: exported flag)
-    fn parse_service_statement_with_attributes(
-        &mut self,
-        position: usize,
-        pre_parsed_attributes: Vec<Attribute>,
+        // Enum variant pattern: Enum::Variant or Enum::Variant(p1, p2)
+        if let (
+            Some(Token::Identifier(enum_name)),
+            Some(Token::Punctuation(Punctuation::DoubleColon)),
+        ) = (
+            self.tokens.get(current_position).cloned(),
+            self.tokens.get(current_position + 1),
+        ) {
+            let (mut new_position, variant) =
+                self.expect_identifier_or_keyword(current_position + 2)?;
+            let mut fields = Vec::new();
+            if let Some(Token::Punctuation(Punctuation::LeftParen)) = self.tokens.get(new_position)
+            {
+                new_position += 1;
+                while !matches!(
+                    self.tokens.get(new_position),
+                    Some(Token::Punctuation(Punctuation::RightParen))
+                ) {
+                    let (pos, field) = self.parse_match_pattern(new_position)?;
+                    fields.push(field);
+                    new_position = pos;
+                    if let Some(Token::Punctuation(Punctuation::Comma)) =
+                        self.tokens.get(new_position)
+                    {
+                        new_position += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let (pos, _) =
+                    self.expect_token(new_position, &Token::Punctuation(Punctuation::RightParen))?;
+                new_position = pos;
+            }
+            return Ok((
+                new_position,
+                MatchPattern::EnumVariant {
+                    enum_name,
+                    variant,
+                    fields,
+                },
+            ));
+        }
+
+        // Otherwise parse as identifier (binds the value)
+        let (new_position, identifier) = self.expect_identifier_or_keyword(current_position)?;
+        Ok((new_position, MatchPattern::Identifier(identifier)))
+    }
+
+    /// Parse `[p1, p2, ..rest]`; `..` may appear once, as the last element
+    fn parse_list_pattern(
+        &mut self,
+        position: usize,
+    ) -> Result<(usize, MatchPattern), ParserError> {
+        let mut current_position = position + 1; // consume '['
+        let mut elements = Vec::new();
+        let mut rest = None;
+        while !matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::RightBracket))
+        ) {
+            if let Some(Token::Punctuation(Punctuation::DotDot)) = self.tokens.get(current_position)
+            {
+                current_position += 1;
+                if let Some(Token::Identifier(name)) = self.tokens.get(current_position) {
+                    rest = Some(name.clone());
+                    current_position += 1;
+                } else {
+                    rest = Some("_".to_string());
+                }
+                break;
+            }
+            let (new_position, element) = self.parse_match_pattern(current_position)?;
+            elements.push(element);
+            current_position = new_position;
+            if let Some(Token::Punctuation(Punctuation::Comma)) = self.tokens.get(current_position)
+            {
+                current_position += 1;
+            } else {
+                break;
+            }
+        }
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::RightBracket),
+        )?;
+        Ok((new_position, MatchPattern::List { elements, rest }))
+    }
+
+    /// Parse `{ key: pattern, shorthand }` (position at the `{`); `struct_name` is set for
+    /// `Point { x, y }`
+    fn parse_map_pattern(
+        &mut self,
+        position: usize,
+        struct_name: Option<String>,
+    ) -> Result<(usize, MatchPattern), ParserError> {
+        let mut current_position = position + 1; // consume '{'
+        let mut fields = Vec::new();
+        while !matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::RightBrace))
+        ) {
+            let (new_position, key) = if let Some(Token::Literal(Literal::String(key))) =
+                self.tokens.get(current_position)
+            {
+                (current_position + 1, key.clone())
+            } else {
+                self.expect_identifier_or_keyword(current_position)?
+            };
+            current_position = new_position;
+            let pattern = if let Some(Token::Punctuation(Punctuation::Colon)) =
+                self.tokens.get(current_position)
+            {
+                let (new_position, pattern) = self.parse_match_pattern(current_position + 1)?;
+                current_position = new_position;
+                pattern
+            } else {
+                MatchPattern::Identifier(key.clone())
+            };
+            fields.push((key, pattern));
+            if let Some(Token::Punctuation(Punctuation::Comma)) = self.tokens.get(current_position)
+            {
+                current_position += 1;
+            } else {
+                break;
+            }
+        }
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::RightBrace),
+        )?;
+        Ok((
+            new_position,
+            MatchPattern::Map {
+                struct_name,
+                fields,
+            },
+        ))
+    }
+
+    /// Parse `trait Name { fn sig(...) -> T; fn with_default() { ... } }` (`interface` is accepted too)
+    fn parse_trait_statement(
+        &mut self,
+        position: usize,
+        depth: usize,
+    ) -> Result<(usize, Statement), ParserError> {
+        let mut current_position = position + 1; // consume 'trait' / 'interface'
+
+        let (new_position, name) = self.expect_identifier(current_position)?;
+        current_position = new_position;
+
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::LeftBrace),
+        )?;
+        current_position = new_position;
+
+        let mut methods: Vec<TraitMethod> = Vec::new();
+        loop {
+            match self.tokens.get(current_position) {
+                Some(Token::Punctuation(Punctuation::RightBrace)) => {
+                    current_position += 1;
+                    break;
+                }
+                Some(Token::Keyword(Keyword::Fn)) => {
+                    let (new_position, (method_name, parameters, return_type)) =
+                        self.parse_function_signature(current_position)?;
+                    current_position = new_position;
+
+                    let default_body = if let Some(Token::Punctuation(Punctuation::LeftBrace)) =
+                        self.tokens.get(current_position)
+                    {
+                        let (new_position, body) =
+                            self.parse_block_statement(current_position, depth + 1)?;
+                        current_position = new_position;
+                        Some(body)
+                    } else {
+                        let (new_position, _) = self.expect_token(
+                            current_position,
+                            &Token::Punctuation(Punctuation::Semicolon),
+                        )?;
+                        current_position = new_position;
+                        None
+                    };
+
+                    if methods.iter().any(|m| m.name == method_name) {
+                        let (line, _) = self.get_token_position(current_position);
+                        return Err(ParserError::SemanticError {
+                            message: format!(
+                                "Duplicate method '{}' in trait '{}'",
+                                method_name, name
+                            ),
+                            line,
+                            context: ErrorContext::new(),
+                        });
+                    }
+                    methods.push(TraitMethod {
+                        name: method_name,
+                        parameters,
+                        return_type,
+                        default_body,
+                    });
+                }
+                _ => return Err(self.error_unexpected_token(current_position, &["fn", "}"])),
+            }
+        }
+
+        Ok((
+            current_position,
+            Statement::Trait(TraitStatement { name, methods }),
+        ))
+    }
+
+    /// Parse `impl Trait for Service { fn ... }` or `impl Service { fn ... }`
+    fn parse_impl_statement(
+        &mut self,
+        position: usize,
+        depth: usize,
+    ) -> Result<(usize, Statement), ParserError> {
+        let mut current_position = position + 1; // consume 'impl'
+
+        let (new_position, first_name) = self.expect_identifier(current_position)?;
+        current_position = new_position;
+
+        let (trait_name, service_name) =
+            if let Some(Token::Keyword(Keyword::For)) = self.tokens.get(current_position) {
+                let (new_position, service_name) = self.expect_identifier(current_position + 1)?;
+                current_position = new_position;
+                (Some(first_name), service_name)
+            } else {
+                (None, first_name)
+            };
+
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::LeftBrace),
+        )?;
+        current_position = new_position;
+
+        let mut methods: Vec<FunctionStatement> = Vec::new();
+        loop {
+            let mut attributes = Vec::new();
+            while let Some(Token::Punctuation(Punctuation::At)) = self.tokens.get(current_position)
+            {
+                let (new_position, attr) = self.parse_attribute(current_position, depth + 1)?;
+                attributes.push(attr);
+                current_position = new_position;
+            }
+            match self.tokens.get(current_position) {
+                Some(Token::Punctuation(Punctuation::RightBrace)) if attributes.is_empty() => {
+                    current_position += 1;
+                    break;
+                }
+                Some(Token::Keyword(Keyword::Fn)) => {
+                    let (new_position, method) = self.parse_function_statement(current_position)?;
+                    current_position = new_position;
+                    if let Statement::Function(mut func) = method {
+                        func.attributes = attributes;
+                        methods.push(func);
+                    }
+                }
+                Some(Token::Keyword(Keyword::Async)) => {
+                    let (new_position, method) =
+                        self.parse_async_function_statement(current_position)?;
+                    current_position = new_position;
+                    if let Statement::Function(mut func) = method {
+                        func.attributes = attributes;
+                        methods.push(func);
+                    }
+                }
+                _ => return Err(self.error_unexpected_token(current_position, &["fn", "}"])),
+            }
+        }
+
+        Ok((
+            current_position,
+            Statement::Impl(ImplStatement {
+                trait_name,
+                service_name,
+                methods,
+            }),
+        ))
+    }
+
+    /// Parse `struct Name { field: type, ... }` (fields separated by `,` or `;`)
+    fn parse_struct_statement(&self, position: usize) -> Result<(usize, Statement), ParserError> {
+        let mut current_position = position + 1; // consume 'struct'
+
+        let (new_position, name) = self.expect_identifier(current_position)?;
+        current_position = new_position;
+
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::LeftBrace),
+        )?;
+        current_position = new_position;
+
+        let mut fields = Vec::new();
+        while !matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::RightBrace))
+        ) {
+            let (new_position, field_name) = self.expect_identifier_or_keyword(current_position)?;
+            current_position = new_position;
+
+            let (new_position, _) =
+                self.expect_token(current_position, &Token::Punctuation(Punctuation::Colon))?;
+            current_position = new_position;
+
+            let (new_position, field_type) = self.parse_type_expression(current_position)?;
+            current_position = new_position;
+
+            if fields.iter().any(|f: &StructField| f.name == field_name) {
+                let (line, _) = self.get_token_position(current_position);
+                return Err(ParserError::SemanticError {
+                    message: format!("Duplicate field '{}' in struct '{}'", field_name, name),
+                    line,
+                    context: ErrorContext::new(),
+                });
+            }
+            fields.push(StructField {
+                name: field_name,
+                field_type,
+            });
+
+            match self.tokens.get(current_position) {
+                Some(Token::Punctuation(Punctuation::Comma))
+                | Some(Token::Punctuation(Punctuation::Semicolon)) => current_position += 1,
+                Some(Token::Punctuation(Punctuation::RightBrace)) => {}
+                _ => return Err(self.error_unexpected_token(current_position, &[",", ";", "}"])),
+            }
+        }
+        current_position += 1; // consume '}'
+
+        Ok((
+            current_position,
+            Statement::Struct(StructStatement { name, fields }),
+        ))
+    }
+
+    /// Parse `enum Name { A, B(type, ...), ... }`
+    fn parse_enum_statement(&self, position: usize) -> Result<(usize, Statement), ParserError> {
+        let mut current_position = position + 1; // consume 'enum'
+
+        let (new_position, name) = self.expect_identifier(current_position)?;
+        current_position = new_position;
+
+        let (new_position, _) = self.expect_token(
+            current_position,
+            &Token::Punctuation(Punctuation::LeftBrace),
+        )?;
+        current_position = new_position;
+
+        let mut variants: Vec<EnumVariant> = Vec::new();
+        while !matches!(
+            self.tokens.get(current_position),
+            Some(Token::Punctuation(Punctuation::RightBrace))
+        ) {
+            let (new_position, variant_name) = self.expect_identifier(current_position)?;
+            current_position = new_position;
+
+            let mut fields = Vec::new();
+            if let Some(Token::Punctuation(Punctuation::LeftParen)) =
+                self.tokens.get(current_position)
+            {
+                current_position += 1;
+                while !matches!(
+                    self.tokens.get(current_position),
+                    Some(Token::Punctuation(Punctuation::RightParen))
+                ) {
+                    let (new_position, field_type) =
+                        self.parse_type_expression(current_position)?;
+                    current_position = new_position;
+                    fields.push(field_type);
+                    if let Some(Token::Punctuation(Punctuation::Comma)) =
+                        self.tokens.get(current_position)
+                    {
+                        current_position += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let (new_position, _) = self.expect_token(
+                    current_position,
+                    &Token::Punctuation(Punctuation::RightParen),
+                )?;
+                current_position = new_position;
+            }
+
+            if variants.iter().any(|v| v.name == variant_name) {
+                let (line, _) = self.get_token_position(current_position);
+                return Err(ParserError::SemanticError {
+                    message: format!("Duplicate variant '{}' in enum '{}'", variant_name, name),
+                    line,
+                    context: ErrorContext::new(),
+                });
+            }
+            variants.push(EnumVariant {
+                name: variant_name,
+                fields,
+            });
+
+            match self.tokens.get(current_position) {
+                Some(Token::Punctuation(Punctuation::Comma)) => current_position += 1,
+                Some(Token::Punctuation(Punctuation::RightBrace)) => {}
+                _ => return Err(self.error_unexpected_token(current_position, &[",", "}"])),
+            }
+        }
+        current_position += 1; // consume '}'
+
+        Ok((
+            current_position,
+            Statement::Enum(EnumStatement { name, variants }),
+        ))
+    }
+
+    // NEW: Service statement parsing with pre-parsed attributes (M5: exported flag)
+    fn parse_service_statement_with_attributes(
+        &mut self,
+        position: usize,
+        pre_parsed_attributes: Vec<Attribute>,
         exported: bool,
         depth: usize,
     ) -> Result<(usize, Statement), ParserError> {
@@ -2782,99 +3616,22 @@ impl Parser {
         // Parse service body (one level deeper: inside service braces)
         let body_depth = depth.saturating_add(1);
         while current_position < self.tokens.len() {
-            match self.tokens.get(current_position) {
-                Some(Token::Punctuation(Punctuation::At)) => {
-                    // Check if this is attributes before a function
-                    let mut attr_position = current_position;
-                    let mut method_attributes = Vec::new();
-
-                    // Collect all attributes
-                    while attr_position < self.tokens.len() {
-                        if let Some(Token::Punctuation(Punctuation::At)) =
-                            self.tokens.get(attr_position)
-                        {
-                            let (new_pos, attr) =
-                                self.parse_attribute(attr_position, body_depth)?;
-                            method_attributes.push(attr);
-                            attr_position = new_pos;
-                        } else {
-                            break;
-                        }
-                    }
-
-                    // Check if attributes are followed by function declaration
-                    // After parsing attributes, attr_position points to the token after the last attribute
-                    if attr_position < self.tokens.len() {
-                        match self.tokens.get(attr_position) {
-                            Some(Token::Keyword(Keyword::Fn)) => {
-                                // Found function - parse it with attributes
-                                let (new_position, mut method) =
-                                    self.parse_function_statement(attr_position)?;
-                                if let Statement::Function(ref mut func) = method {
-                                    func.attributes = method_attributes;
-                                }
-                                methods.push(if let Statement::Function(func) = method {
-                                    func
-                                } else {
-                                    unreachable!()
-                                });
-                                current_position = new_position;
-                            }
-                            Some(Token::Identifier(_)) => {
-                                // Attributes followed by identifier - this is a field with visibility attribute
-                                let (new_position, field) =
-                                    self.parse_service_field(current_position, body_depth)?;
-                                fields.push(field);
-                                current_position = new_position;
-                            }
-                            _ => {
-                                // Attributes not followed by function or field name - error
-                                let (line, _col) = self.get_token_position(attr_position);
-                                return Err(ParserError::SemanticError {
-                                    message: format!(
-                                        "Attributes must be followed by 'fn' (function) or field name, found: {:?}",
-                                        self.tokens.get(attr_position)
-                                    ),
-                                    line,
-                                    context: ErrorContext::new(),
-                                });
-                            }
-                        }
-                    } else {
-                        // End of tokens after attributes - error
-                        let (line, _col) = self.get_token_position(attr_position.saturating_sub(1));
-                        return Err(ParserError::SemanticError {
-                            message: "Attributes must be followed by 'fn' (function) or field name"
-                                .to_string(),
-                            line,
-                            context: ErrorContext::new(),
-                        });
-                    }
-                }
-                Some(Token::Keyword(Keyword::Fn)) => {
-                    let (new_position, method) = self.parse_function_statement(current_position)?;
-                    if let Statement::Function(func) = method {
-                        methods.push(func);
-                    }
-                    current_position = new_position;
-                }
-                Some(Token::Keyword(Keyword::Event)) => {
-                    let (new_position, event) = self.parse_event_declaration(current_position)?;
-                    events.push(event);
-                    current_position = new_position;
-                }
-                Some(Token::Punctuation(Punctuation::RightBrace)) => {
-                    current_position += 1;
-                    break;
-                }
-                _ => {
-                    // Try to parse field declaration
-                    let (new_position, field) =
-                        self.parse_service_field(current_position, body_depth)?;
-                    fields.push(field);
-                    current_position = new_position;
-                }
+            if let Some(Token::Punctuation(Punctuation::RightBrace)) =
+                self.tokens.get(current_position)
+            {
+                current_position += 1;
+                break;
             }
+            current_position = match self.parse_service_member(
+                current_position,
+                body_depth,
+                &mut fields,
+                &mut methods,
+                &mut events,
+            ) {
+                Ok(new_position) => new_position,
+                Err(e) => self.recover_statement(current_position, e)?,
+            };
         }
 
         // All attributes on this service apply to the module
@@ -2950,99 +3707,22 @@ impl Parser {
         // Parse service body (one level deeper: inside service braces)
         let body_depth = depth.saturating_add(1);
         while current_position < self.tokens.len() {
-            match self.tokens.get(current_position) {
-                Some(Token::Punctuation(Punctuation::At)) => {
-                    // Check if this is attributes before a function
-                    let mut attr_position = current_position;
-                    let mut method_attributes = Vec::new();
-
-                    // Collect all attributes
-                    while attr_position < self.tokens.len() {
-                        if let Some(Token::Punctuation(Punctuation::At)) =
-                            self.tokens.get(attr_position)
-                        {
-                            let (new_pos, attr) =
-                                self.parse_attribute(attr_position, body_depth)?;
-                            method_attributes.push(attr);
-                            attr_position = new_pos;
-                        } else {
-                            break;
-                        }
-                    }
-
-                    // Check if attributes are followed by function declaration
-                    // After parsing attributes, attr_position points to the token after the last attribute
-                    if attr_position < self.tokens.len() {
-                        match self.tokens.get(attr_position) {
-                            Some(Token::Keyword(Keyword::Fn)) => {
-                                // Found function - parse it with attributes
-                                let (new_position, mut method) =
-                                    self.parse_function_statement(attr_position)?;
-                                if let Statement::Function(ref mut func) = method {
-                                    func.attributes = method_attributes;
-                                }
-                                methods.push(if let Statement::Function(func) = method {
-                                    func
-                                } else {
-                                    unreachable!()
-                                });
-                                current_position = new_position;
-                            }
-                            Some(Token::Identifier(_)) => {
-                                // Attributes followed by identifier - this is a field with visibility attribute
-                                let (new_position, field) =
-                                    self.parse_service_field(current_position, body_depth)?;
-                                fields.push(field);
-                                current_position = new_position;
-                            }
-                            _ => {
-                                // Attributes not followed by function or field name - error
-                                let (line, _col) = self.get_token_position(attr_position);
-                                return Err(ParserError::SemanticError {
-                                    message: format!(
-                                        "Attributes must be followed by 'fn' (function) or field name, found: {:?}",
-                                        self.tokens.get(attr_position)
-                                    ),
-                                    line,
-                                    context: ErrorContext::new(),
-                                });
-                            }
-                        }
-                    } else {
-                        // End of tokens after attributes - error
-                        let (line, _col) = self.get_token_position(attr_position.saturating_sub(1));
-                        return Err(ParserError::SemanticError {
-                            message: "Attributes must be followed by 'fn' (function) or field name"
-                                .to_string(),
-                            line,
-                            context: ErrorContext::new(),
-                        });
-                    }
-                }
-                Some(Token::Keyword(Keyword::Fn)) => {
-                    let (new_position, method) = self.parse_function_statement(current_position)?;
-                    if let Statement::Function(func) = method {
-                        methods.push(func);
-                    }
-                    current_position = new_position;
-                }
-                Some(Token::Keyword(Keyword::Event)) => {
-                    let (new_position, event) = self.parse_event_declaration(current_position)?;
-                    events.push(event);
-                    current_position = new_position;
-                }
-                Some(Token::Punctuation(Punctuation::RightBrace)) => {
-                    current_position += 1;
-                    break;
-                }
-                _ => {
-                    // Try to parse field declaration
-                    let (new_position, field) =
-                        self.parse_service_field(current_position, body_depth)?;
-                    fields.push(field);
-                    current_position = new_position;
-                }
+            if let Some(Token::Punctuation(Punctuation::RightBrace)) =
+                self.tokens.get(current_position)
+            {
+                current_position += 1;
+                break;
             }
+            current_position = match self.parse_service_member(
+                current_position,
+                body_depth,
+                &mut fields,
+                &mut methods,
+                &mut events,
+            ) {
+                Ok(new_position) => new_position,
+                Err(e) => self.recover_statement(current_position, e)?,
+            };
         }
 
         for attr in &mut attributes {
@@ -3069,6 +3749,106 @@ impl Parser {
         Ok((current_position, Statement::Service(service_stmt)))
     }
 
+    /// Parse one service member (field, method or event) at `current_position` into the
+    /// matching list, returning the position after it.
+    fn parse_service_member(
+        &mut self,
+        current_position: usize,
+        body_depth: usize,
+        fields: &mut Vec<ServiceField>,
+        methods: &mut Vec<FunctionStatement>,
+        events: &mut Vec<EventDeclaration>,
+    ) -> Result<usize, ParserError> {
+        match self.tokens.get(current_position) {
+            Some(Token::Punctuation(Punctuation::At)) => {
+                // Check if this is attributes before a function
+                let mut attr_position = current_position;
+                let mut method_attributes = Vec::new();
+
+                // Collect all attributes
+                while attr_position < self.tokens.len() {
+                    if let Some(Token::Punctuation(Punctuation::At)) =
+                        self.tokens.get(attr_position)
+                    {
+                        let (new_pos, attr) = self.parse_attribute(attr_position, body_depth)?;
+                        method_attributes.push(attr);
+                        attr_position = new_pos;
+                    } else {
+                        break;
+                    }
+                }
+
+                // Check if attributes are followed by function declaration
+                // After parsing attributes, attr_position points to the token after the last attribute
+                if attr_position < self.tokens.len() {
+                    match self.tokens.get(attr_position) {
+                        Some(Token::Keyword(Keyword::Fn)) => {
+                            // Found function - parse it with attributes
+                            let (new_position, mut method) =
+                                self.parse_function_statement(attr_position)?;
+                            if let Statement::Function(ref mut func) = method {
+                                func.attributes = method_attributes;
+                            }
+                            methods.push(if let Statement::Function(func) = method {
+                                func
+                            } else {
+                                unreachable!()
+                            });
+                            Ok(new_position)
+                        }
+                        Some(Token::Identifier(_)) => {
+                            // Attributes followed by identifier - this is a field with visibility attribute
+                            let (new_position, field) =
+                                self.parse_service_field(current_position, body_depth)?;
+                            fields.push(field);
+                            Ok(new_position)
+                        }
+                        _ => {
+                            // Attributes not followed by function or field name - error
+                            let (line, _col) = self.get_token_position(attr_position);
+                            Err(ParserError::SemanticError {
+                                message: format!(
+                                    "Attributes must be followed by 'fn' (function) or field name, found: {:?}",
+                                    self.tokens.get(attr_position)
+                                ),
+                                line,
+                                context: ErrorContext::new(),
+                            })
+                        }
+                    }
+                } else {
+                    // End of tokens after attributes - error
+                    let (line, _col) = self.get_token_position(attr_position.saturating_sub(1));
+                    Err(ParserError::SemanticError {
+                        message: "Attributes must be followed by 'fn' (function) or field name"
+                            .to_string(),
+                        line,
+                        context: ErrorContext::new(),
+                    })
+                }
+            }
+            Some(Token::Keyword(Keyword::Fn)) => {
+                let (new_position, method) = self.parse_function_statement(current_position)?;
+                if let Statement::Function(func) = method {
+                    methods.push(func);
+                }
+                Ok(new_position)
+            }
+            Some(Token::Keyword(Keyword::Event)) => {
+                let (new_position, event) = self.parse_event_declaration(current_position)?;
+                events.push(event);
+                Ok(new_position)
+            }
+            _ => {
+                // Try to parse field declaration
+                let (new_position, field) =
+                    self.parse_service_field(current_position, body_depth)?;
+                fields.push(field);
+                Ok(new_position)
+            }
+        }
+    }
+
     fn parse_service_field(
         &mut self,
         position: usize,
@@ -3077,31 +3857,27 @@ impl Parser {
         let mut current_position = position;
         let mut visibility = FieldVisibility::Public;
 
-        // Optional visibility: @public / @private / @internal or keyword private before field name
-        if let Some(Token::Punctuation(Punctuation::At)) = self.tokens.get(current_position) {
-            let (new_position, _) =
-                self.expect_token(current_position, &Token::Punctuation(Punctuation::At))?;
-            current_position = new_position;
-            let (new_position, vis_name) = self.expect_identifier_or_keyword(current_position)?;
-            current_position = new_position;
-            let vis_name = vis_name.to_lowercase();
-            visibility = match vis_name.as_str() {
+        let mut attributes = Vec::new();
+
+        // Optional annotations: @public / @private / @internal set visibility; anything else
+        // (e.g. @persistent) is kept as a field attribute. Keyword `private` also works.
+        while let Some(Token::Punctuation(Punctuation::At)) = self.tokens.get(current_position) {
+            let (after_name, annotation) =
+                self.expect_identifier_or_keyword(current_position + 1)?;
+            visibility = match annotation.to_lowercase().as_str() {
                 "public" => FieldVisibility::Public,
                 "private" => FieldVisibility::Private,
                 "internal" => FieldVisibility::Internal,
                 _ => {
-                    let (line, col) = self.get_token_position(current_position.saturating_sub(1));
-                    return Err(ParserError::unexpected_token(
-                        self.tokens
-                            .get(current_position.saturating_sub(1))
-                            .unwrap_or(&Token::EOF),
-                        &["@public", "@private", "@internal"],
-                        line,
-                        col,
-                    ));
+                    let (new_position, attr) = self.parse_attribute(current_position, depth)?;
+                    attributes.push(attr);
+                    current_position = new_position;
+                    continue;
                 }
             };
-        } else if let Some(Token::Keyword(Keyword::Private)) = self.tokens.get(current_position) {
+            current_position = after_name;
+        }
+        if let Some(Token::Keyword(Keyword::Private)) = self.tokens.get(current_position) {
             let (new_position, _) =
                 self.expect_token(current_position, &Token::Keyword(Keyword::Private))?;
             current_position = new_position;
@@ -3147,6 +3923,7 @@ impl Parser {
             field_type,
             initial_value,
             visibility,
+            attributes,
         };
 
         Ok((current_position, field))
@@ -3392,14 +4169,21 @@ impl Parser {
             Expression::Assignment(_, v) => {
                 out.extend(self.collect_namespaces_from_expression(v));
             }
-            Expression::FieldAccess(obj, _) => {
+            Expression::FieldAccess(obj, _) | Expression::OptionalFieldAccess(obj, _) => {
                 out.extend(self.collect_namespaces_from_expression(obj));
             }
+            Expression::NullCoalesce(l, r) | Expression::Pipeline(l, r) => {
+                out.extend(self.collect_namespaces_from_expression(l));
+                out.extend(self.collect_namespaces_from_expression(r));
+            }
             Expression::FieldAssignment(obj, _, v) => {
                 out.extend(self.collect_namespaces_from_expression(obj));
                 out.extend(self.collect_namespaces_from_expression(v));
             }
-            Expression::Await(e) | Expression::Spawn(e) | Expression::Throw(e) => {
+            Expression::Await(e)
+            | Expression::Spawn(e)
+            | Expression::Throw(e)
+            | Expression::Propagate(e) => {
                 out.extend(self.collect_namespaces_from_expression(e));
             }
             Expression::IndexAccess(c, i) => {
@@ -3411,7 +4195,7 @@ impl Parser {
                     out.extend(self.collect_namespaces_from_expression(e));
                 }
             }
-            Expression::ArrayLiteral(elems) => {
+            Expression::ArrayLiteral(elems) | Expression::Interpolation(elems) => {
                 for e in elems {
                     out.extend(self.collect_namespaces_from_expression(e));
                 }
@@ -3427,12 +4211,28 @@ impl Parser {
                 receiver,
                 arguments,
                 ..
+            }
+            | Expression::OptionalMethodCall {
+                receiver,
+                arguments,
+                ..
             } => {
                 out.extend(self.collect_namespaces_from_expression(receiver));
                 for arg in arguments {
                     out.extend(self.collect_namespaces_from_expression(arg));
                 }
             }
+            Expression::Call { callee, arguments } => {
+                out.extend(self.collect_namespaces_from_expression(callee));
+                for arg in arguments {
+                    out.extend(self.collect_namespaces_from_expression(arg));
+                }
+            }
+            Expression::StructLiteral { fields, .. } => {
+                for e in fields.values() {
+                    out.extend(self.collect_namespaces_from_expression(e));
+                }
+            }
             _ => {}
         }
         out
@@ -3518,6 +4318,9 @@ impl Parser {
                 out.extend(self.collect_namespaces_from_expression(&match_stmt.expression));
                 for case in &match_stmt.cases {
                     out.extend(self.collect_namespaces_from_block(&case.body));
+                    if let Some(guard) = &case.guard {
+                        out.extend(self.collect_namespaces_from_expression(guard));
+                    }
                     // Check range patterns for expressions
                     if let crate::parser::ast::MatchPattern::Range(start, end) = &case.pattern {
                         out.extend(self.collect_namespaces_from_expression(start));
@@ -3528,8 +4331,20 @@ impl Parser {
                     out.extend(self.collect_namespaces_from_block(default_body));
                 }
             }
-            Statement::Import(_) => {
-                // Import path does not contribute namespace calls in this pass
+            Statement::Trait(trait_stmt) => {
+                for method in &trait_stmt.methods {
+                    if let Some(ref body) = method.default_body {
+                        out.extend(self.collect_namespaces_from_block(body));
+                    }
+                }
+            }
+            Statement::Impl(impl_stmt) => {
+                for method in &impl_stmt.methods {
+                    out.extend(self.collect_namespaces_from_block(&method.body));
+                }
+            }
+            Statement::Import(_) | Statement::Struct(_) | Statement::Enum(_) => {
+                // Imports and type declarations do not contribute namespace calls
             }
         }
         out
@@ -1,4 +1,4 @@
-//! Parse-time warnings (unused variables and similar).
+//! Parse-time warnings (unused variables, calls to `@deprecated` functions and similar).
 //!
 //! Collected by walking the AST after a successful parse. Does not fail the parse;
 //! expose via CLI (e.g. after run/check/lint) and API.
@@ -19,15 +19,63 @@ pub fn collect_warnings(program: &Program) -> Vec<ParseWarning> {
     let mut pass = WarningPass {
         warnings: Vec::new(),
         scopes: Vec::new(),
+        deprecated: collect_deprecated(program),
+        line: 0,
     };
     pass.visit_program(program);
     pass.warnings
 }
 
+/// Deprecated callables by call name (`f`, `Service::method`, `.method`) -> optional note
+/// from `@deprecated("...")`. A deprecated service marks all of its methods.
+fn collect_deprecated(program: &Program) -> HashMap<String, Option<String>> {
+    fn note(attributes: &[Attribute]) -> Option<Option<String>> {
+        let attr = attributes.iter().find(|a| a.name == "@deprecated")?;
+        Some(match attr.parameters.first() {
+            Some(Expression::Literal(crate::lexer::tokens::Literal::String(s))) => Some(s.clone()),
+            _ => None,
+        })
+    }
+    let mut deprecated = HashMap::new();
+    let mut current_methods = Vec::new();
+    for stmt in &program.statements {
+        match stmt {
+            Statement::Function(func) => {
+                if let Some(n) = note(&func.attributes) {
+                    deprecated.insert(func.name.clone(), n);
+                }
+            }
+            Statement::Service(service) => {
+                let service_note = note(&service.attributes);
+                if let Some(n) = &service_note {
+                    deprecated.insert(format!("{}::new", service.name), n.clone());
+                }
+                for method in &service.methods {
+                    if let Some(n) = note(&method.attributes).or_else(|| service_note.clone()) {
+                        deprecated.insert(format!("{}::{}", service.name, method.name), n.clone());
+                        deprecated.insert(format!(".{}", method.name), n);
+                    } else {
+                        current_methods.push(format!(".{}", method.name));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    // `x.method()` only warns when no service has a current method of that name.
+    for name in current_methods {
+        deprecated.remove(&name);
+    }
+    deprecated
+}
+
 struct WarningPass {
     warnings: Vec<ParseWarning>,
     /// Stack of scopes: each scope maps bound name -> (line, used).
     scopes: Vec<HashMap<String, (usize, bool)>>,
+    deprecated: HashMap<String, Option<String>>,
+    /// Line of the innermost statement with a known position; 0 if unknown.
+    line: usize,
 }
 
 impl WarningPass {
@@ -38,7 +86,8 @@ impl WarningPass {
     fn pop_scope(&mut self) {
         if let Some(scope) = self.scopes.pop() {
             for (name, (line, used)) in scope {
-                if !used {
+                // A leading `_` marks a binding as intentionally unused.
+                if !used && !name.starts_with('_') {
                     self.warnings.push(ParseWarning {
                         message: format!("unused variable '{}'", name),
                         line,
@@ -54,6 +103,19 @@ impl WarningPass {
         }
     }
 
+    fn warn_if_deprecated(&mut self, call_name: &str, shown: &str) {
+        if let Some(note) = self.deprecated.get(call_name) {
+            let message = match note {
+                Some(note) => format!("call to deprecated function '{}': {}", shown, note),
+                None => format!("call to deprecated function '{}'", shown),
+            };
+            self.warnings.push(ParseWarning {
+                message,
+                line: self.line,
+            });
+        }
+    }
+
     fn mark_used(&mut self, name: &str) {
         for scope in self.scopes.iter_mut().rev() {
             if let Some((_line, used)) = scope.get_mut(name) {
@@ -65,7 +127,10 @@ impl WarningPass {
 
     fn visit_program(&mut self, program: &Program) {
         self.push_scope();
-        for stmt in &program.statements {
+        for (i, stmt) in program.statements.iter().enumerate() {
+            if let Some(Some(span)) = program.statement_spans.get(i) {
+                self.line = span.line;
+            }
             self.visit_statement(stmt);
         }
         self.pop_scope();
@@ -73,9 +138,22 @@ impl WarningPass {
 
     fn visit_statement(&mut self, stmt: &Statement) {
         match stmt {
-            Statement::Let(LetStatement { name, value, line }) => {
-                let line = line.unwrap_or(0);
-                self.bind(name.clone(), line);
+            Statement::Let(LetStatement {
+                name,
+                value,
+                line,
+                pattern,
+            }) => {
+                let line = line.unwrap_or(self.line);
+                self.line = line;
+                match pattern {
+                    Some(pattern) => {
+                        for bound in pattern.bound_names() {
+                            self.bind(bound, line);
+                        }
+                    }
+                    None => self.bind(name.clone(), line),
+                }
                 self.visit_expression(value);
             }
             Statement::Expression(expr) => self.visit_expression(expr),
@@ -103,7 +181,8 @@ impl WarningPass {
                 }
                 self.pop_scope();
             }
-            Statement::Service(ServiceStatement { methods, .. }) => {
+            Statement::Service(ServiceStatement { methods, .. })
+            | Statement::Impl(ImplStatement { methods, .. }) => {
                 for method in methods {
                     self.push_scope();
                     for param in &method.parameters {
@@ -206,8 +285,9 @@ impl WarningPass {
                 self.visit_expression(expression);
                 for case in cases {
                     self.push_scope();
-                    if let MatchPattern::Identifier(name) = &case.pattern {
-                        self.bind(name.clone(), 0);
+                    self.bind_pattern(&case.pattern);
+                    if let Some(guard) = &case.guard {
+                        self.visit_expression(guard);
                     }
                     for s in &case.body.statements {
                         self.visit_statement(s);
@@ -234,7 +314,28 @@ impl WarningPass {
                 }
             }
             Statement::Continue(ContinueStatement) => {}
-            Statement::Import(_) => {}
+            Statement::Trait(TraitStatement { methods, .. }) => {
+                for method in methods {
+                    let Some(body) = &method.default_body else {
+                        continue;
+                    };
+                    self.push_scope();
+                    for param in &method.parameters {
+                        self.bind(param.name.clone(), 0);
+                    }
+                    for s in &body.statements {
+                        self.visit_statement(s);
+                    }
+                    self.pop_scope();
+                }
+            }
+            Statement::Import(_) | Statement::Struct(_) | Statement::Enum(_) => {}
+        }
+    }
+
+    fn bind_pattern(&mut self, pattern: &MatchPattern) {
+        for name in pattern.bound_names() {
+            self.bind(name, 0);
         }
     }
 
@@ -253,25 +354,35 @@ impl WarningPass {
                 self.visit_expression(r);
             }
             Expression::UnaryOp(_op, e) => self.visit_expression(e),
-            Expression::FunctionCall(FunctionCall { arguments, .. }) => {
+            Expression::FunctionCall(FunctionCall { name, arguments }) => {
+                // A call by name may go through a variable holding a closure.
+                self.mark_used(name);
+                self.warn_if_deprecated(name, name);
                 for arg in arguments {
                     self.visit_expression(arg);
                 }
             }
-            Expression::FieldAccess(e, _) | Expression::Await(e) | Expression::Spawn(e) => {
+            Expression::FieldAccess(e, _)
+            | Expression::OptionalFieldAccess(e, _)
+            | Expression::Await(e)
+            | Expression::Spawn(e) => {
                 self.visit_expression(e);
             }
+            Expression::NullCoalesce(l, r) | Expression::Pipeline(l, r) => {
+                self.visit_expression(l);
+                self.visit_expression(r);
+            }
             Expression::FieldAssignment(e1, _f, e2) => {
                 self.visit_expression(e1);
                 self.visit_expression(e2);
             }
-            Expression::Throw(e) => self.visit_expression(e),
-            Expression::ObjectLiteral(map) => {
+            Expression::Throw(e) | Expression::Propagate(e) => self.visit_expression(e),
+            Expression::ObjectLiteral(map) | Expression::StructLiteral { fields: map, .. } => {
                 for e in map.values() {
                     self.visit_expression(e);
                 }
             }
-            Expression::ArrayLiteral(list) => {
+            Expression::ArrayLiteral(list) | Expression::Interpolation(list) => {
                 for e in list {
                     self.visit_expression(e);
                 }
@@ -280,9 +391,11 @@ impl WarningPass {
                 self.visit_expression(e1);
                 self.visit_expression(e2);
             }
-            Expression::ArrowFunction { param, body } => {
+            Expression::ArrowFunction { params, body } => {
                 self.push_scope();
-                self.bind(param.clone(), 0);
+                for param in params {
+                    self.bind(param.clone(), 0);
+                }
                 for s in &body.statements {
                     self.visit_statement(s);
                 }
@@ -290,14 +403,27 @@ impl WarningPass {
             }
             Expression::MethodCall {
                 receiver,
+                method_name,
+                arguments,
+            }
+            | Expression::OptionalMethodCall {
+                receiver,
+                method_name,
                 arguments,
-                ..
             } => {
+                // The receiver's service is not known statically; match on the method name.
+                self.warn_if_deprecated(&format!(".{}", method_name), method_name);
                 self.visit_expression(receiver);
                 for arg in arguments {
                     self.visit_expression(arg);
                 }
             }
+            Expression::Call { callee, arguments } => {
+                self.visit_expression(callee);
+                for arg in arguments {
+                    self.visit_expression(arg);
+                }
+            }
             Expression::Range(e1, e2) => {
                 self.visit_expression(e1);
                 self.visit_expression(e2);
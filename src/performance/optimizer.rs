@@ -136,6 +136,7 @@ impl Optimizer {
                             name: let_stmt.name.clone(),
                             value: folded_value,
                             line: let_stmt.line,
+                            pattern: let_stmt.pattern.clone(),
                         }));
                         optimizations.push(format!("Constant folded: {}", let_stmt.name));
                     } else {
@@ -364,7 +365,7 @@ impl Optimizer {
 
         for statement in &ast.statements {
             match statement {
-                Statement::Let(let_stmt) => {
+                Statement::Let(let_stmt) if let_stmt.pattern.is_none() => {
                     let cache_key = self.expression_to_string(&let_stmt.value);
                     if let Some(existing_var) = expression_cache.get(&cache_key) {
                         // Replace with existing variable
@@ -372,6 +373,7 @@ impl Optimizer {
                             name: let_stmt.name.clone(),
                             value: Expression::Identifier(existing_var.clone()),
                             line: let_stmt.line,
+                            pattern: None,
                         }));
                         optimizations.push(format!("CSE: {} = {}", let_stmt.name, existing_var));
                     } else {
@@ -477,6 +479,7 @@ impl Optimizer {
                             name: let_stmt.name.clone(),
                             value: folded_value,
                             line: let_stmt.line,
+                            pattern: let_stmt.pattern.clone(),
                         }));
                     } else {
                         optimized_statements.push(statement.clone());
@@ -488,6 +491,7 @@ impl Optimizer {
 
         BlockStatement {
             statements: optimized_statements,
+            statement_spans: block.statement_spans.clone(),
         }
     }
 
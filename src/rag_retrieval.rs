@@ -586,7 +586,7 @@ mod tests {
         std::fs::write(docs.join("a.md"), "# A\nhello").unwrap();
         std::fs::write(docs.join("b.md"), "# B\nworld").unwrap();
         let out_dir = dir.path().join("idx");
-        let (n_files, n_chunks) = write_index(&[docs.clone()], &out_dir).unwrap();
+        let (n_files, n_chunks) = write_index(std::slice::from_ref(&docs), &out_dir).unwrap();
         assert_eq!(n_files, 2);
         assert!(
             n_chunks >= 2,
@@ -8,6 +8,7 @@ use crate::lexer::LexerError;
 use crate::parser::error::{ErrorContext, ParserError};
 use crate::parser::ParseWarning;
 use crate::runtime::functions::RuntimeErrorWithContext;
+use crate::runtime::type_checker::TypeDiagnostic;
 
 /// Format a lexer error for display: message, optional file path, and source line with caret.
 pub fn format_lexer_error(e: &LexerError, file_path: Option<&str>, source: Option<&str>) -> String {
@@ -93,3 +94,36 @@ pub fn format_parse_warnings(
     }
     out
 }
+
+/// Format static type errors from `runtime::check_program` with source line and caret.
+pub fn format_type_errors(
+    errors: &[TypeDiagnostic],
+    file_path: Option<&str>,
+    source: Option<&str>,
+) -> String {
+    if errors.is_empty() {
+        return String::new();
+    }
+    let mut out = String::new();
+    if let Some(path) = file_path {
+        if !path.is_empty() {
+            out.push_str(&format!("  --> {}\n", path));
+        }
+    }
+    out.push_str(&format!("❌ Type errors ({}):\n", errors.len()));
+    let lines: Vec<&str> = source.map(|s| s.lines().collect()).unwrap_or_default();
+    for e in errors {
+        let line = e.span.line;
+        if line > 0 && line <= lines.len() {
+            out.push_str(&format!("  --> Line {}: {}\n", line, e));
+            out.push_str(&format!("      {}\n", lines[line - 1].trim_end()));
+            let pad = " ".repeat(e.span.column.saturating_sub(1));
+            out.push_str(&format!("      {}^\n", pad));
+        } else if line > 0 {
+            out.push_str(&format!("  --> Line {}: {}\n", line, e));
+        } else {
+            out.push_str(&format!("  --> {}\n", e));
+        }
+    }
+    out
+}
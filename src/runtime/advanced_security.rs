@@ -1341,14 +1341,14 @@ impl AdvancedSecurityManager {
         variable_name: &str,
         value: &Value,
     ) -> Result<(), RuntimeError> {
-        // Create a simple assignment verification
-        let contract_code = format!("let {} = {:?};", variable_name, value);
         // Only verify if a specification exists - otherwise skip (optional verification)
         if self
             .formal_verification
             .contract_specifications
             .contains_key("assignment")
         {
+            // Create a simple assignment verification
+            let contract_code = format!("let {} = {:?};", variable_name, value);
             let result = self
                 .formal_verification
                 .verify_contract("assignment", &contract_code)?;
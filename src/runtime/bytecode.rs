@@ -0,0 +1,597 @@
+//! Bytecode for the interpreter hot path.
+//!
+//! [`compile_body`] lowers a function, method or closure body (and [`compile_statement`] a
+//! top-level statement) into a [`Chunk`]: a flat list of stack-machine [`Instruction`]s run by
+//! [`crate::runtime::vm`]. Expressions and the control flow that dominates tight loops —
+//! arithmetic, variables, calls, `if`, `while`, `for` and `loop` — get their own instructions.
+//! Everything else (declarations, `try`, `match`, agents, ...) stays AST and is handed back to
+//! the tree-walking evaluator, so both paths share one set of semantics.
+//!
+//! Variables the compiled code reads or writes are resolved to slots at compile time
+//! ([`Chunk::variables`]); the VM caches them and keeps `Runtime::scope` in step wherever code
+//! that looks variables up by name can run.
+
+use crate::lexer::tokens::{Literal, Operator};
+use crate::parser::ast::{BlockStatement, Expression, Span, Statement};
+use crate::runtime::engine::Runtime;
+use crate::runtime::values::Value;
+use std::sync::{Arc, OnceLock};
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    /// Push a constant.
+    Constant(Value),
+    /// Push a variable slot's value, or a reference to the user function of that name.
+    Load(usize),
+    /// Push `Enum::Variant` when the path names a declared unit variant, else the variable.
+    LoadPath(String),
+    /// `let name = <top>`: verify and bind the top of stack to a variable slot, leaving it in place.
+    Let(usize),
+    /// `name = <top>`: assign the top of stack to a variable slot, leaving it in place.
+    Assign(usize),
+    /// `object.field = <top>` where `object` is a variable (or `self`); leaves the value.
+    AssignField {
+        object: String,
+        field: String,
+    },
+    Pop,
+    /// Pop two operands and apply a non-short-circuit operator.
+    Binary(Operator),
+    Unary(Operator),
+    /// `&&`: keep the left operand and jump when it is falsy, otherwise pop it.
+    AndElse(usize),
+    /// `||`: keep the left operand and jump when it is truthy, otherwise pop it.
+    OrElse(usize),
+    /// Pop both `&&` operands and push their truthiness as a bool.
+    And,
+    /// Pop both `||` operands and push their truthiness as a bool.
+    Or,
+    Jump(usize),
+    /// Pop the condition and jump when it is falsy.
+    JumpIfFalse(usize),
+    /// Fail with `ExecutionTimeout` once the program has run too long; emitted at loop heads.
+    CheckTimeout,
+    /// Pop `argc` arguments and call a function by name.
+    Call {
+        name: String,
+        argc: usize,
+    },
+    /// Pop `argc` arguments, then the callee, and call the function value.
+    CallValue(usize),
+    /// Pop the receiver, then `argc` arguments, and call a method. `storage` is the receiver
+    /// expression when a mutating value method must write back to a variable or service field.
+    CallMethod {
+        method: String,
+        argc: usize,
+        storage: Option<Box<Expression>>,
+    },
+    GetField(String),
+    /// Pop a key and a container and push `container[key]`.
+    Index,
+    /// Pop `n` values into an array.
+    MakeArray(usize),
+    /// Pop one value per key (in order) into a map.
+    MakeMap(Vec<String>),
+    /// Pop `end` and `start` and push the list `start..end`.
+    Range,
+    Throw,
+    /// Pop into a local slot (loop results and iterators).
+    SetLocal(usize),
+    GetLocal(usize),
+    /// Pop an iterable and store the items to visit in a local slot.
+    IterStart(usize),
+    /// Bind the next item from the iterator slot to the `variable` slot, or jump to `exit` when
+    /// done.
+    IterNext {
+        slot: usize,
+        variable: usize,
+        exit: usize,
+    },
+    /// Pop the return value and leave the chunk.
+    Return,
+    /// Record the location of the statement about to run and notify the debugger.
+    Trace(Option<Span>),
+    /// Evaluate an expression with the tree-walker.
+    Eval(Box<Expression>),
+    /// Execute a statement with the tree-walker. Inside a loop, `break`/`continue` signals from
+    /// the statement go to `exits`.
+    Exec {
+        statement: Box<Statement>,
+        exits: Option<LoopExits>,
+    },
+    /// Fail with a general runtime error.
+    Fail(String),
+}
+
+/// Where `break` and `continue` go for the innermost loop.
+#[derive(Debug, Clone, Copy)]
+pub struct LoopExits {
+    pub break_to: usize,
+    pub continue_to: usize,
+    /// Local slot holding the loop's result value (set by `break value`).
+    pub result: usize,
+}
+
+/// Compiled code for one body or statement. Running it leaves the value of its last statement.
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    /// Number of local slots the code uses.
+    pub locals: usize,
+    /// Names of the variable slots used by `Load`, `Let`, `Assign` and `IterNext`.
+    pub variables: Vec<String>,
+}
+
+/// Bytecode for a body, compiled on first use and shared between clones of its owner.
+#[derive(Debug, Clone, Default)]
+pub struct CodeCache(Arc<OnceLock<Arc<Chunk>>>);
+
+impl CodeCache {
+    pub fn get_or_compile(&self, body: &BlockStatement) -> Arc<Chunk> {
+        self.0.get_or_init(|| Arc::new(compile_body(body))).clone()
+    }
+}
+
+/// Compile a function, method or closure body. The chunk's value is the value of the last
+/// statement run, or the `return` value.
+pub fn compile_body(body: &BlockStatement) -> Chunk {
+    let mut compiler = Compiler::default();
+    if body.statements.is_empty() {
+        compiler.emit(Instruction::Constant(Value::Null));
+    }
+    for (index, stmt) in body.statements.iter().enumerate() {
+        if index > 0 {
+            compiler.emit(Instruction::Pop);
+        }
+        compiler.trace(stmt, body.span_at(index));
+        if has_stray_loop_exit(stmt) {
+            // A `break`/`continue` outside any loop turns the whole statement into a control-flow
+            // marker value; the tree-walker reproduces that exactly.
+            compiler.emit(Instruction::Exec {
+                statement: Box::new(stmt.clone()),
+                exits: None,
+            });
+        } else {
+            compiler.statement(stmt);
+        }
+    }
+    compiler.finish()
+}
+
+/// Compile a top-level statement, or `None` when it gains nothing from bytecode and should be
+/// executed by the tree-walker directly (declarations, imports, `try`, `match`, ...).
+pub fn compile_statement(stmt: &Statement) -> Option<Chunk> {
+    let compiled = matches!(
+        stmt,
+        Statement::Let(_)
+            | Statement::Return(_)
+            | Statement::Expression(_)
+            | Statement::Block(_)
+            | Statement::If(_)
+            | Statement::While(_)
+            | Statement::Loop(_)
+            | Statement::ForIn(_)
+    );
+    if !compiled || has_stray_loop_exit(stmt) {
+        return None;
+    }
+    let mut compiler = Compiler::default();
+    compiler.statement(stmt);
+    Some(compiler.finish())
+}
+
+/// True when `stmt` contains a `break` or `continue` that is not inside a loop within `stmt`.
+fn has_stray_loop_exit(stmt: &Statement) -> bool {
+    let block_has = |block: &BlockStatement| block.statements.iter().any(has_stray_loop_exit);
+    match stmt {
+        Statement::Break(_) | Statement::Continue(_) => true,
+        Statement::Block(block) => block_has(block),
+        Statement::If(if_stmt) => {
+            block_has(&if_stmt.consequence) || if_stmt.alternative.as_ref().is_some_and(block_has)
+        }
+        Statement::Try(try_stmt) => {
+            block_has(&try_stmt.try_block)
+                || try_stmt.catch_blocks.iter().any(|c| block_has(&c.body))
+                || try_stmt.finally_block.as_ref().is_some_and(block_has)
+        }
+        Statement::Match(match_stmt) => {
+            match_stmt.cases.iter().any(|c| block_has(&c.body))
+                || match_stmt.default_case.as_ref().is_some_and(block_has)
+        }
+        _ => false,
+    }
+}
+
+/// A loop being compiled: jumps to its exit are patched once the exit is known.
+struct LoopContext {
+    continue_to: usize,
+    result: usize,
+    exit_patches: Vec<usize>,
+}
+
+#[derive(Default)]
+struct Compiler {
+    code: Vec<Instruction>,
+    locals: usize,
+    variables: Vec<String>,
+    loops: Vec<LoopContext>,
+}
+
+impl Compiler {
+    fn finish(self) -> Chunk {
+        Chunk {
+            code: self.code,
+            locals: self.locals,
+            variables: self.variables,
+        }
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.code.push(instruction);
+        self.code.len() - 1
+    }
+
+    fn here(&self) -> usize {
+        self.code.len()
+    }
+
+    fn new_local(&mut self) -> usize {
+        self.locals += 1;
+        self.locals - 1
+    }
+
+    /// The slot for variable `name`, shared by every use of the name in this chunk.
+    fn variable(&mut self, name: &str) -> usize {
+        match self.variables.iter().position(|v| v == name) {
+            Some(slot) => slot,
+            None => {
+                self.variables.push(name.to_string());
+                self.variables.len() - 1
+            }
+        }
+    }
+
+    /// Point the jump at `at` to `target`.
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.code[at] {
+            Instruction::Jump(to)
+            | Instruction::JumpIfFalse(to)
+            | Instruction::AndElse(to)
+            | Instruction::OrElse(to)
+            | Instruction::IterNext { exit: to, .. } => *to = target,
+            Instruction::Exec {
+                exits: Some(exits), ..
+            } => exits.break_to = target,
+            other => unreachable!("not a jump: {:?}", other),
+        }
+    }
+
+    /// Mirrors `Runtime::trace_statement`: `;` after an assignment is not a stopping point.
+    fn trace(&mut self, stmt: &Statement, span: Option<Span>) {
+        if !matches!(
+            stmt,
+            Statement::Expression(Expression::Literal(Literal::Null))
+        ) {
+            self.emit(Instruction::Trace(span));
+        }
+    }
+
+    /// Compile a statement so that it leaves exactly one value: the statement's value.
+    fn statement(&mut self, stmt: &Statement) {
+        match stmt {
+            // Destructuring lets go through the tree-walker's pattern matcher (`Exec`).
+            Statement::Let(let_stmt) if let_stmt.pattern.is_none() => {
+                self.expression(&let_stmt.value, 0);
+                let slot = self.variable(&let_stmt.name);
+                self.emit(Instruction::Let(slot));
+            }
+            Statement::Return(return_stmt) => {
+                match &return_stmt.value {
+                    Some(value) => self.expression(value, 0),
+                    None => {
+                        self.emit(Instruction::Constant(Value::Null));
+                    }
+                }
+                self.emit(Instruction::Return);
+            }
+            Statement::Expression(expr) => self.expression(expr, 0),
+            Statement::Block(block) => self.block(block),
+            Statement::If(if_stmt) => {
+                self.expression(&if_stmt.condition, 0);
+                let to_else = self.emit(Instruction::JumpIfFalse(0));
+                self.block(&if_stmt.consequence);
+                let to_end = self.emit(Instruction::Jump(0));
+                let else_start = self.here();
+                self.patch(to_else, else_start);
+                match &if_stmt.alternative {
+                    Some(alternative) => self.block(alternative),
+                    None => {
+                        self.emit(Instruction::Constant(Value::Null));
+                    }
+                }
+                let end = self.here();
+                self.patch(to_end, end);
+            }
+            Statement::While(while_stmt) => {
+                let result = self.new_local();
+                self.emit(Instruction::Constant(Value::Null));
+                self.emit(Instruction::SetLocal(result));
+                let start = self.emit(Instruction::CheckTimeout);
+                self.expression(&while_stmt.condition, 0);
+                let to_exit = self.emit(Instruction::JumpIfFalse(0));
+                self.loop_body(&while_stmt.body, start, result, true);
+                self.close_loop(to_exit, result);
+            }
+            Statement::ForIn(for_in) => {
+                self.expression(&for_in.iterable, 0);
+                let items = self.new_local();
+                self.emit(Instruction::IterStart(items));
+                let result = self.new_local();
+                self.emit(Instruction::Constant(Value::Null));
+                self.emit(Instruction::SetLocal(result));
+                let variable = self.variable(&for_in.variable);
+                let start = self.emit(Instruction::IterNext {
+                    slot: items,
+                    variable,
+                    exit: 0,
+                });
+                self.loop_body(&for_in.body, start, result, true);
+                self.close_loop(start, result);
+            }
+            Statement::Loop(loop_stmt) => {
+                let result = self.new_local();
+                let start = self.emit(Instruction::CheckTimeout);
+                self.loop_body(&loop_stmt.body, start, result, false);
+                // `loop` only ends through `break`, whose jump is patched to the exit below.
+                let exit = self.here();
+                let context = self.loops.pop().expect("loop context");
+                for at in context.exit_patches {
+                    self.patch(at, exit);
+                }
+                self.emit(Instruction::GetLocal(result));
+            }
+            Statement::Break(break_stmt) if !self.loops.is_empty() => {
+                match &break_stmt.value {
+                    Some(value) => self.expression(value, 0),
+                    None => {
+                        self.emit(Instruction::Constant(Value::Null));
+                    }
+                }
+                let result = self.loops.last().expect("loop context").result;
+                self.emit(Instruction::SetLocal(result));
+                let jump = self.emit(Instruction::Jump(0));
+                self.loops
+                    .last_mut()
+                    .expect("loop context")
+                    .exit_patches
+                    .push(jump);
+                // Unreachable, but keeps "one value per statement" for the code that follows.
+                self.emit(Instruction::Constant(Value::Null));
+            }
+            Statement::Continue(_) if !self.loops.is_empty() => {
+                let continue_to = self.loops.last().expect("loop context").continue_to;
+                self.emit(Instruction::Jump(continue_to));
+                self.emit(Instruction::Constant(Value::Null));
+            }
+            _ => {
+                let exits = self.loops.last().map(|l| LoopExits {
+                    break_to: 0,
+                    continue_to: l.continue_to,
+                    result: l.result,
+                });
+                let at = self.emit(Instruction::Exec {
+                    statement: Box::new(stmt.clone()),
+                    exits,
+                });
+                if let Some(context) = self.loops.last_mut() {
+                    context.exit_patches.push(at);
+                }
+            }
+        }
+    }
+
+    /// Compile a block: its value is that of its last statement, or null when empty.
+    fn block(&mut self, block: &BlockStatement) {
+        if block.statements.is_empty() {
+            self.emit(Instruction::Constant(Value::Null));
+        }
+        for (index, stmt) in block.statements.iter().enumerate() {
+            if index > 0 {
+                self.emit(Instruction::Pop);
+            }
+            self.trace(stmt, block.span_at(index));
+            self.statement(stmt);
+        }
+    }
+
+    /// Compile a loop body that jumps back to `start`. With `keep_values`, each statement's
+    /// value becomes the loop result (`while` and `for`); `loop` discards them.
+    fn loop_body(&mut self, body: &BlockStatement, start: usize, result: usize, keep_values: bool) {
+        self.loops.push(LoopContext {
+            continue_to: start,
+            result,
+            exit_patches: Vec::new(),
+        });
+        for (index, stmt) in body.statements.iter().enumerate() {
+            self.trace(stmt, body.span_at(index));
+            self.statement(stmt);
+            if keep_values {
+                self.emit(Instruction::SetLocal(result));
+            } else {
+                self.emit(Instruction::Pop);
+            }
+        }
+        self.emit(Instruction::Jump(start));
+    }
+
+    /// Patch the loop's exit jumps (`exit_jump` plus every `break`) and push its result.
+    fn close_loop(&mut self, exit_jump: usize, result: usize) {
+        let exit = self.here();
+        let context = self.loops.pop().expect("loop context");
+        self.patch(exit_jump, exit);
+        for at in context.exit_patches {
+            self.patch(at, exit);
+        }
+        self.emit(Instruction::GetLocal(result));
+    }
+
+    fn expression(&mut self, expr: &Expression, depth: usize) {
+        if depth >= Runtime::MAX_EVAL_DEPTH {
+            self.emit(Instruction::Fail(format!(
+                "Expression recursion depth exceeded (limit {})",
+                Runtime::MAX_EVAL_DEPTH
+            )));
+            return;
+        }
+        let depth = depth + 1;
+        match expr {
+            Expression::Literal(literal) => {
+                let value = match literal {
+                    Literal::Int(n) => Value::Int(*n),
+                    Literal::Float(f) => Value::Float(*f),
+                    Literal::String(s) => Value::String(s.clone()),
+                    Literal::Bool(b) => Value::Bool(*b),
+                    Literal::Null => Value::Null,
+                    Literal::U256(n) => Value::U256(*n),
+                    Literal::I256(n) => Value::I256(*n),
+                    Literal::Decimal(d) => Value::Decimal(*d),
+                    Literal::Bytes(b) => Value::Bytes(b.clone()),
+                };
+                self.emit(Instruction::Constant(value));
+            }
+            Expression::Identifier(name) if name.contains("::") => {
+                self.emit(Instruction::LoadPath(name.clone()));
+            }
+            Expression::Identifier(name) => {
+                let slot = self.variable(name);
+                self.emit(Instruction::Load(slot));
+            }
+            Expression::BinaryOp(left, Operator::And, right) => {
+                self.expression(left, depth);
+                let short = self.emit(Instruction::AndElse(0));
+                self.expression(right, depth);
+                self.emit(Instruction::And);
+                let end = self.here();
+                self.patch(short, end);
+            }
+            Expression::BinaryOp(left, Operator::Or, right) => {
+                self.expression(left, depth);
+                let short = self.emit(Instruction::OrElse(0));
+                self.expression(right, depth);
+                self.emit(Instruction::Or);
+                let end = self.here();
+                self.patch(short, end);
+            }
+            Expression::BinaryOp(left, operator, right) => {
+                self.expression(left, depth);
+                self.expression(right, depth);
+                self.emit(Instruction::Binary(operator.clone()));
+            }
+            Expression::UnaryOp(operator, operand) => {
+                self.expression(operand, depth);
+                self.emit(Instruction::Unary(operator.clone()));
+            }
+            Expression::Assignment(name, value) => {
+                self.expression(value, depth);
+                let slot = self.variable(name);
+                self.emit(Instruction::Assign(slot));
+            }
+            Expression::FieldAssignment(object, field, value) => match object.as_ref() {
+                Expression::Identifier(name) => {
+                    self.expression(value, depth);
+                    self.emit(Instruction::AssignField {
+                        object: name.clone(),
+                        field: field.clone(),
+                    });
+                }
+                _ => {
+                    self.emit(Instruction::Eval(Box::new(expr.clone())));
+                }
+            },
+            Expression::MethodCall {
+                receiver,
+                method_name,
+                arguments,
+            } => {
+                for arg in arguments {
+                    self.expression(arg, depth);
+                }
+                self.expression(receiver, depth);
+                let storage = match receiver.as_ref() {
+                    Expression::Identifier(_) => Some(receiver.clone()),
+                    Expression::FieldAccess(inner, _)
+                        if matches!(inner.as_ref(), Expression::Identifier(_)) =>
+                    {
+                        Some(receiver.clone())
+                    }
+                    _ => None,
+                };
+                self.emit(Instruction::CallMethod {
+                    method: method_name.clone(),
+                    argc: arguments.len(),
+                    storage,
+                });
+            }
+            Expression::FunctionCall(call) => {
+                for arg in &call.arguments {
+                    self.expression(arg, depth);
+                }
+                self.emit(Instruction::Call {
+                    name: call.name.clone(),
+                    argc: call.arguments.len(),
+                });
+            }
+            Expression::Call { callee, arguments } => {
+                self.expression(callee, depth);
+                for arg in arguments {
+                    self.expression(arg, depth);
+                }
+                self.emit(Instruction::CallValue(arguments.len()));
+            }
+            Expression::Throw(inner) => {
+                self.expression(inner, depth);
+                self.emit(Instruction::Throw);
+            }
+            Expression::IndexAccess(container, index) => {
+                self.expression(container, depth);
+                self.expression(index, depth);
+                self.emit(Instruction::Index);
+            }
+            Expression::FieldAccess(object, field) => {
+                self.expression(object, depth);
+                self.emit(Instruction::GetField(field.clone()));
+            }
+            Expression::ObjectLiteral(properties) => {
+                let mut keys = Vec::with_capacity(properties.len());
+                for (key, value) in properties {
+                    self.expression(value, depth);
+                    keys.push(key.clone());
+                }
+                self.emit(Instruction::MakeMap(keys));
+            }
+            Expression::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.expression(element, depth);
+                }
+                self.emit(Instruction::MakeArray(elements.len()));
+            }
+            Expression::Range(start, end) => {
+                self.expression(start, depth);
+                self.expression(end, depth);
+                self.emit(Instruction::Range);
+            }
+            Expression::Spawn(_)
+            | Expression::Await(_)
+            | Expression::Propagate(_)
+            | Expression::OptionalFieldAccess(..)
+            | Expression::OptionalMethodCall { .. }
+            | Expression::NullCoalesce(..)
+            | Expression::Pipeline(..)
+            | Expression::Interpolation(_)
+            | Expression::StructLiteral { .. }
+            | Expression::ArrowFunction { .. } => {
+                self.emit(Instruction::Eval(Box::new(expr.clone())));
+            }
+        }
+    }
+}
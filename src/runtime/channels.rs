@@ -0,0 +1,203 @@
+//! Bounded channels between DAL tasks and agents.
+//!
+//! `chan::new` creates a FIFO queue in a process-wide table; `Value::Channel` handles are ids
+//! into it, so they can be copied into `spawn`ed tasks and sent in agent messages. A send to a
+//! full channel waits until a receiver makes room (backpressure), a receive from an empty one
+//! until a value arrives; both take an optional timeout and stop when the task doing them is
+//! cancelled. Receivers drain what is left in a closed channel, then get nothing; a channel
+//! that is closed and drained leaves the table. So does one whose owner, the runtime or
+//! `task::scope` that created it, goes away without closing it (`release`).
+
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::tasks::{self, Task};
+use crate::runtime::types::Type;
+use crate::runtime::values::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest a blocked send or receive sleeps before checking its deadline and cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+struct Channel {
+    capacity: usize,
+    /// Type annotation every sent value must match, from `chan::new(capacity, type)`.
+    element_type: Option<String>,
+    state: Mutex<State>,
+    /// Signalled on every send, receive and close.
+    changed: Condvar,
+}
+
+struct State {
+    buffer: VecDeque<Value>,
+    closed: bool,
+}
+
+struct Table {
+    channels: Mutex<HashMap<u64, Arc<Channel>>>,
+    next_id: AtomicU64,
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| Table {
+        channels: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    })
+}
+
+/// `None` once the channel has been closed and drained.
+fn lookup(id: u64) -> Option<Arc<Channel>> {
+    table().channels.lock().unwrap().get(&id).cloned()
+}
+
+fn forget_if_drained(id: u64, state: &State) {
+    if state.closed && state.buffer.is_empty() {
+        table().channels.lock().unwrap().remove(&id);
+    }
+}
+
+/// Create a channel holding up to `capacity` values, optionally only of `element_type`.
+pub fn new(capacity: usize, element_type: Option<String>) -> Result<u64, RuntimeError> {
+    if capacity == 0 {
+        return Err(RuntimeError::General(
+            "channel capacity must be at least 1".to_string(),
+        ));
+    }
+    let table = table();
+    let id = table.next_id.fetch_add(1, Ordering::Relaxed);
+    let channel = Channel {
+        capacity,
+        element_type,
+        state: Mutex::new(State {
+            buffer: VecDeque::new(),
+            closed: false,
+        }),
+        changed: Condvar::new(),
+    };
+    table.channels.lock().unwrap().insert(id, Arc::new(channel));
+    Ok(id)
+}
+
+/// Lock the channel state and retry `attempt` until it yields, `timeout` passes or `current`
+/// (the task doing this) is cancelled. While it waits, queued tasks get a thread of their own
+/// if the pool has none free, since the other end may be one of them.
+fn wait_until<R>(
+    channel: &Channel,
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+    mut attempt: impl FnMut(&mut State) -> Option<R>,
+) -> Result<R, RuntimeError> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut state: MutexGuard<State> = channel.state.lock().unwrap();
+    loop {
+        if let Some(result) = attempt(&mut state) {
+            channel.changed.notify_all();
+            return Ok(result);
+        }
+        if let Some(current) = current.filter(|c| c.is_cancelled()) {
+            return Err(RuntimeError::TaskCancelled(current.id));
+        }
+        let mut wait = POLL_INTERVAL;
+        if let (Some(deadline), Some(timeout)) = (deadline, timeout) {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RuntimeError::ChannelTimeout(timeout.as_millis() as u64));
+            }
+            wait = wait.min(deadline - now);
+        }
+        let (guard, waited) = channel.changed.wait_timeout(state, wait).unwrap();
+        state = guard;
+        if waited.timed_out() {
+            drop(state);
+            tasks::help_if_stalled();
+            state = channel.state.lock().unwrap();
+        }
+    }
+}
+
+/// Send `value`, waiting while the channel is full. Fails once the channel is closed, and with
+/// a type error when the value does not match the channel's element type.
+pub fn send(
+    id: u64,
+    value: Value,
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+) -> Result<(), RuntimeError> {
+    let channel = lookup(id).ok_or(RuntimeError::ChannelClosed(id))?;
+    if let Some(element_type) = &channel.element_type {
+        if !Type::from_annotation(element_type).is_compatible_with(&Type::of_value(&value)) {
+            return Err(RuntimeError::TypeError {
+                expected: element_type.clone(),
+                got: value.type_name().to_string(),
+            });
+        }
+    }
+    let mut value = Some(value);
+    wait_until(&channel, timeout, current, |state| {
+        if state.closed {
+            Some(Err(RuntimeError::ChannelClosed(id)))
+        } else if state.buffer.len() < channel.capacity {
+            state.buffer.extend(value.take());
+            Some(Ok(()))
+        } else {
+            None
+        }
+    })?
+}
+
+/// Receive the oldest value, waiting while the channel is empty. `None` once the channel is
+/// closed and drained.
+pub fn recv(
+    id: u64,
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+) -> Result<Option<Value>, RuntimeError> {
+    let Some(channel) = lookup(id) else {
+        return Ok(None);
+    };
+    wait_until(&channel, timeout, current, |state| {
+        let received = match state.buffer.pop_front() {
+            Some(value) => Some(Some(value)),
+            None if state.closed => Some(None),
+            None => None,
+        };
+        if received.is_some() {
+            forget_if_drained(id, state);
+        }
+        received
+    })
+}
+
+/// Close the channel: sends fail from now on, receivers drain what is buffered. Returns false
+/// when it was already closed.
+pub fn close(id: u64) -> bool {
+    let Some(channel) = lookup(id) else {
+        return false;
+    };
+    let mut state = channel.state.lock().unwrap();
+    let was_open = !std::mem::replace(&mut state.closed, true);
+    forget_if_drained(id, &state);
+    channel.changed.notify_all();
+    was_open
+}
+
+/// Close the channel and take it out of the table, whatever it still buffers: its owner is gone.
+/// Blocked senders fail, and blocked receivers get what is buffered, then nothing.
+pub fn release(id: u64) {
+    let Some(channel) = table().channels.lock().unwrap().remove(&id) else {
+        return;
+    };
+    channel.state.lock().unwrap().closed = true;
+    channel.changed.notify_all();
+}
+
+/// Values buffered in the channel.
+pub fn len(id: u64) -> usize {
+    lookup(id).map_or(0, |channel| channel.state.lock().unwrap().buffer.len())
+}
+
+pub fn is_closed(id: u64) -> bool {
+    lookup(id).is_none_or(|channel| channel.state.lock().unwrap().closed)
+}
@@ -0,0 +1,502 @@
+//! Step debugger used by `dal debug`.
+//!
+//! The runtime calls [`DebugHook::on_statement`] before every statement that has a source
+//! location. [`Debugger`] owns breakpoints and stepping state and decides where to pause;
+//! [`ConsoleDebugger`] puts a line-oriented prompt on top of it.
+
+use crate::runtime::engine::Runtime;
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::values::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+/// Error message used when the user ends a debug session before the program finishes.
+pub const SESSION_TERMINATED: &str = "Debug session terminated";
+
+/// Receives control before each located statement. Returning an error aborts the program.
+pub trait DebugHook: Send {
+    fn on_statement(&mut self, runtime: &mut Runtime) -> Result<(), RuntimeError>;
+
+    /// Called by the embedder once the program has finished running.
+    fn on_finish(&mut self) {}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub line: usize,
+    /// DAL expression; the breakpoint only pauses when it evaluates truthy.
+    pub condition: Option<String>,
+    pub hits: usize,
+}
+
+/// How execution proceeds after a pause. Depths are `Runtime::call_stack` lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Run until the next breakpoint.
+    Continue,
+    /// Pause at the next statement, entering calls.
+    StepInto,
+    /// Pause at the next statement at or above `depth`.
+    StepOver { depth: usize },
+    /// Pause at the next statement above `depth`.
+    StepOut { depth: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PauseReason {
+    Entry,
+    Breakpoint(usize),
+    Step,
+}
+
+impl std::fmt::Display for PauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseReason::Entry => write!(f, "entry"),
+            PauseReason::Breakpoint(line) => write!(f, "breakpoint at line {}", line),
+            PauseReason::Step => write!(f, "step"),
+        }
+    }
+}
+
+/// One entry of the paused call stack; frame 0 is the innermost.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub index: usize,
+    pub name: String,
+    pub line: Option<usize>,
+}
+
+/// Breakpoints and stepping state, independent of any front end.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    breakpoints: BTreeMap<usize, Breakpoint>,
+    mode: StepMode,
+    stop_on_entry: bool,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeMap::new(),
+            mode: StepMode::Continue,
+            stop_on_entry: false,
+        }
+    }
+
+    /// Pause before the first statement.
+    pub fn with_stop_on_entry(mut self, stop: bool) -> Self {
+        self.stop_on_entry = stop;
+        self
+    }
+
+    /// Set (or replace) the breakpoint on `line`.
+    pub fn set_breakpoint(&mut self, line: usize, condition: Option<String>) {
+        self.breakpoints.insert(
+            line,
+            Breakpoint {
+                line,
+                condition,
+                hits: 0,
+            },
+        );
+    }
+
+    pub fn remove_breakpoint(&mut self, line: usize) -> bool {
+        self.breakpoints.remove(&line).is_some()
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.values()
+    }
+
+    pub fn mode(&self) -> StepMode {
+        self.mode
+    }
+
+    pub fn resume(&mut self, mode: StepMode) {
+        self.mode = mode;
+    }
+
+    pub fn step_into(&mut self) {
+        self.mode = StepMode::StepInto;
+    }
+
+    pub fn step_over(&mut self, runtime: &Runtime) {
+        self.mode = StepMode::StepOver {
+            depth: runtime.call_stack.len(),
+        };
+    }
+
+    pub fn step_out(&mut self, runtime: &Runtime) {
+        self.mode = StepMode::StepOut {
+            depth: runtime.call_stack.len(),
+        };
+    }
+
+    /// Decide whether to pause at the statement the runtime is about to execute.
+    pub fn should_pause(&mut self, runtime: &mut Runtime) -> Option<PauseReason> {
+        let line = runtime.current_location()?.line;
+        if self.stop_on_entry {
+            self.stop_on_entry = false;
+            return Some(PauseReason::Entry);
+        }
+        let depth = runtime.call_stack.len();
+        let stepped = match self.mode {
+            StepMode::Continue => false,
+            StepMode::StepInto => true,
+            StepMode::StepOver { depth: from } => depth <= from,
+            StepMode::StepOut { depth: from } => depth < from,
+        };
+        if stepped {
+            self.mode = StepMode::Continue;
+            return Some(PauseReason::Step);
+        }
+        let condition = self.breakpoints.get(&line)?.condition.clone();
+        let hit = match condition {
+            // A condition that fails to evaluate pauses, so the user can see and fix it.
+            Some(condition) => evaluate(runtime, &condition, 0)
+                .map(|value| runtime.is_truthy(&value))
+                .unwrap_or(true),
+            None => true,
+        };
+        if !hit {
+            return None;
+        }
+        if let Some(breakpoint) = self.breakpoints.get_mut(&line) {
+            breakpoint.hits += 1;
+        }
+        self.mode = StepMode::Continue;
+        Some(PauseReason::Breakpoint(line))
+    }
+}
+
+/// Parse a breakpoint spec such as `12` or `12 if total > 100`.
+pub fn parse_breakpoint_spec(spec: &str) -> Result<(usize, Option<String>), String> {
+    let spec = spec.trim();
+    let (line, condition) = match spec.split_once(" if ") {
+        Some((line, condition)) => (line.trim(), Some(condition.trim().to_string())),
+        None => (spec, None),
+    };
+    let line = line
+        .parse::<usize>()
+        .ok()
+        .filter(|l| *l > 0)
+        .ok_or_else(|| format!("Invalid breakpoint line '{}'", line))?;
+    Ok((line, condition.filter(|c| !c.is_empty())))
+}
+
+/// Evaluate DAL source in call frame `frame` of a paused runtime.
+pub fn evaluate(runtime: &mut Runtime, source: &str, frame: usize) -> Result<Value, String> {
+    let program = crate::parse_source(source).map_err(|e| e.to_string())?;
+    runtime
+        .evaluate_in_frame(&program, frame)
+        .map_err(|e| e.to_string())
+}
+
+/// The paused call stack, innermost frame first, ending with `<main>`.
+pub fn stack_frames(runtime: &Runtime) -> Vec<StackFrame> {
+    let calls = &runtime.call_stack;
+    let depth = calls.len();
+    (0..=depth)
+        .map(|index| {
+            let name = if index < depth {
+                calls[depth - 1 - index]
+                    .function_name
+                    .clone()
+                    .unwrap_or_else(|| "<anonymous>".to_string())
+            } else {
+                "<main>".to_string()
+            };
+            let line = match index {
+                0 => runtime.current_location().map(|loc| loc.line),
+                _ => calls[depth - index].call_site.as_ref().map(|loc| loc.line),
+            };
+            StackFrame { index, name, line }
+        })
+        .collect()
+}
+
+/// Variables visible in call frame `frame`, sorted by name.
+pub fn frame_variables(runtime: &Runtime, frame: usize) -> Vec<(String, Value)> {
+    let depth = runtime.call_stack.len();
+    let scope = match frame {
+        0 => &runtime.scope,
+        f if f <= depth => &runtime.call_stack[depth - f].scope,
+        _ => return Vec::new(),
+    };
+    let mut variables = BTreeMap::new();
+    for name in scope.keys() {
+        if let Some(value) = scope.get(&name) {
+            variables.insert(name, value);
+        }
+    }
+    variables.into_iter().collect()
+}
+
+/// A command typed at the `(dal)` prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugCommand {
+    Continue,
+    StepInto,
+    StepOver,
+    StepOut,
+    Break {
+        line: usize,
+        condition: Option<String>,
+    },
+    Delete(usize),
+    Breakpoints,
+    Backtrace,
+    Frame(usize),
+    Locals,
+    List,
+    Print(String),
+    Help,
+    Quit,
+}
+
+impl DebugCommand {
+    /// Parse one prompt line. Input that is not a command is evaluated as an expression.
+    pub fn parse(input: &str) -> Result<Option<DebugCommand>, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+        let (word, rest) = match input.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (input, ""),
+        };
+        let command = match word {
+            "c" | "continue" => DebugCommand::Continue,
+            "s" | "step" => DebugCommand::StepInto,
+            "n" | "next" => DebugCommand::StepOver,
+            "o" | "out" | "finish" => DebugCommand::StepOut,
+            "b" | "break" => {
+                let (line, condition) = parse_breakpoint_spec(rest)?;
+                DebugCommand::Break { line, condition }
+            }
+            "d" | "delete" => DebugCommand::Delete(
+                rest.parse()
+                    .map_err(|_| format!("Invalid breakpoint line '{}'", rest))?,
+            ),
+            "breakpoints" | "bl" => DebugCommand::Breakpoints,
+            "bt" | "backtrace" | "where" | "stack" => DebugCommand::Backtrace,
+            "f" | "frame" => DebugCommand::Frame(
+                rest.parse()
+                    .map_err(|_| format!("Invalid frame number '{}'", rest))?,
+            ),
+            "locals" | "vars" => DebugCommand::Locals,
+            "l" | "list" => DebugCommand::List,
+            "p" | "print" if !rest.is_empty() => DebugCommand::Print(rest.to_string()),
+            "h" | "help" | "?" => DebugCommand::Help,
+            "q" | "quit" | "exit" => DebugCommand::Quit,
+            _ => DebugCommand::Print(input.to_string()),
+        };
+        Ok(Some(command))
+    }
+}
+
+const HELP: &str = "\
+  c, continue          run to the next breakpoint
+  s, step              step into calls
+  n, next              step over calls
+  o, out, finish       run until the current function returns
+  b, break N [if E]    set a breakpoint on line N, optionally conditional
+  d, delete N          remove the breakpoint on line N
+  breakpoints          list breakpoints
+  bt, backtrace        show the call stack
+  f, frame N           select frame N for locals and expressions
+  locals               show variables in the selected frame
+  l, list              show source around the current line
+  p, print EXPR        evaluate EXPR in the selected frame (bare EXPR works too)
+  q, quit              stop the program";
+
+/// Interactive debugger reading commands from `input` and writing to `output`.
+pub struct ConsoleDebugger<R, W> {
+    debugger: Debugger,
+    source_lines: Vec<String>,
+    input: R,
+    output: W,
+    frame: usize,
+}
+
+impl<R: BufRead, W: Write> ConsoleDebugger<R, W> {
+    pub fn new(debugger: Debugger, source: &str, input: R, output: W) -> Self {
+        Self {
+            debugger,
+            source_lines: source.lines().map(str::to_string).collect(),
+            input,
+            output,
+            frame: 0,
+        }
+    }
+
+    fn source_line(&self, line: usize) -> &str {
+        line.checked_sub(1)
+            .and_then(|i| self.source_lines.get(i))
+            .map(|s| s.trim_end())
+            .unwrap_or("")
+    }
+
+    fn show_lines(&mut self, center: usize, radius: usize) -> std::io::Result<()> {
+        let first = center.saturating_sub(radius).max(1);
+        let last = (center + radius).min(self.source_lines.len());
+        for line in first..=last {
+            let marker = if line == center { "->" } else { "  " };
+            let text = self.source_line(line).to_string();
+            writeln!(self.output, "{} {:>4} | {}", marker, line, text)?;
+        }
+        Ok(())
+    }
+
+    /// Handle one command. Returns `Ok(true)` when execution should resume.
+    fn run_command(
+        &mut self,
+        command: DebugCommand,
+        runtime: &mut Runtime,
+    ) -> Result<bool, RuntimeError> {
+        let io = |e: std::io::Error| RuntimeError::General(format!("Debugger I/O error: {}", e));
+        match command {
+            DebugCommand::Continue => self.debugger.resume(StepMode::Continue),
+            DebugCommand::StepInto => self.debugger.step_into(),
+            DebugCommand::StepOver => self.debugger.step_over(runtime),
+            DebugCommand::StepOut => self.debugger.step_out(runtime),
+            DebugCommand::Break { line, condition } => {
+                match &condition {
+                    Some(c) => writeln!(self.output, "Breakpoint set at line {} if {}", line, c),
+                    None => writeln!(self.output, "Breakpoint set at line {}", line),
+                }
+                .map_err(io)?;
+                self.debugger.set_breakpoint(line, condition);
+                return Ok(false);
+            }
+            DebugCommand::Delete(line) => {
+                let message = if self.debugger.remove_breakpoint(line) {
+                    format!("Breakpoint at line {} removed", line)
+                } else {
+                    format!("No breakpoint at line {}", line)
+                };
+                writeln!(self.output, "{}", message).map_err(io)?;
+                return Ok(false);
+            }
+            DebugCommand::Breakpoints => {
+                let breakpoints: Vec<Breakpoint> = self.debugger.breakpoints().cloned().collect();
+                if breakpoints.is_empty() {
+                    writeln!(self.output, "No breakpoints").map_err(io)?;
+                }
+                for bp in breakpoints {
+                    let condition = bp
+                        .condition
+                        .map(|c| format!(" if {}", c))
+                        .unwrap_or_default();
+                    writeln!(
+                        self.output,
+                        "  line {}{} (hit {})",
+                        bp.line, condition, bp.hits
+                    )
+                    .map_err(io)?;
+                }
+                return Ok(false);
+            }
+            DebugCommand::Backtrace => {
+                for frame in stack_frames(runtime) {
+                    let marker = if frame.index == self.frame { "*" } else { " " };
+                    let line = frame
+                        .line
+                        .map(|l| format!(" at line {}", l))
+                        .unwrap_or_default();
+                    writeln!(
+                        self.output,
+                        "{} #{} {}{}",
+                        marker, frame.index, frame.name, line
+                    )
+                    .map_err(io)?;
+                }
+                return Ok(false);
+            }
+            DebugCommand::Frame(index) => {
+                let frames = stack_frames(runtime);
+                match frames.get(index) {
+                    Some(frame) => {
+                        self.frame = index;
+                        writeln!(self.output, "#{} {}", frame.index, frame.name).map_err(io)?;
+                    }
+                    None => writeln!(self.output, "No frame {}", index).map_err(io)?,
+                }
+                return Ok(false);
+            }
+            DebugCommand::Locals => {
+                for (name, value) in frame_variables(runtime, self.frame) {
+                    writeln!(self.output, "  {} = {}", name, value).map_err(io)?;
+                }
+                return Ok(false);
+            }
+            DebugCommand::List => {
+                let line = stack_frames(runtime)
+                    .get(self.frame)
+                    .and_then(|frame| frame.line)
+                    .unwrap_or(1);
+                self.show_lines(line, 5).map_err(io)?;
+                return Ok(false);
+            }
+            DebugCommand::Print(expression) => {
+                match evaluate(runtime, &expression, self.frame) {
+                    Ok(value) => writeln!(self.output, "{}", value),
+                    Err(e) => writeln!(self.output, "error: {}", e),
+                }
+                .map_err(io)?;
+                return Ok(false);
+            }
+            DebugCommand::Help => {
+                writeln!(self.output, "{}", HELP).map_err(io)?;
+                return Ok(false);
+            }
+            DebugCommand::Quit => return Err(RuntimeError::General(SESSION_TERMINATED.into())),
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> DebugHook for ConsoleDebugger<R, W> {
+    fn on_statement(&mut self, runtime: &mut Runtime) -> Result<(), RuntimeError> {
+        let Some(reason) = self.debugger.should_pause(runtime) else {
+            return Ok(());
+        };
+        let io = |e: std::io::Error| RuntimeError::General(format!("Debugger I/O error: {}", e));
+        self.frame = 0;
+        let line = runtime.current_location().map(|loc| loc.line).unwrap_or(0);
+        writeln!(self.output, "Paused ({}) at line {}", reason, line).map_err(io)?;
+        self.show_lines(line, 0).map_err(io)?;
+        loop {
+            write!(self.output, "(dal) ").map_err(io)?;
+            self.output.flush().map_err(io)?;
+            let mut input = String::new();
+            if self.input.read_line(&mut input).map_err(io)? == 0 {
+                // Input closed: let the program run to completion.
+                self.debugger.clear_breakpoints();
+                self.debugger.resume(StepMode::Continue);
+                return Ok(());
+            }
+            match DebugCommand::parse(&input) {
+                Ok(Some(command)) => {
+                    if self.run_command(command, runtime)? {
+                        return Ok(());
+                    }
+                }
+                Ok(None) => {}
+                Err(message) => writeln!(self.output, "error: {}", message).map_err(io)?,
+            }
+        }
+    }
+}
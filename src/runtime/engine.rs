@@ -1,10 +1,13 @@
 #![allow(clippy::len_zero)]
 use crate::parser::ast::{BlockStatement, Program, ServiceStatement, Statement};
 use crate::runtime::advanced_security::AdvancedSecurityManager;
+use crate::runtime::bytecode::{self, Chunk, CodeCache};
 use crate::runtime::control_flow::{ControlFlow, StatementOutcome, StatementResult};
+use crate::runtime::debugger::DebugHook;
 use crate::runtime::functions::{
     CallFrameInfo, Function, RuntimeError, RuntimeErrorWithContext, SourceLocation,
 };
+use crate::runtime::metering::collection_items;
 use crate::runtime::reentrancy::ReentrancyGuard;
 use crate::runtime::safe_math::SafeMath;
 use crate::runtime::scope::Scope;
@@ -17,7 +20,7 @@ use crate::stdlib::oracle::{self, OracleQuery, OracleResponse, OracleSource};
 use crate::testing::mock::MockRegistry;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::sync::mpsc;
+use std::sync::Arc;
 
 /// Simple Levenshtein distance for "did you mean" suggestions (P5).
 fn edit_distance(a: &str, b: &str) -> usize {
@@ -45,7 +48,7 @@ fn edit_distance(a: &str, b: &str) -> usize {
 
 /// Storage location for method-call receiver write-back (value methods that mutate).
 #[derive(Clone)]
-enum ReceiverStorage {
+pub(crate) enum ReceiverStorage {
     Variable(String),
     ServiceField(String, String), // instance_id, field_name
 }
@@ -86,12 +89,14 @@ impl Default for AgentState {
     }
 }
 
-/// Captured arrow/closure: single param, body, and scope snapshot for callbacks.
+/// Captured arrow/closure: parameters, body, and scope snapshot for callbacks.
 #[derive(Clone)]
 pub struct ClosureEntry {
-    pub param: String,
+    pub params: Vec<String>,
     pub body: BlockStatement,
     pub captured_scope: Scope,
+    /// Bytecode for `body`, compiled on the first call.
+    pub code: CodeCache,
 }
 
 /// User-defined (DAL) function: name, parameter names, body AST, and attributes.
@@ -104,6 +109,49 @@ pub struct UserFunction {
     pub attributes: Vec<crate::parser::ast::Attribute>,
     /// M5: true when declared with `export fn ...` (included in module exports when explicit exports used).
     pub exported: bool,
+    /// Bytecode for `body`, compiled on the first call and shared by clones of this function.
+    pub code: CodeCache,
+}
+
+/// Closure id prefix for a reference to a named user function (`let f = add;`): calling the
+/// value calls the function by name, so it needs no registry entry.
+const FUNCTION_REF_PREFIX: &str = "fn:";
+
+/// Most `@cached` results kept at once; expired entries are swept, then the oldest evicted,
+/// when a new result would go past it.
+const CALL_CACHE_CAPACITY: usize = 1024;
+
+/// An `@cached` result, with its expiry when a ttl was given.
+#[derive(Debug, Clone)]
+struct CachedCall {
+    value: Value,
+    expires: Option<std::time::Instant>,
+    stored: std::time::Instant,
+}
+
+/// Receiver of `?.`: a Result or Option is unwrapped as by `?` first, so `r?.field` on an `Err`
+/// or `None` propagates it the way `(r?).field` does; `Ok` / `Some` give their payload.
+fn propagate_wrapped_receiver(value: Value) -> Result<Value, RuntimeError> {
+    match value {
+        Value::Result(Ok(payload)) | Value::Option(Some(payload)) => Ok(*payload),
+        failed @ (Value::Result(Err(_)) | Value::Option(None)) => {
+            Err(RuntimeError::Propagate(failed))
+        }
+        other => Ok(other),
+    }
+}
+
+/// What a `task::scope` started: its tasks are cancelled and its channels released when it exits.
+#[derive(Default)]
+struct TaskScope {
+    tasks: Vec<u64>,
+    channels: Vec<u64>,
+}
+
+/// A function, method or closure body ready to run.
+enum CallableBody {
+    Bytecode(Arc<Chunk>),
+    Tree(BlockStatement),
 }
 
 pub struct Runtime {
@@ -125,9 +173,18 @@ pub struct Runtime {
     pub current_caller: Option<String>,
     /// Active transaction ID (if within a transaction)
     current_transaction_id: Option<String>,
-    /// Pending spawn handles: task_id -> receiver for join.
-    pending_spawns: HashMap<String, mpsc::Receiver<Result<Value, RuntimeError>>>,
-    spawn_counter: u64,
+    /// The task this runtime executes, when it runs a `spawn`ed expression; checked for
+    /// cancellation and made the parent of the tasks it spawns.
+    current_task: Option<Arc<crate::runtime::tasks::Task>>,
+    /// Tasks spawned and channels created inside each open `task::scope`.
+    task_scopes: Vec<TaskScope>,
+    /// Channels created outside any `task::scope`, released when this runtime is dropped.
+    channels: Vec<u64>,
+    /// Budget this runtime is metered against, shared with the tasks it spawns; `None` when
+    /// execution is unmetered.
+    meter: Option<Arc<crate::runtime::metering::Meter>>,
+    /// What the last decentralized service method metered on its own budget consumed.
+    last_method_usage: Option<crate::runtime::metering::Usage>,
     /// Arrow/closure values: closure_id -> (param, body, captured_scope).
     closure_registry: HashMap<String, ClosureEntry>,
     closure_counter: u64,
@@ -146,7 +203,7 @@ pub struct Runtime {
     /// Registered tests: (suite_name, test_name, closure_id).
     test_tests: Vec<(String, String, String)>,
     /// When a Return is executed inside an If/Block, this is set so call_function can break.
-    return_pending: Option<Value>,
+    pub(crate) return_pending: Option<Value>,
     /// Mock registry for testing (intercepts function calls when enabled)
     pub mock_registry: Option<MockRegistry>,
     /// Current statement span for runtime error location (P3).
@@ -167,6 +224,37 @@ pub struct Runtime {
     desktop_state: DesktopState,
     /// Database connections: connection_id -> Database for database::connect/query wiring.
     database_connections: HashMap<String, crate::stdlib::database::Database>,
+    /// Connection pools: pool_id -> ConnectionPool for database::create_connection_pool.
+    database_pools: HashMap<String, crate::stdlib::database::ConnectionPool>,
+    /// Open SQL transactions: transaction id -> Transaction for database::transaction.
+    database_transactions: HashMap<String, crate::stdlib::database::Transaction>,
+    /// `@cached` results by function name (or `instance.method`) + arguments.
+    call_cache: HashMap<String, CachedCall>,
+    /// Backend for `@persistent` fields and `@versioned` history; opened from env on first use.
+    state_storage: Option<Box<dyn crate::runtime::transaction::StateStorage>>,
+    /// Services served by a loaded wasm module (`dal run --wasm`) instead of the interpreter.
+    #[cfg(feature = "wasm-runtime")]
+    wasm_services: Option<Box<crate::runtime::wasm_host::WasmServices>>,
+    /// Subscriptions and log for `event Name { ... }` statements.
+    pub event_bus: crate::runtime::events::EventBus,
+    /// Nested event deliveries in progress (handlers that publish events).
+    event_depth: usize,
+    /// User-declared struct types (Statement::Struct), used to validate struct literals.
+    pub struct_types: HashMap<String, crate::parser::ast::StructStatement>,
+    /// User-declared enum types (Statement::Enum), used to construct and match variants.
+    pub enum_types: HashMap<String, crate::parser::ast::EnumStatement>,
+    /// User-declared traits (Statement::Trait), checked and defaulted when an impl is applied.
+    pub trait_types: HashMap<String, crate::parser::ast::TraitStatement>,
+    /// Impl blocks waiting for their service or trait declaration to execute, with where they
+    /// appeared; any left when the program ends name an undeclared service or trait.
+    pending_impls: Vec<(crate::parser::ast::ImplStatement, Option<SourceLocation>)>,
+    /// Debugger notified before each statement (`dal debug`); `None` for normal runs.
+    debug_hook: Option<Box<dyn DebugHook>>,
+    /// Run programs and function bodies on the bytecode VM (default) instead of walking the AST.
+    bytecode_vm: bool,
+    /// Bytecode for service methods, keyed by "instance.method"; cleared when a service or impl
+    /// block (re)defines methods.
+    method_code: HashMap<String, CodeCache>,
 }
 
 /// In-memory state for iot:: namespace (device registry, edge cache). Phase 2 stdlib wiring.
@@ -196,6 +284,8 @@ pub struct ServiceInstance {
     pub attributes: Vec<String>,
     /// M5: true when declared with `export service ...` (included in module exports when explicit exports used).
     pub exported: bool,
+    /// Fields stored through the runtime's state storage (`@persistent` field or service).
+    pub persistent_fields: Vec<String>,
 }
 
 /// M4: Exports from a loaded module (scope + functions + services) for alias::name() dispatch.
@@ -255,6 +345,8 @@ pub struct CallFrame {
     pub scope: Scope,
     /// Function or method name for call-stack display (None for anonymous frames).
     pub function_name: Option<String>,
+    /// Location of the statement in the caller that made this call.
+    pub call_site: Option<SourceLocation>,
 }
 
 impl Runtime {
@@ -276,8 +368,11 @@ impl Runtime {
             execution_start: None, // NEW: Initialize execution start time
             current_caller: None,  // Transaction caller address (msg.sender)
             current_transaction_id: None, // Active transaction ID
-            pending_spawns: HashMap::new(),
-            spawn_counter: 0,
+            current_task: None,
+            meter: None,
+            last_method_usage: None,
+            task_scopes: Vec::new(),
+            channels: Vec::new(),
             closure_registry: HashMap::new(),
             closure_counter: 0,
             agent_states: HashMap::new(),
@@ -298,6 +393,21 @@ impl Runtime {
             iot_state: IotState::default(),
             desktop_state: DesktopState::default(),
             database_connections: HashMap::new(),
+            database_pools: HashMap::new(),
+            database_transactions: HashMap::new(),
+            call_cache: HashMap::new(),
+            state_storage: None,
+            #[cfg(feature = "wasm-runtime")]
+            wasm_services: None,
+            event_bus: crate::runtime::events::EventBus::new(),
+            event_depth: 0,
+            struct_types: HashMap::new(),
+            enum_types: HashMap::new(),
+            trait_types: HashMap::new(),
+            pending_impls: Vec::new(),
+            debug_hook: None,
+            bytecode_vm: true,
+            method_code: HashMap::new(),
         };
 
         // Register built-in functions
@@ -323,8 +433,11 @@ impl Runtime {
             execution_start: None, // NEW: Initialize execution start time
             current_caller: None,  // Transaction caller address (msg.sender)
             current_transaction_id: None, // Active transaction ID
-            pending_spawns: HashMap::new(),
-            spawn_counter: 0,
+            current_task: None,
+            meter: None,
+            last_method_usage: None,
+            task_scopes: Vec::new(),
+            channels: Vec::new(),
             closure_registry: HashMap::new(),
             closure_counter: 0,
             agent_states: HashMap::new(),
@@ -345,6 +458,21 @@ impl Runtime {
             iot_state: IotState::default(),
             desktop_state: DesktopState::default(),
             database_connections: HashMap::new(),
+            database_pools: HashMap::new(),
+            database_transactions: HashMap::new(),
+            call_cache: HashMap::new(),
+            state_storage: None,
+            #[cfg(feature = "wasm-runtime")]
+            wasm_services: None,
+            event_bus: crate::runtime::events::EventBus::new(),
+            event_depth: 0,
+            struct_types: HashMap::new(),
+            enum_types: HashMap::new(),
+            trait_types: HashMap::new(),
+            pending_impls: Vec::new(),
+            debug_hook: None,
+            bytecode_vm: true,
+            method_code: HashMap::new(),
         };
 
         // Register built-in functions
@@ -357,6 +485,11 @@ impl Runtime {
         self.allowed_namespaces = Some(names);
     }
 
+    /// Choose between the bytecode VM (the default) and the tree-walking evaluator.
+    pub fn set_bytecode_vm(&mut self, enabled: bool) {
+        self.bytecode_vm = enabled;
+    }
+
     /// Snapshot for `dal serve`: stdlib import aliases and loaded `import ... as name` exports.
     pub fn serve_import_snapshot(
         &self,
@@ -399,8 +532,21 @@ impl Runtime {
             .ok_or_else(|| RuntimeError::VariableNotFound(name.to_string()))
     }
 
+    /// Value of an identifier expression: a variable, or else a reference to the user function
+    /// of that name.
+    pub(crate) fn resolve_identifier(&self, name: &str) -> Result<Value, RuntimeError> {
+        match self.scope.get(name) {
+            Some(value) => Ok(value),
+            None if self.user_functions.contains_key(name) => {
+                Ok(Value::Closure(format!("{}{}", FUNCTION_REF_PREFIX, name)))
+            }
+            None if name == "None" => Ok(Value::none()),
+            None => Err(RuntimeError::VariableNotFound(name.to_string())),
+        }
+    }
+
     /// Determine storage for write-back when receiver is a variable or service field.
-    fn receiver_storage(
+    pub(crate) fn receiver_storage(
         &self,
         receiver: &crate::parser::ast::Expression,
     ) -> Option<ReceiverStorage> {
@@ -422,7 +568,7 @@ impl Runtime {
     }
 
     /// Write receiver value back after a mutating value method.
-    fn write_back_receiver(
+    pub(crate) fn write_back_receiver(
         &mut self,
         storage: ReceiverStorage,
         value: Value,
@@ -440,12 +586,55 @@ impl Runtime {
                     ))
                 })?;
                 instance.fields.insert(field_name, value);
-                Ok(())
+                self.forget_cached_methods(&instance_id);
+                self.store_persistent_fields(&instance_id)
+            }
+        }
+    }
+
+    /// Call `receiver.method_name(args)`: a service method when the receiver is a service
+    /// instance id, otherwise a list/map/set/struct value method. The flag is true for value
+    /// methods, whose (possibly mutated) receiver must be written back to its storage.
+    pub(crate) fn invoke_method(
+        &mut self,
+        receiver: &mut Value,
+        method_name: &str,
+        args: &[Value],
+    ) -> Result<(Value, bool), RuntimeError> {
+        if let Value::String(ref instance_id) = receiver {
+            if self.services.contains_key(instance_id) {
+                let method = self
+                    .services
+                    .get(instance_id)
+                    .and_then(|inst| inst.methods.iter().find(|m| m.name == method_name).cloned())
+                    .ok_or_else(|| {
+                        RuntimeError::General(format!(
+                            "Method '{}' not found on service instance '{}'",
+                            method_name, instance_id
+                        ))
+                    })?;
+                let instance_id = instance_id.clone();
+                return self
+                    .execute_service_method(&instance_id, method_name, &method, args)
+                    .map(|result| (result, false));
             }
         }
+        if let Value::Channel(_) = receiver {
+            // `ch.send(v)` is `chan::send(ch, v)`; the handle itself never changes.
+            let mut chan_args = vec![receiver.clone()];
+            chan_args.extend_from_slice(args);
+            return self
+                .call_chan_function(method_name, &chan_args)
+                .map(|result| (result, false));
+        }
+        let before = collection_items(receiver);
+        let result = self.call_value_method(receiver, method_name, args)?;
+        self.meter_allocation(collection_items(receiver).saturating_sub(before))?;
+        self.meter_allocation(collection_items(&result))?;
+        Ok((result, true))
     }
 
-    /// Dispatch value methods (list/map/set/struct). Mutates receiver when method is mutating.
+    /// Dispatch value methods (list/map/set/struct/bytes). Mutates receiver when method is mutating.
     fn call_value_method(
         &self,
         receiver: &mut Value,
@@ -554,6 +743,36 @@ impl Runtime {
                     .unwrap_or_default();
                 Ok(fields.get(&f).cloned().unwrap_or(Null))
             }
+            (Bytes(ref bytes), "length") | (Bytes(ref bytes), "size") => {
+                Ok(Value::Int(bytes.len() as i64))
+            }
+            (Bytes(ref bytes), "get") => {
+                let i = self.value_to_int(args.get(0).unwrap_or(&Null))? as usize;
+                Ok(bytes.get(i).map(|b| Value::Int(*b as i64)).unwrap_or(Null))
+            }
+            (Bytes(ref bytes), "slice") => {
+                let start = self.value_to_int(args.get(0).unwrap_or(&Int(0)))?;
+                let end = match args.get(1) {
+                    Some(v) => Some(self.value_to_int(v)?),
+                    None => None,
+                };
+                crate::stdlib::bytes::slice(bytes, start, end)
+                    .map(Bytes)
+                    .map_err(RuntimeError::General)
+            }
+            (Bytes(ref bytes), "concat") => match args.get(0) {
+                Some(Bytes(other)) => Ok(Bytes([&bytes[..], &other[..]].concat())),
+                other => Err(RuntimeError::TypeError {
+                    expected: "bytes".to_string(),
+                    got: other.unwrap_or(&Null).type_name().to_string(),
+                }),
+            },
+            (Bytes(ref bytes), "to_hex") => Ok(String(crate::stdlib::bytes::to_hex(bytes))),
+            (Bytes(ref bytes), "to_base64") => Ok(String(crate::stdlib::bytes::to_base64(bytes))),
+            (Bytes(ref bytes), "to_string") => crate::stdlib::bytes::to_utf8(bytes)
+                .map(String)
+                .map_err(RuntimeError::General),
+            (Bytes(ref bytes), "to_list") => Ok(crate::stdlib::bytes::to_list(bytes)),
             _ => Err(RuntimeError::General(format!(
                 "Method '{}' not found on type '{}'",
                 method_name, type_name
@@ -570,6 +789,7 @@ impl Runtime {
             Value::Float(f) => Ok(f.to_string()),
             Value::Bool(b) => Ok(b.to_string()),
             Value::Null => Ok("null".to_string()),
+            Value::Bytes(b) => Ok(crate::stdlib::bytes::to_hex(b)),
             _ => Err(RuntimeError::General(
                 "Cannot convert value to string".to_string(),
             )),
@@ -874,7 +1094,7 @@ impl Runtime {
                     .function_name
                     .clone()
                     .unwrap_or_else(|| "<anonymous>".to_string()),
-                line: None,
+                line: f.call_site.as_ref().map(|loc| loc.line),
             })
             .collect()
     }
@@ -882,7 +1102,7 @@ impl Runtime {
     /// P5: "did you mean" suggestions for VariableNotFound / FunctionNotFound.
     fn suggestions_for_error(&self, e: &RuntimeError) -> Vec<String> {
         const MAX_SUGGESTIONS: usize = 3;
-        match e {
+        match e.root() {
             RuntimeError::VariableNotFound(name) => {
                 let candidates = self.scope.keys();
                 nearest_strings(name, &candidates, MAX_SUGGESTIONS)
@@ -959,10 +1179,18 @@ impl Runtime {
                 }
             }
 
-            match self.execute_statement(&statement) {
+            match self
+                .trace_statement(&statement, span)
+                .and_then(|_| self.execute_top_level_statement(&statement))
+            {
                 Ok(value) => {
                     result = Some(value);
                 }
+                // A top-level `?` ends the program with the propagated value, like `return`.
+                Err(RuntimeError::Propagate(value)) => {
+                    result = Some(value);
+                    break;
+                }
                 Err(e) => {
                     self.execution_start = None;
                     let suggestions = self.suggestions_for_error(&e);
@@ -981,6 +1209,7 @@ impl Runtime {
         }
 
         self.execution_start = None;
+        self.check_unresolved_impls()?;
         Ok(result)
     }
 
@@ -990,9 +1219,9 @@ impl Runtime {
         for (suite_name, test_name, closure_id) in tests {
             let before_id = self.test_suite_before_each.get(&suite_name).cloned();
             if let Some(ref before_id) = before_id {
-                let _ = self.call_closure(before_id, &[Value::Null]);
+                let _ = self.call_test_callback(before_id);
             }
-            if let Err(e) = self.call_closure(&closure_id, &[Value::Null]) {
+            if let Err(e) = self.call_test_callback(&closure_id) {
                 return Err(RuntimeError::General(format!(
                     "Test '{}' in '{}' failed: {}",
                     test_name, suite_name, e
@@ -1027,7 +1256,7 @@ impl Runtime {
     }
 
     /// Check if a function is considered sensitive and requires time-lock protection
-    fn is_sensitive_function(&self, name: &str) -> bool {
+    pub(crate) fn is_sensitive_function(&self, name: &str) -> bool {
         // Define sensitive functions that require time-lock protection
         let sensitive_functions = [
             "transfer",
@@ -1048,6 +1277,115 @@ impl Runtime {
             .any(|&sensitive| name.contains(sensitive))
     }
 
+    /// `container[key]`: map lookup by string/int key or list/array index; missing entries are null.
+    pub(crate) fn index_value(
+        &self,
+        container: &Value,
+        key: &Value,
+    ) -> Result<Value, RuntimeError> {
+        match container {
+            Value::Map(ref map) => {
+                let key_str = match key {
+                    Value::String(s) => s.clone(),
+                    Value::Int(i) => i.to_string(),
+                    _ => {
+                        return Err(RuntimeError::General(format!(
+                            "Map key must be string or int, got: {}",
+                            key.type_name()
+                        )))
+                    }
+                };
+                Ok(map.get(&key_str).cloned().unwrap_or(Value::Null))
+            }
+            Value::Array(ref arr) | Value::List(ref arr) => {
+                let index = match key {
+                    Value::Int(i) => *i as usize,
+                    _ => {
+                        return Err(RuntimeError::General(format!(
+                            "Array index must be int, got: {}",
+                            key.type_name()
+                        )))
+                    }
+                };
+                Ok(arr.get(index).cloned().unwrap_or(Value::Null))
+            }
+            Value::Bytes(ref bytes) => {
+                let index = match key {
+                    Value::Int(i) => *i as usize,
+                    _ => {
+                        return Err(RuntimeError::General(format!(
+                            "Bytes index must be int, got: {}",
+                            key.type_name()
+                        )))
+                    }
+                };
+                Ok(bytes
+                    .get(index)
+                    .map(|b| Value::Int(*b as i64))
+                    .unwrap_or(Value::Null))
+            }
+            _ => Err(RuntimeError::General(format!(
+                "Cannot index value of type: {}",
+                container.type_name()
+            ))),
+        }
+    }
+
+    /// Apply `var_name[key] = value` to `target`, the variable's current value. Arrays grow
+    /// with nulls when assigning past the end.
+    pub(crate) fn assign_index(
+        var_name: &str,
+        target: &mut Value,
+        key: &Value,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        let type_name = target.type_name();
+        match target {
+            Value::Map(map) => {
+                let key_str = match key {
+                    Value::String(s) => s.clone(),
+                    Value::Int(i) => i.to_string(),
+                    _ => {
+                        return Err(RuntimeError::General(format!(
+                            "Map key must be string or int, got: {}",
+                            key.type_name()
+                        )))
+                    }
+                };
+                map.insert(key_str, value);
+                Ok(())
+            }
+            Value::Array(arr) | Value::List(arr) => {
+                let index = match key {
+                    Value::Int(i) if *i >= 0 => *i as usize,
+                    _ => {
+                        return Err(RuntimeError::General(format!(
+                            "Array index must be non-negative int, got: {}",
+                            key.type_name()
+                        )))
+                    }
+                };
+                if index < arr.len() {
+                    arr[index] = value;
+                } else {
+                    while arr.len() < index {
+                        arr.push(Value::Null);
+                    }
+                    arr.push(value);
+                }
+                // Assigning through an index always leaves an array, as it always has.
+                if let Value::List(items) = target {
+                    *target = Value::Array(std::mem::take(items));
+                }
+                Ok(())
+            }
+            _ => Err(RuntimeError::General(format!(
+                "Cannot assign to index of variable '{}' (type: {})",
+                var_name, type_name
+            ))),
+        }
+    }
+
     pub fn call_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
         // Check for mock interception first (before any other logic)
         // The mutable borrow is scoped to this block and released before normal execution
@@ -1106,7 +1444,7 @@ impl Runtime {
                 }
             };
             let expected_msg = self.value_to_string(&args[1])?;
-            match self.call_closure(&closure_id, &[Value::Null]) {
+            match self.call_test_callback(&closure_id) {
                 Ok(_) => {
                     return Err(RuntimeError::General(
                         "Expected code to throw, but it succeeded".to_string(),
@@ -1142,7 +1480,7 @@ impl Runtime {
             };
             crate::stdlib::test::register_suite(suite_name.clone());
             self.test_current_suite = Some(suite_name);
-            let _ = self.call_closure(&closure_id, &[Value::Null]);
+            let _ = self.call_test_callback(&closure_id);
             self.test_current_suite = None;
             return Ok(Value::Null);
         }
@@ -1301,42 +1639,7 @@ impl Runtime {
                     got: args.len(),
                 });
             }
-            let container = &args[0];
-            let key = &args[1];
-
-            match container {
-                Value::Map(ref map) => {
-                    let key_str = match key {
-                        Value::String(s) => s.clone(),
-                        Value::Int(i) => i.to_string(),
-                        _ => {
-                            return Err(RuntimeError::General(format!(
-                                "Map key must be string or int, got: {}",
-                                key.type_name()
-                            )))
-                        }
-                    };
-                    return Ok(map.get(&key_str).cloned().unwrap_or(Value::Null));
-                }
-                Value::Array(ref arr) | Value::List(ref arr) => {
-                    let index = match key {
-                        Value::Int(i) => *i as usize,
-                        _ => {
-                            return Err(RuntimeError::General(format!(
-                                "Array index must be int, got: {}",
-                                key.type_name()
-                            )))
-                        }
-                    };
-                    return Ok(arr.get(index).cloned().unwrap_or(Value::Null));
-                }
-                _ => {
-                    return Err(RuntimeError::General(format!(
-                        "Cannot index value of type: {}",
-                        container.type_name()
-                    )))
-                }
-            }
+            return self.index_value(&args[0], &args[1]);
         }
 
         if name == "__index_assign__" {
@@ -1371,59 +1674,16 @@ impl Runtime {
 
             // Variable assignment: map_var[key] = value or arr_var[index] = value
             if let Some(var_name) = var_name {
-                let current = self.get_variable(&var_name)?;
-                match &current {
-                    Value::Map(map) => {
-                        let key_str = match key {
-                            Value::String(s) => s.clone(),
-                            Value::Int(i) => i.to_string(),
-                            _ => {
-                                return Err(RuntimeError::General(format!(
-                                    "Map key must be string or int, got: {}",
-                                    key.type_name()
-                                )))
-                            }
-                        };
-                        let mut new_map = map.clone();
-                        new_map.insert(key_str, value.clone());
-                        self.set_variable(var_name, Value::Map(new_map));
-                        return Ok(value);
-                    }
-                    Value::Array(arr) | Value::List(arr) => {
-                        let index = match key {
-                            Value::Int(i) if *i >= 0 => *i as usize,
-                            _ => {
-                                return Err(RuntimeError::General(format!(
-                                    "Array index must be non-negative int, got: {}",
-                                    key.type_name()
-                                )))
-                            }
-                        };
-                        let mut new_arr = arr.clone();
-                        if index < new_arr.len() {
-                            new_arr[index] = value.clone();
-                        } else {
-                            while new_arr.len() < index {
-                                new_arr.push(Value::Null);
-                            }
-                            new_arr.push(value.clone());
-                        }
-                        self.set_variable(var_name, Value::Array(new_arr));
-                        return Ok(value);
-                    }
-                    _ => {
-                        return Err(RuntimeError::General(format!(
-                            "Cannot assign to index of variable '{}' (type: {})",
-                            var_name,
-                            current.type_name()
-                        )))
-                    }
-                }
+                let mut current = self.get_variable(&var_name)?;
+                Self::assign_index(&var_name, &mut current, key, value.clone())?;
+                self.set_variable(var_name, current);
+                return Ok(value);
             }
 
             // Check if we're in a service method context (self is in scope)
             if let Ok(Value::String(ref instance_id)) = self.get_variable("self") {
                 if let Value::Map(ref _map) = container {
+                    self.forget_cached_methods(instance_id);
                     if let Some(instance) = self.services.get_mut(instance_id) {
                         let key_str = match key {
                             Value::String(s) => s.clone(),
@@ -1501,9 +1761,14 @@ impl Runtime {
             return self.call_closure(id, args);
         }
 
-        // Handle namespace calls (e.g., oracle::fetch)
+        // Handle namespace calls (e.g., oracle::fetch); declared enum variants take precedence
         if name.contains("::") {
-            return self.call_namespace_function(name, args);
+            if let Some(variant) = self.construct_enum_variant(name, args) {
+                return variant;
+            }
+            let result = self.call_namespace_function(name, args)?;
+            self.meter_allocation(collection_items(&result))?;
+            return Ok(result);
         }
 
         // Handle instance method calls (e.g., nft.initialize)
@@ -1558,53 +1823,54 @@ impl Runtime {
         }
 
         // Dispatch to user-defined (DAL) function if registered during execution
-        let user_func = self.user_functions.get(name).cloned();
-        if let Some(user_func) = user_func {
+        if let Some(user_func) = self.user_functions.get(name) {
             if args.len() != user_func.parameters.len() {
                 return Err(RuntimeError::ArgumentCountMismatch {
                     expected: user_func.parameters.len(),
                     got: args.len(),
                 });
             }
+            let parameters = user_func.parameters.clone();
+            let body = self.callable_body(&user_func.body, &user_func.code);
             // @txn attribute: wrap execution in begin/commit or rollback
-            let has_txn = user_func.attributes.iter().any(|a| a.name == "@txn");
+            let txn_attributes = user_func
+                .attributes
+                .iter()
+                .any(|a| a.name == "@txn")
+                .then(|| user_func.attributes.clone());
+            let has_txn = txn_attributes.is_some();
+            let cache_attributes = user_func
+                .attributes
+                .iter()
+                .any(|a| a.name == "@cached")
+                .then(|| user_func.attributes.clone());
+            let cache_key = cache_attributes
+                .and_then(|attributes| self.call_cache_key(name, &attributes, args));
+            if let Some((key, _)) = &cache_key {
+                if let Some(value) = self.cached_result(key) {
+                    return Ok(value);
+                }
+            }
             let prev_tx = self.current_transaction_id.clone();
-            if has_txn {
-                let (level, timeout) = self.parse_txn_attribute(&user_func);
+            if let Some(attributes) = txn_attributes {
+                let (level, timeout) = self.parse_txn_attribute(&attributes);
                 self.begin_transaction(level, timeout)?;
             }
             let call_frame = CallFrame {
                 scope: self.scope.clone(),
                 function_name: Some(name.to_string()),
+                call_site: self.current_location.clone(),
             };
             self.call_stack.push(call_frame);
-            for (param, arg) in user_func.parameters.iter().zip(args.iter()) {
-                self.scope.set(param.clone(), arg.clone());
-            }
-            let mut result = Value::Null;
-            self.return_pending = None;
-            let mut exec_error = None::<RuntimeError>;
-            for stmt in &user_func.body.statements {
-                match self.execute_statement(stmt) {
-                    Ok(value) => {
-                        result = value;
-                        if self.return_pending.is_some() {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        exec_error = Some(e);
-                        self.return_pending = None;
-                        break;
-                    }
-                }
+            for (param, arg) in parameters.into_iter().zip(args.iter()) {
+                self.scope.set(param, arg.clone());
             }
-            self.return_pending = None;
+            let outcome = self.run_callable_body(&body);
             if let Some(frame) = self.call_stack.pop() {
                 self.scope = frame.scope;
             }
             if has_txn {
-                if exec_error.is_none() {
+                if outcome.is_ok() {
                     self.commit_transaction()
                         .map_err(|e| RuntimeError::General(format!("@txn commit failed: {}", e)))?;
                 } else {
@@ -1612,10 +1878,10 @@ impl Runtime {
                 }
                 self.current_transaction_id = prev_tx;
             }
-            if let Some(e) = exec_error {
-                return Err(e);
+            if let (Some((key, ttl)), Ok(value)) = (cache_key, &outcome) {
+                self.store_cached_result(key, ttl, value);
             }
-            return Ok(result);
+            return outcome;
         }
 
         let function = self
@@ -1627,6 +1893,7 @@ impl Runtime {
         let call_frame = CallFrame {
             scope: self.scope.clone(),
             function_name: Some(name.to_string()),
+            call_site: self.current_location.clone(),
         };
         self.call_stack.push(call_frame);
 
@@ -1638,7 +1905,9 @@ impl Runtime {
             self.scope = frame.scope;
         }
 
-        result
+        let result = result?;
+        self.meter_allocation(collection_items(&result))?;
+        Ok(result)
     }
 
     /// M4: Call a function from a loaded module (alias::function_name).
@@ -1660,6 +1929,7 @@ impl Runtime {
         let call_frame = CallFrame {
             scope: self.scope.clone(),
             function_name: Some(format!("{}::{}", "module", function_name)),
+            call_site: self.current_location.clone(),
         };
         self.call_stack.push(call_frame);
         // Make module-local helpers callable by unqualified name while executing this module function.
@@ -1677,35 +1947,15 @@ impl Runtime {
         for (param, arg) in user_func.parameters.iter().zip(args.iter()) {
             self.scope.set(param.clone(), arg.clone());
         }
-        let mut result = Value::Null;
-        self.return_pending = None;
-        let mut exec_error = None::<RuntimeError>;
-        for stmt in &user_func.body.statements {
-            match self.execute_statement(stmt) {
-                Ok(value) => {
-                    result = value;
-                    if self.return_pending.is_some() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    exec_error = Some(e);
-                    self.return_pending = None;
-                    break;
-                }
-            }
-        }
-        self.return_pending = None;
+        let body = self.callable_body(&user_func.body, &user_func.code);
+        let outcome = self.run_callable_body(&body);
         self.scope = saved_scope;
         self.user_functions = saved_user_functions;
         self.services = saved_services;
         if let Some(frame) = self.call_stack.pop() {
             self.scope = frame.scope;
         }
-        if let Some(e) = exec_error {
-            return Err(e);
-        }
-        Ok(result)
+        outcome
     }
 
     fn call_namespace_function(
@@ -1785,12 +2035,17 @@ impl Runtime {
             return self.call_module_function(&exports, function_name, args);
         }
 
+        #[cfg(feature = "wasm-runtime")]
+        if let Some(result) = self.call_wasm_service(namespace, function_name, args) {
+            return result;
+        }
+
         // Check if namespace is a registered service name
         if self.services.contains_key(namespace) {
             return self.call_service_instance_method(namespace, function_name, args);
         }
 
-        match namespace {
+        let result = match namespace {
             "oracle" => self.call_oracle_function(function_name, args),
             "rag" => self.call_rag_function(function_name, args),
             "service" => self.call_service_function(function_name, args),
@@ -1818,6 +2073,7 @@ impl Runtime {
             "config" => self.call_config_function(function_name, args),
             "sh" => self.call_sh_function(function_name, args),
             "fs" => self.call_fs_function(function_name, args),
+            "bytes" => self.call_bytes_function(function_name, args),
             "evolve" => self.call_evolve_function(function_name, args),
             "http" => self.call_http_function(function_name, args),
             "graph" => self.call_graph_function(function_name, args),
@@ -1825,6 +2081,8 @@ impl Runtime {
             "scatter" => self.call_scatter_function(function_name, args),
             "schedule" => self.call_schedule_function(function_name, args),
             "time" => self.call_time_function(function_name, args),
+            "task" => self.call_task_function(function_name, args),
+            "chan" => self.call_chan_function(function_name, args),
             "workflow" => self.call_workflow_function(function_name, args),
             "skills" => self.call_skills_function(function_name, args),
             "trust" => self.call_trust_function(function_name, args),
@@ -1840,7 +2098,9 @@ impl Runtime {
                     )))
                 }
             }
-        }
+        };
+        // Tag stdlib failures so typed `catch` clauses can match them by namespace.
+        result.map_err(|e| e.in_namespace(namespace))
     }
 
     // Handle method calls on service instances (e.g., TestNFT::new(), TestNFT::someMethod())
@@ -1857,14 +2117,16 @@ impl Runtime {
             })?;
 
             // Create a new instance with copied fields
-            let new_instance = ServiceInstance {
+            let mut new_instance = ServiceInstance {
                 name: service_template.name.clone(),
                 fields: service_template.fields.clone(),
                 methods: service_template.methods.clone(),
                 events: service_template.events.clone(),
                 attributes: service_template.attributes.clone(),
                 exported: service_template.exported,
+                persistent_fields: service_template.persistent_fields.clone(),
             };
+            self.load_persistent_fields(&mut new_instance)?;
 
             // Store the new instance with a unique identifier
             let instance_id = format!("{}_instance_{}", service_name, self.services.len());
@@ -2145,15 +2407,9 @@ impl Runtime {
                 match oracle::fetch(&source, query) {
                     Ok(response) => {
                         // Return as Result<OracleResponse, String>
-                        Ok(Value::Result(
-                            Box::new(self.oracle_response_to_value(&response)),
-                            Box::new(Value::Null),
-                        ))
+                        Ok(Value::ok(self.oracle_response_to_value(&response)))
                     }
-                    Err(e) => Ok(Value::Result(
-                        Box::new(Value::Null),
-                        Box::new(Value::String(e)),
-                    )),
+                    Err(e) => Ok(Value::err(Value::String(e))),
                 }
             }
 
@@ -2205,14 +2461,8 @@ impl Runtime {
 
                 let source_refs: Vec<&str> = sources.iter().map(|s| s.as_str()).collect();
                 match oracle::fetch_with_consensus(source_refs, query, threshold) {
-                    Ok(response) => Ok(Value::Result(
-                        Box::new(self.oracle_response_to_value(&response)),
-                        Box::new(Value::Null),
-                    )),
-                    Err(e) => Ok(Value::Result(
-                        Box::new(Value::Null),
-                        Box::new(Value::String(e)),
-                    )),
+                    Ok(response) => Ok(Value::ok(self.oracle_response_to_value(&response))),
+                    Err(e) => Ok(Value::err(Value::String(e))),
                 }
             }
 
@@ -2289,6 +2539,61 @@ impl Runtime {
 
     fn call_service_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
         match name {
+            "subscribe" => {
+                // service::subscribe("EventName", fn(payload) { ... })
+                if args.len() != 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 2,
+                        got: args.len(),
+                    });
+                }
+                let event_name = self.value_to_string(&args[0])?;
+                if !matches!(args[1], Value::Closure(_)) {
+                    return Err(RuntimeError::General(
+                        "service::subscribe() expects a function as the handler".to_string(),
+                    ));
+                }
+                self.event_bus.subscribe(
+                    &event_name,
+                    crate::runtime::events::EventHandler::Callback(args[1].clone()),
+                );
+                Ok(Value::Bool(true))
+            }
+            "event_log" => {
+                // service::event_log() / service::event_log("EventName") -> published events
+                if args.len() > 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let filter = match args.first() {
+                    Some(name) => Some(self.value_to_string(name)?),
+                    None => None,
+                };
+                Ok(Value::List(
+                    self.event_bus
+                        .log(filter.as_deref())
+                        .into_iter()
+                        .map(|event| event.to_value())
+                        .collect(),
+                ))
+            }
+            "migration_history" => {
+                // service::migration_history("ServiceName") -> list of @versioned records
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let service_name = self.value_to_string(&args[0])?;
+                let key = format!("{}.__versions", service_name);
+                Ok(self
+                    .state_storage()?
+                    .get(&key)
+                    .unwrap_or(Value::List(Vec::new())))
+            }
             "new" => {
                 // service::new("ServiceName") - create a new instance of a service
                 if args.len() != 1 {
@@ -2316,14 +2621,16 @@ impl Runtime {
                 })?;
 
                 // Create a new instance with copied fields
-                let new_instance = ServiceInstance {
+                let mut new_instance = ServiceInstance {
                     name: service_template.name.clone(),
                     fields: service_template.fields.clone(),
                     methods: service_template.methods.clone(),
                     events: service_template.events.clone(),
                     attributes: service_template.attributes.clone(),
                     exported: service_template.exported,
+                    persistent_fields: service_template.persistent_fields.clone(),
                 };
+                self.load_persistent_fields(&mut new_instance)?;
 
                 // Store the new instance with a unique identifier
                 let instance_id = format!("{}_instance_{}", service_name, self.services.len());
@@ -2406,30 +2713,15 @@ impl Runtime {
     fn call_sync_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
         match name {
             "join" => {
-                // sync::join(handle) — block until spawned task completes and return its result
+                // sync::join(task) — wait for a spawned task and return its result (as `await task`)
                 if args.len() != 1 {
                     return Err(RuntimeError::ArgumentCountMismatch {
                         expected: 1,
                         got: args.len(),
                     });
                 }
-                let task_id = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => {
-                        return Err(RuntimeError::General(
-                            "sync::join expects a string handle".to_string(),
-                        ))
-                    }
-                };
-                let rx = self.pending_spawns.remove(&task_id).ok_or_else(|| {
-                    RuntimeError::General(format!(
-                        "Spawn handle '{}' not found or already joined",
-                        task_id
-                    ))
-                })?;
-                rx.recv().map_err(|_| {
-                    RuntimeError::General(format!("Spawn '{}' channel closed", task_id))
-                })?
+                let id = Self::task_handle(&args[0])?;
+                crate::runtime::tasks::join(id, None, self.current_task.as_ref())
             }
             "create_sync_target" => {
                 if args.len() != 2 {
@@ -2833,8 +3125,10 @@ impl Runtime {
                         })
                     }
                 };
-                let balance = crate::stdlib::chain::get_balance(chain_id, address);
-                Ok(Value::Int(balance))
+                // Wei amounts overflow i64 past ~9.2 ETH; -1 still signals strict policy.
+                Ok(crate::stdlib::chain::get_balance_wei(chain_id, address)
+                    .map(Value::U256)
+                    .unwrap_or(Value::Int(-1)))
             }
             "call" => {
                 if args.len() != 4 && args.len() != 5 {
@@ -3008,9 +3302,11 @@ impl Runtime {
                             })
                         }
                     };
-                    let balance = crate::stdlib::chain::get_balance(chain_id, address.clone());
+                    let balance = crate::stdlib::chain::get_balance_wei(chain_id, address.clone())
+                        .map(Value::U256)
+                        .unwrap_or(Value::Int(-1));
                     let mut m = HashMap::new();
-                    m.insert("balance".to_string(), Value::Int(balance));
+                    m.insert("balance".to_string(), balance);
                     m.insert("chain_id".to_string(), Value::Int(chain_id));
                     m.insert("address".to_string(), Value::String(address));
                     return Ok(Value::Map(m));
@@ -3173,12 +3469,12 @@ impl Runtime {
                         })
                     }
                 };
-                let balance = crate::stdlib::chain::get_token_balance(
+                let balance = crate::stdlib::chain::get_token_balance_raw(
                     chain_id,
                     token_symbol_or_contract,
                     address,
                 );
-                Ok(Value::Int(balance))
+                Ok(Value::U256(balance))
             }
             "get_block_hash" => {
                 let chain_id = if args.is_empty() {
@@ -4274,52 +4570,373 @@ impl Runtime {
         }
     }
 
-    fn call_crypto_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
-        use crate::stdlib::crypto::{self, HashAlgorithm, SignatureAlgorithm};
-
-        fn parse_hash_algorithm(s: &str) -> HashAlgorithm {
-            match s.to_uppercase().as_str() {
-                "SHA256" => HashAlgorithm::SHA256,
-                "SHA512" => HashAlgorithm::SHA512,
-                "SIMPLE" => HashAlgorithm::Simple,
-                _ => HashAlgorithm::Custom(s.to_string()),
-            }
-        }
-
-        fn parse_sig_algorithm(s: &str) -> SignatureAlgorithm {
-            match s.to_uppercase().as_str() {
-                "RSA" => SignatureAlgorithm::RSA,
-                "ECDSA" => SignatureAlgorithm::ECDSA,
-                "ED25519" => SignatureAlgorithm::Ed25519,
-                _ => SignatureAlgorithm::Custom(s.to_string()),
-            }
-        }
-
+    fn call_task_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        use crate::runtime::tasks;
+        let current = self.current_task.clone();
+        let current = current.as_ref();
         match name {
-            "hash" => {
-                if args.len() < 1 || args.len() > 2 {
+            "join" => {
+                // task::join(task, timeout_ms?) — the task's value; on timeout it is cancelled
+                if args.is_empty() || args.len() > 2 {
                     return Err(RuntimeError::ArgumentCountMismatch {
-                        expected: 2,
+                        expected: 1,
                         got: args.len(),
                     });
                 }
-                let data = self.value_to_string(&args[0])?;
-                let algorithm = if args.len() > 1 {
-                    parse_hash_algorithm(&self.value_to_string(&args[1])?)
-                } else {
-                    HashAlgorithm::SHA256
-                };
-                Ok(Value::String(crypto::hash(&data, algorithm)))
+                let id = Self::task_handle(&args[0])?;
+                let timeout = Self::timeout_arg(args.get(1))?;
+                tasks::join(id, timeout, current)
             }
-            "hash_bytes" => {
-                if args.len() != 2 {
+            "all" => {
+                // task::all(tasks, timeout_ms?) — every value in order; the first failure cancels the rest
+                if args.is_empty() || args.len() > 2 {
                     return Err(RuntimeError::ArgumentCountMismatch {
-                        expected: 2,
+                        expected: 1,
                         got: args.len(),
                     });
                 }
-                let bytes = match &args[0] {
+                let ids = Self::task_handles(&args[0])?;
+                let timeout = Self::timeout_arg(args.get(1))?;
+                tasks::join_all(&ids, timeout, current).map(Value::List)
+            }
+            "any" | "select" => {
+                // task::any(tasks, timeout_ms?) — the first value; the other tasks are cancelled
+                if args.is_empty() || args.len() > 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let ids = Self::task_handles(&args[0])?;
+                let timeout = Self::timeout_arg(args.get(1))?;
+                let (index, result) = tasks::select(&ids, timeout, current)?;
+                let value = result?;
+                if name == "any" {
+                    return Ok(value);
+                }
+                let mut map = HashMap::new();
+                map.insert("index".to_string(), Value::Int(index as i64));
+                map.insert("value".to_string(), value);
+                Ok(Value::Map(map))
+            }
+            "cancel" => {
+                // task::cancel(task) — false when it had already finished
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::task_handle(&args[0])?;
+                Ok(Value::Bool(tasks::cancel(id)))
+            }
+            "status" => {
+                // task::status(task) — queued, running, done, failed or cancelled
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::task_handle(&args[0])?;
+                Ok(Value::String(tasks::status(id).to_string()))
+            }
+            "is_cancelled" => {
+                // task::is_cancelled() — whether the running task has been asked to stop
+                if !args.is_empty() {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 0,
+                        got: args.len(),
+                    });
+                }
+                Ok(Value::Bool(current.is_some_and(|t| t.is_cancelled())))
+            }
+            "sleep" => {
+                // task::sleep(ms) — pause, waking early if the running task is cancelled
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let duration = Self::timeout_arg(args.first())?.unwrap_or_default();
+                tasks::sleep(duration, current)?;
+                Ok(Value::Null)
+            }
+            "scope" => {
+                // task::scope(fn) — call fn; tasks it spawned and left running are cancelled
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                self.task_scopes.push(TaskScope::default());
+                let result = self.call_value(&args[0], &[]);
+                let scope = self.task_scopes.pop().unwrap_or_default();
+                for id in scope.tasks {
+                    tasks::cancel(id);
+                }
+                for id in scope.channels {
+                    crate::runtime::channels::release(id);
+                }
+                result
+            }
+            "pool_size" => {
+                // task::pool_size() — worker threads shared by every spawned task
+                if !args.is_empty() {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 0,
+                        got: args.len(),
+                    });
+                }
+                Ok(Value::Int(tasks::pool_size() as i64))
+            }
+            _ => Err(RuntimeError::function_not_found(format!("task::{}", name))),
+        }
+    }
+
+    /// The id of a task handle argument.
+    fn task_handle(value: &Value) -> Result<u64, RuntimeError> {
+        match value {
+            Value::Task(id) => Ok(*id),
+            other => Err(RuntimeError::TypeError {
+                expected: "task".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// The ids of a list of task handles.
+    fn task_handles(value: &Value) -> Result<Vec<u64>, RuntimeError> {
+        match value {
+            Value::List(items) | Value::Array(items) => {
+                items.iter().map(Self::task_handle).collect()
+            }
+            other => Err(RuntimeError::TypeError {
+                expected: "list".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// An optional timeout argument in milliseconds.
+    fn timeout_arg(value: Option<&Value>) -> Result<Option<std::time::Duration>, RuntimeError> {
+        match value {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::Int(ms)) if *ms >= 0 => {
+                Ok(Some(std::time::Duration::from_millis(*ms as u64)))
+            }
+            Some(other) => Err(RuntimeError::TypeError {
+                expected: "non-negative int (milliseconds)".to_string(),
+                got: other.to_string(),
+            }),
+        }
+    }
+
+    fn call_chan_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        use crate::runtime::channels;
+        let current = self.current_task.clone();
+        let current = current.as_ref();
+        match name {
+            "new" => {
+                // chan::new(capacity, type?) — a channel buffering up to capacity values
+                if args.is_empty() || args.len() > 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let capacity = match &args[0] {
+                    Value::Int(n) if *n >= 0 => *n as usize,
+                    other => {
+                        return Err(RuntimeError::TypeError {
+                            expected: "non-negative int".to_string(),
+                            got: other.to_string(),
+                        })
+                    }
+                };
+                let element_type = match args.get(1) {
+                    Some(Value::String(annotation)) => Some(annotation.clone()),
+                    None | Some(Value::Null) => None,
+                    Some(other) => {
+                        return Err(RuntimeError::TypeError {
+                            expected: "string".to_string(),
+                            got: other.type_name().to_string(),
+                        })
+                    }
+                };
+                let id = channels::new(capacity, element_type)?;
+                match self.task_scopes.last_mut() {
+                    Some(scope) => scope.channels.push(id),
+                    None => self.channels.push(id),
+                }
+                Ok(Value::Channel(id))
+            }
+            "send" => {
+                // chan::send(ch, value, timeout_ms?) — waits while the channel is full
+                if args.len() < 2 || args.len() > 3 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 2,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::channel_handle(&args[0])?;
+                let value = args[1].clone();
+                let timeout = Self::timeout_arg(args.get(2))?;
+                channels::send(id, value, timeout, current)?;
+                Ok(Value::Null)
+            }
+            "recv" => {
+                // chan::recv(ch, timeout_ms?) — the oldest value; null once closed and drained
+                if args.is_empty() || args.len() > 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::channel_handle(&args[0])?;
+                let timeout = Self::timeout_arg(args.get(1))?;
+                Ok(channels::recv(id, timeout, current)?.unwrap_or(Value::Null))
+            }
+            "close" => {
+                // chan::close(ch) — false when it was already closed
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::channel_handle(&args[0])?;
+                Ok(Value::Bool(channels::close(id)))
+            }
+            "len" => {
+                // chan::len(ch) — values waiting to be received
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::channel_handle(&args[0])?;
+                Ok(Value::Int(channels::len(id) as i64))
+            }
+            "is_closed" => {
+                // chan::is_closed(ch)
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let id = Self::channel_handle(&args[0])?;
+                Ok(Value::Bool(channels::is_closed(id)))
+            }
+            _ => Err(RuntimeError::function_not_found(format!("chan::{}", name))),
+        }
+    }
+
+    /// The id of a channel handle argument.
+    fn channel_handle(value: &Value) -> Result<u64, RuntimeError> {
+        match value {
+            Value::Channel(id) => Ok(*id),
+            other => Err(RuntimeError::TypeError {
+                expected: "chan".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// `await expr`: the value of a task handle, `all(tasks)`, `any(tasks)` and
+    /// `timeout(task, ms)` as `task::all`, `task::any` and `task::join`, and any other value as
+    /// it is. The combinators take an optional timeout in milliseconds; a user function of the
+    /// same name takes precedence.
+    fn evaluate_await(
+        &mut self,
+        expr: &crate::parser::ast::Expression,
+        depth: usize,
+    ) -> Result<Value, RuntimeError> {
+        if let crate::parser::ast::Expression::FunctionCall(call) = expr {
+            let function = match call.name.as_str() {
+                "all" => Some("all"),
+                "any" => Some("any"),
+                "timeout" => Some("join"),
+                _ => None,
+            }
+            .filter(|_| !self.user_functions.contains_key(&call.name));
+            if let Some(function) = function {
+                let mut args = Vec::new();
+                for arg in &call.arguments {
+                    args.push(self.evaluate_expression_at_depth(arg, depth)?);
+                }
+                if function == "join" && !matches!(args.first(), Some(Value::Task(_))) {
+                    // `await timeout(value, ms)` of something already computed.
+                    return Ok(args.into_iter().next().unwrap_or(Value::Null));
+                }
+                return self.call_task_function(function, &args);
+            }
+        }
+        match self.evaluate_expression_at_depth(expr, depth)? {
+            Value::Task(id) => crate::runtime::tasks::join(id, None, self.current_task.as_ref()),
+            value => Ok(value),
+        }
+    }
+
+    fn call_crypto_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        use crate::stdlib::crypto::{self, HashAlgorithm, SignatureAlgorithm};
+
+        fn parse_hash_algorithm(s: &str) -> HashAlgorithm {
+            match s.to_uppercase().as_str() {
+                "SHA256" => HashAlgorithm::SHA256,
+                "SHA512" => HashAlgorithm::SHA512,
+                "SIMPLE" => HashAlgorithm::Simple,
+                _ => HashAlgorithm::Custom(s.to_string()),
+            }
+        }
+
+        fn parse_sig_algorithm(s: &str) -> SignatureAlgorithm {
+            match s.to_uppercase().as_str() {
+                "RSA" => SignatureAlgorithm::RSA,
+                "ECDSA" => SignatureAlgorithm::ECDSA,
+                "ED25519" => SignatureAlgorithm::Ed25519,
+                _ => SignatureAlgorithm::Custom(s.to_string()),
+            }
+        }
+
+        // `bytes` are used as-is; anything else by its string form.
+        let data_of = |value: &Value| -> Result<Vec<u8>, RuntimeError> {
+            match value {
+                Value::Bytes(b) => Ok(b.clone()),
+                other => self.value_to_string(other).map(String::into_bytes),
+            }
+        };
+
+        match name {
+            "hash" => {
+                if args.len() < 1 || args.len() > 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 2,
+                        got: args.len(),
+                    });
+                }
+                let data = data_of(&args[0])?;
+                let algorithm = if args.len() > 1 {
+                    parse_hash_algorithm(&self.value_to_string(&args[1])?)
+                } else {
+                    HashAlgorithm::SHA256
+                };
+                Ok(Value::String(crypto::hash_data(&data, algorithm)))
+            }
+            "hash_bytes" => {
+                if args.len() != 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 2,
+                        got: args.len(),
+                    });
+                }
+                let bytes = match &args[0] {
                     Value::String(s) => s.as_bytes().to_vec(),
+                    Value::Bytes(b) => b.clone(),
                     Value::Array(arr) => arr
                         .iter()
                         .filter_map(|v| match v {
@@ -4329,7 +4946,8 @@ impl Runtime {
                         .collect(),
                     _ => {
                         return Err(RuntimeError::General(
-                            "crypto::hash_bytes: first arg must be string or int array".to_string(),
+                            "crypto::hash_bytes: first arg must be string, bytes or int array"
+                                .to_string(),
                         ))
                     }
                 };
@@ -4353,14 +4971,18 @@ impl Runtime {
                         got: args.len(),
                     });
                 }
-                let data = self.value_to_string(&args[0])?;
+                let data = data_of(&args[0])?;
                 let private_key = self.value_to_string(&args[1])?;
                 let algorithm = if args.len() > 2 {
                     parse_sig_algorithm(&self.value_to_string(&args[2])?)
                 } else {
                     SignatureAlgorithm::RSA
                 };
-                Ok(Value::String(crypto::sign(&data, &private_key, algorithm)))
+                Ok(Value::String(crypto::sign_data(
+                    &data,
+                    &private_key,
+                    algorithm,
+                )))
             }
             "verify" => {
                 if args.len() < 3 || args.len() > 4 {
@@ -4369,7 +4991,7 @@ impl Runtime {
                         got: args.len(),
                     });
                 }
-                let data = self.value_to_string(&args[0])?;
+                let data = data_of(&args[0])?;
                 let signature = self.value_to_string(&args[1])?;
                 let public_key = self.value_to_string(&args[2])?;
                 let algorithm = if args.len() > 3 {
@@ -4377,7 +4999,7 @@ impl Runtime {
                 } else {
                     SignatureAlgorithm::RSA
                 };
-                Ok(Value::Bool(crypto::verify(
+                Ok(Value::Bool(crypto::verify_data(
                     &data,
                     &signature,
                     &public_key,
@@ -4454,25 +5076,219 @@ impl Runtime {
         }
     }
 
-    pub fn execute(&mut self, program: &Program) -> Result<Value, RuntimeError> {
-        let mut last_result = Value::Null;
+    pub fn execute(&mut self, program: &Program) -> Result<Value, RuntimeError> {
+        let mut last_result = Value::Null;
+
+        for statement in &program.statements {
+            match self.execute_statement(statement) {
+                Ok(value) => last_result = value,
+                Err(RuntimeError::Propagate(value)) => return Ok(value),
+                Err(e) => return Err(e),
+            }
+            if self.return_pending.is_some() {
+                self.return_pending = None;
+                break;
+            }
+        }
+
+        Ok(last_result)
+    }
+
+    /// Meter execution against `budget` from now on, or stop metering with `None`. Usage starts
+    /// from zero; what the replaced budget had consumed is returned.
+    pub fn set_budget(
+        &mut self,
+        budget: Option<crate::runtime::metering::Budget>,
+    ) -> Option<crate::runtime::metering::Usage> {
+        let replaced = self.metering_usage();
+        self.meter = budget.map(|budget| Arc::new(crate::runtime::metering::Meter::new(budget)));
+        replaced
+    }
+
+    /// Units consumed since metering was turned on, when it is.
+    pub fn metering_usage(&self) -> Option<crate::runtime::metering::Usage> {
+        self.meter.as_ref().map(|meter| meter.usage())
+    }
+
+    /// Units the last decentralized service method run on `Budget::DECENTRALIZED` consumed.
+    pub fn last_method_usage(&self) -> Option<crate::runtime::metering::Usage> {
+        self.last_method_usage
+    }
+
+    /// Count one evaluation step at the current call depth when metered.
+    pub(crate) fn meter_step(&self) -> Result<(), RuntimeError> {
+        match &self.meter {
+            Some(meter) => meter.step(self.call_stack.len()),
+            None => Ok(()),
+        }
+    }
+
+    /// Count `items` newly allocated collection items when metered.
+    pub(crate) fn meter_allocation(&self, items: u64) -> Result<(), RuntimeError> {
+        match &self.meter {
+            Some(meter) => meter.allocate(items),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail with `ExecutionTimeout` once the running program has exceeded its time limit, and
+    /// with `TaskCancelled` once the task this runtime executes has been cancelled.
+    pub(crate) fn check_execution_timeout(&self) -> Result<(), RuntimeError> {
+        if let Some(start) = self.execution_start {
+            if start.elapsed() > std::time::Duration::from_secs(10) {
+                return Err(RuntimeError::ExecutionTimeout);
+            }
+        }
+        if let Some(task) = self.current_task.as_ref().filter(|t| t.is_cancelled()) {
+            return Err(RuntimeError::TaskCancelled(task.id));
+        }
+        Ok(())
+    }
+
+    /// Prepare `body` to run: its cached bytecode under the VM, a copy of the AST otherwise.
+    fn callable_body(&self, body: &BlockStatement, code: &CodeCache) -> CallableBody {
+        if self.bytecode_vm {
+            CallableBody::Bytecode(code.get_or_compile(body))
+        } else {
+            CallableBody::Tree(body.clone())
+        }
+    }
+
+    /// Run a function, method or closure body in the current scope. Its value is the `return`
+    /// value, the value a `?` propagated, or the value of the last statement run.
+    fn run_callable_body(&mut self, body: &CallableBody) -> Result<Value, RuntimeError> {
+        self.return_pending = None;
+        let result = match body {
+            CallableBody::Bytecode(chunk) => crate::runtime::vm::run(self, chunk),
+            CallableBody::Tree(block) => {
+                let mut result = Ok(Value::Null);
+                for (index, stmt) in block.statements.iter().enumerate() {
+                    result = self
+                        .trace_statement(stmt, block.span_at(index))
+                        .and_then(|_| self.execute_statement(stmt));
+                    if result.is_err() || self.return_pending.is_some() {
+                        break;
+                    }
+                }
+                result
+            }
+        };
+        self.return_pending = None;
+        match result {
+            Err(RuntimeError::Propagate(value)) => Ok(value),
+            other => other,
+        }
+    }
+
+    /// Execute a top-level statement, on the VM unless it is one the tree-walker runs anyway.
+    fn execute_top_level_statement(
+        &mut self,
+        statement: &Statement,
+    ) -> Result<Value, RuntimeError> {
+        if self.bytecode_vm {
+            if let Some(chunk) = bytecode::compile_statement(statement) {
+                return crate::runtime::vm::run(self, &chunk);
+            }
+        }
+        self.execute_statement(statement)
+    }
+
+    /// Record where a nested statement starts and let the debugger pause there.
+    fn trace_statement(
+        &mut self,
+        statement: &Statement,
+        span: Option<crate::parser::ast::Span>,
+    ) -> Result<(), RuntimeError> {
+        // `;` after an assignment parses as a null expression; it is not a stopping point.
+        if matches!(
+            statement,
+            Statement::Expression(crate::parser::ast::Expression::Literal(
+                crate::lexer::tokens::Literal::Null
+            ))
+        ) {
+            return Ok(());
+        }
+        self.trace_span(span)
+    }
+
+    /// Record `span` as the current location and give the debugger a chance to pause there.
+    pub(crate) fn trace_span(
+        &mut self,
+        span: Option<crate::parser::ast::Span>,
+    ) -> Result<(), RuntimeError> {
+        if let Some(span) = span {
+            let file_path = self
+                .current_location
+                .as_ref()
+                .and_then(|loc| loc.file_path.clone());
+            self.current_location = Some(SourceLocation {
+                line: span.line,
+                column: span.column,
+                file_path,
+            });
+        }
+        self.notify_debug_hook()
+    }
+
+    fn notify_debug_hook(&mut self) -> Result<(), RuntimeError> {
+        let Some(mut hook) = self.debug_hook.take() else {
+            return Ok(());
+        };
+        let paused_at = std::time::Instant::now();
+        let outcome = hook.on_statement(self);
+        // Time spent paused in the debugger does not count toward the execution timeout.
+        if let Some(start) = self.execution_start.as_mut() {
+            *start += paused_at.elapsed();
+        }
+        self.debug_hook = Some(hook);
+        outcome
+    }
+
+    /// Install a debugger that is called before every statement with a known location.
+    pub fn set_debug_hook(&mut self, hook: Box<dyn DebugHook>) {
+        self.debug_hook = Some(hook);
+    }
+
+    pub(crate) fn has_debug_hook(&self) -> bool {
+        self.debug_hook.is_some()
+    }
+
+    /// Remove and return the installed debugger, if any.
+    pub fn take_debug_hook(&mut self) -> Option<Box<dyn DebugHook>> {
+        self.debug_hook.take()
+    }
+
+    /// Location of the statement currently executing.
+    pub fn current_location(&self) -> Option<&SourceLocation> {
+        self.current_location.as_ref()
+    }
 
-        for statement in &program.statements {
-            match self.execute_statement(statement) {
-                Ok(value) => last_result = value,
-                Err(e) => return Err(e),
-            }
-            if self.return_pending.is_some() {
-                self.return_pending = None;
-                break;
-            }
+    /// Run `program` in the scope of call frame `frame` (0 = innermost) and return the value of
+    /// its last statement. Used by the debugger to evaluate expressions at a paused frame;
+    /// assignments are written back to that frame.
+    pub fn evaluate_in_frame(
+        &mut self,
+        program: &Program,
+        frame: usize,
+    ) -> Result<Value, RuntimeError> {
+        if frame > self.call_stack.len() {
+            return Err(RuntimeError::General(format!("No frame {}", frame)));
         }
-
-        Ok(last_result)
+        let saved_pending = self.return_pending.take();
+        let frame_index = self.call_stack.len() - frame;
+        if frame > 0 {
+            std::mem::swap(&mut self.scope, &mut self.call_stack[frame_index].scope);
+        }
+        let result = self.execute(program);
+        if frame > 0 {
+            std::mem::swap(&mut self.scope, &mut self.call_stack[frame_index].scope);
+        }
+        self.return_pending = saved_pending;
+        result
     }
 
     /// Execute a statement and return the result, handling control flow properly
-    fn execute_statement(
+    pub(crate) fn execute_statement(
         &mut self,
         statement: &crate::parser::ast::Statement,
     ) -> Result<Value, RuntimeError> {
@@ -4496,6 +5312,213 @@ impl Runtime {
         })
     }
 
+    /// Test `value` against a match pattern. Returns the variables the pattern binds when it
+    /// matches, or `None` when it does not.
+    fn match_pattern(
+        &mut self,
+        pattern: &crate::parser::ast::MatchPattern,
+        value: &Value,
+    ) -> Result<Option<Vec<(String, Value)>>, RuntimeError> {
+        use crate::parser::ast::MatchPattern;
+        match pattern {
+            MatchPattern::Literal(lit) => {
+                let case_value = self.literal_to_value(lit);
+                Ok(self.values_equal(value, &case_value).then(Vec::new))
+            }
+            // Always matches, binds the value
+            MatchPattern::Identifier(name) => Ok(Some(vec![(name.clone(), value.clone())])),
+            MatchPattern::Wildcard => Ok(Some(Vec::new())),
+            MatchPattern::Range(start_expr, end_expr) => {
+                let start = self.evaluate_expression(start_expr)?;
+                let end = self.evaluate_expression(end_expr)?;
+                // Check if value is in range (for numeric types)
+                let in_range = if let (Value::Int(mv), Value::Int(sv), Value::Int(ev)) =
+                    (value, &start, &end)
+                {
+                    *mv >= *sv && *mv <= *ev
+                } else {
+                    false
+                };
+                Ok(in_range.then(Vec::new))
+            }
+            MatchPattern::EnumVariant {
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let Value::Enum(value_enum, value_variant, payload) = value else {
+                    return Ok(None);
+                };
+                if value_enum != enum_name || value_variant != variant {
+                    return Ok(None);
+                }
+                if !fields.is_empty() && fields.len() != payload.len() {
+                    return Err(RuntimeError::General(format!(
+                        "Pattern {}::{} expects {} field(s), but the value has {}",
+                        enum_name,
+                        variant,
+                        fields.len(),
+                        payload.len()
+                    )));
+                }
+                let mut bindings = Vec::new();
+                for (field_pattern, field_value) in fields.iter().zip(payload) {
+                    match self.match_pattern(field_pattern, field_value)? {
+                        Some(inner) => bindings.extend(inner),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(bindings))
+            }
+            MatchPattern::List { elements, rest } => {
+                let (Value::List(items) | Value::Array(items)) = value else {
+                    return Ok(None);
+                };
+                let fits = match rest {
+                    Some(_) => items.len() >= elements.len(),
+                    None => items.len() == elements.len(),
+                };
+                if !fits {
+                    return Ok(None);
+                }
+                let mut bindings = Vec::new();
+                for (element_pattern, item) in elements.iter().zip(items) {
+                    match self.match_pattern(element_pattern, item)? {
+                        Some(inner) => bindings.extend(inner),
+                        None => return Ok(None),
+                    }
+                }
+                if let Some(rest) = rest.as_ref().filter(|r| r.as_str() != "_") {
+                    bindings.push((rest.clone(), Value::List(items[elements.len()..].to_vec())));
+                }
+                Ok(Some(bindings))
+            }
+            MatchPattern::Map {
+                struct_name,
+                fields,
+            } => {
+                let entries = match (value, struct_name) {
+                    (Value::Map(entries), None) => entries,
+                    (Value::Struct(name, entries), expected)
+                        if expected.as_ref().is_none_or(|expected| expected == name) =>
+                    {
+                        entries
+                    }
+                    _ => return Ok(None),
+                };
+                let mut bindings = Vec::new();
+                for (key, field_pattern) in fields {
+                    let Some(field_value) = entries.get(key) else {
+                        return Ok(None);
+                    };
+                    match self.match_pattern(field_pattern, field_value)? {
+                        Some(inner) => bindings.extend(inner),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(bindings))
+            }
+            MatchPattern::Ok(inner) => match value {
+                Value::Result(Ok(ok)) => self.match_pattern(inner, ok),
+                _ => Ok(None),
+            },
+            MatchPattern::Err(inner) => match value {
+                Value::Result(Err(err)) => self.match_pattern(inner, err),
+                _ => Ok(None),
+            },
+            MatchPattern::Some(inner) => match value {
+                Value::Option(Some(payload)) => self.match_pattern(inner, payload),
+                _ => Ok(None),
+            },
+            // `null` counts as `None` so optional map fields can be matched directly.
+            MatchPattern::None => {
+                Ok(matches!(value, Value::Option(None) | Value::Null).then(Vec::new))
+            }
+        }
+    }
+
+    /// Build a value for `Enum::Variant(args)` when `name` names a declared enum variant.
+    /// Returns `None` when `name` is not an enum variant (so callers fall through to namespaces).
+    pub(crate) fn construct_enum_variant(
+        &self,
+        name: &str,
+        args: &[Value],
+    ) -> Option<Result<Value, RuntimeError>> {
+        let (enum_name, variant_name) = name.split_once("::")?;
+        let variant = self
+            .enum_types
+            .get(enum_name)?
+            .variants
+            .iter()
+            .find(|v| v.name == variant_name)?;
+        if variant.fields.len() != args.len() {
+            return Some(Err(RuntimeError::ArgumentCountMismatch {
+                expected: variant.fields.len(),
+                got: args.len(),
+            }));
+        }
+        Some(Ok(Value::Enum(
+            enum_name.to_string(),
+            variant_name.to_string(),
+            args.to_vec(),
+        )))
+    }
+
+    /// Evaluate `Name { field: expr, ... }` against the declared struct's fields.
+    fn construct_struct(
+        &mut self,
+        name: &str,
+        fields: &HashMap<String, crate::parser::ast::Expression>,
+        depth: usize,
+    ) -> Result<Value, RuntimeError> {
+        let declaration = self
+            .struct_types
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RuntimeError::General(format!("Unknown struct '{}'", name)))?;
+        if let Some(unknown) = fields
+            .keys()
+            .find(|k| !declaration.fields.iter().any(|f| &f.name == *k))
+        {
+            return Err(RuntimeError::General(format!(
+                "Struct '{}' has no field '{}'",
+                name, unknown
+            )));
+        }
+        let mut values = HashMap::new();
+        for field in &declaration.fields {
+            let expr = fields.get(&field.name).ok_or_else(|| {
+                RuntimeError::General(format!(
+                    "Missing field '{}' in '{}' constructor",
+                    field.name, name
+                ))
+            })?;
+            let value = self.evaluate_expression_at_depth(expr, depth)?;
+            values.insert(field.name.clone(), value);
+        }
+        Ok(Value::Struct(name.to_string(), values))
+    }
+
+    /// Register a DAL function so call_function(name, args) can dispatch to it, along with the
+    /// functions nested in its body (parser may nest @route fn inside try/catch blocks).
+    pub(crate) fn declare_function(&mut self, func_stmt: &crate::parser::ast::FunctionStatement) {
+        let user_func = UserFunction {
+            name: func_stmt.name.clone(),
+            parameters: func_stmt
+                .parameters
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            body: func_stmt.body.clone(),
+            attributes: func_stmt.attributes.clone(),
+            exported: func_stmt.exported,
+            code: CodeCache::default(),
+        };
+        self.user_functions
+            .insert(func_stmt.name.clone(), user_func);
+        self.register_functions_from_block(&func_stmt.body);
+    }
+
     /// Register any nested functions from a block (parser may nest @route fn inside try/catch).
     fn register_functions_from_block(&mut self, block: &BlockStatement) {
         for stmt in &block.statements {
@@ -4509,6 +5532,7 @@ impl Runtime {
                         body: f.body.clone(),
                         attributes: f.attributes.clone(),
                         exported: f.exported,
+                        code: CodeCache::default(),
                     };
                     self.user_functions.insert(f.name.clone(), user_func);
                     self.register_functions_from_block(&f.body);
@@ -4542,7 +5566,7 @@ impl Runtime {
     }
 
     /// Internal method that properly handles control flow
-    fn execute_statement_internal(
+    pub(crate) fn execute_statement_internal(
         &mut self,
         statement: &crate::parser::ast::Statement,
     ) -> StatementResult {
@@ -4550,10 +5574,25 @@ impl Runtime {
 
         // Check timeout (using a thread-local or passed-in start time)
         // For now, we'll check in execute_program, but this could be enhanced
+        self.meter_step()?;
         match statement {
             crate::parser::ast::Statement::Let(let_stmt) => {
                 let evaluated_value = self.evaluate_expression(&let_stmt.value)?;
 
+                if let Some(pattern) = &let_stmt.pattern {
+                    let Some(bindings) = self.match_pattern(pattern, &evaluated_value)? else {
+                        return Err(RuntimeError::General(format!(
+                            "Cannot destructure a value of type '{}': it does not match the pattern",
+                            evaluated_value.type_name()
+                        )));
+                    };
+                    for (name, value) in bindings {
+                        self.advanced_security.verify_assignment(&name, &value)?;
+                        self.set_variable(name, value);
+                    }
+                    return Ok(StatementOutcome::value(evaluated_value));
+                }
+
                 // Phase 4: Apply formal verification to variable assignments
                 self.advanced_security
                     .verify_assignment(&let_stmt.name, &evaluated_value)?;
@@ -4576,7 +5615,8 @@ impl Runtime {
             }
             crate::parser::ast::Statement::Block(block_stmt) => {
                 let mut last_result = Value::Null;
-                for stmt in &block_stmt.statements {
+                for (index, stmt) in block_stmt.statements.iter().enumerate() {
+                    self.trace_statement(stmt, block_stmt.span_at(index))?;
                     match self.execute_statement_internal(stmt) {
                         Ok(StatementOutcome::Value(value)) => {
                             last_result = value;
@@ -4594,23 +5634,7 @@ impl Runtime {
                 Ok(StatementOutcome::value(last_result))
             }
             Statement::Function(func_stmt) => {
-                // Register top-level DAL function so call_function(name, args) can dispatch to it
-                let parameters: Vec<String> = func_stmt
-                    .parameters
-                    .iter()
-                    .map(|p| p.name.clone())
-                    .collect();
-                let user_func = UserFunction {
-                    name: func_stmt.name.clone(),
-                    parameters: parameters.clone(),
-                    body: func_stmt.body.clone(),
-                    attributes: func_stmt.attributes.clone(),
-                    exported: func_stmt.exported,
-                };
-                self.user_functions
-                    .insert(func_stmt.name.clone(), user_func);
-                // Also register nested functions (parser may nest @route fn inside try/catch blocks)
-                self.register_functions_from_block(&func_stmt.body);
+                self.declare_function(func_stmt);
                 Ok(StatementOutcome::value(Value::Null))
             }
             crate::parser::ast::Statement::Import(imp) => {
@@ -4654,6 +5678,7 @@ impl Runtime {
                                         ))
                                     })?;
                                 let mut mod_runtime = Runtime::new();
+                                mod_runtime.bytecode_vm = self.bytecode_vm;
                                 mod_runtime
                                     .execute_program(dep_program, Some(dep_resolved.as_slice()))
                                     .map_err(|e| {
@@ -4716,6 +5741,7 @@ impl Runtime {
                                         ))
                                     })?;
                                 let mut mod_runtime = Runtime::new();
+                                mod_runtime.bytecode_vm = self.bytecode_vm;
                                 mod_runtime
                                     .execute_program(dep_program, Some(dep_resolved.as_slice()))
                                     .map_err(|e| {
@@ -4737,6 +5763,28 @@ impl Runtime {
             crate::parser::ast::Statement::Service(service_stmt) => self
                 .execute_service_statement(service_stmt)
                 .map(|v| StatementOutcome::value(v)),
+            crate::parser::ast::Statement::Struct(struct_stmt) => {
+                self.struct_types
+                    .insert(struct_stmt.name.clone(), struct_stmt.clone());
+                Ok(StatementOutcome::value(Value::Null))
+            }
+            crate::parser::ast::Statement::Enum(enum_stmt) => {
+                self.enum_types
+                    .insert(enum_stmt.name.clone(), enum_stmt.clone());
+                Ok(StatementOutcome::value(Value::Null))
+            }
+            crate::parser::ast::Statement::Trait(trait_stmt) => {
+                self.trait_types
+                    .insert(trait_stmt.name.clone(), trait_stmt.clone());
+                self.apply_pending_impls()?;
+                Ok(StatementOutcome::value(Value::Null))
+            }
+            crate::parser::ast::Statement::Impl(impl_stmt) => {
+                self.pending_impls
+                    .push((impl_stmt.clone(), self.current_location.clone()));
+                self.apply_pending_impls()?;
+                Ok(StatementOutcome::value(Value::Null))
+            }
             crate::parser::ast::Statement::If(if_stmt) => {
                 let condition = self.evaluate_expression(&if_stmt.condition)?;
                 if self.is_truthy(&condition) {
@@ -4752,25 +5800,24 @@ impl Runtime {
                 }
             }
             crate::parser::ast::Statement::While(while_stmt) => {
-                use std::time::Duration;
-                const MAX_EXECUTION_TIME: Duration = Duration::from_secs(10);
                 let mut last_result = Value::Null;
                 loop {
                     // Check timeout each iteration to prevent infinite-loop DoS (e.g. while(true){})
-                    if let Some(start) = self.execution_start {
-                        if start.elapsed() > MAX_EXECUTION_TIME {
-                            return Err(RuntimeError::ExecutionTimeout);
-                        }
-                    }
+                    self.check_execution_timeout()?;
                     let condition = self.evaluate_expression(&while_stmt.condition)?;
                     if !self.is_truthy(&condition) {
                         break;
                     }
                     // Execute body statements
-                    for stmt in &while_stmt.body.statements {
+                    for (index, stmt) in while_stmt.body.statements.iter().enumerate() {
+                        self.trace_statement(stmt, while_stmt.body.span_at(index))?;
                         match self.execute_statement_internal(stmt) {
                             Ok(StatementOutcome::Value(value)) => {
                                 last_result = value;
+                                // `return` inside the body ends the loop with the returned value.
+                                if self.return_pending.is_some() {
+                                    return Ok(StatementOutcome::value(last_result));
+                                }
                             }
                             Ok(StatementOutcome::ControlFlow(ControlFlow::Break(break_val))) => {
                                 // Break out of loop, return break value if present
@@ -4818,10 +5865,13 @@ impl Runtime {
                         }
                     }
                     // Execute body statements
-                    for stmt in &loop_stmt.body.statements {
+                    for (index, stmt) in loop_stmt.body.statements.iter().enumerate() {
+                        self.trace_statement(stmt, loop_stmt.body.span_at(index))?;
                         match self.execute_statement_internal(stmt) {
-                            Ok(StatementOutcome::Value(_value)) => {
-                                // Store last result but continue loop
+                            Ok(StatementOutcome::Value(value)) => {
+                                if self.return_pending.is_some() {
+                                    return Ok(StatementOutcome::value(value));
+                                }
                             }
                             Ok(StatementOutcome::ControlFlow(ControlFlow::Break(break_val))) => {
                                 // Break out of loop, return break value if present
@@ -4849,47 +5899,44 @@ impl Runtime {
 
                 // Try each case in order
                 for case in &match_stmt.cases {
-                    let matches = match &case.pattern {
-                        crate::parser::ast::MatchPattern::Literal(lit) => {
-                            let case_value = self.literal_to_value(lit);
-                            self.values_equal(&match_value, &case_value)
-                        }
-                        crate::parser::ast::MatchPattern::Identifier(_) => {
-                            // Always matches, binds the value
-                            true
-                        }
-                        crate::parser::ast::MatchPattern::Wildcard => true,
-                        crate::parser::ast::MatchPattern::Range(start_expr, end_expr) => {
-                            let start = self.evaluate_expression(start_expr)?;
-                            let end = self.evaluate_expression(end_expr)?;
-                            // Check if match_value is in range (for numeric types)
-                            if let (Value::Int(mv), Value::Int(sv), Value::Int(ev)) =
-                                (&match_value, &start, &end)
-                            {
-                                *mv >= *sv && *mv <= *ev
-                            } else {
-                                false
-                            }
+                    if let Some(bindings) = self.match_pattern(&case.pattern, &match_value)? {
+                        // Bind pattern variables, remembering any outer values they shadow
+                        let mut shadowed = Vec::with_capacity(bindings.len());
+                        for (name, value) in bindings {
+                            shadowed.push((name.clone(), self.scope.variables.get(&name).cloned()));
+                            self.scope.set(name, value);
                         }
-                    };
 
-                    if matches {
-                        // Bind identifier if present (create new scope for match case)
-                        let original_scope = self.scope.clone();
-                        if let crate::parser::ast::MatchPattern::Identifier(ref name) = case.pattern
-                        {
-                            self.scope.set(name.clone(), match_value.clone());
-                        }
+                        // The guard sees the bindings; a falsy guard moves on to the next case
+                        let guard_passed = match &case.guard {
+                            Some(guard) => {
+                                self.evaluate_expression(guard).map(|v| self.is_truthy(&v))
+                            }
+                            None => Ok(true),
+                        };
 
                         // Execute case body
-                        let result = self.execute_statement_internal(
-                            &crate::parser::ast::Statement::Block(case.body.clone()),
-                        );
+                        let result = match guard_passed {
+                            Ok(true) => Some(self.execute_statement_internal(
+                                &crate::parser::ast::Statement::Block(case.body.clone()),
+                            )),
+                            Ok(false) => None,
+                            Err(e) => Some(Err(e)),
+                        };
 
-                        // Restore scope after match case (pattern bindings are scoped to case)
-                        self.scope = original_scope;
+                        // Pattern bindings are scoped to the case; other assignments persist
+                        for (name, previous) in shadowed.into_iter().rev() {
+                            match previous {
+                                Some(value) => self.scope.set(name, value),
+                                None => {
+                                    self.scope.variables.remove(&name);
+                                }
+                            }
+                        }
 
-                        return result;
+                        if let Some(result) = result {
+                            return result;
+                        }
                     }
                 }
 
@@ -4927,58 +5974,34 @@ impl Runtime {
                         Ok(StatementOutcome::ControlFlow(cf))
                     }
                     Err(error) => {
-                        // Try to find a matching catch block.
-                        // Execute on `self` so catch bodies have access to all
-                        // functions, user_functions, services, etc. — a previous
-                        // implementation created a bare Runtime which wiped out
-                        // every builtin and user function, making any function
-                        // call inside catch blocks fail with FunctionNotFound.
-                        for catch_block in &try_stmt.catch_blocks {
-                            let saved_scope = self.scope.clone();
-
-                            if let Some(error_var) = &catch_block.error_variable {
-                                self.scope
-                                    .set(error_var.clone(), Value::String(format!("{:?}", error)));
-                            }
-
-                            match self.execute_statement_internal(
-                                &crate::parser::ast::Statement::Block(catch_block.body.clone()),
-                            ) {
-                                Ok(StatementOutcome::Value(result)) => {
-                                    if let Some(finally_block) = &try_stmt.finally_block {
-                                        let _ = self.execute_statement_internal(
-                                            &crate::parser::ast::Statement::Block(
-                                                finally_block.clone(),
-                                            ),
-                                        );
-                                    }
-                                    return Ok(StatementOutcome::value(result));
-                                }
-                                Ok(StatementOutcome::ControlFlow(cf)) => {
-                                    if let Some(finally_block) = &try_stmt.finally_block {
-                                        let _ = self.execute_statement_internal(
-                                            &crate::parser::ast::Statement::Block(
-                                                finally_block.clone(),
-                                            ),
-                                        );
-                                    }
-                                    return Ok(StatementOutcome::ControlFlow(cf));
-                                }
-                                Err(_) => {
-                                    self.scope = saved_scope;
-                                    continue;
+                        // Run the first catch block whose type matches (an untyped catch matches
+                        // everything but `?` unwinding). Execute on `self` so catch bodies have
+                        // access to all functions, user_functions, services, etc. — a previous
+                        // implementation created a bare Runtime which wiped out every builtin
+                        // and user function, making any function call inside catch blocks fail
+                        // with FunctionNotFound.
+                        let handler = try_stmt.catch_blocks.iter().find(|c| match &c.error_type {
+                            Some(type_name) => error.matches_catch(type_name),
+                            None => !matches!(error, RuntimeError::Propagate(_)),
+                        });
+                        let outcome = match handler {
+                            Some(catch_block) => {
+                                if let Some(error_var) = &catch_block.error_variable {
+                                    self.scope.set(error_var.clone(), error.caught_value());
                                 }
+                                self.execute_statement_internal(
+                                    &crate::parser::ast::Statement::Block(catch_block.body.clone()),
+                                )
                             }
-                        }
+                            None => Err(error),
+                        };
 
-                        // If no catch block handled the error, execute finally and re-throw
                         if let Some(finally_block) = &try_stmt.finally_block {
                             let _ = self.execute_statement_internal(
                                 &crate::parser::ast::Statement::Block(finally_block.clone()),
                             );
                         }
-
-                        Err(error)
+                        outcome
                     }
                 }
             }
@@ -4989,64 +6012,40 @@ impl Runtime {
                 .execute_agent_statement(agent_stmt)
                 .map(|v| StatementOutcome::value(v)),
             crate::parser::ast::Statement::Message(msg_stmt) => {
-                // Evaluate message data
                 let mut data = HashMap::new();
                 for (key, expr) in &msg_stmt.data {
                     data.insert(key.clone(), self.evaluate_expression(expr)?);
                 }
-
-                // For now, just return the message data as a string
-                Ok(StatementOutcome::value(Value::String(format!(
-                    "Message to {}: {:?}",
-                    msg_stmt.recipient, data
-                ))))
+                self.deliver_message(&msg_stmt.recipient, data)
+                    .map(StatementOutcome::value)
             }
             crate::parser::ast::Statement::Event(event_stmt) => {
-                // Evaluate event data
                 let mut data = HashMap::new();
                 for (key, expr) in &event_stmt.data {
                     data.insert(key.clone(), self.evaluate_expression(expr)?);
                 }
-
-                // For now, just return the event data as a string
-                Ok(StatementOutcome::value(Value::String(format!(
-                    "Event {}: {:?}",
-                    event_stmt.event_name, data
-                ))))
+                self.publish_event(&event_stmt.event_name, data)
+                    .map(StatementOutcome::value)
             }
             crate::parser::ast::Statement::ForIn(for_in_stmt) => {
                 let iterable = self.evaluate_expression(&for_in_stmt.iterable)?;
-                let items: Vec<crate::runtime::values::Value> = match &iterable {
-                    crate::runtime::values::Value::List(list) => list.clone(),
-                    crate::runtime::values::Value::Array(arr) => arr.clone(),
-                    crate::runtime::values::Value::Map(map) => map
-                        .keys()
-                        .cloned()
-                        .map(crate::runtime::values::Value::String)
-                        .collect(),
-                    other => {
-                        return Err(RuntimeError::General(format!(
-                            "for-in requires list, array, or map; got {}",
-                            other.type_name()
-                        )));
-                    }
-                };
+                let mut items = Self::iteration_source(iterable)?;
                 let mut last_result = crate::runtime::values::Value::Null;
-                for item in items {
-                    if let Some(start) = self.execution_start {
-                        if start.elapsed() > std::time::Duration::from_secs(10) {
-                            return Err(RuntimeError::ExecutionTimeout);
-                        }
-                    }
+                while let Some(item) = self.next_iteration_item(&mut items)? {
+                    self.check_execution_timeout()?;
                     // Set loop variable
                     self.set_variable(for_in_stmt.variable.clone(), item.clone());
 
                     // Execute body statements
                     let mut should_continue = false;
-                    for stmt in &for_in_stmt.body.statements {
+                    for (index, stmt) in for_in_stmt.body.statements.iter().enumerate() {
+                        self.trace_statement(stmt, for_in_stmt.body.span_at(index))?;
                         match self.execute_statement_internal(stmt) {
                             Ok(StatementOutcome::Value(value)) => {
                                 last_result = value;
+                                if self.return_pending.is_some() {
+                                    return Ok(StatementOutcome::value(last_result));
+                                }
                             }
                             Ok(StatementOutcome::ControlFlow(ControlFlow::Break(break_val))) => {
                                 // Break out of loop, return break value if present
@@ -5076,14 +6075,14 @@ impl Runtime {
         }
     }
 
-    fn evaluate_expression(
+    pub(crate) fn evaluate_expression(
         &mut self,
         expression: &crate::parser::ast::Expression,
     ) -> Result<Value, RuntimeError> {
         self.evaluate_expression_at_depth(expression, 0)
     }
 
-    const MAX_EVAL_DEPTH: usize = 128;
+    pub(crate) const MAX_EVAL_DEPTH: usize = 128;
 
     fn evaluate_expression_at_depth(
         &mut self,
@@ -5097,20 +6096,11 @@ impl Runtime {
             )));
         }
         // Check timeout periodically to prevent long &&/|| chains from DoS
-        if let Some(start) = self.execution_start {
-            if start.elapsed() > std::time::Duration::from_secs(10) {
-                return Err(RuntimeError::ExecutionTimeout);
-            }
-        }
+        self.check_execution_timeout()?;
+        self.meter_step()?;
         let depth = depth + 1;
         match expression {
-            crate::parser::ast::Expression::Literal(literal) => match literal {
-                crate::lexer::tokens::Literal::Int(n) => Ok(Value::Int(*n)),
-                crate::lexer::tokens::Literal::Float(f) => Ok(Value::Float(*f)),
-                crate::lexer::tokens::Literal::String(s) => Ok(Value::String(s.clone())),
-                crate::lexer::tokens::Literal::Bool(b) => Ok(Value::Bool(*b)),
-                crate::lexer::tokens::Literal::Null => Ok(Value::Null),
-            },
+            crate::parser::ast::Expression::Literal(literal) => Ok(self.literal_to_value(literal)),
             crate::parser::ast::Expression::Identifier(name) => {
                 // Check if this is 'self' - if so, return the instance ID
                 if name == "self" {
@@ -5121,8 +6111,10 @@ impl Runtime {
                     } else {
                         Ok(self_id)
                     }
+                } else if let Some(variant) = self.construct_enum_variant(name, &[]) {
+                    variant
                 } else {
-                    self.get_variable(name)
+                    self.resolve_identifier(name)
                 }
             }
             crate::parser::ast::Expression::BinaryOp(left, operator, right) => {
@@ -5148,59 +6140,13 @@ impl Runtime {
                     _ => {
                         let left_val = self.evaluate_expression_at_depth(left, depth)?;
                         let right_val = self.evaluate_expression_at_depth(right, depth)?;
-                        match operator {
-                            crate::lexer::tokens::Operator::Plus => {
-                                self.add_values(left_val, right_val)
-                            }
-                            crate::lexer::tokens::Operator::Minus => {
-                                self.subtract_values(left_val, right_val)
-                            }
-                            crate::lexer::tokens::Operator::Star => {
-                                self.multiply_values(left_val, right_val)
-                            }
-                            crate::lexer::tokens::Operator::Slash => {
-                                self.divide_values(left_val, right_val)
-                            }
-                            crate::lexer::tokens::Operator::Percent => {
-                                self.modulo_values(left_val, right_val)
-                            }
-                            crate::lexer::tokens::Operator::Equal => {
-                                Ok(Value::Bool(left_val == right_val))
-                            }
-                            crate::lexer::tokens::Operator::NotEqual => {
-                                Ok(Value::Bool(left_val != right_val))
-                            }
-                            crate::lexer::tokens::Operator::Less => {
-                                self.compare_values(left_val, right_val, "<")
-                            }
-                            crate::lexer::tokens::Operator::LessEqual => {
-                                self.compare_values(left_val, right_val, "<=")
-                            }
-                            crate::lexer::tokens::Operator::Greater => {
-                                self.compare_values(left_val, right_val, ">")
-                            }
-                            crate::lexer::tokens::Operator::GreaterEqual => {
-                                self.compare_values(left_val, right_val, ">=")
-                            }
-                            _ => Err(RuntimeError::UnsupportedOperation(format!(
-                                "{:?}",
-                                operator
-                            ))),
-                        }
+                        self.binary_op(operator, left_val, right_val)
                     }
                 }
             }
             crate::parser::ast::Expression::UnaryOp(operator, operand) => {
                 let operand_val = self.evaluate_expression_at_depth(operand, depth)?;
-
-                match operator {
-                    crate::lexer::tokens::Operator::Minus => self.negate_value(operand_val),
-                    crate::lexer::tokens::Operator::Not => self.logical_not(operand_val),
-                    _ => Err(RuntimeError::UnsupportedOperation(format!(
-                        "{:?}",
-                        operator
-                    ))),
-                }
+                self.unary_op(operator, operand_val)
             }
             crate::parser::ast::Expression::Assignment(name, value) => {
                 let evaluated_value = self.evaluate_expression_at_depth(value, depth)?;
@@ -5215,55 +6161,9 @@ impl Runtime {
                 // Evaluate the value to assign
                 let value = self.evaluate_expression_at_depth(value_expr, depth)?;
 
-                // Handle 'self.field = value' for service instances
                 match object_expr.as_ref() {
-                    crate::parser::ast::Expression::Identifier(var_name) if var_name == "self" => {
-                        // Get the instance ID from 'self'
-                        let self_id = self.get_variable("self")?;
-                        if let Value::String(instance_id) = self_id {
-                            // Update the field in the service instance
-                            if let Some(instance) = self.services.get_mut(&instance_id) {
-                                instance.fields.insert(field_name.clone(), value.clone());
-                                Ok(value)
-                            } else {
-                                Err(RuntimeError::General(format!(
-                                    "Service instance '{}' not found",
-                                    instance_id
-                                )))
-                            }
-                        } else {
-                            Err(RuntimeError::General(format!(
-                                "'self' is not a service instance reference"
-                            )))
-                        }
-                    }
                     crate::parser::ast::Expression::Identifier(var_name) => {
-                        // Get the current object from variables
-                        match self.get_variable(var_name) {
-                            Ok(mut current_value) => {
-                                // Update the field in the object
-                                if current_value.struct_set_field(field_name.clone(), value.clone())
-                                {
-                                    // Store the updated object back to the variable
-                                    self.set_variable(var_name.clone(), current_value);
-                                    Ok(value)
-                                } else {
-                                    Err(RuntimeError::General(format!(
-                                        "Cannot assign to field '{}' on variable '{}' of type '{}'",
-                                        field_name,
-                                        var_name,
-                                        match self.get_variable(var_name) {
-                                            Ok(v) => v.type_name().to_string(),
-                                            Err(_) => "unknown".to_string(),
-                                        }
-                                    )))
-                                }
-                            }
-                            Err(_) => Err(RuntimeError::General(format!(
-                                "Variable '{}' not found for field assignment",
-                                var_name
-                            ))),
-                        }
+                        self.assign_field(var_name, field_name, value)
                     }
                     _ => Err(RuntimeError::General(format!(
                         "Field assignment only supported for variables, got: {:?}",
@@ -5282,37 +6182,13 @@ impl Runtime {
                 }
                 let storage_opt = self.receiver_storage(receiver);
                 let mut receiver_val = self.evaluate_expression_at_depth(receiver, depth)?;
-                // Service method dispatch
-                if let Value::String(ref instance_id) = receiver_val {
-                    if self.services.contains_key(instance_id) {
-                        let method = self
-                            .services
-                            .get(instance_id)
-                            .and_then(|inst| {
-                                inst.methods
-                                    .iter()
-                                    .find(|m| m.name == *method_name)
-                                    .cloned()
-                            })
-                            .ok_or_else(|| {
-                                RuntimeError::General(format!(
-                                    "Method '{}' not found on service instance '{}'",
-                                    method_name, instance_id
-                                ))
-                            })?;
-                        return self.execute_service_method(
-                            instance_id,
-                            method_name,
-                            &method,
-                            &args,
-                        );
+                let (result, write_back) =
+                    self.invoke_method(&mut receiver_val, method_name, &args)?;
+                if write_back {
+                    if let Some(storage) = storage_opt {
+                        self.write_back_receiver(storage, receiver_val)?;
                     }
                 }
-                // Value method dispatch (list/map/set/struct)
-                let result = self.call_value_method(&mut receiver_val, method_name, &args)?;
-                if let Some(storage) = storage_opt {
-                    self.write_back_receiver(storage, receiver_val)?;
-                }
                 Ok(result)
             }
             crate::parser::ast::Expression::FunctionCall(call) => {
@@ -5322,37 +6198,50 @@ impl Runtime {
                 }
                 self.call_function(&call.name, &args)
             }
-            crate::parser::ast::Expression::Await(expr) => {
-                // For now, just evaluate the expression normally
-                // In a real implementation, this would handle async/await
-                self.evaluate_expression_at_depth(expr, depth)
-            }
+            crate::parser::ast::Expression::Await(expr) => self.evaluate_await(expr, depth),
             crate::parser::ast::Expression::Spawn(expr) => {
-                // Run expression in a background thread and return a handle; use sync::join(handle) to get the result
-                self.spawn_counter = self.spawn_counter.wrapping_add(1);
-                let task_id = format!("spawn_{}", self.spawn_counter);
-                let (tx, rx) = mpsc::channel();
-                let expr = expr.clone();
+                // Queue the expression on the task pool and return its handle for `await`.
+                let expr = (**expr).clone();
                 let user_functions = self.user_functions.clone();
                 let services = self.services.clone();
                 let scope = self.scope.clone();
-                std::thread::spawn(move || {
+                let closure_registry = self.closure_registry.clone();
+                let struct_types = self.struct_types.clone();
+                let enum_types = self.enum_types.clone();
+                let bytecode_vm = self.bytecode_vm;
+                let meter = self.meter.clone();
+                let job: crate::runtime::tasks::Job = Box::new(move |task| {
                     let mut rt = Runtime::new();
+                    rt.bytecode_vm = bytecode_vm;
+                    rt.meter = meter;
                     rt.user_functions = user_functions;
                     rt.services = services;
                     rt.scope = scope;
-                    let result = rt.evaluate_expression(&expr);
-                    let _ = tx.send(result);
+                    rt.closure_registry = closure_registry;
+                    rt.struct_types = struct_types;
+                    rt.enum_types = enum_types;
+                    rt.current_task = Some(Arc::clone(task));
+                    rt.evaluate_expression(&expr)
                 });
-                self.pending_spawns.insert(task_id.clone(), rx);
-                Ok(Value::String(task_id))
+                let id = crate::runtime::tasks::spawn(job, self.current_task.as_ref());
+                if let Some(scope) = self.task_scopes.last_mut() {
+                    scope.tasks.push(id);
+                }
+                Ok(Value::Task(id))
             }
             crate::parser::ast::Expression::Throw(expr) => {
                 let error_value = self.evaluate_expression_at_depth(expr, depth)?;
-                Err(RuntimeError::General(format!(
-                    "Thrown error: {}",
-                    error_value
-                )))
+                Err(RuntimeError::Thrown(error_value))
+            }
+            crate::parser::ast::Expression::Propagate(expr) => {
+                match self.evaluate_expression_at_depth(expr, depth)? {
+                    Value::Result(Ok(ok)) => Ok(*ok),
+                    Value::Option(Some(payload)) => Ok(*payload),
+                    failed @ (Value::Result(..) | Value::Option(None) | Value::Null) => {
+                        Err(RuntimeError::Propagate(failed))
+                    }
+                    other => Ok(other),
+                }
             }
             crate::parser::ast::Expression::IndexAccess(container, index_expr) => {
                 let container_val = self.evaluate_expression_at_depth(container, depth)?;
@@ -5363,37 +6252,69 @@ impl Runtime {
                 // Evaluate the object expression to get the object
                 let object_value = self.evaluate_expression_at_depth(object_expr, depth)?;
 
-                // Handle 'self.field' access for service instances
-                if let Value::String(ref instance_id) = object_value {
-                    if let Some(instance) = self.services.get(instance_id) {
-                        // Access field from service instance
-                        return instance.fields.get(field_name).cloned().ok_or_else(|| {
-                            RuntimeError::General(format!(
-                                "Field '{}' not found on service instance '{}'",
-                                field_name, instance_id
-                            ))
-                        });
+                self.field_value(object_value, field_name)
+            }
+            crate::parser::ast::Expression::OptionalFieldAccess(object_expr, field_name) => {
+                let object_value = self.evaluate_expression_at_depth(object_expr, depth)?;
+                match propagate_wrapped_receiver(object_value)? {
+                    Value::Null => Ok(Value::Null),
+                    Value::Map(map) => Ok(map.get(field_name).cloned().unwrap_or(Value::Null)),
+                    object_value => self.field_value(object_value, field_name),
+                }
+            }
+            crate::parser::ast::Expression::OptionalMethodCall {
+                receiver,
+                method_name,
+                arguments,
+            } => {
+                let mut receiver_val = self.evaluate_expression_at_depth(receiver, depth)?;
+                // An unwrapped Ok / Some payload is a copy, so it is not written back.
+                let storage_opt = match receiver_val {
+                    Value::Result(_) | Value::Option(_) => None,
+                    _ => self.receiver_storage(receiver),
+                };
+                receiver_val = propagate_wrapped_receiver(receiver_val)?;
+                if receiver_val == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let mut args = Vec::new();
+                for arg in arguments {
+                    args.push(self.evaluate_expression_at_depth(arg, depth)?);
+                }
+                let (result, write_back) =
+                    self.invoke_method(&mut receiver_val, method_name, &args)?;
+                if write_back {
+                    if let Some(storage) = storage_opt {
+                        self.write_back_receiver(storage, receiver_val)?;
                     }
                 }
-
-                // Get the field value directly from the object
-                match object_value {
-                    Value::Struct(_, ref fields) => {
-                        fields.get(field_name).cloned().ok_or_else(|| {
-                            RuntimeError::General(format!(
-                                "Field '{}' not found on struct",
-                                field_name
-                            ))
-                        })
+                Ok(result)
+            }
+            crate::parser::ast::Expression::NullCoalesce(left, right) => {
+                match self.evaluate_expression_at_depth(left, depth)? {
+                    Value::Null | Value::Option(None) => {
+                        self.evaluate_expression_at_depth(right, depth)
+                    }
+                    value => Ok(value),
+                }
+            }
+            crate::parser::ast::Expression::Pipeline(value_expr, stage) => {
+                let value = self.evaluate_expression_at_depth(value_expr, depth)?;
+                match stage.as_ref() {
+                    crate::parser::ast::Expression::FunctionCall(call) => {
+                        let mut args = vec![value];
+                        for arg in &call.arguments {
+                            args.push(self.evaluate_expression_at_depth(arg, depth)?);
+                        }
+                        self.call_function(&call.name, &args)
+                    }
+                    crate::parser::ast::Expression::Identifier(name) => {
+                        self.call_function(name, &[value])
+                    }
+                    callee => {
+                        let callee = self.evaluate_expression_at_depth(callee, depth)?;
+                        self.call_value(&callee, &[value])
                     }
-                    Value::Map(ref map) => map.get(field_name).cloned().ok_or_else(|| {
-                        RuntimeError::General(format!("Field '{}' not found in map", field_name))
-                    }),
-                    _ => Err(RuntimeError::General(format!(
-                        "Cannot access field '{}' on value of type '{}'",
-                        field_name,
-                        object_value.type_name()
-                    ))),
                 }
             }
             crate::parser::ast::Expression::ObjectLiteral(properties) => {
@@ -5402,64 +6323,56 @@ impl Runtime {
                     let value = self.evaluate_expression_at_depth(expr, depth)?;
                     object_value.insert(key.clone(), value);
                 }
+                self.meter_allocation(object_value.len() as u64)?;
                 Ok(Value::Map(object_value))
             }
+            crate::parser::ast::Expression::Interpolation(parts) => {
+                // Each part displays as it would after `"" +`.
+                let mut text = String::new();
+                for part in parts {
+                    match self.evaluate_expression_at_depth(part, depth)? {
+                        Value::String(s) => text.push_str(&s),
+                        other => text.push_str(&other.to_string()),
+                    }
+                }
+                Ok(Value::String(text))
+            }
             crate::parser::ast::Expression::ArrayLiteral(elements) => {
                 let mut array_value = Vec::new();
                 for expr in elements {
                     let value = self.evaluate_expression_at_depth(expr, depth)?;
-                    array_value.push(value);
-                }
-                Ok(Value::Array(array_value))
-            }
-            crate::parser::ast::Expression::Range(start_expr, end_expr) => {
-                const MAX_RANGE_LEN: i64 = 100_000;
-
-                // Evaluate range bounds and create a list of integers
-                let start = self.evaluate_expression_at_depth(start_expr, depth)?;
-                let end = self.evaluate_expression_at_depth(end_expr, depth)?;
-
-                let start_int = match start {
-                    Value::Int(n) => n,
-                    _ => {
-                        return Err(RuntimeError::TypeError {
-                            expected: "int".to_string(),
-                            got: start.type_name().to_string(),
-                        })
-                    }
-                };
-
-                let end_int = match end {
-                    Value::Int(n) => n,
-                    _ => {
-                        return Err(RuntimeError::TypeError {
-                            expected: "int".to_string(),
-                            got: end.type_name().to_string(),
-                        })
-                    }
-                };
-
-                let len = end_int.saturating_sub(start_int);
-                if len > MAX_RANGE_LEN {
-                    return Err(RuntimeError::General(format!(
-                        "Range too large: {}..{} exceeds limit of {} elements",
-                        start_int, end_int, MAX_RANGE_LEN
-                    )));
+                    array_value.push(value);
                 }
-
-                // Create range: start..end (exclusive end, like Rust)
-                let range_vec: Vec<Value> = (start_int..end_int).map(Value::Int).collect();
-
-                Ok(Value::List(range_vec))
+                self.meter_allocation(array_value.len() as u64)?;
+                Ok(Value::Array(array_value))
+            }
+            crate::parser::ast::Expression::Range(start_expr, end_expr) => {
+                let start = self.evaluate_expression_at_depth(start_expr, depth)?;
+                let end = self.evaluate_expression_at_depth(end_expr, depth)?;
+                let range = Self::range_value(start, end)?;
+                self.meter_allocation(collection_items(&range))?;
+                Ok(range)
             }
-            crate::parser::ast::Expression::ArrowFunction { param, body } => {
+            crate::parser::ast::Expression::StructLiteral { name, fields } => {
+                self.construct_struct(name, fields, depth)
+            }
+            crate::parser::ast::Expression::Call { callee, arguments } => {
+                let callee = self.evaluate_expression_at_depth(callee, depth)?;
+                let mut args = Vec::with_capacity(arguments.len());
+                for arg in arguments {
+                    args.push(self.evaluate_expression_at_depth(arg, depth)?);
+                }
+                self.call_value(&callee, &args)
+            }
+            crate::parser::ast::Expression::ArrowFunction { params, body } => {
                 // Capture current scope and register closure; call via variable holding Value::Closure(id)
                 self.closure_counter = self.closure_counter.wrapping_add(1);
                 let closure_id = format!("closure_{}", self.closure_counter);
                 let entry = ClosureEntry {
-                    param: param.clone(),
+                    params: params.clone(),
                     body: body.clone(),
                     captured_scope: self.scope.clone(),
+                    code: CodeCache::default(),
                 };
                 self.closure_registry.insert(closure_id.clone(), entry);
                 Ok(Value::Closure(closure_id))
@@ -5467,9 +6380,222 @@ impl Runtime {
         }
     }
 
+    /// `object.field`: a service instance field (object is the instance id), struct field or map entry.
+    pub(crate) fn field_value(
+        &self,
+        object_value: Value,
+        field_name: &str,
+    ) -> Result<Value, RuntimeError> {
+        // Handle 'self.field' access for service instances
+        if let Value::String(ref instance_id) = object_value {
+            if let Some(instance) = self.services.get(instance_id) {
+                // Access field from service instance
+                return instance.fields.get(field_name).cloned().ok_or_else(|| {
+                    RuntimeError::General(format!(
+                        "Field '{}' not found on service instance '{}'",
+                        field_name, instance_id
+                    ))
+                });
+            }
+        }
+
+        // Get the field value directly from the object
+        match object_value {
+            Value::Struct(_, ref fields) => fields.get(field_name).cloned().ok_or_else(|| {
+                RuntimeError::General(format!("Field '{}' not found on struct", field_name))
+            }),
+            Value::Map(ref map) => map.get(field_name).cloned().ok_or_else(|| {
+                RuntimeError::General(format!("Field '{}' not found in map", field_name))
+            }),
+            _ => Err(RuntimeError::General(format!(
+                "Cannot access field '{}' on value of type '{}'",
+                field_name,
+                object_value.type_name()
+            ))),
+        }
+    }
+
+    /// `var_name.field_name = value`: a service instance field when `var_name` is `self`,
+    /// otherwise a struct or map field of the variable.
+    pub(crate) fn assign_field(
+        &mut self,
+        var_name: &str,
+        field_name: &str,
+        value: Value,
+    ) -> Result<Value, RuntimeError> {
+        if var_name == "self" {
+            // Get the instance ID from 'self'
+            let self_id = self.get_variable("self")?;
+            return if let Value::String(instance_id) = self_id {
+                // Update the field in the service instance
+                if let Some(instance) = self.services.get_mut(&instance_id) {
+                    instance
+                        .fields
+                        .insert(field_name.to_string(), value.clone());
+                    self.forget_cached_methods(&instance_id);
+                    Ok(value)
+                } else {
+                    Err(RuntimeError::General(format!(
+                        "Service instance '{}' not found",
+                        instance_id
+                    )))
+                }
+            } else {
+                Err(RuntimeError::General(
+                    "'self' is not a service instance reference".to_string(),
+                ))
+            };
+        }
+        // Get the current object from variables
+        match self.get_variable(var_name) {
+            Ok(mut current_value) => {
+                // Update the field in the object
+                if current_value.struct_set_field(field_name.to_string(), value.clone()) {
+                    // Store the updated object back to the variable
+                    self.set_variable(var_name.to_string(), current_value);
+                    Ok(value)
+                } else {
+                    Err(RuntimeError::General(format!(
+                        "Cannot assign to field '{}' on variable '{}' of type '{}'",
+                        field_name,
+                        var_name,
+                        current_value.type_name()
+                    )))
+                }
+            }
+            Err(_) => Err(RuntimeError::General(format!(
+                "Variable '{}' not found for field assignment",
+                var_name
+            ))),
+        }
+    }
+
+    /// The values a `for x in iterable` loop visits: list/array elements or map keys.
+    /// What a `for-in` loop takes its items from: the items reversed, so they can be popped off
+    /// in order, or a channel, received from as the loop runs.
+    pub(crate) fn iteration_source(iterable: Value) -> Result<Value, RuntimeError> {
+        match iterable {
+            Value::Channel(id) => Ok(Value::Channel(id)),
+            other => {
+                let mut items = Self::iteration_items(other)?;
+                items.reverse();
+                Ok(Value::List(items))
+            }
+        }
+    }
+
+    /// The next item of an `iteration_source`; `None` when the loop is done, which for a
+    /// channel is once it is closed and drained.
+    pub(crate) fn next_iteration_item(
+        &self,
+        source: &mut Value,
+    ) -> Result<Option<Value>, RuntimeError> {
+        match source {
+            Value::List(items) => Ok(items.pop()),
+            Value::Channel(id) => {
+                crate::runtime::channels::recv(*id, None, self.current_task.as_ref())
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) fn iteration_items(iterable: Value) -> Result<Vec<Value>, RuntimeError> {
+        match iterable {
+            Value::List(list) => Ok(list),
+            Value::Array(arr) => Ok(arr),
+            Value::Map(map) => Ok(map.into_keys().map(Value::String).collect()),
+            other => Err(RuntimeError::General(format!(
+                "for-in requires list, array, map, or chan; got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    /// `start..end` as a list of ints (end exclusive, like Rust).
+    pub(crate) fn range_value(start: Value, end: Value) -> Result<Value, RuntimeError> {
+        const MAX_RANGE_LEN: i64 = 100_000;
+
+        let start_int = match start {
+            Value::Int(n) => n,
+            _ => {
+                return Err(RuntimeError::TypeError {
+                    expected: "int".to_string(),
+                    got: start.type_name().to_string(),
+                })
+            }
+        };
+
+        let end_int = match end {
+            Value::Int(n) => n,
+            _ => {
+                return Err(RuntimeError::TypeError {
+                    expected: "int".to_string(),
+                    got: end.type_name().to_string(),
+                })
+            }
+        };
+
+        let len = end_int.saturating_sub(start_int);
+        if len > MAX_RANGE_LEN {
+            return Err(RuntimeError::General(format!(
+                "Range too large: {}..{} exceeds limit of {} elements",
+                start_int, end_int, MAX_RANGE_LEN
+            )));
+        }
+
+        Ok(Value::List((start_int..end_int).map(Value::Int).collect()))
+    }
+
+    /// Apply a non-short-circuit binary operator (`&&`/`||` are handled by the caller).
+    pub(crate) fn binary_op(
+        &self,
+        operator: &crate::lexer::tokens::Operator,
+        left_val: Value,
+        right_val: Value,
+    ) -> Result<Value, RuntimeError> {
+        use crate::lexer::tokens::Operator;
+        match operator {
+            Operator::Plus => self.add_values(left_val, right_val),
+            Operator::Minus => self.subtract_values(left_val, right_val),
+            Operator::Star => self.multiply_values(left_val, right_val),
+            Operator::Slash => self.divide_values(left_val, right_val),
+            Operator::Percent => self.modulo_values(left_val, right_val),
+            Operator::Equal => Ok(Value::Bool(
+                self.numbers_or_values_equal(&left_val, &right_val),
+            )),
+            Operator::NotEqual => Ok(Value::Bool(
+                !self.numbers_or_values_equal(&left_val, &right_val),
+            )),
+            Operator::Less => self.compare_values(left_val, right_val, "<"),
+            Operator::LessEqual => self.compare_values(left_val, right_val, "<="),
+            Operator::Greater => self.compare_values(left_val, right_val, ">"),
+            Operator::GreaterEqual => self.compare_values(left_val, right_val, ">="),
+            _ => Err(RuntimeError::UnsupportedOperation(format!(
+                "{:?}",
+                operator
+            ))),
+        }
+    }
+
+    pub(crate) fn unary_op(
+        &self,
+        operator: &crate::lexer::tokens::Operator,
+        operand_val: Value,
+    ) -> Result<Value, RuntimeError> {
+        match operator {
+            crate::lexer::tokens::Operator::Minus => self.negate_value(operand_val),
+            crate::lexer::tokens::Operator::Not => self.logical_not(operand_val),
+            _ => Err(RuntimeError::UnsupportedOperation(format!(
+                "{:?}",
+                operator
+            ))),
+        }
+    }
+
     fn add_values(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
         match (&left, &right) {
             (Value::String(a), Value::String(b)) => Ok(Value::String(a.clone() + b)),
+            (Value::Bytes(a), Value::Bytes(b)) => Ok(Value::Bytes([&a[..], &b[..]].concat())),
             (Value::String(a), other) => Ok(Value::String(format!("{}{}", a, other))),
             (other, Value::String(b)) => Ok(Value::String(format!("{}{}", other, b))),
             (Value::Array(a), Value::Array(b)) => {
@@ -5507,10 +6633,26 @@ impl Runtime {
         SafeMath::modulo(&left, &right)
     }
 
+    /// `==`: structural equality, except that `u256` / `i256` / `decimal` compare by value with
+    /// each other and with `int` (`5u256 == 5`).
+    fn numbers_or_values_equal(&self, left: &Value, right: &Value) -> bool {
+        match crate::runtime::numeric::compare(left, right) {
+            Some(ordering) => ordering.is_eq(),
+            None => left == right,
+        }
+    }
+
     fn compare_values(&self, left: Value, right: Value, op: &str) -> Result<Value, RuntimeError> {
         use std::cmp::Ordering;
 
         let ordering = match (&left, &right) {
+            _ if crate::runtime::numeric::is_wide(&left)
+                || crate::runtime::numeric::is_wide(&right) =>
+            {
+                crate::runtime::numeric::compare(&left, &right).ok_or_else(|| {
+                    RuntimeError::General("Cannot compare these value types".to_string())
+                })?
+            }
             (Value::Int(a), Value::Int(b)) => a.cmp(b),
             (Value::Float(a), Value::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
             (Value::Int(a), Value::Float(b)) => {
@@ -5553,6 +6695,9 @@ impl Runtime {
     }
 
     fn negate_value(&self, value: Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = crate::runtime::numeric::negate(&value) {
+            return result;
+        }
         match value {
             Value::Int(n) => Ok(Value::Int(-n)),
             _ => Err(RuntimeError::TypeMismatch("negation".to_string())),
@@ -5563,14 +6708,19 @@ impl Runtime {
         Ok(Value::Bool(!self.is_truthy(&value)))
     }
 
-    fn is_truthy(&self, value: &Value) -> bool {
+    pub(crate) fn is_truthy(&self, value: &Value) -> bool {
         match value {
             Value::Bool(b) => *b,
             Value::Int(n) => *n != 0,
             Value::Float(f) => *f != 0.0,
+            Value::U256(n) => !n.is_zero(),
+            Value::I256(n) => !n.is_zero(),
+            Value::Decimal(d) => !d.raw().is_zero(),
             Value::String(s) => !s.is_empty(),
+            Value::Bytes(b) => !b.is_empty(),
             Value::Null => false,
-            Value::Result(ok_val, _err_val) => self.is_truthy(ok_val),
+            Value::Result(Ok(ok_val)) => self.is_truthy(ok_val),
+            Value::Result(Err(_)) => false,
             Value::Option(opt_val) => {
                 if let Some(val) = opt_val {
                     self.is_truthy(val)
@@ -5584,6 +6734,8 @@ impl Runtime {
             Value::Struct(_, _) => true,
             Value::Array(arr) => !arr.is_empty(),
             Value::Closure(_) => true,
+            Value::Enum(_, _, _) => true,
+            Value::Task(_) | Value::Channel(_) => true,
         }
     }
 
@@ -5595,6 +6747,10 @@ impl Runtime {
             crate::lexer::tokens::Literal::String(s) => Value::String(s.clone()),
             crate::lexer::tokens::Literal::Bool(b) => Value::Bool(*b),
             crate::lexer::tokens::Literal::Null => Value::Null,
+            crate::lexer::tokens::Literal::U256(n) => Value::U256(*n),
+            crate::lexer::tokens::Literal::I256(n) => Value::I256(*n),
+            crate::lexer::tokens::Literal::Decimal(d) => Value::Decimal(*d),
+            crate::lexer::tokens::Literal::Bytes(b) => Value::Bytes(b.clone()),
         }
     }
 
@@ -5606,7 +6762,7 @@ impl Runtime {
             (Value::String(as_), Value::String(bs_)) => as_ == bs_,
             (Value::Bool(ab), Value::Bool(bb)) => ab == bb,
             (Value::Null, Value::Null) => true,
-            _ => false,
+            _ => crate::runtime::numeric::compare(a, b).is_some_and(|o| o.is_eq()),
         }
     }
 
@@ -5645,7 +6801,7 @@ impl Runtime {
         );
         self.register_function(add_fn);
 
-        // Built-in len function: string (char count), list/array (element count). Supports parsed JSON arrays (Value::List).
+        // Built-in len function: string (char count), list/array (element count), bytes (byte count). Supports parsed JSON arrays (Value::List).
         let len_fn = Function::new("len".to_string(), vec!["value".to_string()], |args, _| {
             if args.len() != 1 {
                 return Err(RuntimeError::ArgumentCountMismatch {
@@ -5658,8 +6814,9 @@ impl Runtime {
                 Value::String(s) => Ok(Value::Int(s.len() as i64)),
                 Value::List(list) => Ok(Value::Int(list.len() as i64)),
                 Value::Array(arr) => Ok(Value::Int(arr.len() as i64)),
+                Value::Bytes(bytes) => Ok(Value::Int(bytes.len() as i64)),
                 _ => Err(RuntimeError::TypeError {
-                    expected: "string, list, or array".to_string(),
+                    expected: "string, list, array, or bytes".to_string(),
                     got: args[0].type_name().to_string(),
                 }),
             }
@@ -5798,6 +6955,9 @@ impl Runtime {
 
                 match &args[0] {
                     Value::Int(n) => Ok(Value::Int(*n)),
+                    wide if crate::runtime::numeric::is_wide(wide) => {
+                        crate::runtime::numeric::to_i64(wide).map(Value::Int)
+                    }
                     Value::String(s) => {
                         s.parse::<i64>()
                             .map(Value::Int)
@@ -5815,6 +6975,38 @@ impl Runtime {
         );
         self.register_function(to_int_fn);
 
+        // Built-in u256 / i256 / decimal conversions: from any number or from numeric text
+        for target in ["u256", "i256", "decimal"] {
+            let convert_fn = Function::new(
+                target.to_string(),
+                vec!["value".to_string()],
+                move |args, _| {
+                    if args.len() != 1 {
+                        return Err(RuntimeError::ArgumentCountMismatch {
+                            expected: 1,
+                            got: args.len(),
+                        });
+                    }
+                    crate::runtime::numeric::convert(target, &args[0])
+                },
+            );
+            self.register_function(convert_fn);
+        }
+
+        // Built-in bytes conversion: bytes, string (UTF-8) or list of ints in 0..=255
+        let bytes_fn = Function::new("bytes".to_string(), vec!["value".to_string()], |args, _| {
+            if args.len() != 1 {
+                return Err(RuntimeError::ArgumentCountMismatch {
+                    expected: 1,
+                    got: args.len(),
+                });
+            }
+            crate::stdlib::bytes::from_value(&args[0])
+                .map(Value::Bytes)
+                .map_err(RuntimeError::General)
+        });
+        self.register_function(bytes_fn);
+
         // Built-in to_bool function
         let to_bool_fn = Function::new(
             "to_bool".to_string(),
@@ -5831,9 +7023,14 @@ impl Runtime {
                     Value::Bool(b) => *b,
                     Value::Int(n) => *n != 0,
                     Value::Float(f) => *f != 0.0,
+                    Value::U256(n) => !n.is_zero(),
+                    Value::I256(n) => !n.is_zero(),
+                    Value::Decimal(d) => !d.raw().is_zero(),
                     Value::String(s) => !s.is_empty(),
+                    Value::Bytes(b) => !b.is_empty(),
                     Value::Null => false,
-                    Value::Result(ok_val, _err_val) => {
+                    Value::Result(Err(_)) => false,
+                    Value::Result(Ok(ok_val)) => {
                         // For Result type, check if Ok value is truthy
                         match ok_val.as_ref() {
                             Value::Bool(b) => *b,
@@ -5864,6 +7061,8 @@ impl Runtime {
                     Value::Struct(_, _) => true,
                     Value::Array(arr) => !arr.is_empty(),
                     Value::Closure(_) => true,
+                    Value::Enum(_, _, _) => true,
+                    Value::Task(_) | Value::Channel(_) => true,
                 };
 
                 Ok(Value::Bool(is_truthy))
@@ -5871,6 +7070,29 @@ impl Runtime {
         );
         self.register_function(to_bool_fn);
 
+        // Built-in Ok/Err/Some constructors (`None` is resolved as an identifier)
+        let wrappers: [(&str, fn(Value) -> Value); 3] = [
+            ("Ok", Value::ok),
+            ("Err", Value::err),
+            ("Some", Value::some),
+        ];
+        for (name, wrap) in wrappers {
+            let wrap_fn = Function::new(
+                name.to_string(),
+                vec!["value".to_string()],
+                move |args, _| {
+                    if args.len() != 1 {
+                        return Err(RuntimeError::ArgumentCountMismatch {
+                            expected: 1,
+                            got: args.len(),
+                        });
+                    }
+                    Ok(wrap(args[0].clone()))
+                },
+            );
+            self.register_function(wrap_fn);
+        }
+
         // Built-in assert function (for tests)
         let assert_fn = Function::new(
             "assert".to_string(),
@@ -6714,6 +7936,18 @@ impl Runtime {
                     .map(Value::String)
                     .map_err(RuntimeError::General)
             }
+            "fetch_bytes" => {
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let url = self.value_to_string(&args[0])?;
+                http_fetch::fetch_bytes(&url)
+                    .map(Value::Bytes)
+                    .map_err(RuntimeError::General)
+            }
             _ => Err(RuntimeError::function_not_found(format!("http::{}", name))),
         }
     }
@@ -7098,6 +8332,36 @@ impl Runtime {
                 let n = fs::append_text(&root, &path, &contents).map_err(RuntimeError::General)?;
                 Ok(Value::String(format!("appended {} bytes", n)))
             }
+            "read_bytes" => {
+                if args.len() != 1 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 1,
+                        got: args.len(),
+                    });
+                }
+                let path = self.value_to_string(&args[0])?;
+                fs::read_bytes(&root, &path)
+                    .map(Value::Bytes)
+                    .map_err(RuntimeError::General)
+            }
+            "write_bytes" | "append_bytes" => {
+                if args.len() != 2 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 2,
+                        got: args.len(),
+                    });
+                }
+                let path = self.value_to_string(&args[0])?;
+                let contents =
+                    crate::stdlib::bytes::from_value(&args[1]).map_err(RuntimeError::General)?;
+                let (n, verb) = if name == "write_bytes" {
+                    (fs::write_bytes(&root, &path, &contents), "wrote")
+                } else {
+                    (fs::append_bytes(&root, &path, &contents), "appended")
+                };
+                let n = n.map_err(RuntimeError::General)?;
+                Ok(Value::String(format!("{} {} bytes", verb, n)))
+            }
             "exists" => {
                 if args.len() != 1 {
                     return Err(RuntimeError::ArgumentCountMismatch {
@@ -7114,6 +8378,91 @@ impl Runtime {
         }
     }
 
+    /// `bytes::*` — conversions between `bytes` and hex, base64, UTF-8 text and int lists.
+    fn call_bytes_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        use crate::stdlib::bytes;
+        let expect = |n: usize| {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(RuntimeError::ArgumentCountMismatch {
+                    expected: n,
+                    got: args.len(),
+                })
+            }
+        };
+        let bytes_arg = |i: usize| match &args[i] {
+            Value::Bytes(b) => Ok(b.as_slice()),
+            other => Err(RuntimeError::TypeError {
+                expected: "bytes".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        };
+        match name {
+            "from_hex" | "from_base64" => {
+                expect(1)?;
+                let text = self.value_to_string(&args[0])?;
+                let decoded = if name == "from_hex" {
+                    bytes::from_hex(&text)
+                } else {
+                    bytes::from_base64(&text)
+                };
+                decoded.map(Value::Bytes).map_err(RuntimeError::General)
+            }
+            "from_string" | "from_list" => {
+                expect(1)?;
+                bytes::from_value(&args[0])
+                    .map(Value::Bytes)
+                    .map_err(RuntimeError::General)
+            }
+            "to_hex" => {
+                expect(1)?;
+                Ok(Value::String(bytes::to_hex(bytes_arg(0)?)))
+            }
+            "to_base64" => {
+                expect(1)?;
+                Ok(Value::String(bytes::to_base64(bytes_arg(0)?)))
+            }
+            "to_string" => {
+                expect(1)?;
+                bytes::to_utf8(bytes_arg(0)?)
+                    .map(Value::String)
+                    .map_err(RuntimeError::General)
+            }
+            "to_list" => {
+                expect(1)?;
+                Ok(bytes::to_list(bytes_arg(0)?))
+            }
+            "len" => {
+                expect(1)?;
+                Ok(Value::Int(bytes_arg(0)?.len() as i64))
+            }
+            "concat" => Ok(Value::Bytes(
+                (0..args.len())
+                    .map(bytes_arg)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat(),
+            )),
+            "slice" => {
+                if args.len() < 2 || args.len() > 3 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 3,
+                        got: args.len(),
+                    });
+                }
+                let start = self.value_to_int(&args[1])?;
+                let end = match args.get(2) {
+                    Some(v) => Some(self.value_to_int(v)?),
+                    None => None,
+                };
+                bytes::slice(bytes_arg(0)?, start, end)
+                    .map(Value::Bytes)
+                    .map_err(RuntimeError::General)
+            }
+            _ => Err(RuntimeError::function_not_found(format!("bytes::{}", name))),
+        }
+    }
+
     fn call_evolve_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
         use crate::stdlib::evolve;
         match name {
@@ -8069,37 +9418,59 @@ impl Runtime {
 
             // Legacy transaction functions (kept for compatibility, but deprecated)
             "transaction" => {
-                // Deprecated: use begin_transaction instead
+                // database::transaction(conn_id, [sql, ...]) -> String (tx_id)
+                // Runs the statements inside BEGIN on a PostgreSQL/MySQL connection; finish with
+                // commit_transaction / rollback_transaction. The one-argument form is deprecated.
+                if args.len() == 2 {
+                    let conn_id = self.value_to_string(&args[0])?;
+                    let operations = match &args[1] {
+                        Value::Array(a) | Value::List(a) => a
+                            .iter()
+                            .map(|v| self.value_to_string(v))
+                            .collect::<Result<Vec<_>, _>>()?,
+                        other => vec![self.value_to_string(other)?],
+                    };
+                    let db = self.database_connections.get(&conn_id).ok_or_else(|| {
+                        RuntimeError::General(format!(
+                            "database::transaction: connection '{}' not found",
+                            conn_id
+                        ))
+                    })?;
+                    let tx = crate::stdlib::database::transaction(db, operations)
+                        .map_err(RuntimeError::General)?;
+                    let tx_id = tx.id.clone();
+                    self.database_transactions.insert(tx_id.clone(), tx);
+                    return Ok(Value::String(tx_id));
+                }
                 if args.len() != 1 {
                     return Err(RuntimeError::ArgumentCountMismatch {
-                        expected: 1,
+                        expected: 2,
                         got: args.len(),
                     });
                 }
                 let operations = self.value_to_string(&args[0])?;
                 Ok(Value::String(format!("txn_{}", operations)))
             }
-            "commit_transaction" => {
-                // Deprecated: use commit instead
+            "commit_transaction" | "rollback_transaction" => {
+                // database::commit_transaction(tx_id) / rollback_transaction(tx_id) -> Bool
                 if args.len() != 1 {
                     return Err(RuntimeError::ArgumentCountMismatch {
                         expected: 1,
                         got: args.len(),
                     });
                 }
-                let _transaction = self.value_to_string(&args[0])?;
-                Ok(Value::Bool(true))
-            }
-            "rollback_transaction" => {
-                // Deprecated: use rollback instead
-                if args.len() != 1 {
-                    return Err(RuntimeError::ArgumentCountMismatch {
-                        expected: 1,
-                        got: args.len(),
-                    });
+                let tx_id = self.value_to_string(&args[0])?;
+                let Some(mut tx) = self.database_transactions.remove(&tx_id) else {
+                    // Ids from the deprecated one-argument form have nothing to finish.
+                    return Ok(Value::Bool(true));
+                };
+                let ok = if name == "commit_transaction" {
+                    crate::stdlib::database::commit_transaction(&mut tx)
+                } else {
+                    crate::stdlib::database::rollback_transaction(&mut tx)
                 }
-                let _transaction = self.value_to_string(&args[0])?;
-                Ok(Value::Bool(true))
+                .map_err(RuntimeError::General)?;
+                Ok(Value::Bool(ok))
             }
             "create_table" => {
                 if args.len() != 2 {
@@ -8281,22 +9652,39 @@ impl Runtime {
                     });
                 }
                 let pool_name = self.value_to_string(&args[0])?;
-                let _connection_string = self.value_to_string(&args[1])?;
+                let connection_string = self.value_to_string(&args[1])?;
                 let max_connections = self.value_to_int(&args[2])?;
-                let _min_connections = self.value_to_int(&args[3])?;
-                Ok(Value::String(format!(
-                    "pool_created_{}_{}",
-                    pool_name, max_connections
-                )))
+                let min_connections = self.value_to_int(&args[3])?;
+                let pool = crate::stdlib::database::create_connection_pool(
+                    pool_name,
+                    connection_string,
+                    max_connections,
+                    min_connections,
+                );
+                let pool_id = format!("pool_{}", crate::stdlib::ai::generate_id());
+                self.database_pools.insert(pool_id.clone(), pool);
+                Ok(Value::String(pool_id))
             }
             "get_connection_from_pool" => {
+                // Returns a connection id usable with database::query and friends.
                 if args.len() != 1 {
                     return Err(RuntimeError::ArgumentCountMismatch {
                         expected: 1,
                         got: args.len(),
                     });
                 }
-                Ok(Value::String("connection_from_pool".to_string()))
+                let pool_id = self.value_to_string(&args[0])?;
+                let pool = self.database_pools.get_mut(&pool_id).ok_or_else(|| {
+                    RuntimeError::General(format!(
+                        "database::get_connection_from_pool: pool '{}' not found",
+                        pool_id
+                    ))
+                })?;
+                let db = crate::stdlib::database::get_connection_from_pool(pool)
+                    .map_err(RuntimeError::General)?;
+                let conn_id = format!("db_{}", crate::stdlib::ai::generate_id());
+                self.database_connections.insert(conn_id.clone(), db);
+                Ok(Value::String(conn_id))
             }
             "return_connection_to_pool" => {
                 if args.len() != 2 {
@@ -8305,7 +9693,22 @@ impl Runtime {
                         got: args.len(),
                     });
                 }
-                Ok(Value::String("connection_returned".to_string()))
+                let pool_id = self.value_to_string(&args[0])?;
+                let conn_id = self.value_to_string(&args[1])?;
+                let pool = self.database_pools.get_mut(&pool_id).ok_or_else(|| {
+                    RuntimeError::General(format!(
+                        "database::return_connection_to_pool: pool '{}' not found",
+                        pool_id
+                    ))
+                })?;
+                let db = self.database_connections.remove(&conn_id).ok_or_else(|| {
+                    RuntimeError::General(format!(
+                        "database::return_connection_to_pool: connection '{}' not found",
+                        conn_id
+                    ))
+                })?;
+                crate::stdlib::database::return_connection_to_pool(pool, db);
+                Ok(Value::Bool(true))
             }
 
             // Query Builder Functions
@@ -8787,39 +10190,20 @@ impl Runtime {
 
             // Message Passing System
             "send_message" => {
-                if args.len() != 5 {
-                    return Err(RuntimeError::ArgumentCountMismatch {
-                        expected: 5,
-                        got: args.len(),
-                    });
-                }
-                let sender_id = self.value_to_string(&args[0])?;
-                let receiver_id = self.value_to_string(&args[1])?;
-                let message_type = self.value_to_string(&args[2])?;
-                let content = args[3].clone();
-                let message_id = format!("msg_{}", generate_id());
-
-                let message = crate::stdlib::agent::create_agent_message(
-                    message_id,
-                    sender_id.clone(),
-                    receiver_id.clone(),
-                    message_type,
-                    content.clone(),
-                );
-
-                // Push message content to receiver's in-memory queue
-                self.agent_states
-                    .entry(receiver_id.clone())
-                    .or_default()
-                    .message_queue
-                    .push_back(content);
-                match crate::stdlib::agent::communicate(&sender_id, &receiver_id, message) {
-                    Ok(_) => Ok(Value::String(format!(
-                        "message_sent_{}_{}",
-                        sender_id, receiver_id
-                    ))),
-                    Err(e) => Err(RuntimeError::General(e)),
+                if args.len() != 5 {
+                    return Err(RuntimeError::ArgumentCountMismatch {
+                        expected: 5,
+                        got: args.len(),
+                    });
                 }
+                let sender_id = self.value_to_string(&args[0])?;
+                let receiver_id = self.value_to_string(&args[1])?;
+                let message_type = self.value_to_string(&args[2])?;
+                self.send_agent_message(&sender_id, &receiver_id, message_type, args[3].clone())?;
+                Ok(Value::String(format!(
+                    "message_sent_{}_{}",
+                    sender_id, receiver_id
+                )))
             }
             "receive_message" => {
                 if args.len() != 2 {
@@ -12287,13 +13671,7 @@ impl Runtime {
 
         // Simple literal evaluation for now
         match &tokens[0] {
-            crate::lexer::tokens::Token::Literal(literal) => match literal {
-                crate::lexer::tokens::Literal::Int(i) => Ok(Value::Int(*i)),
-                crate::lexer::tokens::Literal::Float(f) => Ok(Value::Float(*f)),
-                crate::lexer::tokens::Literal::String(s) => Ok(Value::String(s.clone())),
-                crate::lexer::tokens::Literal::Bool(b) => Ok(Value::Bool(*b)),
-                crate::lexer::tokens::Literal::Null => Ok(Value::Null),
-            },
+            crate::lexer::tokens::Token::Literal(literal) => Ok(self.literal_to_value(literal)),
             crate::lexer::tokens::Token::Identifier(name) => self.get_variable(name),
             _ => Err(RuntimeError::General("Unsupported expression".to_string())),
         }
@@ -12331,6 +13709,7 @@ impl Runtime {
         service_stmt: &ServiceStatement,
     ) -> Result<Value, RuntimeError> {
         self.validate_compile_target(service_stmt)?;
+        self.method_code.clear();
 
         // Build attribute strings for context
         // Attribute.name from the parser already includes a leading '@' (e.g. "@admin", "@trust").
@@ -12353,6 +13732,18 @@ impl Runtime {
             })
             .collect();
 
+        // `@persistent` on the service makes every field persistent; on a field, just that one.
+        let service_persistent = service_stmt
+            .attributes
+            .iter()
+            .any(|a| a.name == "@persistent");
+        let persistent_fields: Vec<String> = service_stmt
+            .fields
+            .iter()
+            .filter(|f| service_persistent || f.attributes.iter().any(|a| a.name == "@persistent"))
+            .map(|f| f.name.clone())
+            .collect();
+
         // Create service instance
         let mut service_instance = ServiceInstance {
             name: service_stmt.name.clone(),
@@ -12361,12 +13752,14 @@ impl Runtime {
             events: service_stmt.events.clone(),
             attributes: attr_strings.clone(),
             exported: service_stmt.exported,
+            persistent_fields,
         };
 
         // Initialize fields
         for field in &service_stmt.fields {
             let initial_value = if let Some(ref value) = field.initial_value {
-                self.evaluate_expression(value)?
+                let value = self.evaluate_expression(value)?;
+                crate::runtime::numeric::coerce_to_annotation(&field.field_type, value)?
             } else {
                 self.get_default_value(&field.field_type)?
             };
@@ -12376,9 +13769,38 @@ impl Runtime {
                 .insert(field.name.clone(), initial_value);
         }
 
+        let migrated_from = match service_stmt
+            .attributes
+            .iter()
+            .find(|a| a.name == "@versioned")
+        {
+            Some(attr) => {
+                let label = match attr.parameters.first() {
+                    Some(expr) => Some(self.evaluate_expression(expr)?),
+                    None => None,
+                };
+                self.record_service_version(service_stmt, label)?
+            }
+            None => None,
+        };
+        self.load_persistent_fields(&mut service_instance)?;
+
         // Store service in runtime
         self.services
             .insert(service_stmt.name.clone(), service_instance);
+        self.subscribe_service_handlers(service_stmt)?;
+
+        // A versioned service whose schema or version changed gets `migrate(from_version)`.
+        if let Some(from) = migrated_from {
+            if let Some(migrate) = service_stmt.methods.iter().find(|m| m.name == "migrate") {
+                let args = if migrate.parameters.is_empty() {
+                    vec![]
+                } else {
+                    vec![from]
+                };
+                self.execute_service_method(&service_stmt.name, "migrate", migrate, &args)?;
+            }
+        }
 
         // Set service reference in current scope
         self.set_variable(
@@ -12389,9 +13811,520 @@ impl Runtime {
         // Set current service context for trust validation (chain, auth, etc.)
         self.set_current_service(service_stmt.name.clone(), attr_strings);
 
+        self.apply_pending_impls()?;
+
         Ok(Value::String(format!("service_{}", service_stmt.name)))
     }
 
+    /// Register `@on("Event", ...)` methods of a (re)declared service on the event bus.
+    fn subscribe_service_handlers(
+        &mut self,
+        service_stmt: &ServiceStatement,
+    ) -> Result<(), RuntimeError> {
+        self.event_bus.unsubscribe_service(&service_stmt.name);
+        for method in &service_stmt.methods {
+            for attr in method.attributes.iter().filter(|a| a.name == "@on") {
+                if attr.parameters.is_empty() {
+                    return Err(RuntimeError::General(format!(
+                        "@on on {}::{} needs an event name, e.g. @on(\"Transfer\")",
+                        service_stmt.name, method.name
+                    )));
+                }
+                for param in &attr.parameters {
+                    let event_name = self.evaluate_expression(param)?;
+                    let event_name = self.value_to_string(&event_name)?;
+                    self.event_bus.subscribe(
+                        &event_name,
+                        crate::runtime::events::EventHandler::ServiceMethod {
+                            service: service_stmt.name.clone(),
+                            method: method.name.clone(),
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `msg recipient { ... }`: queue the payload for the recipient agent. `recipient` names the
+    /// agent unless it is a variable holding the agent id. Returns the message id.
+    fn deliver_message(
+        &mut self,
+        recipient: &str,
+        data: HashMap<String, Value>,
+    ) -> Result<Value, RuntimeError> {
+        let receiver_id = match self.scope.get(recipient) {
+            Some(Value::String(id)) => id,
+            _ => recipient.to_string(),
+        };
+        let sender_id = match self.scope.get("self") {
+            Some(Value::String(id)) => id,
+            _ => "main".to_string(),
+        };
+        let message_type = match data.get("type") {
+            Some(Value::String(t)) => t.clone(),
+            _ => "msg".to_string(),
+        };
+        let message_id =
+            self.send_agent_message(&sender_id, &receiver_id, message_type, Value::Map(data))?;
+        Ok(Value::String(message_id))
+    }
+
+    /// Push `content` onto the receiver's in-memory queue (`agent::receive_message`) and the
+    /// agent message bus (`agent::receive_messages`).
+    fn send_agent_message(
+        &mut self,
+        sender_id: &str,
+        receiver_id: &str,
+        message_type: String,
+        content: Value,
+    ) -> Result<String, RuntimeError> {
+        let message_id = format!("msg_{}", generate_id());
+        let message = crate::stdlib::agent::create_agent_message(
+            message_id.clone(),
+            sender_id.to_string(),
+            receiver_id.to_string(),
+            message_type,
+            content.clone(),
+        );
+        self.agent_states
+            .entry(receiver_id.to_string())
+            .or_default()
+            .message_queue
+            .push_back(content);
+        crate::stdlib::agent::communicate(sender_id, receiver_id, message)
+            .map_err(RuntimeError::General)?;
+        Ok(message_id)
+    }
+
+    /// `event Name { ... }`: check the payload against the emitting service's declaration, log
+    /// it and run every subscriber in order. Returns the number of handlers that ran.
+    fn publish_event(
+        &mut self,
+        name: &str,
+        payload: HashMap<String, Value>,
+    ) -> Result<Value, RuntimeError> {
+        const MAX_EVENT_DEPTH: usize = 8;
+        let emitter = match self.scope.get("self") {
+            Some(Value::String(id)) => self.services.get(&id),
+            _ => None,
+        };
+        if let Some(service) = emitter {
+            Self::validate_event_payload(service, name, &payload)?;
+        }
+        let source = emitter.map(|service| service.name.clone());
+        if self.event_depth >= MAX_EVENT_DEPTH {
+            return Err(RuntimeError::General(format!(
+                "event '{}' exceeded the maximum nested delivery depth of {}",
+                name, MAX_EVENT_DEPTH
+            )));
+        }
+
+        let event = self.event_bus.record(name, payload, source);
+        let handlers = self.event_bus.handlers(name);
+        self.event_depth += 1;
+        let delivered = handlers
+            .iter()
+            .try_for_each(|handler| self.deliver_event(handler, &event));
+        self.event_depth -= 1;
+        delivered?;
+        Ok(Value::Int(handlers.len() as i64))
+    }
+
+    fn validate_event_payload(
+        service: &ServiceInstance,
+        name: &str,
+        payload: &HashMap<String, Value>,
+    ) -> Result<(), RuntimeError> {
+        match service.events.iter().find(|e| e.name == name) {
+            Some(declaration) => crate::runtime::events::validate_payload(declaration, payload)
+                .map_err(|e| RuntimeError::General(format!("{}: {}", service.name, e))),
+            None => Ok(()),
+        }
+    }
+
+    /// Run one subscriber. Service handlers see the payload checked against their own declaration
+    /// of the event and receive it as one map argument, or field by field when every parameter
+    /// is named after a payload field.
+    fn deliver_event(
+        &mut self,
+        handler: &crate::runtime::events::EventHandler,
+        event: &crate::runtime::events::PublishedEvent,
+    ) -> Result<(), RuntimeError> {
+        match handler {
+            crate::runtime::events::EventHandler::ServiceMethod { service, method } => {
+                let Some(instance) = self.services.get(service) else {
+                    return Ok(());
+                };
+                Self::validate_event_payload(instance, &event.name, &event.payload)?;
+                let handler_method = instance
+                    .methods
+                    .iter()
+                    .find(|m| &m.name == method)
+                    .cloned()
+                    .ok_or_else(|| {
+                        RuntimeError::General(format!(
+                            "event handler {}::{} not found",
+                            service, method
+                        ))
+                    })?;
+                let params = &handler_method.parameters;
+                let args = if params.is_empty() {
+                    vec![]
+                } else if params.iter().all(|p| event.payload.contains_key(&p.name)) {
+                    params
+                        .iter()
+                        .map(|p| event.payload[&p.name].clone())
+                        .collect()
+                } else if params.len() == 1 {
+                    vec![Value::Map(event.payload.clone())]
+                } else {
+                    return Err(RuntimeError::General(format!(
+                        "event handler {}::{} must take the payload or parameters named after \
+                         fields of '{}'",
+                        service, method, event.name
+                    )));
+                };
+                self.execute_service_method(service, method, &handler_method, &args)?;
+            }
+            crate::runtime::events::EventHandler::Callback(callee) => {
+                self.call_value(callee, &[Value::Map(event.payload.clone())])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Use `storage` for `@persistent` fields and `@versioned` history instead of the backend
+    /// chosen by `DAL_STATE_STORAGE` (`file`, `sqlite` or `memory`) and `DAL_STATE_PATH`.
+    pub fn set_state_storage(
+        &mut self,
+        storage: Box<dyn crate::runtime::transaction::StateStorage>,
+    ) {
+        self.state_storage = Some(storage);
+    }
+
+    pub(crate) fn state_storage(
+        &mut self,
+    ) -> Result<&mut dyn crate::runtime::transaction::StateStorage, RuntimeError> {
+        let storage = match self.state_storage.take() {
+            Some(storage) => storage,
+            None => {
+                let kind =
+                    std::env::var("DAL_STATE_STORAGE").unwrap_or_else(|_| "file".to_string());
+                let path = std::env::var("DAL_STATE_PATH").ok();
+                crate::runtime::transaction::open_state_storage(
+                    &kind,
+                    path.as_deref(),
+                    ".dal/service_state",
+                )
+                .map_err(|e| {
+                    RuntimeError::General(format!("Cannot open service state storage: {}", e))
+                })?
+            }
+        };
+        Ok(self.state_storage.insert(storage).as_mut())
+    }
+
+    /// Route `Service::method` calls for the module's services to `services` (`dal run --wasm`).
+    /// Calls the module does not export, such as `Service::new()`, stay with the interpreter.
+    #[cfg(feature = "wasm-runtime")]
+    pub fn attach_wasm_services(&mut self, services: crate::runtime::wasm_host::WasmServices) {
+        self.wasm_services = Some(Box::new(services));
+    }
+
+    /// Run `service::method` in the attached wasm module when it exports it. The module's
+    /// storage imports use this runtime's state storage for the duration of the call.
+    #[cfg(feature = "wasm-runtime")]
+    fn call_wasm_service(
+        &mut self,
+        service: &str,
+        method: &str,
+        args: &[Value],
+    ) -> Option<Result<Value, RuntimeError>> {
+        let mut wasm = self.wasm_services.take()?;
+        if !wasm.exports(service, method) {
+            self.wasm_services = Some(wasm);
+            return None;
+        }
+        std::mem::swap(&mut self.state_storage, &mut wasm.host().state_storage);
+        let result = wasm.call(service, method, args);
+        std::mem::swap(&mut self.state_storage, &mut wasm.host().state_storage);
+        self.wasm_services = Some(wasm);
+        Some(result)
+    }
+
+    /// Replace persistent field values with the stored ones (keyed `Service.field`, shared by
+    /// every instance of the service).
+    fn load_persistent_fields(
+        &mut self,
+        instance: &mut ServiceInstance,
+    ) -> Result<(), RuntimeError> {
+        if instance.persistent_fields.is_empty() {
+            return Ok(());
+        }
+        let storage = self.state_storage()?;
+        for field in &instance.persistent_fields {
+            if let Some(value) = storage.get(&format!("{}.{}", instance.name, field)) {
+                instance.fields.insert(field.clone(), value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write changed persistent fields of a service instance through to storage.
+    fn store_persistent_fields(&mut self, instance_id: &str) -> Result<(), RuntimeError> {
+        let Some(instance) = self.services.get(instance_id) else {
+            return Ok(());
+        };
+        if instance.persistent_fields.is_empty() {
+            return Ok(());
+        }
+        let entries: Vec<(String, Value)> = instance
+            .persistent_fields
+            .iter()
+            .filter_map(|f| {
+                let value = instance.fields.get(f)?.clone();
+                Some((format!("{}.{}", instance.name, f), value))
+            })
+            .collect();
+        let storage = self.state_storage()?;
+        for (key, value) in entries {
+            if storage.get(&key).as_ref() != Some(&value) {
+                storage.set(&key, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append a migration record to `Service.__versions` when a `@versioned` service's declared
+    /// version or field schema differs from the last record. Returns the previous version when
+    /// a migration happened; fields that were removed lose their persisted values.
+    fn record_service_version(
+        &mut self,
+        service_stmt: &ServiceStatement,
+        label: Option<Value>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let key = format!("{}.__versions", service_stmt.name);
+        let schema: Vec<Value> = service_stmt
+            .fields
+            .iter()
+            .map(|f| Value::String(format!("{}: {}", f.name, f.field_type)))
+            .collect();
+        let storage = self.state_storage()?;
+        let mut history = match storage.get(&key) {
+            Some(Value::List(h)) | Some(Value::Array(h)) => h,
+            _ => Vec::new(),
+        };
+        let last = history.last().and_then(|entry| match entry {
+            Value::Map(m) => Some(m.clone()),
+            _ => None,
+        });
+        let last_version = last.as_ref().and_then(|m| m.get("version").cloned());
+        let last_schema = match last.as_ref().and_then(|m| m.get("fields")) {
+            Some(Value::List(f)) | Some(Value::Array(f)) => f.clone(),
+            _ => Vec::new(),
+        };
+        let schema_changed = last.is_none() || last_schema != schema;
+        let version = match label {
+            Some(label) => label,
+            None if schema_changed => Value::Int(history.len() as i64 + 1),
+            None => last_version.clone().unwrap_or(Value::Int(1)),
+        };
+        if !schema_changed && last_version.as_ref() == Some(&version) {
+            return Ok(None);
+        }
+
+        let added: Vec<Value> = schema
+            .iter()
+            .filter(|f| !last_schema.contains(f))
+            .cloned()
+            .collect();
+        let removed: Vec<Value> = last_schema
+            .iter()
+            .filter(|f| !schema.contains(f))
+            .cloned()
+            .collect();
+        for field in &removed {
+            let Value::String(decl) = field else { continue };
+            let name = decl.split(':').next().unwrap_or(decl).trim();
+            if !service_stmt.fields.iter().any(|f| f.name == name) {
+                storage.remove(&format!("{}.{}", service_stmt.name, name));
+            }
+        }
+        let applied_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let mut entry = HashMap::new();
+        entry.insert("version".to_string(), version);
+        entry.insert("fields".to_string(), Value::List(schema));
+        entry.insert("added".to_string(), Value::List(added));
+        entry.insert("removed".to_string(), Value::List(removed));
+        entry.insert("applied_at".to_string(), Value::Int(applied_at));
+        history.push(Value::Map(entry));
+        storage.set(&key, Value::List(history));
+        Ok(last_version)
+    }
+
+    /// Cache key and ttl for a call to an `@cached` / `@cached(ttl_seconds)` function, or None
+    /// when it is not cached. serde_json sorts map keys, so map arguments give a stable key.
+    fn call_cache_key(
+        &mut self,
+        name: &str,
+        attributes: &[crate::parser::ast::Attribute],
+        args: &[Value],
+    ) -> Option<(String, Option<std::time::Duration>)> {
+        let attr = attributes.iter().find(|a| a.name == "@cached")?;
+        let ttl = attr
+            .parameters
+            .first()
+            .and_then(|e| self.evaluate_expression(e).ok())
+            .and_then(|v| self.value_to_int(&v).ok())
+            .map(|secs| std::time::Duration::from_secs(secs.max(0) as u64));
+        let args_json = serde_json::to_value(args)
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        Some((format!("{}({})", name, args_json), ttl))
+    }
+
+    fn cached_result(&mut self, key: &str) -> Option<Value> {
+        let cached = self.call_cache.get(key)?;
+        if cached
+            .expires
+            .is_some_and(|at| at <= std::time::Instant::now())
+        {
+            self.call_cache.remove(key);
+            return None;
+        }
+        Some(cached.value.clone())
+    }
+
+    fn store_cached_result(
+        &mut self,
+        key: String,
+        ttl: Option<std::time::Duration>,
+        value: &Value,
+    ) {
+        let now = std::time::Instant::now();
+        if self.call_cache.len() >= CALL_CACHE_CAPACITY && !self.call_cache.contains_key(&key) {
+            self.call_cache
+                .retain(|_, cached| cached.expires.is_none_or(|at| at > now));
+            if self.call_cache.len() >= CALL_CACHE_CAPACITY {
+                let oldest = self
+                    .call_cache
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.stored)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    self.call_cache.remove(&oldest);
+                }
+            }
+        }
+        let cached = CachedCall {
+            value: value.clone(),
+            expires: ttl.map(|ttl| now + ttl),
+            stored: now,
+        };
+        self.call_cache.insert(key, cached);
+    }
+
+    /// Drop the cached method results of a service instance whose fields just changed.
+    fn forget_cached_methods(&mut self, instance_id: &str) {
+        let prefix = format!("{}.", instance_id);
+        self.call_cache.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    /// Apply every pending impl block whose service (and trait, if any) has been declared.
+    /// Source order is free: `impl` may come before or after the `service` and `trait`.
+    fn apply_pending_impls(&mut self) -> Result<(), RuntimeError> {
+        let (ready, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_impls)
+            .into_iter()
+            .partition(|(imp, _)| {
+                self.services.contains_key(&imp.service_name)
+                    && imp
+                        .trait_name
+                        .as_ref()
+                        .is_none_or(|t| self.trait_types.contains_key(t))
+            });
+        self.pending_impls = waiting;
+        for (imp, _) in ready {
+            self.apply_impl(&imp)?;
+        }
+        Ok(())
+    }
+
+    /// Fail for the first impl block still pending once a program has run: the service or trait
+    /// it names was never declared, so its methods would silently be missing.
+    fn check_unresolved_impls(&mut self) -> Result<(), RuntimeErrorWithContext> {
+        let Some((imp, location)) = std::mem::take(&mut self.pending_impls).into_iter().next()
+        else {
+            return Ok(());
+        };
+        let (header, missing) = match &imp.trait_name {
+            Some(trait_name) => (
+                format!("impl {} for {}", trait_name, imp.service_name),
+                if self.trait_types.contains_key(trait_name) {
+                    format!("service '{}'", imp.service_name)
+                } else {
+                    format!("trait '{}'", trait_name)
+                },
+            ),
+            None => (
+                format!("impl {}", imp.service_name),
+                format!("service '{}'", imp.service_name),
+            ),
+        };
+        Err(RuntimeErrorWithContext::new(
+            RuntimeError::General(format!("{}: {} is not declared", header, missing)),
+            location,
+            self.get_call_stack_info(),
+        ))
+    }
+
+    /// Add an impl block's methods (plus unoverridden trait defaults) to the service and all of
+    /// its instances, so `Expression::MethodCall` dispatches them like declared methods.
+    fn apply_impl(&mut self, imp: &crate::parser::ast::ImplStatement) -> Result<(), RuntimeError> {
+        self.method_code.clear();
+        let existing: Vec<String> = self
+            .services
+            .get(&imp.service_name)
+            .map(|svc| svc.methods.iter().map(|m| m.name.clone()).collect())
+            .unwrap_or_default();
+        let mut methods = imp.methods.clone();
+        if let Some(trait_name) = &imp.trait_name {
+            let trait_stmt = self.trait_types.get(trait_name).ok_or_else(|| {
+                RuntimeError::General(format!("Trait '{}' is not declared", trait_name))
+            })?;
+            for required in &trait_stmt.methods {
+                let provided = methods.iter().any(|m| m.name == required.name)
+                    || existing.contains(&required.name);
+                if provided {
+                    continue;
+                }
+                if required.default_body.is_none() {
+                    return Err(RuntimeError::General(format!(
+                        "Service '{}' does not implement '{}' required by trait '{}'",
+                        imp.service_name, required.name, trait_name
+                    )));
+                }
+                methods.push(required.to_function());
+            }
+        }
+        for instance in self
+            .services
+            .values_mut()
+            .filter(|inst| inst.name == imp.service_name)
+        {
+            for method in &methods {
+                match instance.methods.iter_mut().find(|m| m.name == method.name) {
+                    Some(slot) => *slot = method.clone(),
+                    None => instance.methods.push(method.clone()),
+                }
+            }
+        }
+        Ok(())
+    }
+
     // Helper method to get default values for field types
     fn get_default_value(&self, field_type: &str) -> Result<Value, RuntimeError> {
         match field_type {
@@ -12399,55 +14332,75 @@ impl Runtime {
             "string" => Ok(Value::String("".to_string())),
             "bool" => Ok(Value::Bool(false)),
             "float" => Ok(Value::Float(0.0)),
+            "u256" | "uint256" => Ok(Value::U256(crate::runtime::numeric::U256::zero())),
+            "i256" | "int256" => Ok(Value::I256(crate::runtime::numeric::I256::ZERO)),
+            "decimal" => Ok(Value::Decimal(crate::runtime::numeric::Decimal::ZERO)),
             _ => Ok(Value::Null),
         }
     }
 
-    /// Call an arrow/closure value by id (single param, body, captured scope).
+    /// Call a function value: `callee(args)` for any expression that evaluates to a closure.
+    pub(crate) fn call_value(
+        &mut self,
+        callee: &Value,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        match callee {
+            Value::Closure(id) => self.call_closure(id, args),
+            other => Err(RuntimeError::General(format!(
+                "Cannot call a value of type '{}'",
+                other.type_name()
+            ))),
+        }
+    }
+
+    /// Call a test DSL callback (`describe`, `it`, ...), written either as `fn() { ... }` or with
+    /// a parameter that receives null.
+    fn call_test_callback(&mut self, id: &str) -> Result<Value, RuntimeError> {
+        let takes_no_args = self
+            .closure_registry
+            .get(id)
+            .is_some_and(|entry| entry.params.is_empty());
+        if takes_no_args {
+            self.call_closure(id, &[])
+        } else {
+            self.call_closure(id, &[Value::Null])
+        }
+    }
+
+    /// Call an arrow/closure value by id (params, body, captured scope), or the named function
+    /// a function reference points to.
     fn call_closure(&mut self, id: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        if let Some(function_name) = id.strip_prefix(FUNCTION_REF_PREFIX) {
+            return self.call_function(function_name, args);
+        }
         let entry = self
             .closure_registry
             .get(id)
-            .ok_or_else(|| RuntimeError::General(format!("Closure '{}' not found", id)))?
-            .clone();
-        if args.len() != 1 {
+            .ok_or_else(|| RuntimeError::General(format!("Closure '{}' not found", id)))?;
+        if args.len() != entry.params.len() {
             return Err(RuntimeError::ArgumentCountMismatch {
-                expected: 1,
+                expected: entry.params.len(),
                 got: args.len(),
             });
         }
-        let saved_scope = self.scope.clone();
+        let params = entry.params.clone();
+        let captured_scope = entry.captured_scope.clone();
+        let body = self.callable_body(&entry.body, &entry.code);
         let call_frame = CallFrame {
-            scope: saved_scope.clone(),
+            scope: std::mem::replace(&mut self.scope, captured_scope),
             function_name: Some("<closure>".to_string()),
+            call_site: self.current_location.clone(),
         };
         self.call_stack.push(call_frame);
-        self.scope = entry.captured_scope.clone();
-        self.scope.set(entry.param.clone(), args[0].clone());
-        let mut result = Value::Null;
-        self.return_pending = None;
-        for stmt in &entry.body.statements {
-            match self.execute_statement(stmt) {
-                Ok(value) => {
-                    result = value;
-                    if self.return_pending.is_some() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    self.return_pending = None;
-                    if let Some(frame) = self.call_stack.pop() {
-                        self.scope = frame.scope;
-                    }
-                    return Err(e);
-                }
-            }
+        for (param, arg) in params.into_iter().zip(args) {
+            self.scope.set(param, arg.clone());
         }
-        self.return_pending = None;
+        let outcome = self.run_callable_body(&body);
         if let Some(frame) = self.call_stack.pop() {
             self.scope = frame.scope;
         }
-        Ok(result)
+        outcome
     }
 
     // Execute a service method with instance context
@@ -12605,11 +14558,23 @@ impl Runtime {
         // Reentrancy token (if present) will be dropped automatically when function returns
         // This ensures protection for the entire method execution
 
+        let cache_key = self.call_cache_key(
+            &format!("{}.{}", instance_id, method_name),
+            &method.attributes,
+            args,
+        );
+        if let Some((key, _)) = &cache_key {
+            if let Some(value) = self.cached_result(key) {
+                return Ok(value);
+            }
+        }
+
         // Save current scope
         let saved_scope = self.scope.clone();
         let call_frame = CallFrame {
             scope: saved_scope.clone(),
             function_name: Some(format!("{}.{}", instance_id, method_name)),
+            call_site: self.current_location.clone(),
         };
         self.call_stack.push(call_frame);
 
@@ -12626,34 +14591,37 @@ impl Runtime {
             .set("self".to_string(), Value::String(instance_id.to_string()));
 
         // Execute method body
-        let mut result = Value::Null;
-        self.return_pending = None;
-        for stmt in &method.body.statements {
-            match self.execute_statement(stmt) {
-                Ok(value) => {
-                    result = value;
-                    if self.return_pending.is_some() {
-                        break;
-                    }
-                }
-                Err(e) => {
-                    self.return_pending = None;
-                    // Restore scope on error
-                    if let Some(frame) = self.call_stack.pop() {
-                        self.scope = frame.scope;
-                    }
-                    return Err(e);
-                }
-            }
+        let code_key = format!("{}.{}", instance_id, method_name);
+        let code = self.method_code.entry(code_key).or_default().clone();
+        let body = self.callable_body(&method.body, &code);
+        // Methods of services marked `@trust("decentralized")` run metered unless their caller
+        // already is; a service without `@trust` only defaults to that model and stays unmetered.
+        let metered_here = self.meter.is_none()
+            && self.current_service.as_ref().is_some_and(|service| {
+                service.trust_model == "decentralized"
+                    && service
+                        .attributes
+                        .iter()
+                        .any(|attr| attr.starts_with("@trust("))
+            });
+        if metered_here {
+            self.set_budget(Some(crate::runtime::metering::Budget::DECENTRALIZED));
+        }
+        let outcome = self.run_callable_body(&body);
+        if metered_here {
+            self.last_method_usage = self.set_budget(None);
         }
-        self.return_pending = None;
 
         // Restore scope
         if let Some(frame) = self.call_stack.pop() {
             self.scope = frame.scope;
         }
 
-        Ok(result)
+        self.store_persistent_fields(instance_id)?;
+        if let (Some((key, ttl)), Ok(value)) = (cache_key, &outcome) {
+            self.store_cached_result(key, ttl, value);
+        }
+        outcome
     }
 
     // Helper method to parse agent configuration from Value
@@ -13557,10 +15525,10 @@ impl Runtime {
     /// Returns (isolation_level, timeout_ms). Defaults: ReadCommitted, 30s.
     fn parse_txn_attribute(
         &mut self,
-        user_func: &UserFunction,
+        attributes: &[crate::parser::ast::Attribute],
     ) -> (crate::runtime::transaction::IsolationLevel, Option<u64>) {
         use crate::runtime::transaction::IsolationLevel;
-        let attr = match user_func.attributes.iter().find(|a| a.name == "@txn") {
+        let attr = match attributes.iter().find(|a| a.name == "@txn") {
             None => return (IsolationLevel::ReadCommitted, Some(30000)),
             Some(a) => a,
         };
@@ -13708,5 +15676,14 @@ impl Default for Runtime {
     }
 }
 
+impl Drop for Runtime {
+    /// Channels do not outlive the program or task that created them.
+    fn drop(&mut self) {
+        for id in self.channels.drain(..) {
+            crate::runtime::channels::release(id);
+        }
+    }
+}
+
 // Use the proper RuntimeError variants
 impl RuntimeError {}
@@ -0,0 +1,238 @@
+//! In-process event bus for `event Name { ... }` statements.
+//! Services subscribe with `@on("Name")` methods; other code with `service::subscribe`.
+
+use crate::parser::ast::EventDeclaration;
+use crate::runtime::types::Type;
+use crate::runtime::values::Value;
+use std::collections::{HashMap, VecDeque};
+
+/// Published events kept for `service::event_log` before the oldest are dropped.
+const EVENT_LOG_CAPACITY: usize = 1024;
+
+/// Who receives an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventHandler {
+    /// A method on a declared service (`@on("Name") fn handler(...)`).
+    ServiceMethod { service: String, method: String },
+    /// A closure or function reference passed to `service::subscribe`.
+    Callback(Value),
+}
+
+/// An event as it was published.
+#[derive(Debug, Clone)]
+pub struct PublishedEvent {
+    pub sequence: u64,
+    pub name: String,
+    pub payload: HashMap<String, Value>,
+    /// Service whose method emitted the event, if any.
+    pub source: Option<String>,
+}
+
+impl PublishedEvent {
+    pub fn to_value(&self) -> Value {
+        let mut map = HashMap::new();
+        map.insert("sequence".to_string(), Value::Int(self.sequence as i64));
+        map.insert("name".to_string(), Value::String(self.name.clone()));
+        map.insert("payload".to_string(), Value::Map(self.payload.clone()));
+        map.insert(
+            "source".to_string(),
+            self.source
+                .clone()
+                .map(Value::String)
+                .unwrap_or(Value::Null),
+        );
+        Value::Map(map)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventBus {
+    subscriptions: HashMap<String, Vec<EventHandler>>,
+    log: VecDeque<PublishedEvent>,
+    next_sequence: u64,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `handler` for `event_name`. Subscribing the same handler twice is a no-op.
+    pub fn subscribe(&mut self, event_name: &str, handler: EventHandler) {
+        let handlers = self
+            .subscriptions
+            .entry(event_name.to_string())
+            .or_default();
+        if !handlers.contains(&handler) {
+            handlers.push(handler);
+        }
+    }
+
+    /// Drop every subscription held by methods of `service` (used when it is redeclared).
+    pub fn unsubscribe_service(&mut self, service: &str) {
+        for handlers in self.subscriptions.values_mut() {
+            handlers.retain(
+                |h| !matches!(h, EventHandler::ServiceMethod { service: s, .. } if s == service),
+            );
+        }
+    }
+
+    pub fn handlers(&self, event_name: &str) -> Vec<EventHandler> {
+        self.subscriptions
+            .get(event_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record a published event and return it with its sequence number.
+    pub fn record(
+        &mut self,
+        name: &str,
+        payload: HashMap<String, Value>,
+        source: Option<String>,
+    ) -> PublishedEvent {
+        self.next_sequence += 1;
+        let event = PublishedEvent {
+            sequence: self.next_sequence,
+            name: name.to_string(),
+            payload,
+            source,
+        };
+        if self.log.len() == EVENT_LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(event.clone());
+        event
+    }
+
+    /// Published events, oldest first, optionally only those named `name`.
+    pub fn log(&self, name: Option<&str>) -> Vec<&PublishedEvent> {
+        self.log
+            .iter()
+            .filter(|e| name.is_none_or(|n| e.name == n))
+            .collect()
+    }
+}
+
+/// Check `payload` against a declared `event Name(param: type, ...)`: every parameter must be
+/// present with a compatible value and no undeclared keys are allowed.
+pub fn validate_payload(
+    declaration: &EventDeclaration,
+    payload: &HashMap<String, Value>,
+) -> Result<(), String> {
+    for param in &declaration.parameters {
+        let value = payload.get(&param.name).ok_or_else(|| {
+            format!(
+                "event '{}' is missing field '{}'",
+                declaration.name, param.name
+            )
+        })?;
+        if let Some(annotation) = &param.param_type {
+            let expected = Type::from_annotation(annotation);
+            if !expected.is_compatible_with(&Type::of_value(value)) {
+                return Err(format!(
+                    "event '{}' field '{}' expects {}, got {}",
+                    declaration.name,
+                    param.name,
+                    annotation,
+                    value.type_name()
+                ));
+            }
+        }
+    }
+    let mut unknown: Vec<&String> = payload
+        .keys()
+        .filter(|k| !declaration.parameters.iter().any(|p| &p.name == *k))
+        .collect();
+    unknown.sort();
+    if let Some(key) = unknown.first() {
+        return Err(format!(
+            "event '{}' has no field '{}'",
+            declaration.name, key
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::Parameter;
+
+    fn transfer() -> EventDeclaration {
+        EventDeclaration {
+            name: "Transfer".to_string(),
+            parameters: vec![
+                Parameter {
+                    name: "to".to_string(),
+                    param_type: Some("string".to_string()),
+                },
+                Parameter {
+                    name: "amount".to_string(),
+                    param_type: Some("int".to_string()),
+                },
+            ],
+        }
+    }
+
+    fn payload(entries: &[(&str, Value)]) -> HashMap<String, Value> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_payload() {
+        let decl = transfer();
+        let ok = payload(&[
+            ("to", Value::String("bob".into())),
+            ("amount", Value::Int(5)),
+        ]);
+        assert!(validate_payload(&decl, &ok).is_ok());
+
+        let missing = payload(&[("to", Value::String("bob".into()))]);
+        assert_eq!(
+            validate_payload(&decl, &missing).unwrap_err(),
+            "event 'Transfer' is missing field 'amount'"
+        );
+
+        let wrong_type = payload(&[
+            ("to", Value::String("bob".into())),
+            ("amount", Value::String("5".into())),
+        ]);
+        assert_eq!(
+            validate_payload(&decl, &wrong_type).unwrap_err(),
+            "event 'Transfer' field 'amount' expects int, got string"
+        );
+
+        let mut extra = ok.clone();
+        extra.insert("memo".to_string(), Value::Null);
+        assert_eq!(
+            validate_payload(&decl, &extra).unwrap_err(),
+            "event 'Transfer' has no field 'memo'"
+        );
+    }
+
+    #[test]
+    fn test_bus_subscriptions_and_log() {
+        let mut bus = EventBus::new();
+        let handler = EventHandler::ServiceMethod {
+            service: "Ledger".to_string(),
+            method: "on_transfer".to_string(),
+        };
+        bus.subscribe("Transfer", handler.clone());
+        bus.subscribe("Transfer", handler.clone());
+        assert_eq!(bus.handlers("Transfer"), vec![handler]);
+
+        bus.unsubscribe_service("Ledger");
+        assert!(bus.handlers("Transfer").is_empty());
+
+        bus.record("Transfer", HashMap::new(), None);
+        bus.record("Mint", HashMap::new(), Some("Token".to_string()));
+        assert_eq!(bus.log(None).len(), 2);
+        let mints = bus.log(Some("Mint"));
+        assert_eq!(mints.len(), 1);
+        assert_eq!(mints[0].sequence, 2);
+    }
+}
@@ -116,6 +116,62 @@ pub enum RuntimeError {
 
     #[error("Execution timeout: program exceeded maximum execution time")]
     ExecutionTimeout,
+
+    /// The task (by id) was cancelled, or the running task was and is unwinding.
+    #[error("Task {0} was cancelled")]
+    TaskCancelled(u64),
+
+    /// An `await` with a timeout (in milliseconds) that expired.
+    #[error("Await timed out after {0} ms")]
+    AwaitTimeout(u64),
+
+    /// A `chan::send` / `chan::recv` with a timeout (in milliseconds) that expired.
+    #[error("Channel operation timed out after {0} ms")]
+    ChannelTimeout(u64),
+
+    /// `chan::send` on a channel that has been closed.
+    #[error("Channel {0} is closed")]
+    ChannelClosed(u64),
+
+    /// A metered run used up one of the limits of its budget.
+    #[error("Execution budget exhausted: {resource} limit of {limit} reached ({usage})")]
+    BudgetExhausted {
+        resource: &'static str,
+        limit: u64,
+        usage: crate::runtime::metering::Usage,
+    },
+
+    /// `throw value`. The value is kept so `catch (e: MyError)` can match a thrown struct.
+    #[error("Thrown error: {0}")]
+    Thrown(Value),
+
+    /// An error raised by a standard-library namespace call (`chain::`, `ai::`, ...), tagged
+    /// with that namespace. Displays as the underlying error.
+    #[error("{error}")]
+    Stdlib {
+        namespace: String,
+        error: Box<RuntimeError>,
+    },
+
+    /// `expr?` on an `Err` / `None`: unwinds to the enclosing function, which returns the value.
+    #[error("`?` returned {0} outside a function")]
+    Propagate(Value),
+}
+
+/// `catch` types that match every error.
+const CATCH_ALL_TYPES: &[&str] = &["Error", "Exception", "any"];
+
+/// Error category for failures inside a stdlib namespace, if it has one.
+fn namespace_category(namespace: &str) -> Option<&'static str> {
+    match namespace {
+        "chain" | "add_sol" => Some("ChainError"),
+        "ai" | "assist" | "agent" => Some("AIError"),
+        "http" | "web" | "graph" | "mcp" | "oracle" => Some("NetworkError"),
+        "database" => Some("DatabaseError"),
+        "fs" => Some("IOError"),
+        "crypto" => Some("CryptoError"),
+        _ => None,
+    }
 }
 
 impl RuntimeError {
@@ -126,6 +182,91 @@ impl RuntimeError {
     pub fn function_not_found(name: String) -> Self {
         RuntimeError::FunctionNotFound(name)
     }
+
+    /// Tag an error from a `namespace::` call with its namespace when that namespace has an
+    /// error category. Throws, `?` unwinding, timeouts, cancellation and already-tagged errors
+    /// pass through.
+    pub fn in_namespace(self, namespace: &str) -> Self {
+        match self {
+            RuntimeError::Thrown(_)
+            | RuntimeError::Propagate(_)
+            | RuntimeError::Stdlib { .. }
+            | RuntimeError::ExecutionTimeout
+            | RuntimeError::TaskCancelled(_)
+            | RuntimeError::AwaitTimeout(_)
+            | RuntimeError::ChannelTimeout(_)
+            | RuntimeError::ChannelClosed(_)
+            | RuntimeError::BudgetExhausted { .. } => self,
+            error if namespace_category(namespace).is_some() => RuntimeError::Stdlib {
+                namespace: namespace.to_string(),
+                error: Box::new(error),
+            },
+            error => error,
+        }
+    }
+
+    /// The error without its namespace tag.
+    pub fn root(&self) -> &RuntimeError {
+        match self {
+            RuntimeError::Stdlib { error, .. } => error.root(),
+            other => other,
+        }
+    }
+
+    /// Names a typed `catch` clause can use for this error, most specific first: the struct
+    /// name of a thrown struct, the namespace category (`ChainError`, `NetworkError`, ...) and
+    /// the kind (`PermissionError`, `ValidationError`, ...).
+    pub fn categories(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        if let RuntimeError::Stdlib { namespace, .. } = self {
+            out.extend(namespace_category(namespace).map(str::to_string));
+        }
+        let kind = match self.root() {
+            RuntimeError::Thrown(Value::Struct(name, _)) => {
+                out.push(name.clone());
+                "ThrownError"
+            }
+            RuntimeError::Thrown(_) => "ThrownError",
+            RuntimeError::PermissionDenied(_)
+            | RuntimeError::AccessDenied
+            | RuntimeError::ReadOnlyViolation
+            | RuntimeError::ReentrancyDetected(_) => "PermissionError",
+            RuntimeError::ArgumentCountMismatch { .. }
+            | RuntimeError::TypeError { .. }
+            | RuntimeError::TypeMismatch(_)
+            | RuntimeError::UnsupportedOperation(_) => "ValidationError",
+            RuntimeError::DivisionByZero
+            | RuntimeError::IntegerOverflow
+            | RuntimeError::IntegerUnderflow => "ArithmeticError",
+            RuntimeError::VariableNotFound(_) | RuntimeError::FunctionNotFound(_) => {
+                "NotFoundError"
+            }
+            RuntimeError::ExecutionTimeout
+            | RuntimeError::AwaitTimeout(_)
+            | RuntimeError::ChannelTimeout(_) => "TimeoutError",
+            RuntimeError::TaskCancelled(_) => "CancelledError",
+            RuntimeError::ChannelClosed(_) => "ChannelClosedError",
+            RuntimeError::BudgetExhausted { .. } => "BudgetError",
+            _ => "RuntimeError",
+        };
+        out.push(kind.to_string());
+        out
+    }
+
+    /// Whether `catch (e: type_name)` catches this error. `?` unwinding is never caught.
+    pub fn matches_catch(&self, type_name: &str) -> bool {
+        !matches!(self, RuntimeError::Propagate(_))
+            && (CATCH_ALL_TYPES.contains(&type_name)
+                || self.categories().iter().any(|c| c == type_name))
+    }
+
+    /// What a `catch` variable is bound to: the thrown value, or the error message.
+    pub fn caught_value(&self) -> Value {
+        match self.root() {
+            RuntimeError::Thrown(value) => value.clone(),
+            _ => Value::String(self.to_string()),
+        }
+    }
 }
 
 /// Source location for error reporting (line/column; optional file path).
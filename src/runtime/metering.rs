@@ -0,0 +1,124 @@
+//! Metered execution: evaluation steps, allocated collection items and call depth counted
+//! against a [`Budget`], the way gas is.
+//!
+//! A step is one bytecode instruction, or one statement or expression the tree-walker
+//! evaluates, so a program uses the same units on every run and fails at the same point when
+//! its budget runs out. Allocation counts the items of every list, array, map and set a
+//! literal, range or stdlib call creates, and the growth of collections changed in place by a
+//! method. A [`Meter`] is shared with the tasks a metered runtime spawns, so they draw from one
+//! budget.
+
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::values::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Limits of a metered run; `None` leaves that resource unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Budget {
+    pub max_steps: Option<u64>,
+    pub max_allocated_items: Option<u64>,
+    pub max_call_depth: Option<u64>,
+}
+
+impl Budget {
+    /// What `@trust("decentralized")` service methods run with unless their caller is metered.
+    pub const DECENTRALIZED: Budget = Budget {
+        max_steps: Some(1_000_000),
+        max_allocated_items: Some(1_000_000),
+        max_call_depth: Some(64),
+    };
+}
+
+/// Units consumed by a metered run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub steps: u64,
+    pub allocated_items: u64,
+    /// Deepest call stack reached.
+    pub call_depth: u64,
+}
+
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} steps, {} items allocated, call depth {}",
+            self.steps, self.allocated_items, self.call_depth
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Meter {
+    budget: Budget,
+    steps: AtomicU64,
+    allocated_items: AtomicU64,
+    call_depth: AtomicU64,
+}
+
+impl Meter {
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            steps: AtomicU64::new(0),
+            allocated_items: AtomicU64::new(0),
+            call_depth: AtomicU64::new(0),
+        }
+    }
+
+    pub fn budget(&self) -> Budget {
+        self.budget
+    }
+
+    pub fn usage(&self) -> Usage {
+        Usage {
+            steps: self.steps.load(Ordering::Relaxed),
+            allocated_items: self.allocated_items.load(Ordering::Relaxed),
+            call_depth: self.call_depth.load(Ordering::Relaxed),
+        }
+    }
+
+    fn exhausted(&self, resource: &'static str, limit: u64) -> RuntimeError {
+        RuntimeError::BudgetExhausted {
+            resource,
+            limit,
+            usage: self.usage(),
+        }
+    }
+
+    /// Count one step taken at `call_depth`.
+    pub fn step(&self, call_depth: usize) -> Result<(), RuntimeError> {
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+        let depth = call_depth as u64;
+        self.call_depth.fetch_max(depth, Ordering::Relaxed);
+        if let Some(limit) = self.budget.max_call_depth.filter(|limit| depth > *limit) {
+            return Err(self.exhausted("call depth", limit));
+        }
+        match self.budget.max_steps {
+            Some(limit) if steps > limit => Err(self.exhausted("steps", limit)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Count `items` newly allocated collection items.
+    pub fn allocate(&self, items: u64) -> Result<(), RuntimeError> {
+        if items == 0 {
+            return Ok(());
+        }
+        let allocated = self.allocated_items.fetch_add(items, Ordering::Relaxed) + items;
+        match self.budget.max_allocated_items {
+            Some(limit) if allocated > limit => Err(self.exhausted("allocated items", limit)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Items held directly by a collection value; 0 for anything else.
+pub fn collection_items(value: &Value) -> u64 {
+    match value {
+        Value::List(items) | Value::Array(items) => items.len() as u64,
+        Value::Map(map) => map.len() as u64,
+        Value::Set(set) => set.len() as u64,
+        _ => 0,
+    }
+}
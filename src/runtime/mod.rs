@@ -1,17 +1,32 @@
 pub mod advanced_security;
+pub mod bytecode;
+pub mod channels;
 pub mod control_flow;
+pub mod debugger;
 pub mod engine;
+pub mod events;
 pub mod functions;
+pub mod metering;
+pub mod numeric;
 pub mod reentrancy;
 pub mod safe_math;
 pub mod scope;
 pub mod state_isolation;
+pub mod tasks;
 pub mod transaction;
+pub mod type_checker;
 pub mod types;
 pub mod values;
+pub mod vm;
+#[cfg(feature = "wasm-runtime")]
+pub mod wasm_host;
 
+pub use debugger::{ConsoleDebugger, DebugHook, Debugger};
 pub use engine::Runtime;
+pub use events::{EventBus, EventHandler, PublishedEvent};
 pub use functions::{CallFrameInfo, RuntimeError, RuntimeErrorWithContext, SourceLocation};
+pub use numeric::{Decimal, I256, U256};
+pub use type_checker::{check_program, TypeDiagnostic};
 
 // Re-export security modules for testing and external use
 pub use control_flow::{ControlFlow, StatementOutcome, StatementResult};
@@ -26,3 +41,5 @@ pub use transaction::{
 
 #[cfg(feature = "sqlite-storage")]
 pub use transaction::SqliteStorage;
+#[cfg(feature = "wasm-runtime")]
+pub use wasm_host::WasmServices;
@@ -0,0 +1,693 @@
+//! Wide numeric values: `u256`, `i256` and the fixed-point `decimal`.
+//!
+//! `u256` is [`U256`] from `primitive-types`. `i256` is a sign and a magnitude over it; two's
+//! complement only appears at the ABI boundary ([`I256::to_twos_complement`]). `decimal` is an
+//! `i256` scaled by 10^18, the "wad" representation Solidity code uses for token amounts, so a
+//! decimal crosses the ABI as that scaled `int256`.
+//!
+//! Arithmetic never wraps: a result outside the type's range is `IntegerOverflow` (or
+//! `IntegerUnderflow` for subtraction and negative-to-unsigned conversions), like `SafeMath`
+//! does for `int`.
+
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::values::Value;
+use primitive_types::U512;
+use std::cmp::Ordering;
+use std::fmt;
+
+pub use primitive_types::U256;
+
+/// Fractional digits carried by a `decimal`.
+pub const DECIMAL_PLACES: usize = 18;
+
+fn decimal_scale() -> U256 {
+    U256::exp10(DECIMAL_PLACES)
+}
+
+/// 2^255: the magnitude of `i256::MIN`, one past `i256::MAX`.
+fn i256_limit() -> U256 {
+    U256::one() << 255
+}
+
+/// Signed 256-bit integer. Zero is never negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct I256 {
+    negative: bool,
+    magnitude: U256,
+}
+
+impl I256 {
+    pub const ZERO: I256 = I256 {
+        negative: false,
+        magnitude: U256([0; 4]),
+    };
+
+    /// `-magnitude` or `magnitude`, if it is within range.
+    pub fn new(negative: bool, magnitude: U256) -> Option<I256> {
+        let limit = i256_limit();
+        if magnitude.is_zero() {
+            Some(I256::ZERO)
+        } else if magnitude > limit || (!negative && magnitude == limit) {
+            None
+        } else {
+            Some(I256 {
+                negative,
+                magnitude,
+            })
+        }
+    }
+
+    pub fn from_i64(n: i64) -> I256 {
+        I256 {
+            negative: n < 0,
+            magnitude: U256::from(n.unsigned_abs()),
+        }
+    }
+
+    pub fn from_u256(n: U256) -> Option<I256> {
+        I256::new(false, n)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    pub fn magnitude(&self) -> U256 {
+        self.magnitude
+    }
+
+    pub fn to_u256(&self) -> Option<U256> {
+        (!self.negative).then_some(self.magnitude)
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude > U256::from(u64::MAX) {
+            return None;
+        }
+        let magnitude = self.magnitude.low_u64();
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// Read an ABI `int256` word.
+    pub fn from_twos_complement(word: U256) -> I256 {
+        if word.bit(255) {
+            I256 {
+                negative: true,
+                magnitude: (!word).overflowing_add(U256::one()).0,
+            }
+        } else {
+            I256 {
+                negative: false,
+                magnitude: word,
+            }
+        }
+    }
+
+    /// The ABI `int256` word for this value.
+    pub fn to_twos_complement(&self) -> U256 {
+        if self.negative {
+            (!self.magnitude).overflowing_add(U256::one()).0
+        } else {
+            self.magnitude
+        }
+    }
+
+    /// Parse `123` or `-123`.
+    pub fn from_dec_str(text: &str) -> Option<I256> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        I256::new(negative, parse_digits(digits)?)
+    }
+
+    pub fn checked_neg(self) -> Option<I256> {
+        I256::new(!self.negative, self.magnitude)
+    }
+
+    fn add_signed(self, negative: bool, magnitude: U256) -> Option<I256> {
+        if self.negative == negative {
+            I256::new(negative, self.magnitude.checked_add(magnitude)?)
+        } else if self.magnitude >= magnitude {
+            I256::new(self.negative, self.magnitude - magnitude)
+        } else {
+            I256::new(negative, magnitude - self.magnitude)
+        }
+    }
+
+    pub fn checked_add(self, other: I256) -> Option<I256> {
+        self.add_signed(other.negative, other.magnitude)
+    }
+
+    pub fn checked_sub(self, other: I256) -> Option<I256> {
+        self.add_signed(!other.negative, other.magnitude)
+    }
+
+    pub fn checked_mul(self, other: I256) -> Option<I256> {
+        I256::new(
+            self.negative != other.negative,
+            self.magnitude.checked_mul(other.magnitude)?,
+        )
+    }
+
+    /// Division truncating toward zero, as in Solidity. `None` on a zero divisor.
+    pub fn checked_div(self, other: I256) -> Option<I256> {
+        if other.is_zero() {
+            return None;
+        }
+        I256::new(
+            self.negative != other.negative,
+            self.magnitude / other.magnitude,
+        )
+    }
+
+    /// Remainder with the sign of the dividend. `None` on a zero divisor.
+    pub fn checked_rem(self, other: I256) -> Option<I256> {
+        if other.is_zero() {
+            return None;
+        }
+        I256::new(self.negative, self.magnitude % other.magnitude)
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for I256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.magnitude)
+    }
+}
+
+/// Fixed-point number with [`DECIMAL_PLACES`] fractional digits, stored as a scaled `i256`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Decimal(I256);
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal(I256::ZERO);
+
+    /// The decimal whose scaled representation is `raw` (`1.5` is `1500000000000000000`).
+    pub fn from_raw(raw: I256) -> Decimal {
+        Decimal(raw)
+    }
+
+    pub fn raw(&self) -> I256 {
+        self.0
+    }
+
+    pub fn from_i256(n: I256) -> Option<Decimal> {
+        n.checked_mul(I256::from_u256(decimal_scale())?)
+            .map(Decimal)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    pub fn is_integral(&self) -> bool {
+        (self.0.magnitude() % decimal_scale()).is_zero()
+    }
+
+    /// The integer part, truncated toward zero.
+    pub fn trunc(&self) -> I256 {
+        I256::new(self.0.is_negative(), self.0.magnitude() / decimal_scale()).unwrap_or(I256::ZERO)
+    }
+
+    /// Parse `12`, `-0.5` or `3.1415`; at most [`DECIMAL_PLACES`] fractional digits.
+    pub fn from_str_exact(text: &str) -> Option<Decimal> {
+        let (negative, body) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (whole, fraction) = body.split_once('.').unwrap_or((body, ""));
+        if fraction.len() > DECIMAL_PLACES || (body.contains('.') && fraction.is_empty()) {
+            return None;
+        }
+        let whole = parse_digits(whole)?;
+        let fraction = if fraction.is_empty() {
+            U256::zero()
+        } else {
+            parse_digits(fraction)? * U256::exp10(DECIMAL_PLACES - fraction.len())
+        };
+        let magnitude = whole.checked_mul(decimal_scale())?.checked_add(fraction)?;
+        I256::new(negative, magnitude).map(Decimal)
+    }
+
+    /// Nearest decimal to a finite float (digits past [`DECIMAL_PLACES`] are dropped).
+    pub fn from_f64(value: f64) -> Option<Decimal> {
+        if !value.is_finite() {
+            return None;
+        }
+        let text = value.to_string();
+        let text = match text.split_once('.') {
+            Some((whole, fraction)) if fraction.len() > DECIMAL_PLACES => {
+                format!("{}.{}", whole, &fraction[..DECIMAL_PLACES])
+            }
+            _ => text,
+        };
+        Decimal::from_str_exact(&text)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    pub fn checked_neg(self) -> Option<Decimal> {
+        self.0.checked_neg().map(Decimal)
+    }
+
+    pub fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_add(other.0).map(Decimal)
+    }
+
+    pub fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_sub(other.0).map(Decimal)
+    }
+
+    /// Product truncated to [`DECIMAL_PLACES`]; the intermediate is 512 bits wide.
+    pub fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product =
+            self.0.magnitude().full_mul(other.0.magnitude()) / U512::from(decimal_scale());
+        let magnitude = U256::try_from(product).ok()?;
+        I256::new(self.is_negative() != other.is_negative(), magnitude).map(Decimal)
+    }
+
+    /// Quotient truncated to [`DECIMAL_PLACES`]. `None` on a zero divisor.
+    pub fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        if other.0.is_zero() {
+            return None;
+        }
+        let quotient =
+            self.0.magnitude().full_mul(decimal_scale()) / U512::from(other.0.magnitude());
+        let magnitude = U256::try_from(quotient).ok()?;
+        I256::new(self.is_negative() != other.is_negative(), magnitude).map(Decimal)
+    }
+
+    pub fn checked_rem(self, other: Decimal) -> Option<Decimal> {
+        self.0.checked_rem(other.0).map(Decimal)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let magnitude = self.0.magnitude();
+        if self.0.is_negative() {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", magnitude / decimal_scale())?;
+        let fraction = format!(
+            "{:0width$}",
+            magnitude % decimal_scale(),
+            width = DECIMAL_PLACES
+        );
+        let fraction = fraction.trim_end_matches('0');
+        if !fraction.is_empty() {
+            write!(f, ".{}", fraction)?;
+        }
+        Ok(())
+    }
+}
+
+/// A non-empty run of ASCII digits.
+fn parse_digits(digits: &str) -> Option<U256> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    U256::from_dec_str(digits).ok()
+}
+
+/// Serialize wide values as their decimal text so stored state stays readable.
+macro_rules! string_serde {
+    ($module:ident, $ty:ty, $parse:expr) => {
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: serde::Serializer>(
+                value: &$ty,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(value)
+            }
+
+            pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<$ty, D::Error> {
+                let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+                let parse: fn(&str) -> Option<$ty> = $parse;
+                parse(&text).ok_or_else(|| {
+                    serde::de::Error::custom(format!("invalid {} '{}'", stringify!($module), text))
+                })
+            }
+        }
+    };
+}
+
+string_serde!(u256_text, U256, parse_digits);
+string_serde!(i256_text, I256, I256::from_dec_str);
+string_serde!(decimal_text, Decimal, Decimal::from_str_exact);
+
+/// Arithmetic operator applied by [`arith`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Op {
+    fn describe(self) -> &'static str {
+        match self {
+            Op::Add => "addition",
+            Op::Sub => "subtraction",
+            Op::Mul => "multiplication",
+            Op::Div => "division",
+            Op::Rem => "modulo",
+        }
+    }
+
+    /// Error for a result outside the target type's range.
+    fn out_of_range(self) -> RuntimeError {
+        match self {
+            Op::Sub => RuntimeError::IntegerUnderflow,
+            _ => RuntimeError::IntegerOverflow,
+        }
+    }
+}
+
+/// True for `u256`, `i256` and `decimal` values.
+pub fn is_wide(value: &Value) -> bool {
+    matches!(value, Value::U256(_) | Value::I256(_) | Value::Decimal(_))
+}
+
+/// Common type of a mixed operation, ordered so the larger kind wins: an `int` widens to the
+/// other operand's type, `u256` and `i256` meet at `i256`, and anything meets `decimal` at
+/// `decimal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Int,
+    U256,
+    I256,
+    Decimal,
+}
+
+fn kind(value: &Value) -> Option<Kind> {
+    match value {
+        Value::Int(_) => Some(Kind::Int),
+        Value::U256(_) => Some(Kind::U256),
+        Value::I256(_) => Some(Kind::I256),
+        Value::Decimal(_) => Some(Kind::Decimal),
+        _ => None,
+    }
+}
+
+/// Convert an `int`, `u256`, `i256` or integral `decimal` to `u256`.
+pub fn to_u256(value: &Value) -> Result<U256, RuntimeError> {
+    match value {
+        Value::U256(n) => Ok(*n),
+        Value::Int(n) => u64::try_from(*n)
+            .map(U256::from)
+            .map_err(|_| RuntimeError::IntegerUnderflow),
+        Value::I256(n) => n.to_u256().ok_or(RuntimeError::IntegerUnderflow),
+        Value::Decimal(d) => to_u256(&Value::I256(integral_part(d)?)),
+        other => Err(conversion_error("u256", other)),
+    }
+}
+
+/// Convert an `int`, `u256`, `i256` or integral `decimal` to `i256`.
+pub fn to_i256(value: &Value) -> Result<I256, RuntimeError> {
+    match value {
+        Value::I256(n) => Ok(*n),
+        Value::Int(n) => Ok(I256::from_i64(*n)),
+        Value::U256(n) => I256::from_u256(*n).ok_or(RuntimeError::IntegerOverflow),
+        Value::Decimal(d) => integral_part(d),
+        other => Err(conversion_error("i256", other)),
+    }
+}
+
+/// Convert any numeric value to `decimal`. Floats take their shortest decimal form.
+pub fn to_decimal(value: &Value) -> Result<Decimal, RuntimeError> {
+    match value {
+        Value::Decimal(d) => Ok(*d),
+        Value::Float(f) => Decimal::from_f64(*f).ok_or(RuntimeError::IntegerOverflow),
+        Value::Int(_) | Value::U256(_) | Value::I256(_) => {
+            Decimal::from_i256(to_i256(value)?).ok_or(RuntimeError::IntegerOverflow)
+        }
+        other => Err(conversion_error("decimal", other)),
+    }
+}
+
+/// Convert a wide value back to `int` (a `decimal` is truncated toward zero).
+pub fn to_i64(value: &Value) -> Result<i64, RuntimeError> {
+    let wide = match value {
+        Value::Decimal(d) => d.trunc(),
+        other => to_i256(other)?,
+    };
+    wide.to_i64().ok_or(RuntimeError::IntegerOverflow)
+}
+
+/// `n` as `i64`, saturating at `i64::MAX` (for legacy i64 amount APIs).
+pub fn saturating_i64(n: U256) -> i64 {
+    if n > U256::from(i64::MAX as u64) {
+        i64::MAX
+    } else {
+        n.as_u64() as i64
+    }
+}
+
+fn integral_part(d: &Decimal) -> Result<I256, RuntimeError> {
+    if d.is_integral() {
+        Ok(d.trunc())
+    } else {
+        Err(RuntimeError::General(format!(
+            "decimal {} has a fractional part; truncate it with to_int() or keep it a decimal",
+            d
+        )))
+    }
+}
+
+fn conversion_error(target: &str, value: &Value) -> RuntimeError {
+    RuntimeError::TypeError {
+        expected: format!("a number convertible to {}", target),
+        got: value.type_name().to_string(),
+    }
+}
+
+/// Parse the text accepted by the `u256(...)` / `i256(...)` / `decimal(...)` builtins:
+/// decimal digits (with a sign where the type has one), or `0x` hex for `u256`.
+pub fn parse_wide(target: &str, text: &str) -> Result<Value, RuntimeError> {
+    let text = text.trim();
+    let parsed = match target {
+        "u256" => match text.strip_prefix("0x") {
+            Some(hex) if !hex.is_empty() => hex.parse::<U256>().ok(),
+            _ => parse_digits(text),
+        }
+        .map(Value::U256),
+        "i256" => I256::from_dec_str(text).map(Value::I256),
+        _ => Decimal::from_str_exact(text).map(Value::Decimal),
+    };
+    parsed.ok_or_else(|| {
+        RuntimeError::General(format!("'{}' is not a valid {} literal", text, target))
+    })
+}
+
+/// `u256(...)`, `i256(...)` and `decimal(...)`: convert a number or parse a string.
+pub fn convert(target: &str, value: &Value) -> Result<Value, RuntimeError> {
+    match (target, value) {
+        (_, Value::String(text)) => parse_wide(target, text),
+        ("u256", other) => to_u256(other).map(Value::U256),
+        ("i256", other) => to_i256(other).map(Value::I256),
+        (_, other) => to_decimal(other).map(Value::Decimal),
+    }
+}
+
+/// A service field declared `u256` / `i256` / `decimal` holds that type even when its
+/// initializer is an `int` (`total: u256 = 0;`), so later arithmetic is 256-bit.
+pub fn coerce_to_annotation(annotation: &str, value: Value) -> Result<Value, RuntimeError> {
+    let target = match annotation.trim() {
+        "u256" | "uint256" => "u256",
+        "i256" | "int256" => "i256",
+        "decimal" => "decimal",
+        _ => return Ok(value),
+    };
+    if kind(&value).is_some() {
+        convert(target, &value)
+    } else {
+        Ok(value)
+    }
+}
+
+/// `left op right` when either side is wide; `None` leaves the operation to `SafeMath`'s
+/// `int` / `float` rules. Floats never mix with wide values implicitly (that would round), so
+/// `decimal(x)` is required first.
+pub(crate) fn arith(op: Op, left: &Value, right: &Value) -> Option<Result<Value, RuntimeError>> {
+    if !is_wide(left) && !is_wide(right) {
+        return None;
+    }
+    let (Some(l), Some(r)) = (kind(left), kind(right)) else {
+        return Some(Err(RuntimeError::TypeMismatch(op.describe().to_string())));
+    };
+    Some(match l.max(r) {
+        Kind::U256 => to_u256(left).and_then(|a| {
+            let b = to_u256(right)?;
+            let result = match op {
+                Op::Add => a.checked_add(b),
+                Op::Sub => a.checked_sub(b),
+                Op::Mul => a.checked_mul(b),
+                Op::Div | Op::Rem if b.is_zero() => return Err(RuntimeError::DivisionByZero),
+                Op::Div => a.checked_div(b),
+                Op::Rem => a.checked_rem(b),
+            };
+            result.map(Value::U256).ok_or(op.out_of_range())
+        }),
+        Kind::I256 | Kind::Int => to_i256(left).and_then(|a| {
+            let b = to_i256(right)?;
+            let result = match op {
+                Op::Add => a.checked_add(b),
+                Op::Sub => a.checked_sub(b),
+                Op::Mul => a.checked_mul(b),
+                Op::Div | Op::Rem if b.is_zero() => return Err(RuntimeError::DivisionByZero),
+                Op::Div => a.checked_div(b),
+                Op::Rem => a.checked_rem(b),
+            };
+            result.map(Value::I256).ok_or(op.out_of_range())
+        }),
+        Kind::Decimal => to_decimal(left).and_then(|a| {
+            let b = to_decimal(right)?;
+            let result = match op {
+                Op::Add => a.checked_add(b),
+                Op::Sub => a.checked_sub(b),
+                Op::Mul => a.checked_mul(b),
+                Op::Div | Op::Rem if b.raw().is_zero() => return Err(RuntimeError::DivisionByZero),
+                Op::Div => a.checked_div(b),
+                Op::Rem => a.checked_rem(b),
+            };
+            result.map(Value::Decimal).ok_or(op.out_of_range())
+        }),
+    })
+}
+
+/// `-value` for a wide value; only zero negates as a `u256`.
+pub(crate) fn negate(value: &Value) -> Option<Result<Value, RuntimeError>> {
+    Some(match value {
+        Value::U256(n) if n.is_zero() => Ok(Value::U256(*n)),
+        Value::U256(_) => Err(RuntimeError::IntegerUnderflow),
+        Value::I256(n) => n
+            .checked_neg()
+            .map(Value::I256)
+            .ok_or(RuntimeError::IntegerOverflow),
+        Value::Decimal(d) => d
+            .checked_neg()
+            .map(Value::Decimal)
+            .ok_or(RuntimeError::IntegerOverflow),
+        _ => return None,
+    })
+}
+
+/// Exact ordering of two numbers when either is wide, across `int`, `u256`, `i256` and
+/// `decimal`. `None` when neither is wide or the other side is not one of those.
+pub(crate) fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    if !is_wide(left) && !is_wide(right) {
+        return None;
+    }
+    // Every such value is an integer multiple of 10^-18 that fits 512 bits once scaled.
+    fn scaled(value: &Value) -> Option<(bool, U512)> {
+        let (negative, magnitude) = match value {
+            Value::Decimal(d) => return Some((d.is_negative(), U512::from(d.raw().magnitude()))),
+            Value::U256(n) => (false, *n),
+            Value::I256(n) => (n.is_negative(), n.magnitude()),
+            Value::Int(n) => (*n < 0, U256::from(n.unsigned_abs())),
+            _ => return None,
+        };
+        Some((negative, magnitude.full_mul(decimal_scale())))
+    }
+    let (a_negative, a) = scaled(left)?;
+    let (b_negative, b) = scaled(right)?;
+    Some(match (a_negative, b_negative) {
+        (false, true) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        (false, false) => a.cmp(&b),
+        (true, true) => b.cmp(&a),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        Decimal::from_str_exact(text).unwrap()
+    }
+
+    #[test]
+    fn test_i256_range_and_twos_complement() {
+        let max = I256::new(false, i256_limit() - 1).unwrap();
+        let min = I256::new(true, i256_limit()).unwrap();
+        assert!(I256::new(false, i256_limit()).is_none());
+        assert_eq!(max.checked_add(I256::from_i64(1)), None);
+        assert_eq!(min.checked_sub(I256::from_i64(1)), None);
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(I256::from_i64(-1).checked_sub(min), Some(max));
+        assert_eq!(I256::from_i64(-1).to_twos_complement(), U256::MAX);
+        assert_eq!(I256::from_twos_complement(min.to_twos_complement()), min);
+        assert_eq!(
+            I256::from_i64(-7).checked_div(I256::from_i64(2)),
+            Some(I256::from_i64(-3))
+        );
+        assert_eq!(
+            I256::from_i64(-7).checked_rem(I256::from_i64(2)),
+            Some(I256::from_i64(-1))
+        );
+        assert_eq!(I256::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert!(I256::from_i64(-3) < I256::from_i64(2));
+    }
+
+    #[test]
+    fn test_decimal_parse_display_and_arithmetic() {
+        assert_eq!(dec("1.50").to_string(), "1.5");
+        assert_eq!(
+            dec("-0.000000000000000001").to_string(),
+            "-0.000000000000000001"
+        );
+        assert_eq!(dec("42").to_string(), "42");
+        assert!(Decimal::from_str_exact("1.").is_none());
+        assert!(Decimal::from_str_exact("0.0000000000000000001").is_none());
+        assert_eq!(dec("1.5").checked_mul(dec("-2.25")), Some(dec("-3.375")));
+        assert_eq!(
+            dec("1").checked_div(dec("3")).unwrap().to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(dec("-7.5").trunc(), I256::from_i64(-7));
+        assert_eq!(Decimal::from_f64(0.1), Some(dec("0.1")));
+        assert_eq!(
+            dec("2.5").raw(),
+            I256::from_u256(U256::from(2_500_000_000_000_000_000u64)).unwrap()
+        );
+    }
+}
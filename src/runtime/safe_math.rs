@@ -1,6 +1,8 @@
 use crate::runtime::functions::RuntimeError;
+use crate::runtime::numeric::{self, Op};
 /// Safe Mathematics Operations for DAL Runtime
 /// Provides overflow/underflow protection for all arithmetic operations
+/// (`u256` / `i256` / `decimal` operands are handled by `runtime::numeric`)
 use crate::runtime::values::Value;
 
 /// Safe arithmetic operations with overflow/underflow protection
@@ -9,6 +11,9 @@ pub struct SafeMath;
 impl SafeMath {
     /// Safe addition with overflow checking
     pub fn add(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = numeric::arith(Op::Add, left, right) {
+            return result;
+        }
         match (left, right) {
             (Value::Int(a), Value::Int(b)) => a
                 .checked_add(*b)
@@ -44,6 +49,9 @@ impl SafeMath {
 
     /// Safe subtraction with underflow checking
     pub fn subtract(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = numeric::arith(Op::Sub, left, right) {
+            return result;
+        }
         match (left, right) {
             (Value::Int(a), Value::Int(b)) => a
                 .checked_sub(*b)
@@ -79,6 +87,9 @@ impl SafeMath {
 
     /// Safe multiplication with overflow checking
     pub fn multiply(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = numeric::arith(Op::Mul, left, right) {
+            return result;
+        }
         match (left, right) {
             (Value::Int(a), Value::Int(b)) => a
                 .checked_mul(*b)
@@ -114,6 +125,9 @@ impl SafeMath {
 
     /// Safe division with overflow and division by zero checking
     pub fn divide(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = numeric::arith(Op::Div, left, right) {
+            return result;
+        }
         match (left, right) {
             (Value::Int(a), Value::Int(b)) => {
                 if *b == 0 {
@@ -162,6 +176,9 @@ impl SafeMath {
 
     /// Safe modulo operation
     pub fn modulo(left: &Value, right: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = numeric::arith(Op::Rem, left, right) {
+            return result;
+        }
         match (left, right) {
             (Value::Int(a), Value::Int(b)) => {
                 if *b == 0 {
@@ -314,4 +331,45 @@ mod tests {
         let result = SafeMath::add(&int_val, &float_val).unwrap();
         assert_eq!(result, Value::Float(15.5));
     }
+
+    #[test]
+    fn test_wide_arithmetic_is_checked() {
+        use crate::runtime::numeric::{Decimal, I256, U256};
+
+        let max = Value::U256(U256::MAX);
+        let one = Value::Int(1);
+        assert!(matches!(
+            SafeMath::add(&max, &one),
+            Err(RuntimeError::IntegerOverflow)
+        ));
+        assert!(matches!(
+            SafeMath::subtract(&Value::U256(U256::zero()), &one),
+            Err(RuntimeError::IntegerUnderflow)
+        ));
+        assert_eq!(
+            SafeMath::subtract(&max, &one).unwrap(),
+            Value::U256(U256::MAX - 1)
+        );
+        assert_eq!(
+            SafeMath::multiply(
+                &Value::I256(I256::from_i64(-3)),
+                &Value::U256(U256::from(4))
+            )
+            .unwrap(),
+            Value::I256(I256::from_i64(-12))
+        );
+        let half = Value::Decimal(Decimal::from_str_exact("0.5").unwrap());
+        assert_eq!(
+            SafeMath::divide(&one, &half).unwrap(),
+            Value::Decimal(Decimal::from_str_exact("2").unwrap())
+        );
+        assert!(matches!(
+            SafeMath::modulo(&max, &Value::U256(U256::zero())),
+            Err(RuntimeError::DivisionByZero)
+        ));
+        assert!(matches!(
+            SafeMath::add(&half, &Value::Float(0.5)),
+            Err(RuntimeError::TypeMismatch(_))
+        ));
+    }
 }
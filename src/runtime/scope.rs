@@ -26,6 +26,16 @@ impl Scope {
         self.variables.insert(name, value);
     }
 
+    /// Same as `set`, but only allocates the name when the variable is new.
+    pub fn assign(&mut self, name: &str, value: Value) {
+        match self.variables.get_mut(name) {
+            Some(slot) => *slot = value,
+            None => {
+                self.variables.insert(name.to_string(), value);
+            }
+        }
+    }
+
     pub fn get(&self, name: &str) -> Option<Value> {
         // First check current scope
         if let Some(value) = self.variables.get(name) {
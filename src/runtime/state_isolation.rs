@@ -547,7 +547,9 @@ impl IsolatedContractState {
             Value::Bool(_) => 4,
             Value::Int(_) => 8,
             Value::Float(_) => 8,
+            Value::U256(_) | Value::I256(_) | Value::Decimal(_) => 32,
             Value::String(s) => s.len(),
+            Value::Bytes(b) => b.len(),
             Value::List(arr) => {
                 arr.iter()
                     .map(|v| self.estimate_value_size(v))
@@ -566,9 +568,7 @@ impl IsolatedContractState {
                     .sum::<usize>()
                     + (arr.len() * 4)
             }
-            Value::Result(ok, err) => {
-                self.estimate_value_size(ok) + self.estimate_value_size(err) + 8
-            }
+            Value::Result(Ok(v) | Err(v)) => self.estimate_value_size(v) + 8,
             Value::Option(Some(v)) => self.estimate_value_size(v) + 4,
             Value::Option(None) => 4,
             Value::Set(s) => s.iter().map(|v| v.len()).sum::<usize>() + (s.len() * 4),
@@ -581,6 +581,16 @@ impl IsolatedContractState {
                     + (fields.len() * 8)
             }
             Value::Closure(id) => id.len() + 8,
+            Value::Task(_) | Value::Channel(_) => 8,
+            Value::Enum(enum_name, variant, payload) => {
+                enum_name.len()
+                    + variant.len()
+                    + payload
+                        .iter()
+                        .map(|v| self.estimate_value_size(v))
+                        .sum::<usize>()
+                    + (payload.len() * 4)
+            }
         }
     }
 
@@ -614,7 +624,11 @@ impl IsolatedContractState {
             Value::Bool(b) => b.hash(hasher),
             Value::Int(i) => i.hash(hasher),
             Value::Float(f) => f.to_bits().hash(hasher),
+            Value::U256(n) => n.hash(hasher),
+            Value::I256(n) => n.hash(hasher),
+            Value::Decimal(d) => d.hash(hasher),
             Value::String(s) => s.hash(hasher),
+            Value::Bytes(b) => b.hash(hasher),
             Value::List(arr) => {
                 arr.len().hash(hasher);
                 for item in arr {
@@ -638,9 +652,13 @@ impl IsolatedContractState {
                     self.hash_value(item, hasher);
                 }
             }
-            Value::Result(ok, err) => {
-                self.hash_value(ok, hasher);
-                self.hash_value(err, hasher);
+            Value::Result(Ok(v)) => {
+                0u8.hash(hasher);
+                self.hash_value(v, hasher);
+            }
+            Value::Result(Err(v)) => {
+                1u8.hash(hasher);
+                self.hash_value(v, hasher);
             }
             Value::Option(Some(v)) => {
                 1u8.hash(hasher);
@@ -670,6 +688,15 @@ impl IsolatedContractState {
                 }
             }
             Value::Closure(id) => id.hash(hasher),
+            Value::Task(id) | Value::Channel(id) => id.hash(hasher),
+            Value::Enum(enum_name, variant, payload) => {
+                enum_name.hash(hasher);
+                variant.hash(hasher);
+                payload.len().hash(hasher);
+                for item in payload {
+                    self.hash_value(item, hasher);
+                }
+            }
         }
     }
 
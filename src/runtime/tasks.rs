@@ -0,0 +1,532 @@
+//! Scheduler for `spawn`ed DAL tasks.
+//!
+//! Tasks run on one bounded, process-wide pool of worker threads (`DAL_TASK_THREADS`, default
+//! the number of CPUs, at least 4) instead of an OS thread each. A thread that awaits, without
+//! a deadline, a task which has not started runs it itself, so nested `spawn` / `await` cannot
+//! starve the pool; a race stalled on a busy pool starts its candidates on helper threads
+//! instead, since the loser may never finish. Cancellation is cooperative: the
+//! runtime executing a task checks `is_cancelled` as it evaluates, and a task that finishes
+//! cancels the children it left running. Handles (`Value::Task`) are ids into a process-wide
+//! table, so the runtime of any task can await or cancel them; a result nobody awaits is
+//! dropped from it once `MAX_UNJOINED` later tasks have finished.
+
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::values::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub type TaskResult = Result<Value, RuntimeError>;
+
+/// The body of a task; it gets the task so the runtime it creates can check for cancellation
+/// and parent the tasks it spawns.
+pub type Job = Box<dyn FnOnce(&Arc<Task>) -> TaskResult + Send>;
+
+/// Longest a waiting thread sleeps before looking for queued work or its own cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Fewest worker threads when `DAL_TASK_THREADS` is unset, so small machines still overlap
+/// tasks that sleep or block on I/O.
+const MIN_WORKERS: usize = 4;
+
+/// Most helper threads `help_if_stalled` and races keep running at once.
+const MAX_HELPERS: usize = 256;
+
+/// How many later tasks may finish before the result of one nobody awaited is dropped.
+const MAX_UNJOINED: usize = 4096;
+
+enum TaskState {
+    Queued(Job),
+    Running,
+    Finished(TaskResult),
+    /// The result has been taken by an await.
+    Joined,
+    /// Cancelled before it started.
+    Cancelled,
+}
+
+pub struct Task {
+    pub id: u64,
+    state: Mutex<TaskState>,
+    cancelled: AtomicBool,
+    /// Tasks spawned while this one ran; cancelled with it, and when it finishes.
+    children: Mutex<Vec<Arc<Task>>>,
+}
+
+impl Task {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `queued`, `running`, `done`, `failed` or `cancelled`.
+    pub fn status(&self) -> &'static str {
+        let state = self.state.lock().unwrap();
+        match &*state {
+            TaskState::Cancelled => "cancelled",
+            _ if self.is_cancelled() => "cancelled",
+            TaskState::Queued(_) => "queued",
+            TaskState::Running => "running",
+            TaskState::Finished(Ok(_)) | TaskState::Joined => "done",
+            TaskState::Finished(Err(_)) => "failed",
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        !matches!(
+            &*self.state.lock().unwrap(),
+            TaskState::Queued(_) | TaskState::Running
+        )
+    }
+}
+
+struct Pool {
+    queue: Mutex<VecDeque<Arc<Task>>>,
+    work: Condvar,
+    size: usize,
+    /// Workers waiting for a task.
+    idle: AtomicUsize,
+    /// Helper threads started by `help_if_stalled` or a race that are still running.
+    helpers: AtomicUsize,
+}
+
+/// How a waiting thread gets the tasks it waits for started.
+#[derive(Clone, Copy)]
+enum Steal {
+    /// Run any that has not started itself: every one of them is needed.
+    Needed,
+    /// When no worker is idle to start them, start those that have not on helper threads:
+    /// the waiter must stay free to see the winner of a race and cancel the rest.
+    WhenStalled,
+}
+
+/// Every task that has not been awaited yet, by id.
+struct Registry {
+    tasks: Mutex<HashMap<u64, Arc<Task>>>,
+    /// Ids of the last `MAX_UNJOINED` tasks to finish, oldest first; some may be awaited since.
+    finished: Mutex<VecDeque<u64>>,
+    next_id: AtomicU64,
+    /// Signalled whenever a task finishes or is cancelled.
+    done_lock: Mutex<()>,
+    done: Condvar,
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Registry {
+        tasks: Mutex::new(HashMap::new()),
+        finished: Mutex::new(VecDeque::new()),
+        next_id: AtomicU64::new(1),
+        done_lock: Mutex::new(()),
+        done: Condvar::new(),
+    })
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let size = std::env::var("DAL_TASK_THREADS")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map_or(MIN_WORKERS, |n| n.get())
+                    .max(MIN_WORKERS)
+            });
+        for i in 0..size {
+            std::thread::Builder::new()
+                .name(format!("dal-task-{}", i))
+                .spawn(worker_loop)
+                .expect("failed to start task worker");
+        }
+        Pool {
+            queue: Mutex::new(VecDeque::new()),
+            work: Condvar::new(),
+            size,
+            idle: AtomicUsize::new(0),
+            helpers: AtomicUsize::new(0),
+        }
+    })
+}
+
+fn worker_loop() {
+    let pool = pool();
+    loop {
+        let task = {
+            let mut queue = pool.queue.lock().unwrap();
+            loop {
+                if let Some(task) = queue.pop_front() {
+                    break task;
+                }
+                pool.idle.fetch_add(1, Ordering::Relaxed);
+                queue = pool.work.wait(queue).unwrap();
+                pool.idle.fetch_sub(1, Ordering::Relaxed);
+            }
+        };
+        run(&task);
+    }
+}
+
+/// Worker threads in the pool.
+pub fn pool_size() -> usize {
+    pool().size
+}
+
+/// For a thread about to block on something other than a task, such as a channel: when no
+/// worker is idle and tasks are queued, one of which may be what would unblock it, start a
+/// helper thread that runs queued tasks until there are none. Helpers blocked in turn start
+/// more, up to `MAX_HELPERS`.
+pub fn help_if_stalled() {
+    let pool = pool();
+    if pool.idle.load(Ordering::Relaxed) > 0 || pool.queue.lock().unwrap().is_empty() {
+        return;
+    }
+    if !reserve_helper(pool) {
+        return;
+    }
+    std::thread::Builder::new()
+        .name("dal-helper".to_string())
+        .spawn(move || {
+            loop {
+                let next = pool.queue.lock().unwrap().pop_front();
+                match next {
+                    Some(task) => run(&task),
+                    None => break,
+                }
+            }
+            pool.helpers.fetch_sub(1, Ordering::Relaxed);
+        })
+        .expect("failed to start task helper");
+}
+
+/// Count one more running helper thread, unless `MAX_HELPERS` already are.
+fn reserve_helper(pool: &Pool) -> bool {
+    pool.helpers
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+            (n < MAX_HELPERS).then_some(n + 1)
+        })
+        .is_ok()
+}
+
+/// Queue `job` as a new task, a child of `parent` when given, and return its id.
+pub fn spawn(job: Job, parent: Option<&Arc<Task>>) -> u64 {
+    let registry = registry();
+    let id = registry.next_id.fetch_add(1, Ordering::Relaxed);
+    let task = Arc::new(Task {
+        id,
+        state: Mutex::new(TaskState::Queued(job)),
+        cancelled: AtomicBool::new(false),
+        children: Mutex::new(Vec::new()),
+    });
+    if let Some(parent) = parent {
+        let mut children = parent.children.lock().unwrap();
+        children.retain(|child| !child.is_finished());
+        children.push(Arc::clone(&task));
+    }
+    registry.tasks.lock().unwrap().insert(id, Arc::clone(&task));
+    let pool = pool();
+    pool.queue.lock().unwrap().push_back(task);
+    pool.work.notify_one();
+    id
+}
+
+/// Run a queued task on this thread; a no-op once it has started or been cancelled.
+fn run(task: &Arc<Task>) {
+    let job = {
+        let mut state = task.state.lock().unwrap();
+        match std::mem::replace(&mut *state, TaskState::Running) {
+            TaskState::Queued(job) => job,
+            other => {
+                *state = other;
+                return;
+            }
+        }
+    };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job(task)))
+        .unwrap_or_else(|_| Err(RuntimeError::General(format!("task {} panicked", task.id))));
+    *task.state.lock().unwrap() = TaskState::Finished(result);
+    // Structured concurrency: children do not outlive their parent.
+    let children = std::mem::take(&mut *task.children.lock().unwrap());
+    for child in &children {
+        cancel_task(child);
+    }
+    retire(task.id);
+    notify_done();
+}
+
+/// Note that task `id` has finished, and drop the oldest finished tasks nobody awaited so that
+/// fire-and-forget spawns do not accumulate in the table.
+fn retire(id: u64) {
+    let registry = registry();
+    let mut finished = registry.finished.lock().unwrap();
+    finished.push_back(id);
+    while finished.len() > MAX_UNJOINED {
+        if let Some(oldest) = finished.pop_front() {
+            registry.tasks.lock().unwrap().remove(&oldest);
+        }
+    }
+}
+
+fn notify_done() {
+    let registry = registry();
+    let _guard = registry.done_lock.lock().unwrap();
+    registry.done.notify_all();
+}
+
+fn lookup(id: u64) -> Result<Arc<Task>, RuntimeError> {
+    registry()
+        .tasks
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| RuntimeError::General(format!("task {} not found or already awaited", id)))
+}
+
+/// `queued`, `running`, `done`, `failed` or `cancelled`; `done` also for tasks already awaited
+/// or dropped unawaited.
+pub fn status(id: u64) -> &'static str {
+    match lookup(id) {
+        Ok(task) => task.status(),
+        Err(_) => "done",
+    }
+}
+
+fn cancel_task(task: &Arc<Task>) {
+    if task.is_finished() {
+        return;
+    }
+    task.cancelled.store(true, Ordering::Relaxed);
+    {
+        let mut state = task.state.lock().unwrap();
+        if matches!(*state, TaskState::Queued(_)) {
+            *state = TaskState::Cancelled;
+            retire(task.id);
+        }
+    }
+    let children = task.children.lock().unwrap().clone();
+    for child in &children {
+        cancel_task(child);
+    }
+    notify_done();
+}
+
+/// Request cancellation of a task and its children. A queued task never starts; a running one
+/// stops at its next check. Returns false when the task had already finished.
+pub fn cancel(id: u64) -> bool {
+    match lookup(id) {
+        Ok(task) if !task.is_finished() => {
+            cancel_task(&task);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Take the result of a finished task, removing it from the table.
+fn take_result(task: &Task) -> Option<TaskResult> {
+    let mut state = task.state.lock().unwrap();
+    let result = match &*state {
+        TaskState::Queued(_) | TaskState::Running => return None,
+        TaskState::Joined => Err(RuntimeError::General(format!(
+            "task {} was already awaited",
+            task.id
+        ))),
+        TaskState::Cancelled => Err(RuntimeError::TaskCancelled(task.id)),
+        TaskState::Finished(_) => match std::mem::replace(&mut *state, TaskState::Joined) {
+            TaskState::Finished(Err(_)) if task.is_cancelled() => {
+                Err(RuntimeError::TaskCancelled(task.id))
+            }
+            TaskState::Finished(result) => result,
+            _ => unreachable!(),
+        },
+    };
+    *state = TaskState::Joined;
+    registry().tasks.lock().unwrap().remove(&task.id);
+    Some(result)
+}
+
+fn queued(task: &&Arc<Task>) -> bool {
+    matches!(*task.state.lock().unwrap(), TaskState::Queued(_))
+}
+
+/// Run each queued task of `waiting_on` on a thread of its own, as far as `MAX_HELPERS`
+/// allows; the rest stay queued for the pool or a later call.
+fn start_on_helpers(waiting_on: &[Arc<Task>]) {
+    let pool = pool();
+    for task in waiting_on.iter().filter(queued) {
+        if !reserve_helper(pool) {
+            return;
+        }
+        let task = Arc::clone(task);
+        std::thread::Builder::new()
+            .name(format!("dal-race-{}", task.id))
+            .spawn(move || {
+                run(&task);
+                pool.helpers.fetch_sub(1, Ordering::Relaxed);
+            })
+            .expect("failed to start task helper");
+    }
+}
+
+/// Wait until `poll` yields, `timeout` passes or `current` (the awaiting task) is cancelled.
+/// Tasks of `waiting_on` that have not started are started per `steal`; `Needed` ones only
+/// without a timeout, since running one on this thread could overrun it.
+fn wait_until<R>(
+    waiting_on: &[Arc<Task>],
+    steal: Steal,
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+    mut poll: impl FnMut() -> Option<R>,
+) -> Result<R, RuntimeError> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let registry = registry();
+    let mut waited = false;
+    loop {
+        match steal {
+            Steal::Needed if deadline.is_none() => {
+                if let Some(task) = waiting_on.iter().find(queued) {
+                    run(task);
+                    continue;
+                }
+            }
+            // Only after a full wait, so a worker between two tasks does not count as busy.
+            Steal::WhenStalled if waited && pool().idle.load(Ordering::Relaxed) == 0 => {
+                start_on_helpers(waiting_on);
+            }
+            _ => {}
+        }
+        let guard = registry.done_lock.lock().unwrap();
+        if let Some(result) = poll() {
+            return Ok(result);
+        }
+        if let Some(current) = current.filter(|c| c.is_cancelled()) {
+            return Err(RuntimeError::TaskCancelled(current.id));
+        }
+        let mut wait = POLL_INTERVAL;
+        if let (Some(deadline), Some(timeout)) = (deadline, timeout) {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RuntimeError::AwaitTimeout(timeout.as_millis() as u64));
+            }
+            wait = wait.min(deadline - now);
+        }
+        drop(registry.done.wait_timeout(guard, wait).unwrap());
+        waited = true;
+    }
+}
+
+/// Wait for task `id` and return its result. On timeout the task is cancelled.
+pub fn join(id: u64, timeout: Option<Duration>, current: Option<&Arc<Task>>) -> TaskResult {
+    let task = lookup(id)?;
+    let waiting_on = [Arc::clone(&task)];
+    let result = wait_until(&waiting_on, Steal::Needed, timeout, current, || {
+        take_result(&task)
+    });
+    if matches!(result, Err(RuntimeError::AwaitTimeout(_))) {
+        cancel_task(&task);
+    }
+    result?
+}
+
+/// Wait for every task and return their values in order. The first failure cancels the rest
+/// and is returned, as is a timeout.
+pub fn join_all(
+    ids: &[u64],
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+) -> Result<Vec<Value>, RuntimeError> {
+    let tasks = ids
+        .iter()
+        .map(|id| lookup(*id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut values: Vec<Option<Value>> = vec![None; tasks.len()];
+    let outcome = wait_until(&tasks, Steal::Needed, timeout, current, || {
+        for (task, value) in tasks.iter().zip(values.iter_mut()) {
+            if value.is_some() {
+                continue;
+            }
+            match take_result(task) {
+                Some(Ok(v)) => *value = Some(v),
+                Some(Err(e)) => return Some(Err(e)),
+                None => {}
+            }
+        }
+        values.iter().all(Option::is_some).then_some(Ok(()))
+    });
+    match outcome.and_then(|o| o) {
+        Ok(()) => Ok(values.into_iter().flatten().collect()),
+        Err(error) => {
+            tasks.iter().for_each(cancel_task);
+            Err(error)
+        }
+    }
+}
+
+/// Wait for the first task to finish and return its index and result; the others are
+/// cancelled. On timeout all of them are.
+pub fn select(
+    ids: &[u64],
+    timeout: Option<Duration>,
+    current: Option<&Arc<Task>>,
+) -> Result<(usize, TaskResult), RuntimeError> {
+    if ids.is_empty() {
+        return Err(RuntimeError::General(
+            "cannot await any of an empty list of tasks".to_string(),
+        ));
+    }
+    let tasks = ids
+        .iter()
+        .map(|id| lookup(*id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcome = wait_until(&tasks, Steal::WhenStalled, timeout, current, || {
+        tasks
+            .iter()
+            .enumerate()
+            .find_map(|(i, task)| take_result(task).map(|result| (i, result)))
+    });
+    tasks.iter().for_each(cancel_task);
+    outcome
+}
+
+/// Sleep for `duration`, waking early with an error if `current` is cancelled.
+pub fn sleep(duration: Duration, current: Option<&Arc<Task>>) -> Result<(), RuntimeError> {
+    let deadline = Instant::now() + duration;
+    loop {
+        if let Some(current) = current.filter(|c| c.is_cancelled()) {
+            return Err(RuntimeError::TaskCancelled(current.id));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(());
+        }
+        std::thread::sleep((deadline - now).min(POLL_INTERVAL));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unawaited_results_are_dropped_once_later_tasks_finish() {
+        let ids: Vec<u64> = (0..MAX_UNJOINED as i64 + 8)
+            .map(|n| spawn(Box::new(move |_| Ok(Value::Int(n))), None))
+            .collect();
+        while ids
+            .iter()
+            .any(|id| matches!(status(*id), "queued" | "running"))
+        {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        let err = join(ids[0], None, None).unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+        let last = *ids.last().unwrap();
+        assert_eq!(
+            join(last, None, None).unwrap(),
+            Value::Int(MAX_UNJOINED as i64 + 7)
+        );
+        assert!(registry().tasks.lock().unwrap().len() <= MAX_UNJOINED);
+    }
+}
@@ -631,6 +631,38 @@ pub struct Transaction {
     pub is_distributed: bool,
 }
 
+/// Open a [`StateStorage`] backend by name: `"file"` (JSON), `"sqlite"` (requires the
+/// `sqlite-storage` feature) or anything else for in-memory. Without `path`, file and SQLite
+/// storage use `default_stem` with a `.json` / `.db` extension.
+pub fn open_state_storage(
+    kind: &str,
+    path: Option<&str>,
+    default_stem: &str,
+) -> io::Result<Box<dyn StateStorage>> {
+    Ok(match kind {
+        "file" => {
+            let path = path.map_or_else(|| format!("{}.json", default_stem), str::to_string);
+            Box::new(FileBackedStorage::new(path)?)
+        }
+        #[cfg(feature = "sqlite-storage")]
+        "sqlite" => {
+            let path = path.map_or_else(|| format!("{}.db", default_stem), str::to_string);
+            Box::new(
+                SqliteStorage::new(path)
+                    .map_err(|e| io::Error::other(format!("SQLite error: {}", e)))?,
+            )
+        }
+        #[cfg(not(feature = "sqlite-storage"))]
+        "sqlite" => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "SQLite backend requires 'sqlite-storage' feature. Compile with: cargo build --features sqlite-storage"
+            ));
+        }
+        _ => Box::new(InMemoryStorage::new()),
+    })
+}
+
 /// Transaction manager. Uses pluggable [`StateStorage`]; default is in-memory.
 pub struct TransactionManager {
     active_transactions: HashMap<String, Transaction>,
@@ -737,29 +769,8 @@ impl TransactionManager {
             .and_then(|s| s.parse::<u64>().ok());
 
         // Select storage backend
-        let storage: Box<dyn StateStorage> = match storage_type.as_str() {
-            "file" => {
-                let storage_path = std::env::var("DAL_TX_STORAGE_PATH")
-                    .unwrap_or_else(|_| "./dal_tx_state.json".to_string());
-                Box::new(FileBackedStorage::new(storage_path)?)
-            }
-            #[cfg(feature = "sqlite-storage")]
-            "sqlite" => {
-                let storage_path = std::env::var("DAL_TX_STORAGE_PATH")
-                    .unwrap_or_else(|_| "./dal_tx_state.db".to_string());
-                Box::new(SqliteStorage::new(storage_path).map_err(|e| {
-                    io::Error::new(io::ErrorKind::Other, format!("SQLite error: {}", e))
-                })?)
-            }
-            #[cfg(not(feature = "sqlite-storage"))]
-            "sqlite" => {
-                return Err(io::Error::new(
-                    io::ErrorKind::Unsupported,
-                    "SQLite backend requires 'sqlite-storage' feature. Compile with: cargo build --features sqlite-storage"
-                ));
-            }
-            "memory" | _ => Box::new(InMemoryStorage::new()),
-        };
+        let storage_path = std::env::var("DAL_TX_STORAGE_PATH").ok();
+        let storage = open_state_storage(&storage_type, storage_path.as_deref(), "./dal_tx_state")?;
 
         let mut manager = Self::with_storage(storage);
 
@@ -0,0 +1,933 @@
+//! Static type checking over the AST, run before execution (`dal check`).
+//!
+//! Uses the annotations already present in source — `Parameter::param_type`,
+//! `FunctionStatement::return_type` and `ServiceField::field_type` — to report
+//! mismatched call arguments, wrong return values and wrong field assignments.
+//! `impl Trait for Service` blocks are checked against the trait's required methods.
+//! Anything unannotated is `Type::Any`, so untyped code never produces errors.
+
+use crate::lexer::tokens::{Literal, Operator};
+use crate::parser::ast::*;
+use crate::runtime::types::{Type, TypeEnvironment, TypeError};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A type error found by [`check_program`], located at the statement that caused it.
+#[derive(Debug, Clone)]
+pub struct TypeDiagnostic {
+    pub error: TypeError,
+    /// What was being checked, e.g. "argument 'amount' of 'deposit'".
+    pub context: String,
+    /// Location of the enclosing statement; line 0 if unknown.
+    pub span: Span,
+}
+
+impl fmt::Display for TypeDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.error)
+    }
+}
+
+/// Type-check a parsed program. Returns every error found (empty when the program is well typed).
+pub fn check_program(program: &Program) -> Vec<TypeDiagnostic> {
+    let mut checker = TypeChecker::new();
+    checker.declare_items(&program.statements);
+    for (i, stmt) in program.statements.iter().enumerate() {
+        if let Some(Some(span)) = program.statement_spans.get(i) {
+            checker.span = *span;
+        }
+        checker.check_statement(stmt);
+    }
+    checker.diagnostics
+}
+
+struct TypeChecker<'a> {
+    env: TypeEnvironment,
+    functions: HashMap<String, &'a FunctionStatement>,
+    services: HashMap<String, &'a ServiceStatement>,
+    traits: HashMap<String, &'a TraitStatement>,
+    /// Methods added to each service by `impl` blocks.
+    impl_methods: HashMap<String, Vec<&'a FunctionStatement>>,
+    /// `(trait, service)` pairs declared with `impl Trait for Service`.
+    implementations: HashSet<(String, String)>,
+    /// Name and declared return type of the function whose body is being checked.
+    return_type: Option<(String, Type)>,
+    span: Span,
+    diagnostics: Vec<TypeDiagnostic>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn new() -> Self {
+        Self {
+            env: TypeEnvironment::new(),
+            functions: HashMap::new(),
+            services: HashMap::new(),
+            traits: HashMap::new(),
+            impl_methods: HashMap::new(),
+            implementations: HashSet::new(),
+            return_type: None,
+            span: Span::default(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Register top-level types, functions and services first so uses may precede declarations.
+    fn declare_items(&mut self, statements: &'a [Statement]) {
+        // Struct names must be known before field and parameter annotations are resolved.
+        for stmt in statements {
+            match stmt {
+                Statement::Struct(decl) => {
+                    self.env.define_struct(decl.name.clone(), HashMap::new());
+                }
+                Statement::Service(service) => {
+                    self.env.define_struct(service.name.clone(), HashMap::new());
+                }
+                Statement::Trait(trait_stmt) => {
+                    self.traits.insert(trait_stmt.name.clone(), trait_stmt);
+                }
+                Statement::Impl(imp) => {
+                    self.impl_methods
+                        .entry(imp.service_name.clone())
+                        .or_default()
+                        .extend(&imp.methods);
+                    if let Some(trait_name) = &imp.trait_name {
+                        self.implementations
+                            .insert((trait_name.clone(), imp.service_name.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for stmt in statements {
+            match stmt {
+                Statement::Struct(decl) => {
+                    let fields = decl
+                        .fields
+                        .iter()
+                        .map(|f| (f.name.clone(), self.resolve(&f.field_type)))
+                        .collect();
+                    self.env.define_struct(decl.name.clone(), fields);
+                }
+                Statement::Service(service) => {
+                    let fields = service
+                        .fields
+                        .iter()
+                        .map(|f| (f.name.clone(), self.resolve(&f.field_type)))
+                        .collect();
+                    self.env.define_struct(service.name.clone(), fields);
+                    self.services.insert(service.name.clone(), service);
+                }
+                _ => {}
+            }
+        }
+        for stmt in statements {
+            if let Statement::Function(func) = stmt {
+                let func_type = self.function_type(func);
+                self.env.define_function(func.name.clone(), func_type);
+                self.functions.insert(func.name.clone(), func);
+            }
+        }
+    }
+
+    /// Resolve a type annotation, treating declared struct and service names as struct types
+    /// and trait names as "any implementing service".
+    fn resolve(&self, annotation: &str) -> Type {
+        let name = annotation.trim();
+        if self.env.get_struct_fields(name).is_some() {
+            return self.service_type(name);
+        }
+        if self.traits.contains_key(name) {
+            return Type::Trait(name.to_string());
+        }
+        Type::from_annotation(annotation)
+    }
+
+    /// Like `Type::is_compatible_with`, but a service satisfies a trait type it implements.
+    fn is_compatible(&self, expected: &Type, got: &Type) -> bool {
+        match (expected, got) {
+            (Type::Trait(trait_name), Type::Struct(service_name, _)) => self
+                .implementations
+                .contains(&(trait_name.clone(), service_name.clone())),
+            _ => got.is_compatible_with(expected),
+        }
+    }
+
+    /// Signature of `method` on a service: its own methods, then impl blocks, then trait defaults.
+    fn service_method(
+        &self,
+        service_name: &str,
+        method: &str,
+    ) -> Option<(&'a [Parameter], Option<&'a str>)> {
+        let own = self
+            .services
+            .get(service_name)
+            .and_then(|svc| svc.methods.iter().find(|m| m.name == method));
+        let from_impl = || {
+            self.impl_methods
+                .get(service_name)
+                .and_then(|methods| methods.iter().find(|m| m.name == method).copied())
+        };
+        if let Some(func) = own.or_else(from_impl) {
+            return Some((&func.parameters, func.return_type.as_deref()));
+        }
+        self.implementations
+            .iter()
+            .filter(|(_, svc)| svc == service_name)
+            .find_map(|(trait_name, _)| self.trait_method(trait_name, method))
+    }
+
+    fn trait_method(
+        &self,
+        trait_name: &str,
+        method: &str,
+    ) -> Option<(&'a [Parameter], Option<&'a str>)> {
+        self.traits
+            .get(trait_name)
+            .and_then(|t| t.methods.iter().find(|m| m.name == method))
+            .map(|m| (m.parameters.as_slice(), m.return_type.as_deref()))
+    }
+
+    fn annotation_type(&self, annotation: Option<&str>) -> Type {
+        annotation.map(|a| self.resolve(a)).unwrap_or(Type::Any)
+    }
+
+    fn function_type(&self, func: &FunctionStatement) -> Type {
+        let params = func
+            .parameters
+            .iter()
+            .map(|p| self.annotation_type(p.param_type.as_deref()))
+            .collect();
+        let ret = self.annotation_type(func.return_type.as_deref());
+        Type::Function(params, Box::new(ret))
+    }
+
+    fn report(&mut self, error: TypeError, context: String) {
+        self.diagnostics.push(TypeDiagnostic {
+            error,
+            context,
+            span: self.span,
+        });
+    }
+
+    fn expect_compatible(&mut self, expected: &Type, got: &Type, context: String) {
+        if !self.is_compatible(expected, got) {
+            self.report(
+                TypeError::TypeMismatch {
+                    expected: expected.clone(),
+                    got: got.clone(),
+                },
+                context,
+            );
+        }
+    }
+
+    fn push_scope(&mut self) {
+        let env = std::mem::take(&mut self.env);
+        self.env = TypeEnvironment::with_parent(env);
+    }
+
+    fn pop_scope(&mut self) {
+        let env = std::mem::take(&mut self.env);
+        self.env = env.into_parent().unwrap_or_default();
+    }
+
+    fn service_type(&self, name: &str) -> Type {
+        let fields = self
+            .env
+            .get_struct_fields(name)
+            .cloned()
+            .unwrap_or_default();
+        Type::Struct(name.to_string(), fields)
+    }
+
+    fn check_block(&mut self, block: &'a BlockStatement) {
+        self.push_scope();
+        self.check_statements(block);
+        self.pop_scope();
+    }
+
+    /// Check a block's statements in the current scope.
+    fn check_statements(&mut self, block: &'a BlockStatement) {
+        let outer_span = self.span;
+        for (i, stmt) in block.statements.iter().enumerate() {
+            if let Some(span) = block.span_at(i) {
+                self.span = span;
+            }
+            self.check_statement(stmt);
+        }
+        self.span = outer_span;
+    }
+
+    fn check_statement(&mut self, stmt: &'a Statement) {
+        match stmt {
+            Statement::Expression(expr) => {
+                self.infer(expr);
+            }
+            Statement::Let(LetStatement {
+                name,
+                value,
+                pattern,
+                ..
+            }) => {
+                let value_type = self.infer(value);
+                match pattern {
+                    Some(pattern) => self.bind_pattern(pattern, &value_type),
+                    None => self.env.define_variable(name.clone(), value_type),
+                }
+            }
+            Statement::Return(ReturnStatement { value }) => {
+                let got = match value {
+                    Some(expr) => self.infer(expr),
+                    None => Type::Null,
+                };
+                if let Some((func_name, expected)) = self.return_type.clone() {
+                    self.expect_compatible(
+                        &expected,
+                        &got,
+                        format!("return value of '{}'", func_name),
+                    );
+                }
+            }
+            Statement::Block(block) => self.check_block(block),
+            Statement::Function(func) => self.check_function(func, None),
+            Statement::Trait(trait_stmt) => {
+                let self_type = Type::Trait(trait_stmt.name.clone());
+                for method in &trait_stmt.methods {
+                    if let Some(body) = &method.default_body {
+                        self.check_body(
+                            &method.name,
+                            &method.parameters,
+                            method.return_type.as_deref(),
+                            body,
+                            Some(self_type.clone()),
+                        );
+                    }
+                }
+            }
+            Statement::Impl(imp) => self.check_impl(imp),
+            Statement::Service(service) => self.check_service(service),
+            Statement::Spawn(SpawnStatement { config, body, .. }) => {
+                if let Some(config) = config {
+                    for expr in config.values() {
+                        self.infer(expr);
+                    }
+                }
+                self.check_block(body);
+            }
+            Statement::Agent(AgentStatement { config, body, .. }) => {
+                for expr in config.values() {
+                    self.infer(expr);
+                }
+                self.check_block(body);
+            }
+            Statement::Message(MessageStatement { data, .. })
+            | Statement::Event(EventStatement { data, .. }) => {
+                for expr in data.values() {
+                    self.infer(expr);
+                }
+            }
+            Statement::If(IfStatement {
+                condition,
+                consequence,
+                alternative,
+            }) => {
+                self.infer(condition);
+                self.check_block(consequence);
+                if let Some(alt) = alternative {
+                    self.check_block(alt);
+                }
+            }
+            Statement::While(WhileStatement { condition, body }) => {
+                self.infer(condition);
+                self.check_block(body);
+            }
+            Statement::Loop(LoopStatement { body }) => self.check_block(body),
+            Statement::ForIn(ForInStatement {
+                variable,
+                iterable,
+                body,
+            }) => {
+                let element = match self.infer(iterable) {
+                    Type::List(inner) | Type::Array(inner) | Type::Set(inner) => *inner,
+                    _ => Type::Any,
+                };
+                self.push_scope();
+                self.env.define_variable(variable.clone(), element);
+                self.check_block(body);
+                self.pop_scope();
+            }
+            Statement::Try(TryStatement {
+                try_block,
+                catch_blocks,
+                finally_block,
+            }) => {
+                self.check_block(try_block);
+                for catch in catch_blocks {
+                    self.push_scope();
+                    if let Some(var) = &catch.error_variable {
+                        self.env.define_variable(var.clone(), Type::Any);
+                    }
+                    self.check_block(&catch.body);
+                    self.pop_scope();
+                }
+                if let Some(finally) = finally_block {
+                    self.check_block(finally);
+                }
+            }
+            Statement::Match(MatchStatement {
+                expression,
+                cases,
+                default_case,
+            }) => {
+                let scrutinee = self.infer(expression);
+                for case in cases {
+                    self.push_scope();
+                    self.bind_pattern(&case.pattern, &scrutinee);
+                    if let Some(guard) = &case.guard {
+                        self.infer(guard);
+                    }
+                    self.check_block(&case.body);
+                    self.pop_scope();
+                }
+                if let Some(default) = default_case {
+                    self.check_block(default);
+                }
+            }
+            Statement::Break(BreakStatement { value }) => {
+                if let Some(expr) = value {
+                    self.infer(expr);
+                }
+            }
+            Statement::Continue(_) | Statement::Import(_) | Statement::Enum(_) => {}
+            Statement::Struct(decl) => {
+                // Nested declarations are only visible from here on; top-level ones were hoisted.
+                if self.env.get_struct_fields(&decl.name).is_none() {
+                    let fields = decl
+                        .fields
+                        .iter()
+                        .map(|f| (f.name.clone(), self.resolve(&f.field_type)))
+                        .collect();
+                    self.env.define_struct(decl.name.clone(), fields);
+                }
+            }
+        }
+    }
+
+    fn bind_pattern(&mut self, pattern: &MatchPattern, scrutinee: &Type) {
+        match pattern {
+            MatchPattern::Identifier(name) => {
+                self.env.define_variable(name.clone(), scrutinee.clone());
+            }
+            // Enum payloads are not tracked statically.
+            MatchPattern::EnumVariant { fields, .. } => {
+                for field in fields {
+                    self.bind_pattern(field, &Type::Any);
+                }
+            }
+            MatchPattern::List { elements, rest } => {
+                let element = match scrutinee {
+                    Type::List(element) | Type::Array(element) => (**element).clone(),
+                    _ => Type::Any,
+                };
+                for pattern in elements {
+                    self.bind_pattern(pattern, &element);
+                }
+                if let Some(rest) = rest.as_ref().filter(|r| r.as_str() != "_") {
+                    self.env
+                        .define_variable(rest.clone(), Type::List(Box::new(element)));
+                }
+            }
+            MatchPattern::Map { fields, .. } => {
+                for (key, pattern) in fields {
+                    let field = match scrutinee {
+                        Type::Map(_, value) => (**value).clone(),
+                        Type::Struct(_, struct_fields) => {
+                            struct_fields.get(key).cloned().unwrap_or(Type::Any)
+                        }
+                        _ => Type::Any,
+                    };
+                    self.bind_pattern(pattern, &field);
+                }
+            }
+            MatchPattern::Ok(inner) | MatchPattern::Err(inner) | MatchPattern::Some(inner) => {
+                let payload = match (pattern, scrutinee) {
+                    (MatchPattern::Ok(_), Type::Result(ok, _)) => (**ok).clone(),
+                    (MatchPattern::Err(_), Type::Result(_, err)) => (**err).clone(),
+                    (MatchPattern::Some(_), Type::Option(value)) => (**value).clone(),
+                    _ => Type::Any,
+                };
+                self.bind_pattern(inner, &payload);
+            }
+            MatchPattern::Literal(_)
+            | MatchPattern::Wildcard
+            | MatchPattern::Range(..)
+            | MatchPattern::None => {}
+        }
+    }
+
+    fn check_service(&mut self, service: &'a ServiceStatement) {
+        for field in &service.fields {
+            if let Some(initial) = &field.initial_value {
+                let got = self.infer(initial);
+                let expected = self.resolve(&field.field_type);
+                self.expect_compatible(
+                    &expected,
+                    &got,
+                    format!(
+                        "initial value of field '{}' in '{}'",
+                        field.name, service.name
+                    ),
+                );
+            }
+        }
+        let self_type = self.service_type(&service.name);
+        for method in &service.methods {
+            self.check_function(method, Some(self_type.clone()));
+        }
+    }
+
+    /// Check an `impl` block: the trait and service must exist, every required trait method must
+    /// be provided (by the service, the impl or a trait default) with a matching signature.
+    fn check_impl(&mut self, imp: &'a ImplStatement) {
+        let service = self.services.get(&imp.service_name).copied();
+        if service.is_none() {
+            self.report(
+                TypeError::UndefinedType {
+                    name: imp.service_name.clone(),
+                },
+                "impl target".to_string(),
+            );
+        }
+        if let Some(trait_name) = &imp.trait_name {
+            match self.traits.get(trait_name).copied() {
+                Some(trait_stmt) => self.check_trait_conformance(trait_stmt, imp, service),
+                None => self.report(
+                    TypeError::UndefinedType {
+                        name: trait_name.clone(),
+                    },
+                    format!("impl for '{}'", imp.service_name),
+                ),
+            }
+        }
+        let self_type = service.map(|svc| self.service_type(&svc.name));
+        for method in &imp.methods {
+            self.check_function(method, self_type.clone());
+        }
+    }
+
+    fn check_trait_conformance(
+        &mut self,
+        trait_stmt: &'a TraitStatement,
+        imp: &'a ImplStatement,
+        service: Option<&'a ServiceStatement>,
+    ) {
+        for required in &trait_stmt.methods {
+            let provided = imp
+                .methods
+                .iter()
+                .chain(service.iter().flat_map(|svc| svc.methods.iter()))
+                .find(|m| m.name == required.name);
+            let Some(provided) = provided else {
+                if required.default_body.is_none() {
+                    self.report(
+                        TypeError::MissingTraitMethod {
+                            trait_name: trait_stmt.name.clone(),
+                            method: required.name.clone(),
+                            type_name: imp.service_name.clone(),
+                        },
+                        format!("impl {} for {}", trait_stmt.name, imp.service_name),
+                    );
+                }
+                continue;
+            };
+            let context = format!(
+                "'{}.{}' implementing '{}.{}'",
+                imp.service_name, provided.name, trait_stmt.name, required.name
+            );
+            if provided.parameters.len() != required.parameters.len() {
+                self.report(
+                    TypeError::ParameterCountMismatch {
+                        expected: required.parameters.len(),
+                        got: provided.parameters.len(),
+                    },
+                    context,
+                );
+                continue;
+            }
+            for (want, have) in required.parameters.iter().zip(&provided.parameters) {
+                if let (Some(want_ann), Some(have_ann)) = (&want.param_type, &have.param_type) {
+                    let (want_type, have_type) = (self.resolve(want_ann), self.resolve(have_ann));
+                    self.expect_compatible(
+                        &want_type,
+                        &have_type,
+                        format!("parameter '{}' of {}", have.name, context),
+                    );
+                }
+            }
+            if let (Some(want_ann), Some(have_ann)) = (&required.return_type, &provided.return_type)
+            {
+                let (want_type, have_type) = (self.resolve(want_ann), self.resolve(have_ann));
+                self.expect_compatible(
+                    &want_type,
+                    &have_type,
+                    format!("return type of {}", context),
+                );
+            }
+        }
+    }
+
+    fn check_function(&mut self, func: &'a FunctionStatement, self_type: Option<Type>) {
+        self.check_body(
+            &func.name,
+            &func.parameters,
+            func.return_type.as_deref(),
+            &func.body,
+            self_type,
+        );
+    }
+
+    fn check_body(
+        &mut self,
+        name: &str,
+        parameters: &[Parameter],
+        return_type: Option<&str>,
+        body: &'a BlockStatement,
+        self_type: Option<Type>,
+    ) {
+        self.push_scope();
+        if let Some(self_type) = self_type {
+            self.env.define_variable("self".to_string(), self_type);
+        }
+        for param in parameters {
+            let param_type = self.annotation_type(param.param_type.as_deref());
+            self.env.define_variable(param.name.clone(), param_type);
+        }
+        let declared = return_type.map(|ann| (name.to_string(), self.resolve(ann)));
+        let outer_return = std::mem::replace(&mut self.return_type, declared);
+        self.check_statements(body);
+        self.return_type = outer_return;
+        self.pop_scope();
+    }
+
+    /// Check argument count and annotated parameter types for a call to `callee`.
+    fn check_call(&mut self, callee: &str, parameters: &[Parameter], args: &[Type]) {
+        if parameters.len() != args.len() {
+            self.report(
+                TypeError::ParameterCountMismatch {
+                    expected: parameters.len(),
+                    got: args.len(),
+                },
+                format!("call to '{}'", callee),
+            );
+            return;
+        }
+        for (param, arg) in parameters.iter().zip(args) {
+            if let Some(annotation) = &param.param_type {
+                let expected = self.resolve(annotation);
+                self.expect_compatible(
+                    &expected,
+                    arg,
+                    format!("argument '{}' of '{}'", param.name, callee),
+                );
+            }
+        }
+    }
+
+    /// Return type of a call of the user function `name`, after checking `args` against its
+    /// parameters. Calls of anything else are `Any`.
+    fn named_call_type(&mut self, name: &str, args: &[Type]) -> Type {
+        match self.functions.get(name).copied() {
+            Some(func) => {
+                self.check_call(name, &func.parameters, args);
+                match self.env.get_function_type(name) {
+                    Some(Type::Function(_, ret)) => (**ret).clone(),
+                    _ => Type::Any,
+                }
+            }
+            None => Type::Any,
+        }
+    }
+
+    /// Infer the static type of an expression, checking any calls and assignments inside it.
+    fn infer(&mut self, expr: &'a Expression) -> Type {
+        match expr {
+            Expression::Literal(literal) => literal_type(literal),
+            // A bare function name is a reference to that function.
+            Expression::Identifier(name) => self
+                .env
+                .get_variable_type(name)
+                .or_else(|| self.env.get_function_type(name))
+                .cloned()
+                .unwrap_or(Type::Any),
+            Expression::BinaryOp(left, op, right) => {
+                let left = self.infer(left);
+                let right = self.infer(right);
+                binary_result_type(op, &left, &right)
+            }
+            Expression::UnaryOp(op, operand) => {
+                let operand = self.infer(operand);
+                match op {
+                    Operator::Not => Type::Bool,
+                    _ if operand.is_numeric() => operand,
+                    _ => Type::Any,
+                }
+            }
+            Expression::Assignment(name, value) => {
+                let value_type = self.infer(value);
+                // Variables are dynamically typed; a rebinding to a different type widens to Any.
+                if let Some(existing) = self.env.get_variable_type(name).cloned() {
+                    if existing != value_type {
+                        self.env.assign_variable(name, Type::Any);
+                    }
+                }
+                value_type
+            }
+            Expression::FunctionCall(call) => {
+                let args: Vec<Type> = call.arguments.iter().map(|a| self.infer(a)).collect();
+                if let Some(service_name) = call.name.strip_suffix("::new") {
+                    if self.services.contains_key(service_name) {
+                        return self.service_type(service_name);
+                    }
+                }
+                self.named_call_type(&call.name, &args)
+            }
+            Expression::MethodCall {
+                receiver,
+                method_name,
+                arguments,
+            } => {
+                let receiver_type = self.infer(receiver);
+                let args: Vec<Type> = arguments.iter().map(|a| self.infer(a)).collect();
+                let (owner, method) = match &receiver_type {
+                    Type::Struct(service_name, _) => {
+                        (service_name, self.service_method(service_name, method_name))
+                    }
+                    Type::Trait(trait_name) => {
+                        (trait_name, self.trait_method(trait_name, method_name))
+                    }
+                    _ => return Type::Any,
+                };
+                match method {
+                    Some((parameters, return_type)) => {
+                        let callee = format!("{}.{}", owner, method_name);
+                        self.check_call(&callee, parameters, &args);
+                        self.annotation_type(return_type)
+                    }
+                    None => Type::Any,
+                }
+            }
+            Expression::FieldAccess(object, field) => match self.infer(object) {
+                Type::Struct(_, fields) => fields.get(field).cloned().unwrap_or(Type::Any),
+                Type::Map(_, value) => *value,
+                _ => Type::Any,
+            },
+            Expression::FieldAssignment(object, field, value) => {
+                let value_type = self.infer(value);
+                if let Type::Struct(service_name, fields) = self.infer(object) {
+                    if let Some(expected) = fields.get(field) {
+                        self.expect_compatible(
+                            expected,
+                            &value_type,
+                            format!("assignment to field '{}' of '{}'", field, service_name),
+                        );
+                    }
+                }
+                value_type
+            }
+            Expression::OptionalFieldAccess(object, _) => {
+                self.infer(object);
+                Type::Any
+            }
+            Expression::OptionalMethodCall {
+                receiver,
+                arguments,
+                ..
+            } => {
+                self.infer(receiver);
+                for arg in arguments {
+                    self.infer(arg);
+                }
+                Type::Any
+            }
+            Expression::NullCoalesce(left, right) => {
+                let left = self.infer(left);
+                let right = self.infer(right);
+                if left == right {
+                    left
+                } else {
+                    Type::Any
+                }
+            }
+            // `value |> f(a)` is checked as the call `f(value, a)`.
+            Expression::Pipeline(value, stage) => {
+                let value = self.infer(value);
+                match stage.as_ref() {
+                    Expression::FunctionCall(call) => {
+                        let mut args = vec![value];
+                        args.extend(call.arguments.iter().map(|a| self.infer(a)));
+                        self.named_call_type(&call.name, &args)
+                    }
+                    Expression::Identifier(name) if self.functions.contains_key(name) => {
+                        self.named_call_type(name, &[value])
+                    }
+                    other => {
+                        self.infer(other);
+                        Type::Any
+                    }
+                }
+            }
+            Expression::Await(inner) => self.infer(inner),
+            Expression::Propagate(inner) => match self.infer(inner) {
+                Type::Result(ok, _) | Type::Option(ok) => *ok,
+                _ => Type::Any,
+            },
+            Expression::Spawn(inner) | Expression::Throw(inner) => {
+                self.infer(inner);
+                Type::Any
+            }
+            Expression::StructLiteral { name, fields } => {
+                let declared = self.env.get_struct_fields(name).cloned();
+                for (field, value) in fields {
+                    let got = self.infer(value);
+                    match declared.as_ref().map(|d| d.get(field)) {
+                        Some(Some(expected)) => self.expect_compatible(
+                            expected,
+                            &got,
+                            format!("field '{}' of struct '{}'", field, name),
+                        ),
+                        Some(None) => self.report(
+                            TypeError::UndefinedField {
+                                type_name: name.clone(),
+                                field: field.clone(),
+                            },
+                            format!("construction of '{}'", name),
+                        ),
+                        None => {}
+                    }
+                }
+                if declared.is_some() {
+                    self.service_type(name)
+                } else {
+                    Type::Any
+                }
+            }
+            Expression::ObjectLiteral(properties) => {
+                let values: Vec<Type> = properties.values().map(|v| self.infer(v)).collect();
+                // Keys are stored as strings at runtime but `map<int, _>` annotations are common,
+                // so only the value type is tracked.
+                Type::map(Type::Any, common_type(&values))
+            }
+            Expression::Interpolation(parts) => {
+                for part in parts {
+                    self.infer(part);
+                }
+                Type::String
+            }
+            Expression::ArrayLiteral(elements) => {
+                let types: Vec<Type> = elements.iter().map(|e| self.infer(e)).collect();
+                Type::list(common_type(&types))
+            }
+            Expression::IndexAccess(container, index) => {
+                let container = self.infer(container);
+                self.infer(index);
+                match container {
+                    Type::List(inner) | Type::Array(inner) => *inner,
+                    Type::Map(_, value) => *value,
+                    _ => Type::Any,
+                }
+            }
+            Expression::ArrowFunction { params, body } => {
+                self.push_scope();
+                for param in params {
+                    self.env.define_variable(param.clone(), Type::Any);
+                }
+                let outer_return = self.return_type.take();
+                self.check_statements(body);
+                self.return_type = outer_return;
+                self.pop_scope();
+                Type::Function(vec![Type::Any; params.len()], Box::new(Type::Any))
+            }
+            Expression::Call { callee, arguments } => {
+                let callee = self.infer(callee);
+                for arg in arguments {
+                    self.infer(arg);
+                }
+                match callee {
+                    Type::Function(_, ret) => *ret,
+                    _ => Type::Any,
+                }
+            }
+            Expression::Range(start, end) => {
+                self.infer(start);
+                self.infer(end);
+                Type::list(Type::Int)
+            }
+        }
+    }
+}
+
+/// The shared type of a literal's elements, or `Any` when empty or mixed.
+fn common_type(types: &[Type]) -> Type {
+    match types.split_first() {
+        Some((first, rest)) if rest.iter().all(|t| t == first) => first.clone(),
+        _ => Type::Any,
+    }
+}
+
+fn literal_type(literal: &Literal) -> Type {
+    match literal {
+        Literal::Int(_) => Type::Int,
+        Literal::Float(_) => Type::Float,
+        Literal::U256(_) => Type::U256,
+        Literal::I256(_) => Type::I256,
+        Literal::Decimal(_) => Type::Decimal,
+        Literal::String(_) => Type::String,
+        Literal::Bool(_) => Type::Bool,
+        Literal::Null => Type::Null,
+        Literal::Bytes(_) => Type::Bytes,
+    }
+}
+
+/// Common type of arithmetic involving `u256` / `i256` / `decimal` (`numeric::arith`): `int`
+/// widens to the other side, `u256` with `i256` is `i256`, anything with `decimal` is `decimal`.
+/// Floats do not mix in implicitly.
+fn wide_result_type(left: &Type, right: &Type) -> Type {
+    let rank = |t: &Type| match t {
+        Type::Int => Some(0),
+        Type::U256 => Some(1),
+        Type::I256 => Some(2),
+        Type::Decimal => Some(3),
+        _ => None,
+    };
+    match (rank(left), rank(right)) {
+        (Some(l), Some(r)) if l >= r => left.clone(),
+        (Some(_), Some(_)) => right.clone(),
+        _ => Type::Any,
+    }
+}
+
+/// Result type of a binary operator, mirroring the runtime's `add_values` / `SafeMath` rules.
+fn binary_result_type(op: &Operator, left: &Type, right: &Type) -> Type {
+    match op {
+        Operator::Equal
+        | Operator::NotEqual
+        | Operator::Less
+        | Operator::LessEqual
+        | Operator::Greater
+        | Operator::GreaterEqual
+        | Operator::And
+        | Operator::Or => Type::Bool,
+        Operator::Plus if left.is_string() || right.is_string() => Type::String,
+        Operator::Plus if left.is_list() && right.is_list() => left.clone(),
+        Operator::Plus if *left == Type::Bytes && *right == Type::Bytes => Type::Bytes,
+        Operator::Plus | Operator::Minus | Operator::Star | Operator::Slash | Operator::Percent => {
+            match (left, right) {
+                (Type::Int, Type::Int) => Type::Int,
+                (l, r) if l.is_wide() || r.is_wide() => wide_result_type(l, r),
+                (l, r) if l.is_numeric() && r.is_numeric() => Type::Float,
+                _ => Type::Any,
+            }
+        }
+        _ => Type::Any,
+    }
+}
@@ -9,6 +9,12 @@ pub enum Type {
     String,
     Bool,
     Null,
+    Bytes,
+
+    // Wide numeric types
+    U256,
+    I256,
+    Decimal,
 
     // Generic types
     Result(Box<Type>, Box<Type>), // Ok(T), Err(E)
@@ -20,6 +26,8 @@ pub enum Type {
     // Structured types
     Struct(String, HashMap<String, Type>),
     Array(Box<Type>),
+    /// Any service that implements the named trait
+    Trait(String),
 
     // Function types
     Function(Vec<Type>, Box<Type>), // parameters, return type
@@ -35,7 +43,15 @@ pub enum Type {
 
 impl Type {
     pub fn is_numeric(&self) -> bool {
-        matches!(self, Type::Int | Type::Float)
+        matches!(
+            self,
+            Type::Int | Type::Float | Type::U256 | Type::I256 | Type::Decimal
+        )
+    }
+
+    /// `u256`, `i256` or `decimal`.
+    pub fn is_wide(&self) -> bool {
+        matches!(self, Type::U256 | Type::I256 | Type::Decimal)
     }
 
     pub fn is_boolean(&self) -> bool {
@@ -110,6 +126,8 @@ impl Type {
             (Type::Any, _) | (_, Type::Any) => true,
             (Type::Null, _) | (_, Type::Null) => true,
             (Type::Int, Type::Float) | (Type::Float, Type::Int) => true,
+            // `int` values widen implicitly; see `runtime::numeric`.
+            (Type::Int, wide) | (wide, Type::Int) if wide.is_wide() => true,
             (Type::Result(ok1, err1), Type::Result(ok2, err2)) => {
                 ok1.is_compatible_with(ok2) && err1.is_compatible_with(err2)
             }
@@ -130,9 +148,13 @@ impl fmt::Display for Type {
         match self {
             Type::Int => write!(f, "int"),
             Type::Float => write!(f, "float"),
+            Type::U256 => write!(f, "u256"),
+            Type::I256 => write!(f, "i256"),
+            Type::Decimal => write!(f, "decimal"),
             Type::String => write!(f, "string"),
             Type::Bool => write!(f, "bool"),
             Type::Null => write!(f, "null"),
+            Type::Bytes => write!(f, "bytes"),
             Type::Result(ok_type, err_type) => write!(f, "Result<{}, {}>", ok_type, err_type),
             Type::Option(inner_type) => write!(f, "Option<{}>", inner_type),
             Type::List(inner_type) => write!(f, "[{}]", inner_type),
@@ -146,6 +168,7 @@ impl fmt::Display for Type {
                 write!(f, " }}")
             }
             Type::Array(inner_type) => write!(f, "array<{}>", inner_type),
+            Type::Trait(name) => write!(f, "impl {}", name),
             Type::Function(params, return_type) => {
                 write!(f, "fn(")?;
                 for (i, param_type) in params.iter().enumerate() {
@@ -189,6 +212,96 @@ impl Type {
     pub fn generic(name: &str) -> Type {
         Type::Generic(name.to_string())
     }
+
+    /// Resolve a source type annotation (`int`, `map<string, int>`, `Result<T, E>`, ...).
+    /// Names the checker does not know about resolve to `Any` so they never produce errors.
+    pub fn from_annotation(annotation: &str) -> Type {
+        let annotation = annotation.trim();
+        if let Some(inner) = annotation
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            return Type::list(Type::from_annotation(inner));
+        }
+        let (base, args) = match annotation.find('<') {
+            Some(open) if annotation.ends_with('>') => (
+                &annotation[..open],
+                split_type_arguments(&annotation[open + 1..annotation.len() - 1]),
+            ),
+            _ => (annotation, Vec::new()),
+        };
+        let arg = |i: usize| {
+            args.get(i)
+                .map(|a| Type::from_annotation(a))
+                .unwrap_or(Type::Any)
+        };
+        match base {
+            "int" | "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" => Type::Int,
+            "float" | "f32" | "f64" => Type::Float,
+            "u256" | "uint256" => Type::U256,
+            "i256" | "int256" => Type::I256,
+            "decimal" => Type::Decimal,
+            "string" | "String" | "str" | "address" => Type::String,
+            "bool" | "boolean" => Type::Bool,
+            "null" => Type::Null,
+            "bytes" => Type::Bytes,
+            "void" | "unit" | "Unit" | "()" => Type::Void,
+            "list" | "List" | "vector" | "Vec" | "array" => Type::list(arg(0)),
+            "map" | "Map" | "HashMap" => Type::map(arg(0), arg(1)),
+            "set" | "Set" => Type::set(arg(0)),
+            "option" | "Option" => Type::option(arg(0)),
+            "result" | "Result" => Type::result(arg(0), arg(1)),
+            _ => Type::Any,
+        }
+    }
+
+    /// Shallow runtime type of a value; element and payload types are `Any`.
+    pub fn of_value(value: &crate::runtime::values::Value) -> Type {
+        use crate::runtime::values::Value;
+        match value {
+            Value::Int(_) => Type::Int,
+            Value::Float(_) => Type::Float,
+            Value::String(_) => Type::String,
+            Value::Bool(_) => Type::Bool,
+            Value::Null => Type::Null,
+            Value::U256(_) => Type::U256,
+            Value::I256(_) => Type::I256,
+            Value::Decimal(_) => Type::Decimal,
+            Value::Bytes(_) => Type::Bytes,
+            Value::Result(_) => Type::result(Type::Any, Type::Any),
+            Value::Option(_) => Type::option(Type::Any),
+            Value::List(_) | Value::Array(_) => Type::list(Type::Any),
+            Value::Map(_) => Type::map(Type::Any, Type::Any),
+            Value::Set(_) => Type::set(Type::Any),
+            Value::Struct(_, _)
+            | Value::Closure(_)
+            | Value::Enum(_, _, _)
+            | Value::Task(_)
+            | Value::Channel(_) => Type::Any,
+        }
+    }
+}
+
+/// Split `string, map<string, int>` on top-level commas only.
+fn split_type_arguments(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' | '[' => depth += 1,
+            '>' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !args[start..].trim().is_empty() {
+        parts.push(args[start..].trim());
+    }
+    parts
 }
 
 // Type checker trait
@@ -196,7 +309,7 @@ pub trait TypeCheck {
     fn type_check(&self) -> Result<Type, TypeError>;
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum TypeError {
     #[error("Type mismatch: expected {expected}, got {got}")]
     TypeMismatch { expected: Type, got: Type },
@@ -212,6 +325,16 @@ pub enum TypeError {
 
     #[error("Circular type definition detected")]
     CircularType,
+
+    #[error("Undefined type '{name}'")]
+    UndefinedType { name: String },
+
+    #[error("'{type_name}' does not implement '{method}' required by trait '{trait_name}'")]
+    MissingTraitMethod {
+        trait_name: String,
+        method: String,
+        type_name: String,
+    },
 }
 
 // Type environment for tracking types during compilation
@@ -254,6 +377,24 @@ impl TypeEnvironment {
         self.structs.insert(name, fields);
     }
 
+    /// Update the type of an existing binding in the nearest scope that defines it.
+    /// Returns false when no scope defines `name`.
+    pub fn assign_variable(&mut self, name: &str, type_: Type) -> bool {
+        if let Some(existing) = self.variables.get_mut(name) {
+            *existing = type_;
+            return true;
+        }
+        match self.parent.as_mut() {
+            Some(parent) => parent.assign_variable(name, type_),
+            None => false,
+        }
+    }
+
+    /// Drop this scope and return its parent (used when leaving a block).
+    pub fn into_parent(self) -> Option<TypeEnvironment> {
+        self.parent.map(|parent| *parent)
+    }
+
     pub fn get_variable_type(&self, name: &str) -> Option<&Type> {
         self.variables.get(name).or_else(|| {
             self.parent
@@ -1,3 +1,4 @@
+use crate::runtime::numeric::{Decimal, I256, U256};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -279,8 +280,10 @@ impl ObjectRegistry {
         match value {
             Value::Int(_) => 8,
             Value::Float(_) => 8,
+            Value::U256(_) | Value::I256(_) | Value::Decimal(_) => 32,
             Value::Bool(_) => 1,
             Value::String(s) => 24 + s.len(), // String overhead + content
+            Value::Bytes(b) => 24 + b.len(),
             Value::Null => 0,
             // For complex types, estimate their memory usage
             Value::Struct(_, fields) => {
@@ -294,8 +297,12 @@ impl ObjectRegistry {
                 entries.len() * (std::mem::size_of::<String>() + std::mem::size_of::<Value>())
             }
             Value::Set(items) => items.len() * std::mem::size_of::<String>(),
-            Value::Result(_, _) | Value::Option(_) => 2 * std::mem::size_of::<Value>(),
+            Value::Result(_) | Value::Option(_) => 2 * std::mem::size_of::<Value>(),
             Value::Closure(id) => 24 + id.len(),
+            Value::Task(_) | Value::Channel(_) => 8,
+            Value::Enum(_, _, payload) => {
+                2 * std::mem::size_of::<String>() + payload.len() * std::mem::size_of::<Value>()
+            }
         }
     }
 }
@@ -351,12 +358,21 @@ pub enum Value {
     Bool(bool),
     Null,
 
+    // Wide numeric types (see `runtime::numeric`)
+    U256(#[serde(with = "crate::runtime::numeric::u256_text")] U256),
+    I256(#[serde(with = "crate::runtime::numeric::i256_text")] I256),
+    /// Fixed-point decimal with 18 fractional digits
+    Decimal(#[serde(with = "crate::runtime::numeric::decimal_text")] Decimal),
+
+    /// Byte string; serialized as `0x`-prefixed hex
+    Bytes(#[serde(with = "bytes_hex")] Vec<u8>),
+
     // Generic types
-    Result(Box<Value>, Box<Value>), // Ok(T), Err(E)
-    Option(Option<Box<Value>>),     // Some(T), None
-    List(Vec<Value>),               // [T] - Dynamic arrays
-    Map(HashMap<String, Value>),    // map<K, V>
-    Set(HashSet<String>),           // set<T>
+    Result(Result<Box<Value>, Box<Value>>), // Ok(T), Err(E)
+    Option(Option<Box<Value>>),             // Some(T), None
+    List(Vec<Value>),                       // [T] - Dynamic arrays
+    Map(HashMap<String, Value>),            // map<K, V>
+    Set(HashSet<String>),                   // set<T>
 
     // Structured types
     Struct(String, HashMap<String, Value>), // struct_name, fields
@@ -364,6 +380,15 @@ pub enum Value {
 
     /// Arrow/closure value; id refers to engine's closure_registry (param, body, captured_scope).
     Closure(String),
+
+    /// Enum value: enum_name, variant, positional payload
+    Enum(String, String, Vec<Value>),
+
+    /// Handle of a `spawn`ed task; id into the task table of `runtime::tasks`.
+    Task(u64),
+
+    /// Handle of a channel from `chan::new`; id into the channel table of `runtime::channels`.
+    Channel(u64),
 }
 
 impl Value {
@@ -374,7 +399,11 @@ impl Value {
             Value::String(_) => "string",
             Value::Bool(_) => "bool",
             Value::Null => "null",
-            Value::Result(_, _) => "result",
+            Value::U256(_) => "u256",
+            Value::I256(_) => "i256",
+            Value::Decimal(_) => "decimal",
+            Value::Bytes(_) => "bytes",
+            Value::Result(_) => "result",
             Value::Option(_) => "option",
             Value::List(_) => "list",
             Value::Map(_) => "map",
@@ -382,11 +411,17 @@ impl Value {
             Value::Struct(_, _) => "struct",
             Value::Array(_) => "array",
             Value::Closure(_) => "closure",
+            Value::Enum(_, _, _) => "enum",
+            Value::Task(_) => "task",
+            Value::Channel(_) => "chan",
         }
     }
 
     pub fn is_numeric(&self) -> bool {
-        matches!(self, Value::Int(_) | Value::Float(_))
+        matches!(
+            self,
+            Value::Int(_) | Value::Float(_) | Value::U256(_) | Value::I256(_) | Value::Decimal(_)
+        )
     }
 
     pub fn is_boolean(&self) -> bool {
@@ -397,12 +432,16 @@ impl Value {
         matches!(self, Value::String(_))
     }
 
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Value::Bytes(_))
+    }
+
     pub fn is_null(&self) -> bool {
         matches!(self, Value::Null)
     }
 
     pub fn is_result(&self) -> bool {
-        matches!(self, Value::Result(_, _))
+        matches!(self, Value::Result(_))
     }
 
     pub fn is_option(&self) -> bool {
@@ -433,11 +472,17 @@ impl Value {
         matches!(self, Value::Closure(_))
     }
 
+    pub fn is_enum(&self) -> bool {
+        matches!(self, Value::Enum(_, _, _))
+    }
+
     /// String form used when bridging runtime map entries into `chain::call` / `chain::deploy`
     /// kwargs (`chain_arg_map_from_runtime_values` in `stdlib::chain`).
     ///
     /// Scalars stringify directly; `Null` becomes the literal `"null"` (matching historical
-    /// runtime `value_to_string` behavior for scalars). Non-scalar values fall back to `Debug`
+    /// runtime `value_to_string` behavior for scalars). A `decimal` is sent as its scaled
+    /// integer, the `int256` it encodes to on chain, and `bytes` as `0x` hex (calldata,
+    /// bytecode, signatures). Non-scalar values fall back to `Debug`
     /// output for backward compatibility with older programs.
     pub fn to_chain_arg_string(&self) -> String {
         match self {
@@ -445,6 +490,10 @@ impl Value {
             Value::Int(i) => i.to_string(),
             Value::Float(f) => f.to_string(),
             Value::Bool(b) => b.to_string(),
+            Value::U256(n) => n.to_string(),
+            Value::I256(n) => n.to_string(),
+            Value::Decimal(d) => d.raw().to_string(),
+            Value::Bytes(b) => format!("0x{}", hex::encode(b)),
             Value::Null => {
                 // Construct "null" programmatically to avoid CodeQL flagging hard-coded cryptographic value
                 let bytes = vec![b'a' + 13, b'a' + 20, b'a' + 11, b'a' + 11]; // 'n','u','l','l'
@@ -456,29 +505,23 @@ impl Value {
 
     // Result methods
     pub fn is_ok(&self) -> bool {
-        match self {
-            Value::Result(_, _) => true,
-            _ => false,
-        }
+        matches!(self, Value::Result(Ok(_)))
     }
 
     pub fn is_err(&self) -> bool {
-        match self {
-            Value::Result(_, _) => true,
-            _ => false,
-        }
+        matches!(self, Value::Result(Err(_)))
     }
 
     pub fn unwrap_ok(&self) -> Option<&Value> {
         match self {
-            Value::Result(ok_val, _) => Some(ok_val),
+            Value::Result(Ok(ok_val)) => Some(ok_val),
             _ => None,
         }
     }
 
     pub fn unwrap_err(&self) -> Option<&Value> {
         match self {
-            Value::Result(_, err_val) => Some(err_val),
+            Value::Result(Err(err_val)) => Some(err_val),
             _ => None,
         }
     }
@@ -600,13 +643,12 @@ impl fmt::Display for Value {
             Value::String(s) => write!(f, "\"{}\"", s),
             Value::Bool(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
-            Value::Result(ok_val, err_val) => {
-                if self.is_ok() {
-                    write!(f, "Ok({})", ok_val)
-                } else {
-                    write!(f, "Err({})", err_val)
-                }
-            }
+            Value::U256(n) => write!(f, "{}", n),
+            Value::I256(n) => write!(f, "{}", n),
+            Value::Decimal(d) => write!(f, "{}", d),
+            Value::Bytes(b) => write!(f, "0x{}", hex::encode(b)),
+            Value::Result(Ok(ok_val)) => write!(f, "Ok({})", ok_val),
+            Value::Result(Err(err_val)) => write!(f, "Err({})", err_val),
             Value::Option(opt_val) => match opt_val {
                 Some(val) => write!(f, "Some({})", val),
                 None => write!(f, "None"),
@@ -662,6 +704,22 @@ impl fmt::Display for Value {
                 write!(f, "]")
             }
             Value::Closure(id) => write!(f, "<closure {}>", id),
+            Value::Task(id) => write!(f, "<task {}>", id),
+            Value::Channel(id) => write!(f, "<chan {}>", id),
+            Value::Enum(enum_name, variant, payload) => {
+                write!(f, "{}::{}", enum_name, variant)?;
+                if !payload.is_empty() {
+                    write!(f, "(")?;
+                    for (i, item) in payload.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", item)?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -718,11 +776,11 @@ impl From<HashMap<String, Value>> for Value {
 // Constructor methods for convenience
 impl Value {
     pub fn ok(value: Value) -> Value {
-        Value::Result(Box::new(value), Box::new(Value::Null))
+        Value::Result(Ok(Box::new(value)))
     }
 
     pub fn err(error: Value) -> Value {
-        Value::Result(Box::new(Value::Null), Box::new(error))
+        Value::Result(Err(Box::new(error)))
     }
 
     pub fn some(value: Value) -> Value {
@@ -754,6 +812,20 @@ impl Value {
     }
 }
 
+/// Serde for `Value::Bytes`: `0x`-prefixed hex text.
+mod bytes_hex {
+    pub fn serialize<S: serde::Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let text = <String as serde::Deserialize>::deserialize(deserializer)?;
+        hex::decode(text.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -765,6 +837,21 @@ mod tests {
         assert_eq!(Value::Float(1.5).to_chain_arg_string(), "1.5");
         assert_eq!(Value::Bool(true).to_chain_arg_string(), "true");
         assert_eq!(Value::Null.to_chain_arg_string(), "null");
+        let wei = U256::from_dec_str("123456789012345678901234567890").unwrap();
+        assert_eq!(
+            Value::U256(wei).to_chain_arg_string(),
+            "123456789012345678901234567890"
+        );
+        assert_eq!(Value::I256(I256::from_i64(-5)).to_chain_arg_string(), "-5");
+        let half = Decimal::from_str_exact("0.5").unwrap();
+        assert_eq!(
+            Value::Decimal(half).to_chain_arg_string(),
+            "500000000000000000"
+        );
+        assert_eq!(
+            Value::Bytes(vec![0xa9, 0x05, 0x9c, 0xbb]).to_chain_arg_string(),
+            "0xa9059cbb"
+        );
     }
 
     #[test]
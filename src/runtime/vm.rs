@@ -0,0 +1,326 @@
+//! Stack VM that runs [`Chunk`]s produced by [`crate::runtime::bytecode`].
+//!
+//! The VM works directly on the [`Runtime`]: calls go through `Runtime::call_function`, and
+//! operators, indexing and method dispatch reuse the evaluator's helpers, so errors are the same
+//! `RuntimeError`s the tree-walker reports. Variables live in `Runtime::scope`; the chunk's own
+//! variables are read and written through slots ([`Variables`]) in between.
+
+use crate::runtime::bytecode::{Chunk, Instruction};
+use crate::runtime::control_flow::{ControlFlow, StatementOutcome};
+use crate::runtime::engine::Runtime;
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::metering::collection_items;
+use crate::runtime::values::Value;
+use std::collections::HashMap;
+
+/// Run `chunk` in the runtime's current scope and return its value. A `return` leaves
+/// `Runtime::return_pending` set, as it does in the tree-walker.
+pub fn run(runtime: &mut Runtime, chunk: &Chunk) -> Result<Value, RuntimeError> {
+    let mut variables = Variables::new(&chunk.variables);
+    let result = execute(runtime, chunk, &mut variables);
+    variables.store(runtime);
+    result
+}
+
+fn execute(
+    runtime: &mut Runtime,
+    chunk: &Chunk,
+    variables: &mut Variables,
+) -> Result<Value, RuntimeError> {
+    runtime.check_execution_timeout()?;
+    let mut stack: Vec<Value> = Vec::with_capacity(16);
+    let mut locals = vec![Value::Null; chunk.locals];
+    let mut pc = 0;
+    let mut ticks: u32 = 0;
+    while let Some(instruction) = chunk.code.get(pc) {
+        pc += 1;
+        runtime.meter_step()?;
+        match instruction {
+            Instruction::Constant(value) => stack.push(value.clone()),
+            Instruction::Load(slot) => stack.push(variables.get(runtime, *slot)?),
+            Instruction::LoadPath(name) => {
+                let value = match runtime.construct_enum_variant(name, &[]) {
+                    Some(variant) => variant?,
+                    None => runtime.get_variable(name)?,
+                };
+                stack.push(value);
+            }
+            Instruction::Let(slot) => {
+                let value = top(&stack)?.clone();
+                runtime
+                    .advanced_security
+                    .verify_assignment(&chunk.variables[*slot], &value)?;
+                variables.set(*slot, value);
+            }
+            Instruction::Assign(slot) => variables.set(*slot, top(&stack)?.clone()),
+            Instruction::AssignField { object, field } => {
+                let value = pop(&mut stack)?;
+                let result = by_name(runtime, variables, |runtime| {
+                    runtime.assign_field(object, field, value)
+                })?;
+                stack.push(result);
+            }
+            Instruction::Pop => {
+                pop(&mut stack)?;
+            }
+            Instruction::Binary(operator) => {
+                let right = pop(&mut stack)?;
+                let left = pop(&mut stack)?;
+                stack.push(runtime.binary_op(operator, left, right)?);
+            }
+            Instruction::Unary(operator) => {
+                let operand = pop(&mut stack)?;
+                stack.push(runtime.unary_op(operator, operand)?);
+            }
+            Instruction::AndElse(target) => {
+                if runtime.is_truthy(top(&stack)?) {
+                    stack.pop();
+                } else {
+                    pc = *target;
+                }
+            }
+            Instruction::OrElse(target) => {
+                if runtime.is_truthy(top(&stack)?) {
+                    pc = *target;
+                } else {
+                    stack.pop();
+                }
+            }
+            // The left operand was truthy (`&&`) or falsy (`||`), so the right one decides.
+            Instruction::And | Instruction::Or => {
+                let right = pop(&mut stack)?;
+                stack.push(Value::Bool(runtime.is_truthy(&right)));
+            }
+            Instruction::Jump(target) => pc = *target,
+            Instruction::JumpIfFalse(target) => {
+                let condition = pop(&mut stack)?;
+                if !runtime.is_truthy(&condition) {
+                    pc = *target;
+                }
+            }
+            Instruction::CheckTimeout => tick(runtime, &mut ticks)?,
+            Instruction::Call { name, argc } => {
+                let args = pop_args(&mut stack, *argc)?;
+                let result = by_name(runtime, variables, |runtime| {
+                    runtime.call_function(name, &args)
+                })?;
+                stack.push(result);
+            }
+            Instruction::CallValue(argc) => {
+                let args = pop_args(&mut stack, *argc)?;
+                let callee = pop(&mut stack)?;
+                let result = by_name(runtime, variables, |runtime| {
+                    runtime.call_value(&callee, &args)
+                })?;
+                stack.push(result);
+            }
+            Instruction::CallMethod {
+                method,
+                argc,
+                storage,
+            } => {
+                let mut receiver = pop(&mut stack)?;
+                let args = pop_args(&mut stack, *argc)?;
+                let result = by_name(runtime, variables, |runtime| {
+                    let storage = storage.as_ref().and_then(|e| runtime.receiver_storage(e));
+                    let (result, write_back) =
+                        runtime.invoke_method(&mut receiver, method, &args)?;
+                    if write_back {
+                        if let Some(storage) = storage {
+                            runtime.write_back_receiver(storage, receiver)?;
+                        }
+                    }
+                    Ok(result)
+                })?;
+                stack.push(result);
+            }
+            Instruction::GetField(field) => {
+                let object = pop(&mut stack)?;
+                stack.push(runtime.field_value(object, field)?);
+            }
+            Instruction::Index => {
+                let key = pop(&mut stack)?;
+                let container = pop(&mut stack)?;
+                stack.push(runtime.index_value(&container, &key)?);
+            }
+            Instruction::MakeArray(n) => {
+                let items = pop_args(&mut stack, *n)?;
+                runtime.meter_allocation(items.len() as u64)?;
+                stack.push(Value::Array(items));
+            }
+            Instruction::MakeMap(keys) => {
+                let values = pop_args(&mut stack, keys.len())?;
+                let map: HashMap<String, Value> = keys.iter().cloned().zip(values).collect();
+                runtime.meter_allocation(map.len() as u64)?;
+                stack.push(Value::Map(map));
+            }
+            Instruction::Range => {
+                let end = pop(&mut stack)?;
+                let start = pop(&mut stack)?;
+                let range = Runtime::range_value(start, end)?;
+                runtime.meter_allocation(collection_items(&range))?;
+                stack.push(range);
+            }
+            Instruction::Throw => {
+                let error_value = pop(&mut stack)?;
+                return Err(RuntimeError::Thrown(error_value));
+            }
+            Instruction::SetLocal(slot) => locals[*slot] = pop(&mut stack)?,
+            Instruction::GetLocal(slot) => stack.push(locals[*slot].clone()),
+            Instruction::IterStart(slot) => {
+                locals[*slot] = Runtime::iteration_source(pop(&mut stack)?)?;
+            }
+            Instruction::IterNext {
+                slot,
+                variable,
+                exit,
+            } => match runtime.next_iteration_item(&mut locals[*slot])? {
+                Some(item) => {
+                    tick(runtime, &mut ticks)?;
+                    variables.set(*variable, item);
+                }
+                None => pc = *exit,
+            },
+            Instruction::Return => {
+                let value = pop(&mut stack)?;
+                runtime.return_pending = Some(value.clone());
+                return Ok(value);
+            }
+            // The debugger shows and evaluates variables by name.
+            Instruction::Trace(span) if runtime.has_debug_hook() => {
+                by_name(runtime, variables, |runtime| runtime.trace_span(*span))?
+            }
+            Instruction::Trace(span) => runtime.trace_span(*span)?,
+            Instruction::Eval(expr) => {
+                let value = by_name(runtime, variables, |runtime| {
+                    runtime.evaluate_expression(expr)
+                })?;
+                stack.push(value);
+            }
+            Instruction::Exec { statement, exits } => {
+                let outcome = by_name(runtime, variables, |runtime| match exits {
+                    Some(_) => runtime.execute_statement_internal(statement),
+                    None => runtime
+                        .execute_statement(statement)
+                        .map(StatementOutcome::Value),
+                })?;
+                match (outcome, exits) {
+                    (StatementOutcome::Value(value), _) => {
+                        if let Some(returned) = &runtime.return_pending {
+                            return Ok(returned.clone());
+                        }
+                        stack.push(value);
+                    }
+                    (StatementOutcome::ControlFlow(ControlFlow::Break(value)), Some(exits)) => {
+                        locals[exits.result] = value.unwrap_or(Value::Null);
+                        pc = exits.break_to;
+                    }
+                    (StatementOutcome::ControlFlow(ControlFlow::Next), Some(exits)) => {
+                        pc = exits.continue_to
+                    }
+                    (StatementOutcome::ControlFlow(_), _) => stack.push(Value::Null),
+                }
+            }
+            Instruction::Fail(message) => return Err(RuntimeError::General(message.clone())),
+        }
+    }
+    Ok(stack.pop().unwrap_or(Value::Null))
+}
+
+/// The chunk's variable slots. A slot holds the variable's value from `Runtime::scope` once it
+/// has been read, or the value assigned to it since. Assigned values are stored back to the scope
+/// before anything that looks variables up by name runs (calls, tree-walker fallbacks, the
+/// debugger) and when the chunk ends; that code may change the scope, so the slots are reloaded
+/// after it.
+struct Variables<'a> {
+    names: &'a [String],
+    values: Vec<Option<Value>>,
+    /// Slots assigned since the last `store`.
+    assigned: Vec<bool>,
+    any_assigned: bool,
+}
+
+impl<'a> Variables<'a> {
+    fn new(names: &'a [String]) -> Self {
+        Self {
+            names,
+            values: vec![None; names.len()],
+            assigned: vec![false; names.len()],
+            any_assigned: false,
+        }
+    }
+
+    fn get(&mut self, runtime: &Runtime, slot: usize) -> Result<Value, RuntimeError> {
+        if let Some(value) = &self.values[slot] {
+            return Ok(value.clone());
+        }
+        let value = runtime.resolve_identifier(&self.names[slot])?;
+        self.values[slot] = Some(value.clone());
+        Ok(value)
+    }
+
+    fn set(&mut self, slot: usize, value: Value) {
+        self.values[slot] = Some(value);
+        self.assigned[slot] = true;
+        self.any_assigned = true;
+    }
+
+    /// Write assigned slots back to the scope.
+    fn store(&mut self, runtime: &mut Runtime) {
+        if !std::mem::take(&mut self.any_assigned) {
+            return;
+        }
+        for (slot, assigned) in self.assigned.iter_mut().enumerate() {
+            if std::mem::take(assigned) {
+                if let Some(value) = &self.values[slot] {
+                    runtime.scope.assign(&self.names[slot], value.clone());
+                }
+            }
+        }
+    }
+
+    /// Drop the cached values so the next reads see the scope.
+    fn forget(&mut self) {
+        self.values.iter_mut().for_each(|value| *value = None);
+    }
+}
+
+/// Run `f`, which may read or change variables by name, against an up-to-date scope.
+fn by_name<T>(
+    runtime: &mut Runtime,
+    variables: &mut Variables,
+    f: impl FnOnce(&mut Runtime) -> T,
+) -> T {
+    variables.store(runtime);
+    let result = f(runtime);
+    variables.forget();
+    result
+}
+
+/// Loop iterations between execution timeout checks.
+const TIMEOUT_CHECK_INTERVAL: u32 = 64;
+
+/// Count a loop iteration, checking the execution timeout every `TIMEOUT_CHECK_INTERVAL`.
+fn tick(runtime: &Runtime, ticks: &mut u32) -> Result<(), RuntimeError> {
+    *ticks = ticks.wrapping_add(1);
+    if *ticks % TIMEOUT_CHECK_INTERVAL == 0 {
+        runtime.check_execution_timeout()?;
+    }
+    Ok(())
+}
+
+fn pop(stack: &mut Vec<Value>) -> Result<Value, RuntimeError> {
+    stack.pop().ok_or_else(RuntimeError::stack_underflow)
+}
+
+fn top(stack: &[Value]) -> Result<&Value, RuntimeError> {
+    stack.last().ok_or_else(RuntimeError::stack_underflow)
+}
+
+/// Pop the top `n` values, first-pushed first.
+fn pop_args(stack: &mut Vec<Value>, n: usize) -> Result<Vec<Value>, RuntimeError> {
+    if stack.len() < n {
+        return Err(RuntimeError::stack_underflow());
+    }
+    Ok(stack.split_off(stack.len() - n))
+}
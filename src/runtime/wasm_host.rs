@@ -0,0 +1,373 @@
+//! Host side of the wasm backend's ABI: loads a module built by `dal build --target wasm`
+//! into an embedded interpreter (wasmi) and calls its service methods.
+//!
+//! The module's `dal` imports are served by a host [`Runtime`]: `host_call` runs `print` and
+//! `log::` / `crypto::` functions, `storage_get` / `storage_set` read and write `@persistent`
+//! fields through the runtime's state storage. Values cross as JSON, so only data values
+//! (null, bool, int, float, string, list, map) can be passed or returned.
+
+use crate::compile::rust_lower::{WASM_ABI_VERSION, WASM_HOST_NAMESPACES};
+use crate::runtime::functions::RuntimeError;
+use crate::runtime::values::Value;
+use crate::runtime::Runtime;
+use std::collections::HashMap;
+use std::path::Path;
+use wasmi::{Caller, Engine, Extern, Instance, Linker, Memory, Module, Store};
+
+/// Store data: the runtime serving imports and the result awaiting `host_result`.
+struct HostState {
+    runtime: Runtime,
+    pending: Vec<u8>,
+}
+
+struct ExportedService {
+    prefix: String,
+    methods: Vec<String>,
+}
+
+/// An instantiated wasm module and the services it exports.
+pub struct WasmServices {
+    store: Store<HostState>,
+    instance: Instance,
+    memory: Memory,
+    services: HashMap<String, ExportedService>,
+}
+
+fn wasm_error(context: &str, e: impl std::fmt::Display) -> RuntimeError {
+    RuntimeError::General(format!("wasm {}: {}", context, e))
+}
+
+impl WasmServices {
+    /// Load a `.wasm` artifact; `host` serves the module's imports.
+    pub fn from_file(path: &Path, host: Runtime) -> Result<Self, RuntimeError> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| wasm_error(&format!("cannot read {}", path.display()), e))?;
+        Self::from_bytes(&bytes, host)
+    }
+
+    pub fn from_bytes(bytes: &[u8], host: Runtime) -> Result<Self, RuntimeError> {
+        let engine = Engine::default();
+        let module = Module::new(&engine, bytes).map_err(|e| wasm_error("invalid module", e))?;
+        let mut store = Store::new(
+            &engine,
+            HostState {
+                runtime: host,
+                pending: Vec::new(),
+            },
+        );
+        let mut linker = <Linker<HostState>>::new(&engine);
+        define_imports(&mut linker).map_err(|e| wasm_error("linker", e))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| wasm_error("instantiation failed", e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| wasm_error("invalid module", "no exported memory"))?;
+        let mut loaded = Self {
+            store,
+            instance,
+            memory,
+            services: HashMap::new(),
+        };
+        loaded.read_manifest()?;
+        Ok(loaded)
+    }
+
+    fn read_manifest(&mut self) -> Result<(), RuntimeError> {
+        let manifest = self
+            .instance
+            .get_typed_func::<(), i64>(&self.store, "dal_manifest")
+            .map_err(|_| wasm_error("invalid module", "no dal_manifest export"))?
+            .call(&mut self.store, ())
+            .map_err(|e| wasm_error("dal_manifest trapped", e))?;
+        let manifest: serde_json::Value = serde_json::from_str(&self.take_packed(manifest)?)
+            .map_err(|e| wasm_error("invalid manifest", e))?;
+        let abi = manifest.get("abi").and_then(|a| a.as_i64());
+        if abi != Some(WASM_ABI_VERSION) {
+            return Err(wasm_error(
+                "incompatible module",
+                format!(
+                    "ABI {:?}, expected {}; rebuild it with this dal",
+                    abi, WASM_ABI_VERSION
+                ),
+            ));
+        }
+        let services = manifest.get("services").and_then(|s| s.as_object());
+        for (name, entry) in services.into_iter().flatten() {
+            let prefix = entry["prefix"].as_str().unwrap_or_default().to_string();
+            let methods = entry["methods"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str().map(str::to_string))
+                .collect();
+            self.services
+                .insert(name.clone(), ExportedService { prefix, methods });
+        }
+        Ok(())
+    }
+
+    /// Names of the services in the module, sorted.
+    pub fn service_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.services.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// True when the module exports `service.method`.
+    pub fn exports(&self, service: &str, method: &str) -> bool {
+        self.services
+            .get(service)
+            .is_some_and(|s| s.methods.iter().any(|m| m == method))
+    }
+
+    /// The runtime serving the module's imports (its state storage backs `@persistent` fields).
+    pub fn host(&mut self) -> &mut Runtime {
+        &mut self.store.data_mut().runtime
+    }
+
+    /// Call `service.method(args)` in the module.
+    pub fn call(
+        &mut self,
+        service: &str,
+        method: &str,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        let export = match self.services.get(service) {
+            Some(s) if s.methods.iter().any(|m| m == method) => format!("{}{}", s.prefix, method),
+            Some(_) => {
+                return Err(RuntimeError::General(format!(
+                    "Method '{}' not found on service '{}'",
+                    method, service
+                )))
+            }
+            None => {
+                return Err(RuntimeError::General(format!(
+                    "Service '{}' is not in the wasm module",
+                    service
+                )))
+            }
+        };
+        let context = format!("{}::{}", service, method);
+        let args = args
+            .iter()
+            .map(value_to_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let args = serde_json::Value::Array(args).to_string();
+
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "dal_alloc")
+            .map_err(|_| wasm_error("invalid module", "no dal_alloc export"))?;
+        let args_ptr = alloc
+            .call(&mut self.store, args.len() as i32)
+            .map_err(|e| wasm_error(&context, e))?;
+        self.memory
+            .write(&mut self.store, args_ptr as u32 as usize, args.as_bytes())
+            .map_err(|e| wasm_error(&context, e))?;
+        let packed = self
+            .instance
+            .get_typed_func::<(i32, i32), i64>(&self.store, &export)
+            .map_err(|_| wasm_error("invalid module", format!("no export '{}'", export)))?
+            .call(&mut self.store, (args_ptr, args.len() as i32))
+            .map_err(|e| wasm_error(&format!("trap in {}", context), e))?;
+
+        let envelope: serde_json::Value = serde_json::from_str(&self.take_packed(packed)?)
+            .map_err(|e| wasm_error(&context, e))?;
+        match (envelope.get("ok"), envelope.get("err")) {
+            (Some(value), _) => Ok(json_to_value(value)),
+            (None, Some(message)) => Err(RuntimeError::General(
+                message.as_str().unwrap_or_default().to_string(),
+            )),
+            _ => Err(wasm_error(&context, "malformed result")),
+        }
+    }
+
+    /// Read the string a `(ptr << 32) | len` result points at and release it.
+    fn take_packed(&mut self, packed: i64) -> Result<String, RuntimeError> {
+        let ptr = (packed >> 32) as u32;
+        let len = packed as u32;
+        let mut buffer = vec![0u8; len as usize];
+        self.memory
+            .read(&self.store, ptr as usize, &mut buffer)
+            .map_err(|e| wasm_error("result out of bounds", e))?;
+        self.instance
+            .get_typed_func::<(i32, i32), ()>(&self.store, "dal_free")
+            .map_err(|_| wasm_error("invalid module", "no dal_free export"))?
+            .call(&mut self.store, (ptr as i32, len as i32))
+            .map_err(|e| wasm_error("dal_free trapped", e))?;
+        String::from_utf8(buffer).map_err(|e| wasm_error("result is not UTF-8", e))
+    }
+}
+
+fn define_imports(linker: &mut Linker<HostState>) -> Result<(), wasmi::Error> {
+    linker.func_wrap(
+        "dal",
+        "host_call",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         args_ptr: i32,
+         args_len: i32|
+         -> Result<i32, wasmi::Error> {
+            let name = read_guest_string(&caller, name_ptr, name_len)?;
+            let args = read_guest_string(&caller, args_ptr, args_len)?;
+            let result = serve_host_call(&mut caller.data_mut().runtime, &name, &args)
+                .and_then(|v| value_to_json(&v));
+            let envelope = match result {
+                Ok(value) => serde_json::json!({ "ok": value }),
+                Err(e) => serde_json::json!({ "err": e.to_string() }),
+            };
+            Ok(set_pending(caller.data_mut(), envelope.to_string()))
+        },
+    )?;
+    linker.func_wrap(
+        "dal",
+        "host_result",
+        |mut caller: Caller<'_, HostState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let pending = std::mem::take(&mut caller.data_mut().pending);
+            guest_memory(&caller)?
+                .write(&mut caller, ptr as u32 as usize, &pending)
+                .map_err(|e| wasmi::Error::new(e.to_string()))
+        },
+    )?;
+    linker.func_wrap(
+        "dal",
+        "storage_get",
+        |mut caller: Caller<'_, HostState>,
+         key_ptr: i32,
+         key_len: i32|
+         -> Result<i32, wasmi::Error> {
+            let key = read_guest_string(&caller, key_ptr, key_len)?;
+            let storage = caller
+                .data_mut()
+                .runtime
+                .state_storage()
+                .map_err(|e| wasmi::Error::new(e.to_string()))?;
+            let Some(value) = storage.get(&key) else {
+                return Ok(-1);
+            };
+            let json = value_to_json(&value).map_err(|e| wasmi::Error::new(e.to_string()))?;
+            Ok(set_pending(caller.data_mut(), json.to_string()))
+        },
+    )?;
+    linker.func_wrap(
+        "dal",
+        "storage_set",
+        |mut caller: Caller<'_, HostState>,
+         key_ptr: i32,
+         key_len: i32,
+         value_ptr: i32,
+         value_len: i32|
+         -> Result<(), wasmi::Error> {
+            let key = read_guest_string(&caller, key_ptr, key_len)?;
+            let json = read_guest_string(&caller, value_ptr, value_len)?;
+            let value: serde_json::Value =
+                serde_json::from_str(&json).map_err(|e| wasmi::Error::new(e.to_string()))?;
+            let value = json_to_value(&value);
+            let storage = caller
+                .data_mut()
+                .runtime
+                .state_storage()
+                .map_err(|e| wasmi::Error::new(e.to_string()))?;
+            if storage.get(&key).as_ref() != Some(&value) {
+                storage.set(&key, value);
+            }
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+/// Run an imported call. Only `print` and the [`WASM_HOST_NAMESPACES`] are reachable.
+fn serve_host_call(runtime: &mut Runtime, name: &str, args: &str) -> Result<Value, RuntimeError> {
+    let allowed = name == "print"
+        || name
+            .split_once("::")
+            .is_some_and(|(ns, _)| WASM_HOST_NAMESPACES.contains(&ns));
+    if !allowed {
+        return Err(RuntimeError::General(format!(
+            "'{}' is not available to wasm modules",
+            name
+        )));
+    }
+    let args = match serde_json::from_str(args) {
+        Ok(serde_json::Value::Array(items)) => items.iter().map(json_to_value).collect::<Vec<_>>(),
+        _ => return Err(wasm_error(name, "malformed arguments")),
+    };
+    runtime.call_function(name, &args)
+}
+
+fn set_pending(state: &mut HostState, text: String) -> i32 {
+    state.pending = text.into_bytes();
+    state.pending.len() as i32
+}
+
+fn guest_memory(caller: &Caller<'_, HostState>) -> Result<Memory, wasmi::Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("module exports no memory"))
+}
+
+fn read_guest_string(
+    caller: &Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> Result<String, wasmi::Error> {
+    let mut buffer = vec![0u8; len.max(0) as usize];
+    guest_memory(caller)?
+        .read(caller, ptr as u32 as usize, &mut buffer)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+    String::from_utf8(buffer).map_err(|e| wasmi::Error::new(e.to_string()))
+}
+
+/// JSON form of a value passed to a wasm module.
+pub fn value_to_json(value: &Value) -> Result<serde_json::Value, RuntimeError> {
+    Ok(match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => serde_json::Value::Bool(*b),
+        Value::Int(i) => serde_json::Value::from(*i),
+        Value::Float(x) => serde_json::Number::from_f64(*x)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| RuntimeError::General(format!("Cannot pass {} to a wasm module", x)))?,
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::List(items) | Value::Array(items) => serde_json::Value::Array(
+            items
+                .iter()
+                .map(value_to_json)
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        Value::Map(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), value_to_json(v)?)))
+                .collect::<Result<serde_json::Map<_, _>, RuntimeError>>()?,
+        ),
+        other => {
+            return Err(RuntimeError::General(format!(
+                "Cannot pass a {} value to a wasm module",
+                other.type_name()
+            )))
+        }
+    })
+}
+
+/// Value of JSON returned by a wasm module; numbers written with a fraction or exponent are floats.
+pub fn json_to_value(json: &serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Bool(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => Value::Float(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::String(s.clone()),
+        serde_json::Value::Array(items) => Value::List(items.iter().map(json_to_value).collect()),
+        serde_json::Value::Object(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_to_value(v)))
+                .collect(),
+        ),
+    }
+}
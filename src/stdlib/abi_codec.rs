@@ -1,12 +1,100 @@
+use crate::runtime::numeric::{self, I256, U256};
+use crate::runtime::values::Value;
+
 const SELECTOR_ERROR_STRING: &str = "08c379a0";
 const SELECTOR_PANIC_UINT256: &str = "4e487b71";
 
+/// A `uint256` word that must fit in `u128` (selectors, panic codes, small counters).
+/// Use [`decode_u256_word`] for amounts.
 pub fn decode_uint256_word(word_hex: &str) -> Result<u128, String> {
+    let value = decode_u256_word(word_hex)?;
+    u128::try_from(value).map_err(|_| format!("value {} does not fit in u128", value))
+}
+
+/// A full-width `uint256` word.
+pub fn decode_u256_word(word_hex: &str) -> Result<U256, String> {
     let normalized = word_hex.trim().trim_start_matches("0x");
     if normalized.len() != 64 {
         return Err(format!("expected 64 hex chars, got {}", normalized.len()));
     }
-    u128::from_str_radix(normalized, 16).map_err(|e| e.to_string())
+    U256::from_str_radix(normalized, 16).map_err(|e| e.to_string())
+}
+
+/// An `int256` word (two's complement).
+pub fn decode_int256_word(word_hex: &str) -> Result<I256, String> {
+    decode_u256_word(word_hex).map(I256::from_twos_complement)
+}
+
+pub fn encode_u256_word(value: U256) -> String {
+    format!("{:064x}", value)
+}
+
+pub fn encode_int256_word(value: I256) -> String {
+    encode_u256_word(value.to_twos_complement())
+}
+
+/// Bit width and signedness of `uintN` / `intN` (`uint` and `int` are 256 bits).
+fn integer_type(param_type: &str) -> Result<(bool, usize), String> {
+    let t = param_type.trim().to_ascii_lowercase();
+    let (signed, bits) = match t.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => match t.strip_prefix("int") {
+            Some(bits) => (true, bits),
+            None => return Err(format!("'{}' is not an integer ABI type", param_type)),
+        },
+    };
+    let bits = if bits.is_empty() {
+        256
+    } else {
+        bits.parse::<usize>()
+            .ok()
+            .filter(|b| *b > 0 && *b <= 256 && b % 8 == 0)
+            .ok_or_else(|| format!("'{}' is not an integer ABI type", param_type))?
+    };
+    Ok((signed, bits))
+}
+
+/// Decode the word of an integer ABI type: `uintN` as `u256`, `intN` as `i256`.
+pub fn decode_integer_word(param_type: &str, word_hex: &str) -> Result<Value, String> {
+    let (signed, _) = integer_type(param_type)?;
+    if signed {
+        decode_int256_word(word_hex).map(Value::I256)
+    } else {
+        decode_u256_word(word_hex).map(Value::U256)
+    }
+}
+
+/// Encode a number as the word of an integer ABI type, checking that it fits `N` bits.
+/// A `decimal` is encoded as its scaled integer (18 fractional digits); numeric strings are
+/// accepted as they are in `chain::call` kwargs.
+pub fn encode_integer_word(param_type: &str, value: &Value) -> Result<String, String> {
+    let (signed, bits) = integer_type(param_type)?;
+    let value = match value {
+        Value::Decimal(d) => Value::I256(d.raw()),
+        Value::String(text) => numeric::parse_wide(if signed { "i256" } else { "u256" }, text)
+            .map_err(|e| e.to_string())?,
+        other => other.clone(),
+    };
+    let out_of_range = || format!("{} does not fit in {}", value, param_type);
+    if signed {
+        let n = numeric::to_i256(&value).map_err(|_| out_of_range())?;
+        let limit = U256::one() << (bits - 1);
+        let fits = if n.is_negative() {
+            n.magnitude() <= limit
+        } else {
+            n.magnitude() < limit
+        };
+        if !fits {
+            return Err(out_of_range());
+        }
+        Ok(encode_int256_word(n))
+    } else {
+        let n = numeric::to_u256(&value).map_err(|_| out_of_range())?;
+        if bits < 256 && n >= U256::one() << bits {
+            return Err(out_of_range());
+        }
+        Ok(encode_u256_word(n))
+    }
 }
 
 pub fn decode_bool_word(word_hex: &str) -> Result<bool, String> {
@@ -196,10 +284,12 @@ pub fn decode_revert_panic_code_payload(payload_hex: &str) -> Result<u128, Strin
 mod tests {
     use super::{
         decode_abi_bytes_data, decode_abi_string_data, decode_abi_tuple_string_bytes_payload,
-        decode_custom_error_payload_words, decode_revert_error_string_payload,
+        decode_custom_error_payload_words, decode_integer_word, decode_revert_error_string_payload,
         decode_revert_panic_code_payload, decode_static_tuple_address_uint_bool_payload,
-        decode_uint256_word,
+        decode_uint256_word, encode_integer_word,
     };
+    use crate::runtime::numeric::{Decimal, I256, U256};
+    use crate::runtime::values::Value;
 
     #[test]
     fn decodes_dynamic_string_and_bytes_payloads() {
@@ -262,4 +352,53 @@ deadbeef00000000000000000000000000000000000000000000000000000000";
         let err = decode_uint256_word(bad_word).unwrap_err();
         assert!(err.contains("expected 64 hex chars"));
     }
+
+    #[test]
+    fn round_trips_full_width_integer_words() {
+        let max = "f".repeat(64);
+        assert_eq!(
+            decode_integer_word("uint256", &max).unwrap(),
+            Value::U256(U256::MAX)
+        );
+        assert_eq!(
+            decode_integer_word("int256", &max).unwrap(),
+            Value::I256(I256::from_i64(-1))
+        );
+        assert!(decode_uint256_word(&max).unwrap_err().contains("u128"));
+
+        let wei = Value::U256(U256::from_dec_str("10000000000000000000000").unwrap());
+        let word = encode_integer_word("uint256", &wei).unwrap();
+        assert_eq!(
+            word,
+            "00000000000000000000000000000000000000000000021e19e0c9bab2400000"
+        );
+        assert_eq!(decode_integer_word("uint", &word).unwrap(), wei);
+
+        let negative = Value::I256(I256::from_i64(-42));
+        let word = encode_integer_word("int256", &negative).unwrap();
+        assert_eq!(decode_integer_word("int256", &word).unwrap(), negative);
+
+        let price = Value::Decimal(Decimal::from_str_exact("-1.25").unwrap());
+        let word = encode_integer_word("int256", &price).unwrap();
+        match decode_integer_word("int256", &word).unwrap() {
+            Value::I256(raw) => assert_eq!(Value::Decimal(Decimal::from_raw(raw)), price),
+            other => panic!("expected i256, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_integers_outside_the_abi_type() {
+        let err = encode_integer_word("uint8", &Value::Int(256)).unwrap_err();
+        assert!(err.contains("does not fit in uint8"), "{}", err);
+        assert!(encode_integer_word("uint8", &Value::Int(255)).is_ok());
+        assert!(encode_integer_word("int8", &Value::Int(-128)).is_ok());
+        assert!(encode_integer_word("int8", &Value::Int(128)).is_err());
+        assert!(encode_integer_word("uint256", &Value::Int(-1)).is_err());
+        assert_eq!(
+            encode_integer_word("uint256", &Value::String("0xff".to_string())).unwrap(),
+            format!("{:0>64}", "ff")
+        );
+        assert!(encode_integer_word("uint7", &Value::Int(1)).is_err());
+        assert!(encode_integer_word("bool", &Value::Int(1)).is_err());
+    }
 }
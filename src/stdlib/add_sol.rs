@@ -192,11 +192,14 @@ fn parse_outputs(outputs_value: Option<&serde_json::Value>) -> Vec<FunctionOutpu
 /// Convert Solidity type to dist_agent_lang type
 pub fn solidity_to_dal_type(solidity_type: &str) -> String {
     match solidity_type {
-        "uint256" | "uint128" | "uint64" | "uint32" | "uint8" | "uint" => "int".to_string(),
-        "int256" | "int128" | "int64" | "int32" | "int8" | "int" => "int".to_string(),
+        // Widths that can exceed i64 map to the 256-bit value types.
+        "uint256" | "uint128" | "uint64" | "uint" => "u256".to_string(),
+        "int256" | "int128" | "int" => "i256".to_string(),
+        "uint32" | "uint8" | "int64" | "int32" | "int8" => "int".to_string(),
         "address" => "string".to_string(),
         "bool" => "bool".to_string(),
-        "string" | "bytes" | "bytes32" => "string".to_string(),
+        "string" => "string".to_string(),
+        "bytes" | "bytes32" => "bytes".to_string(),
         _ => {
             // Handle arrays
             if let Some(stripped) = solidity_type.strip_suffix("[]") {
@@ -576,24 +579,23 @@ fn value_to_string(value: &Value) -> String {
         Value::String(s) => s.clone(),
         Value::Bool(b) => b.to_string(),
         Value::Null => "null".to_string(),
+        Value::U256(_) | Value::I256(_) | Value::Decimal(_) | Value::Bytes(_) => {
+            value.to_chain_arg_string()
+        }
         Value::Array(arr) => format!("{:?}", arr),
         Value::Map(map) => format!("{:?}", map),
         Value::Set(set) => format!("{:?}", set),
         Value::Struct(name, fields) => format!("{} {:?}", name, fields),
         Value::List(list) => format!("{:?}", list),
-        Value::Result(ok_val, err_val) => {
-            // Check if it's Ok or Err by checking if err_val is Null
-            if matches!(err_val.as_ref(), Value::Null) {
-                format!("Ok({})", value_to_string(ok_val))
-            } else {
-                format!("Err({})", value_to_string(err_val))
-            }
-        }
+        Value::Result(Ok(ok_val)) => format!("Ok({})", value_to_string(ok_val)),
+        Value::Result(Err(err_val)) => format!("Err({})", value_to_string(err_val)),
         Value::Option(opt_val) => match opt_val {
             Some(val) => value_to_string(val),
             None => "null".to_string(),
         },
         Value::Closure(id) => format!("<closure {}>", id),
+        Value::Task(_) | Value::Channel(_) => value.to_string(),
+        Value::Enum(..) => value.to_string(),
     }
 }
 
@@ -656,8 +658,7 @@ fn decode_output_value(param_type: &str, payload_hex: &str) -> Result<Value, Str
         "string" => {
             crate::stdlib::abi_codec::decode_abi_string_data(payload_hex).map(Value::String)
         }
-        "bytes" => crate::stdlib::abi_codec::decode_abi_bytes_data(payload_hex)
-            .map(|b| Value::String(format!("0x{}", hex::encode(b)))),
+        "bytes" => crate::stdlib::abi_codec::decode_abi_bytes_data(payload_hex).map(Value::Bytes),
         "bytes32" => {
             let normalized = payload_hex.trim().trim_start_matches("0x").to_lowercase();
             if normalized.len() != 64 {
@@ -666,11 +667,10 @@ fn decode_output_value(param_type: &str, payload_hex: &str) -> Result<Value, Str
                     normalized.len()
                 ));
             }
-            Ok(Value::String(format!("0x{}", normalized)))
+            crate::stdlib::bytes::from_hex(&normalized).map(Value::Bytes)
         }
-        _ if t.starts_with("uint") || t == "uint" || t.starts_with("int") || t == "int" => {
-            crate::stdlib::abi_codec::decode_uint256_word(payload_hex)
-                .map(|n| Value::String(n.to_string()))
+        _ if t.starts_with("uint") || t.starts_with("int") => {
+            crate::stdlib::abi_codec::decode_integer_word(&t, payload_hex)
         }
         _ => Ok(Value::String(format!(
             "0x{}",
@@ -707,6 +707,47 @@ mod tests {
         assert_eq!(decode_error, None);
     }
 
+    #[test]
+    fn decode_result_with_abi_keeps_full_uint256_precision() {
+        let function = ContractFunction {
+            name: "totalSupply".to_string(),
+            inputs: vec![],
+            outputs: vec![FunctionOutput {
+                name: "supply".to_string(),
+                param_type: "uint256".to_string(),
+            }],
+            state_mutability: "view".to_string(),
+        };
+        // 2^200 does not fit in u128 or i64.
+        let (decoded, decode_error) = decode_result_with_function_abi(
+            &function,
+            &Some("0x0000000000000100000000000000000000000000000000000000000000000000".to_string()),
+        );
+        assert_eq!(decode_error, None);
+        assert_eq!(
+            decoded,
+            Value::U256(crate::runtime::numeric::U256::one() << 200)
+        );
+    }
+
+    #[test]
+    fn decode_result_with_abi_returns_bytes32_as_bytes() {
+        let function = ContractFunction {
+            name: "root".to_string(),
+            inputs: vec![],
+            outputs: vec![FunctionOutput {
+                name: "hash".to_string(),
+                param_type: "bytes32".to_string(),
+            }],
+            state_mutability: "view".to_string(),
+        };
+        let word = format!("ab{}01", "00".repeat(30));
+        let (decoded, decode_error) =
+            decode_result_with_function_abi(&function, &Some(format!("0x{}", word)));
+        assert_eq!(decode_error, None);
+        assert_eq!(decoded, Value::Bytes(hex::decode(&word).unwrap()));
+    }
+
     #[test]
     fn call_with_abi_typed_preserves_error_evidence_on_chain_unsupported() {
         let contract = SolidityContract {
@@ -90,7 +90,8 @@ pub struct LifecycleHooks {
     pub on_destroy: Option<String>,
 }
 
-/// Resource limits for agent execution (wall clock, tokens, cost, tool call bounds).
+/// Resource limits for agent execution (wall clock, tokens, cost, tool call bounds, and the
+/// metered execution of DAL the agent runs).
 /// Used by IDE agents and any agent that needs budget enforcement.
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct ResourceBudget {
@@ -100,6 +101,21 @@ pub struct ResourceBudget {
     pub max_consecutive_no_progress: Option<u32>,
     pub max_total_tokens: Option<u64>,
     pub max_cost_microusd: Option<u64>,
+    pub max_eval_steps: Option<u64>,
+    pub max_allocated_items: Option<u64>,
+    pub max_call_depth: Option<u64>,
+}
+
+impl ResourceBudget {
+    /// The metering budget lifecycle hooks run with, when any of its limits is set.
+    pub fn metering_budget(&self) -> Option<crate::runtime::metering::Budget> {
+        let budget = crate::runtime::metering::Budget {
+            max_steps: self.max_eval_steps,
+            max_allocated_items: self.max_allocated_items,
+            max_call_depth: self.max_call_depth,
+        };
+        (budget != Default::default()).then_some(budget)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -502,7 +518,8 @@ pub fn spawn(config: AgentConfig) -> Result<AgentContext, String> {
         if let Some(ref on_create) = lifecycle.on_create {
             let mut vars = HashMap::new();
             vars.insert("agent_id".to_string(), Value::String(agent_id.clone()));
-            if let Err(e) = crate::execute_dal_with_scope(&vars, on_create) {
+            let budget = hook_budget(&agent_context.config);
+            if let Err(e) = crate::execute_dal_with_scope_and_budget(&vars, on_create, budget) {
                 log::warn!(
                     "Mold on_create lifecycle hook failed for {}: {}",
                     agent_id,
@@ -545,6 +562,14 @@ pub fn spawn(config: AgentConfig) -> Result<AgentContext, String> {
     Ok(agent_context)
 }
 
+/// Metering budget for the agent's lifecycle hooks, from its `ResourceBudget`.
+fn hook_budget(config: &AgentConfig) -> Option<crate::runtime::metering::Budget> {
+    config
+        .resource_budget
+        .as_ref()
+        .and_then(ResourceBudget::metering_budget)
+}
+
 /// Terminate an agent: set status to Terminated, run on_destroy lifecycle hook, persist.
 pub fn terminate(agent_id: &str) -> Result<(), String> {
     let on_destroy = {
@@ -558,14 +583,15 @@ pub fn terminate(agent_id: &str) -> Result<(), String> {
             .config
             .lifecycle
             .as_ref()
-            .and_then(|l| l.on_destroy.clone());
+            .and_then(|l| l.on_destroy.clone())
+            .map(|snippet| (snippet, hook_budget(&ctx.config)));
         r.persist();
         hook
     };
-    if let Some(snippet) = on_destroy {
+    if let Some((snippet, budget)) = on_destroy {
         let mut vars = HashMap::new();
         vars.insert("agent_id".to_string(), Value::String(agent_id.to_string()));
-        if let Err(e) = crate::execute_dal_with_scope(&vars, &snippet) {
+        if let Err(e) = crate::execute_dal_with_scope_and_budget(&vars, &snippet, budget) {
             log::warn!(
                 "Mold on_destroy lifecycle hook failed for {}: {}",
                 agent_id,
@@ -806,11 +832,10 @@ pub fn evolve(agent_id: &str, evolution_data: HashMap<String, Value>) -> Result<
         let mut r = get_runtime();
         r.evolution_store
             .insert(agent_id.to_string(), evolution_data.clone());
-        let hook = r
-            .agent_contexts
-            .get(agent_id)
-            .and_then(|ctx| ctx.config.lifecycle.as_ref())
-            .and_then(|l| l.on_evolve.clone());
+        let hook = r.agent_contexts.get(agent_id).and_then(|ctx| {
+            let snippet = ctx.config.lifecycle.as_ref()?.on_evolve.clone()?;
+            Some((snippet, hook_budget(&ctx.config)))
+        });
         r.persist();
         hook
     };
@@ -828,11 +853,11 @@ pub fn evolve(agent_id: &str, evolution_data: HashMap<String, Value>) -> Result<
         data
     });
 
-    if let Some(snippet) = on_evolve {
+    if let Some((snippet, budget)) = on_evolve {
         let mut vars = HashMap::new();
         vars.insert("agent_id".to_string(), Value::String(agent_id.to_string()));
         vars.insert("evolution_data".to_string(), Value::Map(evolution_data));
-        if let Err(e) = crate::execute_dal_with_scope(&vars, &snippet) {
+        if let Err(e) = crate::execute_dal_with_scope_and_budget(&vars, &snippet, budget) {
             log::warn!(
                 "Mold on_evolve lifecycle hook failed for {}: {}",
                 agent_id,
@@ -62,6 +62,12 @@ pub struct ResourceBudgetDto {
     pub max_total_tokens: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_cost_microusd: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_eval_steps: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_allocated_items: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_call_depth: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -190,6 +196,9 @@ impl From<&AgentConfig> for AgentConfigDto {
                 max_consecutive_no_progress: b.max_consecutive_no_progress,
                 max_total_tokens: b.max_total_tokens,
                 max_cost_microusd: b.max_cost_microusd,
+                max_eval_steps: b.max_eval_steps,
+                max_allocated_items: b.max_allocated_items,
+                max_call_depth: b.max_call_depth,
             }),
         }
     }
@@ -218,6 +227,9 @@ impl AgentConfigDto {
                     max_consecutive_no_progress: b.max_consecutive_no_progress,
                     max_total_tokens: b.max_total_tokens,
                     max_cost_microusd: b.max_cost_microusd,
+                    max_eval_steps: b.max_eval_steps,
+                    max_allocated_items: b.max_allocated_items,
+                    max_call_depth: b.max_call_depth,
                 }
             }),
         }
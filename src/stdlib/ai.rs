@@ -2691,8 +2691,37 @@ pub(crate) fn execute_fetch_url_result(url: &str) -> String {
     }
 }
 
+/// GET a URL under the same policy as [`fetch_url_text_result`] and return the raw body bytes
+/// (no HTML stripping, no truncation beyond `DAL_HTTP_FETCH_MAX_BYTES`).
+pub fn fetch_url_bytes_result(url: &str) -> Result<Vec<u8>, String> {
+    #[cfg(feature = "http-interface")]
+    {
+        fetch_url_body(url).map(|(_, bytes)| bytes)
+    }
+    #[cfg(not(feature = "http-interface"))]
+    {
+        let _ = url;
+        Err("fetch_url requires the http-interface feature.".to_string())
+    }
+}
+
 #[cfg(feature = "http-interface")]
 fn fetch_url_http_impl(url: &str) -> Result<String, String> {
+    let (content_type, bytes) = fetch_url_body(url)?;
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+
+    let out = if content_type.contains("html") || text.trim_start().starts_with('<') {
+        html_to_plaintext_best_effort(&text)
+    } else {
+        text
+    };
+
+    Ok(out)
+}
+
+/// Policy-checked GET returning the lowercased content type and the body bytes.
+#[cfg(feature = "http-interface")]
+fn fetch_url_body(url: &str) -> Result<(String, Vec<u8>), String> {
     use reqwest::header::CONTENT_TYPE;
     use reqwest::Url;
 
@@ -2769,15 +2798,7 @@ fn fetch_url_http_impl(url: &str) -> Result<String, String> {
         ));
     }
 
-    let text = String::from_utf8_lossy(&bytes).into_owned();
-
-    let out = if content_type.contains("html") || text.trim_start().starts_with('<') {
-        html_to_plaintext_best_effort(&text)
-    } else {
-        text
-    };
-
-    Ok(out)
+    Ok((content_type, bytes.to_vec()))
 }
 
 /// Execute dal_init (project_init hard skill). Template: general, chain, iot, agent.
@@ -3512,10 +3533,7 @@ pub fn run_multi_step_tool_loop(
 ) -> Result<MultiStepResult, String> {
     use crate::agent_context_schema::ConversationTurn;
     fn duration_ms_i64(duration: std::time::Duration) -> i64 {
-        match i64::try_from(duration.as_millis()) {
-            Ok(ms) => ms,
-            Err(_) => i64::MAX,
-        }
+        i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
     }
     let root = working_root.map(|p| p.to_path_buf()).unwrap_or_else(|| {
         std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
@@ -5204,7 +5222,7 @@ fn openai_compatible_tool_provider(config: &AIConfig) -> bool {
             config
                 .endpoint
                 .as_ref()
-                .map_or(false, |e| openai_compatible_endpoint_hint(e))
+                .is_some_and(|e| openai_compatible_endpoint_hint(e))
         }
         _ => false,
     }
@@ -5573,7 +5591,7 @@ fn merge_openai_reasoning_into_chat_body(model: &str, body: &mut serde_json::Val
             let has_tools = obj
                 .get("tools")
                 .and_then(|v| v.as_array())
-                .map_or(false, |a| !a.is_empty());
+                .is_some_and(|a| !a.is_empty());
             if has_tools {
                 return;
             }
@@ -6995,7 +7013,7 @@ pub fn recommend(
             recommendations.push((item.clone(), score));
         }
     }
-    recommendations.sort_by(|a, b| b.1.cmp(&a.1));
+    recommendations.sort_by_key(|r| std::cmp::Reverse(r.1));
     Ok(recommendations
         .iter()
         .take(count)
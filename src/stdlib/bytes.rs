@@ -0,0 +1,116 @@
+//! Byte-string helpers behind `Value::Bytes` and the DAL `bytes::` namespace.
+//!
+//! Hex text is `0x`-prefixed on output and accepted with or without the prefix on input; base64
+//! uses the standard alphabet with padding. Slices take `start` and an optional exclusive `end`;
+//! negative positions count from the end, like Python.
+
+use crate::runtime::values::Value;
+use base64::Engine as _;
+
+/// `0x`-prefixed lowercase hex.
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Decode hex text, with or without a `0x` prefix. An odd number of digits is an error.
+pub fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits = text
+        .trim()
+        .strip_prefix("0x")
+        .or_else(|| text.trim().strip_prefix("0X"))
+        .unwrap_or(text.trim());
+    hex::decode(digits).map_err(|e| format!("invalid hex: {}", e))
+}
+
+pub fn to_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+pub fn from_base64(text: &str) -> Result<Vec<u8>, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(text.trim())
+        .map_err(|e| format!("invalid base64: {}", e))
+}
+
+/// Decode UTF-8; invalid sequences are an error rather than replaced.
+pub fn to_utf8(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|e| format!("bytes are not valid UTF-8: {}", e.utf8_error()))
+}
+
+/// `bytes[start..end]`, with negative positions counted from the end and `end` defaulting to
+/// the length. Positions past either end are an error; `start > end` yields empty bytes.
+pub fn slice(bytes: &[u8], start: i64, end: Option<i64>) -> Result<Vec<u8>, String> {
+    let len = bytes.len() as i64;
+    let position = |i: i64| {
+        let p = if i < 0 { len + i } else { i };
+        if (0..=len).contains(&p) {
+            Ok(p as usize)
+        } else {
+            Err(format!("slice index {} out of range for {} bytes", i, len))
+        }
+    };
+    let start = position(start)?;
+    let end = position(end.unwrap_or(len))?;
+    Ok(bytes
+        .get(start..end)
+        .map(<[u8]>::to_vec)
+        .unwrap_or_default())
+}
+
+/// Conversion used by `bytes(x)`: bytes pass through, strings become their UTF-8 encoding and
+/// lists must hold ints in `0..=255`.
+pub fn from_value(value: &Value) -> Result<Vec<u8>, String> {
+    match value {
+        Value::Bytes(b) => Ok(b.clone()),
+        Value::String(s) => Ok(s.as_bytes().to_vec()),
+        Value::List(items) | Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Int(n) if (0..=255).contains(n) => Ok(*n as u8),
+                other => Err(format!(
+                    "byte values must be ints in 0..=255, got {}",
+                    other
+                )),
+            })
+            .collect(),
+        other => Err(format!("cannot convert {} to bytes", other.type_name())),
+    }
+}
+
+/// Each byte as an `int` element.
+pub fn to_list(bytes: &[u8]) -> Value {
+    Value::List(bytes.iter().map(|b| Value::Int(*b as i64)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_and_base64_round_trip() {
+        let data = [0u8, 0x7f, 0x80, 0xff];
+        assert_eq!(to_hex(&data), "0x007f80ff");
+        assert_eq!(from_hex("0x007f80ff").unwrap(), data);
+        assert_eq!(from_hex("007F80FF").unwrap(), data);
+        assert!(from_hex("0xabc").is_err());
+        assert_eq!(from_base64(&to_base64(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn slice_counts_negative_positions_from_end() {
+        let data = b"abcdef";
+        assert_eq!(slice(data, 1, Some(3)).unwrap(), b"bc");
+        assert_eq!(slice(data, -2, None).unwrap(), b"ef");
+        assert_eq!(slice(data, 4, Some(2)).unwrap(), b"");
+        assert!(slice(data, 0, Some(7)).is_err());
+    }
+
+    #[test]
+    fn from_value_checks_list_elements() {
+        let list = Value::List(vec![Value::Int(1), Value::Int(255)]);
+        assert_eq!(from_value(&list).unwrap(), vec![1, 255]);
+        assert!(from_value(&Value::List(vec![Value::Int(256)])).is_err());
+        assert!(to_utf8(&[0xff]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::runtime::numeric::{self, U256};
 use crate::runtime::values::Value;
 use crate::stdlib::abi;
 use std::collections::HashMap;
@@ -36,6 +37,7 @@ fn strict_chain_policy_enabled() -> bool {
 
 #[cfg(feature = "http-interface")]
 mod rpc {
+    use crate::runtime::numeric::U256;
     use lazy_static::lazy_static;
     use serde_json::{json, Value as JsonValue};
     use std::sync::{Arc, Mutex};
@@ -119,6 +121,16 @@ mod rpc {
         value.min(i64::MAX as u128) as i64
     }
 
+    /// Parse a hex quantity (with or without 0x) at full 256-bit width; malformed input is 0.
+    pub(super) fn hex_to_u256(hex_str: &str) -> U256 {
+        let s = hex_str.trim().strip_prefix("0x").unwrap_or(hex_str.trim());
+        let s = s.trim_start_matches('0');
+        if s.is_empty() || s.len() > 64 {
+            return U256::zero();
+        }
+        U256::from_str_radix(s, 16).unwrap_or_default()
+    }
+
     /// Parse hex gas price to gwei (f64).
     pub(super) fn hex_gas_price_to_gwei(hex_str: &str) -> f64 {
         let raw = hex_to_i64(hex_str);
@@ -737,7 +749,7 @@ impl ChainDeployArgs {
         })
     }
 
-    fn to_flat_map(self) -> HashMap<String, String> {
+    fn into_flat_map(self) -> HashMap<String, String> {
         let mut m = self.extra;
         m.insert("raw_transaction".to_string(), self.raw_transaction);
         m
@@ -780,7 +792,7 @@ impl ChainCallArgs {
         })
     }
 
-    fn to_flat_map(self) -> HashMap<String, String> {
+    fn into_flat_map(self) -> HashMap<String, String> {
         let mut m = self.extra;
         m.insert("data".to_string(), self.data);
         if let Some(sig) = self.function_signature {
@@ -1001,7 +1013,7 @@ pub fn deploy_typed_with_args(
     contract_name: String,
     args: ChainDeployArgs,
 ) -> ChainDeployResult {
-    deploy_typed(chain_id, contract_name, args.to_flat_map())
+    deploy_typed(chain_id, contract_name, args.into_flat_map())
 }
 
 #[cfg(feature = "http-interface")]
@@ -1311,7 +1323,8 @@ pub fn get_transaction_status(chain_id: i64, tx_hash: String) -> String {
 /// * `address` - The address to check
 ///
 /// # Returns
-/// * `i64` - Balance in wei
+/// * `i64` - Balance in wei, saturated at `i64::MAX` (about 9.2 ETH); `-1` when strict chain
+///   policy forbids the mock fallback. Use [`get_balance_wei`] for the exact amount.
 ///
 /// # Example
 /// ```rust
@@ -1319,6 +1332,18 @@ pub fn get_transaction_status(chain_id: i64, tx_hash: String) -> String {
 /// let balance = chain::get_balance(1, "0x1234...".to_string());
 /// ```
 pub fn get_balance(chain_id: i64, address: String) -> i64 {
+    match get_balance_wei(chain_id, address) {
+        Some(wei) => numeric::saturating_i64(wei),
+        None => -1,
+    }
+}
+
+/// Get balance of an address in wei at full 256-bit width.
+///
+/// # Returns
+/// * `Some(U256)` - Balance in wei (0 for unsupported chains)
+/// * `None` - RPC unavailable and strict chain policy forbids the mock fallback
+pub fn get_balance_wei(chain_id: i64, address: String) -> Option<U256> {
     let chain_config = get_chain_config(chain_id);
 
     #[cfg(feature = "http-interface")]
@@ -1335,13 +1360,13 @@ pub fn get_balance(chain_id: i64, address: String) -> i64 {
             vec![json!(addr), json!("latest")],
         ) {
             if let Some(hex_str) = result.as_str() {
-                return rpc::hex_to_i64(hex_str);
+                return Some(rpc::hex_to_u256(hex_str));
             }
         }
     }
 
     if strict_chain_policy_enabled() {
-        return -1;
+        return None;
     }
 
     // Fallback: mock balance when RPC unavailable or chain not supported
@@ -1354,10 +1379,10 @@ pub fn get_balance(chain_id: i64, address: String) -> i64 {
                 .sum();
             let limited_sum = hash_sum % 1000;
             let wei_per_unit: i64 = 1000000000000000;
-            return limited_sum.checked_mul(wei_per_unit).unwrap_or(0);
+            return Some(U256::from(limited_sum) * U256::from(wei_per_unit));
         }
     }
-    0
+    Some(U256::zero())
 }
 
 /// Known ERC20 contract addresses by symbol (mainnet chain_id 1). Used when token_symbol is not 0x.
@@ -1584,19 +1609,33 @@ fn build_erc721_owner_of_calldata(token_id: u64) -> String {
 /// * `address` - Account address to query
 ///
 /// # Returns
-/// * `i64` - Token balance (raw units, e.g. 6 decimals for USDC)
+/// * `i64` - Token balance (raw units, e.g. 6 decimals for USDC), saturated at `i64::MAX`.
+///   Use [`get_token_balance_raw`] for the exact amount.
 pub fn get_token_balance(chain_id: i64, token_symbol_or_contract: String, address: String) -> i64 {
+    numeric::saturating_i64(get_token_balance_raw(
+        chain_id,
+        token_symbol_or_contract,
+        address,
+    ))
+}
+
+/// Get ERC20 token balance in raw units at full 256-bit width (example-only API).
+pub fn get_token_balance_raw(
+    chain_id: i64,
+    token_symbol_or_contract: String,
+    address: String,
+) -> U256 {
     let contract = if token_symbol_or_contract.starts_with("0x") {
         token_symbol_or_contract.clone()
     } else if let Some(addr) = erc20_contract_for_symbol(chain_id, &token_symbol_or_contract) {
         addr
     } else {
-        return 0;
+        return U256::zero();
     };
 
     let chain_config = match get_chain_config(chain_id) {
         Some(c) => c,
-        None => return 0,
+        None => return U256::zero(),
     };
 
     #[cfg(feature = "http-interface")]
@@ -1613,11 +1652,11 @@ pub fn get_token_balance(chain_id: i64, token_symbol_or_contract: String, addres
             rpc::rpc_request(&chain_config.rpc_url, "eth_call", vec![tx, json!("latest")])
         {
             if let Some(hex_str) = result.as_str() {
-                return rpc::hex_to_i64(hex_str);
+                return rpc::hex_to_u256(hex_str);
             }
         }
     }
-    0
+    U256::zero()
 }
 
 /// Call a contract function on a specific chain
@@ -1798,7 +1837,7 @@ pub fn call_typed_with_args(
         chain_id,
         contract_address,
         function_name,
-        args.to_flat_map(),
+        args.into_flat_map(),
     )
 }
 
@@ -2116,7 +2155,7 @@ mod abi_golden_vectors {
             raw_transaction: "0xabc".to_string(),
             extra: std::collections::HashMap::new(),
         };
-        let flat = args.to_flat_map();
+        let flat = args.into_flat_map();
         assert_eq!(
             flat.get("raw_transaction").map(String::as_str),
             Some("0xabc")
@@ -2156,7 +2195,7 @@ mod abi_golden_vectors {
             function_signature: Some("balanceOf(address)".to_string()),
             extra: std::collections::HashMap::new(),
         };
-        let flat = args.to_flat_map();
+        let flat = args.into_flat_map();
         assert_eq!(flat.get("data").map(String::as_str), Some("0x70a08231"));
         assert_eq!(
             flat.get("function_signature").map(String::as_str),
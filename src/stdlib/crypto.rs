@@ -23,6 +23,13 @@ pub enum SignatureAlgorithm {
     Custom(String),
 }
 
+/// Feed `data` to a mock hasher exactly as `str::hash` would, so the mock hashes and signatures
+/// of a string and of its UTF-8 bytes agree.
+fn write_data(hasher: &mut DefaultHasher, data: &[u8]) {
+    hasher.write(data);
+    hasher.write_u8(0xff);
+}
+
 /// Hash a string using the specified algorithm
 ///
 /// # Arguments
@@ -39,6 +46,11 @@ pub enum SignatureAlgorithm {
 /// let hash = crypto::hash("Hello, World!", HashAlgorithm::SHA256);
 /// ```
 pub fn hash(data: &str, algorithm: HashAlgorithm) -> String {
+    hash_data(data.as_bytes(), algorithm)
+}
+
+/// Hash raw bytes; a string hashes the same as its UTF-8 encoding.
+pub fn hash_data(data: &[u8], algorithm: HashAlgorithm) -> String {
     match algorithm {
         HashAlgorithm::SHA256 => hash_sha256(data),
         HashAlgorithm::SHA512 => hash_sha512(data),
@@ -56,6 +68,10 @@ pub fn hash_bytes(data: &[u8], algorithm: &str) -> Result<String, String> {
             hasher.update(data);
             Ok(format!("{:x}", hasher.finalize()))
         }
+        "SHA512" => {
+            use sha2::{Digest, Sha512};
+            Ok(format!("{:x}", Sha512::digest(data)))
+        }
         "MD5" => {
             // MD5 is deprecated but kept for compatibility
             // Use a simple hash instead
@@ -70,39 +86,39 @@ pub fn hash_bytes(data: &[u8], algorithm: &str) -> Result<String, String> {
 }
 
 /// Hash data using SHA-256
-fn hash_sha256(data: &str) -> String {
+fn hash_sha256(data: &[u8]) -> String {
     use sha2::{Digest, Sha256};
 
     let mut hasher = Sha256::new();
-    hasher.update(data.as_bytes());
+    hasher.update(data);
     let result = hasher.finalize();
 
     format!("{:x}", result)
 }
 
 /// Hash data using SHA-512
-fn hash_sha512(data: &str) -> String {
+fn hash_sha512(data: &[u8]) -> String {
     use sha2::{Digest, Sha512};
 
     let mut hasher = Sha512::new();
-    hasher.update(data.as_bytes());
+    hasher.update(data);
     let result = hasher.finalize();
 
     format!("{:x}", result)
 }
 
 /// Hash data using simple hash (replaces MD5)
-fn hash_simple(data: &str) -> String {
+fn hash_simple(data: &[u8]) -> String {
     let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
+    write_data(&mut hasher, data);
     format!("simple_{:x}", hasher.finish())
 }
 
 /// Hash data using a custom algorithm (fallback to simple hash)
-fn hash_custom(data: &str, algorithm_name: &str) -> String {
+fn hash_custom(data: &[u8], algorithm_name: &str) -> String {
     // Fallback to a simple hash for custom algorithms
     let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
+    write_data(&mut hasher, data);
     algorithm_name.hash(&mut hasher);
 
     format!("custom_{:x}", hasher.finish())
@@ -150,6 +166,11 @@ pub fn random_hash(algorithm: HashAlgorithm) -> String {
 /// let signature = crypto::sign("Hello, World!", "private_key_123", SignatureAlgorithm::RSA);
 /// ```
 pub fn sign(data: &str, private_key: &str, algorithm: SignatureAlgorithm) -> String {
+    sign_data(data.as_bytes(), private_key, algorithm)
+}
+
+/// Sign raw bytes; a string signs the same as its UTF-8 encoding.
+pub fn sign_data(data: &[u8], private_key: &str, algorithm: SignatureAlgorithm) -> String {
     match algorithm {
         SignatureAlgorithm::RSA => sign_rsa(data, private_key),
         SignatureAlgorithm::ECDSA => sign_ecdsa(data, private_key),
@@ -159,10 +180,10 @@ pub fn sign(data: &str, private_key: &str, algorithm: SignatureAlgorithm) -> Str
 }
 
 /// Sign data using RSA (real implementation when key is PEM; otherwise mock for compat)
-fn sign_rsa(data: &str, private_key: &str) -> String {
+fn sign_rsa(data: &[u8], private_key: &str) -> String {
     if !private_key.contains("-----") {
         let mut hasher = DefaultHasher::new();
-        data.hash(&mut hasher);
+        write_data(&mut hasher, data);
         private_key.hash(&mut hasher);
         return format!("rsa_sign_{:x}", hasher.finish());
     }
@@ -175,27 +196,24 @@ fn sign_rsa(data: &str, private_key: &str) -> String {
         Ok(k) => k,
         Err(_) => {
             let mut hasher = DefaultHasher::new();
-            data.hash(&mut hasher);
+            write_data(&mut hasher, data);
             return format!("rsa_sign_{:x}", hasher.finish());
         }
     };
     use sha2::Digest;
     let signing_key = SigningKey::<Sha256>::new(key);
-    let digest = Sha256::digest(data.as_bytes());
+    let digest = Sha256::digest(data);
     let sig = signing_key.sign(digest.as_ref());
     hex::encode(sig.to_bytes())
 }
 
 /// Sign data using ECDSA (delegate to crypto_signatures; fallback mock on invalid key)
-fn sign_ecdsa(data: &str, private_key: &str) -> String {
-    match crate::stdlib::crypto_signatures::ECDSASignatureVerifier::sign(
-        data.as_bytes(),
-        private_key,
-    ) {
+fn sign_ecdsa(data: &[u8], private_key: &str) -> String {
+    match crate::stdlib::crypto_signatures::ECDSASignatureVerifier::sign(data, private_key) {
         Ok(sig) => sig,
         Err(_) => {
             let mut hasher = DefaultHasher::new();
-            data.hash(&mut hasher);
+            write_data(&mut hasher, data);
             private_key.hash(&mut hasher);
             format!("ecdsa_sign_{:x}", hasher.finish())
         }
@@ -203,15 +221,12 @@ fn sign_ecdsa(data: &str, private_key: &str) -> String {
 }
 
 /// Sign data using Ed25519 (delegate to crypto_signatures; fallback mock on invalid key)
-fn sign_ed25519(data: &str, private_key: &str) -> String {
-    match crate::stdlib::crypto_signatures::EdDSASignatureVerifier::sign(
-        data.as_bytes(),
-        private_key,
-    ) {
+fn sign_ed25519(data: &[u8], private_key: &str) -> String {
+    match crate::stdlib::crypto_signatures::EdDSASignatureVerifier::sign(data, private_key) {
         Ok(sig) => sig,
         Err(_) => {
             let mut hasher = DefaultHasher::new();
-            data.hash(&mut hasher);
+            write_data(&mut hasher, data);
             private_key.hash(&mut hasher);
             format!("ed25519_sign_{:x}", hasher.finish())
         }
@@ -219,9 +234,9 @@ fn sign_ed25519(data: &str, private_key: &str) -> String {
 }
 
 /// Sign data using a custom algorithm (unsupported; mock for compat)
-fn sign_custom(data: &str, private_key: &str, algorithm_name: &str) -> String {
+fn sign_custom(data: &[u8], private_key: &str, algorithm_name: &str) -> String {
     let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
+    write_data(&mut hasher, data);
     private_key.hash(&mut hasher);
     algorithm_name.hash(&mut hasher);
     format!("custom_sign_{:x}", hasher.finish())
@@ -250,6 +265,16 @@ pub fn verify(
     signature: &str,
     public_key: &str,
     algorithm: SignatureAlgorithm,
+) -> bool {
+    verify_data(data.as_bytes(), signature, public_key, algorithm)
+}
+
+/// Verify a signature over raw bytes; see [`verify`].
+pub fn verify_data(
+    data: &[u8],
+    signature: &str,
+    public_key: &str,
+    algorithm: SignatureAlgorithm,
 ) -> bool {
     match algorithm {
         SignatureAlgorithm::RSA => verify_rsa(data, signature, public_key),
@@ -260,7 +285,7 @@ pub fn verify(
 }
 
 /// Verify RSA signature (real when public_key is PEM; otherwise prefix check for compat)
-fn verify_rsa(data: &str, signature: &str, public_key: &str) -> bool {
+fn verify_rsa(data: &[u8], signature: &str, public_key: &str) -> bool {
     if !public_key.contains("-----") || hex::decode(signature).is_err() {
         return signature.starts_with("rsa_sign_");
     }
@@ -282,17 +307,15 @@ fn verify_rsa(data: &str, signature: &str, public_key: &str) -> bool {
         Ok(s) => s,
         Err(_) => return signature.starts_with("rsa_sign_"),
     };
-    let digest = Sha256::digest(data.as_bytes());
+    let digest = Sha256::digest(data);
     let verifying_key = VerifyingKey::<Sha256>::new(key);
     verifying_key.verify(digest.as_ref(), &sig).is_ok()
 }
 
 /// Verify ECDSA signature (delegate to crypto_signatures; fallback prefix check)
-fn verify_ecdsa(data: &str, signature: &str, public_key: &str) -> bool {
+fn verify_ecdsa(data: &[u8], signature: &str, public_key: &str) -> bool {
     match crate::stdlib::crypto_signatures::ECDSASignatureVerifier::verify(
-        data.as_bytes(),
-        signature,
-        public_key,
+        data, signature, public_key,
     ) {
         Ok(valid) => valid,
         Err(_) => signature.starts_with("ecdsa_sign_"),
@@ -300,11 +323,9 @@ fn verify_ecdsa(data: &str, signature: &str, public_key: &str) -> bool {
 }
 
 /// Verify Ed25519 signature (delegate to crypto_signatures; fallback prefix check)
-fn verify_ed25519(data: &str, signature: &str, public_key: &str) -> bool {
+fn verify_ed25519(data: &[u8], signature: &str, public_key: &str) -> bool {
     match crate::stdlib::crypto_signatures::EdDSASignatureVerifier::verify(
-        data.as_bytes(),
-        signature,
-        public_key,
+        data, signature, public_key,
     ) {
         Ok(valid) => valid,
         Err(_) => signature.starts_with("ed25519_sign_"),
@@ -54,6 +54,7 @@ fn value_to_param(v: &Value) -> rusqlite::types::Value {
         Value::Int(i) => rusqlite::types::Value::Integer(*i),
         Value::Float(f) => rusqlite::types::Value::Real(*f),
         Value::String(s) => rusqlite::types::Value::Text(s.clone()),
+        Value::Bytes(b) => rusqlite::types::Value::Blob(b.clone()),
         Value::Bool(b) => rusqlite::types::Value::Integer(if *b { 1 } else { 0 }),
         Value::Null => rusqlite::types::Value::Null,
         Value::List(a) | Value::Array(a) if a.len() == 1 => value_to_param(&a[0]),
@@ -67,7 +68,7 @@ fn value_ref_to_value(v: ValueRef<'_>) -> Value {
         ValueRef::Integer(i) => Value::Int(i),
         ValueRef::Real(f) => Value::Float(f),
         ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
-        ValueRef::Blob(b) => Value::String(format!("<blob {} bytes>", b.len())),
+        ValueRef::Blob(b) => Value::Bytes(b.to_vec()),
     }
 }
 
@@ -274,8 +274,8 @@ fn extract_last_summary_block(full: &str, max_chars: usize) -> Option<String> {
     }
     let sline = last_start_line?;
     let mut end_line = lines.len();
-    for j in (sline + 1)..lines.len() {
-        let t = lines[j].trim();
+    for (j, line) in lines.iter().enumerate().skip(sline + 1) {
+        let t = line.trim();
         if t.starts_with("## ") && !t.starts_with("###") {
             end_line = j;
             break;
@@ -413,6 +413,56 @@ pub fn append_working_memory_line(line: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Trim the context file to keep only the last `keep_tail_lines` lines of content (after the header).
+/// Preserves the "# Agent context" and "## Conversation" header, then keeps the last keep_tail_lines lines.
+pub fn trim_retention(keep_tail_lines: i64) -> Result<(), String> {
+    let path = get_context_path();
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    if keep_tail_lines <= 0 {
+        return Ok(());
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let header_end = lines
+        .iter()
+        .position(|&l| l.trim() == "## Conversation")
+        .map(|i| i + 2)
+        .unwrap_or(0);
+    let body_lines = lines.len().saturating_sub(header_end);
+    let keep = keep_tail_lines as usize;
+    if body_lines <= keep {
+        return Ok(());
+    }
+    let drop = body_lines - keep;
+    let new_body_start = header_end + drop;
+    let new_content = [
+        lines[..header_end].join("\n"),
+        lines[new_body_start..].join("\n"),
+    ]
+    .join("\n");
+    std::fs::write(&path, new_content).map_err(|e| e.to_string())
+}
+
+/// Append a summary section (e.g. periodic or on-demand session summary).
+pub fn append_summary(summary_text: &str, title: Option<&str>) -> Result<(), String> {
+    let path = get_context_path();
+    ensure_header(&path, DEFAULT_AGENT_NAME).map_err(|e| e.to_string())?;
+
+    let now = chrono::Utc::now();
+    let ts = now.format("%Y-%m-%d %H:%M");
+    let heading = title
+        .map(|t| format!("## Summary — {}\n\n", t))
+        .unwrap_or_else(|| "## Summary\n\n".to_string());
+    let block = format!("{}\n{}\n\n{}\n\n", heading, ts, summary_text);
+
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+    f.write_all(block.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -548,53 +598,3 @@ Updated: test
         }
     }
 }
-
-/// Trim the context file to keep only the last `keep_tail_lines` lines of content (after the header).
-/// Preserves the "# Agent context" and "## Conversation" header, then keeps the last keep_tail_lines lines.
-pub fn trim_retention(keep_tail_lines: i64) -> Result<(), String> {
-    let path = get_context_path();
-    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    if keep_tail_lines <= 0 {
-        return Ok(());
-    }
-    let lines: Vec<&str> = content.lines().collect();
-    let header_end = lines
-        .iter()
-        .position(|&l| l.trim() == "## Conversation")
-        .map(|i| i + 2)
-        .unwrap_or(0);
-    let body_lines = lines.len().saturating_sub(header_end);
-    let keep = keep_tail_lines as usize;
-    if body_lines <= keep {
-        return Ok(());
-    }
-    let drop = body_lines - keep;
-    let new_body_start = header_end + drop;
-    let new_content = [
-        lines[..header_end].join("\n"),
-        lines[new_body_start..].join("\n"),
-    ]
-    .join("\n");
-    std::fs::write(&path, new_content).map_err(|e| e.to_string())
-}
-
-/// Append a summary section (e.g. periodic or on-demand session summary).
-pub fn append_summary(summary_text: &str, title: Option<&str>) -> Result<(), String> {
-    let path = get_context_path();
-    ensure_header(&path, DEFAULT_AGENT_NAME).map_err(|e| e.to_string())?;
-
-    let now = chrono::Utc::now();
-    let ts = now.format("%Y-%m-%d %H:%M");
-    let heading = title
-        .map(|t| format!("## Summary — {}\n\n", t))
-        .unwrap_or_else(|| "## Summary\n\n".to_string());
-    let block = format!("{}\n{}\n\n{}\n\n", heading, ts, summary_text);
-
-    let mut f = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)
-        .map_err(|e| e.to_string())?;
-    f.write_all(block.as_bytes()).map_err(|e| e.to_string())?;
-    Ok(())
-}
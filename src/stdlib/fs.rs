@@ -52,8 +52,8 @@ pub fn filesystem_root() -> PathBuf {
     std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
 }
 
-/// Read entire file as UTF-8 string. Enforces [`MAX_READ_BYTES`].
-pub fn read_text(root: &Path, rel_path: &str) -> Result<String, String> {
+/// Resolve `rel_path` to an existing file no larger than [`MAX_READ_BYTES`].
+fn readable_file(root: &Path, rel_path: &str) -> Result<PathBuf, String> {
     let p = resolve_path_under_root(root, rel_path)?;
     if !p.is_file() {
         return Err("not a file".to_string());
@@ -66,21 +66,41 @@ pub fn read_text(root: &Path, rel_path: &str) -> Result<String, String> {
             len, MAX_READ_BYTES
         ));
     }
-    std::fs::read_to_string(&p).map_err(|e| e.to_string())
+    Ok(p)
+}
+
+/// Read entire file as UTF-8 string. Enforces [`MAX_READ_BYTES`].
+pub fn read_text(root: &Path, rel_path: &str) -> Result<String, String> {
+    std::fs::read_to_string(readable_file(root, rel_path)?).map_err(|e| e.to_string())
+}
+
+/// Read entire file as raw bytes. Enforces [`MAX_READ_BYTES`].
+pub fn read_bytes(root: &Path, rel_path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(readable_file(root, rel_path)?).map_err(|e| e.to_string())
 }
 
 /// Write UTF-8 text; creates parent directories. Returns byte length written.
 pub fn write_text(root: &Path, rel_path: &str, contents: &str) -> Result<usize, String> {
+    write_bytes(root, rel_path, contents.as_bytes())
+}
+
+/// Write raw bytes; creates parent directories. Returns byte length written.
+pub fn write_bytes(root: &Path, rel_path: &str, contents: &[u8]) -> Result<usize, String> {
     let p = resolve_path_under_root(root, rel_path)?;
     if let Some(parent) = p.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    std::fs::write(&p, contents.as_bytes()).map_err(|e| e.to_string())?;
+    std::fs::write(&p, contents).map_err(|e| e.to_string())?;
     Ok(contents.len())
 }
 
 /// Append UTF-8 text; creates parent directories and file if missing.
 pub fn append_text(root: &Path, rel_path: &str, contents: &str) -> Result<usize, String> {
+    append_bytes(root, rel_path, contents.as_bytes())
+}
+
+/// Append raw bytes; creates parent directories and file if missing.
+pub fn append_bytes(root: &Path, rel_path: &str, contents: &[u8]) -> Result<usize, String> {
     let p = resolve_path_under_root(root, rel_path)?;
     if let Some(parent) = p.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
@@ -91,10 +111,8 @@ pub fn append_text(root: &Path, rel_path: &str, contents: &str) -> Result<usize,
         .append(true)
         .open(&p)
         .map_err(|e| e.to_string())?;
-    let n = contents.as_bytes().len();
-    f.write_all(contents.as_bytes())
-        .map_err(|e| e.to_string())?;
-    Ok(n)
+    f.write_all(contents).map_err(|e| e.to_string())?;
+    Ok(contents.len())
 }
 
 /// Whether a file or directory exists under root.
@@ -133,6 +151,20 @@ mod tests {
         assert_eq!(fs::read_to_string(root.join("a")).unwrap(), "xy");
     }
 
+    #[test]
+    fn bytes_roundtrip_is_binary_safe() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let data = [0u8, 0xff, 0x80, b'\n', 0];
+        write_bytes(root, "bin/blob", &data).unwrap();
+        append_bytes(root, "bin/blob", &[0xfe]).unwrap();
+        assert_eq!(
+            read_bytes(root, "bin/blob").unwrap(),
+            [&data[..], &[0xfe]].concat()
+        );
+        assert!(read_text(root, "bin/blob").is_err());
+    }
+
     #[test]
     fn exists_false_for_missing() {
         let tmp = tempfile::tempdir().unwrap();
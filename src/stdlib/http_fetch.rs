@@ -1,4 +1,4 @@
-//! Bounded HTTP GET for DAL (`http::fetch_text` / `http::fetch`).
+//! Bounded HTTP GET for DAL (`http::fetch_text` / `http::fetch` / `http::fetch_bytes`).
 //!
 //! Uses the same implementation and env policy as the agent **`fetch_url`** tool
 //! ([`crate::stdlib::ai::fetch_url_text_result`]): `DAL_HTTP_FETCH_*`, `http-interface` feature.
@@ -8,3 +8,9 @@
 pub fn fetch_text(url: &str) -> Result<String, String> {
     crate::stdlib::ai::fetch_url_text_result(url)
 }
+
+/// GET an `http` or `https` URL and return the response body unchanged, as bytes.
+/// Same policy and feature requirement as [`fetch_text`].
+pub fn fetch_bytes(url: &str) -> Result<Vec<u8>, String> {
+    crate::stdlib::ai::fetch_url_bytes_result(url)
+}
@@ -7,6 +7,7 @@ pub mod agent_persist;
 pub mod ai;
 pub mod aml;
 pub mod auth;
+pub mod bytes;
 pub mod chain;
 pub mod cloudadmin;
 pub mod config;
@@ -14,6 +15,10 @@ pub mod cross_chain_security;
 pub mod crypto;
 pub mod crypto_signatures; // Production-grade cryptographic signatures
 pub mod database;
+#[cfg(feature = "mysql-storage")]
+mod database_mysql;
+#[cfg(feature = "postgres-storage")]
+mod database_postgres;
 #[cfg(feature = "sqlite-storage")]
 mod database_sqlite;
 pub mod desktop;
@@ -35,6 +40,7 @@ pub mod schedule;
 pub mod secure_auth;
 pub mod service;
 pub mod sh;
+pub mod signatures;
 pub mod sync;
 pub mod test;
 pub mod time;
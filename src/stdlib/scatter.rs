@@ -24,6 +24,7 @@ use crate::runtime::values::Value;
 /// hot spinning when many jobs fire at the same instant).
 const DEFAULT_SCATTER_TICK_MS: u64 = 50;
 
+#[derive(Default)]
 struct ScatterState {
     /// Per-id next fire time and repeat rule.
     schedule: HashMap<String, Job>,
@@ -33,16 +34,6 @@ struct ScatterState {
     heap: BinaryHeap<(Reverse<Instant>, String)>,
 }
 
-impl Default for ScatterState {
-    fn default() -> Self {
-        Self {
-            schedule: HashMap::new(),
-            due_queue: VecDeque::new(),
-            heap: BinaryHeap::new(),
-        }
-    }
-}
-
 struct Job {
     kind: JobKind,
     next: Instant,
@@ -311,6 +302,18 @@ pub(crate) fn reset_for_test() {
     }
 }
 
+/// Convert pending list to DAL `Value::Array` of strings.
+pub fn pending_value() -> Value {
+    let v = pending();
+    Value::Array(v.into_iter().map(Value::String).collect())
+}
+
+/// Non-draining pending snapshot for DAL (debug only).
+pub fn peek_pending_value() -> Value {
+    let v = peek_pending();
+    Value::Array(v.into_iter().map(Value::String).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -378,15 +381,3 @@ mod tests {
         assert!(p.contains(&id), "pending={p:?}");
     }
 }
-
-/// Convert pending list to DAL `Value::Array` of strings.
-pub fn pending_value() -> Value {
-    let v = pending();
-    Value::Array(v.into_iter().map(Value::String).collect())
-}
-
-/// Non-draining pending snapshot for DAL (debug only).
-pub fn peek_pending_value() -> Value {
-    let v = peek_pending();
-    Value::Array(v.into_iter().map(Value::String).collect())
-}
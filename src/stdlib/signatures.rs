@@ -0,0 +1,98 @@
+//! Machine-readable signatures of the stdlib namespace functions.
+//!
+//! The table is generated at build time (`build.rs`) from the dispatch in
+//! `runtime::engine`: each `namespace::function` the engine handles, with the arity it checks,
+//! parameter names and types taken from how the arm reads its arguments, and the value type it
+//! returns (`any` when that varies or cannot be told). It backs completion, hover, semantic
+//! highlighting and inlay hints in the language server.
+
+use serde::Serialize;
+
+/// One parameter of a stdlib function. `ty` is a DAL type name (`int`, `string`, `map`, ...)
+/// or `any`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StdlibParam {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StdlibFunction {
+    pub namespace: &'static str,
+    pub name: &'static str,
+    /// The comment heading the dispatch arm, if any.
+    pub doc: &'static str,
+    pub params: &'static [StdlibParam],
+    pub ret: &'static str,
+}
+
+static SIGNATURES: &[StdlibFunction] = include!(concat!(env!("OUT_DIR"), "/stdlib_signatures.rs"));
+
+impl StdlibFunction {
+    /// `chain::deploy(chain_id: int, contract_name: string, arg3?: map) -> string`
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|p| {
+                let optional = if p.optional { "?" } else { "" };
+                format!("{}{}: {}", p.name, optional, p.ty)
+            })
+            .collect();
+        format!(
+            "{}::{}({}) -> {}",
+            self.namespace,
+            self.name,
+            params.join(", "),
+            self.ret
+        )
+    }
+
+    /// Completion snippet with a tab stop per required parameter: `deploy(${1:chain_id})`.
+    pub fn snippet(&self) -> String {
+        let stops: Vec<String> = self
+            .params
+            .iter()
+            .filter(|p| !p.optional)
+            .enumerate()
+            .map(|(i, p)| format!("${{{}:{}}}", i + 1, p.name))
+            .collect();
+        format!("{}({})", self.name, stops.join(", "))
+    }
+
+    pub fn min_args(&self) -> usize {
+        self.params.iter().filter(|p| !p.optional).count()
+    }
+}
+
+/// Every stdlib function, sorted by namespace then name.
+pub fn all() -> &'static [StdlibFunction] {
+    SIGNATURES
+}
+
+/// The stdlib namespaces, sorted.
+pub fn namespaces() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = SIGNATURES.iter().map(|f| f.namespace).collect();
+    names.dedup();
+    names
+}
+
+pub fn is_namespace(name: &str) -> bool {
+    SIGNATURES.iter().any(|f| f.namespace == name)
+}
+
+pub fn functions(namespace: &str) -> impl Iterator<Item = &'static StdlibFunction> + '_ {
+    SIGNATURES.iter().filter(move |f| f.namespace == namespace)
+}
+
+pub fn lookup(namespace: &str, name: &str) -> Option<&'static StdlibFunction> {
+    SIGNATURES
+        .iter()
+        .find(|f| f.namespace == namespace && f.name == name)
+}
+
+/// The whole registry as pretty-printed JSON.
+pub fn to_json() -> String {
+    serde_json::to_string_pretty(SIGNATURES).unwrap_or_else(|_| "[]".to_string())
+}
@@ -280,9 +280,7 @@ pub fn add_middleware(server: &mut HttpServer, name: String, handler: String, pr
     };
 
     server.middleware.push(middleware);
-    server
-        .middleware
-        .sort_by(|a, b| a.priority.cmp(&b.priority));
+    server.middleware.sort_by_key(|m| m.priority);
 
     crate::stdlib::log::info(
         "Middleware added",
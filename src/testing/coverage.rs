@@ -257,6 +257,12 @@ impl CoverageAnalyzer {
                     self.analyze_statement(stmt);
                 }
             }
+            Expression::Call { callee, arguments } => {
+                self.analyze_expression(callee);
+                for arg in arguments {
+                    self.analyze_expression(arg);
+                }
+            }
             _ => {}
         }
     }
@@ -0,0 +1,304 @@
+// Attribute semantics tests
+// Tests for @cached memoization, @persistent service fields, @versioned migration history and
+// @deprecated call warnings.
+
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::Statement;
+use dist_agent_lang::parser::collect_warnings;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::{FileBackedStorage, InMemoryStorage, Runtime};
+use std::path::PathBuf;
+
+fn parse_and_execute(source: &str) -> Result<Value, String> {
+    run_with_state(source, None)
+}
+
+/// Run `source` with `@persistent` / `@versioned` state kept in the JSON file at `state`
+/// (in memory when None). A fresh runtime per call stands in for a process restart.
+fn run_with_state(source: &str, state: Option<&PathBuf>) -> Result<Value, String> {
+    let program = parse_source(source).map_err(|e| format!("Parser error: {:?}", e))?;
+    let mut runtime = Runtime::new();
+    match state {
+        Some(path) => runtime.set_state_storage(Box::new(FileBackedStorage::new(path).unwrap())),
+        None => runtime.set_state_storage(Box::new(InMemoryStorage::new())),
+    }
+    runtime
+        .execute_program(program, None)
+        .map_err(|e| format!("Runtime error: {:?}", e))?
+        .ok_or_else(|| "No return value".to_string())
+}
+
+fn state_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("dal_attr_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_parse_field_attributes() {
+    let program = parse_source(
+        "service Wallet { @persistent @private balance: int = 0; @public owner: string; }",
+    )
+    .unwrap();
+    let Statement::Service(service) = &program.statements[0] else {
+        panic!("expected service, got {:?}", program.statements[0]);
+    };
+    assert_eq!(service.fields[0].attributes[0].name, "@persistent");
+    assert!(matches!(
+        service.fields[0].visibility,
+        dist_agent_lang::parser::ast::FieldVisibility::Private
+    ));
+    assert!(service.fields[1].attributes.is_empty());
+}
+
+#[test]
+fn test_cached_function_memoizes_by_arguments() {
+    let source = r#"
+service Counter {
+    calls: int = 0;
+    fn bump() { self.calls = self.calls + 1; }
+    fn total() { return self.calls; }
+}
+@cached
+fn square(x) {
+    Counter::bump();
+    return x * x;
+}
+let a = square(3) + square(3) + square(4) + square(3);
+a * 100 + Counter::total()
+"#;
+    // 9 + 9 + 16 + 9 = 43; only square(3) and square(4) ran.
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(4302));
+}
+
+#[test]
+fn test_cached_ttl_and_methods() {
+    let source = r#"
+service Lookup {
+    misses: int = 0;
+
+    @cached
+    fn fetch(key) {
+        self.misses = self.misses + 1;
+        return key + "!";
+    }
+
+    @cached(0)
+    fn fresh(key) {
+        self.misses = self.misses + 1;
+        return key;
+    }
+
+    fn count() { return self.misses; }
+}
+Lookup::fetch("a") + Lookup::fetch("a") + Lookup::fresh("b") + Lookup::fresh("b") + Lookup::count()
+"#;
+    // fetch("a") runs once; fresh("b") has a zero ttl and runs both times.
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("a!a!bb3".to_string())
+    );
+}
+
+#[test]
+fn test_cached_method_is_recomputed_after_a_field_changes() {
+    let source = r#"
+service Prices {
+    rate: int = 2;
+    tags: map = {};
+
+    @cached
+    fn quote(n) { return n * self.rate + self.tags.keys().length(); }
+
+    fn set_rate(r) { self.rate = r; }
+    fn tag(name) { self.tags[name] = true; }
+}
+let a = Prices::quote(10);
+Prices::set_rate(3);
+let b = Prices::quote(10);
+Prices::tag("sale");
+a + ":" + b + ":" + Prices::quote(10)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("20:30:31".to_string())
+    );
+}
+
+#[test]
+fn test_cache_evicts_the_oldest_results_when_full() {
+    let source = r#"
+service Calls {
+    n: int = 0;
+    fn bump() { self.n = self.n + 1; }
+    fn total() { return self.n; }
+}
+@cached
+fn ident(x) {
+    Calls::bump();
+    return x;
+}
+let i = 0;
+while (i < 1100) {
+    ident(i);
+    i = i + 1;
+}
+ident(1099);
+ident(0);
+Calls::total()
+"#;
+    // 1100 misses, a hit for the newest result, and a miss for the evicted oldest one.
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(1101));
+}
+
+#[test]
+fn test_persistent_fields_survive_restart() {
+    let path = state_file("fields");
+    let source = r#"
+service Visits {
+    @persistent count: int = 0;
+    session: int = 0;
+
+    fn visit() {
+        self.count = self.count + 1;
+        self.session = self.session + 1;
+        return self.count + ":" + self.session;
+    }
+}
+let v = Visits::new();
+v.visit();
+v.visit()
+"#;
+    assert_eq!(
+        run_with_state(source, Some(&path)).unwrap(),
+        Value::String("2:2".to_string())
+    );
+    assert_eq!(
+        run_with_state(source, Some(&path)).unwrap(),
+        Value::String("4:2".to_string())
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_persistent_service_persists_every_field() {
+    let path = state_file("service");
+    let source = r#"
+@persistent
+service Inbox {
+    items: list<string> = [];
+    last: string = "";
+
+    fn add(item) {
+        self.items.push(item);
+        self.last = item;
+        return self.items.length() + ":" + self.last;
+    }
+}
+Inbox::add("x")
+"#;
+    assert_eq!(
+        run_with_state(source, Some(&path)).unwrap(),
+        Value::String("1:x".to_string())
+    );
+    assert_eq!(
+        run_with_state(&source.replace("\"x\"", "\"y\""), Some(&path)).unwrap(),
+        Value::String("2:y".to_string())
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_versioned_service_keeps_migration_history() {
+    let path = state_file("versions");
+    let v1 = r#"
+@versioned
+service Profile {
+    @persistent name: string = "ada";
+    @persistent legacy: int = 7;
+}
+service::migration_history("Profile").length()
+"#;
+    let v2 = r#"
+@versioned
+service Profile {
+    @persistent name: string = "ada";
+    @persistent email: string = "";
+
+    fn migrate(from) {
+        self.email = self.name + "@v" + from;
+    }
+
+    fn email() { return self.email; }
+}
+let history = service::migration_history("Profile");
+history.length() + ":" + history[1]["added"][0] + ":" + history[1]["removed"][0] + ":" + Profile::email()
+"#;
+    assert_eq!(run_with_state(v1, Some(&path)).unwrap(), Value::Int(1));
+    // Same schema again: no new record.
+    assert_eq!(run_with_state(v1, Some(&path)).unwrap(), Value::Int(1));
+    assert_eq!(
+        run_with_state(v2, Some(&path)).unwrap(),
+        Value::String("2:email: string:legacy: int:ada@v1".to_string())
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_versioned_label_records_explicit_versions() {
+    let source = r#"
+@versioned("2.1")
+service Api {
+    quota: int = 10;
+}
+let history = service::migration_history("Api");
+history[0]["version"] + ":" + history[0]["fields"][0]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("2.1:quota: int".to_string())
+    );
+}
+
+#[test]
+fn test_deprecated_calls_warn() {
+    let program = parse_source(
+        r#"
+@deprecated("use fetch_v2")
+fn fetch() {
+    return 1;
+}
+
+fn fetch_v2() {
+    return 2;
+}
+
+service Store {
+    @deprecated
+    fn legacy_get() { return 3; }
+    fn get() { return 4; }
+}
+
+let total = fetch() + fetch_v2() + Store::legacy_get() + Store::get();
+"#,
+    )
+    .unwrap();
+    let messages: Vec<(String, usize)> = collect_warnings(&program)
+        .into_iter()
+        .map(|w| (w.message, w.line))
+        .filter(|(m, _)| m.contains("deprecated"))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                "call to deprecated function 'fetch': use fetch_v2".to_string(),
+                17
+            ),
+            (
+                "call to deprecated function 'Store::legacy_get'".to_string(),
+                17
+            ),
+        ]
+    );
+}
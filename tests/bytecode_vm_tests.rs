@@ -0,0 +1,266 @@
+// Bytecode VM tests
+// Every program runs on both the bytecode VM and the tree-walking evaluator; results and
+// errors must match.
+
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::Statement;
+use dist_agent_lang::runtime::bytecode::{compile_body, compile_statement, Instruction};
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+
+fn run(source: &str, bytecode_vm: bool) -> Result<Option<Value>, String> {
+    let program = parse_source(source).map_err(|e| format!("Parse error: {:?}", e))?;
+    let mut runtime = Runtime::new();
+    runtime.set_bytecode_vm(bytecode_vm);
+    runtime
+        .execute_program(program, None)
+        // Suggestions are left out: their order is not deterministic.
+        .map_err(|e| format!("{:?} at {:?}", e.inner, e.location))
+}
+
+/// Run `source` on both engines, assert they agree, and return the VM's result.
+fn run_both(source: &str) -> Result<Option<Value>, String> {
+    let vm = run(source, true);
+    let tree = run(source, false);
+    assert_eq!(vm, tree, "VM and tree-walker disagree on:\n{}", source);
+    vm
+}
+
+fn value_of(source: &str) -> Value {
+    run_both(source)
+        .unwrap_or_else(|e| panic!("program failed: {}", e))
+        .expect("program has a value")
+}
+
+#[test]
+fn test_arithmetic_and_variables() {
+    assert_eq!(
+        value_of("let x = 2; let y = x * 3 + 1; y - 4 / 2"),
+        Value::Int(5)
+    );
+    assert_eq!(
+        value_of("let s = \"a\"; s = s + \"b\"; s"),
+        Value::String("ab".into())
+    );
+    assert_eq!(value_of("-(3 - 5)"), Value::Int(2));
+}
+
+#[test]
+fn test_short_circuit_operators() {
+    assert_eq!(value_of("false && missing_fn()"), Value::Bool(false));
+    assert_eq!(value_of("true || missing_fn()"), Value::Bool(true));
+    assert_eq!(value_of("1 && \"x\""), Value::Bool(true));
+    assert_eq!(value_of("0 || null"), Value::Bool(false));
+}
+
+#[test]
+fn test_while_loop_with_function_calls() {
+    let source = r#"
+fn step(acc, i) {
+    return acc + i % 7;
+}
+let total = 0;
+let i = 0;
+while (i < 100) {
+    total = step(total, i);
+    i = i + 1;
+}
+total
+"#;
+    assert_eq!(value_of(source), Value::Int(295));
+}
+
+#[test]
+fn test_break_continue_and_loop_values() {
+    let source = r#"
+let i = 0;
+let odd = 0;
+while (true) {
+    i = i + 1;
+    if (i > 10) { break; }
+    if (i % 2 == 0) { continue; }
+    odd = odd + i;
+}
+odd
+"#;
+    assert_eq!(value_of(source), Value::Int(25));
+    assert_eq!(
+        value_of("let n = 0; loop { n = n + 1; if (n == 4) { break n * 10; } }"),
+        Value::Int(40)
+    );
+}
+
+#[test]
+fn test_for_in_over_lists_and_ranges() {
+    assert_eq!(
+        value_of("let sum = 0; for x in [1, 2, 3] { sum = sum + x; } sum"),
+        Value::Int(6)
+    );
+    assert_eq!(
+        value_of("let sum = 0; for x in 0..5 { sum = sum + x; } sum"),
+        Value::Int(10)
+    );
+}
+
+#[test]
+fn test_return_from_inside_loops() {
+    let source = r#"
+fn first_over(items, limit) {
+    for x in items {
+        if (x > limit) { return x; }
+    }
+    return -1;
+}
+first_over([1, 5, 9, 12], 6) + first_over([1], 6)
+"#;
+    assert_eq!(value_of(source), Value::Int(8));
+}
+
+#[test]
+fn test_recursion() {
+    let source = r#"
+fn fib(n) {
+    if (n < 2) { return n; }
+    return fib(n - 1) + fib(n - 2);
+}
+fib(15)
+"#;
+    assert_eq!(value_of(source), Value::Int(610));
+}
+
+#[test]
+fn test_collections_fields_and_methods() {
+    assert_eq!(
+        value_of("let m = {\"a\": 1, \"b\": 2}; m[\"b\"] + m.a"),
+        Value::Int(3)
+    );
+    assert_eq!(value_of("let xs = [4, 5, 6]; xs[2]"), Value::Int(6));
+    assert_eq!(
+        value_of("let xs = [1]; xs.push(2); xs.length()"),
+        Value::Int(2)
+    );
+}
+
+#[test]
+fn test_closures() {
+    let source = r#"
+fn apply(v, f) {
+    return f(v);
+}
+apply(21, x => { return x * 2; })
+"#;
+    assert_eq!(value_of(source), Value::Int(42));
+}
+
+#[test]
+fn test_service_methods_and_fields() {
+    let source = r#"
+service Counter {
+    count: int = 0;
+
+    fn bump(by) {
+        self.count = self.count + by;
+        return self.count;
+    }
+}
+let c = Counter::new();
+c.bump(2);
+c.bump(3)
+"#;
+    assert_eq!(value_of(source), Value::Int(5));
+}
+
+#[test]
+fn test_runtime_errors_match() {
+    for source in [
+        "let x = 1; x / 0",
+        "undefined_variable + 1",
+        "fn f(a) { return a; } f(1, 2)",
+        "let i = 0; while (i < 3) { i = i + 1; } nope(i)",
+        "throw \"boom\"",
+        "\"a\" - 1",
+    ] {
+        assert!(
+            run_both(source).is_err(),
+            "expected an error from: {}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_loops_compile_to_jumps() {
+    let program = parse_source("while (i < 10) { i = i + 1; }").unwrap();
+    let chunk = compile_statement(&program.statements[0]).expect("while compiles");
+    assert!(chunk
+        .code
+        .iter()
+        .any(|i| matches!(i, Instruction::JumpIfFalse(_))));
+    assert!(!chunk
+        .code
+        .iter()
+        .any(|i| matches!(i, Instruction::Exec { .. } | Instruction::Eval(_))));
+}
+
+#[test]
+fn test_variables_resolve_to_slots() {
+    let program = parse_source("while (i < 10) { i = i + 1; }").unwrap();
+    let chunk = compile_statement(&program.statements[0]).expect("while compiles");
+    assert_eq!(chunk.variables, vec!["i".to_string()]);
+    assert!(chunk
+        .code
+        .iter()
+        .any(|i| matches!(i, Instruction::Assign(0))));
+}
+
+#[test]
+fn test_slot_variables_stay_in_step_with_the_scope() {
+    // Callees see the caller's variables, closures capture their current values, and the
+    // tree-walker fallbacks (`try`, `match`) read and write them by name.
+    let source = r#"
+fn peek() {
+    return seen;
+}
+fn run() {
+    let seen = 0;
+    let peeked = 0;
+    let total = 0;
+    for x in [1, 2, 3] {
+        seen = seen + x;
+        peeked = peeked + peek();
+        let add_seen = (v) => v + seen;
+        total = add_seen(total);
+        try {
+            total = total + 100;
+        } catch (e) {
+            total = -1;
+        }
+    }
+    return [seen, peeked, total];
+}
+run()
+"#;
+    assert_eq!(
+        value_of(source),
+        Value::Array(vec![Value::Int(6), Value::Int(10), Value::Int(310)])
+    );
+
+    // Top-level variables assigned in a loop are visible to the statements after it.
+    assert_eq!(
+        value_of("let i = 0; let n = 0; while (i < 5) { i = i + 1; n = n + i; } n * 10 + i"),
+        Value::Int(155)
+    );
+}
+
+#[test]
+fn test_declarations_stay_on_the_tree_walker() {
+    let program = parse_source("fn f() { return 1; }").unwrap();
+    assert!(compile_statement(&program.statements[0]).is_none());
+
+    let program = parse_source("fn g() { let x = 1; return x; }").unwrap();
+    let Statement::Function(func) = &program.statements[0] else {
+        panic!("expected a function");
+    };
+    let chunk = compile_body(&func.body);
+    assert!(chunk.code.iter().any(|i| matches!(i, Instruction::Return)));
+}
@@ -0,0 +1,148 @@
+// Bytes value tests
+// `bytes` literals, slicing, concatenation, hex/base64 conversion and the binary-safe `crypto::`,
+// `fs::` and `chain::` paths.
+
+mod common;
+
+use common::parse_and_execute as eval;
+use dist_agent_lang::lexer::lexer::{Lexer, LexerError};
+use dist_agent_lang::runtime::values::Value;
+
+fn bytes(data: &[u8]) -> Value {
+    Value::Bytes(data.to_vec())
+}
+
+#[test]
+fn test_literals() {
+    assert_eq!(eval(r#"b"abc""#).unwrap(), bytes(b"abc"));
+    assert_eq!(eval(r#"b"\x00\xff\n""#).unwrap(), bytes(&[0, 0xff, b'\n']));
+    assert_eq!(
+        eval("0xa9059cbb").unwrap(),
+        bytes(&[0xa9, 0x05, 0x9c, 0xbb])
+    );
+    assert_eq!(
+        eval("type(0x00)").unwrap(),
+        Value::String("bytes".to_string())
+    );
+    assert_eq!(eval("0xdead").unwrap().to_string(), "0xdead");
+
+    let err = eval("0xabc").unwrap_err();
+    assert!(err.starts_with("Lexer error"), "{}", err);
+    let err = Lexer::new("let a = 1;\nlet b = 0xabc;")
+        .tokenize_immutable()
+        .unwrap_err();
+    assert!(
+        matches!(&err, LexerError::InvalidNumber(text, 2, column) if text == "0xabc" && *column > 0),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_concat_index_and_slice() {
+    assert_eq!(eval(r#"b"ab" + 0x00 + b"c""#).unwrap(), bytes(b"ab\0c"));
+    assert_eq!(eval("0x0a0bff[2]").unwrap(), Value::Int(255));
+    assert_eq!(eval("0x0a0bff[3]").unwrap(), Value::Null);
+    assert_eq!(eval("len(0x0a0bff)").unwrap(), Value::Int(3));
+    assert_eq!(
+        eval(r#"let b = b"hello world"; b.slice(6)"#).unwrap(),
+        bytes(b"world")
+    );
+    assert_eq!(
+        eval(r#"bytes::slice(b"hello", 1, -1)"#).unwrap(),
+        bytes(b"ell")
+    );
+    let err = eval(r#"bytes::slice(b"hello", 0, 9)"#).unwrap_err();
+    assert!(err.contains("out of range"), "{}", err);
+    assert_eq!(
+        eval(r#"bytes::concat(b"a", b"b", b"c").length()"#).unwrap(),
+        Value::Int(3)
+    );
+}
+
+#[test]
+fn test_conversions() {
+    assert_eq!(
+        eval(r#"bytes::to_base64(b"hi!")"#).unwrap(),
+        Value::String("aGkh".to_string())
+    );
+    assert_eq!(
+        eval(r#"bytes::from_base64("aGkh") == b"hi!""#).unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        eval(r#"bytes::from_hex("DEAD").to_hex()"#).unwrap(),
+        Value::String("0xdead".to_string())
+    );
+    assert_eq!(
+        eval(r#"bytes("héllo").to_string()"#).unwrap(),
+        Value::String("héllo".to_string())
+    );
+    assert_eq!(eval("bytes([1, 2, 255])").unwrap(), bytes(&[1, 2, 255]));
+    assert_eq!(
+        eval("0x01ff.to_list()").unwrap(),
+        Value::List(vec![Value::Int(1), Value::Int(255)])
+    );
+    let err = eval("bytes::to_string(0xff)").unwrap_err();
+    assert!(err.contains("UTF-8"), "{}", err);
+    let err = eval("bytes([256])").unwrap_err();
+    assert!(err.contains("0..=255"), "{}", err);
+}
+
+#[test]
+fn test_values_serialize_as_hex_text() {
+    let value = bytes(&[0, 1, 0xfe]);
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.contains("\"0x0001fe\""), "{}", json);
+    let back: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, value);
+}
+
+#[test]
+fn test_crypto_hashes_raw_bytes() {
+    assert_eq!(
+        eval(r#"crypto::hash(b"abc", "sha256") == crypto::hash("abc", "sha256")"#).unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        eval(r#"crypto::hash_bytes(0x00ff, "sha512") == crypto::hash(0x00ff, "sha512")"#).unwrap(),
+        Value::Bool(true)
+    );
+    // Not the hash of the "0x00ff" text.
+    assert_eq!(
+        eval(r#"crypto::hash(0x00ff, "sha256") == crypto::hash("0x00ff", "sha256")"#).unwrap(),
+        Value::Bool(false)
+    );
+    assert_eq!(
+        eval(r#"crypto::sign(b"msg", "k", "rsa") == crypto::sign("msg", "k", "rsa")"#).unwrap(),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+#[serial_test::serial]
+fn test_fs_bytes_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("DAL_FS_ROOT", dir.path());
+    let result = eval(
+        r#"
+        fs::write_bytes("blob.bin", 0x00ff80);
+        fs::append_bytes("blob.bin", b"\x01");
+        fs::read_bytes("blob.bin")
+    "#,
+    );
+    std::env::remove_var("DAL_FS_ROOT");
+    assert_eq!(result.unwrap(), bytes(&[0, 0xff, 0x80, 1]));
+    assert_eq!(
+        std::fs::read(dir.path().join("blob.bin")).unwrap(),
+        [0, 0xff, 0x80, 1]
+    );
+}
+
+#[test]
+fn test_chain_args_encode_bytes_as_hex() {
+    let mut args = std::collections::HashMap::new();
+    args.insert("data".to_string(), bytes(&[0xa9, 0x05]));
+    let map = dist_agent_lang::stdlib::chain::chain_arg_map_from_runtime_values(&args);
+    assert_eq!(map["data"], "0xa905");
+}
@@ -0,0 +1,229 @@
+// chan:: channel tests
+// Channels stream values between spawned tasks and agents: sends wait while a channel is
+// full, receives while it is empty, and `for x in ch` runs until the channel is closed.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::runtime::channels;
+use dist_agent_lang::runtime::values::Value;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn test_for_in_receives_until_the_producer_closes() {
+    let source = r#"
+fn produce(ch, n) {
+    let i = 0;
+    while (i < n) {
+        ch.send(i);
+        i = i + 1;
+    }
+    ch.close();
+    return n;
+}
+let ch = chan::new(2);
+let producer = spawn produce(ch, 50);
+let total = 0;
+for x in ch {
+    total = total + x;
+}
+total + ":" + (await producer) + ":" + chan::is_closed(ch)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), string("1225:50:true"));
+}
+
+#[test]
+fn test_send_waits_while_full_and_recv_while_empty() {
+    let source = r#"
+let ch = chan::new(1);
+chan::send(ch, "a");
+let full = "";
+try {
+    chan::send(ch, "b", 20);
+} catch (e: TimeoutError) {
+    full = e;
+}
+let first = chan::recv(ch);
+let empty = "";
+try {
+    chan::recv(ch, 20);
+} catch (e: TimeoutError) {
+    empty = "empty";
+}
+full + ":" + first + ":" + empty + ":" + chan::len(ch)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("Channel operation timed out after 20 ms:a:empty:0")
+    );
+}
+
+#[test]
+fn test_producer_is_held_back_by_a_full_channel() {
+    let source = r#"
+fn produce(ch) {
+    let i = 0;
+    while (i < 5) {
+        chan::send(ch, i);
+        i = i + 1;
+    }
+    return "sent";
+}
+let ch = chan::new(1);
+let producer = spawn produce(ch);
+task::sleep(50);
+let held = chan::len(ch) + ":" + task::status(producer);
+let received = [];
+while (received.length() < 5) {
+    received.push(ch.recv());
+}
+held + ":" + received[4] + ":" + (await producer)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("1:running:4:sent")
+    );
+}
+
+#[test]
+fn test_closed_channel_drains_then_rejects_sends() {
+    let source = r#"
+let ch = chan::new(3);
+ch.send(1);
+ch.send(2);
+let closed = ch.close();
+let rejected = "";
+try {
+    ch.send(3);
+} catch (e: ChannelClosedError) {
+    rejected = "rejected";
+}
+let a = ch.recv();
+let b = ch.recv();
+let done = ch.recv();
+closed + ":" + rejected + ":" + a + b + ":" + (done == null) + ":" + ch.close()
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("true:rejected:12:true:false")
+    );
+}
+
+#[test]
+fn test_unclosed_channels_are_released_with_their_owner() {
+    let source = r#"
+let inner = task::scope(() => {
+    let ch = chan::new(2);
+    ch.send(1);
+    return ch;
+});
+let outer = chan::new(2);
+outer.send(1);
+[inner.is_closed(), inner.len(), outer.is_closed(), outer]
+"#;
+    let Value::Array(values) = parse_and_execute(source).unwrap() else {
+        panic!("expected an array");
+    };
+    assert_eq!(
+        values[..3],
+        [Value::Bool(true), Value::Int(0), Value::Bool(false)]
+    );
+    // The program's runtime is gone, and the channel it created with it.
+    let Value::Channel(outer) = values[3] else {
+        panic!("expected a channel, got {:?}", values[3]);
+    };
+    assert!(channels::is_closed(outer));
+    assert!(channels::send(outer, Value::Int(2), None, None).is_err());
+}
+
+#[test]
+fn test_typed_channel_rejects_other_values() {
+    let source = r#"
+let ch = chan::new(4, "int");
+ch.send(7);
+ch.send("seven");
+"#;
+    let err = parse_and_execute(source).unwrap_err();
+    assert!(err.contains(r#"expected: "int""#), "{}", err);
+    assert!(parse_and_execute("chan::new(0)").is_err());
+    let err = parse_and_execute("chan::recv(1)").unwrap_err();
+    assert!(err.contains(r#"expected: "chan""#), "{}", err);
+}
+
+#[test]
+fn test_channel_travels_in_an_agent_message() {
+    let source = r#"
+fn consume(jobs) {
+    let sum = 0;
+    for job in jobs {
+        sum = sum + job;
+    }
+    return sum;
+}
+let ch = chan::new(2);
+msg chan_test_worker { type: "jobs", jobs: ch };
+let inbox = agent::receive_messages("chan_test_worker");
+let jobs = inbox[0]["content"]["jobs"];
+let consumer = spawn consume(jobs);
+ch.send(1);
+ch.send(2);
+ch.send(3);
+ch.close();
+await consumer
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(6));
+}
+
+#[test]
+fn test_blocked_receiver_can_be_cancelled() {
+    let source = r#"
+fn consume(ch) {
+    for x in ch { }
+    return "finished";
+}
+let ch = chan::new(1);
+let consumer = spawn consume(ch);
+task::sleep(20);
+task::cancel(consumer);
+let caught = "";
+try {
+    await consumer;
+} catch (e: CancelledError) {
+    caught = "cancelled";
+}
+caught
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), string("cancelled"));
+}
+
+#[test]
+fn test_receivers_filling_the_pool_do_not_block_their_producers() {
+    let source = r#"
+let n = task::pool_size() + 2;
+let chans = [];
+let consumers = [];
+let i = 0;
+while (i < n) {
+    let c = chan::new(1);
+    chans.push(c);
+    consumers.push(spawn chan::recv(c));
+    i = i + 1;
+}
+task::sleep(30);
+i = 0;
+while (i < n) {
+    let sent = spawn chan::send(chans[i], i);
+    i = i + 1;
+}
+let got = await all(consumers);
+got.length() + ":" + got[n - 1]
+"#;
+    let n = dist_agent_lang::runtime::tasks::pool_size() + 2;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string(&format!("{}:{}", n, n - 1))
+    );
+}
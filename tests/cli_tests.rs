@@ -47,7 +47,10 @@ fn test_cli_parse_from_global_quiet_with_chain() {
 fn test_cli_parse_from_run_subcommand() {
     let cli = Cli::parse_from(["dal", "run", "app.dal"]);
     match cli.command {
-        Some(Commands::Run { file }) => assert_eq!(file, "app.dal"),
+        Some(Commands::Run { file, wasm }) => {
+            assert_eq!(file, "app.dal");
+            assert!(!wasm);
+        }
         _ => panic!("expected run"),
     }
 }
@@ -0,0 +1,172 @@
+// Closure and function value tests
+// Tests for multi-parameter and expression-bodied arrows, anonymous `fn` expressions, named
+// functions used as values, and calling any expression that evaluates to a function.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::{Expression, Statement};
+use dist_agent_lang::runtime::check_program;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+
+#[test]
+fn test_parse_multi_parameter_arrow() {
+    let program = parse_source("let add = (a, b) => a + b;").unwrap();
+    let Statement::Let(let_stmt) = &program.statements[0] else {
+        panic!("expected let, got {:?}", program.statements[0]);
+    };
+    let Expression::ArrowFunction { params, body } = &let_stmt.value else {
+        panic!("expected arrow function, got {:?}", let_stmt.value);
+    };
+    assert_eq!(params, &["a".to_string(), "b".to_string()]);
+    assert!(matches!(body.statements[0], Statement::Return(_)));
+}
+
+#[test]
+fn test_parenthesized_expression_is_not_an_arrow() {
+    let program = parse_source("let x = (a + b) * 2;").unwrap();
+    let Statement::Let(let_stmt) = &program.statements[0] else {
+        panic!("expected let");
+    };
+    assert!(matches!(let_stmt.value, Expression::BinaryOp(..)));
+}
+
+#[test]
+fn test_arrow_forms() {
+    assert_eq!(
+        parse_and_execute("let add = (a, b) => a + b; add(2, 3)").unwrap(),
+        Value::Int(5)
+    );
+    assert_eq!(
+        parse_and_execute("let answer = () => 42; answer()").unwrap(),
+        Value::Int(42)
+    );
+    assert_eq!(
+        parse_and_execute("let square = x => x * x; square(7)").unwrap(),
+        Value::Int(49)
+    );
+    assert_eq!(
+        parse_and_execute(
+            "let clamp = (x, hi) => { if (x > hi) { return hi; } return x; }; clamp(12, 10)"
+        )
+        .unwrap(),
+        Value::Int(10)
+    );
+}
+
+#[test]
+fn test_anonymous_fn_expression() {
+    assert_eq!(
+        parse_and_execute("let mul = fn(a: int, b: int) -> int { return a * b; }; mul(3, 4)")
+            .unwrap(),
+        Value::Int(12)
+    );
+}
+
+#[test]
+fn test_named_function_as_value() {
+    let source = r#"
+fn double(x) {
+    return x * 2;
+}
+fn apply_twice(f, x) {
+    return f(f(x));
+}
+let g = double;
+g(4) + apply_twice(double, 3)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(20));
+}
+
+#[test]
+fn test_call_operator_on_any_function_value() {
+    let source = r#"
+fn make_adder(n) {
+    return x => x + n;
+}
+let handlers = [x => x + 1, x => x * 10];
+make_adder(1)(2) + handlers[1](3) + (x => x - 1)(10)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(42));
+}
+
+#[test]
+fn test_reduce_written_in_dal() {
+    let source = r#"
+fn reduce(items, init, f) {
+    let acc = init;
+    for x in items {
+        acc = f(acc, x);
+    }
+    return acc;
+}
+reduce([1, 2, 3, 4], 0, (acc, x) => acc + x) * reduce([1, 2, 3], 1, (acc, x) => acc * x)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(60));
+}
+
+#[test]
+fn test_sort_by_with_comparator_written_in_dal() {
+    let source = r#"
+fn sort_by(items, less) {
+    let xs = items;
+    let n = xs.length();
+    let i = 0;
+    while (i < n) {
+        let j = 0;
+        while (j < n - 1 - i) {
+            if (less(xs[j + 1], xs[j])) {
+                let tmp = xs[j];
+                xs[j] = xs[j + 1];
+                xs[j + 1] = tmp;
+            }
+            j = j + 1;
+        }
+        i = i + 1;
+    }
+    return xs;
+}
+sort_by([3, 1, 2], (a, b) => a > b)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::Array(vec![Value::Int(3), Value::Int(2), Value::Int(1)])
+    );
+}
+
+#[test]
+fn test_closure_call_errors() {
+    let err = parse_and_execute("let f = (a, b) => a; f(1)").unwrap_err();
+    assert!(err.contains("ArgumentCountMismatch"), "{}", err);
+
+    let err = parse_and_execute("let xs = [1]; xs[0](2)").unwrap_err();
+    assert!(err.contains("Cannot call a value of type 'int'"), "{}", err);
+}
+
+#[test]
+fn test_tree_walker_agrees_on_closures() {
+    let source = "fn make_adder(n) { return x => x + n; } let add = (a, b) => a + b; add(make_adder(2)(3), 1)";
+    let program = parse_source(source).unwrap();
+    let mut runtime = Runtime::new();
+    runtime.set_bytecode_vm(false);
+    let result = runtime.execute_program(program, None).unwrap();
+    assert_eq!(result, Some(Value::Int(6)));
+}
+
+#[test]
+fn test_check_accepts_closures_and_function_values() {
+    let program = parse_source(
+        r#"
+fn add(a: int, b: int) -> int {
+    return a + b;
+}
+let f = add;
+let g = (x, y) => x * y;
+f(1, 2) + g(3, 4)
+"#,
+    )
+    .unwrap();
+    assert!(check_program(&program).is_empty());
+}
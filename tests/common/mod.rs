@@ -0,0 +1,33 @@
+// Shared integration test helpers
+// Each test binary includes this with `mod common;` and uses only some of the helpers.
+#![allow(dead_code)]
+
+use dist_agent_lang::lexer::Lexer;
+use dist_agent_lang::parser::Parser;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+
+/// Run `source` on a fresh runtime and return the value of its last expression.
+pub fn parse_and_execute(source: &str) -> Result<Value, String> {
+    execute_in(&mut Runtime::new(), source)
+}
+
+/// Run `source` on `runtime`, keeping whatever it defines for later calls.
+pub fn execute_in(runtime: &mut Runtime, source: &str) -> Result<Value, String> {
+    let lexer = Lexer::new(source);
+    let tokens_with_pos = lexer
+        .tokenize_with_positions_immutable()
+        .map_err(|e| format!("Lexer error: {:?}", e))?;
+    let mut parser = Parser::new_with_positions(tokens_with_pos);
+    let program = parser
+        .parse()
+        .map_err(|e| format!("Parser error: {:?}", e))?;
+    runtime
+        .execute_program(program, None)
+        .map_err(|e| format!("Runtime error: {:?}", e))?
+        .ok_or_else(|| "No return value".to_string())
+}
+
+pub fn text(s: &str) -> Value {
+    Value::String(s.to_string())
+}
@@ -1,26 +1,10 @@
 // Control Flow Tests
 // Tests for break, continue, loop, and match statements
 
-use dist_agent_lang::lexer::Lexer;
-use dist_agent_lang::parser::Parser;
+mod common;
+
+use common::parse_and_execute;
 use dist_agent_lang::runtime::values::Value;
-use dist_agent_lang::runtime::Runtime;
-
-fn parse_and_execute(source: &str) -> Result<Value, String> {
-    let lexer = Lexer::new(source);
-    let tokens_with_pos = lexer
-        .tokenize_with_positions_immutable()
-        .map_err(|e| format!("Lexer error: {:?}", e))?;
-    let mut parser = Parser::new_with_positions(tokens_with_pos);
-    let program = parser
-        .parse()
-        .map_err(|e| format!("Parser error: {:?}", e))?;
-    let mut runtime = Runtime::new();
-    runtime
-        .execute_program(program, None)
-        .map_err(|e| format!("Runtime error: {:?}", e))?
-        .ok_or_else(|| "No return value".to_string())
-}
 
 #[test]
 fn test_break_in_while_loop() {
@@ -271,3 +255,38 @@ fn test_break_in_match_case() {
     let result = parse_and_execute(source).unwrap();
     assert_eq!(result, Value::Int(1));
 }
+
+#[test]
+fn test_return_inside_loops_ends_the_function() {
+    let source = r#"
+        fn first_even(xs) {
+            for x in xs {
+                if (x % 2 == 0) {
+                    return x;
+                }
+            }
+            return -1;
+        }
+        fn count_to(n) {
+            let i = 0;
+            while (true) {
+                i = i + 1;
+                if (i == n) {
+                    return i;
+                }
+            }
+        }
+        fn from_loop() {
+            loop {
+                return 7;
+            }
+        }
+        [first_even([1, 4, 6]), count_to(3), from_loop()]
+    "#;
+
+    let result = parse_and_execute(source).unwrap();
+    assert_eq!(
+        result,
+        Value::Array(vec![Value::Int(4), Value::Int(3), Value::Int(7)])
+    );
+}
@@ -0,0 +1,351 @@
+// Debug Adapter Protocol tests
+// Drives a `dap::DapSession` in-process the way an editor would: initialize, launch,
+// breakpoints, stack/variables/evaluate while paused, stepping and termination.
+
+use dist_agent_lang::dap::{read_message, write_message, DapSession};
+use serde_json::{json, Value as Json};
+use std::io::{BufReader, Read, Write};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+const PROGRAM: &str = r#"fn add(a, b) {
+    let sum = a + b;
+    return sum;
+}
+let total = 0;
+for i in [1, 2, 3] {
+    total = add(total, i);
+}
+total
+"#;
+
+/// Input side of the session: bytes arrive over a channel.
+struct ChannelReader {
+    rx: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pending.is_empty() {
+            match self.rx.recv() {
+                Ok(bytes) => self.pending = bytes,
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+struct ChannelWriter(Sender<Vec<u8>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let _ = self.0.send(buf.to_vec());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct Client {
+    input: Option<Sender<Vec<u8>>>,
+    output: Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+    seq: i64,
+    session: Option<thread::JoinHandle<std::io::Result<()>>>,
+    _program: tempfile::NamedTempFile,
+    program_path: String,
+}
+
+impl Client {
+    fn start(source: &str) -> Self {
+        let mut program = tempfile::Builder::new().suffix(".dal").tempfile().unwrap();
+        program.write_all(source.as_bytes()).unwrap();
+        let program_path = program.path().to_string_lossy().into_owned();
+        let (input_tx, input_rx) = channel();
+        let (output_tx, output_rx) = channel();
+        let session = thread::spawn(move || {
+            let reader = BufReader::new(ChannelReader {
+                rx: input_rx,
+                pending: Vec::new(),
+            });
+            DapSession::new(Box::new(ChannelWriter(output_tx))).run(reader)
+        });
+        Client {
+            input: Some(input_tx),
+            output: output_rx,
+            buffer: Vec::new(),
+            seq: 0,
+            session: Some(session),
+            _program: program,
+            program_path,
+        }
+    }
+
+    fn send(&mut self, command: &str, arguments: Json) {
+        self.seq += 1;
+        let body = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        });
+        let mut framed = Vec::new();
+        write_message(&mut framed, &body.to_string()).unwrap();
+        self.input.as_ref().unwrap().send(framed).unwrap();
+    }
+
+    fn next_message(&mut self) -> Json {
+        loop {
+            let mut cursor = std::io::Cursor::new(self.buffer.clone());
+            if let Ok(Some(body)) = read_message(&mut cursor) {
+                self.buffer.drain(..cursor.position() as usize);
+                return serde_json::from_str(&body).unwrap();
+            }
+            let chunk = self
+                .output
+                .recv_timeout(Duration::from_secs(10))
+                .expect("timed out waiting for the adapter");
+            self.buffer.extend(chunk);
+        }
+    }
+
+    /// Read messages until one matches, returning it and everything seen on the way.
+    fn until(&mut self, predicate: impl Fn(&Json) -> bool) -> (Json, Vec<Json>) {
+        let mut seen = Vec::new();
+        loop {
+            let message = self.next_message();
+            if predicate(&message) {
+                return (message, seen);
+            }
+            seen.push(message);
+        }
+    }
+
+    fn request(&mut self, command: &str, arguments: Json) -> Json {
+        self.send(command, arguments);
+        let seq = self.seq;
+        self.until(|m| m["type"] == "response" && m["request_seq"] == seq)
+            .0
+    }
+
+    fn event(&mut self, event: &str) -> (Json, Vec<Json>) {
+        self.until(|m| m["type"] == "event" && m["event"] == event)
+    }
+
+    fn launch(&mut self, extra: Json, breakpoints: Json) {
+        let initialize = self.request("initialize", json!({ "adapterID": "dal" }));
+        assert_eq!(initialize["body"]["supportsConditionalBreakpoints"], true);
+        self.event("initialized");
+        let mut args = json!({ "program": self.program_path });
+        if let Json::Object(extra) = extra {
+            args.as_object_mut().unwrap().extend(extra);
+        }
+        assert_eq!(self.request("launch", args)["success"], true);
+        let path = self.program_path.clone();
+        let response = self.request(
+            "setBreakpoints",
+            json!({ "source": { "path": path }, "breakpoints": breakpoints }),
+        );
+        assert_eq!(response["success"], true);
+        assert_eq!(
+            self.request("configurationDone", json!({}))["success"],
+            true
+        );
+    }
+
+    fn finish(mut self) {
+        self.input.take();
+        self.session.take().unwrap().join().unwrap().unwrap();
+    }
+}
+
+fn stack_lines(client: &mut Client) -> Vec<(String, i64)> {
+    let response = client.request("stackTrace", json!({ "threadId": 1 }));
+    response["body"]["stackFrames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| {
+            (
+                f["name"].as_str().unwrap().to_string(),
+                f["line"].as_i64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_breakpoint_stack_variables_and_evaluate() {
+    let mut client = Client::start(PROGRAM);
+    client.launch(json!({}), json!([{ "line": 2, "condition": "a > 0" }]));
+
+    let (stopped, _) = client.event("stopped");
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    assert_eq!(
+        stack_lines(&mut client),
+        vec![("add".to_string(), 2), ("<main>".to_string(), 7)]
+    );
+
+    let scopes = client.request("scopes", json!({ "frameId": 1 }));
+    let reference = scopes["body"]["scopes"][0]["variablesReference"].clone();
+    let variables = client.request("variables", json!({ "variablesReference": reference }));
+    let a = variables["body"]["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["name"] == "a")
+        .cloned()
+        .unwrap();
+    assert_eq!(a["value"], "1");
+    assert_eq!(a["type"], "int");
+
+    let evaluated = client.request("evaluate", json!({ "expression": "a + b", "frameId": 1 }));
+    assert_eq!(evaluated["body"]["result"], "3");
+    let in_caller = client.request("evaluate", json!({ "expression": "i", "frameId": 2 }));
+    assert_eq!(in_caller["body"]["result"], "2");
+    let bad = client.request("evaluate", json!({ "expression": "nope", "frameId": 1 }));
+    assert_eq!(bad["success"], false);
+
+    // Step out to the caller, then clear breakpoints and run to the end.
+    client.request("stepOut", json!({ "threadId": 1 }));
+    let (stopped, _) = client.event("stopped");
+    assert_eq!(stopped["body"]["reason"], "step");
+    assert_eq!(stack_lines(&mut client), vec![("<main>".to_string(), 7)]);
+
+    let path = client.program_path.clone();
+    client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [] }),
+    );
+    client.request("continue", json!({ "threadId": 1 }));
+    let (exited, seen) = client.event("exited");
+    assert_eq!(exited["body"]["exitCode"], 0);
+    assert!(seen
+        .iter()
+        .any(|m| m["event"] == "output" && m["body"]["output"] == "Result: 6\n"));
+    client.event("terminated");
+    assert_eq!(client.request("disconnect", json!({}))["success"], true);
+    client.finish();
+}
+
+#[test]
+fn test_stop_on_entry_and_stepping() {
+    let mut client = Client::start(PROGRAM);
+    client.launch(json!({ "stopOnEntry": true }), json!([]));
+    let (stopped, _) = client.event("stopped");
+    assert_eq!(stopped["body"]["reason"], "entry");
+
+    let mut lines = Vec::new();
+    for command in ["next", "next", "next", "stepIn"] {
+        client.request(command, json!({ "threadId": 1 }));
+        client.event("stopped");
+        lines.push(stack_lines(&mut client)[0].1);
+    }
+    assert_eq!(lines, vec![5, 6, 7, 2]);
+    client.request("disconnect", json!({}));
+    client.finish();
+}
+
+#[test]
+fn test_disconnect_while_paused_ends_program() {
+    let mut client = Client::start(PROGRAM);
+    client.launch(json!({}), json!([{ "line": 3 }]));
+    client.event("stopped");
+    assert_eq!(client.request("disconnect", json!({}))["success"], true);
+    // The session ends after disconnect; the program thread was joined before responding.
+    client.finish();
+}
+
+#[test]
+fn test_breakpoints_outside_the_program_or_on_empty_lines_are_unverified() {
+    let mut client = Client::start(PROGRAM);
+    client.request("initialize", json!({}));
+    let path = client.program_path.clone();
+    client.request("launch", json!({ "program": path }));
+    let response = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": path }, "breakpoints": [{ "line": 2 }, { "line": 4 }, { "line": 99 }] }),
+    );
+    let verified: Vec<(i64, bool)> = response["body"]["breakpoints"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|bp| (bp["line"].as_i64().unwrap(), bp["verified"] == true))
+        .collect();
+    assert_eq!(verified, vec![(2, true), (4, false), (99, false)]);
+
+    // Another file's breakpoints are rejected and leave the program's in place.
+    let other = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": "/elsewhere/lib.dal" }, "breakpoints": [{ "line": 2 }] }),
+    );
+    assert_eq!(other["body"]["breakpoints"][0]["verified"], false);
+    client.request("configurationDone", json!({}));
+    let (stopped, _) = client.event("stopped");
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    assert_eq!(stack_lines(&mut client)[0], ("add".to_string(), 2));
+    client.request("disconnect", json!({}));
+    client.finish();
+}
+
+#[test]
+fn test_terminate_ends_a_program_launched_without_debugging() {
+    let mut client = Client::start("let i = 0;\nwhile (true) {\n    i = i + 1;\n}\n");
+    client.launch(json!({ "noDebug": true }), json!([{ "line": 3 }]));
+    let stack = client.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(stack["success"], false);
+    assert_eq!(client.request("terminate", json!({}))["success"], true);
+    client.finish();
+}
+
+#[test]
+fn test_runtime_error_reports_exit_code() {
+    let mut client = Client::start("let x = 1;\nmissing_function(x);\n");
+    client.launch(json!({}), json!([]));
+    let (exited, seen) = client.event("exited");
+    assert_eq!(exited["body"]["exitCode"], 1);
+    assert!(seen
+        .iter()
+        .any(|m| m["event"] == "output" && m["body"]["category"] == "stderr"));
+    client.request("disconnect", json!({}));
+    client.finish();
+}
+
+#[test]
+fn test_requests_fail_without_a_program() {
+    let mut client = Client::start(PROGRAM);
+    client.request("initialize", json!({}));
+    let launch = client.request("launch", json!({ "program": "/does/not/exist.dal" }));
+    assert_eq!(launch["success"], false);
+    let stack = client.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(stack["success"], false);
+    let threads = client.request("threads", json!({}));
+    assert_eq!(threads["body"]["threads"][0]["id"], 1);
+    client.request("disconnect", json!({}));
+    client.finish();
+}
+
+#[test]
+fn test_message_framing_handles_back_to_back_messages() {
+    let mut bytes = Vec::new();
+    write_message(&mut bytes, r#"{"a":1}"#).unwrap();
+    write_message(&mut bytes, r#"{"b":2}"#).unwrap();
+    let mut reader = BufReader::new(&bytes[..]);
+    assert_eq!(
+        read_message(&mut reader).unwrap().as_deref(),
+        Some(r#"{"a":1}"#)
+    );
+    assert_eq!(
+        read_message(&mut reader).unwrap().as_deref(),
+        Some(r#"{"b":2}"#)
+    );
+    assert_eq!(read_message(&mut reader).unwrap(), None);
+}
@@ -0,0 +1,175 @@
+// PostgreSQL / MySQL driver tests
+// They need a live server, so they are ignored by default. Set DAL_TEST_POSTGRES_URL (with
+// --features postgres-storage) and/or DAL_TEST_MYSQL_URL (with --features mysql-storage) and run
+// `cargo test --test database_driver_tests -- --ignored`. Conversions and placeholder rewriting
+// are unit tested offline in src/stdlib/database_postgres.rs and database_mysql.rs.
+#![cfg(any(feature = "postgres-storage", feature = "mysql-storage"))]
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::stdlib::database;
+
+const NEEDS_SERVER: &str = "needs DAL_TEST_POSTGRES_URL or DAL_TEST_MYSQL_URL";
+
+/// Server URLs configured for this build, paired with a table-name suffix unique to the backend.
+fn server_urls() -> Vec<(String, &'static str)> {
+    let mut urls = Vec::new();
+    #[cfg(feature = "postgres-storage")]
+    if let Ok(url) = std::env::var("DAL_TEST_POSTGRES_URL") {
+        urls.push((url, "pg"));
+    }
+    #[cfg(feature = "mysql-storage")]
+    if let Ok(url) = std::env::var("DAL_TEST_MYSQL_URL") {
+        urls.push((url, "my"));
+    }
+    assert!(!urls.is_empty(), "{}", NEEDS_SERVER);
+    urls
+}
+
+/// Connect and (re)create `<prefix>_<suffix>` with an integer primary key, a name and a score.
+fn fresh_table(url: &str, prefix: &str, suffix: &str) -> (database::Database, String) {
+    let db = database::connect(url.to_string()).expect("connect");
+    let table = format!("{}_{}", prefix, suffix);
+    database::query(&db, format!("DROP TABLE IF EXISTS {}", table), vec![]).unwrap();
+    database::query(
+        &db,
+        format!(
+            "CREATE TABLE {} (id INTEGER PRIMARY KEY, name VARCHAR(64) NOT NULL, score FLOAT)",
+            table
+        ),
+        vec![],
+    )
+    .unwrap();
+    (db, table)
+}
+
+#[test]
+#[ignore = "needs DAL_TEST_POSTGRES_URL or DAL_TEST_MYSQL_URL"]
+fn test_query_with_bound_params() {
+    for (url, suffix) in server_urls() {
+        let (db, table) = fresh_table(&url, "dal_params", suffix);
+        let insert = format!("INSERT INTO {} (id, name, score) VALUES (?, ?, ?)", table);
+        let result = database::query(
+            &db,
+            insert.clone(),
+            vec![
+                Value::Int(1),
+                Value::String("ada".into()),
+                Value::Float(9.5),
+            ],
+        )
+        .unwrap();
+        assert_eq!(result.affected_rows, 1);
+        database::query(
+            &db,
+            insert,
+            vec![Value::Int(2), Value::String("it's".into()), Value::Null],
+        )
+        .unwrap();
+
+        let rows = database::query(
+            &db,
+            format!(
+                "SELECT id, name, score FROM {} WHERE id >= ? ORDER BY id",
+                table
+            ),
+            vec![Value::Int(1)],
+        )
+        .unwrap();
+        assert_eq!(rows.row_count, 2);
+        assert_eq!(rows.rows[0]["id"], Value::Int(1));
+        assert_eq!(rows.rows[0]["name"], Value::String("ada".into()));
+        assert_eq!(rows.rows[0]["score"], Value::Float(9.5));
+        assert_eq!(rows.rows[1]["name"], Value::String("it's".into()));
+        assert_eq!(rows.rows[1]["score"], Value::Null);
+
+        let err = database::query(&db, format!("SELECT * FROM {} WHERE id = ?", table), vec![])
+            .unwrap_err();
+        assert!(err.contains("expects 1 parameter"), "{}", err);
+    }
+}
+
+#[test]
+#[ignore = "needs DAL_TEST_POSTGRES_URL or DAL_TEST_MYSQL_URL"]
+fn test_schema_introspection_and_explain() {
+    for (url, suffix) in server_urls() {
+        let (db, table) = fresh_table(&url, "dal_schema", suffix);
+        assert!(database::list_tables(&db).unwrap().contains(&table));
+
+        let schema = database::get_table_schema(&db, table.clone()).unwrap();
+        let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "name", "score"]);
+        assert!(schema.columns[0].is_primary_key);
+        assert!(!schema.columns[1].is_nullable);
+        assert!(schema.columns[2].is_nullable);
+
+        assert!(database::get_table_schema(&db, "no_such_table".into()).is_err());
+        assert!(database::ping_database(&db).unwrap());
+
+        let plan =
+            database::get_query_plan(&db, format!("SELECT * FROM {} WHERE id = 1", table)).unwrap();
+        assert!(matches!(&plan["plan_lines"], Value::String(s) if !s.is_empty()));
+    }
+}
+
+#[test]
+#[ignore = "needs DAL_TEST_POSTGRES_URL or DAL_TEST_MYSQL_URL"]
+fn test_transactions_commit_and_roll_back() {
+    for (url, suffix) in server_urls() {
+        let (db, table) = fresh_table(&url, "dal_txn", suffix);
+        let insert = |id: i64| format!("INSERT INTO {} (id, name) VALUES ({}, 'x')", table, id);
+        let count = || {
+            database::query(&db, format!("SELECT id FROM {}", table), vec![])
+                .unwrap()
+                .row_count
+        };
+
+        let mut tx = database::transaction(&db, vec![insert(1), insert(2)]).unwrap();
+        database::commit_transaction(&mut tx).unwrap();
+        assert_eq!(count(), 2);
+
+        let mut tx = database::transaction(&db, vec![insert(3)]).unwrap();
+        database::rollback_transaction(&mut tx).unwrap();
+        assert_eq!(count(), 2);
+
+        // A failing statement rolls back everything before it.
+        assert!(database::transaction(&db, vec![insert(4), insert(1)]).is_err());
+        assert_eq!(count(), 2);
+
+        // An open transaction runs on its own connection: queries on `db` stay outside it, and
+        // dropping it without committing rolls it back.
+        let tx = database::transaction(&db, vec![insert(5)]).unwrap();
+        database::query(&db, insert(6), vec![]).unwrap();
+        drop(tx);
+        assert_eq!(count(), 3);
+    }
+}
+
+#[test]
+#[ignore = "needs DAL_TEST_POSTGRES_URL or DAL_TEST_MYSQL_URL"]
+fn test_dal_pool_and_transaction_builtins() {
+    for (url, suffix) in server_urls() {
+        let (_db, table) = fresh_table(&url, "dal_pool", suffix);
+        let source = format!(
+            r#"
+let pool = database::create_connection_pool("main", "{url}", 2, 1);
+let conn = database::get_connection_from_pool(pool);
+let tx = database::transaction(conn, ["INSERT INTO {table} (id, name) VALUES (1, 'a')"]);
+database::commit_transaction(tx);
+let tx2 = database::transaction(conn, ["INSERT INTO {table} (id, name) VALUES (2, 'b')"]);
+database::rollback_transaction(tx2);
+let result = database::query(conn, "SELECT name FROM {table} WHERE id = ?", [1]);
+database::return_connection_to_pool(pool, conn);
+let again = database::get_connection_from_pool(pool);
+let all = database::query(again, "SELECT id FROM {table}");
+result["rows"][0]["name"] + ":" + all["row_count"]
+"#
+        );
+        assert_eq!(
+            parse_and_execute(&source).unwrap(),
+            Value::String("a:1".to_string())
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Step debugger tests
+// Breakpoints, stepping, frame inspection and the console prompt used by `dal debug`.
+
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::debugger::{
+    frame_variables, parse_breakpoint_spec, stack_frames, DebugCommand, PauseReason, StepMode,
+    SESSION_TERMINATED,
+};
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::{ConsoleDebugger, DebugHook, Debugger, Runtime, RuntimeError};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+const PROGRAM: &str = r#"fn add(a, b) {
+    let sum = a + b;
+    return sum;
+}
+let total = 0;
+for i in [1, 2, 3] {
+    total = add(total, i);
+}
+total
+"#;
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Continue,
+    Into,
+    Over,
+    Out,
+}
+
+#[derive(Debug, Clone)]
+struct Pause {
+    line: usize,
+    reason: PauseReason,
+    frames: Vec<String>,
+    a: Option<Value>,
+}
+
+/// Records every pause and answers it with the next scripted action.
+struct Recorder {
+    debugger: Debugger,
+    script: VecDeque<Action>,
+    pauses: Arc<Mutex<Vec<Pause>>>,
+}
+
+impl DebugHook for Recorder {
+    fn on_statement(&mut self, runtime: &mut Runtime) -> Result<(), RuntimeError> {
+        let Some(reason) = self.debugger.should_pause(runtime) else {
+            return Ok(());
+        };
+        let a = frame_variables(runtime, 0)
+            .into_iter()
+            .find(|(name, _)| name == "a")
+            .map(|(_, v)| v);
+        self.pauses.lock().unwrap().push(Pause {
+            line: runtime.current_location().unwrap().line,
+            reason,
+            frames: stack_frames(runtime).into_iter().map(|f| f.name).collect(),
+            a,
+        });
+        match self.script.pop_front().unwrap_or(Action::Continue) {
+            Action::Continue => self.debugger.resume(StepMode::Continue),
+            Action::Into => self.debugger.step_into(),
+            Action::Over => self.debugger.step_over(runtime),
+            Action::Out => self.debugger.step_out(runtime),
+        }
+        Ok(())
+    }
+}
+
+fn record(debugger: Debugger, script: &[Action]) -> (Vec<Pause>, Value) {
+    let pauses = Arc::new(Mutex::new(Vec::new()));
+    let mut runtime = Runtime::new();
+    runtime.set_debug_hook(Box::new(Recorder {
+        debugger,
+        script: script.iter().copied().collect(),
+        pauses: pauses.clone(),
+    }));
+    let result = runtime
+        .execute_program(parse_source(PROGRAM).unwrap(), None)
+        .unwrap()
+        .unwrap();
+    let pauses = pauses.lock().unwrap().clone();
+    (pauses, result)
+}
+
+fn lines(pauses: &[Pause]) -> Vec<usize> {
+    pauses.iter().map(|p| p.line).collect()
+}
+
+#[test]
+fn test_line_breakpoint_pauses_inside_function_each_call() {
+    let mut debugger = Debugger::new();
+    debugger.set_breakpoint(2, None);
+    let (pauses, result) = record(debugger, &[]);
+    assert_eq!(result, Value::Int(6));
+    assert_eq!(lines(&pauses), vec![2, 2, 2]);
+    assert_eq!(pauses[0].reason, PauseReason::Breakpoint(2));
+    assert_eq!(pauses[0].frames, vec!["add", "<main>"]);
+    let a: Vec<Option<Value>> = pauses.iter().map(|p| p.a.clone()).collect();
+    assert_eq!(
+        a,
+        vec![
+            Some(Value::Int(0)),
+            Some(Value::Int(1)),
+            Some(Value::Int(3))
+        ]
+    );
+}
+
+#[test]
+fn test_conditional_breakpoint_only_pauses_when_true() {
+    let mut debugger = Debugger::new();
+    debugger.set_breakpoint(2, Some("b == 2".to_string()));
+    let (pauses, _) = record(debugger, &[]);
+    assert_eq!(pauses.len(), 1);
+    assert_eq!(pauses[0].a, Some(Value::Int(1)));
+}
+
+#[test]
+fn test_step_over_stays_in_caller() {
+    let mut debugger = Debugger::new();
+    debugger.set_breakpoint(7, None);
+    // Each `next` from line 7 runs the whole `add` call and stops at the next iteration.
+    let (pauses, _) = record(debugger, &[Action::Over, Action::Over]);
+    assert_eq!(lines(&pauses), vec![7, 7, 7]);
+    assert_eq!(pauses[1].reason, PauseReason::Step);
+    assert!(pauses.iter().all(|p| p.frames == vec!["<main>"]));
+}
+
+#[test]
+fn test_step_into_and_out_of_function() {
+    let debugger = Debugger::new().with_stop_on_entry(true);
+    // entry (line 1) -> 5 -> 6 -> 7 -> into add (2) -> out to the next iteration (7)
+    let (pauses, _) = record(
+        debugger,
+        &[
+            Action::Into,
+            Action::Into,
+            Action::Into,
+            Action::Into,
+            Action::Out,
+        ],
+    );
+    assert_eq!(lines(&pauses), vec![1, 5, 6, 7, 2, 7]);
+    assert_eq!(pauses[0].reason, PauseReason::Entry);
+    assert_eq!(pauses[4].frames, vec!["add", "<main>"]);
+    assert_eq!(pauses[5].frames, vec!["<main>"]);
+}
+
+/// Shared output buffer so the test can read what the console debugger wrote.
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn console_session(debugger: Debugger, input: &str) -> (Result<Option<Value>, String>, String) {
+    let output = SharedOutput::default();
+    let mut runtime = Runtime::new();
+    runtime.set_debug_hook(Box::new(ConsoleDebugger::new(
+        debugger,
+        PROGRAM,
+        std::io::Cursor::new(input.as_bytes().to_vec()),
+        output.clone(),
+    )));
+    let result = runtime
+        .execute_program(parse_source(PROGRAM).unwrap(), None)
+        .map_err(|e| e.inner.to_string());
+    let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+    (result, text)
+}
+
+#[test]
+fn test_console_evaluates_expressions_in_selected_frame() {
+    let mut debugger = Debugger::new();
+    debugger.set_breakpoint(2, Some("a > 0".to_string()));
+    let (result, output) =
+        console_session(debugger, "bt\np a * 10\nframe 1\np i\nlocals\nbl\nd 2\nc\n");
+    assert_eq!(result.unwrap(), Some(Value::Int(6)));
+    assert!(
+        output.contains("Paused (breakpoint at line 2) at line 2"),
+        "{}",
+        output
+    );
+    assert!(output.contains("* #0 add at line 2"), "{}", output);
+    assert!(output.contains("  #1 <main> at line 7"), "{}", output);
+    assert!(output.contains("(dal) 10\n"), "{}", output);
+    assert!(output.contains("(dal) 2\n"), "{}", output);
+    assert!(output.contains("  total = 1"), "{}", output);
+    assert!(output.contains("line 2 if a > 0 (hit 1)"), "{}", output);
+    assert!(
+        output.contains("Breakpoint at line 2 removed"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_console_assignment_changes_program_state() {
+    let mut debugger = Debugger::new();
+    debugger.set_breakpoint(9, None);
+    let (result, _) = console_session(debugger, "total = 100\nc\n");
+    assert_eq!(result.unwrap(), Some(Value::Int(100)));
+}
+
+#[test]
+fn test_console_quit_terminates_program() {
+    let debugger = Debugger::new().with_stop_on_entry(true);
+    let (result, output) = console_session(debugger, "quit\n");
+    assert!(result.unwrap_err().contains(SESSION_TERMINATED));
+    assert!(output.contains("Paused (entry) at line 1"));
+}
+
+#[test]
+fn test_console_runs_to_completion_when_input_closes() {
+    let debugger = Debugger::new().with_stop_on_entry(true);
+    let (result, _) = console_session(debugger, "");
+    assert_eq!(result.unwrap(), Some(Value::Int(6)));
+}
+
+#[test]
+fn test_debug_command_parsing() {
+    assert_eq!(
+        DebugCommand::parse("n").unwrap(),
+        Some(DebugCommand::StepOver)
+    );
+    assert_eq!(
+        DebugCommand::parse("break 12 if x > 3").unwrap(),
+        Some(DebugCommand::Break {
+            line: 12,
+            condition: Some("x > 3".to_string())
+        })
+    );
+    assert_eq!(
+        DebugCommand::parse("total + 1").unwrap(),
+        Some(DebugCommand::Print("total + 1".to_string()))
+    );
+    assert_eq!(DebugCommand::parse("   ").unwrap(), None);
+    assert!(DebugCommand::parse("b zero").is_err());
+    assert_eq!(parse_breakpoint_spec("4").unwrap(), (4, None));
+    assert!(parse_breakpoint_spec("0").is_err());
+}
+
+#[test]
+fn test_runtime_error_reports_line_inside_function() {
+    let program = parse_source("fn f() {\n    let x = 1;\n    return missing;\n}\nf();\n").unwrap();
+    let err = Runtime::new().execute_program(program, None).unwrap_err();
+    assert_eq!(err.location.unwrap().line, 3);
+}
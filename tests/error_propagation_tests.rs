@@ -0,0 +1,226 @@
+// Error propagation tests
+// `expr?` on `Result` / `Option` values and typed `catch` clauses matching thrown structs and
+// runtime error categories.
+
+mod common;
+
+use common::{parse_and_execute as eval, text};
+use dist_agent_lang::runtime::values::Value;
+
+#[test]
+fn test_question_mark_unwraps_ok_and_some() {
+    let source = r#"
+        fn parse(s) {
+            if (s == "") { return Err("empty"); }
+            return Ok(s + "!");
+        }
+        fn shout(s) {
+            let loud = parse(s)?;
+            return Ok(loud + loud);
+        }
+        shout("hi")
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::ok(text("hi!hi!")));
+    assert_eq!(
+        eval("fn first(o) { return o? + 1; } first(Some(41))").unwrap(),
+        Value::Int(42)
+    );
+    // Plain values pass through unchanged.
+    assert_eq!(eval("fn f() { return 5?; } f()").unwrap(), Value::Int(5));
+}
+
+#[test]
+fn test_question_mark_returns_err_none_and_null_early() {
+    let source = r#"
+        let reached = false;
+        fn step(r) {
+            let v = r?;
+            reached = true;
+            return Ok(v);
+        }
+        step(Err("boom"))
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::err(text("boom")));
+    assert_eq!(
+        eval("fn f() { let x = None?; return 1; } f()").unwrap(),
+        Value::none()
+    );
+    assert_eq!(
+        eval("fn f(m) { return m[\"missing\"]?.length(); } f({})").unwrap(),
+        Value::Null
+    );
+    // Nested calls propagate one frame at a time.
+    let source = r#"
+        fn inner() { return Err(1)?; }
+        fn outer() { let v = inner()?; return Ok(v); }
+        outer()
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::err(Value::Int(1)));
+}
+
+#[test]
+fn test_err_with_a_null_payload_is_still_an_err() {
+    assert_eq!(
+        eval("fn f() { let z = Err(null)?; return \"passed\"; } f()").unwrap(),
+        Value::err(Value::Null)
+    );
+    assert_eq!(
+        eval("fn f() { let z = Ok(null)?; return z == null; } f()").unwrap(),
+        Value::Bool(true)
+    );
+    let source = r#"
+        fn kind(r) {
+            match r {
+                Ok(v) => { return "ok"; },
+                Err(e) => { return "err"; }
+            }
+        }
+        kind(Err(null)) + ":" + kind(Ok(null)) + ":" + Err(null)
+    "#;
+    assert_eq!(eval(source).unwrap(), text("err:ok:Err(null)"));
+    assert!(Value::err(Value::Null).is_err());
+    assert!(!Value::err(Value::Null).is_ok());
+    assert!(Value::ok(Value::Null).is_ok());
+}
+
+#[test]
+fn test_question_mark_in_methods_and_top_level() {
+    let source = r#"
+        service Vault {
+            fn withdraw(balance, amount) {
+                let checked = (if_enough(balance, amount))?;
+                return Ok(balance - checked);
+            }
+        }
+        fn if_enough(balance, amount) {
+            if (amount > balance) { return Err("insufficient"); }
+            return Ok(amount);
+        }
+        Vault::withdraw(10, 20)
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::err(text("insufficient")));
+    // At top level `?` ends the program with the propagated value.
+    assert_eq!(
+        eval("Err(\"stop\")?;\n42").unwrap(),
+        Value::err(text("stop"))
+    );
+}
+
+#[test]
+fn test_question_mark_is_not_caught_by_try() {
+    let source = r#"
+        fn f() {
+            try {
+                let v = Err("inner")?;
+                return "not reached";
+            } catch (e) {
+                return "caught";
+            }
+        }
+        f()
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::err(text("inner")));
+}
+
+#[test]
+fn test_typed_catch_matches_thrown_struct() {
+    let source = r#"
+        struct InsufficientFunds { needed: int }
+        struct Frozen { reason: string }
+        fn withdraw(n) {
+            if (n > 100) { throw InsufficientFunds { needed: n - 100 }; }
+            throw Frozen { reason: "audit" };
+        }
+        fn attempt(n) {
+            try {
+                withdraw(n);
+            } catch (e: Frozen) {
+                return "frozen: " + e.reason;
+            } catch (e: InsufficientFunds) {
+                return "short by " + e.needed;
+            }
+        }
+        attempt(150) + " / " + attempt(1)
+    "#;
+    assert_eq!(eval(source).unwrap(), text("short by 50 / frozen: audit"));
+}
+
+#[test]
+fn test_catch_forms_bind_and_filter() {
+    // `catch (Type var)` still works, a lowercase single name binds the error.
+    let source = r#"
+        let a = "";
+        try { throw "x"; } catch (ThrownError e) { a = a + "typed:" + e; }
+        try { throw "y"; } catch (err) { a = a + ",var:" + err; }
+        try { throw "z"; } catch (Error) { a = a + ",bare"; }
+        a
+    "#;
+    assert_eq!(eval(source).unwrap(), text("typed:x,var:y,bare"));
+
+    // No matching clause: the error keeps propagating, after `finally` runs.
+    let source = r#"
+        let log = "";
+        try {
+            try { 1 / 0; } catch (e: PermissionError) { log = "wrong"; } finally { log = log + "finally"; }
+        } catch (e: ArithmeticError) {
+            log = log + ",arith:" + e;
+        }
+        log
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        text("finally,arith:Division by zero")
+    );
+}
+
+#[test]
+fn test_error_in_catch_body_propagates() {
+    let source = r#"
+        try {
+            try { throw "first"; } catch (e) { throw "second"; } catch (e: Error) { 1; }
+        } catch (outer) {
+            outer
+        }
+    "#;
+    assert_eq!(eval(source).unwrap(), text("second"));
+}
+
+#[test]
+fn test_typed_catch_matches_runtime_categories() {
+    let source = r#"
+        fn kind(f) {
+            try {
+                f();
+                return "ok";
+            } catch (e: ValidationError) {
+                return "validation";
+            } catch (e: NotFoundError) {
+                return "not-found";
+            } catch (e: NetworkError) {
+                return "network";
+            }
+        }
+        [kind(() => len(1, 2)), kind(() => missing_fn()), kind(() => http::fetch_text("ftp://example.com"))]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![text("validation"), text("not-found"), text("network")])
+    );
+}
+
+#[test]
+fn test_chain_errors_match_chain_and_permission_categories() {
+    // chain:: outside a @trust / @chain service is denied.
+    let source = r#"
+        let caught = [];
+        try { chain::get_balance(1, "0x1234567890abcdef1234567890abcdef12345678"); }
+        catch (e: ChainError) { caught.push("chain"); }
+        try { chain::get_balance(1, "0x1234567890abcdef1234567890abcdef12345678"); }
+        catch (e: PermissionError) { caught.push("permission"); }
+        caught
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![text("chain"), text("permission")])
+    );
+}
@@ -0,0 +1,184 @@
+// Formatter tests
+// `dal fmt` keeps comments and blank-line grouping, normalizes indentation and spacing, splits
+// over-long bracket lists, is idempotent, and reads `[fmt]` from dal.toml.
+
+use dist_agent_lang::formatter::{diff, format_source, FormatConfig, FormatError};
+
+fn fmt(source: &str) -> String {
+    format_source(source, &FormatConfig::default()).unwrap()
+}
+
+#[test]
+fn test_comments_and_blank_lines_are_kept() {
+    let source = r#"// Vault service
+/* header
+   block */
+@trust("hybrid")
+@chain("ethereum")
+service Vault {
+
+
+    balance: int = 0;  // running total
+
+    // Deposit funds.
+    fn deposit(amount: int) {
+        balance = balance + amount; /* no overflow check */
+
+
+
+        return balance;
+
+    }
+}
+"#;
+    let expected = r#"// Vault service
+/* header
+   block */
+@trust("hybrid")
+@chain("ethereum")
+service Vault {
+    balance: int = 0; // running total
+
+    // Deposit funds.
+    fn deposit(amount: int) {
+        balance = balance + amount; /* no overflow check */
+
+        return balance;
+    }
+}
+"#;
+    assert_eq!(fmt(source), expected);
+}
+
+#[test]
+fn test_indentation_and_spacing_are_normalized() {
+    let source = "fn  f(a,b)->map<string,int>{\nif(a > b){\nreturn {\"x\":a};\n}else{\nlet n=-a*2;\nreturn {\"x\": !b};\n}\n}\nlet r=chain::get_balance( 1 ,\"0x\" )?;\nlet v = r?.value ?? 0 |> to_string;\nlet s = items\n.map(f)\n.len();";
+    let expected = r#"fn f(a, b) -> map<string, int> {
+    if (a > b) {
+        return {"x": a};
+    } else {
+        let n = -a * 2;
+        return {"x": !b};
+    }
+}
+let r = chain::get_balance(1, "0x")?;
+let v = r?.value ?? 0 |> to_string;
+let s = items
+    .map(f)
+    .len();
+"#;
+    assert_eq!(fmt(source), expected);
+}
+
+#[test]
+fn test_strings_and_templates_are_untouched() {
+    let source = "let a = \"  spaced   ${ x  +  1 }  \";\nlet p = \"\"\"\n      Dear ${name},\n   bye\n\"\"\";\n";
+    assert_eq!(fmt(source), source);
+}
+
+#[test]
+fn test_long_lines_split_at_commas() {
+    let source = "let result = deploy_contract(\"ethereum\", contract_source, constructor_args, gas_limit, deployer_address);\n";
+    let expected = r#"let result = deploy_contract(
+    "ethereum",
+    contract_source,
+    constructor_args,
+    gas_limit,
+    deployer_address
+);
+"#;
+    assert_eq!(fmt(source), expected);
+    // Short lines stay on one line; a narrower width splits them.
+    let short = "let xs = [first_value, second_value, third_value];\n";
+    assert_eq!(fmt(short), short);
+    let narrow = FormatConfig {
+        width: 30,
+        indent: 2,
+    };
+    assert_eq!(
+        format_source(short, &narrow).unwrap(),
+        "let xs = [\n  first_value,\n  second_value,\n  third_value\n];\n"
+    );
+}
+
+#[test]
+fn test_formatting_is_idempotent() {
+    let samples = [
+        "fn f(a,b){\n// c\nlet x=[1,2,\n3];return x;}\n",
+        "service S {\n  fn m() {\n    let s = \"a\" +\n  \"b\" +\n    \"c\";\n  }\n}",
+        "let m = {\n\"a\": 1, // one\n\n\n\"b\": 2\n};\nlet y = m |>\nkeys;",
+    ];
+    for sample in samples {
+        let once = fmt(sample);
+        assert_eq!(fmt(&once), once, "not idempotent for {:?}", sample);
+    }
+    // Every example that parses formats to a fixed point.
+    let mut formatted = 0;
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("dal") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        if let Ok(once) = format_source(&source, &FormatConfig::default()) {
+            assert_eq!(fmt(&once), once, "not idempotent: {}", path.display());
+            formatted += 1;
+        }
+    }
+    assert!(formatted > 20, "only {} examples formatted", formatted);
+}
+
+#[test]
+fn test_sources_with_syntax_errors_are_refused() {
+    let config = FormatConfig::default();
+    assert!(matches!(
+        format_source("fn f( {\n", &config),
+        Err(FormatError::Parser(_))
+    ));
+    assert!(matches!(
+        format_source("let s = \"unterminated\n", &config),
+        Err(FormatError::Lexer(_))
+    ));
+}
+
+#[test]
+fn test_fmt_config_from_manifest() {
+    let config = FormatConfig::from_manifest_str("[package]\nname = \"x\"\n").unwrap();
+    assert_eq!(config, FormatConfig::default());
+    let config = FormatConfig::from_manifest_str("[fmt]\nwidth = 80\nindent = 2\n").unwrap();
+    assert_eq!(
+        config,
+        FormatConfig {
+            width: 80,
+            indent: 2
+        }
+    );
+    assert!(FormatConfig::from_manifest_str("[fmt]\nindent = \"tab\"\n").is_err());
+    assert!(FormatConfig::from_manifest_str("[fmt]\nwidth = 0\n").is_err());
+
+    // The nearest dal.toml above the file applies.
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("dal.toml"), "[fmt]\nindent = 3\n").unwrap();
+    let nested = dir.path().join("src");
+    std::fs::create_dir(&nested).unwrap();
+    let file = nested.join("main.dal");
+    std::fs::write(&file, "fn f() {\nreturn 1;\n}\n").unwrap();
+    let config = FormatConfig::discover(&file).unwrap().unwrap();
+    assert_eq!(config.indent, 3);
+    let source = std::fs::read_to_string(&file).unwrap();
+    assert_eq!(
+        format_source(&source, &config).unwrap(),
+        "fn f() {\n   return 1;\n}\n"
+    );
+}
+
+#[test]
+fn test_check_diff_lists_changed_lines() {
+    let original = "let a = 1;\nlet b=2;\nlet c = 3;\n";
+    let formatted = fmt(original);
+    assert_eq!(
+        diff(original, &formatted),
+        "@@ line 2 @@\n-let b=2;\n+let b = 2;\n"
+    );
+    assert_eq!(diff(&formatted, &formatted), "");
+}
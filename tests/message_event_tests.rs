@@ -0,0 +1,169 @@
+// msg / event delivery tests
+// `msg` queues payloads for agents; `event` publishes on the in-process event bus to `@on`
+// service handlers and `service::subscribe` callbacks, validated against event declarations.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::runtime::values::Value;
+
+#[test]
+fn test_msg_delivers_to_agent_queue_and_bus() {
+    let source = r#"
+msg msg_test_worker { type: "task", job: 7 };
+let queued = ai::receive_message("msg_test_worker", 0);
+let bus = agent::receive_messages("msg_test_worker");
+queued["job"] + ":" + bus.length() + ":" + bus[0]["message_type"] + ":" + bus[0]["sender_id"] + ":" + bus[0]["content"]["job"]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("7:1:task:main:7".to_string())
+    );
+}
+
+#[test]
+fn test_msg_recipient_variable_and_with_keyword() {
+    let source = r#"
+let target = "msg_test_dynamic";
+msg target with { note: "hi" };
+let queued = ai::receive_message("msg_test_dynamic", 0);
+queued["note"] + ":" + agent::receive_messages("msg_test_dynamic")[0]["message_type"]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("hi:msg".to_string())
+    );
+}
+
+#[test]
+fn test_event_runs_service_handlers() {
+    let source = r#"
+service Token {
+    event Transfer(from: string, to: string, amount: int);
+
+    fn send(to, amount) {
+        event Transfer { from: "alice", to: to, amount: amount };
+    }
+}
+
+service Ledger {
+    total: int = 0;
+    last: string = "";
+
+    @on("Transfer")
+    fn record(payload) {
+        self.total = self.total + payload["amount"];
+    }
+
+    @on("Transfer")
+    fn remember(to, amount) {
+        self.last = to + "=" + amount;
+    }
+
+    fn summary() { return self.total + ":" + self.last; }
+}
+
+Token::send("bob", 5);
+Token::send("carol", 7);
+let log = service::event_log("Transfer");
+Ledger::summary() + ":" + log.length() + ":" + log[1]["source"] + ":" + log[1]["sequence"]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("12:carol=7:2:Token:2".to_string())
+    );
+}
+
+#[test]
+fn test_subscribe_callbacks_and_event_log() {
+    let source = r#"
+service Counter {
+    total: int = 0;
+    fn add(n) { self.total = self.total + n; }
+    fn total() { return self.total; }
+}
+service::subscribe("Ping", fn(payload) { Counter::add(payload["n"]); });
+service::subscribe("Ping", fn(payload) { Counter::add(payload["n"] * 10); });
+event Nobody { n: 2 };
+event Ping { n: 1 };
+event Ping { n: 3 };
+let pings = service::event_log("Ping");
+Counter::total() + ":" + service::event_log().length() + ":" + pings[1]["payload"]["n"] + ":" + pings[0]["source"]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("44:3:3:null".to_string())
+    );
+}
+
+#[test]
+fn test_event_payload_validated_against_declaration() {
+    let emit = |payload: &str| {
+        format!(
+            r#"
+service Token {{
+    event Transfer(to: string, amount: int);
+
+    fn send() {{
+        event Transfer {{ {} }};
+        return "sent";
+    }}
+}}
+Token::send()
+"#,
+            payload
+        )
+    };
+    assert_eq!(
+        parse_and_execute(&emit("to: \"bob\", amount: 5")).unwrap(),
+        Value::String("sent".to_string())
+    );
+    let missing = parse_and_execute(&emit("to: \"bob\"")).unwrap_err();
+    assert!(
+        missing.contains("Token: event 'Transfer' is missing field 'amount'"),
+        "{}",
+        missing
+    );
+    let wrong_type = parse_and_execute(&emit("to: \"bob\", amount: \"5\"")).unwrap_err();
+    assert!(
+        wrong_type.contains("field 'amount' expects int, got string"),
+        "{}",
+        wrong_type
+    );
+    let extra = parse_and_execute(&emit("to: \"bob\", amount: 5, memo: 1")).unwrap_err();
+    assert!(extra.contains("has no field 'memo'"), "{}", extra);
+}
+
+#[test]
+fn test_subscriber_declaration_validates_incoming_events() {
+    let source = r#"
+service Audit {
+    event Alert(level: int);
+
+    @on("Alert")
+    fn handle(level) { return level; }
+}
+event Alert { level: "high" };
+"#;
+    let err = parse_and_execute(source).unwrap_err();
+    assert!(
+        err.contains("Audit: event 'Alert' field 'level' expects int, got string"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_recursive_events_are_bounded() {
+    let source = r#"
+service Echo {
+    @on("Loop")
+    fn again() {
+        event Loop { };
+    }
+}
+event Loop { };
+"#;
+    let err = parse_and_execute(source).unwrap_err();
+    assert!(err.contains("maximum nested delivery depth"), "{}", err);
+}
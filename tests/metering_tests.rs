@@ -0,0 +1,264 @@
+// Metered execution tests
+// A budgeted runtime counts evaluation steps, allocated collection items and call depth, and
+// fails with a BudgetExhausted error reporting what was consumed once a limit is passed.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::functions::RuntimeError;
+use dist_agent_lang::runtime::metering::{Budget, Usage};
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+use dist_agent_lang::stdlib::agent::ResourceBudget;
+
+fn execute_metered(source: &str, budget: Budget) -> (Result<Option<Value>, RuntimeError>, Usage) {
+    let program = parse_source(source).expect("parse");
+    let mut runtime = Runtime::new();
+    runtime.set_budget(Some(budget));
+    let result = runtime.execute_program(program, None).map_err(|e| e.inner);
+    (result, runtime.metering_usage().expect("metered runtime"))
+}
+
+fn steps(max: u64) -> Budget {
+    Budget {
+        max_steps: Some(max),
+        ..Budget::default()
+    }
+}
+
+#[test]
+fn test_step_budget_stops_an_endless_loop() {
+    let (result, usage) = execute_metered("let i = 0; while (true) { i = i + 1; }", steps(1000));
+    match result {
+        Err(RuntimeError::BudgetExhausted {
+            resource, limit, ..
+        }) => {
+            assert_eq!(resource, "steps");
+            assert_eq!(limit, 1000);
+        }
+        other => panic!("expected BudgetExhausted, got {:?}", other),
+    }
+    assert_eq!(usage.steps, 1001);
+    let message = execute_metered("while (true) { }", steps(50))
+        .0
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.contains("steps limit of 50 reached (51 steps"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_metered_runs_are_deterministic() {
+    let source = r#"
+fn fib(n) {
+    if (n < 2) { return n; }
+    return fib(n - 1) + fib(n - 2);
+}
+let xs = [];
+for i in 0..10 {
+    xs.push(fib(i));
+}
+xs
+"#;
+    let (first, first_usage) = execute_metered(source, steps(1_000_000));
+    let (second, second_usage) = execute_metered(source, steps(1_000_000));
+    assert_eq!(first.unwrap(), second.unwrap());
+    assert_eq!(first_usage, second_usage);
+    assert!(first_usage.steps > 0);
+    assert_eq!(first_usage.allocated_items, second_usage.allocated_items);
+
+    let (_, small) = execute_metered(source, steps(first_usage.steps - 1));
+    assert_eq!(small.steps, first_usage.steps);
+}
+
+#[test]
+fn test_allocation_budget_counts_collection_growth() {
+    let budget = Budget {
+        max_allocated_items: Some(100),
+        ..Budget::default()
+    };
+    let (result, usage) = execute_metered("let xs = [1, 2, 3]; xs.length()", budget);
+    assert_eq!(result.unwrap(), Some(Value::Int(3)));
+    assert!(usage.allocated_items >= 3, "{}", usage);
+
+    let source = "let xs = []; while (true) { xs.push(0); }";
+    match execute_metered(source, budget).0 {
+        Err(RuntimeError::BudgetExhausted { resource, .. }) => {
+            assert_eq!(resource, "allocated items")
+        }
+        other => panic!("expected BudgetExhausted, got {:?}", other),
+    }
+    let (result, _) = execute_metered("let r = 0..1000; 1", budget);
+    assert!(matches!(
+        result,
+        Err(RuntimeError::BudgetExhausted {
+            resource: "allocated items",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_call_depth_budget_stops_deep_recursion() {
+    let source = r#"
+fn down(n) { return down(n + 1); }
+down(0)
+"#;
+    let budget = Budget {
+        max_call_depth: Some(10),
+        ..Budget::default()
+    };
+    let (result, usage) = execute_metered(source, budget);
+    assert!(matches!(
+        result,
+        Err(RuntimeError::BudgetExhausted {
+            resource: "call depth",
+            limit: 10,
+            ..
+        })
+    ));
+    assert_eq!(usage.call_depth, 11);
+}
+
+#[test]
+fn test_exhausted_budget_is_not_recovered_by_catch() {
+    let source = r#"
+let caught = "";
+try {
+    while (true) { }
+} catch (e: BudgetError) {
+    caught = "budget";
+}
+caught
+"#;
+    // Like running out of gas: the catch block would need steps the budget no longer has.
+    let program = parse_source(source).unwrap();
+    let mut runtime = Runtime::new();
+    runtime.set_budget(Some(steps(200)));
+    let result = runtime.execute_program(program, None).map_err(|e| e.inner);
+    assert!(
+        matches!(result, Err(RuntimeError::BudgetExhausted { .. })),
+        "{:?}",
+        result
+    );
+    assert!(runtime.metering_usage().unwrap().steps > 200);
+}
+
+#[test]
+fn test_unmetered_runtime_reports_no_usage() {
+    let runtime = Runtime::new();
+    assert!(runtime.metering_usage().is_none());
+}
+
+#[test]
+fn test_decentralized_service_methods_are_metered_by_default() {
+    let source = r#"
+@trust("decentralized")
+@chain("ethereum")
+service Spinner {
+    fn spin() {
+        while (true) { }
+    }
+}
+Spinner::spin()
+"#;
+    let err = parse_and_execute(source).unwrap_err();
+    assert!(err.contains("BudgetExhausted"), "{}", err);
+    assert!(err.contains("resource: \"steps\""), "{}", err);
+
+    let source = r#"
+@trust("hybrid")
+@chain("ethereum")
+service Counter {
+    fn count(n) {
+        let i = 0;
+        while (i < n) { i = i + 1; }
+        return i;
+    }
+}
+Counter::count(2000)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(2000));
+}
+
+#[test]
+fn test_service_without_trust_attribute_runs_unmetered() {
+    let source = r#"
+service Counter {
+    fn count(n) {
+        let i = 0;
+        while (i < n) { i = i + 1; }
+        return i;
+    }
+}
+Counter::count(300000)
+"#;
+    let mut runtime = Runtime::new();
+    let value = runtime
+        .execute_program(parse_source(source).unwrap(), None)
+        .unwrap();
+    assert_eq!(value, Some(Value::Int(300000)));
+    assert!(runtime.last_method_usage().is_none());
+}
+
+#[test]
+fn test_decentralized_method_usage_is_kept_after_the_call() {
+    let source = r#"
+@trust("decentralized")
+@chain("ethereum")
+service Counter {
+    fn count(n) {
+        let i = 0;
+        while (i < n) { i = i + 1; }
+        return i;
+    }
+}
+Counter::count(100)
+"#;
+    let mut runtime = Runtime::new();
+    let value = runtime
+        .execute_program(parse_source(source).unwrap(), None)
+        .unwrap();
+    assert_eq!(value, Some(Value::Int(100)));
+    assert!(runtime.metering_usage().is_none());
+    let usage = runtime.last_method_usage().expect("metered method");
+    assert!(usage.steps > 100, "{}", usage);
+
+    let mut runtime = Runtime::new();
+    assert_eq!(runtime.set_budget(Some(steps(1000))), None);
+    runtime
+        .execute_program(parse_source("let x = 1 + 2;").unwrap(), None)
+        .unwrap();
+    let replaced = runtime
+        .set_budget(None)
+        .expect("usage of the replaced budget");
+    assert!(replaced.steps > 0);
+    assert!(runtime.last_method_usage().is_none());
+}
+
+#[test]
+fn test_resource_budget_maps_onto_a_metering_budget() {
+    assert_eq!(ResourceBudget::default().metering_budget(), None);
+    let budget = ResourceBudget {
+        max_total_tokens: Some(1000),
+        ..ResourceBudget::default()
+    };
+    assert_eq!(budget.metering_budget(), None);
+    let budget = ResourceBudget {
+        max_eval_steps: Some(5000),
+        max_call_depth: Some(8),
+        ..ResourceBudget::default()
+    };
+    assert_eq!(
+        budget.metering_budget(),
+        Some(Budget {
+            max_steps: Some(5000),
+            max_allocated_items: None,
+            max_call_depth: Some(8),
+        })
+    );
+}
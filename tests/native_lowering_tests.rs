@@ -0,0 +1,413 @@
+// Native lowering tests
+// Differential harness: services are lowered to Rust with the native backend's codegen, built
+// with cargo, and every call's result is compared with the same call under the interpreter.
+
+mod common;
+
+use common::{execute_in, parse_and_execute};
+use dist_agent_lang::compile::rust_lower::services_to_rust;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::{ServiceStatement, Statement};
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Display form shared by both sides: the interpreter's, with map keys sorted.
+fn canonical(value: &Value) -> String {
+    match value {
+        Value::Map(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let entries: Vec<String> = keys
+                .iter()
+                .map(|k| format!("\"{}\": {}", k, canonical(&map[*k])))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::List(items) | Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Rust expression building `value` as a generated-crate `dal::Value`.
+fn rust_value(value: &Value) -> String {
+    match value {
+        Value::Null => "Value::Null".to_string(),
+        Value::Int(i) => format!("Value::Int({})", i),
+        Value::Float(x) => format!("Value::Float({:?})", x),
+        Value::Bool(b) => format!("Value::Bool({})", b),
+        Value::String(s) => format!("Value::Str({:?}.to_string())", s),
+        Value::List(items) | Value::Array(items) => format!(
+            "Value::List(vec![{}])",
+            items.iter().map(rust_value).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Map(map) => format!(
+            "dal::map(vec![{}])",
+            map.iter()
+                .map(|(k, v)| format!("({:?}, {})", k, rust_value(v)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => panic!("unsupported argument {:?}", other),
+    }
+}
+
+fn cargo_available() -> bool {
+    Command::new("cargo")
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Build `lib` plus a `main.rs` in a scratch crate and return its stdout.
+fn build_and_run(name: &str, lib: &str, main: &str) -> String {
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("dal_native_diff_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"dal_native\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[workspace]\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), lib).unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), main).unwrap();
+    let output = Command::new("cargo")
+        .current_dir(&dir)
+        .args(["run", "--quiet"])
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(
+        output.status.success(),
+        "generated crate failed to build or run:\n{}\n--- lib.rs ---\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        lib
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn services(program: &dist_agent_lang::parser::ast::Program) -> Vec<&ServiceStatement> {
+    program
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Service(service) => Some(service),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Run each `(service, method, args)` call in order under the interpreter and in the compiled
+/// crate, one service instance per side, and assert the results agree. Errors compare as
+/// `error` since the two sides word them differently.
+fn assert_same_results(name: &str, source: &str, calls: &[(&str, &str, &[&str])]) {
+    if !cargo_available() {
+        eprintln!("skipping {}: cargo not available", name);
+        return;
+    }
+    let mut runtime = Runtime::new();
+    execute_in(&mut runtime, &format!("{}\nnull", source)).unwrap();
+    let mut expected = Vec::new();
+    let mut main = String::from(
+        "use dal_native::dal::{self, Value};\n\nfn show(r: Result<Value, dal::DalError>) -> String {\n    match r {\n        Ok(v) => v.to_string(),\n        Err(_) => \"error\".to_string(),\n    }\n}\n\nfn main() {\n",
+    );
+    let mut declared = Vec::new();
+    for (service, method, args) in calls {
+        let call = format!("{}::{}({})", service, method, args.join(", "));
+        expected.push(match execute_in(&mut runtime, &call) {
+            Ok(value) => canonical(&value),
+            Err(_) => "error".to_string(),
+        });
+        let instance = format!("s_{}", service.to_lowercase());
+        if !declared.contains(&instance) {
+            main.push_str(&format!(
+                "    let mut {} = dal_native::{}::State::new().unwrap();\n",
+                instance,
+                service.to_lowercase()
+            ));
+            declared.push(instance.clone());
+        }
+        let mut arg_values = Vec::new();
+        for arg in args.iter() {
+            let value = parse_and_execute(arg).unwrap();
+            arg_values.push(rust_value(&value));
+        }
+        main.push_str(&format!(
+            "    println!(\"{{}}\", show({}.call({:?}, vec![{}])));\n",
+            instance,
+            method,
+            arg_values.join(", ")
+        ));
+    }
+    main.push_str("}\n");
+
+    let program = parse_source(source).unwrap();
+    let lib = services_to_rust(&program, &services(&program), "// differential test\n");
+    let stdout = build_and_run(name, &lib, &main);
+    let actual: Vec<&str> = stdout.lines().collect();
+    for (i, ((service, method, args), expected)) in calls.iter().zip(&expected).enumerate() {
+        assert_eq!(
+            actual.get(i).copied(),
+            Some(expected.as_str()),
+            "call #{} {}::{}({}) differs (interpreter: {})",
+            i,
+            service,
+            method,
+            args.join(", "),
+            expected
+        );
+    }
+    assert_eq!(actual.len(), calls.len());
+}
+
+#[test]
+fn test_counter_state_and_control_flow() {
+    let source = r#"
+@native
+service Counter @compile_target("native") {
+    count: int = 0;
+    label: string = "n";
+
+    fn bump(by: int) -> int {
+        self.count = self.count + by;
+        return self.count;
+    }
+
+    fn reset() { self.count = 0; }
+
+    fn sum_to(n) {
+        let total = 0;
+        let i = 0;
+        while (i < n) {
+            i = i + 1;
+            if (i % 2 == 0) { continue; }
+            total = total + i;
+        }
+        total
+    }
+
+    fn first_over(limit_value, xs) {
+        for x in xs {
+            if (x > limit_value) { return x; }
+        }
+        return -1;
+    }
+
+    fn search(n) {
+        loop {
+            n = n + 3;
+            if (n > 10) { break n * 2; }
+        }
+    }
+
+    fn classify(x) {
+        if (x < 0) { "neg" } else { if (x == 0) { "zero" } else { "pos" } }
+    }
+
+    fn scopes(flag) {
+        let v = 1;
+        if (flag) { let v = 2; }
+        for k in 0..3 { let w = k; }
+        v + w + k
+    }
+
+    fn describe_count() { self.label + "=" + self.count + ";" + self.double() }
+
+    fn double() { self.count * 2 }
+}
+"#;
+    assert_same_results(
+        "counter",
+        source,
+        &[
+            ("Counter", "bump", &["5"]),
+            ("Counter", "bump", &["-2"]),
+            ("Counter", "describe_count", &[]),
+            ("Counter", "reset", &[]),
+            ("Counter", "bump", &["1"]),
+            ("Counter", "sum_to", &["10"]),
+            ("Counter", "first_over", &["3", "[1, 5, 9]"]),
+            ("Counter", "first_over", &["30", "[1, 5, 9]"]),
+            ("Counter", "search", &["1"]),
+            ("Counter", "classify", &["-4"]),
+            ("Counter", "classify", &["0"]),
+            ("Counter", "classify", &["8"]),
+            ("Counter", "scopes", &["true"]),
+            ("Counter", "scopes", &["false"]),
+        ],
+    );
+}
+
+#[test]
+fn test_arithmetic_strings_and_errors() {
+    let source = r#"
+@native
+service Calc @compile_target("native") {
+    fn mix(a, b) { a + b }
+    fn div(a, b) { a / b }
+    fn rem(a, b) { a % b }
+    fn neg(a) { -a }
+    fn logic(a, b) { [a && b, a || b, !a] }
+    fn cmp(a, b) { [a < b, a <= b, a == b, a != b] }
+    fn big() { 9223372036854775807 + 1 }
+}
+"#;
+    assert_same_results(
+        "calc",
+        source,
+        &[
+            ("Calc", "mix", &["\"a\"", "1.5"]),
+            ("Calc", "mix", &["2", "\"x\""]),
+            ("Calc", "mix", &["\"v\"", "[1, \"b\"]"]),
+            ("Calc", "mix", &["[1]", "[2, 3]"]),
+            ("Calc", "mix", &["1", "2.5"]),
+            ("Calc", "mix", &["true", "1"]),
+            ("Calc", "div", &["7", "2"]),
+            ("Calc", "div", &["7.0", "2"]),
+            ("Calc", "div", &["1", "0"]),
+            ("Calc", "rem", &["-7", "3"]),
+            ("Calc", "rem", &["7.5", "2"]),
+            ("Calc", "neg", &["4"]),
+            ("Calc", "neg", &["2.5"]),
+            ("Calc", "logic", &["0", "\"x\""]),
+            ("Calc", "logic", &["5", "0"]),
+            ("Calc", "logic", &["\"s\"", "0"]),
+            ("Calc", "cmp", &["1", "1.0"]),
+            ("Calc", "cmp", &["\"a\"", "\"b\""]),
+            ("Calc", "cmp", &["[1]", "2"]),
+            ("Calc", "big", &[]),
+        ],
+    );
+}
+
+#[test]
+fn test_lists_maps_and_functions() {
+    let source = r#"
+fn fib(n) {
+    if (n < 2) { return n; }
+    return fib(n - 1) + fib(n - 2);
+}
+
+fn tally(xs) {
+    let counts = {};
+    for x in xs {
+        let key = "k" + x;
+        if (counts.contains_key(key)) { counts[key] = counts[key] + 1; } else { counts[key] = 1; }
+    }
+    counts
+}
+
+@native
+service Inventory @compile_target("native") {
+    items: list<string> = [];
+    stock: map<string, int> = {};
+    meta: map<string, any> = { "owner": "ada", "tags": ["a", "b"] };
+
+    fn add(item: string, qty: int) -> int {
+        self.items.push(item);
+        self.stock[item] = qty;
+        self.items.length()
+    }
+
+    fn qty(item) { self.stock[item] }
+    fn first() { self.items[0] }
+    fn missing() { self.items[10] }
+    fn snapshot() { return { "items": self.items, "stock": self.stock, "n": self.stock.size() }; }
+    fn owner() { self.meta.owner + ":" + self.meta["tags"][1] }
+    fn grow() {
+        let xs = [1];
+        xs[3] = 4;
+        xs
+    }
+    fn fib(n) { fib(n) }
+    fn piped(n) { n + 1 |> fib }
+    fn tally(xs) { tally(xs) }
+    fn note() { [self.meta?.note?.text ?? "none", self.meta?.owner ?? "none"] }
+    fn removed(item) {
+        let r = self.stock.remove(item);
+        [r, self.stock.size()]
+    }
+}
+"#;
+    assert_same_results(
+        "inventory",
+        source,
+        &[
+            ("Inventory", "add", &["\"bolt\"", "4"]),
+            ("Inventory", "add", &["\"nut\"", "9"]),
+            ("Inventory", "qty", &["\"nut\""]),
+            ("Inventory", "qty", &["\"gear\""]),
+            ("Inventory", "first", &[]),
+            ("Inventory", "missing", &[]),
+            ("Inventory", "snapshot", &[]),
+            ("Inventory", "owner", &[]),
+            ("Inventory", "grow", &[]),
+            ("Inventory", "fib", &["15"]),
+            ("Inventory", "piped", &["9"]),
+            ("Inventory", "note", &[]),
+            ("Inventory", "tally", &["[1, 2, 1, 3, 1]"]),
+            ("Inventory", "removed", &["\"bolt\""]),
+            ("Inventory", "snapshot", &[]),
+        ],
+    );
+}
+
+#[test]
+fn test_typed_wrappers_and_unlowered_constructs() {
+    if !cargo_available() {
+        return;
+    }
+    let source = r#"
+@native
+service Wallet @compile_target("native") {
+    balance: int = 10;
+    owner: string = "ada";
+
+    fn deposit(amount: int) -> int {
+        self.balance = self.balance + amount;
+        self.balance
+    }
+
+    fn summary() -> map<string, any> { return { "owner": self.owner, "balance": self.balance }; }
+
+    fn stamp() {
+        let twice = x => x * 2;
+        twice(self.balance)
+    }
+
+    fn type() -> string { "wallet" }
+}
+"#;
+    let program = parse_source(source).unwrap();
+    let lib = services_to_rust(&program, &services(&program), "// typed test\n");
+    assert!(
+        lib.contains("// Not lowered: a closure in Wallet::stamp"),
+        "{}",
+        lib
+    );
+    let main = r#"
+fn main() {
+    let mut w = dal_native::wallet::State::new().unwrap();
+    let balance: i64 = w.deposit(5).unwrap();
+    let summary: std::collections::HashMap<String, dal_native::dal::Value> = w.summary().unwrap();
+    println!("{} {} {}", balance, summary["owner"], w.r#type().unwrap());
+    println!("{}", w.stamp().unwrap_err());
+    println!("{}", w.call("deposit", vec![]).unwrap_err());
+}
+"#;
+    let stdout = build_and_run("typed", &lib, main);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "15 \"ada\" wallet");
+    assert!(
+        lines[1].contains("not lowered by the Rust backend"),
+        "{}",
+        lines[1]
+    );
+    assert_eq!(lines[2], "Argument count mismatch: expected 1, got 0");
+}
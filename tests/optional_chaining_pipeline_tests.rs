@@ -0,0 +1,177 @@
+// Optional chaining, null-coalescing and pipeline tests
+// `a?.b`, `a?.m()`, `x ?? default` and `value |> f |> g(a)`: lexing next to the postfix `?`,
+// precedence, short-circuiting and the static type checker.
+
+mod common;
+
+use common::{parse_and_execute as eval, text};
+use dist_agent_lang::lexer::tokens::{Operator, Punctuation, Token};
+use dist_agent_lang::lexer::Lexer;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::check_program;
+use dist_agent_lang::runtime::values::Value;
+
+#[test]
+fn test_lexer_splits_question_operators() {
+    let tokens = Lexer::new("a?.b ?? c? |> f").tokenize().unwrap();
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Operator(Operator::OptionalChain),
+            Token::Identifier("b".to_string()),
+            Token::Operator(Operator::NullCoalesce),
+            Token::Identifier("c".to_string()),
+            Token::Punctuation(Punctuation::Question),
+            Token::Operator(Operator::Pipeline),
+            Token::Identifier("f".to_string()),
+            Token::EOF,
+        ]
+    );
+}
+
+#[test]
+fn test_optional_chaining_over_nested_maps() {
+    let source = r#"
+        let response = {"data": {"user": {"name": "ada"}}};
+        let empty = {"data": null};
+        [response?.data?.user?.name, empty?.data?.user?.name, response?.meta?.count, null?.x]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![text("ada"), Value::Null, Value::Null, Value::Null])
+    );
+    // A plain `.` after a present value still reports a missing key.
+    let err = eval(r#"let r = {"data": {}}; r?.data.user"#).unwrap_err();
+    assert!(err.contains("not found"), "{}", err);
+}
+
+#[test]
+fn test_optional_method_call_skips_arguments() {
+    let source = r#"
+        let calls = 0;
+        fn count() { calls = calls + 1; return 1; }
+        let items = [1, 2];
+        let missing = null;
+        [items?.length(), missing?.push(count()), Some([1, 2, 3])?.length(), calls]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![
+            Value::Int(2),
+            Value::Null,
+            Value::Int(3),
+            Value::Int(0)
+        ])
+    );
+}
+
+#[test]
+fn test_optional_chaining_propagates_err_and_none_receivers() {
+    // `r?.name` on a Result or Option is `(r?).name`.
+    let source = r#"
+        fn name(r) { return r?.name; }
+        fn size(o) { return o?.length(); }
+        [name(Ok({"name": "ada"})), size(Some([1, 2, 3]))]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![text("ada"), Value::Int(3)])
+    );
+    assert_eq!(
+        eval(r#"fn f(r) { return r?.name; } f(Err("bad"))"#).unwrap(),
+        Value::err(text("bad"))
+    );
+    assert_eq!(
+        eval("fn f(o) { let n = o?.length(); return n + 1; } f(None)").unwrap(),
+        Value::none()
+    );
+}
+
+#[test]
+fn test_null_coalescing() {
+    assert_eq!(eval("null ?? 5").unwrap(), Value::Int(5));
+    assert_eq!(eval("None ?? \"d\"").unwrap(), text("d"));
+    // Only null / None fall back; other falsy values are kept.
+    assert_eq!(eval("0 ?? 5").unwrap(), Value::Int(0));
+    assert_eq!(eval("false ?? true").unwrap(), Value::Bool(false));
+    assert_eq!(eval("null ?? null ?? 3").unwrap(), Value::Int(3));
+    // Binds looser than `||` and arithmetic.
+    assert_eq!(eval("null ?? 1 + 2").unwrap(), Value::Int(3));
+    let source = r#"
+        let hit = false;
+        fn fallback() { hit = true; return 0; }
+        let v = 7 ?? fallback();
+        [v, hit]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![Value::Int(7), Value::Bool(false)])
+    );
+    let source = r#"
+        let config = {"retries": 0};
+        [config?.retries ?? 3, config?.timeout ?? 30]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![Value::Int(0), Value::Int(30)])
+    );
+}
+
+#[test]
+fn test_pipeline_calls_each_stage() {
+    let source = r#"
+        fn double(x) { return x * 2; }
+        fn add(x, n) { return x + n; }
+        3 |> double |> add(10) |> double
+    "#;
+    assert_eq!(eval(source).unwrap(), Value::Int(32));
+    // Closures, function values and builtins.
+    let source = r#"
+        let inc = (x) => x + 1;
+        let stages = [inc];
+        [1 |> inc, 1 |> stages[0], 2 |> ((x) => x * x), len("abc") |> to_string]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![Value::Int(2), Value::Int(2), Value::Int(4), text("3")])
+    );
+    // The whole left side is piped: `1 + 2 |> f` is `f(3)`.
+    assert_eq!(
+        eval("fn neg(x) { return 0 - x; } 1 + 2 |> neg").unwrap(),
+        Value::Int(-3)
+    );
+}
+
+#[test]
+fn test_pipeline_with_optional_chaining() {
+    let source = r#"
+        fn names(users) {
+            let out = "";
+            for u in users { out = out + (u?.name ?? "anon") + ";"; }
+            return out;
+        }
+        fn shout(s) { return s + "!"; }
+        let response = {"users": [{"name": "ada"}, {}, null]};
+        [response?.users ?? [] |> names |> shout, response?.admins ?? [] |> names |> shout]
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::Array(vec![text("ada;anon;anon;!"), text("!")])
+    );
+}
+
+#[test]
+fn test_type_checker_checks_pipeline_calls() {
+    let program = parse_source(
+        r#"
+        fn add(x: int, n: int) -> int { return x + n; }
+        let ok = 1 |> add(2);
+        let bad = "a" |> add(2);
+        let short = 1 |> add;
+    "#,
+    )
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+}
@@ -0,0 +1,160 @@
+// Parser recovery tests
+// `Parser::parse_with_recovery` reports every syntax error in a file and still returns the
+// statements, functions and services around them; `parse` keeps stopping at the first error.
+
+use dist_agent_lang::parser::ast::Statement;
+use dist_agent_lang::parser::error::ParserError;
+use dist_agent_lang::{parse_source, Lexer, Parser};
+
+fn parse_recovering(source: &str) -> (dist_agent_lang::parser::ast::Program, Vec<ParserError>) {
+    let tokens = Lexer::new(source)
+        .tokenize_with_positions_immutable()
+        .unwrap();
+    Parser::new_with_positions(tokens).parse_with_recovery()
+}
+
+fn error_lines(errors: &[ParserError]) -> Vec<usize> {
+    errors.iter().filter_map(|e| e.line_number()).collect()
+}
+
+#[test]
+fn test_one_error_per_broken_statement() {
+    let source = "let a = ;\nlet b = 2;\nlet c = * 3;\nlet d = 4;";
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(error_lines(&errors), vec![1, 3], "{:?}", errors);
+    let names: Vec<&str> = program
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Let(l) => Some(l.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["b", "d"]);
+}
+
+#[test]
+fn test_errors_inside_function_bodies_keep_the_function() {
+    let source = r#"
+fn first(x) {
+    let a = x +;
+    let ok = 1;
+    if (x > 1) {
+        let b = (;
+    } else {
+        return ok;
+    }
+    return x;
+}
+
+fn second() {
+    let c = ];
+}
+
+fn third() { return 3; }
+"#;
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(error_lines(&errors), vec![3, 6, 14], "{:?}", errors);
+    let functions: Vec<(&str, usize)> = program
+        .statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Function(f) => Some((f.name.as_str(), f.body.statements.len())),
+            _ => None,
+        })
+        .collect();
+    // `first` keeps `let ok`, the `if` and the `return`.
+    assert_eq!(functions, vec![("first", 3), ("second", 0), ("third", 1)]);
+}
+
+#[test]
+fn test_errors_inside_services_keep_the_service() {
+    let source = r#"
+service Vault {
+    balance: int = ;
+    owner: string = "me";
+    fn deposit(amount) {
+        balance = balance + ;
+    }
+    fn withdraw(amount { }
+    fn total() { return balance; }
+}
+let after = 1;
+"#;
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(error_lines(&errors), vec![3, 6, 8], "{:?}", errors);
+    let Statement::Service(service) = &program.statements[0] else {
+        panic!("expected a service, got {:?}", program.statements[0]);
+    };
+    let fields: Vec<&str> = service.fields.iter().map(|f| f.name.as_str()).collect();
+    let methods: Vec<&str> = service.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(fields, vec!["owner"]);
+    assert_eq!(methods, vec!["deposit", "total"]);
+    assert!(matches!(&program.statements[1], Statement::Let(l) if l.name == "after"));
+}
+
+#[test]
+fn test_broken_header_skips_its_block() {
+    // The body of a function with a bad parameter list is skipped as a whole.
+    let source = "fn bad(a b) {\n    let x = 1;\n}\nlet y = 2;";
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(program.statements.len(), 1);
+    assert!(matches!(&program.statements[0], Statement::Let(l) if l.name == "y"));
+}
+
+#[test]
+fn test_skipping_starts_at_the_offending_token() {
+    // A stray `@` is not taken for the start of the next statement, and a bad entry inside a
+    // map literal does not end the enclosing function body.
+    let source = r#"
+let @ broken;
+fn f() {
+    let m = {"a": 1, "b" 2};
+    return m;
+}
+let after = 1;
+"#;
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(error_lines(&errors), vec![2, 4], "{:?}", errors);
+    assert_eq!(program.statements.len(), 2);
+    let Statement::Function(f) = &program.statements[0] else {
+        panic!("expected a function, got {:?}", program.statements[0]);
+    };
+    assert_eq!(f.body.statements.len(), 1);
+}
+
+#[test]
+fn test_unclosed_block_reports_eof() {
+    let (program, errors) = parse_recovering("fn f() {\n    let a = ;\n    let b = 1;\n");
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[0].line_number(), Some(2));
+    assert!(matches!(errors[1], ParserError::UnexpectedEOF { .. }));
+    assert!(program.statements.is_empty());
+}
+
+#[test]
+fn test_parse_still_stops_at_first_error() {
+    let err = parse_source("fn f() {\n    let a = ;\n    let b = ;\n}").unwrap_err();
+    let err = err.downcast::<ParserError>().unwrap();
+    assert_eq!(err.line_number(), Some(2));
+}
+
+#[test]
+fn test_ide_diagnostics_and_symbols_use_recovery() {
+    use dist_agent_lang::ide::diagnostics::{completion_at_position, diagnostics_from_source};
+    use dist_agent_lang::ide::symbols::document_symbols_from_source;
+
+    let source = "fn greet(name) {\n    let a = ;\n}\nlet b = ];\nfn farewell() { return 1; }";
+    let diags = diagnostics_from_source(source);
+    let lines: Vec<u32> = diags.iter().map(|d| d.line).collect();
+    assert_eq!(lines, vec![2, 4], "{:?}", diags);
+
+    let symbols: Vec<String> = document_symbols_from_source(source)
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(symbols, vec!["greet", "farewell"]);
+    let completions = completion_at_position(source, 4, 3);
+    assert!(completions.iter().any(|c| c.label == "farewell"));
+}
@@ -0,0 +1,210 @@
+// Pattern matching and destructuring tests
+// Tests for list, map/struct and Result/Option patterns, match guards, and `let` destructuring.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::{MatchPattern, Statement};
+use dist_agent_lang::runtime::check_program;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+
+#[test]
+fn test_parse_patterns_and_guard() {
+    let program = parse_source(
+        r#"
+match x {
+    [head, ..rest] if head > 0 => { 1 },
+    { status: "ok", data } => { 2 },
+    Ok(v) => { 3 },
+    None => { 4 }
+}
+"#,
+    )
+    .unwrap();
+    let Statement::Match(m) = &program.statements[0] else {
+        panic!("expected match, got {:?}", program.statements[0]);
+    };
+    let MatchPattern::List { elements, rest } = &m.cases[0].pattern else {
+        panic!("expected list pattern, got {:?}", m.cases[0].pattern);
+    };
+    assert_eq!(elements.len(), 1);
+    assert_eq!(rest.as_deref(), Some("rest"));
+    assert!(m.cases[0].guard.is_some());
+
+    let MatchPattern::Map {
+        struct_name,
+        fields,
+    } = &m.cases[1].pattern
+    else {
+        panic!("expected map pattern, got {:?}", m.cases[1].pattern);
+    };
+    assert!(struct_name.is_none());
+    assert_eq!(fields[0].0, "status");
+    assert!(matches!(fields[1].1, MatchPattern::Identifier(ref n) if n == "data"));
+
+    assert!(matches!(m.cases[2].pattern, MatchPattern::Ok(_)));
+    assert!(matches!(m.cases[3].pattern, MatchPattern::None));
+    assert_eq!(m.cases[1].pattern.bound_names(), vec!["data".to_string()]);
+}
+
+#[test]
+fn test_list_patterns() {
+    let source = r#"
+fn shape_of(xs) {
+    match xs {
+        [] => { return "empty"; },
+        [only] => { return "one:" + only; },
+        [first, second] => { return "two:" + first + second; },
+        [head, ..rest] => { return "many:" + head + "+" + rest.length(); }
+    }
+}
+shape_of([]) + " " + shape_of(["a"]) + " " + shape_of(["a", "b"]) + " " + shape_of(["a", "b", "c"])
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("empty one:a two:ab many:a+2".to_string())
+    );
+}
+
+#[test]
+fn test_map_patterns_unpack_tool_results() {
+    let source = r#"
+fn handle(result) {
+    match result {
+        { status: "ok", data: { items: [first, ..] } } => { return first; },
+        { status: "ok", data } => { return data; },
+        { status: "error", message } => { return "failed: " + message; },
+        _ => { return "unknown"; }
+    }
+}
+let a = handle({"status": "ok", "data": {"items": ["x", "y"]}});
+let b = handle({"status": "ok", "data": "plain"});
+let c = handle({"status": "error", "message": "timeout"});
+let d = handle({"code": 500});
+a + "," + b + "," + c + "," + d
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("x,plain,failed: timeout,unknown".to_string())
+    );
+}
+
+#[test]
+fn test_struct_patterns() {
+    let source = r#"
+struct Point { x: int, y: int }
+let p = Point { x: 3, y: 4 };
+let r = 0;
+match p {
+    Point { x: 0, y } => { r = y; },
+    Point { x, y } => { r = x * 10 + y; }
+}
+r
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(34));
+}
+
+#[test]
+fn test_result_and_option_patterns() {
+    let source = r#"
+fn unwrap_or(value, fallback) {
+    match value {
+        Ok(v) => { return v; },
+        Err(e) => { return fallback + e; },
+        Some(x) => { return x; },
+        None => { return fallback; }
+    }
+}
+unwrap_or(Ok(1), 0) + unwrap_or(Err(10), 100) + unwrap_or(Some(1000), 0) + unwrap_or(None, 10000)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(11111));
+}
+
+#[test]
+fn test_guards_fall_through_to_next_case() {
+    let source = r#"
+fn classify(n) {
+    match n {
+        x if x > 10 => { return "big"; },
+        x if x < 0 => { return "negative"; },
+        0 => { return "zero"; },
+        default => { return "small"; }
+    }
+}
+classify(42) + " " + classify(-1) + " " + classify(0) + " " + classify(5)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("big negative zero small".to_string())
+    );
+}
+
+#[test]
+fn test_guard_bindings_do_not_leak() {
+    let source = r#"
+let x = "outer";
+let hit = "";
+match 3 {
+    x if x > 5 => { hit = "first"; },
+    y => { hit = "second"; }
+}
+x + ":" + hit
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        Value::String("outer:second".to_string())
+    );
+}
+
+#[test]
+fn test_let_destructuring() {
+    let source = r#"
+let { a, b } = {"a": 1, "b": 2, "c": 3};
+let [first, ..rest] = [10, 20, 30];
+let { data: { score } } = {"data": {"score": 7}};
+a + b + first + rest[1] + score
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(50));
+}
+
+#[test]
+fn test_let_destructuring_mismatch_is_an_error() {
+    let err = parse_and_execute("let { missing } = {\"a\": 1}; missing").unwrap_err();
+    assert!(
+        err.contains("Cannot destructure a value of type 'map'"),
+        "{}",
+        err
+    );
+
+    let err = parse_and_execute("let [a, b] = [1]; a").unwrap_err();
+    assert!(err.contains("does not match the pattern"), "{}", err);
+}
+
+#[test]
+fn test_tree_walker_agrees_on_destructuring() {
+    let source = "let total = 0; for pair in [[1, 2], [3, 4]] { let [l, r] = pair; total = total + l * r; } total";
+    for bytecode_vm in [true, false] {
+        let program = parse_source(source).unwrap();
+        let mut runtime = Runtime::new();
+        runtime.set_bytecode_vm(bytecode_vm);
+        let result = runtime.execute_program(program, None).unwrap();
+        assert_eq!(result, Some(Value::Int(14)));
+    }
+}
+
+#[test]
+fn test_check_accepts_patterns() {
+    let program = parse_source(
+        r#"
+let { a, b } = {"a": 1, "b": 2};
+match [a, b] {
+    [x, ..rest] if x > 0 => { x + rest.length(); },
+    _ => { 0; }
+}
+"#,
+    )
+    .unwrap();
+    assert!(check_program(&program).is_empty());
+}
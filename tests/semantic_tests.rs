@@ -0,0 +1,160 @@
+// Stdlib signature registry, semantic tokens and inlay hints tests
+// The registry generated from the engine dispatch, and the classification and hints that
+// `ide::semantic` computes for the language server.
+
+use dist_agent_lang::ide::semantic::{
+    inlay_hints, semantic_tokens, InlayHintKind, SemanticKind, SemanticToken,
+};
+use dist_agent_lang::ide::workspace::WorkspaceIndex;
+use dist_agent_lang::stdlib::signatures;
+use std::path::Path;
+
+const SOURCE: &str = r#"import stdlib::chain as ch;
+
+@secure
+@txn("serializable", 5000)
+service Vault {
+    balance: int = 0;
+
+    fn deposit(amount) {
+        balance = balance + amount;
+        return self.balance;
+    }
+}
+
+fn check(id, who) {
+    let b = chain::get_balance(id, who);
+    return ch::get_gas_price(id);
+}
+
+check(1, "0xabc");
+agent Bot: ai { name: "x" }
+let v = Vault::new();
+v.deposit(5);
+"#;
+
+fn index() -> (WorkspaceIndex, &'static Path) {
+    let mut index = WorkspaceIndex::new();
+    let path = Path::new("untitled:semantic.dal");
+    index.update_file(path, SOURCE);
+    (index, path)
+}
+
+fn token_at(tokens: &[SemanticToken], line: u32, column: u32) -> &SemanticToken {
+    tokens
+        .iter()
+        .find(|t| (t.line, t.column) == (line, column))
+        .unwrap_or_else(|| panic!("no token at {}:{}", line, column))
+}
+
+#[test]
+fn test_registry_is_generated_from_engine_dispatch() {
+    let gas = signatures::lookup("chain", "estimate_gas").unwrap();
+    assert_eq!(
+        gas.signature(),
+        "chain::estimate_gas(chain_id: int, operation: string) -> int"
+    );
+    assert_eq!(gas.snippet(), "estimate_gas(${1:chain_id}, ${2:operation})");
+    assert_eq!(gas.min_args(), 2);
+    assert!(signatures::lookup("time", "unix_ms_now")
+        .unwrap()
+        .params
+        .is_empty());
+    // Aliased namespaces share one handler.
+    assert!(signatures::is_namespace("ai") && signatures::is_namespace("assist"));
+    assert_eq!(
+        signatures::functions("ai").count(),
+        signatures::functions("assist").count()
+    );
+    assert!(signatures::lookup("chain", "no_such_function").is_none());
+
+    let namespaces = signatures::namespaces();
+    assert!(namespaces.len() >= 35, "{:?}", namespaces);
+    assert!(namespaces.windows(2).all(|w| w[0] < w[1]));
+    let json: serde_json::Value = serde_json::from_str(&signatures::to_json()).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), signatures::all().len());
+}
+
+#[test]
+fn test_semantic_tokens_classify_declarations_and_stdlib() {
+    let (index, path) = index();
+    let tokens = semantic_tokens(&index, path, SOURCE);
+
+    assert_eq!(token_at(&tokens, 3, 1).kind, SemanticKind::Attribute);
+    assert_eq!(token_at(&tokens, 4, 1).kind, SemanticKind::Attribute);
+    let service = token_at(&tokens, 5, 9);
+    assert_eq!(
+        (service.kind, service.declaration),
+        (SemanticKind::Service, true)
+    );
+    assert_eq!(token_at(&tokens, 21, 9).kind, SemanticKind::Service);
+    let agent = token_at(&tokens, 20, 7);
+    assert_eq!((agent.kind, agent.declaration), (SemanticKind::Agent, true));
+    assert_eq!(token_at(&tokens, 8, 8).kind, SemanticKind::Method);
+    assert_eq!(token_at(&tokens, 8, 16).kind, SemanticKind::Parameter);
+    assert_eq!(token_at(&tokens, 9, 9).kind, SemanticKind::Property);
+
+    // `chain::get_balance` and the `ch` alias are stdlib.
+    for (line, column, kind) in [
+        (15, 13, SemanticKind::Namespace),
+        (15, 20, SemanticKind::Function),
+        (16, 12, SemanticKind::Namespace),
+        (16, 16, SemanticKind::Function),
+    ] {
+        let token = token_at(&tokens, line, column);
+        assert_eq!((token.kind, token.default_library), (kind, true));
+    }
+    // A user function is not.
+    let check = token_at(&tokens, 19, 1);
+    assert_eq!(
+        (check.kind, check.default_library),
+        (SemanticKind::Function, false)
+    );
+    assert!(tokens
+        .windows(2)
+        .all(|w| (w[0].line, w[0].column) < (w[1].line, w[1].column)));
+}
+
+#[test]
+fn test_inlay_hints_for_parameters_types_and_attributes() {
+    let (index, path) = index();
+    let hints: Vec<(u32, u32, String, InlayHintKind)> = inlay_hints(&index, path, SOURCE)
+        .into_iter()
+        .map(|h| (h.line, h.column, h.label, h.kind))
+        .collect();
+    let has =
+        |line, column, label: &str, kind| hints.contains(&(line, column, label.to_string(), kind));
+
+    assert!(has(
+        3,
+        8,
+        "requires authentication, guarded against reentrancy",
+        InlayHintKind::Effect
+    ));
+    assert!(has(
+        4,
+        27,
+        "transaction (serializable, 5000 ms timeout), rolled back on error",
+        InlayHintKind::Effect
+    ));
+    // `amount` from `v.deposit(5)`; `id` and `who` from the call site and `get_balance`.
+    assert!(has(8, 22, ": int", InlayHintKind::Type));
+    assert!(has(14, 12, ": int", InlayHintKind::Type));
+    assert!(has(14, 17, ": string", InlayHintKind::Type));
+    assert!(has(15, 32, "chain_id:", InlayHintKind::Parameter));
+    assert!(has(15, 36, "address:", InlayHintKind::Parameter));
+    assert!(has(16, 30, "chain_id:", InlayHintKind::Parameter));
+
+    // Defaults, and no guess when call sites disagree.
+    let source = "@txn\nservice S {}\nfn f(x) { return x; }\nf(1);\nf(\"a\");\n";
+    let mut index = WorkspaceIndex::new();
+    index.update_file(path, source);
+    let labels: Vec<String> = inlay_hints(&index, path, source)
+        .into_iter()
+        .map(|h| h.label)
+        .collect();
+    assert_eq!(
+        labels,
+        vec!["transaction (read_committed, 30000 ms timeout), rolled back on error"]
+    );
+}
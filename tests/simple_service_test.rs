@@ -112,6 +112,10 @@ fn main() {
                 dist_agent_lang::parser::ast::Statement::Continue(_) => println!("     - Continue"),
                 dist_agent_lang::parser::ast::Statement::Loop(_) => println!("     - Loop"),
                 dist_agent_lang::parser::ast::Statement::Match(_) => println!("     - Match"),
+                dist_agent_lang::parser::ast::Statement::Struct(_) => println!("     - Struct"),
+                dist_agent_lang::parser::ast::Statement::Enum(_) => println!("     - Enum"),
+                dist_agent_lang::parser::ast::Statement::Trait(_) => println!("     - Trait"),
+                dist_agent_lang::parser::ast::Statement::Impl(_) => println!("     - Impl"),
                 dist_agent_lang::parser::ast::Statement::Import(_) => println!("     - Import"),
             }
         }
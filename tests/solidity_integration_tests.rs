@@ -55,7 +55,9 @@ fn test_event_parsing() {
 
 #[test]
 fn test_type_conversion() {
-    assert_eq!(add_sol::solidity_to_dal_type("uint256"), "int");
+    assert_eq!(add_sol::solidity_to_dal_type("uint256"), "u256");
+    assert_eq!(add_sol::solidity_to_dal_type("int256"), "i256");
+    assert_eq!(add_sol::solidity_to_dal_type("uint8"), "int");
     assert_eq!(add_sol::solidity_to_dal_type("address"), "string");
     assert_eq!(add_sol::solidity_to_dal_type("bool"), "bool");
     assert_eq!(add_sol::solidity_to_dal_type("string"), "string");
@@ -1953,7 +1953,7 @@ fn test_add_sol_parse_events() {
 
 #[test]
 fn test_add_sol_solidity_to_dal_type() {
-    assert_eq!(add_sol::solidity_to_dal_type("uint256"), "int");
+    assert_eq!(add_sol::solidity_to_dal_type("uint256"), "u256");
     assert_eq!(add_sol::solidity_to_dal_type("address"), "string");
     assert_eq!(add_sol::solidity_to_dal_type("bool"), "bool");
     assert_eq!(add_sol::solidity_to_dal_type("string"), "string");
@@ -0,0 +1,119 @@
+// String interpolation tests
+// `"... ${expr} ..."` interpolation and `"""` multi-line literals with dedent: lexing, parse
+// errors inside `${}`, evaluation and the static type checker.
+
+mod common;
+
+use common::{parse_and_execute as eval, text};
+use dist_agent_lang::lexer::tokens::{Literal, TemplatePart, Token};
+use dist_agent_lang::lexer::Lexer;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::check_program;
+
+#[test]
+fn test_lexer_emits_template_with_positioned_code() {
+    let tokens = Lexer::new("let s = \"hi\n${user.name}!\";")
+        .tokenize_with_positions_immutable()
+        .unwrap();
+    let Token::Template(parts) = &tokens[3].token else {
+        panic!("expected a template, got {:?}", tokens[3].token);
+    };
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts[0], TemplatePart::Text("hi\n".to_string()));
+    let TemplatePart::Code(code) = &parts[1] else {
+        panic!("expected code, got {:?}", parts[1]);
+    };
+    assert_eq!(code[0].token, Token::Identifier("user".to_string()));
+    assert_eq!(code[0].line, 2);
+    assert_eq!(parts[2], TemplatePart::Text("!".to_string()));
+
+    // No `${` (or an escaped one) stays a plain string literal.
+    let tokens = Lexer::new(r#""cost: $5 \${x}""#).tokenize().unwrap();
+    assert_eq!(
+        tokens[0],
+        Token::Literal(Literal::String("cost: $5 ${x}".to_string()))
+    );
+}
+
+#[test]
+fn test_interpolation_evaluates_expressions() {
+    let source = r#"
+        let user = {"name": "ada", "langs": ["rust", "dal"]};
+        let n = 2;
+        "Hello ${user.name}, you know ${n} languages: ${user.langs[0]} and ${user["langs"][1]}."
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        text("Hello ada, you know 2 languages: rust and dal.")
+    );
+    assert_eq!(
+        eval(r#"let xs = [1, 2]; "${xs} ${null} ${1.5} ${true} ${len(xs) * 10}""#).unwrap(),
+        text("[1, 2] null 1.5 true 20")
+    );
+    // Nested braces and string literals inside `${}`.
+    let source = r#"
+        fn pick(m) { return m.k; }
+        "${pick({"k": "v}"})} and ${"inner ${1 + 1}"}"
+    "#;
+    assert_eq!(eval(source).unwrap(), text("v} and inner 2"));
+}
+
+#[test]
+fn test_interpolation_in_prompts_and_functions() {
+    let source = r#"
+        fn prompt(task, context) {
+            return "Task: ${task}\nContext: ${context ?? "none"}";
+        }
+        prompt("summarize", null)
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        text("Task: summarize\nContext: none")
+    );
+}
+
+#[test]
+fn test_triple_quoted_literals_are_dedented() {
+    let source = "let name = \"ada\";\nlet p = \"\"\"\n    Dear ${name},\n\n      Thanks for the \"report\".\n    Bye\n    \"\"\";\np";
+    assert_eq!(
+        eval(source).unwrap(),
+        text("Dear ada,\n\n  Thanks for the \"report\".\nBye")
+    );
+    assert_eq!(eval(r#""""one line""""#).unwrap(), text("one line"));
+    assert_eq!(eval("\"\"\"\"\"\"").unwrap(), text(""));
+    // Escapes still apply, and the dedent ignores the code inside `${}`.
+    assert_eq!(
+        eval("\"\"\"\n  a\\tb\n  ${1 +\n      1}\n  \"\"\"").unwrap(),
+        text("a\tb\n2")
+    );
+}
+
+#[test]
+fn test_errors_inside_interpolation_report_source_positions() {
+    let err = format!(
+        "{:?}",
+        parse_source("let x = 1;\nlet s = \"a ${x +} b\";").unwrap_err()
+    );
+    assert!(err.contains("line: 2") || err.contains("line 2"), "{}", err);
+    assert!(parse_source(r#"let s = "${}";"#).is_err());
+    assert!(parse_source(r#"let s = "${1 2}";"#).is_err());
+    assert!(parse_source(r#"let s = "${1 + 1";"#).is_err());
+    let err = eval(r#""${missing_variable}""#).unwrap_err();
+    assert!(err.contains("missing_variable"), "{}", err);
+}
+
+#[test]
+fn test_type_checker_sees_interpolated_calls() {
+    let program = parse_source(
+        r#"
+        fn twice(n: int) -> int { return n * 2; }
+        fn label() -> string { return "${twice(2)}"; }
+        fn count() -> int { return "${twice(2)}"; }
+        let bad = "${twice("x")}";
+    "#,
+    )
+    .unwrap();
+    // `count` returns a string, and `twice("x")` passes one.
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+}
@@ -0,0 +1,231 @@
+// Struct and enum declaration tests
+// Tests for `struct` / `enum` declarations, struct literals, variant constructors and
+// enum variant patterns in `match`.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::{MatchPattern, Statement};
+use dist_agent_lang::runtime::check_program;
+use dist_agent_lang::runtime::types::TypeError;
+use dist_agent_lang::runtime::values::Value;
+
+#[test]
+fn test_parse_struct_and_enum_declarations() {
+    let program = parse_source(
+        r#"
+struct Point { x: int, y: int }
+enum Shape { Empty, Circle(float), Rect(float, float) }
+"#,
+    )
+    .unwrap();
+    let Statement::Struct(point) = &program.statements[0] else {
+        panic!("expected struct, got {:?}", program.statements[0]);
+    };
+    assert_eq!(point.name, "Point");
+    assert_eq!(point.fields.len(), 2);
+    assert_eq!(point.fields[1].field_type, "int");
+
+    let Statement::Enum(shape) = &program.statements[1] else {
+        panic!("expected enum, got {:?}", program.statements[1]);
+    };
+    let arity: Vec<usize> = shape.variants.iter().map(|v| v.fields.len()).collect();
+    assert_eq!(arity, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_duplicate_struct_field_is_a_parse_error() {
+    assert!(parse_source("struct P { x: int, x: int }").is_err());
+}
+
+#[test]
+fn test_struct_literal_and_field_access() {
+    let result = parse_and_execute(
+        r#"
+        struct Point { x: int, y: int }
+        let p = Point { x: 3, y: 4 };
+        p.x * p.y
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Int(12));
+}
+
+#[test]
+fn test_struct_literal_produces_struct_value() {
+    let result = parse_and_execute(
+        r#"
+        struct User { name: string, age: int }
+        let u = User { name: "ada", age: 36 };
+        u
+    "#,
+    )
+    .unwrap();
+    let Value::Struct(name, fields) = result else {
+        panic!("expected struct value");
+    };
+    assert_eq!(name, "User");
+    assert_eq!(fields.get("name"), Some(&Value::String("ada".to_string())));
+}
+
+#[test]
+fn test_struct_literal_missing_field_errors() {
+    let err = parse_and_execute(
+        r#"
+        struct Point { x: int, y: int }
+        Point { x: 1 }
+    "#,
+    )
+    .unwrap_err();
+    assert!(err.contains("Missing field 'y'"), "{}", err);
+}
+
+#[test]
+fn test_struct_literal_unknown_field_errors() {
+    let err = parse_and_execute(
+        r#"
+        struct Point { x: int, y: int }
+        Point { x: 1, y: 2, z: 3 }
+    "#,
+    )
+    .unwrap_err();
+    assert!(err.contains("no field 'z'"), "{}", err);
+}
+
+#[test]
+fn test_enum_variant_match_binds_payload() {
+    let result = parse_and_execute(
+        r#"
+        enum Shape { Empty, Circle(int), Rect(int, int) }
+        let total = 0;
+        for s in [Shape::Rect(2, 5), Shape::Circle(1), Shape::Empty] {
+            match s {
+                Shape::Empty => { total = total + 100; },
+                Shape::Circle(r) => { total = total + 3 * r * r; },
+                Shape::Rect(w, h) => { total = total + w * h; }
+            }
+        }
+        total
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Int(113));
+}
+
+#[test]
+fn test_match_bindings_do_not_leak_out_of_case() {
+    let result = parse_and_execute(
+        r#"
+        enum Wrap { Of(int) }
+        let n = 1;
+        match Wrap::Of(7) {
+            Wrap::Of(n) => n
+        }
+        n
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Int(1));
+}
+
+#[test]
+fn test_enum_variant_pattern_with_literal_payload() {
+    let result = parse_and_execute(
+        r#"
+        enum Status { Code(int) }
+        let s = Status::Code(404);
+        match s {
+            Status::Code(200) => "ok",
+            Status::Code(n) => "other"
+        }
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::String("other".to_string()));
+}
+
+#[test]
+fn test_enum_values_compare_and_display() {
+    let result = parse_and_execute(
+        r#"
+        enum Color { Red, Green }
+        Color::Red == Color::Red && Color::Red != Color::Green
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Bool(true));
+
+    let value = Value::Enum(
+        "Shape".to_string(),
+        "Rect".to_string(),
+        vec![Value::Int(2), Value::Int(3)],
+    );
+    assert_eq!(value.to_string(), "Shape::Rect(2, 3)");
+}
+
+#[test]
+fn test_enum_constructor_arity_is_checked() {
+    let err = parse_and_execute(
+        r#"
+        enum Shape { Circle(int) }
+        Shape::Circle(1, 2)
+    "#,
+    )
+    .unwrap_err();
+    assert!(err.contains("ArgumentCountMismatch"), "{}", err);
+}
+
+#[test]
+fn test_match_pattern_parses_enum_variant() {
+    let program = parse_source(
+        r#"
+match v {
+    Opt::Some(x) => x,
+    Opt::None => 0
+}
+"#,
+    )
+    .unwrap();
+    let Statement::Match(m) = &program.statements[0] else {
+        panic!("expected match");
+    };
+    assert!(matches!(
+        &m.cases[0].pattern,
+        MatchPattern::EnumVariant { enum_name, variant, fields }
+            if enum_name == "Opt" && variant == "Some" && fields.len() == 1
+    ));
+}
+
+#[test]
+fn test_checker_validates_struct_literal_fields() {
+    let program = parse_source(
+        r#"
+struct Point { x: int, y: int }
+let a = Point { x: 1, y: 2 };
+let b = Point { x: "one", y: 2 };
+let c = Point { x: 1, y: 2, z: 3 };
+"#,
+    )
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(errors[0].context.contains("field 'x' of struct 'Point'"));
+    assert!(matches!(errors[1].error, TypeError::UndefinedField { .. }));
+}
+
+#[test]
+fn test_checker_resolves_struct_annotations() {
+    let program = parse_source(
+        r#"
+struct Point { x: int, y: int }
+fn norm(p: Point) -> int { return p.x + p.y; }
+norm(Point { x: 1, y: 2 });
+norm(5);
+"#,
+    )
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("argument 'p' of 'norm'"));
+}
@@ -0,0 +1,213 @@
+// spawn / await task runtime tests
+// Spawned expressions run on the shared task pool; `await` joins them, `all` / `any` /
+// `timeout` combine them, and cancellation reaches running tasks and their children.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::tasks;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::Runtime;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[test]
+fn test_spawn_returns_task_and_await_joins_it() {
+    let source = r#"
+fn double(n) { return n * 2; }
+let base = 20;
+let h = spawn double(base + 1);
+let again = spawn double(1);
+(await h) + sync::join(again) + (await 5)
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), Value::Int(49));
+
+    let program = parse_source("fn one() { return 1; }\nlet h = spawn one();").unwrap();
+    let mut runtime = Runtime::new();
+    runtime.execute_program(program, None).unwrap();
+    let Value::Task(id) = runtime.get_variable("h").unwrap() else {
+        panic!("spawn did not return a task");
+    };
+    assert_eq!(tasks::join(id, None, None).unwrap(), Value::Int(1));
+    assert_eq!(tasks::status(id), "done");
+}
+
+#[test]
+fn test_await_all_runs_hundreds_of_tasks_on_the_pool() {
+    let source = r#"
+fn square(n) { return n * n; }
+let handles = [];
+let i = 0;
+while (i < 300) {
+    handles.push(spawn square(i));
+    i = i + 1;
+}
+let values = await all(handles);
+values.length() + ":" + values[299] + ":" + task::pool_size()
+"#;
+    let expected = format!("300:89401:{}", tasks::pool_size());
+    assert_eq!(parse_and_execute(source).unwrap(), string(&expected));
+}
+
+#[test]
+fn test_pool_is_bounded() {
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let ids: Vec<u64> = (0..200)
+        .map(|i| {
+            let threads = Arc::clone(&threads);
+            tasks::spawn(
+                Box::new(move |_| {
+                    let name = std::thread::current().name().unwrap_or("").to_string();
+                    threads.lock().unwrap().insert(name);
+                    Ok(Value::Int(i))
+                }),
+                None,
+            )
+        })
+        .collect();
+    let values = tasks::join_all(&ids, None, None).unwrap();
+    assert_eq!(values.len(), 200);
+    assert_eq!(values[199], Value::Int(199));
+    let workers = threads
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|name| name.starts_with("dal-task-"))
+        .count();
+    assert!(workers <= tasks::pool_size(), "{} workers", workers);
+}
+
+#[test]
+fn test_nested_spawns_do_not_starve_the_pool() {
+    let source = r#"
+fn leaf(n) { return n + 1; }
+fn branch(n) {
+    let a = spawn leaf(n);
+    let b = spawn leaf(n * 10);
+    return (await a) + (await b);
+}
+let handles = [];
+let i = 0;
+while (i < 40) {
+    handles.push(spawn branch(i));
+    i = i + 1;
+}
+let values = await all(handles);
+values[0] + ":" + values[39]
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), string("2:431"));
+}
+
+#[test]
+fn test_await_any_returns_first_and_cancels_the_rest() {
+    let source = r#"
+fn spin() { while (true) { } }
+fn quick() { return "quick"; }
+let slow = spawn spin();
+let first = await any([slow, spawn quick()]);
+let picked = task::select([spawn spin(), spawn quick()]);
+first + ":" + task::status(slow) + ":" + picked["index"] + ":" + picked["value"]
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("quick:cancelled:1:quick")
+    );
+}
+
+#[test]
+fn test_await_timeout_raises_timeout_error_and_cancels() {
+    let source = r#"
+fn spin() { while (true) { } }
+let h = spawn spin();
+let caught = "";
+try {
+    await timeout(h, 30);
+} catch (e: TimeoutError) {
+    caught = e;
+}
+let all_caught = "";
+try {
+    await all([spawn spin()], 20);
+} catch (e: TimeoutError) {
+    all_caught = "all";
+}
+caught + ":" + task::status(h) + ":" + all_caught + ":" + (await timeout(7, 10))
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("Await timed out after 30 ms:cancelled:all:7")
+    );
+}
+
+#[test]
+fn test_cancel_stops_running_task_and_its_children() {
+    let source = r#"
+fn spin() { while (true) { } }
+fn parent() {
+    let child = spawn spin();
+    task::sleep(5000);
+    return child;
+}
+let h = spawn parent();
+task::sleep(30);
+let stopped = task::cancel(h);
+let caught = "";
+try {
+    await h;
+} catch (e: CancelledError) {
+    caught = "cancelled";
+}
+stopped + ":" + caught + ":" + task::cancel(h)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("true:cancelled:false")
+    );
+}
+
+#[test]
+fn test_children_are_cancelled_when_their_scope_exits() {
+    let source = r#"
+fn spin() { while (true) { } }
+fn leaves_child() {
+    return spawn spin();
+}
+let parent = spawn leaves_child();
+let orphan = await parent;
+let orphan_status = "";
+try {
+    await orphan;
+} catch (e: CancelledError) {
+    orphan_status = "cancelled";
+}
+let inner = task::scope(() => spawn spin());
+orphan_status + ":" + task::status(inner)
+"#;
+    assert_eq!(
+        parse_and_execute(source).unwrap(),
+        string("cancelled:cancelled")
+    );
+}
+
+#[test]
+fn test_task_errors_reach_the_awaiter() {
+    let source = r#"
+fn fail() { throw "boom"; }
+fn ok() { return 1; }
+let caught = "";
+try {
+    await all([spawn ok(), spawn fail()]);
+} catch (e) {
+    caught = e;
+}
+caught
+"#;
+    assert_eq!(parse_and_execute(source).unwrap(), string("boom"));
+    let err = parse_and_execute("task::join(\"spawn_1\")").unwrap_err();
+    assert!(err.contains(r#"expected: "task""#), "{}", err);
+}
@@ -0,0 +1,273 @@
+// Trait and impl tests
+// Tests for `trait` / `interface` declarations, `impl Trait for Service` blocks, runtime
+// dispatch of trait methods and the checker's conformance checks.
+
+mod common;
+
+use common::parse_and_execute;
+use dist_agent_lang::parse_source;
+use dist_agent_lang::parser::ast::Statement;
+use dist_agent_lang::runtime::check_program;
+use dist_agent_lang::runtime::types::TypeError;
+use dist_agent_lang::runtime::values::Value;
+
+const PAYMENT_PROVIDER: &str = r#"
+trait PaymentProvider {
+    fn charge(amount: int) -> int;
+    fn name() -> string;
+    fn describe() -> string {
+        return "provider " + self.name();
+    }
+}
+"#;
+
+#[test]
+fn test_parse_trait_and_impl() {
+    let program = parse_source(&format!(
+        "{}\nservice Stripe {{}}\nimpl PaymentProvider for Stripe {{\n    fn charge(amount: int) -> int {{ return amount; }}\n    fn name() -> string {{ return \"stripe\"; }}\n}}\n",
+        PAYMENT_PROVIDER
+    ))
+    .unwrap();
+    let Statement::Trait(provider) = &program.statements[0] else {
+        panic!("expected trait, got {:?}", program.statements[0]);
+    };
+    assert_eq!(provider.name, "PaymentProvider");
+    let defaults: Vec<bool> = provider
+        .methods
+        .iter()
+        .map(|m| m.default_body.is_some())
+        .collect();
+    assert_eq!(defaults, vec![false, false, true]);
+    assert_eq!(provider.methods[0].return_type.as_deref(), Some("int"));
+
+    let Statement::Impl(imp) = &program.statements[2] else {
+        panic!("expected impl, got {:?}", program.statements[2]);
+    };
+    assert_eq!(imp.trait_name.as_deref(), Some("PaymentProvider"));
+    assert_eq!(imp.service_name, "Stripe");
+    assert_eq!(imp.methods.len(), 2);
+}
+
+#[test]
+fn test_interface_is_an_alias_for_trait() {
+    let program = parse_source("interface Named { fn name() -> string; }").unwrap();
+    assert!(matches!(&program.statements[0], Statement::Trait(t) if t.name == "Named"));
+}
+
+#[test]
+fn test_duplicate_trait_method_is_a_parse_error() {
+    assert!(parse_source("trait T { fn a(); fn a(); }").is_err());
+}
+
+#[test]
+fn test_two_services_dispatch_through_shared_trait() {
+    let result = parse_and_execute(&format!(
+        r#"{}
+        service Stripe {{
+            fee: int = 3;
+        }}
+        service Paypal {{}}
+        impl PaymentProvider for Stripe {{
+            fn charge(amount: int) -> int {{ return amount + self.fee; }}
+            fn name() -> string {{ return "stripe"; }}
+        }}
+        impl PaymentProvider for Paypal {{
+            fn charge(amount: int) -> int {{ return amount * 2; }}
+            fn name() -> string {{ return "paypal"; }}
+        }}
+        fn pay(provider: PaymentProvider, amount: int) -> int {{
+            return provider.charge(amount);
+        }}
+        pay(Stripe::new(), 10) + pay(Paypal::new(), 10)
+    "#,
+        PAYMENT_PROVIDER
+    ))
+    .unwrap();
+    assert_eq!(result, Value::Int(33));
+}
+
+#[test]
+fn test_trait_default_method_uses_impl_methods() {
+    let result = parse_and_execute(&format!(
+        r#"{}
+        service Stripe {{}}
+        impl PaymentProvider for Stripe {{
+            fn charge(amount: int) -> int {{ return amount; }}
+            fn name() -> string {{ return "stripe"; }}
+        }}
+        Stripe::new().describe()
+    "#,
+        PAYMENT_PROVIDER
+    ))
+    .unwrap();
+    assert_eq!(result, Value::String("provider stripe".to_string()));
+}
+
+#[test]
+fn test_service_methods_satisfy_trait_requirements() {
+    let result = parse_and_execute(&format!(
+        r#"{}
+        service Ledger {{
+            fn charge(amount: int) -> int {{ return amount - 1; }}
+            fn name() -> string {{ return "ledger"; }}
+        }}
+        impl PaymentProvider for Ledger {{}}
+        let l = Ledger::new();
+        l.charge(5)
+    "#,
+        PAYMENT_PROVIDER
+    ))
+    .unwrap();
+    assert_eq!(result, Value::Int(4));
+}
+
+#[test]
+fn test_missing_trait_method_is_a_runtime_error() {
+    let err = parse_and_execute(&format!(
+        r#"{}
+        service Broken {{}}
+        impl PaymentProvider for Broken {{
+            fn name() -> string {{ return "broken"; }}
+        }}
+        1
+    "#,
+        PAYMENT_PROVIDER
+    ))
+    .unwrap_err();
+    assert!(
+        err.contains("does not implement 'charge' required by trait 'PaymentProvider'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_impl_before_service_declaration() {
+    let result = parse_and_execute(
+        r#"
+        trait Named { fn name() -> string; }
+        impl Named for Late {
+            fn name() -> string { return "late"; }
+        }
+        service Late {}
+        Late::new().name()
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::String("late".to_string()));
+}
+
+#[test]
+fn test_impl_of_undeclared_trait_or_service_is_a_runtime_error() {
+    let err = parse_and_execute(&format!(
+        r#"{}
+        service Stripe {{}}
+        impl PaymentProvidr for Stripe {{
+            fn charge(amount: int) -> int {{ return amount; }}
+        }}
+        1
+    "#,
+        PAYMENT_PROVIDER
+    ))
+    .unwrap_err();
+    assert!(
+        err.contains("impl PaymentProvidr for Stripe: trait 'PaymentProvidr' is not declared"),
+        "{}",
+        err
+    );
+    assert!(err.contains("line: 11"), "{}", err);
+
+    let err = parse_and_execute("impl Ghost {\n    fn boo() { return 1; }\n}\n2").unwrap_err();
+    assert!(
+        err.contains("impl Ghost: service 'Ghost' is not declared"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_inherent_impl_adds_methods() {
+    let result = parse_and_execute(
+        r#"
+        service Counter {
+            count: int = 1;
+        }
+        impl Counter {
+            fn bump() -> int {
+                self.count = self.count + 1;
+                return self.count;
+            }
+        }
+        let c = Counter::new();
+        c.bump();
+        c.bump()
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Int(3));
+}
+
+#[test]
+fn test_checker_reports_missing_trait_method() {
+    let program = parse_source(&format!(
+        "{}\nservice Broken {{}}\nimpl PaymentProvider for Broken {{\n    fn name() -> string {{ return \"b\"; }}\n}}\n",
+        PAYMENT_PROVIDER
+    ))
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        &errors[0].error,
+        TypeError::MissingTraitMethod { trait_name, method, type_name }
+            if trait_name == "PaymentProvider" && method == "charge" && type_name == "Broken"
+    ));
+}
+
+#[test]
+fn test_checker_validates_impl_signatures_and_targets() {
+    let program = parse_source(
+        r#"
+trait Named { fn name(lang: string) -> string; }
+service A {}
+impl Named for A {
+    fn name(lang: int) -> string { return "a"; }
+}
+impl Named for Missing {
+    fn name(lang: string) -> string { return "m"; }
+}
+"#,
+    )
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(
+        errors[0].context.contains("parameter 'lang'"),
+        "{:?}",
+        errors
+    );
+    assert!(matches!(
+        &errors[1].error,
+        TypeError::UndefinedType { name } if name == "Missing"
+    ));
+}
+
+#[test]
+fn test_checker_accepts_implementing_service_for_trait_parameter() {
+    let program = parse_source(
+        r#"
+trait Named { fn name() -> string; }
+service A {}
+service B {}
+impl Named for A {
+    fn name() -> string { return "a"; }
+}
+fn greet(n: Named) -> string { return n.name(); }
+greet(A::new());
+greet(B::new());
+"#,
+    )
+    .unwrap();
+    let errors = check_program(&program);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("argument 'n' of 'greet'"));
+}
@@ -0,0 +1,227 @@
+// Static type checker tests: annotations on parameters, return types and service fields
+// are checked before execution (`dal check`), with statement locations.
+
+use dist_agent_lang::parse_source;
+use dist_agent_lang::runtime::types::{Type, TypeError};
+use dist_agent_lang::runtime::{check_program, TypeDiagnostic};
+
+fn check(source: &str) -> Vec<TypeDiagnostic> {
+    let program = parse_source(source).expect("source should parse");
+    check_program(&program)
+}
+
+#[test]
+fn test_well_typed_program_has_no_errors() {
+    let errors = check(
+        r#"
+fn add(a: int, b: int) -> int {
+    return a + b;
+}
+let total = add(1, 2);
+let scaled = add(total, 3);
+"#,
+    );
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_unannotated_code_is_never_an_error() {
+    let errors = check(
+        r#"
+fn echo(x) {
+    return x;
+}
+let a = echo("hi");
+let b = echo(42);
+"#,
+    );
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_argument_type_mismatch_reports_span() {
+    let errors = check(
+        r#"fn double(n: int) -> int {
+    return n * 2;
+}
+let x = double("two");
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].span.line, 4);
+    assert!(errors[0].context.contains("argument 'n' of 'double'"));
+    assert!(matches!(
+        &errors[0].error,
+        TypeError::TypeMismatch {
+            expected: Type::Int,
+            got: Type::String
+        }
+    ));
+}
+
+#[test]
+fn test_argument_count_mismatch() {
+    let errors = check(
+        r#"
+fn pair(a: int, b: int) -> int { return a; }
+pair(1);
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(matches!(
+        errors[0].error,
+        TypeError::ParameterCountMismatch {
+            expected: 2,
+            got: 1
+        }
+    ));
+}
+
+#[test]
+fn test_call_before_declaration_is_checked() {
+    let errors = check(
+        r#"
+let ok = is_ready(1);
+fn is_ready(flag: bool) -> bool { return flag; }
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("argument 'flag'"));
+}
+
+#[test]
+fn test_wrong_return_type_reports_nested_line() {
+    let errors = check(
+        r#"fn name() -> string {
+    if (true) {
+        return 42;
+    }
+    return "ok";
+}
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].span.line, 3);
+    assert!(errors[0].context.contains("return value of 'name'"));
+}
+
+#[test]
+fn test_int_and_float_are_compatible() {
+    let errors = check(
+        r#"
+fn half(x: float) -> float { return x / 2; }
+let h = half(3);
+"#,
+    );
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_inferred_variable_types_flow_into_calls() {
+    let errors = check(
+        r#"
+fn greet(name: string) -> string { return "hi " + name; }
+let count = 1 + 2;
+greet(count);
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_reassigned_variable_widens_to_any() {
+    let errors = check(
+        r#"
+fn greet(name: string) -> string { return name; }
+let v = 1;
+v = "now a string";
+greet(v);
+"#,
+    );
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_service_field_assignment_mismatch() {
+    let errors = check(
+        r#"
+service Counter {
+    count: int = 0;
+
+    fn reset() {
+        self.count = "zero";
+    }
+}
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0]
+        .context
+        .contains("assignment to field 'count' of 'Counter'"));
+    assert_eq!(errors[0].span.line, 6);
+}
+
+#[test]
+fn test_service_field_initial_value_mismatch() {
+    let errors = check(
+        r#"
+service Wallet {
+    owner: string = 7;
+}
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("initial value of field 'owner'"));
+}
+
+#[test]
+fn test_service_method_arguments_checked_through_instance() {
+    let errors = check(
+        r#"
+service Bank {
+    balance: int = 0;
+
+    fn deposit(amount: int) -> int {
+        self.balance = self.balance + amount;
+        return self.balance;
+    }
+}
+let bank = Bank::new();
+bank.deposit(10);
+bank.deposit("ten");
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("'Bank.deposit'"));
+    assert_eq!(errors[0].span.line, 12);
+}
+
+#[test]
+fn test_generic_annotations() {
+    let errors = check(
+        r#"
+fn first(items: list<int>) -> int { return items[0]; }
+fn lookup(m: map<string, int>) -> int { return m["a"]; }
+first([1, 2, 3]);
+first(["a", "b"]);
+lookup({"a": 1});
+"#,
+    );
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].context.contains("argument 'items' of 'first'"));
+}
+
+#[test]
+fn test_type_from_annotation() {
+    assert_eq!(Type::from_annotation("int"), Type::Int);
+    assert_eq!(Type::from_annotation("String"), Type::String);
+    assert_eq!(
+        Type::from_annotation("map<string, list<int>>"),
+        Type::map(Type::String, Type::list(Type::Int))
+    );
+    assert_eq!(
+        Type::from_annotation("Result<Unit, Error>"),
+        Type::result(Type::Void, Type::Any)
+    );
+    assert_eq!(Type::from_annotation("HttpRequest"), Type::Any);
+}
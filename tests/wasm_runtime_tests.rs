@@ -0,0 +1,247 @@
+// WASM runtime tests
+// Services are built with the wasm backend, loaded into the embedded wasm runtime and called
+// through the host ABI; results are compared with the same calls under the interpreter.
+
+mod common;
+
+use common::{execute_in, parse_and_execute};
+use dist_agent_lang::compile::{run_compile, CompileError};
+use dist_agent_lang::lexer::tokens::CompilationTarget;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::{FileBackedStorage, InMemoryStorage, Runtime, WasmServices};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const SOURCE: &str = r#"
+fn label_for(n) {
+    if (n > 100) { return "large"; }
+    return "small";
+}
+
+@web
+service Calc @compile_target("wasm") {
+    history: list<int> = [];
+
+    fn classify(n: int) -> string {
+        if (n < 0) { return "negative"; }
+        return label_for(n) + ":" + n;
+    }
+
+    fn sum_to(n: int) -> int {
+        let total = 0;
+        for i in 0..n { total = total + i; }
+        self.history.push(total);
+        return total;
+    }
+
+    fn divide(a, b) { return a / b; }
+    fn mix(a, b) { return a + b; }
+    fn echo(v) { return v; }
+
+    fn stats() {
+        return { "count": self.history.length(), "last": self.history[self.history.length() - 1], "tags": ["a", "b"] };
+    }
+}
+
+@web
+@persistent
+service Ledger @compile_target("wasm") {
+    total: int = 0;
+
+    fn add(n: int) -> int {
+        self.total = self.total + n;
+        log::info("ledger", { "n": n });
+        return self.total;
+    }
+
+    fn fingerprint(s: string) -> string {
+        print("hashing " + s);
+        return crypto::hash(s, "sha256");
+    }
+}
+"#;
+
+/// The module built from `SOURCE`, or `None` when the wasm32 target is not installed.
+fn module() -> Option<&'static PathBuf> {
+    static MODULE: OnceLock<Option<PathBuf>> = OnceLock::new();
+    MODULE
+        .get_or_init(|| {
+            let out = std::env::temp_dir().join(format!("dal_wasm_runtime_{}", std::process::id()));
+            match run_compile(
+                out.join("main.dal"),
+                CompilationTarget::WebAssembly,
+                out.clone(),
+                SOURCE,
+            ) {
+                Ok(artifacts) => artifacts
+                    .artifact_paths
+                    .into_iter()
+                    .find(|p| p.extension().is_some_and(|e| e == "wasm")),
+                Err(CompileError::CompilerNotFound { .. }) => None,
+                Err(e) => panic!("wasm build failed: {}", e),
+            }
+        })
+        .as_ref()
+}
+
+fn load(host: Runtime) -> WasmServices {
+    WasmServices::from_file(module().unwrap(), host).unwrap()
+}
+
+fn memory_runtime() -> Runtime {
+    let mut runtime = Runtime::new();
+    runtime.set_state_storage(Box::new(InMemoryStorage::new()));
+    runtime
+}
+
+/// Display form shared by both sides, with map keys sorted.
+fn canonical(value: &Value) -> String {
+    match value {
+        Value::Map(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let entries: Vec<String> = keys
+                .iter()
+                .map(|k| format!("\"{}\": {}", k, canonical(&map[*k])))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::List(items) | Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical).collect();
+            format!("[{}]", items.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+#[test]
+fn test_wasm_methods_match_interpreter() {
+    if module().is_none() {
+        eprintln!("skipping: wasm32-unknown-unknown target not installed");
+        return;
+    }
+    let calls: &[(&str, &str)] = &[
+        ("classify", "-3"),
+        ("classify", "7"),
+        ("classify", "512"),
+        ("sum_to", "10"),
+        ("sum_to", "4"),
+        ("divide", "7, 2"),
+        ("divide", "7.0, 2"),
+        ("divide", "1, 0"),
+        ("mix", "\"a\", 1.5"),
+        ("mix", "[1], [2, 3]"),
+        ("echo", "{ \"k\": [1, 2.5, null, true], \"s\": \"q\" }"),
+        ("stats", ""),
+        ("classify", "\"x\", 2"),
+    ];
+    let mut interpreter = memory_runtime();
+    execute_in(&mut interpreter, &format!("{}\nnull", SOURCE)).unwrap();
+    let mut wasm = load(memory_runtime());
+    for (method, args) in calls {
+        let call = format!("Calc::{}({})", method, args);
+        let expected = execute_in(&mut interpreter, &call)
+            .map(|v| canonical(&v))
+            .unwrap_or_else(|_| "error".to_string());
+        let args = parse_and_execute(&format!("[{}]", args)).unwrap();
+        let args = match args {
+            Value::List(items) | Value::Array(items) => items,
+            other => panic!("expected list, got {:?}", other),
+        };
+        let actual = wasm
+            .call("Calc", method, &args)
+            .map(|v| canonical(&v))
+            .unwrap_or_else(|_| "error".to_string());
+        assert_eq!(actual, expected, "{} differs", call);
+    }
+}
+
+#[test]
+fn test_host_imports_serve_log_crypto_and_print() {
+    if module().is_none() {
+        return;
+    }
+    let mut wasm = load(memory_runtime());
+    assert_eq!(
+        wasm.service_names(),
+        vec!["Calc".to_string(), "Ledger".to_string()]
+    );
+    assert!(wasm.exports("Ledger", "fingerprint"));
+    assert!(!wasm.exports("Ledger", "new"));
+    let expected = parse_and_execute("crypto::hash(\"abc\", \"sha256\")").unwrap();
+    assert_eq!(
+        wasm.call("Ledger", "fingerprint", &[Value::String("abc".to_string())])
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        wasm.call("Ledger", "add", &[Value::Int(4)]).unwrap(),
+        Value::Int(4)
+    );
+    let err = wasm.call("Ledger", "add", &[]).unwrap_err().to_string();
+    assert!(err.contains("Argument count mismatch"), "{}", err);
+    let err = wasm
+        .call("Calc", "echo", &[Value::Set(Default::default())])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Cannot pass a set value"), "{}", err);
+}
+
+#[test]
+fn test_persistent_fields_use_host_storage() {
+    if module().is_none() {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    let host = || {
+        let mut runtime = Runtime::new();
+        runtime.set_state_storage(Box::new(FileBackedStorage::new(&path).unwrap()));
+        runtime
+    };
+    let mut first = load(host());
+    first.call("Ledger", "add", &[Value::Int(2)]).unwrap();
+    first.call("Ledger", "add", &[Value::Int(3)]).unwrap();
+    drop(first);
+
+    let mut second = load(host());
+    assert_eq!(
+        second.call("Ledger", "add", &[Value::Int(1)]).unwrap(),
+        Value::Int(6)
+    );
+    drop(second);
+
+    // The interpreter reads the same `Ledger.total` key.
+    let mut interpreter = host();
+    assert_eq!(
+        execute_in(&mut interpreter, &format!("{}\nLedger::add(0)", SOURCE)).unwrap(),
+        Value::Int(6)
+    );
+}
+
+#[test]
+fn test_attached_module_serves_service_calls() {
+    if module().is_none() {
+        return;
+    }
+    let mut runtime = memory_runtime();
+    let wasm = load(Runtime::new());
+    runtime.attach_wasm_services(wasm);
+    let script = format!(
+        "{}\nLedger::add(5);\nlet total = Ledger::add(5);\nlet sum = Calc::sum_to(4);\ntotal + \":\" + sum",
+        SOURCE
+    );
+    assert_eq!(
+        execute_in(&mut runtime, &script).unwrap(),
+        Value::String("10:6".to_string())
+    );
+    // Storage calls from the module went to the script runtime's storage.
+    assert_eq!(
+        execute_in(
+            &mut runtime,
+            "@persistent\nservice Ledger { total: int = 0; fn peek() { return self.total; } }\nLedger::peek()"
+        )
+        .unwrap(),
+        Value::Int(10)
+    );
+}
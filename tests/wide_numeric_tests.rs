@@ -0,0 +1,181 @@
+// Wide numeric type tests
+// `u256`, `i256` and `decimal` values: literals, checked arithmetic, conversions, ABI words and
+// the chain APIs that return wei amounts.
+
+mod common;
+
+use common::parse_and_execute as eval;
+use dist_agent_lang::runtime::values::Value;
+use dist_agent_lang::runtime::{Decimal, I256, U256};
+use dist_agent_lang::stdlib::abi_codec;
+
+fn shown(source: &str) -> String {
+    match eval(source).unwrap() {
+        Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+const U256_MAX: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+#[test]
+fn test_literals_and_display() {
+    assert_eq!(eval("100u256").unwrap(), Value::U256(U256::from(100u64)));
+    assert_eq!(eval("-5i256").unwrap(), Value::I256(I256::from_i64(-5)));
+    assert_eq!(
+        eval("1.25d").unwrap(),
+        Value::Decimal(Decimal::from_str_exact("1.25").unwrap())
+    );
+    assert_eq!(shown(&format!("{}u256", U256_MAX)), U256_MAX);
+    assert_eq!(shown("2.50d"), "2.5");
+    assert_eq!(
+        shown("type(1u256) + \",\" + type(1i256) + \",\" + type(1d)"),
+        "u256,i256,decimal"
+    );
+}
+
+#[test]
+fn test_out_of_range_literal_is_a_lexer_error() {
+    let err = eval(&format!("{}0u256", U256_MAX)).unwrap_err();
+    assert!(err.starts_with("Lexer error"), "{}", err);
+    let err = eval("1.5u256").unwrap_err();
+    assert!(err.starts_with("Lexer error"), "{}", err);
+}
+
+#[test]
+fn test_i256_min_literal_needs_its_minus() {
+    let min = "57896044618658097711785492504343953926634992332820282019728792003956564819968i256";
+    assert_eq!(
+        shown(&format!("-{}", min)),
+        "-57896044618658097711785492504343953926634992332820282019728792003956564819968"
+    );
+    assert_eq!(shown(&format!("[-{}][0] < 0", min)), "true");
+    let err = eval(min).unwrap_err();
+    assert!(err.starts_with("Lexer error"), "{}", err);
+    // After an operand the `-` subtracts, and the magnitude alone is still out of range.
+    let err = eval(&format!("let x = 0i256; x -{}", min)).unwrap_err();
+    assert!(err.starts_with("Lexer error"), "{}", err);
+}
+
+#[test]
+fn test_arithmetic_is_checked() {
+    let err = eval(&format!("{}u256 + 1", U256_MAX)).unwrap_err();
+    assert!(err.contains("IntegerOverflow"), "{}", err);
+    let err = eval("0u256 - 1").unwrap_err();
+    assert!(err.contains("IntegerUnderflow"), "{}", err);
+    let err = eval("1u256 / 0").unwrap_err();
+    assert!(err.contains("DivisionByZero"), "{}", err);
+    let err = eval("1.5d + 1.5").unwrap_err();
+    assert!(err.contains("TypeMismatch"), "{}", err);
+
+    // Amounts past i64::MAX stay exact.
+    assert_eq!(
+        shown("9223372036854775807u256 * 1000"),
+        "9223372036854775807000"
+    );
+    assert_eq!(shown("-7i256 / 2"), "-3");
+    assert_eq!(shown("-7i256 % 2"), "-1");
+}
+
+#[test]
+fn test_mixed_promotion_and_comparison() {
+    assert_eq!(shown("type(1u256 + 1i256)"), "i256");
+    assert_eq!(shown("type(2u256 * 1.5d)"), "decimal");
+    assert_eq!(shown("2u256 * 1.5d"), "3");
+    assert_eq!(shown("1d / 3"), "0.333333333333333333");
+    assert_eq!(eval("5u256 == 5").unwrap(), Value::Bool(true));
+    assert_eq!(eval("5u256 == 5.0d").unwrap(), Value::Bool(true));
+    assert_eq!(eval("-1i256 < 0u256").unwrap(), Value::Bool(true));
+    assert_eq!(eval("0.1d + 0.2d == 0.3d").unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn test_conversions() {
+    assert_eq!(shown("u256(\"0xff\")"), "255");
+    assert_eq!(
+        shown("u256(\"1000000000000000000000000\")"),
+        "1000000000000000000000000"
+    );
+    assert_eq!(shown("i256(-3)"), "-3");
+    assert_eq!(shown("decimal(\"12.5\") + decimal(1)"), "13.5");
+    assert_eq!(eval("to_int(42u256)").unwrap(), Value::Int(42));
+    let err = eval("u256(-1)").unwrap_err();
+    assert!(err.contains("IntegerUnderflow"), "{}", err);
+    let err = eval("to_int(100000000000000000000u256)").unwrap_err();
+    assert!(err.contains("IntegerOverflow"), "{}", err);
+}
+
+#[test]
+fn test_service_field_grows_past_i64() {
+    let source = r#"
+        service Treasury {
+            total: u256 = 0;
+
+            fn deposit(amount) {
+                self.total = self.total + amount;
+                return self.total;
+            }
+        }
+        Treasury::deposit(9000000000000000000);
+        Treasury::deposit(9000000000000000000)
+    "#;
+    assert_eq!(
+        eval(source).unwrap(),
+        Value::U256(U256::from_dec_str("18000000000000000000").unwrap())
+    );
+}
+
+#[test]
+fn test_values_serialize_as_decimal_text() {
+    let value = Value::U256(U256::MAX);
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.contains(&format!("\"{}\"", U256_MAX)), "{}", json);
+    let back: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, value);
+
+    let price = Value::Decimal(Decimal::from_str_exact("-0.000000000000000001").unwrap());
+    let back: Value = serde_json::from_str(&serde_json::to_string(&price).unwrap()).unwrap();
+    assert_eq!(back, price);
+}
+
+#[test]
+fn test_abi_words_round_trip() {
+    let amount = eval("10000000000000000000000u256").unwrap();
+    let word = abi_codec::encode_integer_word("uint256", &amount).unwrap();
+    assert_eq!(
+        abi_codec::decode_integer_word("uint256", &word).unwrap(),
+        amount
+    );
+    let debt = eval("-10000000000000000000000i256").unwrap();
+    let word = abi_codec::encode_integer_word("int256", &debt).unwrap();
+    assert_eq!(
+        abi_codec::decode_integer_word("int256", &word).unwrap(),
+        debt
+    );
+    assert!(abi_codec::encode_integer_word("uint64", &amount).is_err());
+}
+
+#[test]
+#[serial_test::serial]
+fn test_chain_get_balance_returns_u256() {
+    const WALLET: &str = r#"
+        @trust("hybrid")
+        @chain("ethereum")
+        service Wallet {
+            fn balance() {
+                return chain::get_balance(1, "0x1234567890abcdef1234567890abcdef12345678");
+            }
+        }
+        Wallet::balance()
+    "#;
+    std::env::remove_var("DAL_CHAIN_STRICT");
+    std::env::remove_var("DAL_CHAIN_STRICT_FROM_TRUST_MODE");
+    let balance = eval(WALLET).unwrap();
+    assert!(matches!(balance, Value::U256(_)), "{:?}", balance);
+
+    std::env::set_var("DAL_CHAIN_STRICT", "1");
+    let strict = eval(WALLET);
+    std::env::remove_var("DAL_CHAIN_STRICT");
+    assert_eq!(strict.unwrap(), Value::Int(-1));
+}
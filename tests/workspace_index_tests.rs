@@ -0,0 +1,292 @@
+// Workspace index and quick-fix tests
+// Cross-file definitions, references and renames through `import ... as alias`, scoping of
+// locals and service members, workspace symbols, and the LSP quick fixes in `ide::code_actions`.
+
+use dist_agent_lang::ide::code_actions::{quick_fixes, warning_diagnostics};
+use dist_agent_lang::ide::workspace::{RenameError, SymbolKind, SymbolLocation, WorkspaceIndex};
+use std::path::{Path, PathBuf};
+
+const MATH: &str = r#"// Arithmetic helpers.
+fn add(a, b) {
+    return a + b;
+}
+
+fn scale(x, factor) {
+    return add(x, 0) * factor;
+}
+"#;
+
+const MAIN: &str = r#"import "./math.dal" as m;
+
+fn total(items) {
+    let sum = 0;
+    for item in items {
+        sum = m::add(sum, item);
+    }
+    return "total: ${m::add(sum, 0)}";
+}
+
+fn other() {
+    let sum = 1;
+    return sum;
+}
+"#;
+
+fn workspace() -> (tempfile::TempDir, PathBuf, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    std::fs::write(root.join("math.dal"), MATH).unwrap();
+    std::fs::write(root.join("main.dal"), MAIN).unwrap();
+    (dir, root.join("math.dal"), root.join("main.dal"))
+}
+
+/// (file name, line, column) of each location.
+fn places(locations: &[SymbolLocation]) -> Vec<(String, u32, u32)> {
+    locations
+        .iter()
+        .map(|l| {
+            let name = l.path.file_name().unwrap().to_string_lossy().to_string();
+            (name, l.line, l.column)
+        })
+        .collect()
+}
+
+fn place(file: &str, line: u32, column: u32) -> (String, u32, u32) {
+    (file.to_string(), line, column)
+}
+
+fn single_file(source: &str) -> (WorkspaceIndex, &'static Path) {
+    let mut index = WorkspaceIndex::new();
+    let path = Path::new("untitled:scratch.dal");
+    index.update_file(path, source);
+    (index, path)
+}
+
+#[test]
+fn test_definition_and_references_follow_imports() {
+    let (_dir, math, main) = workspace();
+    let mut index = WorkspaceIndex::new();
+    index.load_file(&main);
+    index.load_imports(&main);
+    assert!(index.contains(&math));
+
+    // `m::add` in main.dal, inside a template too, is `add` in math.dal.
+    let definition = index.definition(&main, 6, 18).unwrap();
+    assert_eq!(places(&[definition]), vec![place("math.dal", 2, 4)]);
+    let references = index.references(&math, 2, 4, true);
+    assert_eq!(
+        places(&references),
+        vec![
+            place("main.dal", 6, 18),
+            place("main.dal", 8, 25),
+            place("math.dal", 2, 4),
+            place("math.dal", 7, 12),
+        ]
+    );
+    assert_eq!(index.references(&math, 2, 4, false).len(), 3);
+    // The alias itself.
+    assert_eq!(
+        places(&index.references(&main, 6, 15, true)),
+        vec![
+            place("main.dal", 1, 24),
+            place("main.dal", 6, 15),
+            place("main.dal", 8, 22)
+        ]
+    );
+}
+
+#[test]
+fn test_locals_are_scoped_to_their_function() {
+    let (_dir, _math, main) = workspace();
+    let mut index = WorkspaceIndex::new();
+    index.load_file(&main);
+    // `sum` in `total` is not `sum` in `other`.
+    let references = index.references(&main, 4, 9, true);
+    assert_eq!(
+        places(&references),
+        vec![
+            place("main.dal", 4, 9),
+            place("main.dal", 6, 9),
+            place("main.dal", 6, 22),
+            place("main.dal", 8, 29),
+        ]
+    );
+    assert_eq!(index.references(&main, 13, 12, true).len(), 2);
+    // Parameters and loop variables.
+    assert_eq!(
+        places(&index.references(&main, 5, 17, true)),
+        vec![place("main.dal", 3, 10), place("main.dal", 5, 17)]
+    );
+    assert_eq!(
+        places(
+            &index
+                .definition(&main, 6, 27)
+                .into_iter()
+                .collect::<Vec<_>>()
+        ),
+        vec![place("main.dal", 5, 9)]
+    );
+}
+
+#[test]
+fn test_rename_across_files() {
+    let (_dir, math, main) = workspace();
+    let mut index = WorkspaceIndex::new().with_root(math.parent().unwrap().to_path_buf());
+    index.index_directory(math.parent().unwrap());
+    assert_eq!(index.file_count(), 2);
+    let edits = index.rename(&main, 6, 18, "plus").unwrap();
+    assert_eq!(edits.len(), 4);
+    assert!(edits.iter().all(|e| e.length == 3));
+
+    assert_eq!(
+        index.rename(&main, 6, 18, "fn"),
+        Err(RenameError::InvalidName("fn".to_string()))
+    );
+    assert_eq!(
+        index.rename(&main, 6, 18, "two words"),
+        Err(RenameError::InvalidName("two words".to_string()))
+    );
+    assert_eq!(
+        index.rename(&math, 2, 4, "scale"),
+        Err(RenameError::Conflict("scale".to_string()))
+    );
+    // A local may not take a name already bound in an overlapping scope.
+    assert_eq!(
+        index.rename(&main, 4, 9, "item"),
+        Err(RenameError::Conflict("item".to_string()))
+    );
+    assert!(index.rename(&main, 13, 12, "item").is_ok());
+    // Builtins and unknown names are not renameable.
+    assert_eq!(index.rename(&main, 9, 1, "x"), Err(RenameError::NoSymbol));
+}
+
+#[test]
+fn test_service_members() {
+    let source = r#"service Vault {
+    balance: int = 0;
+
+    fn deposit(amount) {
+        balance = balance + amount;
+        return self.balance;
+    }
+}
+
+service Ledger {
+    entries: list = [];
+    fn record(e) { self.entries.push(e); }
+}
+
+let v = Vault::new();
+v.deposit(5);
+let cfg = {balance: 1};
+"#;
+    let (index, path) = single_file(source);
+    // The field, bare and through `self`; the map key is not a reference.
+    assert_eq!(
+        index
+            .references(path, 2, 5, true)
+            .iter()
+            .map(|l| (l.line, l.column))
+            .collect::<Vec<_>>(),
+        vec![(2, 5), (5, 9), (5, 19), (6, 21)]
+    );
+    // `v.deposit` is the only `deposit` in the workspace.
+    let definition = index.definition(path, 16, 3).unwrap();
+    assert_eq!((definition.line, definition.column), (4, 8));
+
+    let symbols = index.symbols("");
+    let names: Vec<(&str, SymbolKind, Option<&str>)> = symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.container.as_deref()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("Vault", SymbolKind::Service, None),
+            ("balance", SymbolKind::Field, Some("Vault")),
+            ("deposit", SymbolKind::Method, Some("Vault")),
+            ("Ledger", SymbolKind::Service, None),
+            ("entries", SymbolKind::Field, Some("Ledger")),
+            ("record", SymbolKind::Method, Some("Ledger")),
+            ("v", SymbolKind::Variable, None),
+            ("cfg", SymbolKind::Variable, None),
+        ]
+    );
+    let found: Vec<String> = index.symbols("LED").into_iter().map(|s| s.name).collect();
+    assert_eq!(found, vec!["Ledger"]);
+}
+
+#[test]
+fn test_closures_match_arms_and_broken_files() {
+    let source = r#"let x = 10;
+let double = (x) => x * 2;
+let r = match Some(3) {
+    Some(x) => x + 1,
+    _ => x
+};
+fn broken( {
+    let y = x;
+"#;
+    let (index, path) = single_file(source);
+    let lines = |line, column| -> Vec<(u32, u32)> {
+        index
+            .references(path, line, column, true)
+            .iter()
+            .map(|l| (l.line, l.column))
+            .collect()
+    };
+    assert_eq!(lines(2, 15), vec![(2, 15), (2, 21)]);
+    assert_eq!(lines(4, 10), vec![(4, 10), (4, 16)]);
+    // The global `x`, also used after the syntax error.
+    assert_eq!(lines(1, 5), vec![(1, 5), (5, 10), (8, 13)]);
+}
+
+#[test]
+fn test_quick_fix_inserts_missing_tokens() {
+    let fixes = quick_fixes("let a = 1\nlet b = foo(1, 2;\n");
+    let applied: Vec<(String, u32, u32, String)> = fixes
+        .iter()
+        .map(|f| {
+            let e = &f.edits[0];
+            (f.title.clone(), e.line, e.column, e.new_text.clone())
+        })
+        .collect();
+    assert_eq!(
+        applied[0],
+        ("Insert missing `;`".to_string(), 1, 10, ";".to_string())
+    );
+    assert_eq!(fixes[0].diagnostic.severity, "error");
+
+    let fixes = quick_fixes("fn f() {\n    let a = [1, 2;\n    return a;\n");
+    let edits: Vec<(u32, u32, &str)> = fixes
+        .iter()
+        .map(|f| {
+            (
+                f.edits[0].line,
+                f.edits[0].column,
+                f.edits[0].new_text.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(edits, vec![(2, 18, "]"), (4, 1, "}\n")]);
+}
+
+#[test]
+fn test_unused_variable_warning_and_fix() {
+    let source = "fn f() {\n    let used = 1;\n    let spare = 2;\n    return used;\n}\n";
+    let warnings = warning_diagnostics(source);
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert_eq!(warnings[0].message, "unused variable 'spare'");
+    assert_eq!(
+        (warnings[0].line, warnings[0].column, warnings[0].end_column),
+        (3, 9, 14)
+    );
+    let fixes = quick_fixes(source);
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0].title, "Prefix `spare` with `_` to mark it unused");
+    assert_eq!((fixes[0].edits[0].line, fixes[0].edits[0].column), (3, 9));
+    // The fixed source has no warning left.
+    let fixed = source.replace("let spare", "let _spare");
+    assert!(warning_diagnostics(&fixed).is_empty());
+    assert!(quick_fixes(&fixed).is_empty());
+}